@@ -0,0 +1,11 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Server and client of the control plane generated from its proto
+    #[cfg(feature = "grpc")]
+    {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+        tonic_build::compile_protos("proto/cs_jobs.proto")?;
+    }
+
+    Ok(())
+}
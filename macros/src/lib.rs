@@ -0,0 +1,230 @@
+//! Procedural macros of `cs-jobs-v3`, re-exported by its prelude.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, FnArg, Ident, ItemFn, Meta, Pat, Token, Type};
+
+/// Turns an async function into a routine: the function is kept as is, and the macro generates
+///
+/// * the arguments struct (`<FnName>Args`, serializable), made of the other parameters of the
+///   function, which implements `Routine` by calling the function,
+/// * a typed `enqueue_<fn_name>` helper, pushing a job to a queue whose routine type can be
+///   built from the arguments struct (itself, or an enum variant with a `From` impl),
+/// * a `register_<fn_name>` helper, registering the arguments struct in a `RoutineRegistry`
+///   under the name of the function (jobs then created with `Job::registered`).
+///
+/// The attribute names the context type of the queues running the routine. The function
+/// must return `Result<Vec<u8>, Error>`, and its parameters must implement `Clone`. A first
+/// parameter named `context` receives the context of the queue (`Option<Shared<MyContext>>`)
+/// and isn't part of the arguments.
+///
+/// ```ignore
+/// #[cs_job(context = MyContext)]
+/// async fn resize_image(path: String, width: u32) -> Result<Vec<u8>, Error> {
+///     // ...
+/// }
+///
+/// let job_id = enqueue_resize_image(&queue, "cat.png".to_owned(), 640)?;
+/// ```
+#[proc_macro_attribute]
+pub fn cs_job(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_fn = parse_macro_input!(item as ItemFn);
+
+    match expand(attr.into(), item_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Expands the `cs_job` attribute.
+///
+/// # Arguments
+/// * `attr` - Arguments of the attribute.
+/// * `item_fn` - Function annotated.
+///
+/// # Returns
+/// The function followed by the generated items.
+///
+/// # Errors
+/// A compile error pointing at the invalid part of the function or the attribute.
+fn expand(
+    attr: proc_macro2::TokenStream,
+    item_fn: ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let context = parse_context(attr)?;
+
+    if item_fn.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            item_fn.sig.fn_token,
+            "cs_job functions must be async",
+        ));
+    }
+
+    let vis = &item_fn.vis;
+    let fn_name = &item_fn.sig.ident;
+    let args_name = format_ident!("{}Args", camel_case(&fn_name.to_string()));
+    let enqueue_name = format_ident!("enqueue_{}", fn_name);
+    let register_name = format_ident!("register_{}", fn_name);
+
+    let mut inputs = item_fn.sig.inputs.iter().peekable();
+
+    // Context of the queue passed as first parameter
+    let with_context = matches!(
+        inputs.peek(),
+        Some(FnArg::Typed(arg)) if matches!(arg.pat.as_ref(), Pat::Ident(pat) if pat.ident == "context")
+    );
+
+    if with_context {
+        inputs.next();
+    }
+
+    let mut fields: Vec<Ident> = vec![];
+    let mut types: Vec<&Type> = vec![];
+
+    for input in inputs {
+        let FnArg::Typed(arg) = input else {
+            return Err(syn::Error::new_spanned(
+                input,
+                "cs_job functions cannot take self",
+            ));
+        };
+
+        let Pat::Ident(pat) = arg.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                &arg.pat,
+                "cs_job parameters must be plain identifiers",
+            ));
+        };
+
+        fields.push(pat.ident.clone());
+        types.push(&arg.ty);
+    }
+
+    let context_arg = match with_context {
+        true => quote!(context,),
+        false => quote!(),
+    };
+
+    let doc_args = format!("Arguments of the routine `{fn_name}` (see `cs_job`).");
+    let doc_enqueue = format!("Pushes a job running `{fn_name}` to a queue.");
+    let doc_register = format!("Registers `{fn_name}` in a routine registry, under its name.");
+
+    Ok(quote! {
+        #item_fn
+
+        #[doc = #doc_args]
+        #[derive(Clone, ::cs_jobs_v3::prelude::Serialize, ::cs_jobs_v3::prelude::Deserialize)]
+        #[serde(crate = "::cs_jobs_v3::serde")]
+        #vis struct #args_name {
+            #(pub #fields: #types,)*
+        }
+
+        #[::cs_jobs_v3::prelude::async_trait]
+        impl ::cs_jobs_v3::job::Routine<#context> for #args_name {
+            async fn call(
+                &self,
+                _job: &::cs_jobs_v3::job::Job,
+                _messages_channel: ::cs_jobs_v3::types::SharedMessageChannel,
+                context: ::std::option::Option<::cs_jobs_v3::types::Shared<#context>>,
+            ) -> ::std::result::Result<::std::vec::Vec<u8>, ::cs_jobs_v3::error::Error> {
+                let _ = &context;
+
+                #fn_name(#context_arg #(self.#fields.clone()),*).await
+            }
+        }
+
+        #[doc = #doc_enqueue]
+        #vis fn #enqueue_name<__Routine>(
+            queue: &::cs_jobs_v3::job_queue::JobQueue<__Routine, #context>,
+            #(#fields: #types),*
+        ) -> ::std::result::Result<::cs_jobs_v3::prelude::Uuid, ::cs_jobs_v3::error::ApiError>
+        where
+            __Routine: ::cs_jobs_v3::job::Routine<#context>
+                + ::std::convert::From<#args_name>
+                + Sync
+                + 'static,
+        {
+            let routine = __Routine::from(#args_name { #(#fields),* });
+
+            queue.enqueue(::cs_jobs_v3::job::Job::new::<#context>(routine)?)
+        }
+
+        #[doc = #doc_register]
+        #vis fn #register_name(
+            registry: &mut ::cs_jobs_v3::registry::RoutineRegistry<#context>,
+        ) -> ::std::result::Result<
+            &mut ::cs_jobs_v3::registry::RoutineRegistry<#context>,
+            ::cs_jobs_v3::error::ApiError,
+        > {
+            registry.register::<#args_name>(::std::stringify!(#fn_name))
+        }
+    })
+}
+
+/// Parses the arguments of the attribute.
+///
+/// # Arguments
+/// * `attr` - Arguments of the attribute (`context = Type`).
+///
+/// # Returns
+/// The context type of the queues.
+///
+/// # Errors
+/// A compile error for an unknown or missing argument.
+fn parse_context(attr: proc_macro2::TokenStream) -> syn::Result<Type> {
+    let metas = Punctuated::<Meta, Token![,]>::parse_terminated.parse2(attr)?;
+    let mut context = None;
+
+    for meta in metas {
+        match meta {
+            Meta::NameValue(nv) if nv.path.is_ident("context") => {
+                let syn::Expr::Path(path) = nv.value else {
+                    return Err(syn::Error::new_spanned(nv.value, "expected a type"));
+                };
+
+                context = Some(Type::Path(syn::TypePath {
+                    qself: path.qself,
+                    path: path.path,
+                }));
+            }
+            meta => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "unknown cs_job argument `{}`",
+                        quote!(#meta).to_string().replace(' ', "")
+                    ),
+                ))
+            }
+        }
+    }
+
+    context.ok_or(syn::Error::new(
+        Span::call_site(),
+        "cs_job requires the context type of the queues: #[cs_job(context = MyContext)]",
+    ))
+}
+
+/// Converts a snake case name to camel case.
+///
+/// # Arguments
+/// * `name` - Name in snake case.
+///
+/// # Returns
+/// The name in camel case.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Policy adjusting the maximum number of jobs in flight to the depth of the queue, within
+/// bounds, instead of a fixed limit.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AutoscalePolicy {
+    /// Minimum number of jobs in flight.
+    pub min: usize,
+
+    /// Maximum number of jobs in flight.
+    pub max: usize,
+
+    /// Number of jobs waiting to be dispatched above which the queue scales up.
+    pub target_depth: usize,
+}
+
+impl AutoscalePolicy {
+    /// Creates a policy scaling between bounds.
+    ///
+    /// # Arguments
+    /// * `min` - Minimum number of jobs in flight (at least 1).
+    /// * `max` - Maximum number of jobs in flight (at least `min`).
+    /// * `target_depth` - Number of waiting jobs above which the queue scales up.
+    ///
+    /// # Returns
+    /// An instance of `AutoscalePolicy`.
+    pub fn new(min: usize, max: usize, target_depth: usize) -> Self {
+        let min = min.max(1);
+
+        Self {
+            min,
+            max: max.max(min),
+            target_depth,
+        }
+    }
+
+    /// Computes the maximum number of jobs in flight for a depth: it grows by the number of
+    /// jobs above the target depth, and shrinks by one once nothing is waiting.
+    ///
+    /// # Arguments
+    /// * `limit` - Current maximum number of jobs in flight.
+    /// * `depth` - Number of jobs waiting to be dispatched.
+    ///
+    /// # Returns
+    /// The new maximum, within the bounds of the policy.
+    pub fn scale(&self, limit: usize, depth: usize) -> usize {
+        let limit = if depth > self.target_depth {
+            limit.saturating_add(depth - self.target_depth)
+        } else if depth == 0 {
+            limit.saturating_sub(1)
+        } else {
+            limit
+        };
+
+        limit.clamp(self.min, self.max)
+    }
+}
@@ -1,9 +1,15 @@
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use tokio::sync::Mutex;
 
 use crate::prelude::*;
 
+/// Namespace used by the queues when none is configured.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
 /// Type used to share the backend instance across threads.
 pub type SharedBackend<Routine, Context> = Arc<Mutex<Box<dyn Backend<Routine, Context>>>>;
 
@@ -47,7 +53,7 @@ where
     async fn run(
         &mut self,
         id: &Uuid,
-        context: Option<Shared<Context>>,
+        context: Option<ContextMode<Context>>,
         messages_channel: SharedMessageChannel,
     ) -> Result<(), ApiError>;
 
@@ -63,6 +69,18 @@ where
     /// One of `Error` enum.
     fn status(&self, id: &Uuid) -> Result<Status, ApiError>;
 
+    /// Get the status of several jobs at once.
+    ///
+    /// # Arguments
+    /// * `ids` - Job identifiers to be fetched.
+    ///
+    /// # Returns
+    /// The status of the jobs found, by job identifier.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError>;
+
     /// Set the status of a job.
     ///
     /// # Arguments
@@ -95,6 +113,76 @@ where
     /// One of `Error` enum.
     fn set_result(&mut self, id: &Uuid, result: Vec<u8>) -> Result<(), ApiError>;
 
+    /// Set the key of the result of a job written to the result store, the backend keeping
+    /// only this reference.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be modified.
+    /// * `key` - Key of the result in the store.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn set_result_ref(&mut self, id: &Uuid, key: String) -> Result<(), ApiError>;
+
+    /// Record an execution attempt of a job.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be modified.
+    /// * `attempt` - Attempt to be recorded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn add_attempt(&mut self, id: &Uuid, attempt: Attempt) -> Result<(), ApiError>;
+
+    /// Set the checkpoint of a job, kept across attempts.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be modified.
+    /// * `checkpoint` - Checkpoint to be set.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn set_checkpoint(&mut self, id: &Uuid, checkpoint: Vec<u8>) -> Result<(), ApiError>;
+
+    /// Attach a metadata to a job (replaces the previous value of the key).
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be modified.
+    /// * `key` - Key of the metadata.
+    /// * `value` - Value to be attached.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn set_metadata(
+        &mut self,
+        id: &Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError>;
+
+    /// Append a chunk to the result streamed by a running job.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be modified.
+    /// * `chunk` - Chunk to be appended.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn append_chunk(&mut self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError>;
+
+    /// Get the chunks of the result streamed by a job.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be fetched.
+    /// * `from` - Index of the first chunk to be fetched.
+    ///
+    /// # Returns
+    /// The chunks following the index, the oldest first.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn chunks(&self, id: &Uuid, from: usize) -> Result<Vec<Vec<u8>>, ApiError>;
+
     /// Set the number of steps for a job.
     ///
     /// # Arguments
@@ -157,6 +245,16 @@ where
     /// One of `Error` enum.
     fn expire_policy(&self, id: &Uuid) -> Result<ExpirePolicy, ApiError>;
 
+    /// Set the time at which a job expires.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be modified.
+    /// * `expired` - Timestamp of the expiration.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn set_expiration(&mut self, id: &Uuid, expired: SystemTime) -> Result<(), ApiError>;
+
     /// Remove a job.
     ///
     /// # Arguments:
@@ -175,12 +273,347 @@ where
     /// One of `Error` enum.
     fn remove_expired(&mut self) -> Result<Vec<Uuid>, ApiError>;
 
+    /// Drop the results of the finished jobs that have expired (see `Job::set_result_ttl`),
+    /// keeping the jobs.
+    ///
+    /// # Returns
+    /// The IDs of the jobs whose result has been dropped, with the key of the result in the
+    /// result store if offloaded (to be removed from it).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn expire_results(&mut self) -> Result<Vec<(Uuid, Option<String>)>, ApiError>;
+
+    /// Remove the oldest finished jobs of every kind, keeping only the newest ones.
+    /// Jobs with the `ExpirePolicy::Never` policy are neither removed nor counted.
+    ///
+    /// # Arguments
+    /// * `count` - Number of finished jobs to keep for every kind.
+    ///
+    /// # Returns
+    /// The list of job IDs removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn retain_last(&mut self, count: usize) -> Result<Vec<Uuid>, ApiError>;
+
+    /// Remove, in one pass, all the jobs selected by a filter (up to its limit).
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs to be removed.
+    ///
+    /// # Returns
+    /// The list of job IDs removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn purge(&mut self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError>;
+
+    /// Take a lock on a key, unless held by another owner and not stale.
+    ///
+    /// # Arguments
+    /// * `key` - Key to be locked.
+    /// * `owner` - Identifier of the worker taking the lock.
+    /// * `ttl` - Duration after which the lock expires if not refreshed.
+    ///
+    /// # Returns
+    /// `true` if the lock has been taken, `false` if held by another owner.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn acquire_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError>;
+
+    /// Extend a lock held by an owner (heartbeat).
+    ///
+    /// # Arguments
+    /// * `key` - Key locked.
+    /// * `owner` - Identifier of the worker holding the lock.
+    /// * `ttl` - Duration, from now, after which the lock expires if not refreshed.
+    ///
+    /// # Returns
+    /// `true` if the lock has been extended, `false` if not held by this owner.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn refresh_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError>;
+
+    /// Release a lock held by an owner.
+    ///
+    /// # Arguments
+    /// * `key` - Key locked.
+    /// * `owner` - Identifier of the worker holding the lock.
+    ///
+    /// # Returns
+    /// `true` if the lock has been released, `false` if not held by this owner.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn release_lock(&mut self, key: &str, owner: &str) -> Result<bool, ApiError>;
+
+    /// Release all the stale locks (i.e. held by dead workers).
+    ///
+    /// # Returns
+    /// The list of locks released.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn release_stale_locks(&mut self) -> Result<Vec<Lock>, ApiError>;
+
+    /// Claim the oldest claimable job (see `Job::is_claimable`) of a kind executed by a worker,
+    /// whose requirements are satisfied by its capabilities, on its behalf, for the duration of
+    /// a lease. A job whose lease expires before being extended or released can be claimed
+    /// again by another worker (at-least-once processing). Backends shared by several processes
+    /// must do it atomically, so that a job is leased to a single worker.
+    ///
+    /// # Arguments
+    /// * `worker_id` - Identifier of the worker.
+    /// * `lease` - Duration of the lease.
+    /// * `kinds` - Kinds of job executed by the worker.
+    /// * `capabilities` - Capabilities advertised by the worker.
+    ///
+    /// # Returns
+    /// The job claimed, `None` if there's no job to claim.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn claim(
+        &mut self,
+        worker_id: &str,
+        lease: Duration,
+        kinds: &KindFilter,
+        capabilities: &Capabilities,
+    ) -> Result<Option<Job>, ApiError>;
+
+    /// Extend the lease of a job claimed by a worker.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier.
+    /// * `worker_id` - Identifier of the worker.
+    /// * `lease` - Duration of the lease, from now.
+    ///
+    /// # Returns
+    /// `true` if extended, `false` if the job is not leased to the worker anymore.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn extend_lease(
+        &mut self,
+        id: &Uuid,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError>;
+
+    /// Release the lease of a job claimed by a worker, a job not finished being claimable
+    /// again right away.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier.
+    /// * `worker_id` - Identifier of the worker.
+    ///
+    /// # Returns
+    /// `true` if released, `false` if the job is not leased to the worker.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn release(&mut self, id: &Uuid, worker_id: &str) -> Result<bool, ApiError>;
+
+    /// Recover the jobs claimed by dead workers, i.e. not finished and whose heartbeat is older
+    /// than a timeout.
+    ///
+    /// # Arguments
+    /// * `timeout` - Duration after which a heartbeat is stale.
+    /// * `policy` - Policy applied on the orphaned jobs.
+    ///
+    /// # Returns
+    /// The list of IDs of the jobs recovered.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn reap_orphans(
+        &mut self,
+        timeout: Duration,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, ApiError>;
+
+    /// Get the queued and running intervals of the jobs active during a time range.
+    ///
+    /// # Arguments
+    /// * `range` - Time range.
+    /// * `filter` - Filter selecting the jobs.
+    ///
+    /// # Returns
+    /// The intervals of the jobs selected, sorted by enqueue time.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn timeline(
+        &self,
+        range: &Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError>;
+
+    /// Set the pause flag of the current namespace, honored by every worker sharing the
+    /// storage: no job is dispatched while paused.
+    ///
+    /// # Arguments
+    /// * `paused` - Whether the jobs must not be dispatched.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn set_paused(&mut self, paused: bool) -> Result<(), ApiError>;
+
+    /// Get the pause flag of the current namespace.
+    ///
+    /// # Returns
+    /// `true` if paused, `false` otherwise.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn is_paused(&self) -> Result<bool, ApiError>;
+
+    /// Record the duration of a successful run of a job, feeding the statistics of its kind
+    /// shared by the workers of the current namespace.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the job.
+    /// * `duration` - Time spent running the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn record_duration(&mut self, kind: &str, duration: Duration) -> Result<(), ApiError>;
+
+    /// Get the statistics of the durations of the runs of a kind of job.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the jobs.
+    ///
+    /// # Returns
+    /// The statistics, `None` if no job of this kind has been run yet.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn duration_stats(&self, kind: &str) -> Result<Option<DurationStats>, ApiError>;
+
+    /// Set the namespace isolating the jobs of the queue from the ones of other queues sharing
+    /// the same storage. Every other method only sees the jobs of the current namespace.
+    ///
+    /// # Arguments
+    /// * `namespace` - Namespace to be set.
+    fn set_namespace(&mut self, namespace: &str);
+
+    /// Set the migrator upgrading the routines stored by an older release: the routines that
+    /// no longer deserialize, or are older than the current ones, are migrated when read.
+    ///
+    /// # Arguments
+    /// * `migrator` - Migrator to be set.
+    fn set_migrator(&mut self, migrator: SharedMigrator);
+
+    /// Set the format of the job records, for the backends storing them encoded (see
+    /// `Job::encode`).
+    ///
+    /// # Arguments
+    /// * `codec` - Codec to be set.
+    fn set_codec(&mut self, codec: SharedCodec);
+
+    /// Set the compression of the large entries of the job records, for the backends storing
+    /// them encoded (see `Job::encode_with`).
+    ///
+    /// # Arguments
+    /// * `compression` - Compression policy to be set.
+    fn set_compression(&mut self, compression: Compression);
+
+    /// Set the encryption of the private data and results of the job records, for the
+    /// backends storing them encoded (see `Job::encode_with`).
+    ///
+    /// # Arguments
+    /// * `encryption` - Encryption policy to be set.
+    fn set_encryption(&mut self, encryption: Encryption);
+
+    /// Set the clock timestamping the jobs and evaluating their expiration.
+    ///
+    /// # Arguments
+    /// * `clock` - Clock to be set.
+    fn set_clock(&mut self, clock: SharedClock);
+
     /// Get the list of all jobs.
     ///
     /// # Returns
-    /// The list of jobs
+    /// The list of jobs, ordered by enqueue time then ID.
     ///
     /// # Errors
     /// One of `Error` enum.
     fn jobs(&self) -> Result<Vec<Job>, ApiError>;
+
+    /// Get the summary of all jobs, without cloning their payload.
+    ///
+    /// # Returns
+    /// The summary of the jobs, ordered by enqueue time then ID.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn job_infos(&self) -> Result<Vec<JobInfo>, ApiError>;
+
+    /// Get a page of the summary of the jobs, ordered by enqueue time then ID. Cut out of
+    /// `job_infos` by default, the backends storing many jobs are expected to override it and
+    /// seek the cursor in an index (e.g. keyset pagination on `(enqueued, id)` in SQL), rather
+    /// than scanning every job.
+    ///
+    /// # Arguments
+    /// * `after` - Cursor the page starts after, `None` for the first page.
+    /// * `limit` - Maximum number of jobs in the page.
+    ///
+    /// # Returns
+    /// The page, with the cursor of the next one.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn jobs_page(&self, after: Option<&Cursor>, limit: usize) -> Result<JobPage, ApiError> {
+        Ok(JobPage::cut(self.job_infos()?, after, limit))
+    }
+
+    /// Count the jobs selected by a filter. Derived from `jobs` by default, the backends able
+    /// to count without listing the jobs (e.g. `COUNT` in SQL) are expected to override it.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs counted.
+    ///
+    /// # Returns
+    /// The number of jobs selected.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn count(&self, filter: &JobFilter) -> Result<usize, ApiError> {
+        Ok(self
+            .jobs()?
+            .iter()
+            .filter(|job| filter.matches(job))
+            .count())
+    }
+
+    /// Count the jobs selected by a filter, by group. Derived from `jobs` by default, the
+    /// backends able to group without listing the jobs (e.g. `GROUP BY` in SQL) are expected to
+    /// override it.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs counted.
+    /// * `group_by` - Criterion grouping the jobs.
+    ///
+    /// # Returns
+    /// The number of jobs selected, by group (the empty groups being omitted).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn aggregate(
+        &self,
+        filter: &JobFilter,
+        group_by: GroupBy,
+    ) -> Result<HashMap<GroupKey, usize>, ApiError> {
+        let mut groups = HashMap::new();
+
+        for job in self.jobs()?.iter().filter(|job| filter.matches(job)) {
+            *groups.entry(group_by.key(job)).or_default() += 1;
+        }
+
+        Ok(groups)
+    }
 }
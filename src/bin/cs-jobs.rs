@@ -0,0 +1,373 @@
+//! Command line tool used to inspect a job queue served over gRPC (see
+//! `JobQueueGrpcServer::serve`).
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use cs_jobs_v3::api_err;
+use cs_jobs_v3::prelude::*;
+use serde::Serialize;
+
+/// URL used when neither `--addr` nor `CS_JOBS_ADDR` is set.
+const DEFAULT_ADDR: &str = "http://127.0.0.1:7878";
+
+/// Help displayed on usage errors.
+const USAGE: &str = "Usage: cs-jobs [--addr URL] [--token TOKEN] [--json] <COMMAND>
+
+Commands:
+  list                  List the jobs
+  status <ID>           Show the status of a job
+  result <ID>           Show the result of a job
+  retry <ID>            Enqueue again a finished job
+  purge [OPTIONS]       Remove finished jobs (all of them without option)
+        --success             Only the jobs finished successfully
+        --errors              Only the jobs finished in error
+        --expired             Only the expired jobs
+        --older-than <SECS>   Only the jobs finished for at least SECS seconds
+        --tag <TAG>           Only the jobs holding TAG
+  stats                 Count the jobs by status
+  pause                 Stop dispatching jobs on every worker
+  resume                Dispatch jobs again on every worker
+
+The URL of the gRPC server (http://127.0.0.1:7878 by default) and its auth token can also be
+set with the CS_JOBS_ADDR and CS_JOBS_TOKEN environment variables.";
+
+/// Options common to every command.
+struct Options {
+    /// URL of the control plane.
+    addr: String,
+
+    /// Auth token of the control plane.
+    token: Option<String>,
+
+    /// Output JSON instead of tables.
+    json: bool,
+
+    /// Command and its arguments.
+    command: Vec<String>,
+}
+
+impl Options {
+    /// Parses the command line.
+    ///
+    /// # Arguments
+    /// * `args` - Arguments, without the program name.
+    ///
+    /// # Returns
+    /// The options parsed.
+    ///
+    /// # Errors
+    /// A message describing the usage error.
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            addr: std::env::var("CS_JOBS_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_owned()),
+            token: std::env::var("CS_JOBS_TOKEN").ok(),
+            json: false,
+            command: vec![],
+        };
+
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next_if(|arg| arg.starts_with("--")) {
+            match arg.as_str() {
+                "--addr" => options.addr = args.next().ok_or("Missing address")?,
+                "--token" => options.token = Some(args.next().ok_or("Missing token")?),
+                "--json" => options.json = true,
+                _ => return Err(format!("Unknown option {arg}")),
+            }
+        }
+
+        options.command = args.collect();
+
+        Ok(options)
+    }
+}
+
+/// Parses the ID of a job.
+///
+/// # Arguments
+/// * `arg` - Argument holding the ID.
+///
+/// # Returns
+/// The ID parsed.
+///
+/// # Errors
+/// A message describing the usage error.
+fn parse_id(arg: Option<&String>) -> Result<Uuid, String> {
+    let arg = arg.ok_or("Missing job ID")?;
+
+    Uuid::parse_str(arg).map_err(|e| format!("Invalid job ID {arg} ({e})"))
+}
+
+/// Parses the filter of the purge command.
+///
+/// # Arguments
+/// * `args` - Options of the command.
+///
+/// # Returns
+/// The filter parsed.
+///
+/// # Errors
+/// A message describing the usage error.
+fn parse_filter(args: &[String]) -> Result<PurgeFilter, String> {
+    let mut filter = PurgeFilter::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--success" => filter.success = true,
+            "--errors" => filter.error = true,
+            "--expired" => filter.expired = true,
+            "--older-than" => {
+                let secs = args.next().ok_or("Missing duration")?;
+                let secs = secs
+                    .parse()
+                    .map_err(|e| format!("Invalid duration {secs} ({e})"))?;
+
+                filter.older_than = Some(Duration::from_secs(secs));
+            }
+            "--tag" => filter.tag = Some(args.next().ok_or("Missing tag")?.to_owned()),
+            _ => return Err(format!("Unknown option {arg}")),
+        }
+    }
+
+    // Every finished job by default
+    if !filter.success && !filter.error && !filter.expired {
+        filter.success = true;
+        filter.error = true;
+    }
+
+    Ok(filter)
+}
+
+/// Prints rows as a table with aligned columns.
+///
+/// # Arguments
+/// * `header` - Names of the columns.
+/// * `rows` - Rows to be printed.
+fn print_table(header: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = header.iter().map(|name| name.len()).collect();
+
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let print_row = |cells: Vec<&str>| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+
+        println!("{}", line.join("  ").trim_end());
+    };
+
+    print_row(header.to_vec());
+
+    for row in rows {
+        print_row(row.iter().map(String::as_str).collect());
+    }
+}
+
+/// Prints a value as JSON.
+///
+/// # Arguments
+/// * `value` - Value to be printed.
+///
+/// # Errors
+/// One of `Error` enum.
+fn print_json(value: &impl Serialize) -> Result<(), ApiError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| api_err!(e.into()))?;
+
+    println!("{json}");
+
+    Ok(())
+}
+
+/// Runs a command against the remote queue.
+///
+/// # Arguments
+/// * `options` - Options of the command line.
+///
+/// # Errors
+/// A message describing the error.
+async fn run(options: Options) -> Result<(), String> {
+    let token = options.token.as_deref().ok_or("Missing auth token")?;
+    let transport = GrpcTransport::new(options.addr.as_str(), token).map_err(|e| e.to_string())?;
+
+    let client = JobQueueClient::new(transport);
+    let args = &options.command;
+
+    let command = args.first().ok_or("Missing command")?;
+
+    let result = match command.as_str() {
+        "list" => {
+            let jobs = client.list_jobs().await;
+
+            jobs.and_then(|jobs| {
+                if options.json {
+                    return print_json(&jobs);
+                }
+
+                let rows: Vec<Vec<String>> = jobs
+                    .iter()
+                    .map(|job| {
+                        vec![
+                            job.id.to_string(),
+                            job.kind.to_owned(),
+                            format!("{:?}", job.status),
+                            format!("{}/{}", job.progression.step, job.progression.steps),
+                            job.tags.join(","),
+                        ]
+                    })
+                    .collect();
+
+                print_table(&["ID", "KIND", "STATUS", "PROGRESS", "TAGS"], &rows);
+
+                Ok(())
+            })
+        }
+
+        "status" => {
+            let id = parse_id(args.get(1))?;
+
+            client.job_status(&id).await.and_then(|status| {
+                if options.json {
+                    print_json(&status)
+                } else {
+                    println!("{status:?}");
+
+                    Ok(())
+                }
+            })
+        }
+
+        "result" => {
+            let id = parse_id(args.get(1))?;
+
+            client.job_result(&id).await.and_then(|result| {
+                if options.json {
+                    print_json(&String::from_utf8_lossy(&result))
+                } else {
+                    println!("{}", String::from_utf8_lossy(&result));
+
+                    Ok(())
+                }
+            })
+        }
+
+        "retry" => {
+            let id = parse_id(args.get(1))?;
+
+            client.retry_job(&id).await.and_then(|new_id| {
+                if options.json {
+                    print_json(&new_id)
+                } else {
+                    println!("{new_id}");
+
+                    Ok(())
+                }
+            })
+        }
+
+        "purge" => {
+            let filter = parse_filter(&args[1..])?;
+
+            client.purge(&filter).await.and_then(|job_ids| {
+                if options.json {
+                    print_json(&job_ids)
+                } else {
+                    for job_id in &job_ids {
+                        println!("{job_id}");
+                    }
+
+                    println!("{} job(s) purged", job_ids.len());
+
+                    Ok(())
+                }
+            })
+        }
+
+        "stats" => client.stats().await.and_then(|stats| {
+            if options.json {
+                return print_json(&stats);
+            }
+
+            let drain_time = stats
+                .drain_time
+                .map(|drain_time| format!("{drain_time:?}"))
+                .unwrap_or_else(|| "-".to_owned());
+
+            print_table(
+                &["STATUS", "JOBS"],
+                &[
+                    vec!["NotReady".to_owned(), stats.not_ready.to_string()],
+                    vec!["Ready".to_owned(), stats.ready.to_string()],
+                    vec!["Running".to_owned(), stats.running.to_string()],
+                    vec!["Succeeded".to_owned(), stats.succeeded.to_string()],
+                    vec!["Failed".to_owned(), stats.failed.to_string()],
+                    vec!["Total".to_owned(), stats.total().to_string()],
+                ],
+            );
+
+            println!("\nEstimated drain time: {drain_time}");
+
+            Ok(())
+        }),
+
+        "pause" => client.pause().await.map(|_| {
+            if !options.json {
+                println!("Paused");
+            }
+        }),
+
+        "resume" => client.resume().await.map(|_| {
+            if !options.json {
+                println!("Resumed");
+            }
+        }),
+
+        _ => return Err(format!("Unknown command {command}")),
+    };
+
+    result.map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) if !options.command.is_empty() => options,
+        Ok(_) => {
+            eprintln!("{USAGE}");
+
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("{e}");
+
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match runtime.block_on(run(options)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
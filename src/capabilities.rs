@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Capabilities advertised by a worker (e.g. `gpu`, `eu-region`), so that the jobs requiring
+/// them (see `Job::set_requirements`) are pinned to the workers of the fleet providing them.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Capabilities(HashSet<String>);
+
+impl Capabilities {
+    /// Creates the capabilities of a worker.
+    ///
+    /// # Arguments
+    /// * `capabilities` - Capabilities advertised.
+    ///
+    /// # Returns
+    /// An instance of `Capabilities`.
+    pub fn new<T: Into<String>>(capabilities: impl IntoIterator<Item = T>) -> Self {
+        Self(capabilities.into_iter().map(Into::into).collect())
+    }
+
+    /// Checks if a capability is advertised.
+    ///
+    /// # Arguments
+    /// * `capability` - Capability.
+    ///
+    /// # Returns
+    /// `true` if advertised, `false` otherwise.
+    pub fn has(&self, capability: &str) -> bool {
+        self.0.contains(capability)
+    }
+
+    /// Checks if the requirements of a job are satisfied.
+    ///
+    /// # Arguments
+    /// * `requirements` - Capabilities required by the job.
+    ///
+    /// # Returns
+    /// `true` if every requirement is advertised, `false` otherwise.
+    pub fn satisfies(&self, requirements: &[String]) -> bool {
+        requirements.iter().all(|requirement| self.has(requirement))
+    }
+
+    /// Gets the requirements of a job not satisfied.
+    ///
+    /// # Arguments
+    /// * `requirements` - Capabilities required by the job.
+    ///
+    /// # Returns
+    /// The capabilities required but not advertised.
+    pub fn missing(&self, requirements: &[String]) -> Vec<String> {
+        requirements
+            .iter()
+            .filter(|requirement| !self.has(requirement))
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,257 @@
+//! Channel carrying the messages sent to the thread of a queue. Commands (progression, stop...)
+//! overtake the jobs waiting to be processed, so that they're handled promptly even when
+//! thousands of jobs have been enqueued in a burst.
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{RecvError, RecvTimeoutError, SendError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::prelude::*;
+
+/// Messages waiting in a channel.
+#[derive(Default)]
+struct Queues {
+    /// Commands waiting, processed first.
+    commands: VecDeque<Message>,
+
+    /// Jobs waiting.
+    jobs: VecDeque<Message>,
+
+    /// Number of sending ends alive.
+    senders: usize,
+
+    /// Whether the receiving end is alive.
+    receiver: bool,
+}
+
+impl Queues {
+    /// Pops the next message to be processed.
+    ///
+    /// # Returns
+    /// The oldest command if any, the oldest job otherwise.
+    fn pop(&mut self) -> Option<Message> {
+        self.commands.pop_front().or_else(|| self.jobs.pop_front())
+    }
+}
+
+/// State shared by both ends of a channel.
+#[derive(Default)]
+struct Channel {
+    /// Messages waiting.
+    queues: Mutex<Queues>,
+
+    /// Wakes up the receiver waiting for a message.
+    available: Condvar,
+}
+
+impl Channel {
+    /// Locks the messages waiting, even if a thread panicked while holding them.
+    ///
+    /// # Returns
+    /// The guard of the queues.
+    fn lock(&self) -> MutexGuard<'_, Queues> {
+        self.queues.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Sending end of the channel of a queue.
+pub struct MessageSender {
+    /// State shared with the receiver.
+    channel: Arc<Channel>,
+}
+
+impl MessageSender {
+    /// Sends a message to the thread of the queue.
+    ///
+    /// # Arguments
+    /// * `msg` - Message to be sent: commands are received before the jobs waiting.
+    ///
+    /// # Errors
+    /// The message given back if the receiving end has been dropped.
+    pub fn send(&self, msg: Message) -> Result<(), SendError<Message>> {
+        let mut queues = self.channel.lock();
+
+        if !queues.receiver {
+            return Err(SendError(msg));
+        }
+
+        match msg {
+            Message::Command(_) => queues.commands.push_back(msg),
+            Message::Job(_) => queues.jobs.push_back(msg),
+        }
+
+        self.channel.available.notify_one();
+
+        Ok(())
+    }
+
+    /// Withdraws a job waiting to be processed, e.g. cancelled before the queue stores it.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The job, `None` if not waiting anymore.
+    pub(crate) fn withdraw(&self, id: &Uuid) -> Option<Job> {
+        let mut queues = self.channel.lock();
+
+        let idx = queues
+            .jobs
+            .iter()
+            .position(|msg| matches!(msg, Message::Job(job) if job.id() == *id))?;
+
+        match queues.jobs.remove(idx)? {
+            Message::Job(job) => Some(*job),
+            Message::Command(_) => None,
+        }
+    }
+}
+
+impl Clone for MessageSender {
+    fn clone(&self) -> Self {
+        self.channel.lock().senders += 1;
+
+        Self {
+            channel: self.channel.clone(),
+        }
+    }
+}
+
+impl Drop for MessageSender {
+    fn drop(&mut self) {
+        self.channel.lock().senders -= 1;
+
+        // Receiver waiting woken up to notice the channel closed
+        self.channel.available.notify_one();
+    }
+}
+
+/// Receiving end of the channel of a queue.
+pub(crate) struct MessageReceiver {
+    /// State shared with the senders.
+    channel: Arc<Channel>,
+}
+
+impl MessageReceiver {
+    /// Waits for the next message: the oldest command if any, the oldest job otherwise.
+    ///
+    /// # Returns
+    /// The message.
+    ///
+    /// # Errors
+    /// `RecvError` once all the senders are dropped and no message is left.
+    pub(crate) fn recv(&self) -> Result<Message, RecvError> {
+        let mut queues = self.channel.lock();
+
+        loop {
+            if let Some(msg) = queues.pop() {
+                return Ok(msg);
+            }
+
+            if queues.senders == 0 {
+                return Err(RecvError);
+            }
+
+            queues = self
+                .channel
+                .available
+                .wait(queues)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Gets the next message without waiting.
+    ///
+    /// # Returns
+    /// The message.
+    ///
+    /// # Errors
+    /// `TryRecvError::Empty` if no message is waiting, `TryRecvError::Disconnected` once all
+    /// the senders are dropped and no message is left.
+    #[cfg(any(test, feature = "test-util"))]
+    pub(crate) fn try_recv(&self) -> Result<Message, std::sync::mpsc::TryRecvError> {
+        let mut queues = self.channel.lock();
+
+        match queues.pop() {
+            Some(msg) => Ok(msg),
+            None if queues.senders == 0 => Err(std::sync::mpsc::TryRecvError::Disconnected),
+            None => Err(std::sync::mpsc::TryRecvError::Empty),
+        }
+    }
+
+    /// Waits for the next message, for a limited time.
+    ///
+    /// # Arguments
+    /// * `timeout` - Maximum duration of the wait.
+    ///
+    /// # Returns
+    /// The message.
+    ///
+    /// # Errors
+    /// `RecvTimeoutError::Timeout` if no message is received in time,
+    /// `RecvTimeoutError::Disconnected` once all the senders are dropped and no message is left.
+    pub(crate) fn recv_timeout(&self, timeout: Duration) -> Result<Message, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut queues = self.channel.lock();
+
+        loop {
+            if let Some(msg) = queues.pop() {
+                return Ok(msg);
+            }
+
+            if queues.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+
+            if remaining.is_zero() {
+                return Err(RecvTimeoutError::Timeout);
+            }
+
+            queues = self
+                .channel
+                .available
+                .wait_timeout(queues, remaining)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    /// Takes the jobs still waiting, leaving the commands.
+    ///
+    /// # Returns
+    /// The jobs, the oldest first.
+    pub(crate) fn take_jobs(&self) -> Vec<Message> {
+        self.channel.lock().jobs.drain(..).collect()
+    }
+}
+
+impl Drop for MessageReceiver {
+    fn drop(&mut self) {
+        self.channel.lock().receiver = false;
+    }
+}
+
+/// Creates the channel of a queue.
+///
+/// # Returns
+/// The sending and receiving ends.
+pub(crate) fn channel() -> (MessageSender, MessageReceiver) {
+    let channel = Arc::new(Channel {
+        queues: Mutex::new(Queues {
+            senders: 1,
+            receiver: true,
+            ..Queues::default()
+        }),
+        available: Condvar::new(),
+    });
+
+    (
+        MessageSender {
+            channel: channel.clone(),
+        },
+        MessageReceiver { channel },
+    )
+}
@@ -0,0 +1,527 @@
+//! Remote client of a job queue, sharing its async API with the in-process `JobQueue` so that
+//! application code can switch between both without changing call sites.
+
+use async_trait::async_trait;
+#[cfg(feature = "grpc")]
+use serde_json::Value;
+use tokio::net::TcpStream;
+
+#[cfg(feature = "grpc")]
+use crate::grpc::proto::job_queue_client::JobQueueClient as GrpcClient;
+#[cfg(feature = "grpc")]
+use crate::grpc::{self, proto};
+use crate::prelude::*;
+use crate::tcp;
+
+/// Async API shared by the in-process queue and the remote client.
+#[async_trait]
+pub trait JobQueueApi: Send + Sync {
+    /// Push a new job to be processed in the queue.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn enqueue(&self, job: Job) -> Result<Uuid, ApiError>;
+
+    /// Get the status of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The status of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn job_status(&self, id: &Uuid) -> Result<Status, ApiError>;
+
+    /// Get the result of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The result of the job as vector of bytes.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn job_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError>;
+
+    /// Cancels a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be cancelled.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn cancel_job(&self, id: &Uuid) -> Result<(), ApiError>;
+}
+
+#[async_trait]
+impl<RoutineType, Context> JobQueueApi for JobQueue<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    async fn enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        Self::enqueue(self, job)
+    }
+
+    async fn job_status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        Self::job_status(self, id).await
+    }
+
+    async fn job_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        Self::job_result(self, id).await
+    }
+
+    async fn cancel_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        Self::cancel_job(self, id).await
+    }
+}
+
+/// Trait implemented by the transports (HTTP, gRPC...) carrying the unary RPCs of the control
+/// plane to a `JobQueueService`.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Calls a RPC of the remote control plane.
+    ///
+    /// # Arguments
+    /// * `method` - RPC to be called.
+    /// * `request` - Request encoded (see `JobQueueService::handle`).
+    ///
+    /// # Returns
+    /// The response encoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn call(&self, method: Method, request: Vec<u8>) -> Result<Vec<u8>, ApiError>;
+}
+
+/// Transport calling a control plane of the same process.
+pub struct LocalTransport<RoutineType, Context> {
+    /// Control plane called.
+    service: JobQueueService<RoutineType, Context>,
+}
+
+impl<RoutineType, Context> LocalTransport<RoutineType, Context> {
+    /// Creates a transport calling a control plane of the same process.
+    ///
+    /// # Arguments
+    /// * `service` - Control plane to be called.
+    ///
+    /// # Returns
+    /// An instance of `LocalTransport`.
+    pub fn new(service: JobQueueService<RoutineType, Context>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl<RoutineType, Context> Transport for LocalTransport<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    async fn call(&self, method: Method, request: Vec<u8>) -> Result<Vec<u8>, ApiError> {
+        Ok(self.service.handle(method, &request).await)
+    }
+}
+
+/// Transport calling a control plane served over TCP (see `JobQueueService::serve`).
+#[derive(Clone, Debug)]
+pub struct TcpTransport {
+    /// Address of the control plane.
+    addr: String,
+
+    /// Auth token sent with every request.
+    token: String,
+}
+
+impl TcpTransport {
+    /// Creates a transport calling a control plane served over TCP.
+    ///
+    /// # Arguments
+    /// * `addr` - Address of the control plane (e.g. `127.0.0.1:7878`).
+    /// * `token` - Auth token of the control plane (see `JobQueueService::token`).
+    ///
+    /// # Returns
+    /// An instance of `TcpTransport`.
+    pub fn new(addr: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            token: token.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTransport {
+    async fn call(&self, method: Method, request: Vec<u8>) -> Result<Vec<u8>, ApiError> {
+        let stream = TcpStream::connect(&self.addr)
+            .await
+            .map_err(|e| api_err!(e.into()))?;
+
+        let frame = tcp::Frame::new(method, &request, &self.token)?;
+        let line = serde_json::to_vec(&frame).map_err(|e| api_err!(e.into()))?;
+
+        tcp::write_line(&stream, &line).await?;
+
+        tcp::read_line(&stream, &mut vec![], tcp::MAX_RESPONSE_SIZE)
+            .await?
+            .ok_or(api_err!(Error::Remote("Connection closed".to_owned())))
+    }
+}
+
+/// Transport calling a control plane served over gRPC (see `JobQueueGrpcServer`).
+#[cfg(feature = "grpc")]
+#[derive(Clone, Debug)]
+pub struct GrpcTransport {
+    /// Client of the gRPC server, connected on the first call.
+    client: GrpcClient<tonic::transport::Channel>,
+
+    /// Auth token sent with every RPC.
+    token: String,
+}
+
+#[cfg(feature = "grpc")]
+impl GrpcTransport {
+    /// Creates a transport calling a control plane served over gRPC. Must be called within a
+    /// Tokio runtime.
+    ///
+    /// # Arguments
+    /// * `addr` - URL of the gRPC server (e.g. `http://127.0.0.1:7878`).
+    /// * `token` - Auth token of the control plane (see `JobQueueService::token`).
+    ///
+    /// # Returns
+    /// An instance of `GrpcTransport`.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn new(addr: impl Into<String>, token: impl Into<String>) -> Result<Self, ApiError> {
+        let channel = tonic::transport::Endpoint::from_shared(addr.into())
+            .map_err(|e| api_err!(Error::InvalidConfig(e.to_string())))?
+            .connect_lazy();
+
+        Ok(Self {
+            client: GrpcClient::new(channel),
+            token: token.into(),
+        })
+    }
+
+    /// Wraps the message of a RPC into a request carrying the auth token.
+    ///
+    /// # Arguments
+    /// * `message` - Message of the RPC.
+    ///
+    /// # Returns
+    /// The request.
+    fn request<T>(&self, message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+
+        if let Ok(value) = format!("Bearer {}", self.token).parse() {
+            request
+                .metadata_mut()
+                .insert(grpc::AUTHORIZATION_HEADER, value);
+        }
+
+        request
+    }
+
+    /// Calls the RPC of the gRPC server matching a unary RPC of the control plane.
+    ///
+    /// # Arguments
+    /// * `method` - RPC to be called.
+    /// * `request` - Request encoded (see `JobQueueService::handle`).
+    ///
+    /// # Returns
+    /// The response of the RPC, or the status of the gRPC call if it failed.
+    ///
+    /// # Errors
+    /// One of `Error` enum, if the request or the response can't be converted.
+    async fn send(
+        &self,
+        method: Method,
+        request: Vec<u8>,
+    ) -> Result<Result<Value, tonic::Status>, ApiError> {
+        let mut client = self.client.clone();
+
+        match method {
+            Method::Enqueue => Self::respond(
+                client
+                    .enqueue(self.request(proto::EnqueueRequest { job: request }))
+                    .await
+                    .map(|response| response.into_inner().id),
+            ),
+            Method::GetStatus => Self::respond(
+                client
+                    .get_status(self.request(proto::GetStatusRequest {
+                        id: Self::decode::<Uuid>(&request)?.to_string(),
+                    }))
+                    .await
+                    .map(|response| Status::from(response.into_inner().status())),
+            ),
+            Method::GetResult => Self::respond(
+                client
+                    .get_result(self.request(proto::GetResultRequest {
+                        id: Self::decode::<Uuid>(&request)?.to_string(),
+                    }))
+                    .await
+                    .map(|response| response.into_inner().result),
+            ),
+            Method::Cancel => Self::respond(
+                client
+                    .cancel(self.request(proto::CancelRequest {
+                        id: Self::decode::<Uuid>(&request)?.to_string(),
+                    }))
+                    .await
+                    .map(|_| ()),
+            ),
+            Method::ListJobs => Self::respond(
+                client
+                    .list_jobs(self.request(proto::ListJobsRequest {}))
+                    .await
+                    .and_then(|response| {
+                        response
+                            .into_inner()
+                            .jobs
+                            .into_iter()
+                            .map(JobSummary::try_from)
+                            .collect::<Result<Vec<_>, _>>()
+                    }),
+            ),
+            Method::Retry => Self::respond(
+                client
+                    .retry(self.request(proto::RetryRequest {
+                        id: Self::decode::<Uuid>(&request)?.to_string(),
+                    }))
+                    .await
+                    .map(|response| response.into_inner().id),
+            ),
+            Method::Purge => {
+                Self::respond(
+                    client
+                        .purge(self.request(proto::PurgeRequest::from(
+                            Self::decode::<PurgeFilter>(&request)?,
+                        )))
+                        .await
+                        .map(|response| response.into_inner().ids),
+                )
+            }
+            Method::Stats => Self::respond(
+                client
+                    .stats(self.request(proto::StatsRequest {}))
+                    .await
+                    .map(|response| QueueStats::from(response.into_inner())),
+            ),
+            Method::Pause => Self::respond(
+                client
+                    .pause(self.request(proto::PauseRequest {}))
+                    .await
+                    .map(|_| ()),
+            ),
+            Method::Resume => Self::respond(
+                client
+                    .resume(self.request(proto::ResumeRequest {}))
+                    .await
+                    .map(|_| ()),
+            ),
+        }
+    }
+
+    /// Converts the response of a RPC to a JSON value, as returned by the control plane.
+    ///
+    /// # Arguments
+    /// * `response` - Response of the RPC, or the status of the gRPC call if it failed.
+    ///
+    /// # Returns
+    /// The response as JSON value, or the status of the gRPC call if it failed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn respond<T: Serialize>(
+        response: Result<T, tonic::Status>,
+    ) -> Result<Result<Value, tonic::Status>, ApiError> {
+        match response {
+            Ok(response) => Ok(Ok(
+                serde_json::to_value(response).map_err(|e| api_err!(e.into()))?
+            )),
+            Err(status) => Ok(Err(status)),
+        }
+    }
+
+    /// Decodes a request.
+    ///
+    /// # Arguments
+    /// * `request` - Request encoded.
+    ///
+    /// # Returns
+    /// The request decoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn decode<T: for<'a> Deserialize<'a>>(request: &[u8]) -> Result<T, ApiError> {
+        serde_json::from_slice(request).map_err(|e| api_err!(e.into()))
+    }
+}
+
+#[cfg(feature = "grpc")]
+#[async_trait]
+impl Transport for GrpcTransport {
+    async fn call(&self, method: Method, request: Vec<u8>) -> Result<Vec<u8>, ApiError> {
+        let response = match self.send(method, request).await? {
+            Ok(response) => Ok(response),
+            Err(status) => Err(status.message().to_owned()),
+        };
+
+        serde_json::to_vec(&response).map_err(|e| api_err!(e.into()))
+    }
+}
+
+/// Client of a remote job queue. Jobs are sent in the wire format, hence only their routine,
+/// ID, metadata and idempotency key are transmitted.
+pub struct JobQueueClient<T> {
+    /// Transport carrying the calls.
+    transport: T,
+}
+
+impl<T: Transport> JobQueueClient<T> {
+    /// Creates a client of a remote job queue.
+    ///
+    /// # Arguments
+    /// * `transport` - Transport carrying the calls.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueClient`.
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Lists the jobs of the remote queue.
+    ///
+    /// # Returns
+    /// The summary of every job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn list_jobs(&self) -> Result<Vec<JobSummary>, ApiError> {
+        self.call(Method::ListJobs, vec![]).await
+    }
+
+    /// Enqueues again a finished job of the remote queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be retried.
+    ///
+    /// # Returns
+    /// The unique ID of the new job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn retry_job(&self, id: &Uuid) -> Result<Uuid, ApiError> {
+        self.call(Method::Retry, Self::encode_id(id)?).await
+    }
+
+    /// Removes the finished jobs of the remote queue selected by a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs to be removed.
+    ///
+    /// # Returns
+    /// The list of IDs of the removed jobs.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn purge(&self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        let request = serde_json::to_vec(filter).map_err(|e| api_err!(e.into()))?;
+
+        self.call(Method::Purge, request).await
+    }
+
+    /// Gets the statistics of the remote queue.
+    ///
+    /// # Returns
+    /// The jobs of the queue counted by status.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn stats(&self) -> Result<QueueStats, ApiError> {
+        self.call(Method::Stats, vec![]).await
+    }
+
+    /// Pauses the remote queue, and every worker sharing its backend.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn pause(&self) -> Result<(), ApiError> {
+        self.call(Method::Pause, vec![]).await
+    }
+
+    /// Resumes the remote queue, and every worker sharing its backend.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn resume(&self) -> Result<(), ApiError> {
+        self.call(Method::Resume, vec![]).await
+    }
+
+    /// Calls a RPC of the remote control plane.
+    ///
+    /// # Arguments
+    /// * `method` - RPC to be called.
+    /// * `request` - Request encoded.
+    ///
+    /// # Returns
+    /// The response decoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::Remote` for the errors raised by the remote queue.
+    async fn call<R>(&self, method: Method, request: Vec<u8>) -> Result<R, ApiError>
+    where
+        R: for<'a> Deserialize<'a>,
+    {
+        let bytes = self.transport.call(method, request).await?;
+        let response: Result<R, String> =
+            serde_json::from_slice(&bytes).map_err(|e| api_err!(e.into()))?;
+
+        response.map_err(|e| api_err!(Error::Remote(e)))
+    }
+
+    /// Encodes a request made of a job ID.
+    ///
+    /// # Arguments
+    /// * `id` - Job ID.
+    ///
+    /// # Returns
+    /// The request encoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn encode_id(id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        serde_json::to_vec(id).map_err(|e| api_err!(e.into()))
+    }
+}
+
+#[async_trait]
+impl<T: Transport> JobQueueApi for JobQueueClient<T> {
+    async fn enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        self.call(Method::Enqueue, job.to_wire()?).await
+    }
+
+    async fn job_status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.call(Method::GetStatus, Self::encode_id(id)?).await
+    }
+
+    async fn job_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        self.call(Method::GetResult, Self::encode_id(id)?).await
+    }
+
+    async fn cancel_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        self.call(Method::Cancel, Self::encode_id(id)?).await
+    }
+}
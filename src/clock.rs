@@ -0,0 +1,130 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::Notify;
+
+use crate::prelude::*;
+
+/// Type used to share a clock across threads.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Source of time of a queue, used for the expiration of the jobs, their delays and deadlines.
+#[async_trait]
+pub trait Clock: Send + Sync {
+    /// Gets the current time.
+    ///
+    /// # Returns
+    /// The current time.
+    fn now(&self) -> SystemTime;
+
+    /// Waits until a time is reached.
+    ///
+    /// # Arguments
+    /// * `deadline` - Time to be reached.
+    async fn sleep_until(&self, deadline: SystemTime);
+}
+
+/// Clock of the system, the default one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    async fn sleep_until(&self, deadline: SystemTime) {
+        let delay = deadline
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Time of a manual clock, with the sleepers waiting for it to move forward.
+struct ManualTime {
+    /// Current time.
+    now: Mutex<SystemTime>,
+
+    /// Wakes the sleepers up when the time moves.
+    moved: Notify,
+}
+
+/// Clock frozen until moved forward explicitly, for tests. Its clones share the same time.
+#[derive(Clone)]
+pub struct ManualClock {
+    /// Time shared by the clones.
+    time: Arc<ManualTime>,
+}
+
+impl ManualClock {
+    /// Creates a clock frozen at a time.
+    ///
+    /// # Arguments
+    /// * `now` - Time of the clock.
+    ///
+    /// # Returns
+    /// An instance of `ManualClock`.
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            time: Arc::new(ManualTime {
+                now: Mutex::new(now),
+                moved: Notify::new(),
+            }),
+        }
+    }
+
+    /// Sets the time of the clock, waking up the sleepers it reaches.
+    ///
+    /// # Arguments
+    /// * `now` - New time of the clock.
+    pub fn set(&self, now: SystemTime) {
+        *self
+            .time
+            .now
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = now;
+
+        self.time.moved.notify_waiters();
+    }
+
+    /// Moves the clock forward, waking up the sleepers it reaches.
+    ///
+    /// # Arguments
+    /// * `duration` - Duration elapsed.
+    pub fn advance(&self, duration: Duration) {
+        self.set(self.now() + duration);
+    }
+}
+
+impl Default for ManualClock {
+    /// Creates a clock frozen at the current time.
+    fn default() -> Self {
+        Self::new(SystemTime::now())
+    }
+}
+
+#[async_trait]
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        *self
+            .time
+            .now
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    async fn sleep_until(&self, deadline: SystemTime) {
+        loop {
+            // Registered before checking, not to miss a move in between
+            let moved = self.time.moved.notified();
+
+            if self.now() >= deadline {
+                return;
+            }
+
+            moved.await;
+        }
+    }
+}
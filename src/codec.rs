@@ -0,0 +1,324 @@
+//! Serialization formats of the job records stored by the backends.
+//!
+//! A record is the job serialized directly by the codec. In human-readable formats (JSON), the
+//! routine, the private data and the metadata of the job are embedded as structured values;
+//! binary formats store them as JSON text, so that non self-describing formats (bincode) can
+//! decode them too. JSON is used unless another `Codec` is set on the queue; bincode,
+//! MessagePack and CBOR are provided with the `bincode`, `msgpack` and `cbor` features, other
+//! formats being plugged by implementing the trait on top of their crate.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share the codec with the backend.
+pub type SharedCodec = Arc<dyn Codec>;
+
+/// Trait encoding the job records stored by the backends.
+pub trait Codec: Send + Sync {
+    /// Name of the format, to be stored alongside the records.
+    ///
+    /// # Returns
+    /// The name of the format (e.g. `json`).
+    fn name(&self) -> &str;
+
+    /// Encodes a record.
+    ///
+    /// # Arguments
+    /// * `job` - Record to be encoded (see `Job::encode_with`).
+    ///
+    /// # Returns
+    /// The record encoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError>;
+
+    /// Decodes a record.
+    ///
+    /// # Arguments
+    /// * `bytes` - Record encoded.
+    ///
+    /// # Returns
+    /// The record decoded (see `Job::decode_with`).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn decode(&self, bytes: &[u8]) -> Result<Job, ApiError>;
+}
+
+/// Codec storing the records as JSON documents (default).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        serde_json::to_vec(job).map_err(|e| api_err!(e.into()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Job, ApiError> {
+        serde_json::from_slice(bytes).map_err(|e| api_err!(e.into()))
+    }
+}
+
+/// Codec storing the records in the bincode format, compact but not self-describing.
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode")]
+impl Codec for BincodeCodec {
+    fn name(&self) -> &str {
+        "bincode"
+    }
+
+    fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        bincode::serialize(job).map_err(|e| api_err!(Error::Custom(e.to_string())))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Job, ApiError> {
+        bincode::deserialize(bytes).map_err(|e| api_err!(Error::Custom(e.to_string())))
+    }
+}
+
+/// Codec storing the records in the MessagePack format, the fields of the job by name.
+#[cfg(feature = "msgpack")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessagePackCodec;
+
+#[cfg(feature = "msgpack")]
+impl Codec for MessagePackCodec {
+    fn name(&self) -> &str {
+        "msgpack"
+    }
+
+    fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        rmp_serde::to_vec_named(job).map_err(|e| api_err!(Error::Custom(e.to_string())))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Job, ApiError> {
+        rmp_serde::from_slice(bytes).map_err(|e| api_err!(Error::Custom(e.to_string())))
+    }
+}
+
+/// Codec storing the records in the CBOR format.
+#[cfg(feature = "cbor")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CborCodec;
+
+#[cfg(feature = "cbor")]
+impl Codec for CborCodec {
+    fn name(&self) -> &str {
+        "cbor"
+    }
+
+    fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        let mut bytes = vec![];
+
+        ciborium::into_writer(job, &mut bytes)
+            .map_err(|e| api_err!(Error::Custom(e.to_string())))?;
+
+        Ok(bytes)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Job, ApiError> {
+        ciborium::from_reader(bytes).map_err(|e| api_err!(Error::Custom(e.to_string())))
+    }
+}
+
+/// Serializes a JSON document held as text: embedded as a structured value in the
+/// human-readable formats, kept as text in the binary ones. An empty text stands for an
+/// entry moved out of the record (compressed or encrypted).
+pub(crate) mod embedded {
+    use super::*;
+
+    /// Serializes a JSON document.
+    ///
+    /// # Arguments
+    /// * `json` - JSON document to be serialized.
+    /// * `serializer` - Serializer used.
+    ///
+    /// # Errors
+    /// The error of the serializer, if the document is not valid JSON.
+    pub(crate) fn serialize<S: Serializer>(json: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        match (serializer.is_human_readable(), json.is_empty()) {
+            (true, true) => serializer.serialize_unit(),
+            (true, false) => serde_json::from_str::<Value>(json)
+                .map_err(serde::ser::Error::custom)?
+                .serialize(serializer),
+            (false, _) => serializer.serialize_str(json),
+        }
+    }
+
+    /// Deserializes a JSON document.
+    ///
+    /// # Arguments
+    /// * `deserializer` - Deserializer used.
+    ///
+    /// # Returns
+    /// The JSON document, as text.
+    ///
+    /// # Errors
+    /// The error of the deserializer.
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<String, D::Error> {
+        match deserializer.is_human_readable() {
+            true => match Value::deserialize(deserializer)? {
+                Value::Null => Ok(String::new()),
+                value => Ok(value.to_string()),
+            },
+            false => String::deserialize(deserializer),
+        }
+    }
+}
+
+/// Serializes an optional JSON document held as text (see `embedded`).
+pub(crate) mod embedded_opt {
+    use super::*;
+
+    /// Serializes an optional JSON document.
+    ///
+    /// # Arguments
+    /// * `json` - JSON document to be serialized.
+    /// * `serializer` - Serializer used.
+    ///
+    /// # Errors
+    /// The error of the serializer, if the document is not valid JSON.
+    pub(crate) fn serialize<S: Serializer>(
+        json: &Option<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match (serializer.is_human_readable(), json) {
+            (true, Some(json)) => embedded::serialize(json, serializer),
+            (true, None) => serializer.serialize_unit(),
+            (false, json) => json.serialize(serializer),
+        }
+    }
+
+    /// Deserializes an optional JSON document.
+    ///
+    /// # Arguments
+    /// * `deserializer` - Deserializer used.
+    ///
+    /// # Returns
+    /// The JSON document as text, `None` if missing.
+    ///
+    /// # Errors
+    /// The error of the deserializer.
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<String>, D::Error> {
+        match deserializer.is_human_readable() {
+            true => embedded::deserialize(deserializer)
+                .map(|json| Some(json).filter(|json| !json.is_empty())),
+            false => Option::<String>::deserialize(deserializer),
+        }
+    }
+}
+
+/// Serializes an optional JSON value: as is in the human-readable formats, as JSON text in the
+/// binary ones.
+pub(crate) mod value_opt {
+    use super::*;
+
+    /// Serializes an optional JSON value.
+    ///
+    /// # Arguments
+    /// * `value` - Value to be serialized.
+    /// * `serializer` - Serializer used.
+    ///
+    /// # Errors
+    /// The error of the serializer.
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<Value>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match serializer.is_human_readable() {
+            true => value.serialize(serializer),
+            false => value.as_ref().map(Value::to_string).serialize(serializer),
+        }
+    }
+
+    /// Deserializes an optional JSON value.
+    ///
+    /// # Arguments
+    /// * `deserializer` - Deserializer used.
+    ///
+    /// # Returns
+    /// The value, `None` if missing.
+    ///
+    /// # Errors
+    /// The error of the deserializer, if the text is not valid JSON.
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Value>, D::Error> {
+        match deserializer.is_human_readable() {
+            true => Option::<Value>::deserialize(deserializer),
+            false => Option::<String>::deserialize(deserializer)?
+                .map(|json| serde_json::from_str(&json).map_err(serde::de::Error::custom))
+                .transpose(),
+        }
+    }
+}
+
+/// Serializes JSON values by key: as is in the human-readable formats, as JSON text in the
+/// binary ones.
+pub(crate) mod values {
+    use super::*;
+
+    /// Serializes JSON values by key.
+    ///
+    /// # Arguments
+    /// * `values` - Values to be serialized.
+    /// * `serializer` - Serializer used.
+    ///
+    /// # Errors
+    /// The error of the serializer.
+    pub(crate) fn serialize<S: Serializer>(
+        values: &BTreeMap<String, Value>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match serializer.is_human_readable() {
+            true => values.serialize(serializer),
+            false => values
+                .iter()
+                .map(|(key, value)| (key, value.to_string()))
+                .collect::<BTreeMap<_, _>>()
+                .serialize(serializer),
+        }
+    }
+
+    /// Deserializes JSON values by key.
+    ///
+    /// # Arguments
+    /// * `deserializer` - Deserializer used.
+    ///
+    /// # Returns
+    /// The values by key.
+    ///
+    /// # Errors
+    /// The error of the deserializer, if a text is not valid JSON.
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Value>, D::Error> {
+        match deserializer.is_human_readable() {
+            true => BTreeMap::deserialize(deserializer),
+            false => BTreeMap::<String, String>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(key, json)| {
+                    serde_json::from_str(&json)
+                        .map(|value| (key, value))
+                        .map_err(serde::de::Error::custom)
+                })
+                .collect(),
+        }
+    }
+}
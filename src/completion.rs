@@ -0,0 +1,63 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::prelude::*;
+
+/// Key of the metadata holding the outcome of a job in its follow-up job (see
+/// `Job::set_on_complete_routine`).
+pub const OUTCOME_METADATA: &str = "outcome";
+
+/// Callback called once a job is finished (see `Job::set_on_complete`).
+pub(crate) type CompletionFn = Box<dyn FnOnce(&Outcome) + Send>;
+
+lazy_static! {
+    /// Callbacks of the jobs not finished yet, by key.
+    static ref REGISTRY: Mutex<HashMap<Uuid, CompletionFn>> = Mutex::new(HashMap::new());
+}
+
+/// Outcome of a finished job, given to its completion callback or follow-up job.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Outcome {
+    /// ID of the job.
+    pub id: Uuid,
+
+    /// Kind of the job.
+    pub kind: String,
+
+    /// Result status of the job.
+    pub status: ResultStatus,
+
+    /// Result of the job (empty if failed).
+    pub result: Vec<u8>,
+
+    /// Error message of the job, if failed.
+    pub error: Option<String>,
+}
+
+/// Registers the completion callback of a job.
+///
+/// # Arguments
+/// * `key` - Key of the callback.
+/// * `callback` - Callback to be called.
+pub(crate) fn register(key: Uuid, callback: CompletionFn) {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(key, callback);
+}
+
+/// Takes the completion callback of a job, to be called once.
+///
+/// # Arguments
+/// * `key` - Key of the callback.
+///
+/// # Returns
+/// The callback, unless already taken or registered by another process.
+pub(crate) fn take(key: &Uuid) -> Option<CompletionFn> {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .remove(key)
+}
@@ -0,0 +1,246 @@
+//! Compression of the large payloads of the job records stored by the backends (see
+//! `Job::encode_with`).
+//!
+//! The routine, the private data and the result of a job are compressed once larger than the
+//! threshold of the `Compression` policy, each entry recording the compressor used so that
+//! records written with other settings can still be read. Algorithms are plugged by
+//! implementing `Compressor` on top of their crate, zstd and gzip being provided with the
+//! `zstd` and `gzip` features (see `ZstdCompressor` and `GzipCompressor`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "gzip")]
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share a compressor.
+pub type SharedCompressor = Arc<dyn Compressor>;
+
+/// Trait implementing a compression algorithm.
+pub trait Compressor: Send + Sync {
+    /// Name of the algorithm, recorded with every entry compressed.
+    ///
+    /// # Returns
+    /// The name of the algorithm (e.g. `zstd`).
+    fn name(&self) -> &str;
+
+    /// Compresses bytes.
+    ///
+    /// # Arguments
+    /// * `bytes` - Bytes to be compressed.
+    ///
+    /// # Returns
+    /// The bytes compressed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError>;
+
+    /// Decompresses bytes.
+    ///
+    /// # Arguments
+    /// * `bytes` - Bytes compressed.
+    ///
+    /// # Returns
+    /// The bytes decompressed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError>;
+}
+
+/// Entry of a record compressed.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Packed {
+    /// Name of the compressor used.
+    pub compressor: String,
+
+    /// Bytes compressed.
+    pub data: Vec<u8>,
+}
+
+/// Policy compressing the entries of the job records larger than a threshold.
+#[derive(Clone, Default)]
+pub struct Compression {
+    /// Compressor of the new entries (disabled if `None`).
+    compressor: Option<SharedCompressor>,
+
+    /// Size in bytes from which an entry is compressed.
+    threshold: usize,
+
+    /// Compressors able to read the entries, by name.
+    readers: HashMap<String, SharedCompressor>,
+}
+
+impl Compression {
+    /// Creates a policy compressing the entries from a given size.
+    ///
+    /// # Arguments
+    /// * `compressor` - Compressor of the new entries.
+    /// * `threshold` - Size in bytes from which an entry is compressed.
+    ///
+    /// # Returns
+    /// An instance of `Compression`.
+    pub fn new(compressor: impl Compressor + 'static, threshold: usize) -> Self {
+        let compressor: SharedCompressor = Arc::new(compressor);
+
+        Self {
+            readers: HashMap::from([(compressor.name().to_owned(), compressor.clone())]),
+            compressor: Some(compressor),
+            threshold,
+        }
+    }
+
+    /// Adds a compressor only used to read entries (e.g. the one of a previous setting).
+    ///
+    /// # Arguments
+    /// * `compressor` - Compressor to be added.
+    ///
+    /// # Returns
+    /// The policy.
+    pub fn reader(mut self, compressor: impl Compressor + 'static) -> Self {
+        self.readers
+            .insert(compressor.name().to_owned(), Arc::new(compressor));
+
+        self
+    }
+
+    /// Compresses an entry if larger than the threshold.
+    ///
+    /// # Arguments
+    /// * `bytes` - Entry to be compressed.
+    ///
+    /// # Returns
+    /// The entry compressed, `None` if kept as is.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn pack(&self, bytes: &[u8]) -> Result<Option<Packed>, ApiError> {
+        match &self.compressor {
+            Some(compressor) if bytes.len() >= self.threshold => Ok(Some(Packed {
+                compressor: compressor.name().to_owned(),
+                data: compressor.compress(bytes)?,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    /// Decompresses an entry with the compressor recorded.
+    ///
+    /// # Arguments
+    /// * `packed` - Entry compressed.
+    ///
+    /// # Returns
+    /// The entry decompressed.
+    ///
+    /// # Errors
+    /// `Error::UnknownCompressor` if the compressor is not readable by the policy.
+    pub fn unpack(&self, packed: &Packed) -> Result<Vec<u8>, ApiError> {
+        self.readers
+            .get(&packed.compressor)
+            .ok_or(api_err!(Error::UnknownCompressor(
+                packed.compressor.to_owned()
+            )))?
+            .decompress(&packed.data)
+    }
+}
+
+/// Zstandard compressor, named `zstd` in the entries.
+#[cfg(feature = "zstd")]
+#[derive(Clone, Copy, Debug)]
+pub struct ZstdCompressor {
+    /// Compression level (1 to 22).
+    level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdCompressor {
+    /// Creates a compressor with a given level.
+    ///
+    /// # Arguments
+    /// * `level` - Compression level (1 to 22, the higher the smaller and slower).
+    ///
+    /// # Returns
+    /// An instance of `ZstdCompressor`.
+    pub fn new(level: i32) -> Self {
+        Self { level }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Default for ZstdCompressor {
+    fn default() -> Self {
+        Self::new(zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Compressor for ZstdCompressor {
+    fn name(&self) -> &str {
+        "zstd"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+        zstd::encode_all(bytes, self.level).map_err(|e| api_err!(e.into()))
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+        zstd::decode_all(bytes).map_err(|e| api_err!(e.into()))
+    }
+}
+
+/// Gzip compressor, named `gzip` in the entries.
+#[cfg(feature = "gzip")]
+#[derive(Clone, Copy, Debug)]
+pub struct GzipCompressor {
+    /// Compression level (0 to 9).
+    level: u32,
+}
+
+#[cfg(feature = "gzip")]
+impl GzipCompressor {
+    /// Creates a compressor with a given level.
+    ///
+    /// # Arguments
+    /// * `level` - Compression level (0 to 9, the higher the smaller and slower).
+    ///
+    /// # Returns
+    /// An instance of `GzipCompressor`.
+    pub fn new(level: u32) -> Self {
+        Self { level }
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl Default for GzipCompressor {
+    fn default() -> Self {
+        Self::new(6)
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl Compressor for GzipCompressor {
+    fn name(&self) -> &str {
+        "gzip"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+        let mut encoder =
+            flate2::write::GzEncoder::new(vec![], flate2::Compression::new(self.level));
+
+        encoder.write_all(bytes).map_err(|e| api_err!(e.into()))?;
+        encoder.finish().map_err(|e| api_err!(e.into()))
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+        let mut decompressed = vec![];
+
+        flate2::read::GzDecoder::new(bytes)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| api_err!(e.into()))?;
+
+        Ok(decompressed)
+    }
+}
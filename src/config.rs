@@ -0,0 +1,390 @@
+//! Declarative configuration of a job queue, deserializable from any serde format, loaded from
+//! a TOML document (`toml` feature) or from environment variables.
+//!
+//! ```toml
+//! thread_pool_size = 8
+//! max_in_flight = 32
+//! namespace = "billing"
+//! poll_interval_ms = 500
+//! sweep_interval_ms = 1000
+//! keep_last = 100
+//! allowed_kinds = ["SendEmail"]
+//! max_attempts = 3
+//! retry_backoff_ms = 1000
+//! default_timeout_ms = 60000
+//! ```
+//!
+//! Durations are expressed in milliseconds, every setting is optional (the default of the
+//! queue is kept when missing).
+
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+use crate::prelude::*;
+
+/// Configuration of a job queue (see `JobQueueBuilder::from_config`).
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobQueueConfig {
+    /// Number of threads of the pool running the jobs (number of CPUs if missing).
+    pub thread_pool_size: Option<usize>,
+
+    /// Maximum number of jobs running at the same time.
+    pub max_in_flight: Option<usize>,
+
+    /// Policy adjusting the maximum number of jobs running to the depth of the queue.
+    pub autoscale: Option<AutoscalePolicy>,
+
+    /// Namespace isolating the jobs of the queue in the backend.
+    pub namespace: Option<String>,
+
+    /// Identifier of the worker claiming jobs in the backend.
+    pub worker_id: Option<String>,
+
+    /// Only kinds of job executed by the worker.
+    pub allowed_kinds: Option<Vec<String>>,
+
+    /// Kinds of job not executed by the worker.
+    pub denied_kinds: Option<Vec<String>>,
+
+    /// Interval at which a backend shared with other workers is polled (distributed mode).
+    pub poll_interval_ms: Option<u64>,
+
+    /// Duration of the leases of the jobs claimed from a shared backend.
+    pub lease_duration_ms: Option<u64>,
+
+    /// Interval at which the expired jobs are reaped (evaluated lazily if 0).
+    pub sweep_interval_ms: Option<u64>,
+
+    /// Number of finished jobs kept by kind (retention policy).
+    pub keep_last: Option<usize>,
+
+    /// Maximum duration given to the queue to drain once stopped.
+    pub drain_timeout_ms: Option<u64>,
+
+    /// Window during which the jobs holding the same idempotency key are duplicates.
+    pub idempotency_window_ms: Option<u64>,
+
+    /// Duration during which the removed jobs are kept to be restored (soft-delete).
+    pub soft_delete_ms: Option<u64>,
+
+    /// Maximum number of attempts of the jobs whose routine fails (see `RetryPolicy`).
+    pub max_attempts: Option<u32>,
+
+    /// Delay before the first retry of a job, doubled for every following one.
+    pub retry_backoff_ms: Option<u64>,
+
+    /// Maximum delay between two attempts of a job.
+    pub retry_max_backoff_ms: Option<u64>,
+
+    /// Timeout of the jobs enqueued without deadline.
+    pub default_timeout_ms: Option<u64>,
+}
+
+impl JobQueueConfig {
+    /// Loads a configuration from the environment variables holding a prefix, named after the
+    /// settings in upper case (e.g. `CS_JOBS_MAX_IN_FLIGHT=32` for the prefix `CS_JOBS_`). A
+    /// numeric string setting must be quoted (e.g. `CS_JOBS_NAMESPACE='"42"'`).
+    ///
+    /// # Arguments
+    /// * `prefix` - Prefix of the variables.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueConfig`.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::InvalidConfig` for an unknown or invalid variable.
+    pub fn from_env(prefix: &str) -> Result<Self, ApiError> {
+        Self::from_vars(prefix, std::env::vars())
+    }
+
+    /// Loads a configuration from a TOML document, holding the settings at its top level.
+    ///
+    /// # Arguments
+    /// * `toml` - TOML document.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueConfig`.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::InvalidConfig` for an invalid document or setting.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(toml: &str) -> Result<Self, ApiError> {
+        toml::from_str(toml).map_err(|e| api_err!(Error::InvalidConfig(e.to_string())))
+    }
+
+    /// Loads a configuration from variables holding a prefix.
+    ///
+    /// # Arguments
+    /// * `prefix` - Prefix of the variables.
+    /// * `vars` - Variables as name and value.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueConfig`.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::InvalidConfig` for an unknown or invalid variable.
+    fn from_vars(
+        prefix: &str,
+        vars: impl Iterator<Item = (String, String)>,
+    ) -> Result<Self, ApiError> {
+        let mut settings = Map::new();
+
+        for (name, value) in vars {
+            let Some(setting) = name.strip_prefix(prefix) else {
+                continue;
+            };
+
+            // Numbers parsed as such, anything else kept as string (unless quoted)
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+
+            settings.insert(setting.to_lowercase(), value);
+        }
+
+        serde_json::from_value(Value::Object(settings))
+            .map_err(|e| api_err!(Error::InvalidConfig(e.to_string())))
+    }
+}
+
+impl<RoutineType, Context> JobQueueBuilder<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Create a builder for the job queue from a configuration, the settings not configured
+    /// keeping their default.
+    ///
+    /// # Arguments:
+    /// * `config` - Configuration of the queue.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::InvalidConfig` if both allowed and denied kinds are set, or
+    /// a retry backoff without a maximum number of attempts.
+    pub fn from_config(config: JobQueueConfig) -> Result<Self, ApiError> {
+        let mut builder = match config.thread_pool_size {
+            Some(thread_pool_size) => Self::new_with_pool_size(thread_pool_size)?,
+            None => Self::new()?,
+        };
+
+        if let Some(max_in_flight) = config.max_in_flight {
+            builder = builder.max_in_flight(max_in_flight);
+        }
+
+        if let Some(autoscale) = config.autoscale {
+            builder = builder.autoscale(AutoscalePolicy::new(
+                autoscale.min,
+                autoscale.max,
+                autoscale.target_depth,
+            ));
+        }
+
+        if let Some(namespace) = config.namespace {
+            builder = builder.namespace(namespace);
+        }
+
+        if let Some(worker_id) = config.worker_id {
+            builder = builder.worker_id(worker_id);
+        }
+
+        match (config.allowed_kinds, config.denied_kinds) {
+            (Some(_), Some(_)) => {
+                return Err(api_err!(Error::InvalidConfig(
+                    "allowed_kinds and denied_kinds are exclusive".to_owned()
+                )))
+            }
+            (Some(kinds), None) => builder = builder.kinds(KindFilter::allow(kinds)),
+            (None, Some(kinds)) => builder = builder.kinds(KindFilter::deny(kinds)),
+            (None, None) => {}
+        }
+
+        if let Some(poll_interval) = config.poll_interval_ms {
+            builder = builder.distributed(Duration::from_millis(poll_interval));
+        }
+
+        if let Some(lease) = config.lease_duration_ms {
+            builder = builder.lease_duration(Duration::from_millis(lease));
+        }
+
+        if let Some(interval) = config.sweep_interval_ms {
+            builder = builder.sweep_strategy(match interval {
+                0 => SweepStrategy::Lazy,
+                interval => SweepStrategy::Interval(Duration::from_millis(interval)),
+            });
+        }
+
+        if let Some(count) = config.keep_last {
+            builder = builder.retention(RetentionPolicy::KeepLastN(count));
+        }
+
+        if let Some(timeout) = config.drain_timeout_ms {
+            builder = builder.drain_timeout(Duration::from_millis(timeout));
+        }
+
+        if let Some(window) = config.idempotency_window_ms {
+            builder = builder.idempotency_window(Duration::from_millis(window));
+        }
+
+        if let Some(retention) = config.soft_delete_ms {
+            builder = builder.soft_delete(Duration::from_millis(retention));
+        }
+
+        match (
+            config.max_attempts,
+            config.retry_backoff_ms,
+            config.retry_max_backoff_ms,
+        ) {
+            (Some(max_attempts), initial, max) => {
+                let policy = RetryPolicy::new(max_attempts);
+
+                builder = builder.retry_policy(policy.backoff(
+                    initial.map_or(policy.initial_backoff, Duration::from_millis),
+                    max.map_or(policy.max_backoff, Duration::from_millis),
+                ));
+            }
+            (None, None, None) => {}
+            (None, _, _) => {
+                return Err(api_err!(Error::InvalidConfig(
+                    "retry_backoff_ms and retry_max_backoff_ms require max_attempts".to_owned()
+                )))
+            }
+        }
+
+        if let Some(timeout) = config.default_timeout_ms {
+            builder = builder.default_timeout(Duration::from_millis(timeout));
+        }
+
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{Context, Routines};
+
+    fn vars(vars: &[(&str, &str)]) -> impl Iterator<Item = (String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn from_vars() {
+        let config = JobQueueConfig::from_vars(
+            "CS_JOBS_",
+            vars(&[
+                ("CS_JOBS_MAX_IN_FLIGHT", "32"),
+                ("CS_JOBS_NAMESPACE", "billing"),
+                ("CS_JOBS_SWEEP_INTERVAL_MS", "0"),
+                ("CS_JOBS_WORKER_ID", "\"42\""),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            JobQueueConfig {
+                max_in_flight: Some(32),
+                namespace: Some("billing".to_owned()),
+                sweep_interval_ms: Some(0),
+                worker_id: Some("42".to_owned()),
+                ..Default::default()
+            }
+        );
+
+        // Typos and invalid values rejected
+        for (name, value) in [("CS_JOBS_MAX_INFLIGHT", "32"), ("CS_JOBS_KEEP_LAST", "all")] {
+            let err = JobQueueConfig::from_vars("CS_JOBS_", vars(&[(name, value)])).unwrap_err();
+
+            assert!(matches!(*err, Error::InvalidConfig(_)));
+        }
+    }
+
+    #[test]
+    fn from_config() {
+        let config = JobQueueConfig {
+            thread_pool_size: Some(0),
+            ..Default::default()
+        };
+
+        assert!(JobQueueBuilder::<Routines, Context>::from_config(config).is_err());
+
+        let config = JobQueueConfig {
+            allowed_kinds: Some(vec!["Nop".to_owned()]),
+            denied_kinds: Some(vec!["Sleep".to_owned()]),
+            ..Default::default()
+        };
+
+        assert!(JobQueueBuilder::<Routines, Context>::from_config(config).is_err());
+
+        // Backoff without retries
+        let config = JobQueueConfig {
+            retry_backoff_ms: Some(100),
+            ..Default::default()
+        };
+
+        assert!(JobQueueBuilder::<Routines, Context>::from_config(config).is_err());
+
+        let config = JobQueueConfig {
+            thread_pool_size: Some(2),
+            worker_id: Some("worker-1".to_owned()),
+            allowed_kinds: Some(vec!["Nop".to_owned()]),
+            keep_last: Some(10),
+            ..Default::default()
+        };
+
+        let jq = JobQueueBuilder::<Routines, Context>::from_config(config)
+            .unwrap()
+            .build();
+
+        assert_eq!(jq.worker_id(), "worker-1");
+        assert_eq!(jq.kinds(), &KindFilter::allow(["Nop"]));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml_str() {
+        let config = JobQueueConfig::from_toml_str(
+            r#"
+            max_in_flight = 32
+            namespace = "billing"
+            allowed_kinds = ["Nop"]
+            max_attempts = 3
+            retry_backoff_ms = 500
+            default_timeout_ms = 60000
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config,
+            JobQueueConfig {
+                max_in_flight: Some(32),
+                namespace: Some("billing".to_owned()),
+                allowed_kinds: Some(vec!["Nop".to_owned()]),
+                max_attempts: Some(3),
+                retry_backoff_ms: Some(500),
+                default_timeout_ms: Some(60_000),
+                ..Default::default()
+            }
+        );
+
+        assert!(JobQueueBuilder::<Routines, Context>::from_config(config).is_ok());
+
+        // Typos and invalid values rejected
+        for toml in [
+            "max_inflight = 32",
+            "keep_last = \"all\"",
+            "max_in_flight =",
+        ] {
+            let err = JobQueueConfig::from_toml_str(toml).unwrap_err();
+
+            assert!(matches!(*err, Error::InvalidConfig(_)));
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Context shared by the routines of a queue, either behind a mutex, read-only or behind a
+//! read-write lock so that routines only reading configuration don't contend for it.
+
+use serde::de::DeserializeOwned;
+use std::any::Any;
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::prelude::*;
+
+/// Function building the context of a queue asynchronously, on its runtime when started (see
+/// `JobQueue::set_context_factory`).
+pub type ContextFactory<C> = Arc<
+    dyn Fn() -> Pin<Box<dyn Future<Output = Result<ContextMode<C>, ApiError>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Function resolving the context passed to the routine of a job holding its own context (see
+/// `Job::with_context`), from the context of the queue (if any) and the one of the job: in place
+/// of the context of the queue, or merged with it.
+pub type ContextResolver<C> = Arc<
+    dyn Fn(Option<&ContextMode<C>>, &serde_json::Value) -> Result<ContextMode<C>, ApiError>
+        + Send
+        + Sync,
+>;
+
+/// How the context of a queue is shared by its routines.
+pub enum ContextMode<C> {
+    /// Context behind a mutex, routines having exclusive access to it (passed to
+    /// `Routine::call`).
+    Exclusive(Shared<C>),
+
+    /// Context never mutated, read concurrently by the routines without any lock.
+    ReadOnly(SyncContext<C>),
+
+    /// Context behind a read-write lock, read concurrently by the routines and mutated
+    /// exclusively.
+    ReadWrite(SyncContext<RwLock<C>>),
+}
+
+/// Context read concurrently by the routines, which must be `Sync`. The bound is checked when
+/// the context is built and hidden from its type, so that a queue whose context is only `Send`
+/// can still share it behind a mutex.
+pub struct SyncContext<T> {
+    /// Context, type-erased.
+    context: Arc<dyn Any + Send + Sync>,
+
+    /// Erases another context of the same type.
+    erase: fn(T) -> Arc<dyn Any + Send + Sync>,
+}
+
+impl<T: 'static> SyncContext<T> {
+    /// Creates a context read concurrently by the routines.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be shared.
+    ///
+    /// # Returns
+    /// An instance of `SyncContext`.
+    pub fn new(context: T) -> Self
+    where
+        T: Send + Sync,
+    {
+        Arc::new(context).into()
+    }
+
+    /// Shares another context the same way.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be shared.
+    ///
+    /// # Returns
+    /// The context shared like this one.
+    fn share(&self, context: T) -> Self {
+        Self {
+            context: (self.erase)(context),
+            erase: self.erase,
+        }
+    }
+
+    /// Gets the context shared.
+    ///
+    /// # Returns
+    /// The context.
+    pub fn into_inner(self) -> Arc<T>
+    where
+        T: Send + Sync,
+    {
+        match self.context.downcast() {
+            Ok(context) => context,
+            Err(_) => unreachable!("Context erased from another type"),
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> From<Arc<T>> for SyncContext<T> {
+    fn from(context: Arc<T>) -> Self {
+        Self {
+            context,
+            erase: |context| Arc::new(context),
+        }
+    }
+}
+
+impl<T: 'static> Deref for SyncContext<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self.context.downcast_ref() {
+            Some(context) => context,
+            None => unreachable!("Context erased from another type"),
+        }
+    }
+}
+
+impl<T> Clone for SyncContext<T> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            erase: self.erase,
+        }
+    }
+}
+
+impl<C> ContextMode<C> {
+    /// Creates a context shared behind a mutex.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be shared.
+    ///
+    /// # Returns
+    /// An instance of `ContextMode::Exclusive`.
+    pub fn exclusive(context: C) -> Self {
+        Self::Exclusive(Arc::new(Mutex::new(context)))
+    }
+
+    /// Creates a context shared read-only.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be shared.
+    ///
+    /// # Returns
+    /// An instance of `ContextMode::ReadOnly`.
+    pub fn read_only(context: C) -> Self
+    where
+        C: Send + Sync + 'static,
+    {
+        Self::ReadOnly(SyncContext::new(context))
+    }
+
+    /// Creates a context shared behind a read-write lock.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be shared.
+    ///
+    /// # Returns
+    /// An instance of `ContextMode::ReadWrite`.
+    pub fn read_write(context: C) -> Self
+    where
+        C: Send + Sync + 'static,
+    {
+        Self::ReadWrite(SyncContext::new(RwLock::new(context)))
+    }
+
+    /// Shares another context in the same mode.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be shared.
+    ///
+    /// # Returns
+    /// The context shared like this one.
+    pub fn same_mode(&self, context: C) -> Self
+    where
+        C: 'static,
+    {
+        match self {
+            Self::Exclusive(_) => Self::exclusive(context),
+            Self::ReadOnly(shared) => Self::ReadOnly(shared.share(context)),
+            Self::ReadWrite(shared) => Self::ReadWrite(shared.share(RwLock::new(context))),
+        }
+    }
+
+    /// Gets the context shared behind a mutex.
+    ///
+    /// # Returns
+    /// The context, `None` if shared in another mode.
+    pub fn into_exclusive(self) -> Option<Shared<C>> {
+        match self {
+            Self::Exclusive(context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Gets the context shared read-only.
+    ///
+    /// # Returns
+    /// The context, `None` if shared in another mode.
+    pub fn into_read_only(self) -> Option<Arc<C>>
+    where
+        C: Send + Sync + 'static,
+    {
+        match self {
+            Self::ReadOnly(context) => Some(context.into_inner()),
+            _ => None,
+        }
+    }
+
+    /// Gets the context shared behind a read-write lock.
+    ///
+    /// # Returns
+    /// The context, `None` if shared in another mode.
+    pub fn into_read_write(self) -> Option<Arc<RwLock<C>>>
+    where
+        C: Send + Sync + 'static,
+    {
+        match self {
+            Self::ReadWrite(context) => Some(context.into_inner()),
+            _ => None,
+        }
+    }
+}
+
+impl<C> From<C> for ContextMode<C> {
+    fn from(context: C) -> Self {
+        Self::exclusive(context)
+    }
+}
+
+impl<C> Clone for ContextMode<C> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Exclusive(context) => Self::Exclusive(context.clone()),
+            Self::ReadOnly(context) => Self::ReadOnly(context.clone()),
+            Self::ReadWrite(context) => Self::ReadWrite(context.clone()),
+        }
+    }
+}
+
+impl<C> std::fmt::Debug for ContextMode<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exclusive(_) => f.write_str("Exclusive"),
+            Self::ReadOnly(_) => f.write_str("ReadOnly"),
+            Self::ReadWrite(_) => f.write_str("ReadWrite"),
+        }
+    }
+}
+
+/// Context resolver passing the context of a job in place of the context of the queue,
+/// deserialized and shared in the same mode (behind a mutex if the queue has no context).
+///
+/// # Arguments
+/// * `global` - Context of the queue.
+/// * `context` - Context of the job.
+///
+/// # Returns
+/// The context of the job.
+///
+/// # Errors
+/// One of `Error` enum if the context of the job doesn't deserialize.
+pub fn replace_context<C: DeserializeOwned + 'static>(
+    global: Option<&ContextMode<C>>,
+    context: &serde_json::Value,
+) -> Result<ContextMode<C>, ApiError> {
+    let context = C::deserialize(context).map_err(|e| api_err!(e.into()))?;
+
+    Ok(match global {
+        Some(global) => global.same_mode(context),
+        None => ContextMode::exclusive(context),
+    })
+}
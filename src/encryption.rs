@@ -0,0 +1,223 @@
+//! Encryption at rest of the private data and results of the job records stored by the
+//! backends (see `Job::encode_with`).
+//!
+//! Entries are encrypted with the active key of the `Encryption` policy and record its ID, so
+//! that keys can be rotated: records encrypted with a retired key stay readable as long as the
+//! key is kept in the policy. Ciphers are plugged by implementing `Encryptor` on top of their
+//! crate, AES-256-GCM being provided with the `aes-gcm` feature (see `AesGcmEncryptor`).
+
+#[cfg(feature = "aes-gcm")]
+use aes_gcm::aead::{Aead, KeyInit};
+#[cfg(feature = "aes-gcm")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share an encryptor.
+pub type SharedEncryptor = Arc<dyn Encryptor>;
+
+/// Trait implementing a cipher bound to a key.
+pub trait Encryptor: Send + Sync {
+    /// Encrypts bytes.
+    ///
+    /// # Arguments
+    /// * `plaintext` - Bytes to be encrypted.
+    ///
+    /// # Returns
+    /// The bytes encrypted (including the nonce, if any).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, ApiError>;
+
+    /// Decrypts bytes.
+    ///
+    /// # Arguments
+    /// * `ciphertext` - Bytes encrypted.
+    ///
+    /// # Returns
+    /// The bytes decrypted.
+    ///
+    /// # Errors
+    /// One of `Error` enum, e.g. if the bytes have been tampered with.
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ApiError>;
+}
+
+/// Entry of a record encrypted.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Sealed {
+    /// ID of the key used.
+    pub key_id: String,
+
+    /// Name of the compressor used before encrypting, if compressed.
+    #[serde(default)]
+    pub compressor: Option<String>,
+
+    /// Bytes encrypted.
+    pub data: Vec<u8>,
+}
+
+/// Policy encrypting the private data and results of the job records.
+#[derive(Clone, Default)]
+pub struct Encryption {
+    /// ID of the key encrypting the new entries (disabled if `None`).
+    active: Option<String>,
+
+    /// Keys able to decrypt the entries, by ID.
+    keys: HashMap<String, SharedEncryptor>,
+}
+
+impl Encryption {
+    /// Creates a policy encrypting the entries with a key.
+    ///
+    /// # Arguments
+    /// * `key_id` - ID of the key, recorded with every entry.
+    /// * `encryptor` - Cipher bound to the key.
+    ///
+    /// # Returns
+    /// An instance of `Encryption`.
+    pub fn new(key_id: impl Into<String>, encryptor: impl Encryptor + 'static) -> Self {
+        let key_id = key_id.into();
+
+        Self {
+            keys: HashMap::from([(key_id.clone(), Arc::new(encryptor) as SharedEncryptor)]),
+            active: Some(key_id),
+        }
+    }
+
+    /// Adds a key only used to decrypt entries (e.g. a retired key after a rotation).
+    ///
+    /// # Arguments
+    /// * `key_id` - ID of the key.
+    /// * `encryptor` - Cipher bound to the key.
+    ///
+    /// # Returns
+    /// The policy.
+    pub fn key(mut self, key_id: impl Into<String>, encryptor: impl Encryptor + 'static) -> Self {
+        self.keys.insert(key_id.into(), Arc::new(encryptor));
+
+        self
+    }
+
+    /// Checks if the new entries are encrypted.
+    ///
+    /// # Returns
+    /// `true` if a key is active, `false` otherwise.
+    pub fn is_enabled(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Encrypts an entry with the active key.
+    ///
+    /// # Arguments
+    /// * `bytes` - Entry to be encrypted.
+    /// * `compressor` - Name of the compressor used on the entry, if compressed.
+    ///
+    /// # Returns
+    /// The entry encrypted, `None` if encryption is disabled.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn seal(
+        &self,
+        bytes: &[u8],
+        compressor: Option<String>,
+    ) -> Result<Option<Sealed>, ApiError> {
+        let Some(key_id) = &self.active else {
+            return Ok(None);
+        };
+
+        Ok(Some(Sealed {
+            key_id: key_id.to_owned(),
+            compressor,
+            data: self.encryptor(key_id)?.encrypt(bytes)?,
+        }))
+    }
+
+    /// Decrypts an entry with the key recorded.
+    ///
+    /// # Arguments
+    /// * `sealed` - Entry encrypted.
+    ///
+    /// # Returns
+    /// The entry decrypted (still compressed if a compressor is recorded).
+    ///
+    /// # Errors
+    /// `Error::UnknownEncryptionKey` if the key is not known by the policy.
+    pub fn open(&self, sealed: &Sealed) -> Result<Vec<u8>, ApiError> {
+        self.encryptor(&sealed.key_id)?.decrypt(&sealed.data)
+    }
+
+    /// Gets the cipher bound to a key.
+    ///
+    /// # Arguments
+    /// * `key_id` - ID of the key.
+    ///
+    /// # Returns
+    /// The cipher.
+    ///
+    /// # Errors
+    /// `Error::UnknownEncryptionKey` if the key is not known by the policy.
+    fn encryptor(&self, key_id: &str) -> Result<&SharedEncryptor, ApiError> {
+        self.keys
+            .get(key_id)
+            .ok_or(api_err!(Error::UnknownEncryptionKey(key_id.to_owned())))
+    }
+}
+
+/// Size of the nonce prepended to the entries encrypted with AES-GCM.
+#[cfg(feature = "aes-gcm")]
+const NONCE_SIZE: usize = 12;
+
+/// AES-256-GCM cipher: every entry is encrypted with a random nonce, prepended to it, and
+/// authenticated so that tampered entries fail to decrypt.
+#[cfg(feature = "aes-gcm")]
+#[derive(Clone)]
+pub struct AesGcmEncryptor {
+    /// Cipher bound to the key.
+    cipher: aes_gcm::Aes256Gcm,
+}
+
+#[cfg(feature = "aes-gcm")]
+impl AesGcmEncryptor {
+    /// Creates a cipher bound to a key.
+    ///
+    /// # Arguments
+    /// * `key` - Key of 256 bits.
+    ///
+    /// # Returns
+    /// An instance of `AesGcmEncryptor`.
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: aes_gcm::Aes256Gcm::new(key.into()),
+        }
+    }
+}
+
+#[cfg(feature = "aes-gcm")]
+impl Encryptor for AesGcmEncryptor {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, ApiError> {
+        let nonce = rand::thread_rng().gen::<[u8; NONCE_SIZE]>();
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce.into(), plaintext)
+            .map_err(|e| api_err!(Error::Custom(format!("Cannot encrypt entry ({e})"))))?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ApiError> {
+        if ciphertext.len() < NONCE_SIZE {
+            return Err(api_err!(Error::Decryption("missing nonce".to_owned())));
+        }
+
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_SIZE);
+
+        self.cipher
+            .decrypt(nonce.into(), ciphertext)
+            .map_err(|e| api_err!(Error::Decryption(e.to_string())))
+    }
+}
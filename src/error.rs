@@ -18,24 +18,64 @@ pub type ApiError = Box<Error>;
 pub enum Error {
     #[error("Queue is already running")]
     AlreadyRunning,
+    #[error("Cannot access backend ({0})")]
+    CannotAccessBackend(String),
+    #[error("Cannot access deleted jobs ({0})")]
+    CannotAccessDeletedJobs(String),
+    #[error("Cannot access dispatcher ({0})")]
+    CannotAccessDispatcher(String),
     #[error("Cannot access error handler ({0})")]
     CannotAccessErrorHandler(String),
     #[error("Cannot access expirations list ({0})")]
     CannotAccessExpirations(String),
+    #[error("Cannot access idempotency cache ({0})")]
+    CannotAccessIdempotencyCache(String),
     #[error("Cannot access receiver ({0})")]
     CannotAccessReceiver(String),
+    #[error("Cannot access running jobs ({0})")]
+    CannotAccessRunningJobs(String),
     #[error("Cannot access runtime ({0})")]
     CannotAccessRuntime(String),
     #[error("Cannot access sender ({0})")]
     CannotAccessSender(String),
+    #[error("Cannot access staged jobs ({0})")]
+    CannotAccessStagedJobs(String),
+    #[error("Cannot access subscribers ({0})")]
+    CannotAccessSubscribers(String),
+    #[error("Cannot access throughput tracker ({0})")]
+    CannotAccessThroughput(String),
+    #[error("Cannot access undecodable jobs ({0})")]
+    CannotAccessUndecodableJobs(String),
     #[error("Cannot join the queue thread")]
     CannotJoinThread,
     #[error("Cannot send message to the queue ({0})")]
     CannotSendMessage(String),
+    #[error("Capacity of the backend exceeded with no finished job left to evict ({0})")]
+    CapacityExceeded(String),
+    #[error("Circuit breaker of the backend open after repeated failures")]
+    CircuitOpen,
     #[error("{0}")]
     Custom(String),
+    #[error("Deadline of the job exceeded")]
+    DeadlineExceeded,
+    #[error("Cannot decrypt entry ({0})")]
+    Decryption(String),
+    #[error("Job with id {0} already exists")]
+    DuplicateJobId(Uuid),
+    #[error("Event log of the queue is disabled")]
+    EventLogDisabled,
+    #[error("Events are evicted from the log, the oldest retained being {0}")]
+    EventsEvicted(u64),
+    #[error("Frame of more than {0} bytes received")]
+    FrameTooLarge(usize),
     #[error(transparent)]
     GenericError(#[from] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Invalid configuration ({0})")]
+    InvalidConfig(String),
+    #[error("Invalid cursor ({0})")]
+    InvalidCursor(String),
+    #[error("Invalid trace context ({0})")]
+    InvalidTraceContext(String),
     #[error("Invalid job status")]
     InvalidJobStatus,
     #[error("Invalid job status transition: {0:?}")]
@@ -44,16 +84,30 @@ pub enum Error {
     InvalidThreadPoolSize,
     #[error(transparent)]
     IO(#[from] std::io::Error),
+    #[error("The job has been cancelled")]
+    JobCancelled,
     #[error("The job cannot be removed as it's not finished")]
     JobNotFinished,
     #[error("Job with id {0} is not found")]
     JobNotFound(Uuid),
+    #[error("Job with id {0} is not running")]
+    JobNotRunning(Uuid),
+    #[error("Job rejected ({0})")]
+    JobRejected(String),
     #[error(transparent)]
     Join(#[from] tokio::task::JoinError),
     #[error(transparent)]
     JsonSerialization(#[from] serde_json::Error),
+    #[error("Kind of job {0} is not executed by the worker")]
+    KindNotAccepted(String),
+    #[error("Capabilities {0:?} required by the job are not provided by the worker")]
+    MissingCapabilities(Vec<String>),
+    #[error("Routine of job {0} is a closure only runnable once, in the process creating it")]
+    LocalRoutine(Uuid),
     #[error(transparent)]
     MessageSend(#[from] std::sync::mpsc::SendError<Message>),
+    #[error("Lease of job {0} lost, it may be claimed by another worker")]
+    LeaseLost(Uuid),
     #[error("Missing channel for communicating with thread")]
     MissingChannel,
     #[error("Missing thread's join handle")]
@@ -62,14 +116,40 @@ pub enum Error {
     MissingNotificationHandler,
     #[error("Missing private data in job")]
     MissingPrivateData,
+    #[error("Missing private data entry {0} in job")]
+    MissingData(String),
     #[error("Queue is not started")]
     NotStarted,
     #[error("Queue is not stopping")]
     NotStopping,
     #[error("Progression overflow")]
     ProgressionOverflow,
+    #[error("Queue is full")]
+    QueueFull,
+    #[error("Quota of tenant {0} exceeded")]
+    QuotaExceeded(String),
+    #[error("Remote queue error: {0}")]
+    Remote(String),
+    #[error("Result of the job with id {0} has expired")]
+    ResultExpired(Uuid),
+    #[error("Routine {0} is already registered")]
+    RoutineAlreadyRegistered(String),
+    #[error("Routine {0} is not registered, the job is kept pending")]
+    RoutineNotRegistered(String),
     #[error("Queue is stopped")]
     Stopped,
     #[error("Error during waiting for timeout ({0})")]
     Timeout(String),
+    #[error("Missing or invalid auth token")]
+    Unauthenticated,
+    #[error("Unknown compressor {0}")]
+    UnknownCompressor(String),
+    #[error("Unknown encryption key {0}")]
+    UnknownEncryptionKey(String),
+    #[error("Job of kind {0} requires private data of type {1}")]
+    UnmetPrivateDataRequirement(String, String),
+    #[error("Unsupported wire format version {0}")]
+    UnsupportedWireVersion(u32),
+    #[error("Cannot deliver notification to webhook {0} ({1})")]
+    WebhookDelivery(String, String),
 }
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::SystemTime;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::prelude::*;
+
+/// Change in the life of a job, as recorded by the event log.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum JobEvent {
+    /// The job has been enqueued.
+    Enqueued,
+
+    /// The job has started (or resumed) running.
+    Started,
+
+    /// The job has reported its progression.
+    Progress(Progression),
+
+    /// The job has finished with the given result.
+    Finished(ResultStatus),
+
+    /// The job has been reaped because it has expired.
+    Expired,
+}
+
+/// Entry of the event log of a queue.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Event {
+    /// Sequence number, increasing monotonically with every event of the queue.
+    pub seq: u64,
+
+    /// ID of the job concerned.
+    pub job_id: Uuid,
+
+    /// Timestamp at which the event has been recorded.
+    pub timestamp: SystemTime,
+
+    /// Change in the life of the job.
+    pub event: JobEvent,
+}
+
+/// Append-only log of the events of the jobs of a queue, replayable from any sequence number
+/// still retained, so that external systems sync their state without missing a change.
+#[derive(Debug, Default)]
+pub(crate) struct EventLog {
+    /// Maximum number of events retained (the log is disabled if `None`).
+    capacity: Option<usize>,
+
+    /// Sequence number of the last event recorded.
+    last_seq: u64,
+
+    /// Events retained, the oldest first.
+    events: VecDeque<Event>,
+
+    /// Streams following the log.
+    streams: Vec<UnboundedSender<Event>>,
+}
+
+impl EventLog {
+    /// Enables the log.
+    ///
+    /// # Arguments
+    /// * `capacity` - Maximum number of events retained, the oldest ones being evicted.
+    pub(crate) fn enable(&mut self, capacity: usize) {
+        self.capacity = Some(capacity.max(1));
+    }
+
+    /// Records the event of a job.
+    ///
+    /// # Arguments
+    /// * `job_id` - ID of the job.
+    /// * `event` - Change in the life of the job.
+    pub(crate) fn record(&mut self, job_id: Uuid, event: JobEvent) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+
+        self.last_seq += 1;
+
+        let event = Event {
+            seq: self.last_seq,
+            job_id,
+            timestamp: SystemTime::now(),
+            event,
+        };
+
+        if self.events.len() >= capacity {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(event);
+
+        // Streams whose receiver has been dropped closed
+        self.streams.retain(|stream| stream.send(event).is_ok());
+    }
+
+    /// Records the event matching a notification, if any.
+    ///
+    /// # Arguments
+    /// * `notification` - Notification of the queue.
+    pub(crate) fn record_notification(&mut self, notification: &Notification) {
+        let (job_id, event) = match notification {
+            Notification::Status(id, Status::Running) => (id, JobEvent::Started),
+            Notification::Status(id, Status::Finished(result)) => (id, JobEvent::Finished(*result)),
+            Notification::Progression(id, progression) => (id, JobEvent::Progress(*progression)),
+            Notification::JobExpired(id) => (id, JobEvent::Expired),
+            _ => return,
+        };
+
+        self.record(*job_id, event);
+    }
+
+    /// Gets the events recorded after a sequence number.
+    ///
+    /// # Arguments
+    /// * `seq` - Sequence number of the last event known (0 to get every event).
+    ///
+    /// # Returns
+    /// The events, in order.
+    ///
+    /// # Errors
+    /// `Error::EventLogDisabled` if disabled, `Error::EventsEvicted` if events after the
+    /// sequence number are no longer retained.
+    pub(crate) fn since(&self, seq: u64) -> Result<Vec<Event>, ApiError> {
+        if self.capacity.is_none() {
+            return Err(api_err!(Error::EventLogDisabled));
+        }
+
+        if let Some(oldest) = self.events.front() {
+            if seq + 1 < oldest.seq {
+                return Err(api_err!(Error::EventsEvicted(oldest.seq)));
+            }
+        }
+
+        Ok(self
+            .events
+            .iter()
+            .filter(|event| event.seq > seq)
+            .copied()
+            .collect())
+    }
+
+    /// Follows the log: the events recorded after a sequence number are replayed, then the
+    /// new ones are streamed as recorded.
+    ///
+    /// # Arguments
+    /// * `seq` - Sequence number of the last event known (0 to get every event).
+    ///
+    /// # Returns
+    /// The receiving end of the stream.
+    ///
+    /// # Errors
+    /// Same as `since`.
+    pub(crate) fn stream(&mut self, seq: u64) -> Result<UnboundedReceiver<Event>, ApiError> {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        for event in self.since(seq)? {
+            let _ = tx.send(event);
+        }
+
+        self.streams.push(tx);
+
+        Ok(rx)
+    }
+}
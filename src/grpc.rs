@@ -0,0 +1,458 @@
+//! gRPC server of the control plane of a job queue, generated from `proto/cs_jobs.proto` and
+//! mapping its messages to the `JobQueueService` of the queue.
+
+use std::pin::Pin;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response};
+
+use crate::prelude::*;
+use proto::job_queue_server::JobQueueServer;
+
+/// Messages, server and client generated from `proto/cs_jobs.proto`.
+pub mod proto {
+    tonic::include_proto!("cs_jobs.v1");
+}
+
+/// Metadata holding the auth token of the client, as `Bearer <token>`.
+pub const AUTHORIZATION_HEADER: &str = "authorization";
+
+/// Stream of the updates of a job, as sent by `StreamProgress`.
+type ProgressStream =
+    Pin<Box<dyn Stream<Item = Result<proto::ProgressUpdate, tonic::Status>> + Send>>;
+
+/// gRPC server of the control plane of a started job queue. Every RPC must carry the auth
+/// token of the service (see `JobQueueService::token`) in its `authorization` metadata.
+pub struct JobQueueGrpcServer<RoutineType, Context> {
+    /// Control plane served.
+    service: JobQueueService<RoutineType, Context>,
+}
+
+impl<RoutineType, Context> JobQueueGrpcServer<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates the gRPC server of a control plane.
+    ///
+    /// # Arguments
+    /// * `service` - Control plane to be served.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueGrpcServer`.
+    pub fn new(service: JobQueueService<RoutineType, Context>) -> Self {
+        Self { service }
+    }
+
+    /// Wraps the server into a tonic service, to be added to a router next to other services.
+    ///
+    /// # Returns
+    /// The tonic service.
+    pub fn into_service(self) -> JobQueueServer<Self> {
+        JobQueueServer::new(self)
+    }
+
+    /// Checks the auth token carried by a RPC.
+    ///
+    /// # Arguments
+    /// * `request` - Request of the RPC.
+    ///
+    /// # Errors
+    /// `UNAUTHENTICATED` if the token is missing or invalid.
+    fn authenticate<T>(&self, request: &Request<T>) -> Result<(), tonic::Status> {
+        let token = request
+            .metadata()
+            .get(AUTHORIZATION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+
+        self.service.authenticate(token).map_err(to_status)
+    }
+
+    /// Serves the control plane over gRPC until the listener fails.
+    ///
+    /// # Arguments
+    /// * `listener` - Listener accepting the connections of the clients (see
+    ///   `JobQueueService::bind`).
+    ///
+    /// # Errors
+    /// `Error::InvalidConfig` if no auth token is set, one of `Error` enum otherwise.
+    pub async fn serve(self, listener: TcpListener) -> Result<(), ApiError> {
+        if !self.service.has_token() {
+            return Err(api_err!(Error::InvalidConfig(
+                "An auth token is required to serve the control plane".to_owned()
+            )));
+        }
+
+        tonic::transport::Server::builder()
+            .add_service(self.into_service())
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .map_err(|e| api_err!(Error::Custom(e.to_string())))
+    }
+}
+
+#[tonic::async_trait]
+impl<RoutineType, Context> proto::job_queue_server::JobQueue
+    for JobQueueGrpcServer<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    async fn enqueue(
+        &self,
+        request: Request<proto::EnqueueRequest>,
+    ) -> Result<Response<proto::EnqueueResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let id = self
+            .service
+            .enqueue(&request.into_inner().job)
+            .await
+            .map_err(to_status)?;
+
+        Ok(Response::new(proto::EnqueueResponse { id: id.to_string() }))
+    }
+
+    async fn get_status(
+        &self,
+        request: Request<proto::GetStatusRequest>,
+    ) -> Result<Response<proto::GetStatusResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let id = parse_id(&request.into_inner().id)?;
+        let status = self.service.get_status(&id).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::GetStatusResponse {
+            status: proto::Status::from(status).into(),
+        }))
+    }
+
+    async fn get_result(
+        &self,
+        request: Request<proto::GetResultRequest>,
+    ) -> Result<Response<proto::GetResultResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let id = parse_id(&request.into_inner().id)?;
+        let result = self.service.get_result(&id).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::GetResultResponse { result }))
+    }
+
+    type StreamProgressStream = ProgressStream;
+
+    async fn stream_progress(
+        &self,
+        request: Request<proto::StreamProgressRequest>,
+    ) -> Result<Response<Self::StreamProgressStream>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let id = parse_id(&request.into_inner().id)?;
+        let updates = self.service.stream_progress(&id).map_err(to_status)?;
+
+        let stream = ReceiverStream::new(updates)
+            .map(|update| update.map(proto::ProgressUpdate::from).map_err(to_status));
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn cancel(
+        &self,
+        request: Request<proto::CancelRequest>,
+    ) -> Result<Response<proto::CancelResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let id = parse_id(&request.into_inner().id)?;
+
+        self.service.cancel(&id).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::CancelResponse {}))
+    }
+
+    async fn list_jobs(
+        &self,
+        request: Request<proto::ListJobsRequest>,
+    ) -> Result<Response<proto::ListJobsResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let jobs = self.service.list_jobs().await.map_err(to_status)?;
+
+        Ok(Response::new(proto::ListJobsResponse {
+            jobs: jobs.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn retry(
+        &self,
+        request: Request<proto::RetryRequest>,
+    ) -> Result<Response<proto::RetryResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let id = parse_id(&request.into_inner().id)?;
+        let id = self.service.retry(&id).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::RetryResponse { id: id.to_string() }))
+    }
+
+    async fn purge(
+        &self,
+        request: Request<proto::PurgeRequest>,
+    ) -> Result<Response<proto::PurgeResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let filter = PurgeFilter::from(request.into_inner());
+        let ids = self.service.purge(filter).await.map_err(to_status)?;
+
+        Ok(Response::new(proto::PurgeResponse {
+            ids: ids.iter().map(ToString::to_string).collect(),
+        }))
+    }
+
+    async fn stats(
+        &self,
+        request: Request<proto::StatsRequest>,
+    ) -> Result<Response<proto::StatsResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        let stats = self.service.stats().await.map_err(to_status)?;
+
+        Ok(Response::new(stats.into()))
+    }
+
+    async fn pause(
+        &self,
+        request: Request<proto::PauseRequest>,
+    ) -> Result<Response<proto::PauseResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        self.service.pause().await.map_err(to_status)?;
+
+        Ok(Response::new(proto::PauseResponse {}))
+    }
+
+    async fn resume(
+        &self,
+        request: Request<proto::ResumeRequest>,
+    ) -> Result<Response<proto::ResumeResponse>, tonic::Status> {
+        self.authenticate(&request)?;
+
+        self.service.resume().await.map_err(to_status)?;
+
+        Ok(Response::new(proto::ResumeResponse {}))
+    }
+}
+
+/// Parses the ID of a job received.
+///
+/// # Arguments
+/// * `id` - ID received.
+///
+/// # Returns
+/// The ID parsed.
+///
+/// # Errors
+/// `INVALID_ARGUMENT` if the ID is not a UUID.
+pub(crate) fn parse_id(id: &str) -> Result<Uuid, tonic::Status> {
+    Uuid::parse_str(id).map_err(|e| tonic::Status::invalid_argument(e.to_string()))
+}
+
+/// Converts an error raised by the queue to the status of a gRPC response.
+///
+/// # Arguments
+/// * `error` - Error raised.
+///
+/// # Returns
+/// The status with the code matching the error.
+pub(crate) fn to_status(error: ApiError) -> tonic::Status {
+    let message = error.to_string();
+
+    match *error {
+        Error::JobNotFound(_) | Error::ResultExpired(_) => tonic::Status::not_found(message),
+        Error::JsonSerialization(_)
+        | Error::UnsupportedWireVersion(_)
+        | Error::JobRejected(_)
+        | Error::LocalRoutine(_) => tonic::Status::invalid_argument(message),
+        Error::JobNotFinished
+        | Error::InvalidJobStatus
+        | Error::InvalidJobStatusTransition(_)
+        | Error::NotStarted
+        | Error::Stopped => tonic::Status::failed_precondition(message),
+        Error::QueueFull | Error::QuotaExceeded(_) => tonic::Status::resource_exhausted(message),
+        Error::CircuitOpen => tonic::Status::unavailable(message),
+        Error::Unauthenticated => tonic::Status::unauthenticated(message),
+        _ => tonic::Status::internal(message),
+    }
+}
+
+/// Converts a timestamp to milliseconds since the Unix epoch.
+///
+/// # Arguments
+/// * `time` - Timestamp to convert.
+///
+/// # Returns
+/// The number of milliseconds.
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl From<Status> for proto::Status {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::NotReady => Self::NotReady,
+            Status::Ready => Self::Ready,
+            Status::Running => Self::Running,
+            Status::Finished(ResultStatus::Success) => Self::FinishedSuccess,
+            Status::Finished(ResultStatus::Error) => Self::FinishedError,
+            Status::Finished(ResultStatus::Interrupted) => Self::FinishedInterrupted,
+            Status::Finished(ResultStatus::Cancelled) => Self::FinishedCancelled,
+            Status::Finished(ResultStatus::TimedOut) => Self::FinishedTimedOut,
+            Status::Finished(ResultStatus::DeadlineExceeded) => Self::FinishedDeadlineExceeded,
+            Status::Removed => Self::Removed,
+            Status::Suspended => Self::Suspended,
+            Status::Retrying => Self::Retrying,
+        }
+    }
+}
+
+impl From<proto::Status> for Status {
+    fn from(status: proto::Status) -> Self {
+        match status {
+            proto::Status::NotReady => Self::NotReady,
+            proto::Status::Ready => Self::Ready,
+            proto::Status::Running => Self::Running,
+            proto::Status::FinishedSuccess => Self::Finished(ResultStatus::Success),
+            proto::Status::FinishedError => Self::Finished(ResultStatus::Error),
+            proto::Status::FinishedInterrupted => Self::Finished(ResultStatus::Interrupted),
+            proto::Status::FinishedCancelled => Self::Finished(ResultStatus::Cancelled),
+            proto::Status::FinishedTimedOut => Self::Finished(ResultStatus::TimedOut),
+            proto::Status::FinishedDeadlineExceeded => {
+                Self::Finished(ResultStatus::DeadlineExceeded)
+            }
+            proto::Status::Removed => Self::Removed,
+            proto::Status::Suspended => Self::Suspended,
+            proto::Status::Retrying => Self::Retrying,
+        }
+    }
+}
+
+impl From<Progression> for proto::Progression {
+    fn from(progression: Progression) -> Self {
+        Self {
+            step: progression.step,
+            steps: progression.steps,
+            eta_ms: progression.eta.map(to_millis),
+        }
+    }
+}
+
+impl From<proto::Progression> for Progression {
+    fn from(progression: proto::Progression) -> Self {
+        Self {
+            step: progression.step,
+            steps: progression.steps,
+            eta: progression
+                .eta_ms
+                .map(|eta| UNIX_EPOCH + Duration::from_millis(eta)),
+        }
+    }
+}
+
+impl From<ProgressUpdate> for proto::ProgressUpdate {
+    fn from(update: ProgressUpdate) -> Self {
+        Self {
+            id: update.id.to_string(),
+            status: proto::Status::from(update.status).into(),
+            progression: Some(update.progression.into()),
+        }
+    }
+}
+
+impl From<JobSummary> for proto::JobSummary {
+    fn from(summary: JobSummary) -> Self {
+        Self {
+            id: summary.id.to_string(),
+            kind: summary.kind,
+            status: proto::Status::from(summary.status).into(),
+            progression: Some(summary.progression.into()),
+            tags: summary.tags,
+        }
+    }
+}
+
+impl TryFrom<proto::JobSummary> for JobSummary {
+    type Error = tonic::Status;
+
+    fn try_from(summary: proto::JobSummary) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: parse_id(&summary.id)?,
+            status: summary.status().into(),
+            kind: summary.kind,
+            progression: summary.progression.unwrap_or_default().into(),
+            tags: summary.tags,
+        })
+    }
+}
+
+impl From<PurgeFilter> for proto::PurgeRequest {
+    fn from(filter: PurgeFilter) -> Self {
+        Self {
+            success: filter.success,
+            error: filter.error,
+            expired: filter.expired,
+            older_than_secs: filter.older_than.map(|older_than| older_than.as_secs()),
+            tag: filter.tag,
+            limit: filter.limit.map(|limit| limit as u64),
+        }
+    }
+}
+
+impl From<proto::PurgeRequest> for PurgeFilter {
+    fn from(request: proto::PurgeRequest) -> Self {
+        Self {
+            success: request.success,
+            error: request.error,
+            expired: request.expired,
+            older_than: request.older_than_secs.map(Duration::from_secs),
+            tag: request.tag,
+            limit: request.limit.map(|limit| limit as usize),
+        }
+    }
+}
+
+impl From<QueueStats> for proto::StatsResponse {
+    fn from(stats: QueueStats) -> Self {
+        Self {
+            not_ready: stats.not_ready as u64,
+            ready: stats.ready as u64,
+            running: stats.running as u64,
+            succeeded: stats.succeeded as u64,
+            failed: stats.failed as u64,
+            drain_time_ms: stats
+                .drain_time
+                .map(|drain_time| drain_time.as_millis() as u64),
+            retrying: stats.retrying as u64,
+            suspended: stats.suspended as u64,
+        }
+    }
+}
+
+impl From<proto::StatsResponse> for QueueStats {
+    fn from(stats: proto::StatsResponse) -> Self {
+        Self {
+            not_ready: stats.not_ready as usize,
+            ready: stats.ready as usize,
+            running: stats.running as usize,
+            suspended: stats.suspended as usize,
+            retrying: stats.retrying as usize,
+            succeeded: stats.succeeded as usize,
+            failed: stats.failed as usize,
+            drain_time: stats.drain_time_ms.map(Duration::from_millis),
+        }
+    }
+}
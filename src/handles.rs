@@ -0,0 +1,259 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::prelude::*;
+
+/// Handle on a job queue exposing only its read APIs, see `ReadOnlyQueue`.
+pub type ReaderHandle<RoutineType, Context> = ReadOnlyQueue<RoutineType, Context>;
+
+/// Handle on a job queue exposing only the enqueuing of jobs, safe to be handed to request
+/// handlers: the jobs can neither be inspected nor cancelled or removed through it.
+pub struct ProducerHandle<RoutineType, Context> {
+    /// Queue fed.
+    queue: Arc<JobQueue<RoutineType, Context>>,
+}
+
+impl<RoutineType, Context> Clone for ProducerHandle<RoutineType, Context> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<RoutineType, Context> From<Arc<JobQueue<RoutineType, Context>>>
+    for ProducerHandle<RoutineType, Context>
+{
+    fn from(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self { queue }
+    }
+}
+
+impl<RoutineType, Context> ProducerHandle<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates a producer handle on a queue.
+    ///
+    /// # Arguments
+    /// * `queue` - Queue to be fed.
+    ///
+    /// # Returns
+    /// An instance of `ProducerHandle`.
+    pub fn new(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self { queue }
+    }
+
+    /// Push a new job to be processed in the queue.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        self.queue.enqueue(job)
+    }
+
+    /// Push a new job to be processed in the queue, unless the queue is full.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::QueueFull` if the queue is full.
+    pub fn try_enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        self.queue.try_enqueue(job)
+    }
+
+    /// Push a new job to be processed in the queue, waiting up to a timeout for room if the
+    /// queue is full.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    /// * `timeout` - Maximum duration to wait.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::QueueFull` if the queue is still full after the timeout.
+    pub fn enqueue_timeout(&self, job: Job, timeout: Duration) -> Result<Uuid, ApiError> {
+        self.queue.enqueue_timeout(job, timeout)
+    }
+
+    /// Push a new job to be processed in the queue, unless a job holding the same idempotency
+    /// key has been submitted within the idempotency window. The cached result of a duplicate
+    /// is not exposed.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job enqueued, or of the original one for a duplicate.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn enqueue_idempotent(&self, job: Job) -> Result<Uuid, ApiError> {
+        Ok(self.queue.enqueue_idempotent(job)?.id)
+    }
+}
+
+/// Handle on a job queue exposing its destructive APIs (cancel, retry, remove, purge, pause),
+/// on top of its read APIs, to be kept by the operators of the queue.
+pub struct AdminHandle<RoutineType, Context> {
+    /// Queue administered.
+    queue: Arc<JobQueue<RoutineType, Context>>,
+
+    /// Read APIs of the queue.
+    reader: ReaderHandle<RoutineType, Context>,
+}
+
+impl<RoutineType, Context> Clone for AdminHandle<RoutineType, Context> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+            reader: self.reader.clone(),
+        }
+    }
+}
+
+impl<RoutineType, Context> From<Arc<JobQueue<RoutineType, Context>>>
+    for AdminHandle<RoutineType, Context>
+{
+    fn from(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self {
+            reader: ReadOnlyQueue::from(queue.clone()),
+            queue,
+        }
+    }
+}
+
+impl<RoutineType, Context> std::ops::Deref for AdminHandle<RoutineType, Context> {
+    type Target = ReaderHandle<RoutineType, Context>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reader
+    }
+}
+
+impl<RoutineType, Context> AdminHandle<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates an admin handle on a queue.
+    ///
+    /// # Arguments
+    /// * `queue` - Queue to be administered.
+    ///
+    /// # Returns
+    /// An instance of `AdminHandle`.
+    pub fn new(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self::from(queue)
+    }
+
+    /// Cancels a job: a pending job is never run and a running job is aborted. In both cases
+    /// the job is finished as `ResultStatus::Cancelled`. Nothing is done if the job is already
+    /// finished.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be cancelled.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn cancel_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        self.queue.cancel_job(id).await
+    }
+
+    /// Enqueues again a finished job: a new job runs the same routine with the same settings.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be retried.
+    ///
+    /// # Returns
+    /// The unique ID of the new job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn retry_job(&self, id: &Uuid) -> Result<Uuid, ApiError> {
+        self.queue.retry_job(id).await
+    }
+
+    /// Removes a finished job from the queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn remove_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        self.queue.remove_job(id).await
+    }
+
+    /// Removes a job from the queue, applying a policy if it's not finished yet.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be removed.
+    /// * `policy` - Policy applied if the job is not finished.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn remove_job_with(&self, id: &Uuid, policy: RemovePolicy) -> Result<(), ApiError> {
+        self.queue.remove_job_with(id, policy).await
+    }
+
+    /// Removes, in one pass, all the finished jobs selected by a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs to be removed.
+    ///
+    /// # Returns
+    /// The list of IDs of the removed jobs.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn purge(&self, filter: PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        self.queue.purge(filter).await
+    }
+
+    /// Pauses the queue: the jobs running keep running but no other job is dispatched until
+    /// resumed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn pause(&self) -> Result<(), ApiError> {
+        self.queue.pause().await
+    }
+
+    /// Resumes the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn resume(&self) -> Result<(), ApiError> {
+        self.queue.resume().await
+    }
+
+    /// Gets a producer handle on the same queue.
+    ///
+    /// # Returns
+    /// An instance of `ProducerHandle`.
+    pub fn producer(&self) -> ProducerHandle<RoutineType, Context> {
+        ProducerHandle::from(self.queue.clone())
+    }
+
+    /// Gets a reader handle on the same queue.
+    ///
+    /// # Returns
+    /// An instance of `ReaderHandle`.
+    pub fn reader(&self) -> ReaderHandle<RoutineType, Context> {
+        self.reader.clone()
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type of the hook called when a job starts running.
+pub type StartHook = Arc<dyn Fn(&Job) + Send + Sync>;
+
+/// Type of the hook called when a job succeeds, with its result.
+pub type SuccessHook = Arc<dyn Fn(&Job, &[u8]) + Send + Sync>;
+
+/// Type of the hook called when a job run fails (error, cancellation, timeout...), with its
+/// result status and error message.
+pub type FailureHook = Arc<dyn Fn(&Job, ResultStatus, &str) + Send + Sync>;
+
+/// Type of the hook called when a running job publishes a domain-specific signal, with the
+/// name of the command and its payload.
+pub type CommandHook = Arc<dyn Fn(&Job, &str, &[u8]) + Send + Sync>;
+
+/// Hooks called around every execution of a job, independently of the notification handler.
+#[derive(Clone, Default)]
+pub struct LifecycleHooks {
+    /// Hook called when a job starts running.
+    on_start: Option<StartHook>,
+
+    /// Hook called when a job succeeds.
+    on_success: Option<SuccessHook>,
+
+    /// Hook called when a job run fails.
+    on_failure: Option<FailureHook>,
+
+    /// Hook called when a running job publishes a domain-specific signal.
+    on_command: Option<CommandHook>,
+}
+
+impl LifecycleHooks {
+    /// Sets the hook called when a job starts running.
+    ///
+    /// # Arguments
+    /// * `hook` - Hook to set.
+    pub fn set_on_start(&mut self, hook: impl Fn(&Job) + Send + Sync + 'static) {
+        self.on_start = Some(Arc::new(hook));
+    }
+
+    /// Sets the hook called when a job succeeds.
+    ///
+    /// # Arguments
+    /// * `hook` - Hook to set.
+    pub fn set_on_success(&mut self, hook: impl Fn(&Job, &[u8]) + Send + Sync + 'static) {
+        self.on_success = Some(Arc::new(hook));
+    }
+
+    /// Sets the hook called when a job run fails.
+    ///
+    /// # Arguments
+    /// * `hook` - Hook to set.
+    pub fn set_on_failure(
+        &mut self,
+        hook: impl Fn(&Job, ResultStatus, &str) + Send + Sync + 'static,
+    ) {
+        self.on_failure = Some(Arc::new(hook));
+    }
+
+    /// Sets the hook called when a running job publishes a domain-specific signal.
+    ///
+    /// # Arguments
+    /// * `hook` - Hook to set.
+    pub fn set_on_command(&mut self, hook: impl Fn(&Job, &str, &[u8]) + Send + Sync + 'static) {
+        self.on_command = Some(Arc::new(hook));
+    }
+
+    /// Checks if the result of the jobs is needed by the hooks.
+    ///
+    /// # Returns
+    /// `true` if a success hook is set, `false` otherwise.
+    pub(crate) fn wants_result(&self) -> bool {
+        self.on_success.is_some()
+    }
+
+    /// Calls the hook of the jobs starting.
+    ///
+    /// # Arguments
+    /// * `job` - Job started.
+    pub(crate) fn started(&self, job: &Job) {
+        if let Some(hook) = &self.on_start {
+            hook(job);
+        }
+    }
+
+    /// Calls the hook of the jobs finished, according to their result status.
+    ///
+    /// # Arguments
+    /// * `job` - Job finished.
+    /// * `status` - Result status of the job.
+    /// * `result` - Result of a successful job.
+    /// * `error` - Error message of a failed job.
+    pub(crate) fn finished(&self, job: &Job, status: ResultStatus, result: &[u8], error: &str) {
+        match (status, &self.on_success, &self.on_failure) {
+            (ResultStatus::Success, Some(hook), _) => hook(job, result),
+            (ResultStatus::Success, None, _) => (),
+            (status, _, Some(hook)) => hook(job, status, error),
+            (_, _, None) => (),
+        }
+    }
+
+    /// Calls the hook of the domain-specific signals.
+    ///
+    /// # Arguments
+    /// * `job` - Job publishing the signal.
+    /// * `name` - Name of the command.
+    /// * `payload` - Payload of the command.
+    pub(crate) fn command(&self, job: &Job, name: &str, payload: &[u8]) {
+        if let Some(hook) = &self.on_command {
+            hook(job, name, payload);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use lazy_static::lazy_static;
+use rand::Rng;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Builder;
+
+use crate::prelude::*;
+
+lazy_static! {
+    /// Random data used to generate UUID V1 values.
+    pub(crate) static ref GROUP_ID: [u8; 6] = rand::thread_rng().gen::<[u8; 6]>();
+}
+
+/// Generator of the IDs of the jobs shared with the queue.
+pub type SharedIdGenerator = Arc<dyn IdGenerator>;
+
+/// Trait implemented by the generators of the IDs of the jobs (e.g. time-sortable IDs, so that
+/// listings sort naturally by creation time).
+pub trait IdGenerator: Send + Sync {
+    /// Generates the ID of a new job.
+    ///
+    /// # Returns
+    /// The ID, unique.
+    fn generate(&self) -> Uuid;
+}
+
+/// Generator of UUID V1 values (timestamp and random node ID), used by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UuidV1Generator;
+
+impl IdGenerator for UuidV1Generator {
+    fn generate(&self) -> Uuid {
+        Uuid::now_v1(&GROUP_ID)
+    }
+}
+
+/// Generator of UUID V7 values (Unix timestamp in milliseconds followed by random bits), sorting
+/// by creation time. The IDs generated within the same millisecond are incremented from the
+/// previous one, so that they sort in order of creation too.
+#[derive(Debug, Default)]
+pub struct UuidV7Generator {
+    /// Timestamp (in milliseconds) and random bits of the last ID generated.
+    last: Mutex<(u64, [u8; 2], u64)>,
+}
+
+impl IdGenerator for UuidV7Generator {
+    fn generate(&self) -> Uuid {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Clock going backwards: the timestamp of the last ID kept to preserve the order
+        *last = match millis > last.0 {
+            // Top bits (replaced by the variant) cleared, so that the increments don't overflow
+            true => {
+                let mut rng = rand::thread_rng();
+
+                (millis, rng.gen::<[u8; 2]>(), rng.gen::<u64>() >> 2)
+            }
+            false => (last.0, last.1, last.2 + 1),
+        };
+
+        let mut bytes = [0; 10];
+        bytes[..2].copy_from_slice(&last.1);
+        bytes[2..].copy_from_slice(&last.2.to_be_bytes());
+
+        Builder::from_unix_timestamp_millis(last.0, &bytes).into_uuid()
+    }
+}
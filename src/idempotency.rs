@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Outcome of an enqueue honoring idempotency keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enqueued {
+    /// ID of the job, the one of the original submission for a duplicate.
+    pub id: Uuid,
+
+    /// Whether the job is a duplicate of a previous submission (not enqueued again).
+    pub duplicate: bool,
+
+    /// Result of the original submission, if it has already completed.
+    pub cached_result: Option<Vec<u8>>,
+}
+
+/// Submission of a job holding an idempotency key.
+#[derive(Clone, Debug)]
+struct Submission {
+    /// ID of the job.
+    id: Uuid,
+
+    /// Timestamp of the enqueue, or of the completion once completed.
+    timestamp: SystemTime,
+
+    /// Result of the job, once completed successfully.
+    result: Option<Vec<u8>>,
+}
+
+/// Submissions of the jobs holding an idempotency key, kept for a window of time after they
+/// have been enqueued (or completed).
+#[derive(Debug)]
+pub(crate) struct IdempotencyCache {
+    /// Duration during which a key is remembered.
+    window: Duration,
+
+    /// Submissions by key.
+    submissions: HashMap<String, Submission>,
+}
+
+impl IdempotencyCache {
+    /// Creates an empty cache.
+    ///
+    /// # Arguments
+    /// * `window` - Duration during which a key is remembered.
+    ///
+    /// # Returns
+    /// An instance of `IdempotencyCache`.
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            submissions: HashMap::new(),
+        }
+    }
+
+    /// Sets the duration during which a key is remembered.
+    ///
+    /// # Arguments
+    /// * `window` - Duration to set.
+    pub(crate) fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    /// Registers the submission of a job, unless its key has been submitted within the window.
+    ///
+    /// # Arguments
+    /// * `key` - Idempotency key of the job.
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The original submission if the job is a duplicate, `None` otherwise.
+    pub(crate) fn submit(&mut self, key: &str, id: Uuid) -> Option<Enqueued> {
+        let window = self.window;
+
+        self.submissions.retain(|_, submission| {
+            SystemTime::now()
+                .duration_since(submission.timestamp)
+                .map_or(true, |elapsed| elapsed < window)
+        });
+
+        if let Some(submission) = self.submissions.get(key) {
+            return Some(Enqueued {
+                id: submission.id,
+                duplicate: true,
+                cached_result: submission.result.clone(),
+            });
+        }
+
+        self.submissions.insert(
+            key.to_owned(),
+            Submission {
+                id,
+                timestamp: SystemTime::now(),
+                result: None,
+            },
+        );
+
+        None
+    }
+
+    /// Caches the result of a job completed successfully, for the window from now.
+    ///
+    /// # Arguments
+    /// * `key` - Idempotency key of the job.
+    /// * `id` - ID of the job.
+    /// * `result` - Result of the job.
+    pub(crate) fn complete(&mut self, key: &str, id: Uuid, result: Vec<u8>) {
+        if let Some(submission) = self.submissions.get_mut(key) {
+            if submission.id == id {
+                submission.timestamp = SystemTime::now();
+                submission.result = Some(result);
+            }
+        }
+    }
+
+    /// Forgets the submission of a job that hasn't completed successfully, so that it can be
+    /// submitted again.
+    ///
+    /// # Arguments
+    /// * `key` - Idempotency key of the job.
+    /// * `id` - ID of the job.
+    pub(crate) fn forget(&mut self, key: &str, id: Uuid) {
+        if self
+            .submissions
+            .get(key)
+            .is_some_and(|submission| submission.id == id)
+        {
+            self.submissions.remove(key);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use crate::channel::{self, MessageReceiver};
+use crate::prelude::*;
+
+/// Runs a job inline, on the calling task, once the private data required by its routine
+/// checked.
+///
+/// # Arguments
+/// * `job` - Job to be run.
+/// * `context` - Context to be passed to the routine.
+///
+/// # Returns
+/// The result of the routine, with the messages it sent (progress...).
+pub(crate) async fn run<RoutineType, Context>(
+    job: &Job,
+    context: Option<ContextMode<Context>>,
+) -> (Result<Vec<u8>, ApiError>, MessageReceiver)
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    let (tx, rx) = channel::channel();
+
+    if !job.is_local() {
+        let checked = job
+            .routine::<RoutineType, Context>()
+            .and_then(|routine| job.check_private_data(&routine.private_data_requirements()));
+
+        if let Err(e) = checked {
+            return (Err(e), rx);
+        }
+    }
+
+    let result = job
+        .run::<RoutineType, Context>(Arc::new(Mutex::new(tx)), context)
+        .await;
+
+    (result, rx)
+}
+
+/// Executor running every job inline, on the calling task, as soon as enqueued: no queue, no
+/// thread, no storage. Meant for the tests and the tools running the same routines without
+/// background processing.
+pub struct InlineExecutor<RoutineType, Context> {
+    /// Context passed to every routine.
+    context: Option<ContextMode<Context>>,
+
+    /// Type of routine handled.
+    routine: PhantomData<fn() -> RoutineType>,
+}
+
+impl<RoutineType, Context> Default for InlineExecutor<RoutineType, Context> {
+    fn default() -> Self {
+        Self {
+            context: None,
+            routine: PhantomData,
+        }
+    }
+}
+
+impl<RoutineType, Context> InlineExecutor<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates a new executor without context.
+    ///
+    /// # Returns
+    /// An instance of `InlineExecutor`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new executor passing a context to every routine.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be passed.
+    ///
+    /// # Returns
+    /// An instance of `InlineExecutor`.
+    pub fn with_context(context: Context) -> Self {
+        Self {
+            context: Some(ContextMode::exclusive(context)),
+            ..Default::default()
+        }
+    }
+
+    /// Runs a job right away, then calls its completion callback and runs its follow-up job,
+    /// if any (see `Job::set_on_complete`). The progress reported by the routine is ignored.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be run.
+    ///
+    /// # Returns
+    /// The result of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, raised by the routine or when the job cannot be run (e.g. missing
+    /// private data).
+    pub async fn enqueue(&self, job: Job) -> Result<Vec<u8>, ApiError> {
+        let result = self.run(&job).await;
+        let mut follow_up = job.complete(&job.outcome_of(&result))?;
+
+        while let Some(job) = follow_up {
+            let result = self.run(&job).await;
+
+            follow_up = job.complete(&job.outcome_of(&result))?;
+        }
+
+        result
+    }
+
+    /// Runs a job inline.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be run.
+    ///
+    /// # Returns
+    /// The result of the routine.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn run(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        // Messages of the routine (progress...) dropped once run
+        let (result, _) = run::<RoutineType, Context>(job, self.context.clone()).await;
+
+        result
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share an enqueue interceptor across threads.
+pub type SharedEnqueueInterceptor = Arc<dyn EnqueueInterceptor>;
+
+/// Trait implemented by the interceptors inspecting the jobs when enqueued, before being taken
+/// by the queue: they can complete a job (e.g. add tags) or reject it (e.g. payload too large),
+/// the caller of `enqueue` getting the rejection instead of the job failing when run.
+pub trait EnqueueInterceptor: Send + Sync {
+    /// Inspects a job enqueued.
+    ///
+    /// # Arguments
+    /// * `job` - Job enqueued, which can be modified.
+    ///
+    /// # Errors
+    /// The rejection returned to the caller, `Error::JobRejected` by convention.
+    fn intercept(&self, job: &mut Job) -> Result<(), ApiError>;
+}
+
+/// Interceptors written as closures.
+impl<F> EnqueueInterceptor for F
+where
+    F: Fn(&mut Job) -> Result<(), ApiError> + Send + Sync,
+{
+    fn intercept(&self, job: &mut Job) -> Result<(), ApiError> {
+        self(job)
+    }
+}
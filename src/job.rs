@@ -1,15 +1,17 @@
 use async_trait::async_trait;
-use lazy_static::lazy_static;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
 use std::time::{Duration, SystemTime};
 
+use crate::completion;
+use crate::id_generator::GROUP_ID;
+use crate::local_routine;
 use crate::prelude::*;
-
-lazy_static! {
-    /// Random data used to generate UUID V1 values.
-    static ref GROUP_ID: [u8; 6] = rand::thread_rng().gen::<[u8; 6]>();
-}
+use crate::result_stream::ChunkSink;
+use crate::sensitive::DataStore;
 
 /// List of expiry configurations available for a job.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
@@ -18,16 +20,24 @@ pub enum ExpirePolicy {
     #[default]
     Manual,
 
-    /// The job is removed once it's result is fetched.
-    /// Force the user to provide a timeout to avoid storing ad vitam aeternam.
+    /// The job is removed once it's result is fetched, after a grace period so that the result
+    /// can be fetched again meanwhile. The grace period also applies once the job is finished,
+    /// to avoid storing ad vitam aeternam the results never fetched.
     OnResultFetch(Duration),
 
     /// The job is removed after a specified duration.
     Timeout(Duration),
+
+    /// The job never expires and is never evicted by the retention policy of the queue.
+    Never,
+
+    /// The job is removed after a specified duration only if it has finished successfully,
+    /// failures are kept for inspection.
+    OnSuccessOnly(Duration),
 }
 
 /// List of result statuses of a job.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum ResultStatus {
     /// The job has finished successfully.
     #[default]
@@ -35,10 +45,22 @@ pub enum ResultStatus {
 
     /// The job has finished in error.
     Error,
+
+    /// The job was running on a worker that died.
+    Interrupted,
+
+    /// The job has been cancelled, before or while running.
+    Cancelled,
+
+    /// The job has been stopped for exceeding the time it was given.
+    TimedOut,
+
+    /// The job has been dropped, or stopped, for not being finished by its deadline.
+    DeadlineExceeded,
 }
 
 /// List of statuses of a job.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum Status {
     /// Job is not ready and not scheduled.
     #[default]
@@ -55,16 +77,36 @@ pub enum Status {
 
     /// Job has been removed from the job queue.
     Removed,
+
+    /// Job is running but parked at a suspension point until resumed.
+    Suspended,
+
+    /// Job has failed an attempt and waits for the backoff delay before being run again.
+    Retrying,
+}
+
+impl Status {
+    /// Checks if the status is terminal, the job being no longer run.
+    ///
+    /// # Returns
+    /// `true` if finished or removed, `false` otherwise.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Status::Finished(_) | Status::Removed)
+    }
 }
 
 /// Structure used to store the progression steps of the job.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct Progression {
     /// Current step.
     pub step: u64,
 
     /// Number of steps.
     pub steps: u64,
+
+    /// Estimated completion time of the job, if known (see `JobQueue::job_eta`).
+    #[serde(default)]
+    pub eta: Option<SystemTime>,
 }
 
 /// Structure used to store timestamps and result of the job.
@@ -77,6 +119,25 @@ pub struct Payload {
     pub result: Vec<u8>,
 }
 
+/// Execution attempt of a job, kept across retries.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Attempt {
+    /// Timestamp at which the attempt started.
+    pub started: SystemTime,
+
+    /// Timestamp at which the attempt ended.
+    pub finished: SystemTime,
+
+    /// Result of the attempt.
+    pub status: ResultStatus,
+
+    /// Error message of a failed attempt.
+    pub error: Option<String>,
+
+    /// Identifier of the worker that ran the attempt.
+    pub worker: Option<String>,
+}
+
 /// Timestamps of every steps of the lifecycle of a job.
 #[derive(Clone, PartialEq, Deserialize, Serialize)]
 pub struct Timestamps {
@@ -93,6 +154,66 @@ pub struct Timestamps {
     pub expired: Option<SystemTime>,
 }
 
+/// Execution classes of the routines, deciding the threads running them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum ExecClass {
+    /// The routine runs on the workers of the queue, shared by the async jobs, and must not
+    /// block them (see `Job::yield_every` for CPU-bound loops).
+    #[default]
+    Async,
+
+    /// The routine runs on a thread of the blocking pool of the queue, so that a blocking or
+    /// CPU-heavy routine doesn't starve the async jobs. Such a job cannot be interrupted once
+    /// started: cancelling it only discards its result.
+    Blocking,
+}
+
+/// Type of private data a routine needs to run, checked when its job is enqueued.
+#[derive(Clone, Copy, Debug)]
+pub struct PrivateDataRequirement {
+    /// Name of the type required.
+    type_name: &'static str,
+
+    /// Checks if private data can be read as the type required.
+    check: fn(&str) -> bool,
+}
+
+impl PrivateDataRequirement {
+    /// Creates a requirement on the type of the private data.
+    ///
+    /// # Returns
+    /// An instance of `PrivateDataRequirement`.
+    pub fn of<T>() -> Self
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        Self {
+            type_name: std::any::type_name::<T>(),
+            check: |data| serde_json::from_str::<T>(data).is_ok(),
+        }
+    }
+
+    /// Gets the name of the type required.
+    ///
+    /// # Returns
+    /// The name of the type.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Checks if the private data of a job meets the requirement.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be checked.
+    ///
+    /// # Returns
+    /// `true` if the job holds private data of the type required, `false` otherwise.
+    pub fn is_met(&self, job: &Job) -> bool {
+        job.private_data.as_deref().map_or(false, self.check)
+            || job.data.get(self.type_name).map_or(false, self.check)
+    }
+}
+
 /// Trait that must be derived for the list of possible routines handled by the jobs.
 #[async_trait]
 pub trait Routine<Context>: for<'a> Deserialize<'a> + Serialize + Send {
@@ -113,6 +234,202 @@ pub trait Routine<Context>: for<'a> Deserialize<'a> + Serialize + Send {
         messages_channel: SharedMessageChannel,
         context: Option<Shared<Context>>,
     ) -> Result<Vec<u8>, Error>;
+
+    /// Function that is called when the job is processed, receiving the context in the mode it
+    /// is shared by the queue (see `JobQueue::set_context_mode`).
+    ///
+    /// # Arguments
+    /// * `job` - Job this routine belongs to.
+    /// * `messages_channel` - Channel to be used to send back messages to the job queue.
+    /// * `context` - Context shared by the routines.
+    ///
+    /// # Returns
+    /// A list of bytes: the result of the job to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    ///
+    /// Calls `call` by default, with the context only if shared behind a mutex: to be
+    /// implemented by the routines of the queues sharing it read-only or behind a read-write
+    /// lock.
+    async fn call_with_context(
+        &self,
+        job: &Job,
+        messages_channel: SharedMessageChannel,
+        context: Option<ContextMode<Context>>,
+    ) -> Result<Vec<u8>, Error>
+    where
+        Context: Send + 'async_trait,
+    {
+        let context = context.and_then(ContextMode::into_exclusive);
+
+        self.call(job, messages_channel, context).await
+    }
+
+    /// Private data needed by the routine, a job missing it being rejected when enqueued
+    /// rather than failing when run.
+    ///
+    /// # Returns
+    /// The list of requirements (none by default).
+    fn private_data_requirements(&self) -> Vec<PrivateDataRequirement> {
+        vec![]
+    }
+
+    /// Execution class of the routine.
+    ///
+    /// # Returns
+    /// The class deciding the threads running the routine (`ExecClass::Async` by default).
+    fn exec_class(&self) -> ExecClass {
+        ExecClass::Async
+    }
+
+    /// Version of the arguments of the routine, stored with its jobs so that the jobs persisted
+    /// by an older release can be upgraded (see `RoutineMigrator`).
+    ///
+    /// # Returns
+    /// The version (0 by default), to be bumped when the arguments change.
+    fn version(&self) -> u32 {
+        0
+    }
+}
+
+/// Trait that can be derived instead of `Routine` for synchronous routines (e.g. image
+/// processing, CSV crunching), always run on the blocking pool of the queue (see
+/// `ExecClass::Blocking`).
+pub trait BlockingRoutine<Context>: for<'a> Deserialize<'a> + Serialize + Send + Sync {
+    /// Function that is called when the job is processed, on a thread of the blocking pool.
+    ///
+    /// # Arguments
+    /// * `job` - Job this routine belongs to.
+    /// * `messages_channel` - Channel to be used to send back messages to the job queue.
+    /// * `context` - Context shared by the routines.
+    ///
+    /// # Returns
+    /// A list of bytes: the result of the job to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn call(
+        &self,
+        job: &Job,
+        messages_channel: SharedMessageChannel,
+        context: Option<Shared<Context>>,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Function that is called when the job is processed, on a thread of the blocking pool,
+    /// receiving the context in the mode it is shared by the queue (see
+    /// `Routine::call_with_context`).
+    ///
+    /// # Arguments
+    /// * `job` - Job this routine belongs to.
+    /// * `messages_channel` - Channel to be used to send back messages to the job queue.
+    /// * `context` - Context shared by the routines.
+    ///
+    /// # Returns
+    /// A list of bytes: the result of the job to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn call_with_context(
+        &self,
+        job: &Job,
+        messages_channel: SharedMessageChannel,
+        context: Option<ContextMode<Context>>,
+    ) -> Result<Vec<u8>, Error> {
+        let context = context.and_then(ContextMode::into_exclusive);
+
+        self.call(job, messages_channel, context)
+    }
+
+    /// Private data needed by the routine (see `Routine::private_data_requirements`).
+    ///
+    /// # Returns
+    /// The list of requirements (none by default).
+    fn private_data_requirements(&self) -> Vec<PrivateDataRequirement> {
+        vec![]
+    }
+
+    /// Version of the arguments of the routine (see `Routine::version`).
+    ///
+    /// # Returns
+    /// The version (0 by default).
+    fn version(&self) -> u32 {
+        0
+    }
+}
+
+#[async_trait]
+impl<T, Context> Routine<Context> for T
+where
+    T: BlockingRoutine<Context>,
+    Context: Send + 'static,
+{
+    async fn call(
+        &self,
+        job: &Job,
+        messages_channel: SharedMessageChannel,
+        context: Option<Shared<Context>>,
+    ) -> Result<Vec<u8>, Error> {
+        BlockingRoutine::call(self, job, messages_channel, context)
+    }
+
+    async fn call_with_context(
+        &self,
+        job: &Job,
+        messages_channel: SharedMessageChannel,
+        context: Option<ContextMode<Context>>,
+    ) -> Result<Vec<u8>, Error>
+    where
+        Context: Send + 'async_trait,
+    {
+        BlockingRoutine::call_with_context(self, job, messages_channel, context)
+    }
+
+    fn private_data_requirements(&self) -> Vec<PrivateDataRequirement> {
+        BlockingRoutine::private_data_requirements(self)
+    }
+
+    fn exec_class(&self) -> ExecClass {
+        ExecClass::Blocking
+    }
+
+    fn version(&self) -> u32 {
+        BlockingRoutine::version(self)
+    }
+}
+
+/// Entry of a job record, as stored by a backend.
+enum StoredEntry {
+    /// Entry embedded as is.
+    Plain,
+
+    /// Entry compressed.
+    Packed(Packed),
+
+    /// Entry encrypted (possibly compressed first).
+    Sealed(Sealed),
+}
+
+/// Namespace of the jobs serialized without one.
+///
+/// # Returns
+/// The default namespace.
+fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_owned()
+}
+
+/// Origin of the ID of a job.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum IdOrigin {
+    /// Given at creation, replaced by the generator of the queue, if any, when enqueued.
+    #[default]
+    Created,
+
+    /// Generated by the generator of the queue.
+    Generated,
+
+    /// Supplied by the caller (see `Job::new_with_id`).
+    Supplied,
 }
 
 /// Description of a job.
@@ -122,8 +439,17 @@ pub struct Job {
     id: Uuid,
 
     /// The routine called when running.
+    #[serde(with = "crate::codec::embedded")]
     routine: String,
 
+    /// Kind of the routine (i.e. name of the variant of the routine enum).
+    #[serde(default)]
+    kind: String,
+
+    /// Version of the routine when created (see `Routine::version`).
+    #[serde(default)]
+    routine_version: u32,
+
     /// Status of the job.
     status: Status,
 
@@ -140,7 +466,182 @@ pub struct Job {
     expire_policy: ExpirePolicy,
 
     /// Private data.
+    #[serde(default, with = "crate::codec::embedded_opt")]
     private_data: Option<String>,
+
+    /// Private data entries attached by several layers (auth info, app payload...), by name
+    /// (the name of their type for the typed ones), stored as JSON. The sensitive ones (see
+    /// `insert_sensitive_data`) aren't serialized.
+    #[serde(default)]
+    data: DataStore,
+
+    /// Tags attached to the job.
+    #[serde(default)]
+    tags: Vec<String>,
+
+    /// Priority of the job (the higher, the more urgent).
+    #[serde(default)]
+    priority: i32,
+
+    /// Timestamp before which the job should be run.
+    #[serde(default)]
+    deadline: Option<SystemTime>,
+
+    /// Free key/value pairs attached to the job.
+    #[serde(default, with = "crate::codec::values")]
+    metadata: BTreeMap<String, serde_json::Value>,
+
+    /// Key identifying duplicated submissions.
+    #[serde(default)]
+    idempotency_key: Option<String>,
+
+    /// Namespace of the queue storing the job.
+    #[serde(default = "default_namespace")]
+    namespace: String,
+
+    /// Identifier of the worker that claimed the job (shared backends).
+    #[serde(default)]
+    worker: Option<String>,
+
+    /// Timestamp of the last heartbeat of the worker that claimed the job.
+    #[serde(default)]
+    heartbeat: Option<SystemTime>,
+
+    /// Timestamp at which the lease of the worker expires, the job being claimable again.
+    #[serde(default)]
+    lease_until: Option<SystemTime>,
+
+    /// Last checkpoint saved by the routine, to resume from.
+    #[serde(default)]
+    checkpoint: Option<Vec<u8>>,
+
+    /// Chunks of the result emitted by the routine while running, the oldest first.
+    #[serde(default)]
+    chunks: Vec<Vec<u8>>,
+
+    /// Key of the closure run in place of the routine (local job, see `from_fn`).
+    #[serde(default)]
+    local: Option<Uuid>,
+
+    /// Key of the result in the result store, if offloaded (see `ResultOffload`).
+    #[serde(default)]
+    result_ref: Option<String>,
+
+    /// Duration the result is kept once the job is finished (see `set_result_ttl`).
+    #[serde(default)]
+    result_ttl: Option<Duration>,
+
+    /// Timestamp at which the result expires.
+    #[serde(default)]
+    result_expires: Option<SystemTime>,
+
+    /// Whether the result has been dropped for being expired.
+    #[serde(default)]
+    result_dropped: bool,
+
+    /// Whether the routine is looked up by name in a registry (see `registered`).
+    #[serde(default)]
+    registered: bool,
+
+    /// Execution attempts, including the ones of the jobs it retries.
+    #[serde(default)]
+    attempts: Vec<Attempt>,
+
+    /// Time at which the job is scheduled to run (delayed or recurring job).
+    #[serde(default)]
+    run_at: Option<SystemTime>,
+
+    /// Maximum random delay added to the scheduled time of every occurrence.
+    #[serde(default)]
+    jitter: Duration,
+
+    /// Random delay picked within the jitter for this occurrence.
+    #[serde(default)]
+    jitter_delay: Duration,
+
+    /// Recurrence of the job, run again once started.
+    #[serde(default)]
+    recurrence: Option<Recurrence>,
+
+    /// ID of the first occurrence of the recurring job this one belongs to.
+    #[serde(default)]
+    series: Option<Uuid>,
+
+    /// Tenant owning the job, subject to its quota.
+    #[serde(default)]
+    tenant: Option<String>,
+
+    /// Named queue of the job, `DEFAULT_QUEUE` if `None`.
+    #[serde(default)]
+    queue: Option<String>,
+
+    /// Capabilities required from the worker running the job.
+    #[serde(default)]
+    requirements: Vec<String>,
+
+    /// Trace context of the span enqueuing the job.
+    #[serde(default)]
+    trace_context: Option<TraceContext>,
+
+    /// Context of the job, passed to its routine in place of (or merged with) the context of
+    /// the queue (see `with_context`).
+    #[serde(default, with = "crate::codec::value_opt")]
+    context: Option<serde_json::Value>,
+
+    /// Key of the callback called once the job is finished (see `set_on_complete`).
+    #[serde(default)]
+    on_complete: Option<Uuid>,
+
+    /// Follow-up job enqueued once the job is finished (see `set_on_complete_routine`).
+    #[serde(default)]
+    on_complete_job: Option<Box<Job>>,
+
+    /// Entries of the record compressed, by field (only set in the records, see
+    /// `encode_with`).
+    #[serde(default)]
+    packed: BTreeMap<String, Packed>,
+
+    /// Entries of the record encrypted, by field (only set in the records, see
+    /// `encode_with`).
+    #[serde(default)]
+    sealed: BTreeMap<String, Sealed>,
+
+    /// Origin of the ID of the job, the supplied ones being checked against the jobs already
+    /// known by the queue.
+    #[serde(skip)]
+    id_origin: IdOrigin,
+
+    /// Suspension state shared with the queue while running.
+    #[serde(skip)]
+    suspension: Suspension,
+
+    /// Sink of the chunks of the result shared with the queue while running.
+    #[serde(skip)]
+    chunk_sink: ChunkSink,
+}
+
+impl fmt::Debug for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Sensitive entries redacted, so that jobs can be logged safely
+        let data = self
+            .data
+            .iter()
+            .map(|(name, data, sensitive)| match sensitive {
+                true => (name, "<redacted>"),
+                false => (name, data),
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        f.debug_struct("Job")
+            .field("id", &self.id)
+            .field("kind", &self.kind)
+            .field("status", &self.status)
+            .field("priority", &self.priority)
+            .field("tags", &self.tags)
+            .field("metadata", &self.metadata)
+            .field("data", &data)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Job {
@@ -162,6 +663,30 @@ impl Job {
         )
     }
 
+    /// Creates a new job given its ID and a routine to be executed, e.g. to keep the identifier
+    /// of a job created from an external system. The job is rejected when enqueued if a job with
+    /// the same ID is already known by the queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job, in place of a generated one (see `IdGenerator`).
+    /// * `routine` - Routine to be called.
+    ///
+    /// # Returns
+    /// An `Job` instance.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn new_with_id<Context>(
+        id: Uuid,
+        routine: impl Routine<Context>,
+    ) -> Result<Self, ApiError> {
+        Ok(Self {
+            id,
+            id_origin: IdOrigin::Supplied,
+            ..Self::new(routine)?
+        })
+    }
+
     /// Creates a new job given a routine to be executed and an expire policy.
     ///
     /// # Arguments
@@ -177,9 +702,30 @@ impl Job {
         routine: impl Routine<Context>,
         expire_policy: ExpirePolicy,
     ) -> Result<Self, ApiError> {
-        Ok(Self {
+        let version = routine.version();
+        let routine = serde_json::to_value(&routine).map_err(|e| api_err!(e.into()))?;
+
+        let mut job = Self::with_routine(routine, expire_policy);
+
+        job.routine_version = version;
+
+        Ok(job)
+    }
+
+    /// Creates a new job given a routine serialized as JSON value.
+    ///
+    /// # Arguments
+    /// * `routine` - Routine to be called, serialized.
+    /// * `expire_policy` - Policy to be applied for job removal.
+    ///
+    /// # Returns
+    /// An `Job` instance.
+    fn with_routine(routine: serde_json::Value, expire_policy: ExpirePolicy) -> Self {
+        Self {
             id: Uuid::now_v1(&GROUP_ID),
-            routine: serde_json::to_string(&routine).map_err(|e| api_err!(e.into()))?,
+            kind: Self::routine_kind(&routine),
+            routine_version: 0,
+            routine: routine.to_string(),
             status: Status::NotReady,
             payload: Payload {
                 timestamps: Timestamps {
@@ -194,79 +740,376 @@ impl Job {
             step: 0,
             expire_policy,
             private_data: None,
-        })
+            data: DataStore::default(),
+            tags: vec![],
+            priority: 0,
+            deadline: None,
+            metadata: BTreeMap::new(),
+            idempotency_key: None,
+            namespace: DEFAULT_NAMESPACE.to_owned(),
+            worker: None,
+            heartbeat: None,
+            lease_until: None,
+            checkpoint: None,
+            chunks: vec![],
+            local: None,
+            result_ref: None,
+            result_ttl: None,
+            result_expires: None,
+            result_dropped: false,
+            registered: false,
+            attempts: vec![],
+            run_at: None,
+            jitter: Duration::ZERO,
+            jitter_delay: Duration::ZERO,
+            recurrence: None,
+            series: None,
+            tenant: None,
+            queue: None,
+            requirements: vec![],
+            trace_context: None,
+            context: None,
+            on_complete: None,
+            on_complete_job: None,
+            packed: BTreeMap::new(),
+            sealed: BTreeMap::new(),
+            id_origin: IdOrigin::Created,
+            suspension: Suspension::default(),
+            chunk_sink: ChunkSink::default(),
+        }
     }
 
-    /// Get the unique identifier of the job.
+    /// Creates a new job running a routine registered by name in the `RoutineRegistry` of the
+    /// queue, instead of a variant of its routine type. A queue whose registry doesn't know
+    /// the name leaves the job pending, for a worker that knows it.
     ///
-    /// # Returns
-    /// The ID of the job.
-    pub fn id(&self) -> Uuid {
-        self.id
-    }
-
-    /// Get the status of the job.
+    /// # Arguments
+    /// * `name` - Name of the routine, used as the kind of the job.
+    /// * `payload` - Arguments of the routine.
     ///
     /// # Returns
-    /// The current status of the job.
-    pub fn status(&self) -> Status {
-        self.status
-    }
-
-    /// Set the status of the job.
-    ///
-    /// # Arguments
-    /// * ̀`status` - Value to be set.
+    /// An `Job` instance.
     ///
     /// # Errors
     /// One of `Error` enum.
-    pub fn set_status(&mut self, status: Status) -> Result<(), ApiError> {
-        match status {
-            Status::NotReady => {
-                return Err(api_err!(Error::InvalidJobStatusTransition((
-                    self.status,
-                    status
-                ))))
-            }
+    pub fn registered(name: impl Into<String>, payload: &impl Serialize) -> Result<Self, ApiError> {
+        let name = name.into();
+        let payload = serde_json::to_value(payload).map_err(|e| api_err!(e.into()))?;
 
-            Status::Ready => {
-                if self.status != Status::NotReady {
-                    return Err(api_err!(Error::InvalidJobStatusTransition((
-                        self.status,
-                        status
-                    ))));
-                } else {
-                    self.payload.timestamps.enqueued = SystemTime::now();
-                }
-            }
+        // Stored as a variant of an enum, so that the kind is the name
+        let routine = serde_json::Value::Object([(name, payload)].into_iter().collect());
 
-            Status::Running => {
-                if self.status != Status::Ready {
-                    return Err(api_err!(Error::InvalidJobStatusTransition((
-                        self.status,
-                        status
-                    ))));
-                } else {
-                    self.payload.timestamps.started = SystemTime::now();
-                }
-            }
+        let mut job = Self::with_routine(
+            routine,
+            ExpirePolicy::OnResultFetch(Duration::from_secs(60)),
+        );
 
-            Status::Finished(_) => {
-                if self.status != Status::Running {
-                    return Err(api_err!(Error::InvalidJobStatusTransition((
-                        self.status,
-                        status
-                    ))));
-                } else {
-                    let now = SystemTime::now();
+        job.registered = true;
 
-                    self.payload.timestamps.finished = now;
+        Ok(job)
+    }
 
-                    // If expire policy has a timeout, then store the time of expiration
-                    match self.expire_policy {
+    /// Creates a local job running a closure instead of a routine, for ad-hoc work that
+    /// doesn't deserve a routine variant. Such a job never leaves the process creating it:
+    /// its closure is run once by a queue with a memory backend, and it cannot be encoded in
+    /// the wire format nor retried.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the job, used as its kind.
+    /// * `routine` - Closure called with the context of the queue when the job is processed.
+    ///
+    /// # Returns
+    /// An `Job` instance.
+    pub fn from_fn<Context, F, Fut>(name: impl Into<String>, routine: F) -> Self
+    where
+        Context: 'static,
+        F: FnOnce(Option<Shared<Context>>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Vec<u8>, Error>> + Send + 'static,
+    {
+        let name = name.into();
+        let key = Uuid::now_v1(&GROUP_ID);
+
+        local_routine::register::<Context>(
+            key,
+            Box::new(move |context| Box::pin(routine(context))),
+        );
+
+        Self {
+            id: Uuid::now_v1(&GROUP_ID),
+            routine: serde_json::Value::String(name.clone()).to_string(),
+            kind: name,
+            routine_version: 0,
+            status: Status::NotReady,
+            payload: Payload {
+                timestamps: Timestamps {
+                    enqueued: SystemTime::now(),
+                    started: SystemTime::UNIX_EPOCH,
+                    finished: SystemTime::UNIX_EPOCH,
+                    expired: None,
+                },
+                result: vec![],
+            },
+            steps: 0,
+            step: 0,
+            expire_policy: ExpirePolicy::OnResultFetch(Duration::from_secs(60)),
+            private_data: None,
+            data: DataStore::default(),
+            tags: vec![],
+            priority: 0,
+            deadline: None,
+            metadata: BTreeMap::new(),
+            idempotency_key: None,
+            namespace: DEFAULT_NAMESPACE.to_owned(),
+            worker: None,
+            heartbeat: None,
+            lease_until: None,
+            checkpoint: None,
+            chunks: vec![],
+            local: Some(key),
+            result_ref: None,
+            result_ttl: None,
+            result_expires: None,
+            result_dropped: false,
+            registered: false,
+            attempts: vec![],
+            run_at: None,
+            jitter: Duration::ZERO,
+            jitter_delay: Duration::ZERO,
+            recurrence: None,
+            series: None,
+            tenant: None,
+            queue: None,
+            requirements: vec![],
+            trace_context: None,
+            context: None,
+            on_complete: None,
+            on_complete_job: None,
+            packed: BTreeMap::new(),
+            sealed: BTreeMap::new(),
+            id_origin: IdOrigin::Created,
+            suspension: Suspension::default(),
+            chunk_sink: ChunkSink::default(),
+        }
+    }
+
+    /// Checks if the job runs a closure (see `from_fn`).
+    ///
+    /// # Returns
+    /// `true` for a local job, `false` otherwise.
+    pub fn is_local(&self) -> bool {
+        self.local.is_some()
+    }
+
+    /// Checks if the job runs a routine registered by name (see `registered`).
+    ///
+    /// # Returns
+    /// `true` for a registered routine, `false` otherwise.
+    pub fn is_registered(&self) -> bool {
+        self.registered
+    }
+
+    /// Checks if the ID of the job has been supplied (see `new_with_id`).
+    ///
+    /// # Returns
+    /// `true` if supplied, `false` if generated.
+    pub(crate) fn has_supplied_id(&self) -> bool {
+        self.id_origin == IdOrigin::Supplied
+    }
+
+    /// Replaces the ID given to the job at creation by one of the generator of the queue, unless
+    /// supplied (see `new_with_id`) or already generated by the queue (e.g. staged by `prepare`).
+    ///
+    /// # Arguments
+    /// * `generator` - Generator of the IDs of the queue.
+    pub(crate) fn generate_id(&mut self, generator: &dyn IdGenerator) {
+        if self.id_origin == IdOrigin::Created {
+            self.id = generator.generate();
+            self.id_origin = IdOrigin::Generated;
+        }
+    }
+
+    /// Creates a new job running the same routine, with the same settings, as this one. The
+    /// attempts of this job are kept in the history of the new one.
+    ///
+    /// # Returns
+    /// An `Job` instance, with a new ID.
+    pub fn retry(&self) -> Self {
+        Self {
+            id: Uuid::now_v1(&GROUP_ID),
+            status: Status::NotReady,
+            payload: Payload {
+                timestamps: Timestamps {
+                    enqueued: SystemTime::now(),
+                    started: SystemTime::UNIX_EPOCH,
+                    finished: SystemTime::UNIX_EPOCH,
+                    expired: None,
+                },
+                result: vec![],
+            },
+            steps: 0,
+            step: 0,
+            idempotency_key: None,
+            worker: None,
+            heartbeat: None,
+            lease_until: None,
+            result_ref: None,
+            result_expires: None,
+            result_dropped: false,
+            chunks: vec![],
+            // The retry of an occurrence doesn't start another series
+            recurrence: None,
+            series: None,
+            id_origin: IdOrigin::Created,
+            ..self.clone()
+        }
+    }
+
+    /// Creates the job of the occurrence following this one, for a recurring job.
+    ///
+    /// # Arguments
+    /// * `now` - Current time, from which the missed occurrences are evaluated.
+    ///
+    /// # Returns
+    /// The job of the next occurrence, with a new ID, `None` if the job is not recurring.
+    pub fn next_occurrence(&self, now: SystemTime) -> Option<Self> {
+        let recurrence = self.recurrence?;
+        let run_at = recurrence.next(self.run_at.unwrap_or(now), now);
+
+        let mut next = Self {
+            recurrence: Some(recurrence),
+            series: Some(self.series()),
+            attempts: vec![],
+            checkpoint: None,
+            ..self.retry()
+        };
+
+        next.run_at = Some(run_at);
+        next.set_jitter(self.jitter);
+
+        Some(next)
+    }
+
+    /// Get the unique identifier of the job.
+    ///
+    /// # Returns
+    /// The ID of the job.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Get the kind of the job, that is the name of the variant of its routine.
+    ///
+    /// # Returns
+    /// The kind of the job (empty if the routine is not an enum).
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Get the version of the routine of the job when created.
+    ///
+    /// # Returns
+    /// The version (see `Routine::version`).
+    pub fn routine_version(&self) -> u32 {
+        self.routine_version
+    }
+
+    /// Get the status of the job.
+    ///
+    /// # Returns
+    /// The current status of the job.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Set the status of the job.
+    ///
+    /// # Arguments
+    /// * ̀`status` - Value to be set.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn set_status(&mut self, status: Status) -> Result<(), ApiError> {
+        self.set_status_at(status, SystemTime::now())
+    }
+
+    /// Set the status of the job, timestamped at a given time (see `Clock`).
+    ///
+    /// # Arguments
+    /// * `status` - Value to be set.
+    /// * `now` - Current time.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn set_status_at(&mut self, status: Status, now: SystemTime) -> Result<(), ApiError> {
+        match status {
+            Status::NotReady => {
+                return Err(api_err!(Error::InvalidJobStatusTransition((
+                    self.status,
+                    status
+                ))))
+            }
+
+            Status::Ready => match self.status {
+                Status::NotReady => self.payload.timestamps.enqueued = now,
+                // Backoff elapsed, enqueued as the first attempt
+                Status::Retrying => (),
+                _ => {
+                    return Err(api_err!(Error::InvalidJobStatusTransition((
+                        self.status,
+                        status
+                    ))))
+                }
+            },
+
+            Status::Running => match self.status {
+                Status::Ready => self.payload.timestamps.started = now,
+                Status::Suspended | Status::Retrying => (),
+                _ => {
+                    return Err(api_err!(Error::InvalidJobStatusTransition((
+                        self.status,
+                        status
+                    ))))
+                }
+            },
+
+            Status::Suspended | Status::Retrying => {
+                if self.status != Status::Running {
+                    return Err(api_err!(Error::InvalidJobStatusTransition((
+                        self.status,
+                        status
+                    ))));
+                }
+            }
+
+            Status::Finished(_) => {
+                // A ready job can be finished without being run (e.g. when cancelled)
+                if !matches!(
+                    self.status,
+                    Status::Ready | Status::Running | Status::Suspended | Status::Retrying
+                ) {
+                    return Err(api_err!(Error::InvalidJobStatusTransition((
+                        self.status,
+                        status
+                    ))));
+                } else {
+                    self.payload.timestamps.finished = now;
+
+                    // Secrets not needed anymore
+                    self.data.remove_sensitive();
+
+                    // Result possibly expiring before the job
+                    self.result_expires = self.result_ttl.map(|ttl| now + ttl);
+
+                    // If expire policy has a timeout, then store the time of expiration
+                    match self.expire_policy {
                         ExpirePolicy::OnResultFetch(duration) | ExpirePolicy::Timeout(duration) => {
                             self.payload.timestamps.expired = Some(now + duration)
                         }
+                        ExpirePolicy::OnSuccessOnly(duration) => {
+                            if status == Status::Finished(ResultStatus::Success) {
+                                self.payload.timestamps.expired = Some(now + duration)
+                            }
+                        }
                         _ => (),
                     }
                 }
@@ -280,6 +1123,14 @@ impl Job {
         Ok(())
     }
 
+    /// Get the timestamps of the job.
+    ///
+    /// # Returns
+    /// The timestamps of every step of the lifecycle of the job.
+    pub fn timestamps(&self) -> &Timestamps {
+        &self.payload.timestamps
+    }
+
     /// Get the result of the job.
     ///
     /// # Returns
@@ -301,6 +1152,76 @@ impl Job {
         Ok(())
     }
 
+    /// Get the key of the result in the result store, if offloaded.
+    ///
+    /// # Returns
+    /// The key, `None` if the result is held by the backend.
+    pub fn result_ref(&self) -> Option<&str> {
+        self.result_ref.as_deref()
+    }
+
+    /// Set the key of the result in the result store, the result held by the job being
+    /// emptied.
+    ///
+    /// # Arguments
+    /// * `key` - Key of the result in the store.
+    pub fn set_result_ref(&mut self, key: impl Into<String>) {
+        self.payload.result = vec![];
+        self.result_ref = Some(key.into());
+    }
+
+    /// Get the duration the result is kept once the job is finished.
+    ///
+    /// # Returns
+    /// The duration, `None` if the result is kept as long as the job.
+    pub fn result_ttl(&self) -> Option<Duration> {
+        self.result_ttl
+    }
+
+    /// Set the duration the result is kept once the job is finished, the job itself (status,
+    /// history...) being kept according to its expire policy: large results are dropped earlier
+    /// than the job, `JobQueue::job_result` then failing with `Error::ResultExpired`.
+    ///
+    /// # Arguments
+    /// * `ttl` - Duration the result is kept.
+    pub fn set_result_ttl(&mut self, ttl: Duration) {
+        self.result_ttl = Some(ttl);
+    }
+
+    /// Check if the result has expired at a given time (see `Clock`), at the latest along with
+    /// the job.
+    ///
+    /// # Arguments
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// `true` if expired (dropped or not yet), `false` otherwise.
+    pub fn is_result_expired_at(&self, now: SystemTime) -> bool {
+        self.result_dropped
+            || self.result_expires.is_some_and(|expires| now >= expires)
+            || self.is_expired_at(now)
+    }
+
+    /// Check if the result has been dropped for being expired.
+    ///
+    /// # Returns
+    /// `true` if dropped, `false` otherwise.
+    pub fn is_result_dropped(&self) -> bool {
+        self.result_dropped
+    }
+
+    /// Drop the result of the job (and its chunks), once expired.
+    ///
+    /// # Returns
+    /// The key of the result in the result store, to be removed from it, if offloaded.
+    pub fn expire_result(&mut self) -> Option<String> {
+        self.payload.result = vec![];
+        self.chunks = vec![];
+        self.result_dropped = true;
+
+        self.result_ref.take()
+    }
+
     /// Set the total steps of the job.
     ///
     /// # Arguments
@@ -349,6 +1270,7 @@ impl Job {
         Progression {
             step: self.step,
             steps: self.steps,
+            eta: None,
         }
     }
 
@@ -365,87 +1287,1443 @@ impl Job {
     /// # Returns
     ///  `true` if expired, `false`otherwise.
     pub fn is_expired(&self) -> bool {
-        if let Some(expired) = self.payload.timestamps.expired {
-            SystemTime::now() >= expired
-        } else {
-            false
-        }
+        self.is_expired_at(SystemTime::now())
     }
 
-    /// Get the private data owned by the job.
+    /// Check if the job is expired at a given time (see `Clock`).
+    ///
+    /// # Arguments
+    /// * `now` - Current time.
     ///
     /// # Returns
-    /// The private data casted to the type provided.
+    ///  `true` if expired, `false`otherwise.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.payload
+            .timestamps
+            .expired
+            .is_some_and(|expired| now >= expired)
+    }
+
+    /// Set the time at which the job expires (done by the backends).
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    pub fn private_data<T>(&self) -> Result<T, ApiError>
-    where
-        T: for<'a> Deserialize<'a> + Serialize,
-    {
-        let data = self.private_data.clone().ok_or(Error::MissingPrivateData)?;
-        let data: T = serde_json::from_str(&data).map_err(|e| api_err!(e.into()))?;
+    /// # Arguments
+    /// * `expired` - Timestamp of the expiration.
+    pub fn set_expiration(&mut self, expired: SystemTime) {
+        self.payload.timestamps.expired = Some(expired);
+    }
 
-        Ok(data)
+    /// Get the last checkpoint saved by the routine, on a previous attempt (retry) or before
+    /// the worker running it died.
+    ///
+    /// # Returns
+    /// The checkpoint, `None` if there's nothing to resume from.
+    pub fn checkpoint(&self) -> Option<&[u8]> {
+        self.checkpoint.as_deref()
     }
 
-    /// Set the private data owned by the job.
+    /// Set the last checkpoint of the job (done by the backends).
     ///
     /// # Arguments
-    /// * `value` - Serializable value to be stored.
+    /// * `checkpoint` - Checkpoint to be set.
+    pub fn set_checkpoint(&mut self, checkpoint: Vec<u8>) {
+        self.checkpoint = Some(checkpoint);
+    }
+
+    /// Get the execution attempts of the job, the oldest first, including the ones of the jobs
+    /// it retries.
+    ///
+    /// # Returns
+    /// The attempts.
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+
+    /// Record an execution attempt of the job (done by the backends).
+    ///
+    /// # Arguments
+    /// * `attempt` - Attempt to be recorded.
+    pub fn add_attempt(&mut self, attempt: Attempt) {
+        self.attempts.push(attempt);
+    }
+
+    /// Save a checkpoint of a running job, stored by the backend so that the next attempt can
+    /// resume from it instead of restarting from scratch.
+    ///
+    /// # Arguments
+    /// * `messages_channel` - Channel used to send messages to the queue (passed to the
+    ///   routine).
+    /// * `checkpoint` - Checkpoint to be saved.
     ///
     /// # Errors
     /// One of `Error` enum.
-    pub fn set_private_data<'a>(
-        &mut self,
-        value: impl Deserialize<'a> + Serialize,
+    pub fn save_checkpoint(
+        &self,
+        messages_channel: &SharedMessageChannel,
+        checkpoint: Vec<u8>,
     ) -> Result<(), ApiError> {
-        self.private_data = Some(serde_json::to_string(&value).map_err(|e| api_err!(e.into()))?);
-
-        Ok(())
+        messages_channel
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::SaveCheckpoint(self.id, checkpoint)))
+            .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))
     }
 
-    /// Get the routine for this job.
+    /// Get the chunks of the result emitted by the routine so far (see `emit_chunk`).
     ///
     /// # Returns
-    /// The routine (typed).
+    /// The chunks, the oldest first.
+    pub fn chunks(&self) -> &[Vec<u8>] {
+        &self.chunks
+    }
+
+    /// Append a chunk to the result of the job (done by the backends).
+    ///
+    /// # Arguments
+    /// * `chunk` - Chunk to be appended.
+    pub fn append_chunk(&mut self, chunk: Vec<u8>) {
+        self.chunks.push(chunk);
+    }
+
+    /// Emit a chunk of the result of a running job, stored by the backend right away so that
+    /// it's streamed to the clients (see `JobQueue::stream_result`) before the job is
+    /// finished, e.g. the rows of a large report or the tokens of a generated text.
+    ///
+    /// # Arguments
+    /// * `chunk` - Chunk to be emitted.
     ///
     /// # Errors
-    /// One of `Error` enum.
-    pub fn routine<T: Routine<Context>, Context>(&self) -> Result<T, ApiError> {
-        let routine: T = serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
+    /// `Error::JobNotRunning` if the job isn't run by a queue.
+    pub fn emit_chunk(&self, chunk: Vec<u8>) -> Result<(), ApiError> {
+        self.chunk_sink.send(&self.id, chunk)
+    }
 
-        Ok(routine)
+    /// Set the sink of the chunks of the result shared with the queue while running.
+    ///
+    /// # Arguments
+    /// * `chunk_sink` - Sink of the chunks.
+    pub(crate) fn set_chunk_sink(&mut self, chunk_sink: ChunkSink) {
+        self.chunk_sink = chunk_sink;
     }
 
-    /// Call the underlying routine of the job.
+    /// Attach a metadata to a running job (e.g. the path of a generated file, the number of
+    /// rows processed so far), stored by the backend so that it's queryable (see
+    /// `JobQueue::job_metadata`) before the job is finished.
     ///
     /// # Arguments
-    /// * `messages_channel` - Channel used to send message to the job queue.
+    /// * `messages_channel` - Channel used to send messages to the queue (passed to the
+    ///   routine).
+    /// * `key` - Key of the metadata.
+    /// * `value` - Value to be attached (replaces the previous value of the key).
     ///
     /// # Errors
     /// One of `Error` enum.
-    pub async fn run<T: Routine<Context>, Context>(
+    pub fn save_metadata(
         &self,
-        messages_channel: SharedMessageChannel,
-        context: Option<Shared<Context>>,
-    ) -> Result<Vec<u8>, ApiError> {
-        // Routine information is stored as string so deserialize it
-        let routine: T = serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
-
-        // Call the routine
-        Ok(routine.call(self, messages_channel, context).await?)
+        messages_channel: &SharedMessageChannel,
+        key: impl Into<String>,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        messages_channel
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::SetMetadata(
+                self.id,
+                key.into(),
+                value,
+            )))
+            .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Duration;
+    /// Publish a domain-specific signal of a running job (e.g. an intermediate file ready at
+    /// some path), handed to the command hook of the queue and its subscribers (see
+    /// `Notification::Custom`).
+    ///
+    /// # Arguments
+    /// * `messages_channel` - Channel used to send messages to the queue (passed to the
+    ///   routine).
+    /// * `name` - Name of the command.
+    /// * `payload` - Payload of the command.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn send_command(
+        &self,
+        messages_channel: &SharedMessageChannel,
+        name: impl Into<String>,
+        payload: Vec<u8>,
+    ) -> Result<(), ApiError> {
+        messages_channel
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Custom(self.id, name.into(), payload)))
+            .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))
+    }
+
+    /// Set the suspension state shared with the queue while running.
+    ///
+    /// # Arguments
+    /// * `suspension` - Suspension state.
+    pub(crate) fn set_suspension(&mut self, suspension: Suspension) {
+        self.suspension = suspension;
+    }
+
+    /// Suspension point of a running job: parks the routine while the job is suspended (see
+    /// `JobQueue::suspend_job`), returns right away otherwise. Routines call it between two
+    /// units of work, e.g. at every iteration of a heavy computation.
+    pub async fn yield_if_suspended(&self) {
+        self.suspension.wait_resumed().await;
+    }
+
+    /// Get the private data owned by the job.
+    ///
+    /// # Returns
+    /// The private data casted to the type provided.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn private_data<T>(&self) -> Result<T, ApiError>
+    where
+        T: for<'a> Deserialize<'a> + Serialize,
+    {
+        let data = self.private_data.clone().ok_or(Error::MissingPrivateData)?;
+        let data: T = serde_json::from_str(&data).map_err(|e| api_err!(e.into()))?;
+
+        Ok(data)
+    }
+
+    /// Check that the job holds the private data needed by its routine.
+    ///
+    /// # Arguments
+    /// * `requirements` - Private data needed by the routine.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::UnmetPrivateDataRequirement` for the first requirement
+    /// not met.
+    pub fn check_private_data(
+        &self,
+        requirements: &[PrivateDataRequirement],
+    ) -> Result<(), ApiError> {
+        match requirements
+            .iter()
+            .find(|requirement| !requirement.is_met(self))
+        {
+            Some(requirement) => Err(api_err!(Error::UnmetPrivateDataRequirement(
+                self.kind.clone(),
+                requirement.type_name().to_owned()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Set the private data owned by the job.
+    ///
+    /// # Arguments
+    /// * `value` - Serializable value to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn set_private_data<'a>(
+        &mut self,
+        value: impl Deserialize<'a> + Serialize,
+    ) -> Result<(), ApiError> {
+        self.private_data = Some(serde_json::to_string(&value).map_err(|e| api_err!(e.into()))?);
+
+        Ok(())
+    }
+
+    /// Attach a private data entry to the job, keyed by its type (replaces the previous entry
+    /// of the type), alongside the entries of the other types.
+    ///
+    /// # Arguments
+    /// * `value` - Serializable value to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn insert_data<T: Serialize>(&mut self, value: T) -> Result<(), ApiError> {
+        self.insert_named_data(std::any::type_name::<T>(), value)
+    }
+
+    /// Get the private data entry of a type (see `insert_data`).
+    ///
+    /// # Returns
+    /// The entry casted to the type provided.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::MissingData` if the job holds no entry of the type.
+    pub fn get_data<T>(&self) -> Result<T, ApiError>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        self.get_named_data(std::any::type_name::<T>())
+    }
+
+    /// Attach a private data entry to the job, keyed by name (replaces the previous entry of
+    /// the name), so that several entries of a type don't clobber each other.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    /// * `value` - Serializable value to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn insert_named_data(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Serialize,
+    ) -> Result<(), ApiError> {
+        let data = serde_json::to_string(&value).map_err(|e| api_err!(e.into()))?;
+
+        self.data.insert(name.into(), data, false);
+
+        Ok(())
+    }
+
+    /// Attach a sensitive private data entry to the job, keyed by its type (see
+    /// `insert_sensitive_named_data`).
+    ///
+    /// # Arguments
+    /// * `value` - Serializable value to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn insert_sensitive_data<T: Serialize>(&mut self, value: T) -> Result<(), ApiError> {
+        self.insert_sensitive_named_data(std::any::type_name::<T>(), value)
+    }
+
+    /// Attach a sensitive private data entry to the job, keyed by name: a secret (e.g. a
+    /// short-lived API token) left out of the serialized job (hence of the records written by
+    /// the backends encoding the jobs), redacted in the debug output, and dropped once the job
+    /// is finished. Its memory is then zeroed, which doesn't cover the copies made while
+    /// encoding it nor the ones made by the routine reading it.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    /// * `value` - Serializable value to be stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn insert_sensitive_named_data(
+        &mut self,
+        name: impl Into<String>,
+        value: impl Serialize,
+    ) -> Result<(), ApiError> {
+        let data = serde_json::to_string(&value).map_err(|e| api_err!(e.into()))?;
+
+        self.data.insert(name.into(), data, true);
+
+        Ok(())
+    }
+
+    /// Checks if a private data entry is sensitive.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry (the name of its type for a typed entry).
+    ///
+    /// # Returns
+    /// `true` if flagged as sensitive, `false` otherwise.
+    pub fn is_sensitive(&self, name: &str) -> bool {
+        self.data.is_sensitive(name)
+    }
+
+    /// Get a private data entry by name (see `insert_named_data`).
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    ///
+    /// # Returns
+    /// The entry casted to the type provided.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::MissingData` if the job holds no entry of the name.
+    pub fn get_named_data<T>(&self, name: &str) -> Result<T, ApiError>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        let data = self
+            .data
+            .get(name)
+            .ok_or_else(|| api_err!(Error::MissingData(name.to_owned())))?;
+
+        serde_json::from_str(data).map_err(|e| api_err!(e.into()))
+    }
+
+    /// Remove a private data entry.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry (the name of its type for a typed entry).
+    ///
+    /// # Returns
+    /// `true` if the entry was attached, `false` otherwise.
+    pub fn remove_data(&mut self, name: &str) -> bool {
+        self.data.remove(name)
+    }
+
+    /// Get the tags attached to the job.
+    ///
+    /// # Returns
+    /// The list of tags.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Check if a tag is attached to the job.
+    ///
+    /// # Arguments
+    /// * `tag` - Tag to look for.
+    ///
+    /// # Returns
+    /// `true` if the tag is attached, `false` otherwise.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Attach a tag to the job (does nothing if already attached).
+    ///
+    /// # Arguments
+    /// * `tag` - Tag to be attached.
+    pub fn add_tag(&mut self, tag: impl Into<String>) {
+        let tag = tag.into();
+
+        if !self.has_tag(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
+    /// Get the priority of the job.
+    ///
+    /// # Returns
+    /// The priority (the higher, the more urgent).
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// Set the priority of the job, used by the scheduler strategies.
+    ///
+    /// # Arguments
+    /// * `priority` - Priority to be set (the higher, the more urgent).
+    pub fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    /// Get the deadline of the job.
+    ///
+    /// # Returns
+    /// The timestamp before which the job should be run, if any.
+    pub fn deadline(&self) -> Option<SystemTime> {
+        self.deadline
+    }
+
+    /// Set the deadline of the job, used by the scheduler strategies. A job not started by its
+    /// deadline is dropped, and a job still running is cancelled, both finished as
+    /// `ResultStatus::DeadlineExceeded`.
+    ///
+    /// # Arguments
+    /// * `deadline` - Timestamp before which the job should be run.
+    pub fn set_deadline(&mut self, deadline: SystemTime) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Get the time at which the job is scheduled to run.
+    ///
+    /// # Returns
+    /// The scheduled time (without jitter), `None` if the job runs as soon as enqueued.
+    pub fn run_at(&self) -> Option<SystemTime> {
+        self.run_at
+    }
+
+    /// Set the time at which the job is scheduled to run, the job being kept ready until then.
+    ///
+    /// # Arguments
+    /// * `run_at` - Scheduled time.
+    pub fn set_run_at(&mut self, run_at: SystemTime) {
+        self.run_at = Some(run_at);
+    }
+
+    /// Set the delay after which the job is scheduled to run, from now.
+    ///
+    /// # Arguments
+    /// * `delay` - Delay before the job is run.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.set_run_at(SystemTime::now() + delay);
+    }
+
+    /// Get the maximum random delay added to the scheduled time of the job.
+    ///
+    /// # Returns
+    /// The jitter, zero if disabled.
+    pub fn jitter(&self) -> Duration {
+        self.jitter
+    }
+
+    /// Set the maximum random delay added to the scheduled time of every occurrence of the
+    /// job, so that jobs scheduled at the same time (e.g. after a restart) are spread out.
+    ///
+    /// # Arguments
+    /// * `jitter` - Maximum random delay.
+    pub fn set_jitter(&mut self, jitter: Duration) {
+        self.jitter = jitter;
+        self.jitter_delay = match jitter.is_zero() {
+            true => Duration::ZERO,
+            false => jitter.mul_f64(rand::thread_rng().gen_range(0.0..=1.0)),
+        };
+    }
+
+    /// Get the recurrence of the job.
+    ///
+    /// # Returns
+    /// The recurrence, `None` for a job run once.
+    pub fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence
+    }
+
+    /// Set the recurrence of the job, the next occurrence being enqueued once the job starts
+    /// running. The first occurrence runs at the scheduled time of the job if set, as soon as
+    /// enqueued otherwise.
+    ///
+    /// # Arguments
+    /// * `recurrence` - Recurrence of the job.
+    pub fn set_recurrence(&mut self, recurrence: Recurrence) {
+        self.recurrence = Some(recurrence);
+    }
+
+    /// Get the series of a recurring job, i.e. the ID of its first occurrence.
+    ///
+    /// # Returns
+    /// The ID of the series (the ID of the job itself for the first occurrence).
+    pub fn series(&self) -> Uuid {
+        self.series.unwrap_or(self.id)
+    }
+
+    /// Get the time at which the job is due, jitter included.
+    ///
+    /// # Returns
+    /// The due time, `None` if the job runs as soon as enqueued.
+    pub fn due_at(&self) -> Option<SystemTime> {
+        self.run_at.map(|run_at| run_at + self.jitter_delay)
+    }
+
+    /// Check if the job is due.
+    ///
+    /// # Returns
+    /// `true` if the job can run now, `false` if scheduled later.
+    pub fn is_due(&self) -> bool {
+        self.is_due_at(SystemTime::now())
+    }
+
+    /// Check if the job is due at a given time (see `Clock`).
+    ///
+    /// # Arguments
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// `true` if the job can run at that time, `false` if scheduled later.
+    pub fn is_due_at(&self, now: SystemTime) -> bool {
+        !self.due_at().is_some_and(|due_at| due_at > now)
+    }
+
+    /// Check if this occurrence of a recurring job has been missed, i.e. the following one is
+    /// already due.
+    ///
+    /// # Arguments
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// `true` if missed, `false` otherwise (or if the job is not recurring).
+    pub fn is_misfired(&self, now: SystemTime) -> bool {
+        match (self.recurrence, self.run_at) {
+            (Some(recurrence), Some(run_at)) => recurrence.is_misfired(run_at, now),
+            _ => false,
+        }
+    }
+
+    /// Get the metadata attached to the job.
+    ///
+    /// # Returns
+    /// The key/value pairs attached.
+    pub fn metadata(&self) -> &BTreeMap<String, serde_json::Value> {
+        &self.metadata
+    }
+
+    /// Attach a metadata to the job (replaces the previous value of the key).
+    ///
+    /// # Arguments
+    /// * `key` - Key of the metadata.
+    /// * `value` - Value to be attached.
+    pub fn set_metadata(&mut self, key: impl Into<String>, value: serde_json::Value) {
+        self.metadata.insert(key.into(), value);
+    }
+
+    /// Get the idempotency key of the job.
+    ///
+    /// # Returns
+    /// The key identifying duplicated submissions, if any.
+    pub fn idempotency_key(&self) -> Option<&str> {
+        self.idempotency_key.as_deref()
+    }
+
+    /// Set the idempotency key of the job.
+    ///
+    /// # Arguments
+    /// * `key` - Key identifying duplicated submissions.
+    pub fn set_idempotency_key(&mut self, key: impl Into<String>) {
+        self.idempotency_key = Some(key.into());
+    }
+
+    /// Get the tenant owning the job.
+    ///
+    /// # Returns
+    /// The tenant, if any.
+    pub fn tenant(&self) -> Option<&str> {
+        self.tenant.as_deref()
+    }
+
+    /// Set the tenant owning the job, whose quota applies to the job (see `TenantQuotas`).
+    ///
+    /// # Arguments
+    /// * `tenant` - Tenant owning the job.
+    pub fn set_tenant(&mut self, tenant: impl Into<String>) {
+        self.tenant = Some(tenant.into());
+    }
+
+    /// Get the named queue of the job.
+    ///
+    /// # Returns
+    /// The name of the queue, `DEFAULT_QUEUE` if not set.
+    pub fn queue(&self) -> &str {
+        self.queue.as_deref().unwrap_or(DEFAULT_QUEUE)
+    }
+
+    /// Set the named queue of the job, whose settings apply to the job (see `NamedQueues`).
+    ///
+    /// # Arguments
+    /// * `queue` - Name of the queue.
+    pub fn set_queue(&mut self, queue: impl Into<String>) {
+        self.queue = Some(queue.into());
+    }
+
+    /// Get the capabilities required from the worker running the job.
+    ///
+    /// # Returns
+    /// The capabilities required.
+    pub fn requirements(&self) -> &[String] {
+        &self.requirements
+    }
+
+    /// Set the capabilities required from the worker running the job (see `Capabilities`): a
+    /// worker of a shared backend only claims the job if it advertises all of them.
+    ///
+    /// # Arguments
+    /// * `requirements` - Capabilities required.
+    pub fn set_requirements<T: Into<String>>(&mut self, requirements: impl IntoIterator<Item = T>) {
+        self.requirements = requirements.into_iter().map(Into::into).collect();
+    }
+
+    /// Get the trace context of the span enqueuing the job.
+    ///
+    /// # Returns
+    /// The trace context, `None` if not traced.
+    pub fn trace_context(&self) -> Option<&TraceContext> {
+        self.trace_context.as_ref()
+    }
+
+    /// Set the trace context of the span enqueuing the job, restored as the parent of the span
+    /// running it by the trace propagator of the queue (see `JobQueue::set_trace_propagator`).
+    ///
+    /// # Arguments
+    /// * `context` - Trace context.
+    pub fn set_trace_context(&mut self, context: TraceContext) {
+        self.trace_context = Some(context);
+    }
+
+    /// Attach a context to the job, e.g. the configuration of the tenant owning it. Resolved by
+    /// the context resolver of the queue (see `JobQueue::set_context_resolver`) into the
+    /// context passed to the routine, in place of (or merged with) the context of the queue.
+    /// Without resolver, the routine gets the context of the queue and reads the one of the
+    /// job with `context`.
+    ///
+    /// # Arguments
+    /// * `context` - Context (or patch of the context of the queue), stored with the job.
+    ///
+    /// # Returns
+    /// The updated job.
+    ///
+    /// # Errors
+    /// One of `Error` enum if the context doesn't serialize.
+    pub fn with_context(self, context: impl Serialize) -> Result<Self, ApiError> {
+        let context = serde_json::to_value(context).map_err(|e| api_err!(e.into()))?;
+
+        Ok(Self {
+            context: Some(context),
+            ..self
+        })
+    }
+
+    /// Get the context attached to the job.
+    ///
+    /// # Returns
+    /// The context (typed), `None` if not set.
+    ///
+    /// # Errors
+    /// One of `Error` enum if the context doesn't deserialize into the type.
+    pub fn context<T: for<'a> Deserialize<'a>>(&self) -> Result<Option<T>, ApiError> {
+        self.context
+            .as_ref()
+            .map(|context| T::deserialize(context).map_err(|e| api_err!(e.into())))
+            .transpose()
+    }
+
+    /// Get the context attached to the job, as stored.
+    ///
+    /// # Returns
+    /// The context, `None` if not set.
+    pub(crate) fn raw_context(&self) -> Option<&serde_json::Value> {
+        self.context.as_ref()
+    }
+
+    /// Set the named queue of the job, unless already set.
+    ///
+    /// # Arguments
+    /// * `queue` - Name of the queue the kind of the job is routed to.
+    pub(crate) fn route_to(&mut self, queue: &str) {
+        if self.queue.is_none() {
+            self.queue = Some(queue.to_owned());
+        }
+    }
+
+    /// Set a callback called with the outcome of the job once finished (successfully, in error,
+    /// cancelled...), in place of the previous one. The callback lives in the process creating
+    /// the job: it isn't called when the job is run by another process.
+    ///
+    /// # Arguments
+    /// * `callback` - Callback called with the outcome of the job.
+    pub fn set_on_complete(&mut self, callback: impl FnOnce(&Outcome) + Send + 'static) {
+        let key = Uuid::now_v1(&GROUP_ID);
+
+        completion::register(key, Box::new(callback));
+
+        if let Some(previous) = self.on_complete.replace(key) {
+            completion::take(&previous);
+        }
+    }
+
+    /// Set a routine run by a follow-up job enqueued once the job is finished (successfully, in
+    /// error, cancelled...), its outcome given to the follow-up job as metadata (see
+    /// `outcome`). Unlike `set_on_complete`, the follow-up job is enqueued by whichever process
+    /// runs the job.
+    ///
+    /// # Arguments
+    /// * `routine` - Routine of the follow-up job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn set_on_complete_routine<Context>(
+        &mut self,
+        routine: impl Routine<Context>,
+    ) -> Result<(), ApiError> {
+        self.on_complete_job = Some(Box::new(Job::new(routine)?));
+
+        Ok(())
+    }
+
+    /// Get the outcome of the job this follow-up job was enqueued for (see
+    /// `set_on_complete_routine`).
+    ///
+    /// # Returns
+    /// The outcome, if a follow-up job.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.metadata
+            .get(OUTCOME_METADATA)
+            .and_then(|outcome| serde_json::from_value(outcome.clone()).ok())
+    }
+
+    /// Check if something awaits the outcome of the job (see `set_on_complete`).
+    ///
+    /// # Returns
+    /// `true` if a callback or a follow-up job is set, `false` otherwise.
+    pub(crate) fn awaits_outcome(&self) -> bool {
+        self.on_complete.is_some() || self.on_complete_job.is_some()
+    }
+
+    /// Builds the outcome of the job run inline (see `inline::run`).
+    ///
+    /// # Arguments
+    /// * `result` - Result of the job.
+    ///
+    /// # Returns
+    /// The outcome of the job.
+    pub(crate) fn outcome_of(&self, result: &Result<Vec<u8>, ApiError>) -> Outcome {
+        Outcome {
+            id: self.id,
+            kind: self.kind.clone(),
+            status: match result {
+                Ok(_) => ResultStatus::Success,
+                Err(_) => ResultStatus::Error,
+            },
+            result: result.as_ref().cloned().unwrap_or_default(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Take what awaits the outcome of the job, once finished.
+    ///
+    /// # Arguments
+    /// * `outcome` - Outcome of the job.
+    ///
+    /// # Returns
+    /// The follow-up job, its outcome set, if any.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn complete(&self, outcome: &Outcome) -> Result<Option<Job>, ApiError> {
+        if let Some(callback) = self.on_complete.as_ref().and_then(completion::take) {
+            callback(outcome);
+        }
+
+        let Some(follow_up) = &self.on_complete_job else {
+            return Ok(None);
+        };
+
+        // With a new ID, the job possibly completing several times (retries, occurrences...)
+        let mut follow_up = follow_up.retry();
+
+        follow_up.set_metadata(
+            OUTCOME_METADATA,
+            serde_json::to_value(outcome).map_err(|e| api_err!(e.into()))?,
+        );
+
+        Ok(Some(follow_up))
+    }
+
+    /// Get the namespace of the queue storing the job.
+    ///
+    /// # Returns
+    /// The namespace.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Set the namespace of the queue storing the job (done by the backends when scheduled).
+    ///
+    /// # Arguments
+    /// * `namespace` - Namespace to be set.
+    pub fn set_namespace(&mut self, namespace: impl Into<String>) {
+        self.namespace = namespace.into();
+    }
+
+    /// Get yield points for a compute-heavy routine, to stay fair on the runtime shared with
+    /// the other jobs and be cancellable (see `YieldPoints`).
+    ///
+    /// # Arguments
+    /// * `every` - Number of ticks between two yields.
+    ///
+    /// # Returns
+    /// An instance of `YieldPoints`.
+    pub fn yield_every(&self, every: u64) -> YieldPoints {
+        YieldPoints::new(every)
+    }
+
+    /// Get the identifier of the worker that claimed the job.
+    ///
+    /// # Returns
+    /// The worker ID, `None` if the job is not claimed.
+    pub fn worker(&self) -> Option<&str> {
+        self.worker.as_deref()
+    }
+
+    /// Get the timestamp of the last heartbeat of the worker that claimed the job.
+    ///
+    /// # Returns
+    /// The timestamp, `None` if the job is not claimed.
+    pub fn heartbeat(&self) -> Option<SystemTime> {
+        self.heartbeat
+    }
+
+    /// Get the timestamp at which the lease of the worker that claimed the job expires.
+    ///
+    /// # Returns
+    /// The timestamp, `None` if the job is not leased.
+    pub fn lease_until(&self) -> Option<SystemTime> {
+        self.lease_until
+    }
+
+    /// Check if the job can be claimed by a worker: ready and not claimed, or not finished
+    /// and whose lease has expired (visibility timeout).
+    ///
+    /// # Returns
+    /// `true` if claimable, `false` otherwise.
+    pub fn is_claimable(&self) -> bool {
+        match self.status {
+            Status::Ready if self.worker.is_none() => self.is_due(),
+            Status::Ready | Status::Running | Status::Suspended => self
+                .lease_until
+                .is_some_and(|lease_until| SystemTime::now() >= lease_until),
+            _ => false,
+        }
+    }
+
+    /// Claim the job for a worker (done by the backends). A job whose lease has expired is
+    /// made ready again first.
+    ///
+    /// # Arguments
+    /// * `worker_id` - Identifier of the worker.
+    /// * `lease` - Duration of the lease.
+    pub fn claim(&mut self, worker_id: impl Into<String>, lease: Duration) {
+        if matches!(self.status, Status::Running | Status::Suspended) {
+            self.requeue();
+        }
+
+        self.worker = Some(worker_id.into());
+        self.extend_lease(lease);
+    }
+
+    /// Extend the lease of the worker that claimed the job (done by the backends), which
+    /// is also its heartbeat.
+    ///
+    /// # Arguments
+    /// * `lease` - Duration of the lease, from now.
+    pub fn extend_lease(&mut self, lease: Duration) {
+        let now = SystemTime::now();
+
+        self.heartbeat = Some(now);
+        self.lease_until = Some(now + lease);
+    }
+
+    /// Release the lease of the worker that claimed the job (done by the backends). A job not
+    /// finished is made ready again, to be claimed by another worker.
+    pub fn release(&mut self) {
+        if matches!(
+            self.status,
+            Status::Ready | Status::Running | Status::Suspended
+        ) {
+            self.requeue();
+        }
+
+        self.lease_until = None;
+    }
+
+    /// Check if the worker that claimed the job is considered dead.
+    ///
+    /// # Arguments
+    /// * `timeout` - Duration after which a heartbeat is stale.
+    ///
+    /// # Returns
+    /// `true` if the job is claimed, not finished and its heartbeat is stale, `false`
+    /// otherwise.
+    pub fn is_orphaned(&self, timeout: Duration) -> bool {
+        matches!(
+            self.status,
+            Status::Ready | Status::Running | Status::Suspended
+        ) && self.heartbeat.is_some_and(|heartbeat| {
+            SystemTime::now()
+                .duration_since(heartbeat)
+                .is_ok_and(|elapsed| elapsed >= timeout)
+        })
+    }
+
+    /// Make a claimed job ready again so that another worker can claim it (done by the
+    /// backends when the worker died).
+    pub fn requeue(&mut self) {
+        if matches!(self.status, Status::Running | Status::Suspended) {
+            self.add_lost_attempt(ResultStatus::Interrupted);
+        }
+
+        self.status = Status::Ready;
+        self.step = 0;
+        self.worker = None;
+        self.heartbeat = None;
+        self.lease_until = None;
+        self.payload.timestamps.started = SystemTime::UNIX_EPOCH;
+    }
+
+    /// Finish a claimed job as interrupted (done by the backends when the worker died).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn interrupt(&mut self) -> Result<(), ApiError> {
+        if matches!(self.status, Status::Running | Status::Suspended) {
+            self.add_lost_attempt(ResultStatus::Interrupted);
+        }
+
+        self.set_status(Status::Finished(ResultStatus::Interrupted))
+    }
+
+    /// Record the attempt of a worker that lost the job.
+    ///
+    /// # Arguments
+    /// * `status` - Result of the attempt.
+    fn add_lost_attempt(&mut self, status: ResultStatus) {
+        self.add_attempt(Attempt {
+            started: self.payload.timestamps.started,
+            finished: SystemTime::now(),
+            status,
+            error: Some(Error::LeaseLost(self.id).to_string()),
+            worker: self.worker.clone(),
+        });
+    }
+
+    /// Decode a job from the cross-language wire format (see `wire` module).
+    /// The routine is checked against the routine type so that a job that cannot be run is
+    /// rejected when decoded.
+    ///
+    /// # Arguments
+    /// * `bytes` - Job encoded in the wire format.
+    ///
+    /// # Returns
+    /// An `Job` instance.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn from_wire<T: Routine<Context>, Context>(bytes: &[u8]) -> Result<Self, ApiError> {
+        let wire: WireJob = serde_json::from_slice(bytes).map_err(|e| api_err!(e.into()))?;
+
+        if wire.version == 0 || wire.version > WIRE_VERSION {
+            return Err(api_err!(Error::UnsupportedWireVersion(wire.version)));
+        }
+
+        let routine: T = serde_json::from_value(wire.routine()).map_err(|e| api_err!(e.into()))?;
+        let mut job = Self::new(routine)?;
+
+        if let Some(id) = wire.id {
+            job.id = id;
+        }
+
+        job.metadata = wire.metadata;
+        job.idempotency_key = wire.idempotency_key;
+        job.deadline = wire.deadline.map(Into::into);
+
+        Ok(job)
+    }
+
+    /// Encode the job in the cross-language wire format (see `wire` module).
+    ///
+    /// # Returns
+    /// The job encoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn to_wire(&self) -> Result<Vec<u8>, ApiError> {
+        if self.is_local() {
+            return Err(api_err!(Error::LocalRoutine(self.id)));
+        }
+
+        let routine: serde_json::Value =
+            serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
+        let (kind, args) = WireJob::split_routine(routine);
+
+        let wire = WireJob {
+            version: WIRE_VERSION,
+            id: Some(self.id),
+            kind,
+            args,
+            metadata: self.metadata.clone(),
+            idempotency_key: self.idempotency_key.clone(),
+            deadline: self.deadline.map(Into::into),
+        };
+
+        serde_json::to_vec(&wire).map_err(|e| api_err!(e.into()))
+    }
+
+    /// Upgrade the routine of the job if it no longer deserializes against the routine type,
+    /// or if it's older than the routine deserialized. Local and registered jobs are left
+    /// untouched.
+    ///
+    /// # Arguments
+    /// * `migrator` - Migrator of the routines.
+    ///
+    /// # Returns
+    /// `true` if the routine has been migrated, `false` if up to date.
+    ///
+    /// # Errors
+    /// One of `Error` enum, raised by the migrator or when the migrated routine doesn't
+    /// deserialize.
+    pub fn migrate<T: Routine<Context>, Context>(
+        &mut self,
+        migrator: &dyn RoutineMigrator,
+    ) -> Result<bool, ApiError> {
+        if self.is_local() || self.is_registered() {
+            return Ok(false);
+        }
+
+        let routine: serde_json::Value =
+            serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
+
+        let up_to_date = serde_json::from_value::<T>(routine.clone())
+            .is_ok_and(|current| current.version() <= self.routine_version);
+
+        if up_to_date {
+            return Ok(false);
+        }
+
+        let routine = migrator.migrate(&self.kind, self.routine_version, routine)?;
+        let current: T = serde_json::from_value(routine.clone()).map_err(|e| api_err!(e.into()))?;
+
+        self.kind = Self::routine_kind(&routine);
+        self.routine = routine.to_string();
+        self.routine_version = current.version();
+
+        Ok(true)
+    }
+
+    /// Encode the job as a record stored by a backend, its routine and private data being
+    /// embedded as structured values.
+    ///
+    /// # Arguments
+    /// * `codec` - Format of the record.
+    ///
+    /// # Returns
+    /// The record encoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn encode(&self, codec: &dyn Codec) -> Result<Vec<u8>, ApiError> {
+        self.encode_with(codec, &Compression::default(), &Encryption::default())
+    }
+
+    /// Encode the job as a record stored by a backend, its routine, private data and result
+    /// being compressed when larger than the threshold of the compression policy, and its
+    /// private data and result encrypted if enabled.
+    ///
+    /// # Arguments
+    /// * `codec` - Format of the record.
+    /// * `compression` - Compression policy of the entries.
+    /// * `encryption` - Encryption policy of the private data and result.
+    ///
+    /// # Returns
+    /// The record encoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn encode_with(
+        &self,
+        codec: &dyn Codec,
+        compression: &Compression,
+        encryption: &Encryption,
+    ) -> Result<Vec<u8>, ApiError> {
+        let mut record = self.clone();
+
+        // Large entries compressed, the other ones embedded as structured values
+        if let Some(entry) = compression.pack(self.routine.as_bytes())? {
+            record.packed.insert("routine".to_owned(), entry);
+            record.routine = String::new();
+        }
+
+        if let Some(private_data) = &self.private_data {
+            let entry = Self::store_entry(private_data.as_bytes(), compression, encryption)?;
+
+            if record.stash("private_data", entry) {
+                record.private_data = None;
+            }
+        }
+
+        // Entries of the private data store sealed like the private data, the sensitive ones
+        // not being serialized
+        for (name, data, sensitive) in self.data.iter() {
+            if sensitive {
+                continue;
+            }
+
+            let entry = Self::store_entry(data.as_bytes(), compression, encryption)?;
+
+            if record.stash(&format!("data.{name}"), entry) {
+                record.data.insert(name.to_owned(), String::new(), false);
+            }
+        }
+
+        let entry = Self::store_entry(&self.payload.result, compression, encryption)?;
+
+        if record.stash("result", entry) {
+            record.payload.result = vec![];
+        }
+
+        codec.encode(&record)
+    }
+
+    /// Decode a job from a record stored by a backend (see `encode`).
+    ///
+    /// # Arguments
+    /// * `bytes` - Record encoded.
+    /// * `codec` - Format of the record.
+    ///
+    /// # Returns
+    /// The job decoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn decode(bytes: &[u8], codec: &dyn Codec) -> Result<Self, ApiError> {
+        Self::decode_with(
+            bytes,
+            codec,
+            &Compression::default(),
+            &Encryption::default(),
+        )
+    }
+
+    /// Decode a job from a record stored by a backend, decompressing and decrypting its
+    /// entries with the compressors and keys recorded (see `encode_with`).
+    ///
+    /// # Arguments
+    /// * `bytes` - Record encoded.
+    /// * `codec` - Format of the record.
+    /// * `compression` - Compression policy able to read the entries.
+    /// * `encryption` - Encryption policy holding the keys of the entries.
+    ///
+    /// # Returns
+    /// The job decoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn decode_with(
+        bytes: &[u8],
+        codec: &dyn Codec,
+        compression: &Compression,
+        encryption: &Encryption,
+    ) -> Result<Self, ApiError> {
+        let mut job = codec.decode(bytes)?;
+
+        let mut packed = std::mem::take(&mut job.packed);
+        let mut sealed = std::mem::take(&mut job.sealed);
+
+        let mut entry = |field: &str| -> Result<Option<Vec<u8>>, ApiError> {
+            if let Some(entry) = sealed.remove(field) {
+                let bytes = encryption.open(&entry)?;
+
+                return match entry.compressor {
+                    Some(compressor) => Ok(Some(compression.unpack(&Packed {
+                        compressor,
+                        data: bytes,
+                    })?)),
+                    None => Ok(Some(bytes)),
+                };
+            }
+
+            packed
+                .remove(field)
+                .map(|entry| compression.unpack(&entry))
+                .transpose()
+        };
+
+        // Routine and private data stored as JSON strings in the job
+        let text = |bytes: Vec<u8>| -> Result<String, ApiError> {
+            String::from_utf8(bytes).map_err(|e| api_err!(Error::Custom(e.to_string())))
+        };
+
+        if let Some(bytes) = entry("routine")? {
+            job.routine = text(bytes)?;
+        }
+
+        if let Some(bytes) = entry("private_data")? {
+            job.private_data = Some(text(bytes)?);
+        }
+
+        let names = job
+            .data
+            .iter()
+            .map(|(name, _, _)| name.to_owned())
+            .collect::<Vec<_>>();
+
+        for name in names {
+            if let Some(bytes) = entry(&format!("data.{name}"))? {
+                job.data.insert(name, text(bytes)?, false);
+            }
+        }
+
+        if let Some(bytes) = entry("result")? {
+            job.payload.result = bytes;
+        }
+
+        Ok(job)
+    }
+
+    /// Moves an entry of a record to the compressed or encrypted entries.
+    ///
+    /// # Arguments
+    /// * `field` - Field of the entry.
+    /// * `entry` - Entry as stored.
+    ///
+    /// # Returns
+    /// `true` if moved (the field being cleared by the caller), `false` if kept as is.
+    fn stash(&mut self, field: &str, entry: StoredEntry) -> bool {
+        match entry {
+            StoredEntry::Plain => return false,
+            StoredEntry::Packed(entry) => {
+                self.packed.insert(field.to_owned(), entry);
+            }
+            StoredEntry::Sealed(entry) => {
+                self.sealed.insert(field.to_owned(), entry);
+            }
+        }
+
+        true
+    }
+
+    /// Compresses and encrypts an entry of a record, according to the policies.
+    ///
+    /// # Arguments
+    /// * `bytes` - Entry to be stored.
+    /// * `compression` - Compression policy of the entries.
+    /// * `encryption` - Encryption policy of the entries.
+    ///
+    /// # Returns
+    /// The entry as stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn store_entry(
+        bytes: &[u8],
+        compression: &Compression,
+        encryption: &Encryption,
+    ) -> Result<StoredEntry, ApiError> {
+        let packed = compression.pack(bytes)?;
+
+        // Compressed before being encrypted, as ciphertexts don't compress
+        if encryption.is_enabled() {
+            let sealed = match packed {
+                Some(packed) => encryption.seal(&packed.data, Some(packed.compressor))?,
+                None => encryption.seal(bytes, None)?,
+            };
+
+            return Ok(sealed.map_or(StoredEntry::Plain, StoredEntry::Sealed));
+        }
+
+        Ok(packed.map_or(StoredEntry::Plain, StoredEntry::Packed))
+    }
+
+    /// Get the routine for this job.
+    ///
+    /// # Returns
+    /// The routine (typed).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn routine<T: Routine<Context>, Context>(&self) -> Result<T, ApiError> {
+        let routine: T = serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
+
+        Ok(routine)
+    }
+
+    /// Call the underlying routine of the job.
+    ///
+    /// # Arguments
+    /// * `messages_channel` - Channel used to send message to the job queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn run<T, Context>(
+        &self,
+        messages_channel: SharedMessageChannel,
+        context: Option<ContextMode<Context>>,
+    ) -> Result<Vec<u8>, ApiError>
+    where
+        T: Routine<Context> + Sync + 'static,
+        Context: Send + 'static,
+    {
+        if let Some(key) = &self.local {
+            let routine = local_routine::take::<Context>(key)?;
+
+            return Ok(routine(context.and_then(ContextMode::into_exclusive)).await?);
+        }
+
+        // Routine information is stored as string so deserialize it
+        let routine: T = serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
+
+        self.call(routine, messages_channel, context).await
+    }
+
+    /// Get the arguments of the routine registered by name run by this job.
+    ///
+    /// # Returns
+    /// The arguments (typed).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn registered_args<T: for<'a> Deserialize<'a>>(&self) -> Result<T, ApiError> {
+        let mut routine: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&self.routine).map_err(|e| api_err!(e.into()))?;
+
+        let args = routine.remove(&self.kind).unwrap_or_default();
+
+        serde_json::from_value(args).map_err(|e| api_err!(e.into()))
+    }
+
+    /// Call a routine on behalf of the job, on the threads matching its execution class.
+    ///
+    /// # Arguments
+    /// * `routine` - Routine to be called.
+    /// * `messages_channel` - Channel used to send message to the job queue.
+    /// * `context` - Context shared by the routines.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) async fn call<T, Context>(
+        &self,
+        routine: T,
+        messages_channel: SharedMessageChannel,
+        context: Option<ContextMode<Context>>,
+    ) -> Result<Vec<u8>, ApiError>
+    where
+        T: Routine<Context> + Sync + 'static,
+        Context: Send + 'static,
+    {
+        match routine.exec_class() {
+            ExecClass::Async => Ok(routine
+                .call_with_context(self, messages_channel, context)
+                .await?),
+            ExecClass::Blocking => {
+                let job = self.clone();
+                let handle = tokio::runtime::Handle::current();
+
+                let result = tokio::task::spawn_blocking(move || {
+                    handle.block_on(routine.call_with_context(&job, messages_channel, context))
+                })
+                .await
+                .map_err(|e| api_err!(e.into()))?;
+
+                Ok(result?)
+            }
+        }
+    }
+
+    /// Extract the kind of a routine from its serialized value.
+    ///
+    /// # Arguments
+    /// * `routine` - Routine serialized as JSON value.
+    ///
+    /// # Returns
+    /// The name of the variant (unit variants are serialized as string, others as a single-key
+    /// object).
+    fn routine_kind(routine: &serde_json::Value) -> String {
+        match routine {
+            serde_json::Value::String(name) => name.to_owned(),
+            serde_json::Value::Object(map) if map.len() == 1 => {
+                map.keys().next().cloned().unwrap_or_default()
+            }
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Duration;
     use super::ExpirePolicy;
     use super::Job;
+    use super::SystemTime;
+    use super::WIRE_VERSION;
+    use crate::error::Error;
+    use crate::tests::Context;
 
     use crate::tests::Routines;
 
+    #[test]
+    fn job_kind_is_routine_variant() {
+        let job = Job::new(Routines::Nop).unwrap();
+        assert_eq!(job.kind(), "Nop");
+
+        let routine: Routines = serde_json::from_str(r#"{"SetFlag":{"value":true}}"#).unwrap();
+        let job = Job::new(routine).unwrap();
+        assert_eq!(job.kind(), "SetFlag");
+    }
+
     #[test]
     fn job_default_expiration_policy_on_result_fetch() {
         let job = Job::new(Routines::Nop).unwrap();
@@ -455,4 +2733,53 @@ mod tests {
             ExpirePolicy::OnResultFetch(Duration::from_secs(60))
         );
     }
+
+    #[test]
+    fn job_wire_round_trip() {
+        let routine: Routines = serde_json::from_str(r#"{"SetFlag":{"value":true}}"#).unwrap();
+        let mut job = Job::new(routine).unwrap();
+        job.set_metadata("source", serde_json::json!("billing-api"));
+        job.set_idempotency_key("invoice-42");
+
+        let decoded = Job::from_wire::<Routines, Context>(&job.to_wire().unwrap()).unwrap();
+
+        assert_eq!(decoded.id(), job.id());
+        assert_eq!(decoded.kind(), "SetFlag");
+        assert_eq!(decoded.metadata(), job.metadata());
+        assert_eq!(decoded.idempotency_key(), Some("invoice-42"));
+        assert!(decoded.routine::<Routines, Context>().is_ok());
+    }
+
+    #[test]
+    fn job_from_foreign_wire() {
+        // As produced by a non-Rust producer: no ID, no arguments
+        let bytes = format!(r#"{{"version":{WIRE_VERSION},"kind":"Nop"}}"#);
+        let job = Job::from_wire::<Routines, Context>(bytes.as_bytes()).unwrap();
+        assert_eq!(job.kind(), "Nop");
+        assert!(job.metadata().is_empty());
+        assert_eq!(job.idempotency_key(), None);
+        assert_eq!(job.deadline(), None);
+
+        // Deadline in milliseconds since the Unix epoch
+        let bytes = br#"{"version":1,"kind":"Nop","deadline":1723456789000}"#;
+        let job = Job::from_wire::<Routines, Context>(bytes).unwrap();
+        assert_eq!(
+            job.deadline(),
+            Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1_723_456_789_000))
+        );
+        assert!(String::from_utf8(job.to_wire().unwrap())
+            .unwrap()
+            .contains(r#""deadline":1723456789000"#));
+
+        // Unknown kind
+        let bytes = br#"{"version":1,"kind":"Unknown","args":{}}"#;
+        assert!(Job::from_wire::<Routines, Context>(bytes).is_err());
+
+        // Unsupported version
+        let bytes = br#"{"version":99,"kind":"Nop"}"#;
+        assert!(matches!(
+            Job::from_wire::<Routines, Context>(bytes).map_err(|e| *e),
+            Err(Error::UnsupportedWireVersion(99))
+        ));
+    }
 }
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Summary of a job returned by the listing APIs, without its routine, payload nor private
+/// data (see `JobQueue::job` to get the full job).
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct JobInfo {
+    /// Unique identifier of the job.
+    pub id: Uuid,
+
+    /// Kind of the job.
+    pub kind: String,
+
+    /// Status of the job.
+    pub status: Status,
+
+    /// Progression of the job.
+    pub progression: Progression,
+
+    /// Priority of the job (the higher, the more urgent).
+    pub priority: i32,
+
+    /// Timestamps of the lifecycle of the job.
+    pub timestamps: Timestamps,
+
+    /// Tags of the job.
+    pub tags: Vec<String>,
+
+    /// Named queue of the job.
+    #[serde(default = "default_queue")]
+    pub queue: String,
+}
+
+/// Gets the queue of the jobs listed before the named queues.
+///
+/// # Returns
+/// `DEFAULT_QUEUE`.
+fn default_queue() -> String {
+    DEFAULT_QUEUE.to_owned()
+}
+
+impl From<&Job> for JobInfo {
+    fn from(job: &Job) -> Self {
+        Self {
+            id: job.id(),
+            kind: job.kind().to_owned(),
+            status: job.status(),
+            progression: job.progression(),
+            priority: job.priority(),
+            timestamps: job.timestamps().to_owned(),
+            tags: job.tags().to_vec(),
+            queue: job.queue().to_owned(),
+        }
+    }
+}
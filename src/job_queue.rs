@@ -1,12 +1,24 @@
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::sync::mpsc::{Receiver, Sender};
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
 use std::thread::JoinHandle;
-use tokio::runtime::Builder;
-use tokio::sync::Mutex as AsyncMutex;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::{Mutex as AsyncMutex, Notify, OwnedMutexGuard};
+use tokio::task::AbortHandle;
 
+use crate::channel::{self, MessageReceiver};
+use crate::idempotency::IdempotencyCache;
 use crate::memory_backend::*;
 use crate::prelude::*;
+use crate::result_stream::ChunkSink;
+use crate::scheduler::{Dispatcher, Room};
+use crate::tombstones::Tombstones;
+use crate::trace_context::TraceMiddleware;
 
 /// Type of messages that can be sent to the job queue.
 #[derive(PartialEq)]
@@ -15,11 +27,11 @@ pub enum Message {
     Command(Cmd),
 
     /// Job message used to push a new job to be processed.
-    Job(Job),
+    Job(Box<Job>),
 }
 
 /// Commands handled by the thread of the job queue.
-#[derive(PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum Cmd {
     /// Check expirations times and remove jobs if needed.
     CheckExpirations,
@@ -30,6 +42,37 @@ pub enum Cmd {
     /// Set number of steps for a job.
     SetSteps(Uuid, u64),
 
+    /// Evict the oldest finished jobs of every kind, keeping only the given number of them.
+    ApplyRetention(usize),
+
+    /// Dispatch the pending jobs if slots are available.
+    Dispatch,
+
+    /// Release the locks held by dead workers.
+    ReleaseStaleLocks,
+
+    /// Extend the leases of the jobs claimed and claim new ones from the shared backend.
+    Fetch,
+
+    /// Recover the jobs claimed by workers whose heartbeat is stale.
+    ReapOrphans,
+
+    /// Save the checkpoint of a job.
+    SaveCheckpoint(Uuid, Vec<u8>),
+
+    /// Suspend a running job at its next suspension point.
+    Suspend(Uuid),
+
+    /// Resume a suspended job.
+    Resume(Uuid),
+
+    /// Attach a metadata to a job (key and value).
+    SetMetadata(Uuid, String, serde_json::Value),
+
+    /// Domain-specific signal published by a running job (name and payload), handed to the
+    /// command hook and the subscribers (see `Job::send_command`).
+    Custom(Uuid, String, Vec<u8>),
+
     /// Stop the job queue.
     Stop,
 }
@@ -45,6 +88,138 @@ pub enum Notification {
 
     /// Update of the status of a job.
     Status(Uuid, Status),
+
+    /// A job of a shed-first kind has been delayed or dropped because the queue is overloaded.
+    JobShed(Uuid, SheddingAction),
+
+    /// A job has been reaped because it has expired.
+    JobExpired(Uuid),
+
+    /// A job is running longer than expected (see `WatchdogPolicy`), for the given duration.
+    JobStalled(Uuid, Duration),
+
+    /// A lock held by a dead worker (not refreshed in time) has been released.
+    StaleLockReleased(Lock),
+
+    /// Stored pending jobs of a kind no longer deserialize against the routine type.
+    SchemaDrift(SchemaDrift),
+
+    /// A dispatched job cannot be deserialized, the given policy has been applied.
+    JobUndecodable(Uuid, UndecodablePolicy),
+
+    /// The worker of a job died (stale heartbeat), the given policy has been applied.
+    JobOrphaned(Uuid, OrphanPolicy),
+
+    /// The autoscale policy changed the maximum number of jobs in flight (previous and new).
+    Scaled(usize, usize),
+
+    /// A purge run in the background has removed a chunk of jobs.
+    PurgeProgress(Uuid, PurgeProgress),
+
+    /// The queue has changed of state (`State::Stopped` once its thread has exited).
+    StateChanged(State),
+
+    /// The queue has become idle: no job is pending or running.
+    Idle,
+
+    /// A running job has published a domain-specific signal (name and payload).
+    Custom(Uuid, String, Vec<u8>),
+}
+
+/// Retention policies applied by the queue on finished jobs, in addition to the expire policy
+/// of every job.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum RetentionPolicy {
+    /// Finished jobs are kept until they expire.
+    #[default]
+    KeepAll,
+
+    /// Only the newest finished jobs of every kind are kept, the oldest ones are evicted.
+    KeepLastN(usize),
+}
+
+/// Policies applied on a dispatched job whose routine can no longer be deserialized (e.g.
+/// stored by a previous release).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum UndecodablePolicy {
+    /// The job is run and finishes in error.
+    #[default]
+    Fail,
+
+    /// The job is moved from the backend to the dead letters of the queue.
+    DeadLetter,
+
+    /// The job is left ready in the backend but never dispatched.
+    Quarantine,
+
+    /// The job is removed from the backend.
+    Drop,
+}
+
+/// Policies applied on the jobs claimed by a worker that died (i.e. whose heartbeat is stale).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum OrphanPolicy {
+    /// The job is made ready again, to be claimed by another worker.
+    #[default]
+    Requeue,
+
+    /// The job is finished as `ResultStatus::Interrupted`.
+    Interrupt,
+}
+
+/// Policies applied when removing a job that is not finished yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum RemovePolicy {
+    /// Only a finished job can be removed (fails otherwise).
+    #[default]
+    OnlyFinished,
+
+    /// The job is cancelled (aborted if running) before being removed.
+    CancelAndRemove,
+
+    /// The job is removed once finished, waiting at most the given duration.
+    WaitAndRemove(Duration),
+}
+
+/// Strategies used to evaluate the expirations of the jobs.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum SweepStrategy {
+    /// Expired jobs are reaped by a background sweeper running at the given interval.
+    Interval(Duration),
+
+    /// Expired jobs are reaped when the queue is accessed (no background sweeper).
+    Lazy,
+}
+
+impl Default for SweepStrategy {
+    fn default() -> Self {
+        Self::Interval(Duration::from_secs(1))
+    }
+}
+
+/// Token of a job staged by `JobQueue::prepare`, consumed when the job is confirmed or aborted.
+/// A job whose token is dropped is dropped once stale (see `JobQueue::set_staging_ttl`).
+#[derive(Debug, PartialEq)]
+#[must_use = "a staged job is never run unless confirmed"]
+pub struct Token(Uuid);
+
+impl Token {
+    /// Gets the ID of the job staged.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    pub fn id(&self) -> Uuid {
+        self.0
+    }
+}
+
+/// Job submitted to the queue, once screened (see `JobQueue::screen`).
+enum Screened {
+    /// Job to be admitted in the queue.
+    Admissible(Box<Job>),
+
+    /// Duplicate of a previous submission, not enqueued again.
+    Duplicate(Enqueued),
 }
 
 /// States of the tread running the job queue.
@@ -59,6 +234,193 @@ pub enum State {
 
     /// Going to stop.
     Stopping,
+
+    /// Stopped (i.e. the thread of the queue has been joined).
+    Stopped,
+}
+
+/// Resources shared between the thread of the queue and the jobs it runs.
+struct QueueResources<RoutineType, Context> {
+    /// Backend used to store the list of jobs with their results.
+    backend: SharedBackend<RoutineType, Context>,
+
+    /// Tokio runtime instance with dedicated thread pool.
+    runtime: SharedRuntime,
+
+    /// Notification handler function.
+    notification_handler: SharedNotificationHandler,
+
+    /// Channel used to send messages to the thread of the job queue.
+    messages_channel: SharedMessageChannel,
+
+    /// Context to be passed to every routine.
+    context: Option<ContextMode<Context>>,
+
+    /// Resolver of the context passed to the routines of the jobs holding their own context.
+    context_resolver: Option<ContextResolver<Context>>,
+
+    /// Recent throughput of the queue.
+    throughput: Shared<Throughput>,
+
+    /// Dispatch state of the queue.
+    dispatcher: Shared<Dispatcher>,
+
+    /// Abort handles of the jobs currently dispatched.
+    running: Shared<HashMap<Uuid, AbortHandle>>,
+
+    /// Abort handles of the tasks enforcing the deadlines of the jobs not finished yet.
+    deadlines: Shared<HashMap<Uuid, AbortHandle>>,
+
+    /// Suspension states of the jobs currently running.
+    suspensions: Shared<HashMap<Uuid, Suspension>>,
+
+    /// Policy applied on the jobs that cannot be deserialized.
+    undecodable_policy: UndecodablePolicy,
+
+    /// Jobs moved out of the backend because they cannot be deserialized.
+    dead_letters: Shared<HashMap<Uuid, Job>>,
+
+    /// Jobs left in the backend because they cannot be deserialized.
+    quarantined: Shared<HashSet<Uuid>>,
+
+    /// Identifier of the worker claiming jobs in the backend.
+    worker_id: String,
+
+    /// Kinds of job executed by the worker.
+    kinds: KindFilter,
+
+    /// Capabilities advertised by the worker.
+    capabilities: Capabilities,
+
+    /// Routines registered by name, run by the registered jobs.
+    registry: Arc<RoutineRegistry<Context>>,
+
+    /// Middlewares wrapping the execution of the jobs, the outermost first.
+    middlewares: Vec<SharedMiddleware>,
+
+    /// Hooks called around every execution of a job.
+    hooks: LifecycleHooks,
+
+    /// Clock of the expirations, delays and deadlines of the jobs.
+    clock: SharedClock,
+
+    /// Whether the jobs are claimed from a backend shared with other workers.
+    distributed: bool,
+
+    /// Heartbeat timeout and policy applied on the jobs of dead workers.
+    orphan_reaper: Option<(Duration, OrphanPolicy)>,
+
+    /// Policy of the watchdog flagging the jobs running longer than expected.
+    watchdog: Option<WatchdogPolicy>,
+
+    /// Policy running again the jobs whose routine fails.
+    retry_policy: Option<RetryPolicy>,
+
+    /// Duration of the leases of the jobs claimed.
+    lease: Duration,
+
+    /// Submissions of the jobs holding an idempotency key, with their results.
+    idempotency: Shared<IdempotencyCache>,
+
+    /// Policy writing the large results to a result store.
+    result_offload: Option<ResultOffload>,
+
+    /// Subscriptions to the notifications, tracking the kinds of the jobs enqueued.
+    subscribers: Shared<Subscribers>,
+
+    /// Occurrences of the recurring jobs running, by series.
+    series: Shared<Series>,
+
+    /// Wakes up the callers of `wait_idle` once the queue is idle or stopped.
+    idle: Arc<Notify>,
+
+    /// Generator of the IDs of the jobs created by the queue itself (the ones given at creation
+    /// kept if `None`).
+    id_generator: Option<SharedIdGenerator>,
+}
+
+impl<RoutineType, Context> Clone for QueueResources<RoutineType, Context> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            runtime: self.runtime.clone(),
+            notification_handler: self.notification_handler.clone(),
+            messages_channel: self.messages_channel.clone(),
+            context: self.context.clone(),
+            context_resolver: self.context_resolver.clone(),
+            throughput: self.throughput.clone(),
+            dispatcher: self.dispatcher.clone(),
+            running: self.running.clone(),
+            deadlines: self.deadlines.clone(),
+            suspensions: self.suspensions.clone(),
+            undecodable_policy: self.undecodable_policy,
+            dead_letters: self.dead_letters.clone(),
+            quarantined: self.quarantined.clone(),
+            worker_id: self.worker_id.clone(),
+            kinds: self.kinds.clone(),
+            capabilities: self.capabilities.clone(),
+            registry: self.registry.clone(),
+            middlewares: self.middlewares.clone(),
+            hooks: self.hooks.clone(),
+            clock: self.clock.clone(),
+            distributed: self.distributed,
+            orphan_reaper: self.orphan_reaper,
+            watchdog: self.watchdog.clone(),
+            retry_policy: self.retry_policy,
+            lease: self.lease,
+            idempotency: self.idempotency.clone(),
+            result_offload: self.result_offload.clone(),
+            subscribers: self.subscribers.clone(),
+            series: self.series.clone(),
+            idle: self.idle.clone(),
+            id_generator: self.id_generator.clone(),
+        }
+    }
+}
+
+/// Stop signal of the threads sending the periodic commands of the queue (see `spawn_ticker`),
+/// waking them up while they sleep.
+#[derive(Default)]
+struct Tickers {
+    /// Whether the tickers are stopped.
+    stopped: Mutex<bool>,
+
+    /// Wakes up the tickers sleeping.
+    woken: Condvar,
+}
+
+impl Tickers {
+    /// Sleeps for an interval, unless the tickers are stopped meanwhile.
+    ///
+    /// # Arguments
+    /// * `interval` - Duration to sleep.
+    ///
+    /// # Returns
+    /// `true` if stopped, `false` otherwise.
+    fn sleep(&self, interval: Duration) -> bool {
+        let stopped = self.stopped.lock().unwrap_or_else(|e| e.into_inner());
+
+        *self
+            .woken
+            .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+            .unwrap_or_else(|e| e.into_inner())
+            .0
+    }
+
+    /// Checks if the tickers are stopped.
+    ///
+    /// # Returns
+    /// `true` if stopped, `false` otherwise.
+    fn is_stopped(&self) -> bool {
+        *self.stopped.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Stops the tickers, waking them up.
+    fn stop(&self) {
+        *self.stopped.lock().unwrap_or_else(|e| e.into_inner()) = true;
+
+        self.woken.notify_all();
+    }
 }
 
 /// Structure of a job queue.
@@ -70,7 +432,7 @@ pub struct JobQueue<RoutineType, Context> {
     tx: SharedMessageChannel,
 
     /// Channel used to receive messages from the thread of the job queue.
-    rx: Shared<Receiver<Message>>,
+    rx: Shared<MessageReceiver>,
 
     /// Join handle used to wait the thread of the job queue.
     join_handle: Option<JoinHandle<()>>,
@@ -78,6 +440,22 @@ pub struct JobQueue<RoutineType, Context> {
     /// Join handle used to wait the thread of the expiration checking.
     expiration_join_handle: Option<JoinHandle<()>>,
 
+    /// Join handle used to wait the thread releasing the stale locks.
+    janitor_join_handle: Option<JoinHandle<()>>,
+
+    /// Join handle used to wait the thread fetching jobs from the shared backend.
+    fetch_join_handle: Option<JoinHandle<()>>,
+
+    /// Stop signal of the threads sending the periodic commands.
+    tickers: Arc<Tickers>,
+
+    /// Whether the queue is shutting down on a termination signal.
+    shutdown: Arc<AtomicBool>,
+
+    /// Stops the thread watching the termination signals when dropped.
+    #[cfg(feature = "signals")]
+    signal_watcher: Option<std::sync::mpsc::Sender<()>>,
+
     /// Backend used to store the list of jobs with their results.
     backend: SharedBackend<RoutineType, Context>,
 
@@ -87,8 +465,186 @@ pub struct JobQueue<RoutineType, Context> {
     /// Notification handler function.
     notification_handler: SharedNotificationHandler,
 
+    /// Notification handler set by the client (delivering to the subscriptions too), wrapped by
+    /// the notification handler of the queue when a webhook notifier is registered.
+    #[cfg(feature = "webhook")]
+    client_notification_handler: SharedNotificationHandler,
+
+    /// Notifier posting the notifications to webhooks.
+    #[cfg(feature = "webhook")]
+    webhook_notifier: Option<WebhookNotifier>,
+
     /// Context to be passed to every routine.
-    context: Option<Shared<Context>>,
+    context: Option<ContextMode<Context>>,
+
+    /// Resolver of the context passed to the routines of the jobs holding their own context.
+    context_resolver: Option<ContextResolver<Context>>,
+
+    /// Factory building the context when the queue is started.
+    context_factory: Option<ContextFactory<Context>>,
+
+    /// Recent throughput of the queue.
+    throughput: Shared<Throughput>,
+
+    /// Retention policy applied on finished jobs.
+    retention: RetentionPolicy,
+
+    /// Durations the results are kept once the jobs are finished, by kind.
+    result_ttls: HashMap<String, Duration>,
+
+    /// Timeout of the jobs enqueued without deadline.
+    default_timeout: Option<Duration>,
+
+    /// Strategy used to evaluate the expirations.
+    sweep_strategy: SweepStrategy,
+
+    /// Strategy deciding which eligible job runs next (moved to the dispatcher at start).
+    scheduler: Box<dyn SchedulerStrategy>,
+
+    /// Maximum number of jobs running at the same time (unlimited if `None`).
+    max_in_flight: Option<usize>,
+
+    /// Policy used to delay or drop non-critical jobs when overloaded.
+    shedding: Option<SheddingPolicy>,
+
+    /// Quotas of the tenants owning the jobs.
+    tenant_quotas: Option<TenantQuotas>,
+
+    /// Named queues hosted by the queue.
+    named_queues: Option<NamedQueues>,
+
+    /// Maximum number of jobs waiting to be dispatched.
+    max_pending: Option<usize>,
+
+    /// Policy letting the urgent jobs take the slot of the running jobs of lower priority.
+    preemption: Option<PreemptionPolicy>,
+
+    /// Policy adjusting the maximum number of jobs in flight to the depth of the queue.
+    autoscale: Option<AutoscalePolicy>,
+
+    /// Dispatch state of the queue.
+    dispatcher: Shared<Dispatcher>,
+
+    /// Abort handles of the jobs currently dispatched.
+    running: Shared<HashMap<Uuid, AbortHandle>>,
+
+    /// Abort handles of the tasks enforcing the deadlines of the jobs not finished yet.
+    deadlines: Shared<HashMap<Uuid, AbortHandle>>,
+
+    /// Suspension states of the jobs currently running.
+    suspensions: Shared<HashMap<Uuid, Suspension>>,
+
+    /// Namespace isolating the jobs of the queue in the backend.
+    namespace: String,
+
+    /// Interval at which the stale locks are released (disabled if `None`).
+    lock_janitor: Option<Duration>,
+
+    /// Jobs staged by `prepare`, waiting to be confirmed or aborted, with the time they were
+    /// staged.
+    staged: Shared<HashMap<Uuid, (Job, SystemTime)>>,
+
+    /// Duration after which a job staged but neither confirmed nor aborted is dropped.
+    staging_ttl: Duration,
+
+    /// Number of stored pending jobs checked against the routine type at start.
+    schema_check_sample: usize,
+
+    /// Subscriptions to the notifications, called before the notification handler.
+    subscribers: Shared<Subscribers>,
+
+    /// Policy applied on the jobs that cannot be deserialized.
+    undecodable_policy: UndecodablePolicy,
+
+    /// Jobs moved out of the backend because they cannot be deserialized.
+    dead_letters: Shared<HashMap<Uuid, Job>>,
+
+    /// Jobs left in the backend because they cannot be deserialized.
+    quarantined: Shared<HashSet<Uuid>>,
+
+    /// Identifier of the worker claiming jobs in the backend.
+    worker_id: String,
+
+    /// Kinds of job executed by the worker.
+    kinds: KindFilter,
+
+    /// Capabilities advertised by the worker.
+    capabilities: Capabilities,
+
+    /// Routines registered by name, run by the registered jobs.
+    registry: Arc<RoutineRegistry<Context>>,
+
+    /// Middlewares wrapping the execution of the jobs, the outermost first.
+    middlewares: Vec<SharedMiddleware>,
+
+    /// Hooks called around every execution of a job.
+    hooks: LifecycleHooks,
+
+    /// Clock of the expirations, delays and deadlines of the jobs (the system clock if `None`).
+    clock: Option<SharedClock>,
+
+    /// Propagator of the trace contexts of the jobs.
+    trace_propagator: Option<SharedTracePropagator>,
+
+    /// Generator of the IDs of the jobs enqueued (the ones given at creation kept if `None`).
+    id_generator: Option<SharedIdGenerator>,
+
+    /// Interceptors inspecting the jobs enqueued, in order.
+    interceptors: Vec<SharedEnqueueInterceptor>,
+
+    /// Migrator of the routines stored by an older release (set on the backend at start).
+    migrator: Option<SharedMigrator>,
+
+    /// Format of the job records stored by the backend (set on the backend at start, JSON if
+    /// `None`).
+    codec: Option<SharedCodec>,
+
+    /// Compression of the large entries of the job records stored by the backend (set on the
+    /// backend at start).
+    compression: Option<Compression>,
+
+    /// Encryption of the private data and results of the job records stored by the backend
+    /// (set on the backend at start).
+    encryption: Option<Encryption>,
+
+    /// Policy writing the large results to a result store, the backend keeping a reference.
+    result_offload: Option<ResultOffload>,
+
+    /// Heartbeat timeout after which the jobs of a dead worker are recovered, with the policy
+    /// applied on them.
+    orphan_reaper: Option<(Duration, OrphanPolicy)>,
+
+    /// Join handle used to wait the thread recovering the orphaned jobs.
+    reaper_join_handle: Option<JoinHandle<()>>,
+
+    /// Policy of the watchdog flagging the jobs running longer than expected.
+    watchdog: Option<WatchdogPolicy>,
+
+    /// Policy running again the jobs whose routine fails.
+    retry_policy: Option<RetryPolicy>,
+
+    /// Interval at which jobs are claimed from a backend shared with other workers (jobs are
+    /// dispatched locally if `None`).
+    fetch_interval: Option<Duration>,
+
+    /// Duration of the leases of the jobs claimed.
+    lease: Duration,
+
+    /// Submissions of the jobs holding an idempotency key, with their results.
+    idempotency: Shared<IdempotencyCache>,
+
+    /// Maximum duration given to the jobs taken to finish once the queue is stopped (stopped
+    /// immediately if `None`).
+    drain_timeout: Option<Duration>,
+
+    /// Jobs removed while soft-delete is enabled, to be restored.
+    tombstones: Shared<Tombstones>,
+
+    /// Occurrences of the recurring jobs running, by series.
+    series: Shared<Series>,
+
+    /// Wakes up the callers of `wait_idle` once the queue is idle or stopped.
+    idle: Arc<Notify>,
 }
 
 impl<RoutineType, Context> JobQueue<RoutineType, Context>
@@ -124,7 +680,9 @@ where
         let runtime = builder.build().map_err(|e| api_err!(e.into()))?;
 
         // Create the channel for communicating with the thread of the queue.
-        let (tx, rx) = std::sync::mpsc::channel();
+        let (tx, rx) = channel::channel();
+
+        let subscribers = Arc::new(Mutex::new(Subscribers::default()));
 
         Ok(Self {
             state: State::default(),
@@ -132,10 +690,80 @@ where
             rx: Arc::new(Mutex::new(rx)),
             join_handle: None,
             expiration_join_handle: None,
+            janitor_join_handle: None,
+            fetch_join_handle: None,
+            tickers: Arc::default(),
             backend: Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new()))),
             runtime: Arc::new(Mutex::new(runtime)),
-            notification_handler: Arc::new(|_| {}),
+            notification_handler: Self::with_subscribers(&subscribers, |_| {}),
+            #[cfg(feature = "webhook")]
+            client_notification_handler: Self::with_subscribers(&subscribers, |_| {}),
+            #[cfg(feature = "webhook")]
+            webhook_notifier: None,
             context: None,
+            context_resolver: None,
+            context_factory: None,
+            throughput: Arc::new(Mutex::new(Throughput::new())),
+            retention: RetentionPolicy::default(),
+            result_ttls: HashMap::new(),
+            default_timeout: None,
+            sweep_strategy: SweepStrategy::default(),
+            scheduler: Box::new(FifoStrategy),
+            max_in_flight: None,
+            shedding: None,
+            tenant_quotas: None,
+            named_queues: None,
+            max_pending: None,
+            preemption: None,
+            autoscale: None,
+            dispatcher: Arc::new(Mutex::new(Dispatcher::new(
+                Box::new(FifoStrategy),
+                None,
+                None,
+            ))),
+            running: Arc::new(Mutex::new(HashMap::new())),
+            deadlines: Arc::new(Mutex::new(HashMap::new())),
+            suspensions: Arc::new(Mutex::new(HashMap::new())),
+            namespace: DEFAULT_NAMESPACE.to_owned(),
+            lock_janitor: None,
+            staged: Arc::new(Mutex::new(HashMap::new())),
+            staging_ttl: Duration::from_secs(60 * 60),
+            schema_check_sample: 100,
+            subscribers,
+            undecodable_policy: UndecodablePolicy::default(),
+            dead_letters: Arc::new(Mutex::new(HashMap::new())),
+            quarantined: Arc::new(Mutex::new(HashSet::new())),
+            worker_id: format!("worker-{:016x}", rand::random::<u64>()),
+            kinds: KindFilter::default(),
+            capabilities: Capabilities::default(),
+            registry: Arc::new(RoutineRegistry::new()),
+            middlewares: vec![],
+            hooks: LifecycleHooks::default(),
+            clock: None,
+            trace_propagator: None,
+            id_generator: None,
+            interceptors: vec![],
+            migrator: None,
+            codec: None,
+            compression: None,
+            encryption: None,
+            result_offload: None,
+            orphan_reaper: None,
+            reaper_join_handle: None,
+            watchdog: None,
+            retry_policy: None,
+            fetch_interval: None,
+            lease: Duration::from_secs(30),
+            idempotency: Arc::new(Mutex::new(IdempotencyCache::new(Duration::from_secs(
+                24 * 60 * 60,
+            )))),
+            drain_timeout: None,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "signals")]
+            signal_watcher: None,
+            tombstones: Arc::new(Mutex::new(Tombstones::default())),
+            series: Arc::new(Mutex::new(Series::default())),
+            idle: Arc::new(Notify::new()),
         })
     }
 
@@ -144,533 +772,4582 @@ where
     /// # Returns
     /// A value of the enum `State`.
     pub fn state(&self) -> State {
-        self.state
+        match self.state {
+            State::Running if self.shutdown.load(Ordering::SeqCst) => State::Stopping,
+            state => state,
+        }
     }
 
-    /// Sets the backend used by the queue to store jobs and their results.
+    /// Sets the backend used by the queue to store jobs and their results, before the queue
+    /// is started (see `JobQueueBuilder::backend`).
     ///
     /// # Arguments:
     /// * `backend` - Backend instance that will replace the current one.
-    pub fn set_backend(&mut self, backend: impl Backend<RoutineType, Context> + 'static) {
-        self.backend = Arc::new(AsyncMutex::new(Box::new(backend)));
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::AlreadyRunning` if the queue is started.
+    pub fn set_backend(
+        &mut self,
+        backend: impl Backend<RoutineType, Context> + 'static,
+    ) -> Result<(), ApiError> {
+        self.set_shared_backend(Arc::new(AsyncMutex::new(Box::new(backend))))
     }
 
-    /// Sets the notification handler used by the queue to notify client.
+    /// Sets a backend shared with other queues of the same process, e.g. several worker pools
+    /// claiming jobs from the same storage, before the queue is started.
     ///
     /// # Arguments:
-    /// * `handler` - Handler instance that will replace the current one.
-    pub fn set_notification_handler(
+    /// * `backend` - Shared backend instance that will replace the current one.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::AlreadyRunning` if the queue is started.
+    pub fn set_shared_backend(
         &mut self,
-        handler: impl Fn(Notification) + Send + Sync + 'static,
-    ) {
-        self.notification_handler = Arc::new(handler);
+        backend: SharedBackend<RoutineType, Context>,
+    ) -> Result<(), ApiError> {
+        // The jobs taken by the queue are bound to the backend it started with
+        self.try_starting()?;
+
+        self.backend = backend;
+
+        Ok(())
     }
 
-    /// Sets the context to be passed to every routine.
+    /// Gets the identifier of the worker claiming jobs in the backend.
+    ///
+    /// # Returns
+    /// The worker ID (random unless set).
+    pub fn worker_id(&self) -> &str {
+        &self.worker_id
+    }
+
+    /// Sets the identifier of the worker claiming jobs in the backend, unique among the
+    /// workers sharing the backend (e.g. host name and process ID).
     ///
     /// # Arguments:
-    /// * `context` - Context instance to set.
-    pub fn set_context(&mut self, context: Context) {
-        self.context = Some(Arc::new(Mutex::new(context)));
+    /// * `worker_id` - Worker ID to set.
+    pub fn set_worker_id(&mut self, worker_id: impl Into<String>) {
+        self.worker_id = worker_id.into();
     }
 
-    /// Starts the job queue with async support.
+    /// Gets the kinds of job executed by the worker.
     ///
-    /// # Errors
-    /// One of `Error` enum.
+    /// # Returns
+    /// The filter of the kinds (every kind unless set).
+    pub fn kinds(&self) -> &KindFilter {
+        &self.kinds
+    }
+
+    /// Sets the kinds of job executed by the worker: a worker of a shared backend only claims
+    /// the jobs of these kinds, leaving the other ones to the rest of the fleet, and a local
+    /// queue rejects them when enqueued.
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    pub fn start(&mut self) -> Result<(), ApiError> {
-        self.try_starting()?;
+    /// # Arguments:
+    /// * `kinds` - Filter of the kinds.
+    pub fn set_kinds(&mut self, kinds: KindFilter) {
+        self.kinds = kinds;
+    }
 
-        // Thread waiting for messages and jobs
-        let backend = self.backend.clone();
-        let runtime = self.runtime.clone();
-        let rx = self.rx.clone();
-        let notification_handler = self.notification_handler.clone();
-        let messages_channel = self.tx.clone();
-        let context = self.context.clone();
+    /// Gets the capabilities advertised by the worker.
+    ///
+    /// # Returns
+    /// The capabilities (none unless set).
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
 
-        let handle = std::thread::spawn(move || {
-            let rx = match rx.lock() {
-                Ok(rx) => rx,
-                Err(e) => {
-                    notification_handler(Notification::Error(Error::CannotAccessReceiver(
-                        e.to_string(),
-                    )));
+    /// Sets the capabilities advertised by the worker: a worker of a shared backend only claims
+    /// the jobs whose requirements it satisfies (see `Job::set_requirements`), and a local
+    /// queue rejects the other ones when enqueued.
+    ///
+    /// # Arguments:
+    /// * `capabilities` - Capabilities advertised.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
 
-                    return;
-                }
-            };
+    /// Gets the routines registered by name.
+    ///
+    /// # Returns
+    /// The registry of the routines (empty unless set).
+    pub fn registry(&self) -> &RoutineRegistry<Context> {
+        &self.registry
+    }
 
-            while let Ok(msg) = rx.recv() {
-                // Special case used to stop the thread.
-                if msg == Message::Command(Cmd::Stop) {
-                    break;
-                }
+    /// Sets the routines registered by name, run by the jobs created with `Job::registered`.
+    /// Registered jobs of unknown names are left pending by the queue.
+    ///
+    /// # Arguments:
+    /// * `registry` - Registry of the routines.
+    pub fn set_registry(&mut self, registry: RoutineRegistry<Context>) {
+        self.registry = Arc::new(registry);
+    }
 
-                // Process the message received: job or command.
-                JobQueue::process_message(
-                    backend.clone(),
-                    runtime.clone(),
-                    notification_handler.clone(),
-                    messages_channel.clone(),
-                    context.clone(),
-                    msg,
-                );
-            }
-        });
+    /// Sets the migrator upgrading the routines stored by an older release, so that the jobs
+    /// persisted before a deployment renaming a variant or changing its arguments can still be
+    /// run (see `Routine::version`).
+    ///
+    /// # Arguments:
+    /// * `migrator` - Migrator of the routines.
+    pub fn set_migrator(&mut self, migrator: impl RoutineMigrator + 'static) {
+        self.migrator = Some(Arc::new(migrator));
+    }
 
-        self.join_handle = Some(handle);
+    /// Sets the format of the job records stored by the backend (JSON by default), e.g. a
+    /// binary format for jobs carrying large numeric payloads.
+    ///
+    /// # Arguments:
+    /// * `codec` - Codec of the records.
+    pub fn set_codec(&mut self, codec: impl Codec + 'static) {
+        self.codec = Some(Arc::new(codec));
+    }
 
-        // Thread checking the expirations
-        let notification_handler = self.notification_handler.clone();
-        let messages_channel = self.tx.clone();
+    /// Sets the compression of the large routines, private data and results of the job
+    /// records stored by the backend.
+    ///
+    /// # Arguments:
+    /// * `compression` - Compression policy (e.g. `Compression::new(zstd, 64 * 1024)`).
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = Some(compression);
+    }
+
+    /// Sets the encryption at rest of the private data and results of the job records stored
+    /// by the backend.
+    ///
+    /// # Arguments:
+    /// * `encryption` - Encryption policy, with the keys of the records already stored.
+    pub fn set_encryption(&mut self, encryption: Encryption) {
+        self.encryption = Some(encryption);
+    }
 
-        let handle = std::thread::spawn(move || loop {
-            std::thread::sleep(std::time::Duration::from_secs(1));
+    /// Sets the clock of the expirations, delays and deadlines of the jobs, the system clock by
+    /// default (e.g. a `ManualClock` in tests).
+    ///
+    /// # Arguments:
+    /// * `clock` - Clock to be set.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Some(Arc::new(clock));
+    }
 
-            let messages_channel = match messages_channel.lock() {
-                Ok(messages_channel) => messages_channel,
-                Err(e) => {
-                    notification_handler(Notification::Error(Error::CannotAccessSender(
-                        e.to_string(),
-                    )));
+    /// Sets the generator of the IDs of the jobs enqueued, replacing the UUID V1 values given
+    /// at creation (the ones given an ID with `Job::new_with_id` excepted): the ID of a job is
+    /// then the one returned by `enqueue` or held by the token of `prepare`.
+    ///
+    /// # Arguments:
+    /// * `generator` - Generator to be used (e.g. `UuidV7Generator` for time-sortable IDs).
+    pub fn set_id_generator(&mut self, generator: impl IdGenerator + 'static) {
+        self.id_generator = Some(Arc::new(generator));
+    }
 
-                    return;
-                }
-            };
+    /// Sets the policy writing the results larger than a threshold to an external store, the
+    /// backend keeping only a reference; `job_result` fetches them back transparently.
+    ///
+    /// # Arguments:
+    /// * `offload` - Policy offloading the large results.
+    pub fn set_result_offload(&mut self, offload: ResultOffload) {
+        self.result_offload = Some(offload);
+    }
 
-            if let Err(e) = messages_channel.send(Message::Command(Cmd::CheckExpirations)) {
-                notification_handler(Notification::Error(Error::CannotSendMessage(e.to_string())));
+    /// Adds a middleware wrapping the execution of every job, inside the ones added before.
+    ///
+    /// # Arguments:
+    /// * `middleware` - Middleware to add.
+    pub fn add_middleware(&mut self, middleware: impl Middleware + 'static) {
+        self.middlewares.push(Arc::new(middleware));
+    }
 
-                return;
-            }
-        });
+    /// Sets the hook called whenever a job starts running, independently of the notification
+    /// handler.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job.
+    pub fn set_on_start(&mut self, hook: impl Fn(&Job) + Send + Sync + 'static) {
+        self.hooks.set_on_start(hook);
+    }
 
-        self.expiration_join_handle = Some(handle);
+    /// Sets the hook called whenever a job succeeds, independently of the notification handler.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job and its result.
+    pub fn set_on_success(&mut self, hook: impl Fn(&Job, &[u8]) + Send + Sync + 'static) {
+        self.hooks.set_on_success(hook);
+    }
 
-        self.state = State::Running;
+    /// Sets the hook called whenever a job run fails (in error, or cancelled, timed out... while
+    /// running), independently of the notification handler.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job, its result status and its error message.
+    pub fn set_on_failure(
+        &mut self,
+        hook: impl Fn(&Job, ResultStatus, &str) + Send + Sync + 'static,
+    ) {
+        self.hooks.set_on_failure(hook);
+    }
 
-        Ok(())
+    /// Sets the hook called whenever a running job publishes a domain-specific signal (see
+    /// `Job::send_command`), independently of the notification handler.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job, the name of the command and its payload.
+    pub fn set_on_command(&mut self, hook: impl Fn(&Job, &str, &[u8]) + Send + Sync + 'static) {
+        self.hooks.set_on_command(hook);
     }
 
-    /// Tries to join the job queue waiting it to finish.
+    /// Sets the propagator of the trace contexts: the jobs enqueued without trace context get
+    /// the one of the current span, and run within a span restored from it, around the other
+    /// middlewares.
+    ///
+    /// # Arguments:
+    /// * `propagator` - Bridge to the tracing stack.
+    pub fn set_trace_propagator(&mut self, propagator: impl TracePropagator + 'static) {
+        let propagator: SharedTracePropagator = Arc::new(propagator);
+
+        self.middlewares
+            .insert(0, Arc::new(TraceMiddleware(propagator.clone())));
+        self.trace_propagator = Some(propagator);
+    }
+
+    /// Adds an interceptor inspecting the jobs enqueued, after the ones added before. A job
+    /// rejected by an interceptor is not taken by the queue, the rejection being returned to
+    /// the caller of `enqueue`.
+    ///
+    /// # Arguments:
+    /// * `interceptor` - Interceptor to add.
+    pub fn add_interceptor(&mut self, interceptor: impl EnqueueInterceptor + 'static) {
+        self.interceptors.push(Arc::new(interceptor));
+    }
+
+    /// Makes the queue a worker of a backend shared with other workers: jobs enqueued by any
+    /// of them are stored ready and claimed atomically by the first worker having a free
+    /// slot, instead of being dispatched by the queue that received them. The worker claims
+    /// jobs and extends the leases of the ones it holds at every interval, and claims as soon as
+    /// a slot is released. Set `max_in_flight` so that a worker doesn't claim every ready job.
+    ///
+    /// # Arguments:
+    /// * `poll_interval` - Interval at which the backend is polled.
+    pub fn set_distributed(&mut self, poll_interval: Duration) {
+        self.fetch_interval = Some(poll_interval);
+    }
+
+    /// Sets the duration of the leases of the jobs claimed from a shared backend: a job whose
+    /// lease isn't extended in time (e.g. the worker died) is claimed again by another worker.
+    /// It must be longer than the poll interval, at which the leases are extended.
+    ///
+    /// # Arguments:
+    /// * `lease` - Duration of the leases.
+    pub fn set_lease_duration(&mut self, lease: Duration) {
+        self.lease = lease;
+    }
+
+    /// Sets the maximum duration given to the queue to drain once stopped: the jobs already
+    /// taken keep being dispatched, the most urgent first (highest priority), and the ones not
+    /// finished when the timeout elapses are cancelled as `ResultStatus::TimedOut`, the least
+    /// urgent first. By default the queue stops right away.
+    ///
+    /// # Arguments:
+    /// * `timeout` - Maximum duration of the drain.
+    pub fn set_drain_timeout(&mut self, timeout: Duration) {
+        self.drain_timeout = Some(timeout);
+    }
+
+    /// Installs a handler of the termination signals (`SIGTERM`, `SIGINT`), before the queue is
+    /// started: once a signal is received, the queue stops accepting jobs and drains for up to
+    /// a grace period (see `set_drain_timeout`), the jobs not finished by then being cancelled,
+    /// and can then be joined. The handler replaces the default action of the signals for the
+    /// whole process until the first one is received: a second signal terminates the process.
+    ///
+    /// # Arguments:
+    /// * `grace_period` - Maximum duration of the drain.
     ///
     /// # Errors
-    /// One of `Error` enum.
-    pub fn join(self) -> Result<(), ApiError> {
-        self.try_joining()?;
+    /// One of `Error` enum, `Error::AlreadyRunning` if the queue is started.
+    #[cfg(feature = "signals")]
+    pub fn install_signal_handler(&mut self, grace_period: Duration) -> Result<(), ApiError> {
+        self.try_starting()?;
+
+        crate::signals::install()?;
+
+        self.drain_timeout = Some(grace_period);
+
+        let shutdown = self.shutdown.clone();
+        let notification_handler = self.notification_handler.clone();
+        let messages_channel = self.tx.clone();
+        let (watcher, stopped) = std::sync::mpsc::channel::<()>();
+
+        self.signal_watcher = Some(watcher);
 
-        if let Some(handle) = self.join_handle {
-            if handle.join().is_err() {
-                return Err(api_err!(Error::CannotJoinThread));
+        std::thread::spawn(move || {
+            while !crate::signals::received() {
+                // Queue stopped or dropped: no signal to watch anymore
+                if !matches!(
+                    stopped.recv_timeout(Duration::from_millis(50)),
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout)
+                ) {
+                    return;
+                }
             }
-        } else {
-            return Err(api_err!(Error::MissingJoinHandle));
-        }
 
-        // TODO: allow the thread to be stopped
-        //if let Some(handle) = self.expiration_join_handle {
-        //if handle.join().is_err() {
-        //return Err(api_err!(Error::CannotJoinThread));
-        //}
-        //} else {
-        //return Err(api_err!(Error::MissingJoinHandle));
-        //}
+            shutdown.store(true, Ordering::SeqCst);
+            notification_handler(Notification::StateChanged(State::Stopping));
 
-        // TODO
-        //self.runtime
-        //.lock()
-        //.unwrap()
-        //.shutdown_timeout(std::time::Duration::from_millis(100));
+            let result = messages_channel
+                .lock()
+                .map_err(|e| Error::CannotAccessSender(e.to_string()))
+                .and_then(|messages_channel| {
+                    messages_channel
+                        .send(Message::Command(Cmd::Stop))
+                        .map_err(|e| Error::CannotSendMessage(e.to_string()))
+                });
+
+            if let Err(e) = result {
+                notification_handler(Notification::Error(e));
+            }
+        });
 
         Ok(())
     }
 
-    /// Send a stop command to the queue.
-    /// There's no garantee that it will be processed but we'll do our best.
+    /// Sets the duration after which a job staged by `prepare` but neither confirmed nor aborted
+    /// is dropped (one hour by default), so that the jobs whose token is lost don't pile up.
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    pub fn stop(&mut self) -> Result<(), ApiError> {
-        self.try_stopping()?;
+    /// # Arguments:
+    /// * `ttl` - Duration during which a job stays staged.
+    pub fn set_staging_ttl(&mut self, ttl: Duration) {
+        self.staging_ttl = ttl;
+    }
 
-        self.state = State::Stopping;
+    /// Sets the window during which the jobs holding the same idempotency key are considered
+    /// duplicates: from the enqueue of the original job, then from its successful completion
+    /// (one day by default). A job that doesn't complete successfully can be submitted again.
+    ///
+    /// # Arguments:
+    /// * `window` - Duration of the window.
+    pub fn set_idempotency_window(&mut self, window: Duration) {
+        self.idempotency
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_window(window);
+    }
 
-        self.tx
+    /// Enables soft-delete: the jobs removed (one by one or purged) are kept for a retention
+    /// window, during which they can be listed and restored. The jobs reaped because they
+    /// have expired or by the retention policy are not kept.
+    ///
+    /// # Arguments:
+    /// * `retention` - Duration during which a removed job is kept.
+    pub fn set_soft_delete(&mut self, retention: Duration) {
+        self.tombstones
             .lock()
-            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
-            .send(Message::Command(Cmd::Stop))
-            .map_err(|e| api_err!(e.into()))
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_retention(retention);
     }
 
-    /// Push a new job to be processed in the queue.
+    /// Enables the reaper recovering the jobs claimed by dead workers, i.e. whose heartbeat is
+    /// older than a timeout, so that they don't stay running forever. The timeout must be
+    /// longer than the poll interval of the workers, at which they heartbeat.
     ///
-    /// # Arguments
-    /// * `job` - Job to be enqueued.
+    /// # Arguments:
+    /// * `timeout` - Duration after which a heartbeat is stale (also the reaping interval).
+    /// * `policy` - Policy applied on the orphaned jobs.
+    pub fn set_orphan_reaper(&mut self, timeout: Duration, policy: OrphanPolicy) {
+        self.orphan_reaper = Some((timeout, policy));
+    }
+
+    /// Enables the watchdog flagging the jobs running longer than expected with
+    /// `Notification::JobStalled`, and cancelling them past the threshold of the policy.
+    ///
+    /// # Arguments:
+    /// * `policy` - Policy of the watchdog.
+    pub fn set_watchdog(&mut self, policy: WatchdogPolicy) {
+        self.watchdog = Some(policy);
+    }
+
+    /// Sets the policy running again the jobs whose routine fails, as `Status::Retrying` during
+    /// the backoff delay. The jobs are finished as `ResultStatus::Error` once their attempts are
+    /// exhausted.
+    ///
+    /// # Arguments:
+    /// * `policy` - Policy to set.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = Some(policy);
+    }
+
+    /// Gets the namespace of the queue.
     ///
     /// # Returns
-    /// The unique ID of the job.
+    /// The namespace isolating the jobs of the queue in the backend.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Sets the namespace isolating the jobs of the queue from the ones of other queues (or
+    /// environments) sharing the same backend storage. Passed to the backend at start.
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    pub fn enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
-        let job_id = job.id();
+    /// # Arguments:
+    /// * `namespace` - Namespace to set.
+    pub fn set_namespace(&mut self, namespace: impl Into<String>) {
+        self.namespace = namespace.into();
+    }
 
-        self.tx
+    /// Sets the notification handler used by the queue to notify client.
+    ///
+    /// # Arguments:
+    /// * `handler` - Handler instance that will replace the current one.
+    pub fn set_notification_handler(
+        &mut self,
+        handler: impl Fn(Notification) + Send + Sync + 'static,
+    ) {
+        self.notification_handler = Self::with_subscribers(&self.subscribers, handler);
+
+        #[cfg(feature = "webhook")]
+        {
+            self.client_notification_handler = self.notification_handler.clone();
+            self.wrap_notification_handler();
+        }
+    }
+
+    /// Sets the notifier posting the completions, failures and expirations of the jobs to
+    /// webhooks, next to the notification handler (which is also notified of the failed
+    /// deliveries).
+    ///
+    /// # Arguments:
+    /// * `notifier` - Notifier instance that will replace the current one.
+    #[cfg(feature = "webhook")]
+    pub fn set_webhook_notifier(&mut self, notifier: WebhookNotifier) {
+        self.webhook_notifier = Some(notifier);
+        self.wrap_notification_handler();
+    }
+
+    /// Wraps the notification handler of the client so that the notifications are also
+    /// posted to the webhooks, on the runtime of the queue.
+    #[cfg(feature = "webhook")]
+    fn wrap_notification_handler(&mut self) {
+        let Some(notifier) = self.webhook_notifier.clone() else {
+            return;
+        };
+
+        let handler = self.client_notification_handler.clone();
+        let runtime = self
+            .runtime
             .lock()
-            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
-            .send(Message::Job(job))
-            .map_err(Into::<Error>::into)?;
+            .unwrap_or_else(|e| e.into_inner())
+            .handle()
+            .clone();
 
-        Ok(job_id)
+        self.notification_handler = Arc::new(move |notification| {
+            notifier.notify(&runtime, &notification, &handler);
+            handler(notification);
+        });
     }
 
-    /// Get the status of a job.
+    /// Subscribes to the notifications of the queue, in addition to the notification handler.
     ///
     /// # Arguments
-    /// * `id` - ID of the job to be inspected.
+    /// * `filter` - Filter selecting the notifications delivered.
+    /// * `handler` - Handler called with every notification selected.
     ///
     /// # Returns
-    /// The status of the job.
+    /// The ID of the subscription.
     ///
     /// # Errors
     /// One of `Error` enum.
-    pub async fn job_status(&self, id: &Uuid) -> Result<Status, ApiError> {
-        self.backend.lock().await.status(id)
+    pub fn subscribe(
+        &self,
+        filter: NotificationFilter,
+        handler: impl Fn(&Notification) + Send + Sync + 'static,
+    ) -> Result<SubscriptionId, ApiError> {
+        Ok(self
+            .subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .subscribe(filter, Arc::new(handler)))
     }
 
-    /// Get the result of a job.
+    /// Cancels a subscription to the notifications of the queue.
     ///
     /// # Arguments
-    /// * `id` - ID of the job to be inspected.
+    /// * `id` - ID of the subscription.
     ///
     /// # Returns
-    /// The result of the job as vector of bytes.
+    /// `true` if the subscription existed, `false` otherwise.
     ///
     /// # Errors
     /// One of `Error` enum.
-    pub async fn job_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
-        let mut backend = self.backend.lock().await;
+    pub fn unsubscribe(&self, id: SubscriptionId) -> Result<bool, ApiError> {
+        Ok(self
+            .subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .unsubscribe(id))
+    }
 
-        let result = backend.result(id)?.to_vec();
+    /// Builds a notification handler delivering the notifications to the subscriptions too.
+    ///
+    /// # Arguments
+    /// * `subscribers` - Subscriptions to the notifications.
+    /// * `handler` - Notification handler.
+    ///
+    /// # Returns
+    /// The shared notification handler.
+    fn with_subscribers(
+        subscribers: &Shared<Subscribers>,
+        handler: impl Fn(Notification) + Send + Sync + 'static,
+    ) -> SharedNotificationHandler {
+        let subscribers = subscribers.clone();
 
-        if let ExpirePolicy::OnResultFetch(_) = backend.expire_policy(id)? {
-            if let Status::Finished(_) = backend.status(id)? {
-                backend.remove(id)?;
+        Arc::new(move |notification| {
+            let handlers = subscribers
+                .lock()
+                .map(|mut subscribers| subscribers.select(&notification))
+                .unwrap_or_default();
 
-                (self.notification_handler)(Notification::Status(id.to_owned(), Status::Removed));
+            for subscriber in handlers {
+                subscriber(&notification);
             }
-        }
 
-        Ok(result)
+            handler(notification);
+        })
     }
 
-    /// Get the progression of a job.
+    /// Enables the event log of the queue, recording every change in the life of the jobs
+    /// with a sequence number (see `events_since` and `subscribe_events`).
+    ///
+    /// # Arguments:
+    /// * `capacity` - Maximum number of events retained, the oldest ones being evicted.
+    pub fn set_event_log(&mut self, capacity: usize) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .events
+            .enable(capacity);
+    }
+
+    /// Gets the events of the jobs recorded after a sequence number, for external systems to
+    /// poll the changes they haven't synced yet.
     ///
     /// # Arguments
-    /// * `id` - ID of the job to be inspected.
+    /// * `seq` - Sequence number of the last event synced (0 to get every event retained).
     ///
     /// # Returns
-    /// The progression of the job.
+    /// The events, in order.
     ///
     /// # Errors
-    /// One of `Error` enum.
-    pub async fn job_progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
-        self.backend.lock().await.progression(id)
+    /// One of `Error` enum, `Error::EventsEvicted` if events following the sequence number are
+    /// no longer retained.
+    pub fn events_since(&self, seq: u64) -> Result<Vec<Event>, ApiError> {
+        self.subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .events
+            .since(seq)
     }
 
-    /// Get the routine of a job.
+    /// Follows the events of the jobs: the events recorded after a sequence number are
+    /// replayed, then the new ones are streamed as recorded, without gap.
     ///
     /// # Arguments
-    /// * `id` - ID of the job to be inspected.
+    /// * `seq` - Sequence number of the last event synced (0 to get every event retained).
     ///
     /// # Returns
-    /// The routine of the job.
+    /// The receiving end of the stream, closed once the queue is dropped.
     ///
     /// # Errors
-    /// One of `Error` enum.
-    pub async fn job_routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
-        self.backend.lock().await.routine(id)
+    /// One of `Error` enum, `Error::EventsEvicted` if events following the sequence number are
+    /// no longer retained.
+    pub fn subscribe_events(
+        &self,
+        seq: u64,
+    ) -> Result<tokio::sync::mpsc::UnboundedReceiver<Event>, ApiError> {
+        self.subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .events
+            .stream(seq)
     }
 
-    /// Get the list of jobs.
-    ///
-    /// # Returns
-    /// The list of jobs.
+    /// Sets the context to be passed to every routine.
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    pub async fn jobs(&self) -> Result<Vec<Job>, ApiError> {
-        self.backend.lock().await.jobs()
+    /// # Arguments:
+    /// * `context` - Context instance to set.
+    pub fn set_context(&mut self, context: Context) {
+        self.context = Some(ContextMode::exclusive(context));
     }
 
-    /// Removes a finished job from the queue.
+    /// Sets the context to be passed to every routine, shared in the mode given: read-only or
+    /// behind a read-write lock, the routines reading it don't contend for a mutex but receive it
+    /// through `Routine::call_with_context` only.
     ///
-    /// # Arguments
-    /// * `id` - ID of the job to be removed.
-    ///
-    /// # Errors
-    /// One of `Error` enum.
-    pub async fn remove_job(&self, id: &Uuid) -> Result<(), ApiError> {
-        self.backend.lock().await.remove(id)?;
+    /// # Arguments:
+    /// * `mode` - Context and the way it is shared.
+    pub fn set_context_mode(&mut self, mode: ContextMode<Context>) {
+        self.context = Some(mode);
+    }
 
-        (self.notification_handler)(Notification::Status(id.to_owned(), Status::Removed));
+    /// Sets the factory building the context to be passed to every routine asynchronously (e.g.
+    /// opening database pools), on the runtime of the queue when started, in place of the
+    /// context set. A queue whose context can't be built doesn't start.
+    ///
+    /// # Arguments:
+    /// * `factory` - Function returning the future building the context (shared behind a mutex,
+    ///   unless given as a `ContextMode`).
+    pub fn set_context_factory<F, Fut, C>(&mut self, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<C, ApiError>> + Send + 'static,
+        C: Into<ContextMode<Context>>,
+    {
+        self.context_factory = Some(Arc::new(move || {
+            let context = factory();
 
-        Ok(())
+            Box::pin(async move { context.await.map(Into::into) })
+        }));
     }
 
-    /// Checks if the current state allows to start the queue.
+    /// Sets the resolver of the context passed to the routines of the jobs holding their own
+    /// context (see `Job::with_context`), in place of the context of the queue (e.g.
+    /// `replace_context`) or merged with it. A job whose context can't be resolved fails.
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    fn try_starting(&self) -> Result<(), ApiError> {
-        match self.state {
-            State::Running => Err(api_err!(Error::AlreadyRunning)),
-            State::Stopping => Err(api_err!(Error::Stopped)),
-            _ => Ok(()),
-        }
+    /// # Arguments:
+    /// * `resolver` - Function called with the context of the queue (if any) and the one of the
+    ///   job.
+    pub fn set_context_resolver(
+        &mut self,
+        resolver: impl Fn(
+                Option<&ContextMode<Context>>,
+                &serde_json::Value,
+            ) -> Result<ContextMode<Context>, ApiError>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.context_resolver = Some(Arc::new(resolver));
     }
 
-    /// Checks if the current state allows to join the queue.
+    /// Sets the retention policy applied on finished jobs.
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    fn try_joining(&self) -> Result<(), ApiError> {
-        match self.state {
-            State::Idle => Err(api_err!(Error::NotStarted)),
-            State::Running => Err(api_err!(Error::NotStopping)),
-            _ => Ok(()),
-        }
+    /// # Arguments:
+    /// * `retention` - Retention policy to set.
+    pub fn set_retention(&mut self, retention: RetentionPolicy) {
+        self.retention = retention;
     }
 
-    /// Checks if the current state allows to stop the queue.
+    /// Sets the duration the results of a kind of job are kept once finished, the jobs
+    /// themselves being kept according to their expire policy. Applied on the jobs enqueued
+    /// without a duration of their own (see `Job::set_result_ttl`).
     ///
-    /// # Errors
-    /// One of `Error` enum.
-    fn try_stopping(&self) -> Result<(), ApiError> {
-        match self.state {
-            State::Idle => Err(api_err!(Error::NotStarted)),
-            State::Stopping => Err(api_err!(Error::Stopped)),
-            _ => Ok(()),
-        }
+    /// # Arguments:
+    /// * `kind` - Kind of the jobs.
+    /// * `ttl` - Duration the results are kept.
+    pub fn set_result_ttl(&mut self, kind: impl Into<String>, ttl: Duration) {
+        self.result_ttls.insert(kind.into(), ttl);
     }
 
-    /// Processes a message (can be a command or job).
+    /// Sets the timeout of the jobs enqueued without deadline: their deadline is set to the
+    /// time they're enqueued plus the timeout (see `Job::set_deadline`).
     ///
-    /// # Arguments
-    /// * `backend` - Backend instance used to process the jobs.
-    /// * `runtime` - Runtime carrying the thread pool.
+    /// # Arguments:
+    /// * `timeout` - Timeout of the jobs.
+    pub fn set_default_timeout(&mut self, timeout: Duration) {
+        self.default_timeout = Some(timeout);
+    }
+
+    /// Sets the strategy used to evaluate the expirations of the jobs.
+    ///
+    /// # Arguments:
+    /// * `sweep_strategy` - Strategy to set.
+    pub fn set_sweep_strategy(&mut self, sweep_strategy: SweepStrategy) {
+        self.sweep_strategy = sweep_strategy;
+    }
+
+    /// Sets the strategy deciding which eligible job runs next, FIFO by default. Besides the
+    /// built-in strategies, a closure picking the index of the job among the candidates can be
+    /// given for domain-specific orderings.
+    ///
+    /// # Arguments:
+    /// * `strategy` - Strategy to set.
+    pub fn set_scheduler(&mut self, strategy: impl SchedulerStrategy + 'static) {
+        self.scheduler = Box::new(strategy);
+    }
+
+    /// Sets the maximum number of jobs running at the same time. Jobs above this limit wait to
+    /// be dispatched according to the scheduler strategy.
+    ///
+    /// # Arguments:
+    /// * `max_in_flight` - Maximum number of jobs (at least 1).
+    pub fn set_max_in_flight(&mut self, max_in_flight: usize) {
+        self.max_in_flight = Some(max_in_flight.max(1));
+    }
+
+    /// Sets the maximum number of jobs waiting to be dispatched by a started queue (delayed
+    /// ones included): `enqueue` then blocks until a job is dispatched, `try_enqueue` fails with
+    /// `Error::QueueFull` and `enqueue_timeout` waits up to a timeout.
+    ///
+    /// # Arguments:
+    /// * `max_pending` - Maximum number of jobs.
+    pub fn set_max_pending(&mut self, max_pending: usize) {
+        self.max_pending = Some(max_pending);
+    }
+
+    /// Sets the policy used to delay or drop the jobs of non-critical kinds when the queue is
+    /// overloaded.
+    ///
+    /// # Arguments:
+    /// * `shedding` - Policy to set.
+    pub fn set_shedding(&mut self, shedding: SheddingPolicy) {
+        self.shedding = Some(shedding);
+    }
+
+    /// Sets the quotas of the tenants owning the jobs (see `Job::set_tenant`): the jobs of a
+    /// tenant above its quota of pending jobs are rejected when enqueued, and the ones above
+    /// its quota of running jobs wait for one of them to finish. Combine it with
+    /// `TenantRoundRobinStrategy` so that the tenants are dispatched in turn.
+    ///
+    /// # Arguments:
+    /// * `quotas` - Quotas to set.
+    pub fn set_tenant_quotas(&mut self, quotas: TenantQuotas) {
+        self.tenant_quotas = Some(quotas);
+    }
+
+    /// Sets the named queues hosted by the queue (see `enqueue_to`), sharing its threads and
+    /// backend: the jobs of a queue above its maximum of running jobs wait for one of them to
+    /// finish, and the jobs of a queue are only dispatched once no job of a queue of higher
+    /// priority is waiting.
+    ///
+    /// # Arguments:
+    /// * `named_queues` - Named queues to set.
+    pub fn set_named_queues(&mut self, named_queues: NamedQueues) {
+        self.named_queues = Some(named_queues);
+    }
+
+    /// Routes the jobs of a kind, enqueued without queue, to a named queue (see
+    /// `NamedQueues::route_kind`).
+    ///
+    /// # Arguments:
+    /// * `kind` - Kind of job.
+    /// * `queue` - Name of the queue.
+    pub fn set_route_kind(&mut self, kind: impl Into<String>, queue: impl Into<String>) {
+        self.named_queues
+            .get_or_insert_with(NamedQueues::default)
+            .routes
+            .insert(kind.into(), queue.into());
+    }
+
+    /// Sets the policy letting the urgent jobs take the slot of a running job of lower priority
+    /// when every slot is occupied (see `set_max_in_flight`). The job preempted is suspended
+    /// (see `suspend_job`) and resumed once the urgent job is finished.
+    ///
+    /// # Arguments:
+    /// * `preemption` - Policy to set.
+    pub fn set_preemption(&mut self, preemption: PreemptionPolicy) {
+        self.preemption = Some(preemption);
+    }
+
+    /// Sets the policy adjusting the maximum number of jobs in flight to the number of jobs
+    /// waiting to be dispatched, in place of `max_in_flight`. The limit is evaluated whenever
+    /// jobs are dispatched, each change being notified with `Notification::Scaled`.
+    ///
+    /// # Arguments:
+    /// * `autoscale` - Policy to set.
+    pub fn set_autoscale(&mut self, autoscale: AutoscalePolicy) {
+        self.autoscale = Some(autoscale);
+    }
+
+    /// Enables the janitor releasing the locks held by dead workers (i.e. not refreshed in
+    /// time), so that a crashed worker cannot hold a key forever.
+    ///
+    /// # Arguments:
+    /// * `interval` - Interval at which the stale locks are released.
+    pub fn set_lock_janitor(&mut self, interval: Duration) {
+        self.lock_janitor = Some(interval);
+    }
+
+    /// Sets the number of stored pending jobs checked against the routine type at start, the
+    /// incompatible kinds being notified (0 disables the check).
+    ///
+    /// # Arguments:
+    /// * `sample` - Maximum number of jobs checked.
+    pub fn set_schema_check_sample(&mut self, sample: usize) {
+        self.schema_check_sample = sample;
+    }
+
+    /// Sets the policy applied on the dispatched jobs that cannot be deserialized anymore.
+    ///
+    /// # Arguments:
+    /// * `policy` - Policy to be set.
+    pub fn set_undecodable_policy(&mut self, policy: UndecodablePolicy) {
+        self.undecodable_policy = policy;
+    }
+
+    /// Starts the job queue with async support.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn start(&mut self) -> Result<(), ApiError> {
+        self.try_starting()?;
+
+        // Context built on the runtime of the queue
+        if let Some(factory) = &self.context_factory {
+            let context = self
+                .runtime
+                .lock()
+                .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))?
+                .block_on(factory())?;
+
+            self.context = Some(context);
+        }
+
+        self.launch()
+    }
+
+    /// Spawns the threads of the job queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn launch(&mut self) -> Result<(), ApiError> {
+        self.try_starting()?;
+
+        // Jobs of the backend isolated by namespace
+        let mut backend = self
+            .backend
+            .try_lock()
+            .map_err(|e| api_err!(Error::CannotAccessBackend(e.to_string())))?;
+
+        backend.set_namespace(&self.namespace);
+
+        if let Some(migrator) = &self.migrator {
+            backend.set_migrator(migrator.clone());
+        }
+
+        if let Some(codec) = &self.codec {
+            backend.set_codec(codec.clone());
+        }
+
+        if let Some(compression) = &self.compression {
+            backend.set_compression(compression.clone());
+        }
+
+        if let Some(encryption) = &self.encryption {
+            backend.set_encryption(encryption.clone());
+        }
+
+        if let Some(clock) = &self.clock {
+            backend.set_clock(clock.clone());
+        }
+
+        // Stored jobs that cannot be run anymore reported before being dispatched
+        if self.schema_check_sample > 0 {
+            let jobs = backend.jobs()?;
+
+            for drift in detect_drift::<RoutineType, Context>(&jobs, self.schema_check_sample) {
+                (self.notification_handler)(Notification::SchemaDrift(drift));
+            }
+        }
+
+        drop(backend);
+
+        // Dispatch state built from the configured strategy
+        let strategy = std::mem::replace(&mut self.scheduler, Box::new(FifoStrategy));
+        let mut dispatcher = Dispatcher::new(strategy, self.max_in_flight, self.shedding.clone());
+
+        dispatcher.set_pool_size(
+            self.runtime
+                .lock()
+                .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))?
+                .metrics()
+                .num_workers(),
+        );
+
+        if let Some(autoscale) = self.autoscale {
+            dispatcher.set_autoscale(autoscale);
+        }
+
+        if let Some(quotas) = &self.tenant_quotas {
+            dispatcher.set_quotas(quotas.clone());
+        }
+
+        if let Some(named_queues) = &self.named_queues {
+            dispatcher.set_named_queues(named_queues.clone());
+        }
+
+        if let Some(max_pending) = self.max_pending {
+            dispatcher.set_max_pending(max_pending);
+        }
+
+        if let Some(preemption) = self.preemption {
+            dispatcher.set_preemption(preemption);
+        }
+
+        self.dispatcher = Arc::new(Mutex::new(dispatcher));
+
+        // Thread waiting for messages and jobs
+        let resources = self.resources();
+        let rx = self.rx.clone();
+        let drain_timeout = self.drain_timeout;
+
+        let handle = std::thread::spawn(move || {
+            let rx = match rx.lock() {
+                Ok(rx) => rx,
+                Err(e) => {
+                    (resources.notification_handler)(Notification::Error(
+                        Error::CannotAccessReceiver(e.to_string()),
+                    ));
+
+                    return;
+                }
+            };
+
+            let mut busy = false;
+
+            while let Ok(msg) = rx.recv() {
+                // Special case used to stop the thread.
+                if msg == Message::Command(Cmd::Stop) {
+                    // Stop overtakes the jobs waiting, which have been accepted: stored first
+                    for msg in rx.take_jobs() {
+                        JobQueue::process_message(&resources, msg);
+                    }
+
+                    if let Some(timeout) = drain_timeout {
+                        JobQueue::drain(&resources, &rx, timeout);
+                    }
+
+                    break;
+                }
+
+                // Process the message received: job or command.
+                JobQueue::process_message(&resources, msg);
+
+                // Every job finished releases its slot with a message, checked here
+                match resources.dispatcher.lock() {
+                    Ok(dispatcher) => {
+                        let idle = dispatcher.is_empty();
+
+                        if busy && idle {
+                            (resources.notification_handler)(Notification::Idle);
+                        }
+
+                        // The jobs possibly removed meanwhile without a transition
+                        if idle {
+                            resources.idle.notify_waiters();
+                        }
+
+                        busy = !idle;
+                    }
+                    Err(e) => (resources.notification_handler)(Notification::Error(
+                        Error::CannotAccessDispatcher(e.to_string()),
+                    )),
+                }
+            }
+
+            (resources.notification_handler)(Notification::StateChanged(State::Stopped));
+            resources.idle.notify_waiters();
+        });
+
+        self.join_handle = Some(handle);
+
+        self.state = State::Running;
+        (self.notification_handler)(Notification::StateChanged(State::Running));
+
+        // Threads sending the periodic commands, stopped with the queue
+        self.tickers = Arc::default();
+
+        // Thread releasing the stale locks
+        if let Some(interval) = self.lock_janitor {
+            self.janitor_join_handle =
+                Some(self.spawn_ticker(interval, vec![Cmd::ReleaseStaleLocks]));
+        }
+
+        // Thread fetching the jobs from the shared backend
+        if let Some(interval) = self.fetch_interval {
+            self.fetch_join_handle = Some(self.spawn_ticker(interval, vec![Cmd::Fetch]));
+        }
+
+        // Thread recovering the jobs of dead workers
+        if let Some((timeout, _)) = self.orphan_reaper {
+            self.reaper_join_handle = Some(self.spawn_ticker(timeout, vec![Cmd::ReapOrphans]));
+        }
+
+        // Thread checking the expirations (only when they're not evaluated lazily)
+        if let SweepStrategy::Interval(interval) = self.sweep_strategy {
+            let mut commands = vec![Cmd::CheckExpirations];
+
+            if let RetentionPolicy::KeepLastN(count) = self.retention {
+                commands.push(Cmd::ApplyRetention(count));
+            }
+
+            self.expiration_join_handle = Some(self.spawn_ticker(interval, commands));
+        }
+
+        Ok(())
+    }
+
+    /// Spawns a thread sending commands to the thread of the queue at a regular interval,
+    /// until the queue is stopped (see `join_tickers`).
+    ///
+    /// # Arguments
+    /// * `interval` - Interval between the commands.
+    /// * `commands` - Commands sent at every interval, in order.
+    ///
+    /// # Returns
+    /// The join handle of the thread.
+    fn spawn_ticker(&self, interval: Duration, commands: Vec<Cmd>) -> JoinHandle<()> {
+        let notification_handler = self.notification_handler.clone();
+        let messages_channel = self.tx.clone();
+        let tickers = self.tickers.clone();
+
+        std::thread::spawn(move || {
+            while !tickers.sleep(interval) {
+                let result = messages_channel
+                    .lock()
+                    .map_err(|e| Error::CannotAccessSender(e.to_string()))
+                    .and_then(|messages_channel| {
+                        commands.iter().try_for_each(|command| {
+                            messages_channel
+                                .send(Message::Command(command.clone()))
+                                .map_err(|e| Error::CannotSendMessage(e.to_string()))
+                        })
+                    });
+
+                if let Err(e) = result {
+                    // Queue stopped meanwhile: nothing to report
+                    if !tickers.is_stopped() {
+                        notification_handler(Notification::Error(e));
+                    }
+
+                    return;
+                }
+            }
+        })
+    }
+
+    /// Stops the threads sending the periodic commands and waits for them to finish.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn join_tickers(&mut self) -> Result<(), ApiError> {
+        self.tickers.stop();
+
+        for handle in [
+            self.janitor_join_handle.take(),
+            self.fetch_join_handle.take(),
+            self.reaper_join_handle.take(),
+            self.expiration_join_handle.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            handle
+                .join()
+                .map_err(|_| api_err!(Error::CannotJoinThread))?;
+        }
+
+        Ok(())
+    }
+
+    /// Tries to join the job queue waiting it to finish.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn join(mut self) -> Result<(), ApiError> {
+        self.join_thread()?;
+        self.join_tickers()?;
+
+        // TODO
+        //self.runtime
+        //.lock()
+        //.unwrap()
+        //.shutdown_timeout(std::time::Duration::from_millis(100));
+
+        Ok(())
+    }
+
+    /// Starts the job queue from an async context: the threads of the queue are spawned
+    /// without blocking the runtime of the caller.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn start_async(&mut self) -> Result<(), ApiError> {
+        self.try_starting()?;
+
+        // Context built on the runtime of the queue, awaited without blocking the caller
+        if let Some(factory) = &self.context_factory {
+            let build = self
+                .runtime
+                .lock()
+                .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))?
+                .spawn(factory());
+
+            self.context = Some(build.await.map_err(|e| api_err!(e.into()))??);
+        }
+
+        self.launch()
+    }
+
+    /// Shuts the job queue down from an async context: the queue is stopped (unless already
+    /// stopping) and the future completes once its thread and the jobs still running have
+    /// finished. The blocking join and the drop of the runtime of the jobs are done on a
+    /// thread of their own, so no thread of the runtime of the caller is blocked.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn shutdown(mut self) -> Result<(), ApiError> {
+        if self.state() == State::Running {
+            self.stop()?;
+        }
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        std::thread::spawn(move || {
+            let result = self
+                .join_thread()
+                .and_then(|_| self.join_tickers())
+                .and_then(|_| self.wait_running());
+            let _ = tx.send(result);
+        });
+
+        rx.await.map_err(|_| api_err!(Error::CannotJoinThread))?
+    }
+
+    /// Send a stop command to the queue.
+    /// There's no garantee that it will be processed but we'll do our best.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn stop(&mut self) -> Result<(), ApiError> {
+        self.try_stopping()?;
+
+        #[cfg(feature = "signals")]
+        {
+            self.signal_watcher = None;
+        }
+
+        self.state = State::Stopping;
+        (self.notification_handler)(Notification::StateChanged(State::Stopping));
+
+        self.tickers.stop();
+
+        self.tx
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Stop))
+            .map_err(|e| api_err!(e.into()))
+    }
+
+    /// Push a new job to be processed in the queue, waiting for room if the queue is full (see
+    /// `set_max_pending`).
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        Ok(self.enqueue_idempotent(job)?.id)
+    }
+
+    /// Push a new job to be processed in the queue, awaiting room if the queue is full (see
+    /// `set_max_pending`) without blocking the thread.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn enqueue_async(&self, job: Job) -> Result<Uuid, ApiError> {
+        if job.has_supplied_id() && self.backend.lock().await.status(&job.id()).is_ok() {
+            return Err(api_err!(Error::DuplicateJobId(job.id())));
+        }
+
+        let job = match self.screen(job)? {
+            Screened::Admissible(job) => job,
+            Screened::Duplicate(enqueued) => return Ok(enqueued.id),
+        };
+
+        let admitted = self.admit_async(&job).await;
+
+        Ok(self.push(*job, admitted)?.id)
+    }
+
+    /// Push a new job to be processed in a named queue (see `set_named_queues`).
+    ///
+    /// # Arguments
+    /// * `queue` - Name of the queue.
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn enqueue_to(&self, queue: &str, mut job: Job) -> Result<Uuid, ApiError> {
+        job.set_queue(queue);
+
+        self.enqueue(job)
+    }
+
+    /// Push a new job to be processed in the queue, unless the queue is full.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::QueueFull` if the queue is full.
+    pub fn try_enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        Ok(self.submit(job, Some(Duration::ZERO))?.id)
+    }
+
+    /// Push a new job to be processed in the queue, waiting up to a timeout for room if the
+    /// queue is full.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    /// * `timeout` - Maximum duration to wait.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::QueueFull` if the queue is still full after the timeout.
+    pub fn enqueue_timeout(&self, job: Job, timeout: Duration) -> Result<Uuid, ApiError> {
+        Ok(self.submit(job, Some(timeout))?.id)
+    }
+
+    /// Push a new job to be processed in the queue, unless a job holding the same idempotency
+    /// key has been submitted within the idempotency window.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Returns
+    /// The job enqueued, or the original one with its cached result for a duplicate.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn enqueue_idempotent(&self, job: Job) -> Result<Enqueued, ApiError> {
+        self.submit(job, None)
+    }
+
+    /// Push a new job to be processed in the queue, unless a duplicate (see
+    /// `enqueue_idempotent`).
+    ///
+    /// # Arguments
+    /// * `job` - Job to be enqueued.
+    /// * `timeout` - Maximum duration to wait for room if the queue is full (forever if
+    ///   `None`).
+    ///
+    /// # Returns
+    /// The job enqueued, or the original one with its cached result for a duplicate.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn submit(&self, job: Job, timeout: Option<Duration>) -> Result<Enqueued, ApiError> {
+        if job.has_supplied_id() && self.is_stored(job.id())? {
+            return Err(api_err!(Error::DuplicateJobId(job.id())));
+        }
+
+        let job = match self.screen(job)? {
+            Screened::Admissible(job) => job,
+            Screened::Duplicate(enqueued) => return Ok(enqueued),
+        };
+
+        let admitted = self.admit(&job, timeout);
+
+        self.push(*job, admitted)
+    }
+
+    /// Checks if a job is stored in the backend, from the runtime of the queue so that the
+    /// caller may be within another runtime.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// `true` if stored, `false` otherwise.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn is_stored(&self, id: Uuid) -> Result<bool, ApiError> {
+        let backend = self.backend.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        self.runtime
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))?
+            .spawn(async move {
+                let _ = tx.send(backend.lock().await.status(&id).is_ok());
+            });
+
+        rx.recv()
+            .map_err(|e| api_err!(Error::CannotAccessBackend(e.to_string())))
+    }
+
+    /// Prepares a job submitted to be admitted in the queue: traced, intercepted, routed and
+    /// checked, unless a duplicate (see `enqueue_idempotent`).
+    ///
+    /// # Arguments
+    /// * `job` - Job submitted.
+    ///
+    /// # Returns
+    /// The job to be admitted, or the original one for a duplicate.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn screen(&self, mut job: Job) -> Result<Screened, ApiError> {
+        if self.shutdown.load(Ordering::SeqCst) {
+            return Err(api_err!(Error::Stopped));
+        }
+
+        if let Some(generator) = &self.id_generator {
+            job.generate_id(generator.as_ref());
+        }
+
+        // Job traced from the span enqueuing it, unless given a trace context
+        if job.trace_context().is_none() {
+            if let Some(context) = self
+                .trace_propagator
+                .as_ref()
+                .and_then(|propagator| propagator.current())
+            {
+                job.set_trace_context(context);
+            }
+        }
+
+        // Results of the kind possibly expiring before the job
+        if job.result_ttl().is_none() {
+            if let Some(ttl) = self.result_ttls.get(job.kind()) {
+                job.set_result_ttl(*ttl);
+            }
+        }
+
+        // Jobs cancelled past the timeout of the queue, unless given a deadline
+        if let (None, Some(timeout)) = (job.deadline(), self.default_timeout) {
+            let now = self
+                .clock
+                .as_ref()
+                .map_or_else(SystemTime::now, |clock| clock.now());
+
+            job.set_deadline(now + timeout);
+        }
+
+        for interceptor in &self.interceptors {
+            interceptor.intercept(&mut job)?;
+        }
+
+        // Queue resolved from the kind, unless enqueued to a named queue
+        if let Some(queue) = self
+            .named_queues
+            .as_ref()
+            .and_then(|named_queues| named_queues.route(job.kind()))
+        {
+            job.route_to(queue);
+        }
+
+        let job_id = job.id();
+
+        // A job of a shared backend may be claimed by another worker
+        if self.fetch_interval.is_none() && !self.kinds.accepts(job.kind()) {
+            return Err(api_err!(Error::KindNotAccepted(job.kind().to_owned())));
+        }
+
+        if self.fetch_interval.is_none() && !self.capabilities.satisfies(job.requirements()) {
+            return Err(api_err!(Error::MissingCapabilities(
+                self.capabilities.missing(job.requirements())
+            )));
+        }
+
+        // Undecodable jobs are left to the undecodable policy, unknown registered ones pending
+        if job.is_registered() {
+            if self.registry.contains(job.kind()) {
+                job.check_private_data(&self.registry.requirements(&job)?)?;
+            }
+        } else if let Ok(routine) = job.routine::<RoutineType, Context>() {
+            job.check_private_data(&routine.private_data_requirements())?;
+        }
+
+        if let Some(key) = job.idempotency_key() {
+            let original = self
+                .idempotency
+                .lock()
+                .map_err(|e| api_err!(Error::CannotAccessIdempotencyCache(e.to_string())))?
+                .submit(key, job_id);
+
+            if let Some(original) = original {
+                return Ok(Screened::Duplicate(original));
+            }
+        }
+
+        Ok(Screened::Admissible(Box::new(job)))
+    }
+
+    /// Pushes a job to the thread of the queue once admitted.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be pushed.
+    /// * `admitted` - Outcome of the admission of the job.
+    ///
+    /// # Returns
+    /// The job enqueued.
+    ///
+    /// # Errors
+    /// One of `Error` enum, the error of the admission if the job hasn't been admitted.
+    fn push(&self, job: Job, admitted: Result<(), ApiError>) -> Result<Enqueued, ApiError> {
+        let job_id = job.id();
+
+        // Jobs above the bound of the queue or the quota of their tenant rejected (submitted
+        // again later with the same key)
+        if let Err(e) = admitted {
+            if let Some(key) = job.idempotency_key() {
+                self.idempotency
+                    .lock()
+                    .map_err(|e| api_err!(Error::CannotAccessIdempotencyCache(e.to_string())))?
+                    .forget(key, job_id);
+            }
+
+            return Err(e);
+        }
+
+        self.subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .track(job_id, job.kind());
+
+        self.tx
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Job(Box::new(job)))
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Enqueued {
+            id: job_id,
+            duplicate: false,
+            cached_result: None,
+        })
+    }
+
+    /// Admits a job in the queue, waiting for room if full.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be admitted.
+    /// * `timeout` - Maximum duration to wait for room (forever if `None`).
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::QueueFull` if the queue is still full after the timeout.
+    fn admit(&self, job: &Job, timeout: Option<Duration>) -> Result<(), ApiError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        while let Some((room, made)) = self.try_admit(job)? {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            if remaining.is_some_and(|remaining| remaining.is_zero()) {
+                return Err(api_err!(Error::QueueFull));
+            }
+
+            // Woken up once a job is dispatched or dropped
+            room.wait(made, remaining);
+        }
+
+        Ok(())
+    }
+
+    /// Admits a job in the queue, awaiting room if full.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be admitted.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn admit_async(&self, job: &Job) -> Result<(), ApiError> {
+        while let Some((room, made)) = self.try_admit(job)? {
+            room.wait_async(made).await;
+        }
+
+        Ok(())
+    }
+
+    /// Tries to admit a job in the queue once.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be admitted.
+    ///
+    /// # Returns
+    /// `None` if admitted, the room of the queue and the number of times room had been made if
+    /// full, to wait for room.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::QuotaExceeded` if the quota of the tenant is reached.
+    fn try_admit(&self, job: &Job) -> Result<Option<(Arc<Room>, u64)>, ApiError> {
+        let mut dispatcher = self
+            .dispatcher
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessDispatcher(e.to_string())))?;
+        let room = dispatcher.room();
+        let made = room.made();
+
+        match dispatcher.admit(job.id(), job.tenant()) {
+            Err(e) if matches!(*e, Error::QueueFull) => Ok(Some((room, made))),
+            admitted => admitted.map(|()| None),
+        }
+    }
+
+    /// Stages a job without making it eligible for dispatch, so that the caller can complete
+    /// its own side effects first. The job is pushed to the queue by `confirm` or dropped by
+    /// `abort`, or dropped once stale (see `set_staging_ttl`).
+    ///
+    /// # Arguments
+    /// * `job` - Job to be staged.
+    ///
+    /// # Returns
+    /// The token of the job staged.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn prepare(&self, mut job: Job) -> Result<Token, ApiError> {
+        if let Some(generator) = &self.id_generator {
+            job.generate_id(generator.as_ref());
+        }
+
+        let job_id = job.id();
+        let now = self.now();
+
+        let mut staged = self
+            .staged
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessStagedJobs(e.to_string())))?;
+
+        // Stale jobs, whose token has been lost, dropped
+        staged.retain(|_, (_, staged_at)| !self.is_stale(*staged_at, now));
+        staged.insert(job_id, (job, now));
+
+        Ok(Token(job_id))
+    }
+
+    /// Pushes a job staged by `prepare` to be processed in the queue.
+    ///
+    /// # Arguments
+    /// * `token` - Token of the job staged.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::JobNotFound` if the job has been dropped once stale.
+    pub fn confirm(&self, token: Token) -> Result<Uuid, ApiError> {
+        let (job, staged_at) = self
+            .staged
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessStagedJobs(e.to_string())))?
+            .remove(&token.0)
+            .ok_or(api_err!(Error::JobNotFound(token.0)))?;
+
+        if self.is_stale(staged_at, self.now()) {
+            return Err(api_err!(Error::JobNotFound(token.0)));
+        }
+
+        self.enqueue(job)
+    }
+
+    /// Checks if a job staged by `prepare` is stale, to be dropped.
+    ///
+    /// # Arguments
+    /// * `staged_at` - Time the job was staged.
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// `true` if stale, `false` otherwise.
+    fn is_stale(&self, staged_at: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(staged_at)
+            .is_ok_and(|elapsed| elapsed >= self.staging_ttl)
+    }
+
+    /// Gets the current time of the clock of the queue.
+    ///
+    /// # Returns
+    /// The current time.
+    fn now(&self) -> SystemTime {
+        self.clock
+            .as_ref()
+            .map_or_else(SystemTime::now, |clock| clock.now())
+    }
+
+    /// Drops a job staged by `prepare`, it's never run.
+    ///
+    /// # Arguments
+    /// * `token` - Token of the job staged.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn abort(&self, token: Token) -> Result<(), ApiError> {
+        self.staged
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessStagedJobs(e.to_string())))?
+            .remove(&token.0)
+            .map(|_| ())
+            .ok_or(api_err!(Error::JobNotFound(token.0)))
+    }
+
+    /// Get the status of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The status of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.status(id)
+    }
+
+    /// Get the execution attempts of a job, the oldest first, including the ones of the jobs
+    /// it retries.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The attempts of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_attempts(&self, id: &Uuid) -> Result<Vec<Attempt>, ApiError> {
+        self.sweep_lazily().await?;
+
+        Ok(self.backend.lock().await.get(id).await?.attempts().to_vec())
+    }
+
+    /// Get the status of several jobs in a single call to the backend.
+    ///
+    /// # Arguments
+    /// * `ids` - IDs of the jobs to be inspected.
+    ///
+    /// # Returns
+    /// The status of the jobs, by ID; the jobs not found are left out.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.statuses(ids)
+    }
+
+    /// Wait for several jobs to reach a terminal status (see `Status::is_terminal`), or for a
+    /// timeout to expire.
+    ///
+    /// # Arguments
+    /// * `ids` - IDs of the jobs to be awaited.
+    /// * `timeout` - Maximum duration of the wait.
+    ///
+    /// # Returns
+    /// The status of the jobs, by ID, some of them not terminal if the timeout expired; the
+    /// jobs not found (e.g. removed) are left out.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn wait_for(
+        &self,
+        ids: &[Uuid],
+        timeout: Duration,
+    ) -> Result<HashMap<Uuid, Status>, ApiError> {
+        let clock = self.clock.clone().unwrap_or_else(|| Arc::new(SystemClock));
+        let deadline = clock.now() + timeout;
+
+        // Woken up on every change of status of the jobs awaited
+        let changed = Arc::new(Notify::new());
+        let awaited = ids.iter().copied().collect::<HashSet<_>>();
+        let subscription = {
+            let changed = changed.clone();
+
+            self.subscribe(
+                NotificationFilter::all().notification_type(NotificationType::Status),
+                move |notification| {
+                    if notification
+                        .job_id()
+                        .is_some_and(|id| awaited.contains(&id))
+                    {
+                        changed.notify_one();
+                    }
+                },
+            )?
+        };
+
+        let wait = async {
+            loop {
+                let statuses = self.job_statuses(ids).await?;
+
+                if !self.is_awaited(ids, &statuses)? {
+                    return Ok(statuses);
+                }
+
+                changed.notified().await;
+            }
+        };
+
+        let result = tokio::select! {
+            result = wait => result,
+            _ = clock.sleep_until(deadline) => self.job_statuses(ids).await,
+        };
+
+        self.unsubscribe(subscription)?;
+
+        result
+    }
+
+    /// Checks if some jobs awaited by `wait_for` haven't reached a terminal status yet.
+    ///
+    /// # Arguments
+    /// * `ids` - IDs of the jobs awaited.
+    /// * `statuses` - Status of the jobs found.
+    ///
+    /// # Returns
+    /// `true` if a job is still awaited, `false` otherwise.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn is_awaited(&self, ids: &[Uuid], statuses: &HashMap<Uuid, Status>) -> Result<bool, ApiError> {
+        let subscribers = self
+            .subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?;
+
+        // A job not found is either removed, or enqueued but not stored yet
+        Ok(ids.iter().any(|id| match statuses.get(id) {
+            Some(status) => !status.is_terminal(),
+            None => subscribers.is_tracked(id),
+        }))
+    }
+
+    /// Waits for the queue to become idle: no job is pending (including the delayed ones) or
+    /// running, e.g. to stop a batch pipeline once all the work submitted is done.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::Stopped` if the queue is stopped before becoming idle.
+    pub async fn wait_idle(&self) -> Result<(), ApiError> {
+        if self.state() == State::Idle {
+            return Err(api_err!(Error::NotStarted));
+        }
+
+        loop {
+            // Registered before checking, so that the wake-up sent meanwhile isn't missed
+            let idle = self.idle.notified();
+
+            if matches!(self.state(), State::Stopping | State::Stopped)
+                || self
+                    .join_handle
+                    .as_ref()
+                    .is_some_and(|handle| handle.is_finished())
+            {
+                return Err(api_err!(Error::Stopped));
+            }
+
+            if self
+                .dispatcher
+                .lock()
+                .map_err(|e| api_err!(Error::CannotAccessDispatcher(e.to_string())))?
+                .is_empty()
+            {
+                return Ok(());
+            }
+
+            idle.await;
+        }
+    }
+
+    /// Get the result of a job. A finished job with the `ExpirePolicy::OnResultFetch` policy
+    /// expires once its grace period is over.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The result of the job as vector of bytes.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        self.sweep_lazily().await?;
+
+        let mut backend = self.backend.lock().await;
+
+        let (result, _) = self.fetch_result(backend.as_mut(), id).await?;
+
+        // Removed by the next sweep once the grace period is over
+        if let ExpirePolicy::OnResultFetch(grace) = backend.expire_policy(id)? {
+            if let Status::Finished(_) = backend.status(id)? {
+                let now = self
+                    .clock
+                    .as_ref()
+                    .map_or_else(SystemTime::now, |clock| clock.now());
+
+                backend.set_expiration(id, now + grace)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Get the result of a job without consuming it (the `ExpirePolicy::OnResultFetch` policy is
+    /// not applied).
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The result of the job as vector of bytes.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn peek_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        self.sweep_lazily().await?;
+
+        let mut backend = self.backend.lock().await;
+
+        Ok(self.fetch_result(backend.as_mut(), id).await?.0)
+    }
+
+    /// Reads the result of a job, from the result store if offloaded.
+    ///
+    /// # Arguments
+    /// * `backend` - Backend of the queue, locked.
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The result of the job, with its key in the result store if offloaded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn fetch_result(
+        &self,
+        backend: &mut dyn Backend<RoutineType, Context>,
+        id: &Uuid,
+    ) -> Result<(Vec<u8>, Option<String>), ApiError> {
+        if let Some(offload) = &self.result_offload {
+            let job = backend.get(id).await?;
+            let now = self
+                .clock
+                .as_ref()
+                .map_or_else(SystemTime::now, |clock| clock.now());
+
+            if job.is_result_expired_at(now) {
+                return Err(api_err!(Error::ResultExpired(*id)));
+            }
+
+            if let Some(key) = job.result_ref() {
+                return Ok((offload.store.get(key)?, Some(key.to_owned())));
+            }
+        }
+
+        Ok((backend.result(id)?.to_vec(), None))
+    }
+
+    /// Get the progression of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The progression of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
+        self.sweep_lazily().await?;
+
+        let mut backend = self.backend.lock().await;
+
+        Ok(Progression {
+            eta: Self::estimate_eta(backend.as_mut(), id).await?,
+            ..backend.progression(id)?
+        })
+    }
+
+    /// Estimates the completion time of a job, from the durations of the previous runs of its
+    /// kind and its progression.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The estimated completion time, `None` if the job is finished or no run of its kind has
+    /// been recorded yet.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_eta(&self, id: &Uuid) -> Result<Option<SystemTime>, ApiError> {
+        self.sweep_lazily().await?;
+
+        Self::estimate_eta(self.backend.lock().await.as_mut(), id).await
+    }
+
+    /// Get the metadata attached to a job, including the ones attached by its routine while
+    /// running (see `Job::save_metadata`).
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The key/value pairs attached.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_metadata(
+        &self,
+        id: &Uuid,
+    ) -> Result<BTreeMap<String, serde_json::Value>, ApiError> {
+        self.sweep_lazily().await?;
+
+        Ok(self.backend.lock().await.get(id).await?.metadata().clone())
+    }
+
+    /// Streams the result of a job chunk by chunk, as emitted by its routine (see
+    /// `Job::emit_chunk`): the chunks already stored are replayed, then the new ones are
+    /// streamed as stored.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be followed.
+    ///
+    /// # Returns
+    /// The receiving end of the stream, closed once the job is finished and all of its chunks
+    /// received.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn stream_result(
+        &self,
+        id: &Uuid,
+    ) -> Result<tokio::sync::mpsc::Receiver<Result<Vec<u8>, ApiError>>, ApiError> {
+        self.sweep_lazily().await?;
+        self.backend.lock().await.status(id)?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let backend = self.backend.clone();
+        let id = id.to_owned();
+
+        tokio::spawn(async move {
+            let mut next = 0;
+
+            loop {
+                // Status fetched first: the chunks of a finished job are all stored
+                let fetched = {
+                    let backend = backend.lock().await;
+
+                    backend
+                        .status(&id)
+                        .and_then(|status| Ok((status, backend.chunks(&id, next)?)))
+                };
+
+                let (status, chunks) = match fetched {
+                    Ok(fetched) => fetched,
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+
+                        return;
+                    }
+                };
+
+                for chunk in chunks {
+                    if tx.send(Ok(chunk)).await.is_err() {
+                        // Receiver dropped
+                        return;
+                    }
+
+                    next += 1;
+                }
+
+                if let Status::Finished(_) | Status::Removed = status {
+                    return;
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// Get the routine of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The routine of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.routine(id)
+    }
+
+    /// Get a job, with its routine, payload and private data.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job(&self, id: &Uuid) -> Result<Job, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.get(id).await
+    }
+
+    /// Get the list of jobs.
+    ///
+    /// # Returns
+    /// The summary of every job (see `job` to get a full job), ordered by enqueue time then ID.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn jobs(&self) -> Result<Vec<JobInfo>, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.job_infos()
+    }
+
+    /// Get a page of the list of jobs, ordered by enqueue time then ID: paging through the jobs
+    /// from one cursor to the next lists every job once, even if jobs are enqueued meanwhile.
+    ///
+    /// # Arguments
+    /// * `after` - Cursor returned with the previous page, `None` for the first page.
+    /// * `limit` - Maximum number of jobs in the page.
+    ///
+    /// # Returns
+    /// The page, with the cursor of the next one (`None` for the last page).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn jobs_page(
+        &self,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Result<JobPage, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.jobs_page(after, limit)
+    }
+
+    /// Counts the jobs selected by a filter, without listing them.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs counted.
+    ///
+    /// # Returns
+    /// The number of jobs selected.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn count_jobs(&self, filter: &JobFilter) -> Result<usize, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.count(filter)
+    }
+
+    /// Counts the jobs selected by a filter, by group, without listing them.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs counted.
+    /// * `group_by` - Criterion grouping the jobs (status, kind or named queue).
+    ///
+    /// # Returns
+    /// The number of jobs selected, by group.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn aggregate_jobs(
+        &self,
+        filter: &JobFilter,
+        group_by: GroupBy,
+    ) -> Result<HashMap<GroupKey, usize>, ApiError> {
+        self.sweep_lazily().await?;
+
+        self.backend.lock().await.aggregate(filter, group_by)
+    }
+
+    /// Removes a job from the queue, applying a policy if it's not finished yet.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be removed.
+    /// * `policy` - Policy applied if the job is not finished.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn remove_job_with(&self, id: &Uuid, policy: RemovePolicy) -> Result<(), ApiError> {
+        match policy {
+            RemovePolicy::OnlyFinished => (),
+            RemovePolicy::CancelAndRemove => self.cancel_job(id).await?,
+            RemovePolicy::WaitAndRemove(timeout) => self.wait_finished(id, timeout).await?,
+        }
+
+        self.remove_job(id).await
+    }
+
+    /// Cancels a job: a pending job is never run and a running job is aborted. In both cases
+    /// the job is finished as `ResultStatus::Cancelled`. Nothing is done if the job is already
+    /// finished.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be cancelled.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn cancel_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        Self::cancel(&self.resources(), id, ResultStatus::Cancelled).await
+    }
+
+    /// Cancels a job taken by the queue (pending or running).
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `id` - ID of the job to be cancelled.
+    /// * `reason` - Result status reported for the job (cancelled or timed out).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn cancel(
+        resources: &QueueResources<RoutineType, Context>,
+        id: &Uuid,
+        reason: ResultStatus,
+    ) -> Result<(), ApiError> {
+        Self::forget_deadline(&resources.deadlines, id)?;
+
+        // A job still in the channel is stored as cancelled, the queue never getting it
+        let withdrawn = resources
+            .messages_channel
+            .lock()
+            .map_err(|e| Error::CannotAccessSender(e.to_string()))?
+            .withdraw(id);
+
+        let pending = resources
+            .dispatcher
+            .lock()
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+            .remove(id)
+            || resources
+                .series
+                .lock()
+                .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?
+                .remove_waiting(id);
+
+        let aborted = match resources
+            .running
+            .lock()
+            .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?
+            .remove(id)
+        {
+            Some(handle) => {
+                handle.abort();
+
+                true
+            }
+
+            None => false,
+        };
+
+        if aborted {
+            Self::finish_occurrence(resources, id)?;
+            Self::release_slot(&resources.dispatcher, &resources.messages_channel, id)?;
+        }
+
+        let mut backend = resources.backend.lock().await;
+
+        if let Some(job) = withdrawn {
+            backend.schedule(job)?;
+            backend.set_status(id, Status::Ready)?;
+        }
+
+        let status = backend.status(id)?;
+
+        if let Status::Finished(_) = status {
+            return Ok(());
+        }
+
+        // A job waiting for its backoff delay is cancelled before being run again
+        if pending || aborted || status == Status::Retrying {
+            let status = Status::Finished(reason);
+            let job = backend.get(id).await?;
+
+            // A cancelled job can be submitted again with the same idempotency key
+            if let Some(key) = job.idempotency_key() {
+                resources
+                    .idempotency
+                    .lock()
+                    .map_err(|e| api_err!(Error::CannotAccessIdempotencyCache(e.to_string())))?
+                    .forget(key, id.to_owned());
+            }
+
+            let error = match reason {
+                ResultStatus::DeadlineExceeded => Error::DeadlineExceeded,
+                _ => Error::JobCancelled,
+            };
+
+            if let Ok(bytes) = Self::string_to_json_error(&error.to_string()) {
+                backend.set_result(id, bytes)?;
+            }
+
+            // Only a job aborted while running has made an attempt
+            if aborted {
+                backend.add_attempt(
+                    id,
+                    Attempt {
+                        started: job.timestamps().started,
+                        finished: SystemTime::now(),
+                        status: reason,
+                        error: Some(error.to_string()),
+                        worker: Some(resources.worker_id.clone()),
+                    },
+                )?;
+            }
+
+            backend.set_status(id, status)?;
+
+            (resources.notification_handler)(Notification::Status(id.to_owned(), status));
+
+            if aborted {
+                resources
+                    .hooks
+                    .finished(&job, reason, &[], &error.to_string());
+            }
+
+            Self::complete(resources, &job, reason, vec![], Some(error.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// Resizes the pool of workers of a started queue, i.e. the maximum number of jobs running
+    /// at the same time (see `set_max_in_flight`). When shrunk, the jobs running keep running
+    /// and no other job is dispatched until enough of them have finished. The threads of the
+    /// runtime carrying the jobs are left as they are.
+    ///
+    /// # Arguments
+    /// * `size` - Number of workers (at least 1).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn resize_pool(&self, size: usize) -> Result<(), ApiError> {
+        if self.state == State::Idle {
+            return Err(api_err!(Error::NotStarted));
+        }
+
+        self.dispatcher
+            .lock()
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+            .set_max_in_flight(size.max(1));
+
+        // Jobs waiting for a slot dispatched right away when grown
+        self.tx
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Dispatch))
+            .map_err(|e| api_err!(e.into()))
+    }
+
+    /// Pauses the queue: the jobs running keep running but no other job is dispatched until
+    /// resumed. The flag is stored in the backend, hence pausing every worker sharing it (same
+    /// namespace).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn pause(&self) -> Result<(), ApiError> {
+        self.backend.lock().await.set_paused(true)
+    }
+
+    /// Resumes the queue, paused by any worker sharing the backend. The other workers resume
+    /// at their next dispatch (or poll in distributed mode).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn resume(&self) -> Result<(), ApiError> {
+        self.backend.lock().await.set_paused(false)?;
+
+        self.tx
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Dispatch))
+            .map_err(|e| api_err!(e.into()))
+    }
+
+    /// Checks if the queue is paused.
+    ///
+    /// # Returns
+    /// `true` if paused, `false` otherwise.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn is_paused(&self) -> Result<bool, ApiError> {
+        self.backend.lock().await.is_paused()
+    }
+
+    /// Suspends a running job: its routine is parked at its next suspension point (see
+    /// `Job::yield_if_suspended`), keeping its slot, until resumed. A routine without
+    /// suspension point runs until finished.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be suspended.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn suspend_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        self.tx
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Suspend(id.to_owned())))
+            .map_err(|e| api_err!(e.into()))
+    }
+
+    /// Resumes a suspended job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be resumed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn resume_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        self.tx
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Resume(id.to_owned())))
+            .map_err(|e| api_err!(e.into()))
+    }
+
+    /// Waits for a job to be finished.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to wait for.
+    /// * `timeout` - Maximum duration to wait.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn wait_finished(&self, id: &Uuid, timeout: Duration) -> Result<(), ApiError> {
+        let waiting = async {
+            loop {
+                if let Status::Finished(_) = self.backend.lock().await.status(id)? {
+                    return Ok::<(), ApiError>(());
+                }
+
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+
+        tokio::time::timeout(timeout, waiting)
+            .await
+            .map_err(|e| api_err!(Error::Timeout(e.to_string())))?
+    }
+
+    /// Estimates the time needed to process every pending job (ready or running), based on the
+    /// recent throughput of each kind of job.
+    ///
+    /// # Returns
+    /// The estimated duration, `None` if a pending kind has no completed job to measure from.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn estimated_drain_time(&self) -> Result<Option<Duration>, ApiError> {
+        let mut depths: HashMap<String, u64> = HashMap::new();
+        let backend = self.backend.lock().await;
+
+        // Counted by the backend, without listing the jobs
+        for status in [
+            Status::NotReady,
+            Status::Ready,
+            Status::Running,
+            Status::Suspended,
+            Status::Retrying,
+        ] {
+            let groups = backend.aggregate(&JobFilter::default().status(status), GroupBy::Kind)?;
+
+            for (key, count) in groups {
+                if let GroupKey::Kind(kind) = key {
+                    *depths.entry(kind).or_default() += count as u64;
+                }
+            }
+        }
+
+        drop(backend);
+
+        let throughput = self
+            .throughput
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessThroughput(e.to_string())))?;
+
+        Ok(throughput.drain_time(&depths))
+    }
+
+    /// Enqueues again a finished job: a new job runs the same routine with the same settings.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be retried.
+    ///
+    /// # Returns
+    /// The unique ID of the new job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn retry_job(&self, id: &Uuid) -> Result<Uuid, ApiError> {
+        let job = self.backend.lock().await.get(id).await?;
+
+        if !matches!(job.status(), Status::Finished(_)) {
+            return Err(api_err!(Error::JobNotFinished));
+        }
+
+        self.enqueue(job.retry())
+    }
+
+    /// Gives access to the backend of the queue, locked until the future returned by the
+    /// closure completes, for the operations the queue doesn't expose. The jobs must be kept
+    /// consistent with the queue (e.g. a job dispatched must not be removed).
+    ///
+    /// # Arguments
+    /// * `f` - Closure called with the backend locked.
+    ///
+    /// # Returns
+    /// The output of the future returned by the closure.
+    pub async fn with_backend<F, Fut, T>(&self, f: F) -> T
+    where
+        F: FnOnce(OwnedMutexGuard<Box<dyn Backend<RoutineType, Context>>>) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        f(self.backend.clone().lock_owned().await).await
+    }
+
+    /// Checks that a sample of the stored pending jobs still deserializes against the routine
+    /// type.
+    ///
+    /// # Arguments
+    /// * `sample` - Maximum number of jobs checked.
+    ///
+    /// # Returns
+    /// The incompatible kinds.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn check_schema(&self, sample: usize) -> Result<Vec<SchemaDrift>, ApiError> {
+        let jobs = self.backend.lock().await.jobs()?;
+
+        Ok(detect_drift::<RoutineType, Context>(&jobs, sample))
+    }
+
+    /// Gets the queued and running intervals of the jobs active during a time range, e.g. to
+    /// render the utilization of the workers.
+    ///
+    /// # Arguments
+    /// * `range` - Time range.
+    /// * `filter` - Filter selecting the jobs.
+    ///
+    /// # Returns
+    /// The intervals of the jobs selected, sorted by enqueue time.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn timeline(
+        &self,
+        range: Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError> {
+        self.backend.lock().await.timeline(&range, filter)
+    }
+
+    /// Gets the jobs moved to the dead letters because they cannot be deserialized.
+    ///
+    /// # Returns
+    /// The dead letters.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn dead_letters(&self) -> Result<Vec<Job>, ApiError> {
+        Ok(self
+            .dead_letters
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessUndecodableJobs(e.to_string())))?
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    /// Gets the jobs quarantined in the backend because they cannot be deserialized.
+    ///
+    /// # Returns
+    /// The list of IDs of the quarantined jobs.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn quarantined_jobs(&self) -> Result<Vec<Uuid>, ApiError> {
+        Ok(self
+            .quarantined
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessUndecodableJobs(e.to_string())))?
+            .iter()
+            .copied()
+            .collect())
+    }
+
+    /// Gets a snapshot of the jobs of the queue, counted by status.
+    ///
+    /// # Returns
+    /// The statistics of the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn stats(&self) -> Result<QueueStats, ApiError> {
+        let groups = self
+            .aggregate_jobs(&JobFilter::default(), GroupBy::Status)
+            .await?;
+        let mut stats = QueueStats::from_groups(&groups);
+
+        stats.drain_time = self.estimated_drain_time().await?;
+
+        Ok(stats)
+    }
+
+    /// Gets the utilization of the worker (time spent by its slots running jobs or idle) and
+    /// its saturation (jobs ready to be run vs free slots), e.g. to size its pool or autoscale.
+    ///
+    /// # Returns
+    /// An instance of `WorkerStats`.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn worker_stats(&self) -> Result<WorkerStats, ApiError> {
+        let ready = self
+            .count_jobs(&JobFilter::default().status(Status::Ready))
+            .await?;
+
+        Ok(self
+            .dispatcher
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessDispatcher(e.to_string())))?
+            .worker_stats(&self.worker_id, ready))
+    }
+
+    /// Gets a snapshot of the jobs of every named queue, counted by status.
+    ///
+    /// # Returns
+    /// The statistics of the queues (without drain time), by name.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn queue_stats(&self) -> Result<HashMap<String, QueueStats>, ApiError> {
+        self.sweep_lazily().await?;
+
+        let backend = self.backend.lock().await;
+        let mut queues = HashMap::new();
+
+        for key in backend
+            .aggregate(&JobFilter::default(), GroupBy::Queue)?
+            .into_keys()
+        {
+            if let GroupKey::Queue(queue) = key {
+                let groups = backend
+                    .aggregate(&JobFilter::default().queue(queue.clone()), GroupBy::Status)?;
+
+                queues.insert(queue, QueueStats::from_groups(&groups));
+            }
+        }
+
+        Ok(queues)
+    }
+
+    /// Takes a lock on a key (serial key, singleton...), unless held by another owner and not
+    /// stale. The lock must be refreshed before it expires to be kept.
+    ///
+    /// # Arguments
+    /// * `key` - Key to be locked.
+    /// * `owner` - Identifier of the worker taking the lock.
+    /// * `ttl` - Duration after which the lock expires if not refreshed.
+    ///
+    /// # Returns
+    /// `true` if the lock has been taken, `false` if held by another owner.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn acquire_lock(
+        &self,
+        key: &str,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<bool, ApiError> {
+        self.backend.lock().await.acquire_lock(key, owner, ttl)
+    }
+
+    /// Extends a lock held by an owner (heartbeat).
+    ///
+    /// # Arguments
+    /// * `key` - Key locked.
+    /// * `owner` - Identifier of the worker holding the lock.
+    /// * `ttl` - Duration, from now, after which the lock expires if not refreshed.
+    ///
+    /// # Returns
+    /// `true` if the lock has been extended, `false` if not held by this owner.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn refresh_lock(
+        &self,
+        key: &str,
+        owner: &str,
+        ttl: Duration,
+    ) -> Result<bool, ApiError> {
+        self.backend.lock().await.refresh_lock(key, owner, ttl)
+    }
+
+    /// Releases a lock held by an owner.
+    ///
+    /// # Arguments
+    /// * `key` - Key locked.
+    /// * `owner` - Identifier of the worker holding the lock.
+    ///
+    /// # Returns
+    /// `true` if the lock has been released, `false` if not held by this owner.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn release_lock(&self, key: &str, owner: &str) -> Result<bool, ApiError> {
+        self.backend.lock().await.release_lock(key, owner)
+    }
+
+    /// Removes a finished job from the queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn remove_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        let mut backend = self.backend.lock().await;
+
+        let soft_delete = self
+            .tombstones
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessDeletedJobs(e.to_string())))?
+            .is_enabled();
+
+        let job = match soft_delete {
+            true => Some(backend.get(id).await?),
+            false => None,
+        };
+
+        // The offloaded result kept for a restored job, dropped otherwise
+        let result_ref = match (&self.result_offload, &job) {
+            (Some(_), None) => backend.get(id).await?.result_ref().map(str::to_owned),
+            _ => None,
+        };
+
+        backend.remove(id)?;
+
+        Self::forget_deadline(&self.deadlines, id)?;
+
+        if let (Some(offload), Some(key)) = (&self.result_offload, result_ref) {
+            offload.store.remove(&key)?;
+        }
+
+        if let Some(job) = job {
+            self.tombstones
+                .lock()
+                .map_err(|e| api_err!(Error::CannotAccessDeletedJobs(e.to_string())))?
+                .bury(job);
+        }
+
+        (self.notification_handler)(Notification::Status(id.to_owned(), Status::Removed));
+
+        Ok(())
+    }
+
+    /// Gets the jobs removed while soft-delete is enabled, that can still be restored.
+    ///
+    /// # Returns
+    /// The removed jobs, the most recently removed first.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn deleted_jobs(&self) -> Result<Vec<Job>, ApiError> {
+        Ok(self
+            .tombstones
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessDeletedJobs(e.to_string())))?
+            .jobs())
+    }
+
+    /// Restores a job removed while soft-delete is enabled, as it was when removed.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be restored.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::JobNotFound` if the job is not removed or out of the
+    /// retention window.
+    pub async fn restore_job(&self, id: &Uuid) -> Result<(), ApiError> {
+        let job = self
+            .tombstones
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessDeletedJobs(e.to_string())))?
+            .exhume(id)
+            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?;
+
+        let status = job.status();
+
+        self.subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .track(id.to_owned(), job.kind());
+
+        self.backend.lock().await.schedule(job)?;
+
+        (self.notification_handler)(Notification::Status(id.to_owned(), status));
+
+        Ok(())
+    }
+
+    /// Removes, in one pass, all the finished jobs selected by a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs to be removed.
+    ///
+    /// # Returns
+    /// The list of IDs of the removed jobs.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn purge(&self, filter: PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        Self::purge_once(
+            &self.backend,
+            &self.tombstones,
+            &self.notification_handler,
+            &filter,
+        )
+        .await
+    }
+
+    /// Removes the finished jobs selected by a filter in the background, by chunks spaced by an
+    /// interval, so that a large purge neither blocks the queue nor loads the backend. The
+    /// progress is notified with `Notification::PurgeProgress` after every chunk.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs to be removed (its limit is ignored).
+    /// * `chunk_size` - Maximum number of jobs removed at once.
+    /// * `interval` - Pause between two chunks.
+    ///
+    /// # Returns
+    /// The purge running.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn purge_in_background(
+        &self,
+        filter: PurgeFilter,
+        chunk_size: usize,
+        interval: Duration,
+    ) -> Result<PurgeTask, ApiError> {
+        let id = Uuid::from_bytes(rand::random());
+        let chunk_size = chunk_size.max(1);
+        let filter = PurgeFilter {
+            limit: Some(chunk_size),
+            ..filter
+        };
+
+        let backend = self.backend.clone();
+        let tombstones = self.tombstones.clone();
+        let notification_handler = self.notification_handler.clone();
+
+        let handle = self
+            .runtime
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))?
+            .spawn(async move {
+                let mut progress = PurgeProgress::default();
+
+                while !progress.done {
+                    match Self::purge_once(&backend, &tombstones, &notification_handler, &filter)
+                        .await
+                    {
+                        Ok(job_ids) => {
+                            progress.removed += job_ids.len();
+                            progress.done = job_ids.len() < chunk_size;
+                        }
+                        Err(e) => {
+                            notification_handler(Notification::Error(*e));
+                            progress.done = true;
+                        }
+                    }
+
+                    notification_handler(Notification::PurgeProgress(id, progress));
+
+                    if !progress.done {
+                        tokio::time::sleep(interval).await;
+                    }
+                }
+
+                progress.removed
+            });
+
+        Ok(PurgeTask::new(id, handle))
+    }
+
+    /// Removes the finished jobs selected by a filter, keeping them if soft-delete is enabled.
+    ///
+    /// # Arguments
+    /// * `backend` - Backend storing the jobs.
+    /// * `tombstones` - Jobs removed while soft-delete is enabled.
+    /// * `notification_handler` - Notification handler function.
+    /// * `filter` - Filter selecting the jobs to be removed.
+    ///
+    /// # Returns
+    /// The list of IDs of the removed jobs.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn purge_once(
+        backend: &SharedBackend<RoutineType, Context>,
+        tombstones: &Shared<Tombstones>,
+        notification_handler: &SharedNotificationHandler,
+        filter: &PurgeFilter,
+    ) -> Result<Vec<Uuid>, ApiError> {
+        let mut backend = backend.lock().await;
+        let mut tombstones = tombstones
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessDeletedJobs(e.to_string())))?;
+
+        // Jobs kept as they were before being purged
+        let mut jobs: HashMap<Uuid, Job> = match tombstones.is_enabled() {
+            true => backend
+                .jobs()?
+                .into_iter()
+                .filter(|job| filter.matches(job))
+                .map(|job| (job.id(), job))
+                .collect(),
+            false => HashMap::new(),
+        };
+
+        let job_ids = backend.purge(filter)?;
+
+        for job_id in &job_ids {
+            if let Some(job) = jobs.remove(job_id) {
+                tombstones.bury(job);
+            }
+        }
+
+        for job_id in &job_ids {
+            notification_handler(Notification::Status(job_id.to_owned(), Status::Removed));
+        }
+
+        Ok(job_ids)
+    }
+
+    /// Reaps the expired jobs (and applies the retention policy) when the expirations are
+    /// evaluated lazily.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn sweep_lazily(&self) -> Result<(), ApiError> {
+        if self.sweep_strategy != SweepStrategy::Lazy {
+            return Ok(());
+        }
+
+        let mut backend = self.backend.lock().await;
+
+        // Results offloaded by the expired jobs dropped before the jobs
+        Self::expire_results(backend.as_mut(), &self.result_offload)?;
+
+        let job_ids = backend.remove_expired()?;
+
+        Self::notify_expired(&self.notification_handler, job_ids);
+
+        if let RetentionPolicy::KeepLastN(count) = self.retention {
+            for job_id in backend.retain_last(count)? {
+                (self.notification_handler)(Notification::Status(job_id, Status::Removed));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drops the results of the finished jobs that have expired, the ones offloaded being
+    /// removed from the result store.
+    ///
+    /// # Arguments
+    /// * `backend` - Backend storing the jobs.
+    /// * `result_offload` - Policy writing the large results to a result store.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn expire_results(
+        backend: &mut dyn Backend<RoutineType, Context>,
+        result_offload: &Option<ResultOffload>,
+    ) -> Result<(), ApiError> {
+        for (_, key) in backend.expire_results()? {
+            if let (Some(offload), Some(key)) = (result_offload, key) {
+                offload.store.remove(&key)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notifies that jobs have been reaped because they have expired.
+    ///
+    /// # Arguments
     /// * `notification_handler` - Handler for notifications.
-    /// * `messages_channel` - Channel used to communicate with the queue thread.
-    /// * `context` - Context used by the jobs.
+    /// * `job_ids` - IDs of the reaped jobs.
+    fn notify_expired(notification_handler: &SharedNotificationHandler, job_ids: Vec<Uuid>) {
+        for job_id in job_ids {
+            notification_handler(Notification::JobExpired(job_id));
+            notification_handler(Notification::Status(job_id, Status::Removed));
+        }
+    }
+
+    /// Joins the thread of the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn join_thread(&mut self) -> Result<(), ApiError> {
+        self.try_joining()?;
+
+        match self.join_handle.take() {
+            Some(handle) => handle
+                .join()
+                .map_err(|_| api_err!(Error::CannotJoinThread))?,
+            None => return Err(api_err!(Error::MissingJoinHandle)),
+        }
+
+        self.state = State::Stopped;
+
+        Ok(())
+    }
+
+    /// Waits for the jobs still running to finish.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn wait_running(&self) -> Result<(), ApiError> {
+        while !self
+            .running
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRunningJobs(e.to_string())))?
+            .is_empty()
+        {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        Ok(())
+    }
+
+    /// Estimates the completion time of a job.
+    ///
+    /// # Arguments
+    /// * `backend` - Backend storing the job.
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The estimated completion time, if known.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn estimate_eta(
+        backend: &mut dyn Backend<RoutineType, Context>,
+        id: &Uuid,
+    ) -> Result<Option<SystemTime>, ApiError> {
+        let job = backend.get(id).await?;
+        let stats = backend.duration_stats(job.kind())?;
+
+        Ok(DurationStats::eta(stats.as_ref(), &job, SystemTime::now()))
+    }
+
+    /// Checks if the current state allows to start the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn try_starting(&self) -> Result<(), ApiError> {
+        match self.state {
+            State::Running => Err(api_err!(Error::AlreadyRunning)),
+            State::Stopping | State::Stopped => Err(api_err!(Error::Stopped)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks if the current state allows to join the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn try_joining(&self) -> Result<(), ApiError> {
+        match self.state() {
+            State::Idle => Err(api_err!(Error::NotStarted)),
+            State::Running => Err(api_err!(Error::NotStopping)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks if the current state allows to stop the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn try_stopping(&self) -> Result<(), ApiError> {
+        match self.state() {
+            State::Idle => Err(api_err!(Error::NotStarted)),
+            State::Stopping | State::Stopped => Err(api_err!(Error::Stopped)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the resources shared between the thread of the queue and the jobs it runs.
+    ///
+    /// # Returns
+    /// An instance of `QueueResources`.
+    fn resources(&self) -> QueueResources<RoutineType, Context> {
+        QueueResources {
+            backend: self.backend.clone(),
+            runtime: self.runtime.clone(),
+            notification_handler: self.notification_handler.clone(),
+            messages_channel: self.tx.clone(),
+            context: self.context.clone(),
+            context_resolver: self.context_resolver.clone(),
+            throughput: self.throughput.clone(),
+            dispatcher: self.dispatcher.clone(),
+            running: self.running.clone(),
+            deadlines: self.deadlines.clone(),
+            suspensions: self.suspensions.clone(),
+            undecodable_policy: self.undecodable_policy,
+            dead_letters: self.dead_letters.clone(),
+            quarantined: self.quarantined.clone(),
+            worker_id: self.worker_id.clone(),
+            kinds: self.kinds.clone(),
+            capabilities: self.capabilities.clone(),
+            registry: self.registry.clone(),
+            middlewares: self.middlewares.clone(),
+            hooks: self.hooks.clone(),
+            clock: self.clock.clone().unwrap_or_else(|| Arc::new(SystemClock)),
+            distributed: self.fetch_interval.is_some(),
+            orphan_reaper: self.orphan_reaper,
+            watchdog: self.watchdog.clone(),
+            retry_policy: self.retry_policy,
+            lease: self.lease,
+            idempotency: self.idempotency.clone(),
+            result_offload: self.result_offload.clone(),
+            subscribers: self.subscribers.clone(),
+            series: self.series.clone(),
+            idle: self.idle.clone(),
+            id_generator: self.id_generator.clone(),
+        }
+    }
+
+    /// Drains the queue once stopped: the jobs taken keep being dispatched by priority until
+    /// they're all finished or the timeout elapses, the remaining ones being then cancelled by
+    /// increasing priority.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `rx` - Channel receiving the messages of the queue.
+    /// * `timeout` - Maximum duration of the drain.
+    fn drain(
+        resources: &QueueResources<RoutineType, Context>,
+        rx: &MessageReceiver,
+        timeout: Duration,
+    ) {
+        let notification_handler = &resources.notification_handler;
+        let deadline = SystemTime::now() + timeout;
+
+        let draining = resources
+            .dispatcher
+            .lock()
+            .map(|mut dispatcher| dispatcher.drain())
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()));
+
+        if let Err(e) = draining {
+            notification_handler(Notification::Error(e));
+
+            return;
+        }
+
+        Self::process_message(resources, Message::Command(Cmd::Dispatch));
+
+        loop {
+            match resources.dispatcher.lock() {
+                Ok(dispatcher) if dispatcher.is_idle() => return,
+                Ok(_) => {}
+                Err(e) => {
+                    notification_handler(Notification::Error(Error::CannotAccessDispatcher(
+                        e.to_string(),
+                    )));
+
+                    return;
+                }
+            }
+
+            let remaining = deadline
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+
+            match rx.recv_timeout(remaining) {
+                Ok(Message::Command(Cmd::Stop)) => {}
+                Ok(msg) => Self::process_message(resources, msg),
+                Err(_) => break,
+            }
+        }
+
+        // Timeout elapsed: the least urgent jobs cancelled first
+        let cancelling = resources
+            .runtime
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))
+            .and_then(|rt| {
+                rt.block_on(async {
+                    let mut job_ids = resources
+                        .dispatcher
+                        .lock()
+                        .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+                        .pending_ids();
+
+                    job_ids.extend(
+                        resources
+                            .running
+                            .lock()
+                            .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?
+                            .keys(),
+                    );
+
+                    let mut jobs = vec![];
+
+                    for job_id in job_ids {
+                        jobs.push(resources.backend.lock().await.get(&job_id).await?);
+                    }
+
+                    jobs.sort_by_key(|job| job.priority());
+
+                    for job in jobs {
+                        Self::cancel(resources, &job.id(), ResultStatus::TimedOut).await?;
+                    }
+
+                    Ok(())
+                })
+            });
+
+        if let Err(e) = cancelling {
+            notification_handler(Notification::Error(*e));
+        }
+    }
+
+    /// Processes a message (can be a command or job).
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
     /// * `msg` - Message to be processed.
-    fn process_message(
-        backend: SharedBackend<RoutineType, Context>,
-        runtime: SharedRuntime,
-        notification_handler: SharedNotificationHandler,
-        messages_channel: Shared<Sender<Message>>,
-        context: Option<Shared<Context>>,
-        msg: Message,
+    fn process_message(resources: &QueueResources<RoutineType, Context>, msg: Message) {
+        let notification_handler = resources.notification_handler.clone();
+
+        match msg {
+            Message::Job(job) => {
+                let _ = JobQueue::process_job(resources, *job)
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+            }
+
+            Message::Command(cmd) => {
+                let _ = JobQueue::process_command(resources, cmd)
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+            }
+        }
+    }
+
+    /// Processes a command.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `cmd` - Command to be processed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn process_command(
+        resources: &QueueResources<RoutineType, Context>,
+        cmd: Cmd,
+    ) -> Result<(), ApiError> {
+        let notification_handler = &resources.notification_handler;
+
+        let runtime = resources
+            .runtime
+            .lock()
+            .map_err(|e| Error::CannotAccessRuntime(e.to_string()))?;
+
+        if cmd == Cmd::Dispatch || cmd == Cmd::Fetch {
+            if resources.distributed {
+                Self::fetch(resources, &runtime, cmd == Cmd::Fetch)?;
+            }
+
+            return Self::dispatch(resources, &runtime);
+        }
+
+        runtime.block_on(async {
+            let mut backend = resources.backend.lock().await;
+
+            match cmd {
+                Cmd::CheckExpirations => {
+                    let _ = Self::expire_results(backend.as_mut(), &resources.result_offload)
+                        .map_err(|e| notification_handler(Notification::Error(*e)));
+
+                    if let Ok(job_ids) = backend
+                        .remove_expired()
+                        .map_err(|e| notification_handler(Notification::Error(*e)))
+                    {
+                        Self::notify_expired(notification_handler, job_ids);
+                    }
+                }
+
+                Cmd::ApplyRetention(count) => {
+                    if let Ok(job_ids) = backend
+                        .retain_last(count)
+                        .map_err(|e| notification_handler(Notification::Error(*e)))
+                    {
+                        for job_id in job_ids {
+                            notification_handler(Notification::Status(job_id, Status::Removed));
+                        }
+                    }
+                }
+
+                Cmd::ReleaseStaleLocks => {
+                    if let Ok(locks) = backend
+                        .release_stale_locks()
+                        .map_err(|e| notification_handler(Notification::Error(*e)))
+                    {
+                        for lock in locks {
+                            notification_handler(Notification::StaleLockReleased(lock));
+                        }
+                    }
+                }
+
+                Cmd::ReapOrphans => {
+                    if let Some((timeout, policy)) = resources.orphan_reaper {
+                        if let Ok(job_ids) = backend
+                            .reap_orphans(timeout, policy)
+                            .map_err(|e| notification_handler(Notification::Error(*e)))
+                        {
+                            for job_id in job_ids {
+                                notification_handler(Notification::JobOrphaned(job_id, policy));
+                            }
+                        }
+                    }
+                }
+
+                Cmd::SaveCheckpoint(job_id, checkpoint) => {
+                    let _ = backend
+                        .set_checkpoint(&job_id, checkpoint)
+                        .map_err(|e| notification_handler(Notification::Error(*e)));
+                }
+
+                Cmd::SetMetadata(job_id, key, value) => {
+                    let _ = backend
+                        .set_metadata(&job_id, &key, value)
+                        .map_err(|e| notification_handler(Notification::Error(*e)));
+                }
+
+                Cmd::Custom(job_id, name, payload) => match backend.get(&job_id).await {
+                    Ok(job) => {
+                        resources.hooks.command(&job, &name, &payload);
+
+                        notification_handler(Notification::Custom(job_id, name, payload));
+                    }
+                    Err(e) => notification_handler(Notification::Error(*e)),
+                },
+
+                Cmd::Suspend(job_id) => {
+                    let _ = Self::suspension(resources, &job_id)
+                        .and_then(|suspension| {
+                            suspension.suspend();
+                            backend.set_status(&job_id, Status::Suspended)
+                        })
+                        .map(|_| {
+                            notification_handler(Notification::Status(job_id, Status::Suspended))
+                        })
+                        .map_err(|e| notification_handler(Notification::Error(*e)));
+                }
+
+                Cmd::Resume(job_id) => {
+                    let _ = Self::suspension(resources, &job_id)
+                        .and_then(|suspension| {
+                            backend.set_status(&job_id, Status::Running)?;
+                            suspension.resume();
+
+                            Ok(())
+                        })
+                        .map(|_| {
+                            notification_handler(Notification::Status(job_id, Status::Running))
+                        })
+                        .map_err(|e| notification_handler(Notification::Error(*e)));
+                }
+
+                Cmd::SetSteps(job_id, steps) => {
+                    if let Ok(p) = backend
+                        .set_steps(&job_id, steps)
+                        .map_err(|e| notification_handler(Notification::Error(*e)))
+                    {
+                        let eta = Self::estimate_eta(backend.as_mut(), &job_id)
+                            .await
+                            .unwrap_or_default();
+
+                        notification_handler(Notification::Progression(
+                            job_id,
+                            Progression { eta, ..p },
+                        ));
+                    }
+                }
+
+                Cmd::SetStep(job_id, step) => {
+                    if let Ok(p) = backend
+                        .set_step(&job_id, step)
+                        .map_err(|e| notification_handler(Notification::Error(*e)))
+                    {
+                        let eta = Self::estimate_eta(backend.as_mut(), &job_id)
+                            .await
+                            .unwrap_or_default();
+
+                        notification_handler(Notification::Progression(
+                            job_id,
+                            Progression { eta, ..p },
+                        ));
+                    }
+                }
+
+                _ => (),
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Gets the suspension state of a running job.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job.
+    ///
+    /// # Returns
+    /// The suspension state of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, `Error::JobNotRunning` if the job is not running.
+    fn suspension(
+        resources: &QueueResources<RoutineType, Context>,
+        job_id: &Uuid,
+    ) -> Result<Suspension, ApiError> {
+        resources
+            .suspensions
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRunningJobs(e.to_string())))?
+            .get(job_id)
+            .cloned()
+            .ok_or(api_err!(Error::JobNotRunning(job_id.to_owned())))
+    }
+
+    /// Processes a job: stores it in the backend and makes it eligible for dispatch.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job` - Job to be processed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn process_job(
+        resources: &QueueResources<RoutineType, Context>,
+        job: Job,
+    ) -> Result<(), ApiError> {
+        let notification_handler = &resources.notification_handler;
+        let pending_job = PendingJob::from(&job);
+        let job_id = job.id();
+        let due_at = job.due_at();
+        let deadline = job.deadline();
+
+        let shedding_action = resources
+            .dispatcher
+            .lock()
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+            .shedding_action(&pending_job);
+
+        if shedding_action == Some(SheddingAction::Drop) {
+            resources
+                .dispatcher
+                .lock()
+                .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+                .forget(&job_id);
+
+            notification_handler(Notification::JobShed(job_id, SheddingAction::Drop));
+
+            return Ok(());
+        }
+
+        let rt = resources
+            .runtime
+            .lock()
+            .map_err(|e| Error::CannotAccessRuntime(e.to_string()))?;
+
+        let scheduling = SystemTime::now();
+
+        let ready = rt.block_on(async {
+            let mut bk = resources.backend.lock().await;
+
+            // Push the job in the backend (to be stored)
+            if bk
+                .schedule(job)
+                .map_err(|e| notification_handler(Notification::Error(*e)))
+                .is_err()
+            {
+                return false;
+            }
+
+            // Set its status to ready (can be processed)
+            bk.set_status(&job_id, Status::Ready)
+                .map_err(|e| notification_handler(Notification::Error(*e)))
+                .is_ok()
+        });
+
+        if !ready {
+            resources
+                .dispatcher
+                .lock()
+                .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+                .forget(&job_id);
+
+            return Ok(());
+        }
+
+        {
+            let mut dispatcher = resources
+                .dispatcher
+                .lock()
+                .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?;
+
+            dispatcher.set_backend_latency(scheduling.elapsed().unwrap_or_default());
+
+            let delayed = due_at.filter(|due_at| *due_at > resources.clock.now());
+
+            // A shared job is dispatched by the worker claiming it, once due
+            match (resources.distributed, delayed) {
+                (true, _) => dispatcher.forget(&job_id),
+                (false, Some(due_at)) => {
+                    rt.spawn(Self::dispatch_later(resources.clone(), pending_job, due_at));
+                }
+                (false, None) => dispatcher.push(pending_job),
+            }
+
+            // A shared job is given its deadline by the worker claiming it
+            if let (false, Some(deadline)) = (resources.distributed, deadline) {
+                let handle = rt.spawn(Self::enforce_deadline(resources.clone(), job_id, deadline));
+
+                Self::watch_deadline(resources, job_id, handle.abort_handle())?;
+            }
+        }
+
+        if resources.distributed {
+            Self::fetch(resources, &rt, false)?;
+        }
+
+        if shedding_action == Some(SheddingAction::Delay) {
+            notification_handler(Notification::JobShed(job_id, SheddingAction::Delay));
+        }
+
+        Self::dispatch(resources, &rt)
+    }
+
+    /// Drops a job not started by its deadline, or cancels it if still running.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job.
+    /// * `deadline` - Deadline of the job.
+    async fn enforce_deadline(
+        resources: QueueResources<RoutineType, Context>,
+        job_id: Uuid,
+        deadline: SystemTime,
     ) {
-        match msg {
-            Message::Job(job) => {
-                let _ = JobQueue::process_job(
-                    backend,
-                    runtime,
-                    notification_handler.clone(),
-                    messages_channel.clone(),
-                    context,
-                    job,
-                )
-                .map_err(|e| notification_handler(Notification::Error(*e)));
+        resources.clock.sleep_until(deadline).await;
+
+        // Handle dropped first, so that cancelling the job doesn't abort this task
+        if let Ok(mut deadlines) = resources.deadlines.lock() {
+            deadlines.remove(&job_id);
+        }
+
+        // Nothing done if already finished
+        let _ = Self::cancel(&resources, &job_id, ResultStatus::DeadlineExceeded)
+            .await
+            .map_err(|e| (resources.notification_handler)(Notification::Error(*e)));
+    }
+
+    /// Keeps the handle of the task enforcing the deadline of a job, to be aborted once the
+    /// job is finished or removed.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job.
+    /// * `handle` - Abort handle of the task enforcing the deadline.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn watch_deadline(
+        resources: &QueueResources<RoutineType, Context>,
+        job_id: Uuid,
+        handle: AbortHandle,
+    ) -> Result<(), ApiError> {
+        let previous = resources
+            .deadlines
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRunningJobs(e.to_string())))?
+            .insert(job_id, handle);
+
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Aborts the task enforcing the deadline of a job no longer to be run, if any.
+    ///
+    /// # Arguments
+    /// * `deadlines` - Abort handles of the tasks enforcing the deadlines.
+    /// * `job_id` - ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn forget_deadline(
+        deadlines: &Shared<HashMap<Uuid, AbortHandle>>,
+        job_id: &Uuid,
+    ) -> Result<(), ApiError> {
+        let handle = deadlines
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessRunningJobs(e.to_string())))?
+            .remove(job_id);
+
+        if let Some(handle) = handle {
+            handle.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Flags a job still running past its expected duration, and cancels it past the
+    /// threshold of the watchdog.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job.
+    /// * `attempt` - Number of attempts made before the run watched.
+    /// * `expected` - Expected duration of the job.
+    /// * `cancel_after` - Duration after which the job is cancelled, if any.
+    async fn watch_stall(
+        resources: QueueResources<RoutineType, Context>,
+        job_id: Uuid,
+        attempt: usize,
+        expected: Duration,
+        cancel_after: Option<Duration>,
+    ) {
+        let started = resources.clock.now();
+
+        // Nothing done once the run is over (the job possibly retried since)
+        let running = || async {
+            match resources.backend.lock().await.get(&job_id).await {
+                Ok(job) => {
+                    matches!(job.status(), Status::Running | Status::Suspended)
+                        && job.attempts().len() == attempt
+                }
+                Err(_) => false,
             }
+        };
 
-            Message::Command(cmd) => {
-                let _ =
-                    JobQueue::process_command(backend, runtime, notification_handler.clone(), cmd)
-                        .map_err(|e| notification_handler(Notification::Error(*e)));
+        resources.clock.sleep_until(started + expected).await;
+
+        if !running().await {
+            return;
+        }
+
+        let elapsed = resources
+            .clock
+            .now()
+            .duration_since(started)
+            .unwrap_or_default();
+
+        (resources.notification_handler)(Notification::JobStalled(job_id, elapsed));
+
+        let Some(cancel_after) = cancel_after else {
+            return;
+        };
+
+        resources
+            .clock
+            .sleep_until(started + cancel_after.max(expected))
+            .await;
+
+        if running().await {
+            let _ = Self::cancel(&resources, &job_id, ResultStatus::TimedOut)
+                .await
+                .map_err(|e| (resources.notification_handler)(Notification::Error(*e)));
+        }
+    }
+
+    /// Makes a delayed job eligible for dispatch once due, unless cancelled meanwhile.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `pending_job` - Job delayed.
+    /// * `due_at` - Time at which the job is due.
+    async fn dispatch_later(
+        resources: QueueResources<RoutineType, Context>,
+        pending_job: PendingJob,
+        due_at: SystemTime,
+    ) {
+        resources.clock.sleep_until(due_at).await;
+
+        let ready = matches!(
+            resources.backend.lock().await.status(&pending_job.id),
+            Ok(Status::Ready)
+        );
+
+        if !ready {
+            if let Ok(mut dispatcher) = resources.dispatcher.lock() {
+                dispatcher.forget(&pending_job.id);
+            }
+
+            return;
+        }
+
+        let result = resources
+            .dispatcher
+            .lock()
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))
+            .map(|mut dispatcher| dispatcher.push(pending_job))
+            .and_then(|_| {
+                resources
+                    .messages_channel
+                    .lock()
+                    .map_err(|e| Error::CannotAccessSender(e.to_string()))?
+                    .send(Message::Command(Cmd::Dispatch))
+                    .map_err(|e| Error::CannotSendMessage(e.to_string()))
+            });
+
+        if let Err(e) = result {
+            (resources.notification_handler)(Notification::Error(e));
+        }
+    }
+
+    /// Makes a job waiting for its backoff delay eligible for dispatch again once due, unless
+    /// cancelled meanwhile.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `pending_job` - Job retried.
+    /// * `due_at` - Time at which the backoff delay elapses.
+    async fn retry_later(
+        resources: QueueResources<RoutineType, Context>,
+        pending_job: PendingJob,
+        due_at: SystemTime,
+    ) {
+        let notification_handler = &resources.notification_handler;
+        let job_id = pending_job.id;
+
+        // Held admitted meanwhile, the queue not being idle
+        match resources.dispatcher.lock() {
+            Ok(mut dispatcher) => dispatcher.defer(job_id, pending_job.tenant.as_deref()),
+            Err(e) => {
+                notification_handler(Notification::Error(Error::CannotAccessDispatcher(
+                    e.to_string(),
+                )));
+
+                return;
+            }
+        }
+
+        resources.clock.sleep_until(due_at).await;
+
+        let ready = {
+            let mut backend = resources.backend.lock().await;
+
+            match backend.status(&job_id) {
+                Ok(Status::Retrying) => backend
+                    .set_status(&job_id, Status::Ready)
+                    .map_err(|e| notification_handler(Notification::Error(*e)))
+                    .is_ok(),
+                _ => false,
+            }
+        };
+
+        if !ready {
+            if let Ok(mut dispatcher) = resources.dispatcher.lock() {
+                dispatcher.forget(&job_id);
+            }
+
+            return;
+        }
+
+        notification_handler(Notification::Status(job_id, Status::Ready));
+
+        Self::dispatch_later(resources, pending_job, due_at).await;
+    }
+
+    /// Enqueues the next occurrence of a recurring job.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job` - Occurrence finished (or skipped).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn enqueue_next_occurrence(
+        resources: &QueueResources<RoutineType, Context>,
+        job: &Job,
+    ) -> Result<(), ApiError> {
+        let Some(next) = job.next_occurrence(resources.clock.now()) else {
+            return Ok(());
+        };
+
+        Self::send_job(resources, next)
+    }
+
+    /// Calls the completion callback of a finished job, and enqueues its follow-up job, if any
+    /// (see `Job::set_on_complete`).
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job` - Job finished.
+    /// * `status` - Result status of the job.
+    /// * `result` - Result of the job.
+    /// * `error` - Error message of the job, if failed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn complete(
+        resources: &QueueResources<RoutineType, Context>,
+        job: &Job,
+        status: ResultStatus,
+        result: Vec<u8>,
+        error: Option<String>,
+    ) -> Result<(), ApiError> {
+        if !job.awaits_outcome() {
+            return Ok(());
+        }
+
+        let outcome = Outcome {
+            id: job.id(),
+            kind: job.kind().to_owned(),
+            status,
+            result,
+            error,
+        };
+
+        match job.complete(&outcome)? {
+            Some(follow_up) => Self::send_job(resources, follow_up),
+            None => Ok(()),
+        }
+    }
+
+    /// Sends a job created by the queue itself (next occurrence, follow-up...) to be enqueued.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job` - Job to be enqueued.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn send_job(
+        resources: &QueueResources<RoutineType, Context>,
+        mut job: Job,
+    ) -> Result<(), ApiError> {
+        if let Some(generator) = &resources.id_generator {
+            job.generate_id(generator.as_ref());
+        }
+
+        resources
+            .subscribers
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSubscribers(e.to_string())))?
+            .track(job.id(), job.kind());
+
+        resources
+            .messages_channel
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Job(Box::new(job)))
+            .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))
+    }
+
+    /// Unregisters an occurrence of a recurring job no longer running, and dispatches the
+    /// occurrence of its series waiting for it, if any.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `id` - ID of the job no longer running.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn finish_occurrence(
+        resources: &QueueResources<RoutineType, Context>,
+        id: &Uuid,
+    ) -> Result<(), ApiError> {
+        let waiting = resources
+            .series
+            .lock()
+            .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?
+            .finish(id);
+
+        let Some(pending_job) = waiting else {
+            return Ok(());
+        };
+
+        resources
+            .dispatcher
+            .lock()
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+            .push(pending_job);
+
+        resources
+            .messages_channel
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(Cmd::Dispatch))
+            .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))
+    }
+
+    /// Claims jobs from the shared backend, as long as slots are available, and makes them
+    /// eligible for dispatch.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `rt` - Runtime carrying the thread pool.
+    /// * `heartbeat` - Whether the leases of the jobs already claimed must be extended.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn fetch(
+        resources: &QueueResources<RoutineType, Context>,
+        rt: &Runtime,
+        heartbeat: bool,
+    ) -> Result<(), ApiError> {
+        let notification_handler = &resources.notification_handler;
+
+        rt.block_on(async {
+            let mut backend = resources.backend.lock().await;
+
+            if heartbeat {
+                let mut job_ids = resources
+                    .dispatcher
+                    .lock()
+                    .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+                    .pending_ids();
+
+                job_ids.extend(
+                    resources
+                        .running
+                        .lock()
+                        .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?
+                        .keys(),
+                );
+
+                for job_id in job_ids {
+                    // A job whose lease was lost may be run twice (at-least-once)
+                    if !backend.extend_lease(&job_id, &resources.worker_id, resources.lease)? {
+                        notification_handler(Notification::Error(Error::LeaseLost(job_id)));
+                    }
+                }
+            }
+
+            // Jobs left to the other workers while paused
+            if backend.is_paused()? {
+                return Ok(());
+            }
+
+            loop {
+                let mut dispatcher = resources
+                    .dispatcher
+                    .lock()
+                    .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?;
+
+                if !dispatcher.has_capacity() {
+                    return Ok(());
+                }
+
+                match backend.claim(
+                    &resources.worker_id,
+                    resources.lease,
+                    &resources.kinds,
+                    &resources.capabilities,
+                )? {
+                    Some(job) => dispatcher.push(PendingJob::from(&job)),
+                    None => return Ok(()),
+                }
             }
-        }
+        })
     }
 
-    /// Processes a command.
+    /// Dispatches the pending jobs chosen by the scheduler strategy, as long as slots are
+    /// available.
     ///
     /// # Arguments
-    /// * `backend` - Backend instance used to process the jobs.
-    /// * `runtime` - Runtime carrying the thread pool.
-    /// * `notification_handler` - Handler for notifications.
-    /// * `cmd` - Command to be processed.
+    /// * `resources` - Resources shared with the jobs.
+    /// * `rt` - Runtime carrying the thread pool.
     ///
     /// # Errors
     /// One of `Error` enum.
-    fn process_command(
-        backend: SharedBackend<RoutineType, Context>,
-        runtime: SharedRuntime,
-        notification_handler: SharedNotificationHandler,
-        cmd: Cmd,
+    fn dispatch(
+        resources: &QueueResources<RoutineType, Context>,
+        rt: &Runtime,
     ) -> Result<(), ApiError> {
-        let runtime = runtime
+        // Pause flag checked between dispatches, as it can be set by any worker
+        if rt.block_on(async { resources.backend.lock().await.is_paused() })? {
+            return Ok(());
+        }
+
+        let scaled = resources
+            .dispatcher
             .lock()
-            .map_err(|e| Error::CannotAccessRuntime(e.to_string()))?;
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+            .autoscale();
 
-        runtime.block_on(async {
-            let mut backend = backend.lock().await;
+        if let Some((from, to)) = scaled {
+            (resources.notification_handler)(Notification::Scaled(from, to));
+        }
 
-            match cmd {
-                Cmd::CheckExpirations => {
-                    if let Ok(job_ids) = backend
-                        .remove_expired()
-                        .map_err(|e| notification_handler(Notification::Error(*e)))
-                    {
-                        for job_id in job_ids {
-                            notification_handler(Notification::Status(
-                                job_id.to_owned(),
-                                Status::Removed,
-                            ));
+        loop {
+            // Keep the running jobs locked until the handle is stored, so the job cannot finish
+            // (or be cancelled) before being registered
+            let mut running = resources
+                .running
+                .lock()
+                .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?;
+
+            let (job_id, preempted) = {
+                let mut dispatcher = resources
+                    .dispatcher
+                    .lock()
+                    .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?;
+
+                match dispatcher.next() {
+                    Some(job_id) => (job_id, None),
+                    // Every slot occupied, an urgent job may take the slot of a running one
+                    None => {
+                        let suspensions = resources
+                            .suspensions
+                            .lock()
+                            .map_err(|e| Error::CannotAccessRunningJobs(e.to_string()))?;
+
+                        let preempt = dispatcher.preempt(|id| {
+                            suspensions
+                                .get(id)
+                                .is_some_and(|suspension| !suspension.is_suspended())
+                        });
+
+                        match preempt {
+                            Some((preempted, job_id)) => (job_id, Some(preempted)),
+                            None => return Ok(()),
                         }
                     }
                 }
+            };
 
-                Cmd::SetSteps(job_id, steps) => {
-                    if let Ok(p) = backend
-                        .set_steps(&job_id, steps)
-                        .map_err(|e| notification_handler(Notification::Error(*e)))
-                    {
-                        notification_handler(Notification::Progression(job_id, p));
-                    }
-                }
+            // Parked at its next suspension point, resumed once the urgent job is finished
+            if let Some(preempted) = preempted {
+                resources
+                    .messages_channel
+                    .lock()
+                    .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+                    .send(Message::Command(Cmd::Suspend(preempted)))
+                    .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))?;
+            }
 
-                Cmd::SetStep(job_id, step) => {
-                    if let Ok(p) = backend
-                        .set_step(&job_id, step)
-                        .map_err(|e| notification_handler(Notification::Error(*e)))
-                    {
-                        notification_handler(Notification::Progression(job_id, p));
+            let resources = resources.clone();
+
+            let handle = rt.spawn(async move {
+                Self::run_job(&resources, job_id).await;
+
+                let notification_handler = &resources.notification_handler;
+
+                let _ = Self::finish_occurrence(&resources, &job_id)
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+
+                // The slot is released by whoever unregisters the job (here or on cancellation)
+                let registered = match resources.running.lock() {
+                    Ok(mut running) => running.remove(&job_id).is_some(),
+                    Err(e) => {
+                        notification_handler(Notification::Error(Error::CannotAccessRunningJobs(
+                            e.to_string(),
+                        )));
+
+                        true
                     }
-                }
+                };
 
-                _ => (),
-            }
-        });
+                if registered {
+                    let _ = Self::release_slot(
+                        &resources.dispatcher,
+                        &resources.messages_channel,
+                        &job_id,
+                    )
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+                }
+            });
 
-        Ok(())
+            running.insert(job_id, handle.abort_handle());
+        }
     }
 
-    /// Processes a job.
+    /// Frees the slot of a job that is no longer running and asks for the next job to be
+    /// dispatched, or for the job it has preempted to be resumed.
     ///
     /// # Arguments
-    /// * `backend` - Backend instance used to process the jobs.
-    /// * `runtime` - Runtime carrying the thread pool.
-    /// * `notification_handler` - Handler for notifications.
+    /// * `dispatcher` - Dispatch state of the queue.
     /// * `messages_channel` - Channel used to communicate with the queue thread.
-    /// * `context` - Context used by the jobs.
-    /// * `job` - Job to be processed.
+    /// * `id` - ID of the job.
     ///
     /// # Errors
     /// One of `Error` enum.
-    fn process_job(
-        backend: SharedBackend<RoutineType, Context>,
-        runtime: SharedRuntime,
-        notification_handler: SharedNotificationHandler,
-        messages_channel: Shared<Sender<Message>>,
-        context: Option<Shared<Context>>,
-        job: Job,
+    fn release_slot(
+        dispatcher: &Shared<Dispatcher>,
+        messages_channel: &SharedMessageChannel,
+        id: &Uuid,
     ) -> Result<(), ApiError> {
-        let job_id = job.id();
+        let preempted = dispatcher
+            .lock()
+            .map_err(|e| Error::CannotAccessDispatcher(e.to_string()))?
+            .release(id);
+
+        let command = match preempted {
+            Some(preempted) => Cmd::Resume(preempted),
+            None => Cmd::Dispatch,
+        };
 
-        let rt = runtime
+        messages_channel
             .lock()
-            .map_err(|e| Error::CannotAccessRuntime(e.to_string()))?;
+            .map_err(|e| api_err!(Error::CannotAccessSender(e.to_string())))?
+            .send(Message::Command(command))
+            .map_err(|e| api_err!(Error::CannotSendMessage(e.to_string())))
+    }
 
-        rt.block_on(async {
-            let mut bk = backend.lock().await;
+    /// Store a chunk of the result emitted by a running job.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources of the queue.
+    /// * `id` - ID of the job.
+    /// * `chunk` - Chunk to be stored.
+    async fn store_chunk(
+        resources: &QueueResources<RoutineType, Context>,
+        id: &Uuid,
+        chunk: Vec<u8>,
+    ) {
+        let _ = resources
+            .backend
+            .lock()
+            .await
+            .append_chunk(id, chunk)
+            .map_err(|e| (resources.notification_handler)(Notification::Error(*e)));
+    }
+
+    /// Resolves the context passed to the routine of a job.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources of the queue.
+    /// * `job` - Job run.
+    ///
+    /// # Returns
+    /// The context of the job resolved by the context resolver if it holds one, the context of
+    /// the queue otherwise.
+    ///
+    /// # Errors
+    /// One of `Error` enum if the context of the job can't be resolved.
+    fn job_context(
+        resources: &QueueResources<RoutineType, Context>,
+        job: &Job,
+    ) -> Result<Option<ContextMode<Context>>, ApiError> {
+        match (job.raw_context(), &resources.context_resolver) {
+            (Some(context), Some(resolver)) => {
+                resolver(resources.context.as_ref(), context).map(Some)
+            }
+            _ => Ok(resources.context.clone()),
+        }
+    }
+
+    /// Runs a dispatched job and stores its outcome.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job to be run.
+    async fn run_job(resources: &QueueResources<RoutineType, Context>, job_id: Uuid) {
+        let backend = &resources.backend;
+        let notification_handler = &resources.notification_handler;
+
+        // Registered jobs of unknown routines left pending, for a worker that knows them
+        let unregistered = match backend.lock().await.get(&job_id).await {
+            Ok(job) if job.is_registered() && !resources.registry.contains(job.kind()) => {
+                Some(job.kind().to_owned())
+            }
+            _ => None,
+        };
+
+        if let Some(kind) = unregistered {
+            if resources.distributed {
+                let _ = backend
+                    .lock()
+                    .await
+                    .release(&job_id, &resources.worker_id)
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+            }
+
+            notification_handler(Notification::Error(Error::RoutineNotRegistered(kind)));
+
+            return;
+        }
+
+        // Jobs that cannot be deserialized handled by the policy instead of being run
+        if resources.undecodable_policy != UndecodablePolicy::Fail {
+            let undecodable = {
+                let mut bk = backend.lock().await;
+
+                let failed = matches!(
+                    bk.routine(&job_id),
+                    Err(e) if matches!(*e, Error::JsonSerialization(_))
+                );
+
+                // Local and registered jobs run a closure or a registered routine instead
+                failed
+                    && !bk
+                        .get(&job_id)
+                        .await
+                        .is_ok_and(|job| job.is_local() || job.is_registered())
+            };
+
+            if undecodable {
+                let _ = Self::handle_undecodable(resources, job_id)
+                    .await
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
 
-            // Push the job in the backend (to be stored)
-            if bk
-                .schedule(job)
-                .map_err(|e| notification_handler(Notification::Error(*e)))
-                .is_err()
-            {
                 return;
             }
+        }
+
+        let stored = backend.lock().await.get(&job_id).await.ok();
+        let deadline = stored.as_ref().and_then(Job::deadline);
+        let occurrence = stored
+            .as_ref()
+            .and_then(|job| job.recurrence().map(|recurrence| (job.clone(), recurrence)));
+
+        // Job not started by its deadline (e.g. delayed or claimed too late) dropped
+        if deadline.is_some_and(|deadline| deadline <= resources.clock.now()) {
+            let status = Status::Finished(ResultStatus::DeadlineExceeded);
+            let mut bk = backend.lock().await;
+
+            let _ = Self::forget_deadline(&resources.deadlines, &job_id)
+                .map_err(|e| notification_handler(Notification::Error(*e)));
+
+            if let Ok(bytes) = Self::string_to_json_error(&Error::DeadlineExceeded.to_string()) {
+                let _ = bk
+                    .set_result(&job_id, bytes)
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+            }
 
-            // Set its status to ready (can be processed)
             let _ = bk
-                .set_status(&job_id, Status::Ready)
+                .set_status(&job_id, status)
+                .map(|_| notification_handler(Notification::Status(job_id, status)))
                 .map_err(|e| notification_handler(Notification::Error(*e)));
-        });
 
-        rt.spawn(async move {
-            // Set status of the job to `Status::Running`
-            if backend
+            if let Some(job) = &stored {
+                let _ = Self::complete(
+                    resources,
+                    job,
+                    ResultStatus::DeadlineExceeded,
+                    vec![],
+                    Some(Error::DeadlineExceeded.to_string()),
+                )
+                .map_err(|e| notification_handler(Notification::Error(*e)));
+            }
+
+            return;
+        }
+
+        // Occurrence missed while the queue was down or too busy, or due while the previous one
+        // is still running, handled by its policies
+        let overlap = match &occurrence {
+            Some((job, recurrence))
+                if recurrence.misfire == MisfirePolicy::Skip
+                    && job.is_misfired(resources.clock.now()) =>
+            {
+                Overlap::Skip
+            }
+            Some((job, _)) => match resources.series.lock() {
+                Ok(mut series) => series.start(job),
+                Err(e) => {
+                    notification_handler(Notification::Error(Error::CannotAccessRunningJobs(
+                        e.to_string(),
+                    )));
+
+                    return;
+                }
+            },
+            None => Overlap::Run,
+        };
+
+        match overlap {
+            Overlap::Run | Overlap::Skip => {}
+            // Dispatched again once the previous occurrence is finished
+            Overlap::Wait => return,
+            Overlap::CancelPrevious(previous) => {
+                let _ = Self::cancel(resources, &previous, ResultStatus::Cancelled)
+                    .await
+                    .map_err(|e| notification_handler(Notification::Error(*e)));
+            }
+        }
+
+        if let (Overlap::Skip, Some((job, _))) = (overlap, &occurrence) {
+            let status = Status::Finished(ResultStatus::Cancelled);
+
+            let _ = backend
                 .lock()
                 .await
-                .set_status(&job_id, Status::Running)
-                .map_err(|e| notification_handler(Notification::Error(*e)))
-                .is_err()
-            {
+                .set_status(&job_id, status)
+                .map(|_| notification_handler(Notification::Status(job_id, status)))
+                .and_then(|_| Self::enqueue_next_occurrence(resources, job))
+                .map_err(|e| notification_handler(Notification::Error(*e)));
+
+            return;
+        }
+
+        // Set status of the job to `Status::Running`
+        if backend
+            .lock()
+            .await
+            .set_status(&job_id, Status::Running)
+            .map_err(|e| notification_handler(Notification::Error(*e)))
+            .is_err()
+        {
+            return;
+        }
+
+        notification_handler(Notification::Status(job_id, Status::Running));
+
+        // Job claimed from a shared backend cancelled at its deadline by this worker
+        if let (true, Some(deadline)) = (resources.distributed, deadline) {
+            let handle = tokio::spawn(Self::enforce_deadline(resources.clone(), job_id, deadline));
+
+            let _ = Self::watch_deadline(resources, job_id, handle.abort_handle())
+                .map_err(|e| notification_handler(Notification::Error(*e)));
+        }
+
+        // Series of a recurring job carried on by its next occurrence, which may then overlap
+        if let Some((job, _)) = &occurrence {
+            let _ = Self::enqueue_next_occurrence(resources, job)
+                .map_err(|e| notification_handler(Notification::Error(*e)));
+        }
+
+        // Call the routine of the job
+        let mut result_status = ResultStatus::Error;
+
+        let mut job = match backend.lock().await.get(&job_id).await {
+            Ok(job) => job,
+            Err(e) => {
+                notification_handler(Notification::Error(*e));
                 return;
             }
+        };
 
-            notification_handler(Notification::Status(job_id, Status::Running));
+        resources.hooks.started(&job);
 
-            // Call the routine of the job
-            let mut result_status = ResultStatus::Error;
+        // Job flagged by the watchdog if running longer than expected
+        if let Some(watchdog) = &resources.watchdog {
+            let stats = backend
+                .lock()
+                .await
+                .duration_stats(job.kind())
+                .map_err(|e| notification_handler(Notification::Error(*e)))
+                .unwrap_or_default();
 
-            let job = match backend.lock().await.get(&job_id).await {
-                Ok(job) => job,
-                Err(e) => {
-                    notification_handler(Notification::Error(*e));
-                    return;
-                }
-            };
+            if let Some(expected) = watchdog.expected(job.kind(), stats.as_ref()) {
+                tokio::spawn(Self::watch_stall(
+                    resources.clone(),
+                    job_id,
+                    job.attempts().len(),
+                    expected,
+                    watchdog.cancel_threshold(expected),
+                ));
+            }
+        }
 
-            let result = job
-                .run::<RoutineType, Context>(messages_channel, context)
-                .await;
-
-            let (bytes, maybe_err, status) = match result {
-                Ok(bytes) => (Some(bytes), None, ResultStatus::Success),
-                Err(err) => (
-                    Self::string_to_json_error(&err.to_string()).ok(), // Formatting to JSON as expected
-                    Some(err),
-                    ResultStatus::Error,
-                ),
-            };
+        // Suspension state shared with the routine while running
+        let suspension = Suspension::default();
 
-            if let Some(bytes) = bytes {
-                if backend
-                    .lock()
-                    .await
-                    .set_result(&job_id, bytes)
-                    .map_err(|e| notification_handler(Notification::Error(*e)))
-                    .is_ok()
-                {
-                    result_status = status;
-                    if let Some(err) = maybe_err {
-                        notification_handler(Notification::Error(*err));
+        job.set_suspension(suspension.clone());
+
+        match resources.suspensions.lock() {
+            Ok(mut suspensions) => {
+                suspensions.insert(job_id, suspension);
+            }
+            Err(e) => notification_handler(Notification::Error(Error::CannotAccessRunningJobs(
+                e.to_string(),
+            ))),
+        }
+
+        // Chunks of the result emitted by the routine stored as they come
+        let (chunk_sink, mut chunks) = ChunkSink::channel();
+
+        job.set_chunk_sink(chunk_sink);
+
+        // Routine run at the end of the middleware chain
+        let routine = || -> RoutineFuture<'_> {
+            Box::pin(async {
+                let context = Self::job_context(resources, &job)?;
+
+                match job.is_registered() {
+                    true => {
+                        resources
+                            .registry
+                            .run(&job, resources.messages_channel.clone(), context)
+                            .await
                     }
+                    false => {
+                        job.run::<RoutineType, Context>(resources.messages_channel.clone(), context)
+                            .await
+                    }
+                }
+            })
+        };
+
+        let mut run = std::pin::pin!(Next::new(&resources.middlewares, &routine).run(&job));
+
+        let result = loop {
+            tokio::select! {
+                result = &mut run => break result,
+                Some(chunk) = chunks.recv() => Self::store_chunk(resources, &job_id, chunk).await,
+            }
+        };
+
+        // Chunks emitted last stored before the job is finished, so that streams don't miss them
+        while let Ok(chunk) = chunks.try_recv() {
+            Self::store_chunk(resources, &job_id, chunk).await;
+        }
+
+        if let Ok(mut suspensions) = resources.suspensions.lock() {
+            suspensions.remove(&job_id);
+        }
+
+        let (bytes, maybe_err, status) = match result {
+            Ok(bytes) => (Some(bytes), None, ResultStatus::Success),
+            Err(err) => (
+                Self::string_to_json_error(&err.to_string()).ok(), // Formatting to JSON as expected
+                Some(err),
+                ResultStatus::Error,
+            ),
+        };
+
+        let cached = job.idempotency_key().and(bytes.clone());
+        let output = (resources.hooks.wants_result() || job.awaits_outcome())
+            .then(|| bytes.clone())
+            .flatten();
+        let error = maybe_err.as_ref().map(|err| err.to_string());
+        let failure = error.clone().unwrap_or_default();
+
+        if let Some(bytes) = bytes {
+            if Self::store_result(resources, &job_id, bytes)
+                .await
+                .map_err(|e| notification_handler(Notification::Error(*e)))
+                .is_ok()
+            {
+                result_status = status;
+                if let Some(err) = maybe_err {
+                    notification_handler(Notification::Error(*err));
                 }
             }
+        }
 
-            // Set status of the job to `Status::Finished`
-            let status = Status::Finished(result_status);
+        // Record the attempt, kept in the history of the job across retries
+        let attempt = Attempt {
+            started: job.timestamps().started,
+            finished: SystemTime::now(),
+            status: result_status,
+            error,
+            worker: Some(resources.worker_id.clone()),
+        };
+
+        let _ = backend
+            .lock()
+            .await
+            .add_attempt(&job_id, attempt)
+            .map_err(|e| notification_handler(Notification::Error(*e)));
 
+        // Failed job run again after the backoff delay, while attempts remain
+        if let Some(delay) = resources
+            .retry_policy
+            .filter(|_| result_status == ResultStatus::Error)
+            .and_then(|policy| policy.delay(job.attempts().len() + 1))
+        {
             if backend
                 .lock()
                 .await
-                .set_status(&job_id, status)
+                .set_status(&job_id, Status::Retrying)
                 .map_err(|e| notification_handler(Notification::Error(*e)))
-                .is_err()
+                .is_ok()
             {
-                return;
+                notification_handler(Notification::Status(job_id, Status::Retrying));
+
+                tokio::spawn(Self::retry_later(
+                    resources.clone(),
+                    PendingJob::from(&job),
+                    resources.clock.now() + delay,
+                ));
             }
 
-            notification_handler(Notification::Status(job_id, status));
-        });
+            return;
+        }
+
+        // Set status of the job to `Status::Finished`
+        let status = Status::Finished(result_status);
+
+        if backend
+            .lock()
+            .await
+            .set_status(&job_id, status)
+            .map_err(|e| notification_handler(Notification::Error(*e)))
+            .is_err()
+        {
+            return;
+        }
+
+        let _ = Self::forget_deadline(&resources.deadlines, &job_id)
+            .map_err(|e| notification_handler(Notification::Error(*e)));
+
+        // Result of a job holding an idempotency key cached for its duplicates
+        if let Some(key) = job.idempotency_key() {
+            match resources.idempotency.lock() {
+                Ok(mut idempotency) => match cached {
+                    Some(bytes) if result_status == ResultStatus::Success => {
+                        idempotency.complete(key, job_id, bytes)
+                    }
+                    _ => idempotency.forget(key, job_id),
+                },
+                Err(e) => notification_handler(Notification::Error(
+                    Error::CannotAccessIdempotencyCache(e.to_string()),
+                )),
+            }
+        }
+
+        // Feed the throughput tracker
+        let finished = SystemTime::now();
+        let duration = finished
+            .duration_since(job.timestamps().started)
+            .unwrap_or_default();
+
+        match resources.throughput.lock() {
+            Ok(mut throughput) => throughput.record(job.kind(), duration, finished),
+            Err(e) => notification_handler(Notification::Error(Error::CannotAccessThroughput(
+                e.to_string(),
+            ))),
+        }
+
+        // Feed the duration statistics shared by the workers, only with complete runs
+        if result_status == ResultStatus::Success {
+            let _ = backend
+                .lock()
+                .await
+                .record_duration(job.kind(), duration)
+                .map_err(|e| notification_handler(Notification::Error(*e)));
+        }
+
+        notification_handler(Notification::Status(job_id, status));
+
+        resources.hooks.finished(
+            &job,
+            result_status,
+            output.as_deref().unwrap_or_default(),
+            &failure,
+        );
+
+        let _ = Self::complete(
+            resources,
+            &job,
+            result_status,
+            output.unwrap_or_default(),
+            (result_status != ResultStatus::Success).then_some(failure),
+        )
+        .map_err(|e| notification_handler(Notification::Error(*e)));
+    }
+
+    /// Stores the result of a job, in the result store if larger than the offload threshold.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job.
+    /// * `bytes` - Result of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn store_result(
+        resources: &QueueResources<RoutineType, Context>,
+        job_id: &Uuid,
+        bytes: Vec<u8>,
+    ) -> Result<(), ApiError> {
+        match &resources.result_offload {
+            Some(offload) if bytes.len() >= offload.threshold => {
+                let key = offload.store.put(job_id, &bytes)?;
+
+                resources.backend.lock().await.set_result_ref(job_id, key)
+            }
+            _ => resources.backend.lock().await.set_result(job_id, bytes),
+        }
+    }
+
+    /// Applies the undecodable policy on a dispatched job.
+    ///
+    /// # Arguments
+    /// * `resources` - Resources shared with the jobs.
+    /// * `job_id` - ID of the job that cannot be deserialized.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn handle_undecodable(
+        resources: &QueueResources<RoutineType, Context>,
+        job_id: Uuid,
+    ) -> Result<(), ApiError> {
+        let policy = resources.undecodable_policy;
+
+        match policy {
+            UndecodablePolicy::Fail => {}
+
+            UndecodablePolicy::Quarantine => {
+                resources
+                    .quarantined
+                    .lock()
+                    .map_err(|e| api_err!(Error::CannotAccessUndecodableJobs(e.to_string())))?
+                    .insert(job_id);
+            }
+
+            UndecodablePolicy::DeadLetter | UndecodablePolicy::Drop => {
+                let mut backend = resources.backend.lock().await;
+                let job = backend.get(&job_id).await?;
+
+                backend.set_status(&job_id, Status::Finished(ResultStatus::Error))?;
+                backend.remove(&job_id)?;
+                drop(backend);
+
+                if policy == UndecodablePolicy::DeadLetter {
+                    resources
+                        .dead_letters
+                        .lock()
+                        .map_err(|e| api_err!(Error::CannotAccessUndecodableJobs(e.to_string())))?
+                        .insert(job_id, job);
+                }
+            }
+        }
+
+        (resources.notification_handler)(Notification::JobUndecodable(job_id, policy));
 
         Ok(())
     }
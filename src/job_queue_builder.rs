@@ -1,3 +1,6 @@
+use std::future::Future;
+use std::time::Duration;
+
 use crate::prelude::*;
 
 pub struct JobQueueBuilder<RoutineType, Context> {
@@ -49,7 +52,23 @@ where
     pub fn backend(self, backend: impl Backend<RoutineType, Context> + 'static) -> Self {
         let mut jq = self.jq;
 
-        jq.set_backend(backend);
+        // Cannot fail as the queue is not started yet
+        let _ = jq.set_backend(backend);
+
+        Self { jq }
+    }
+
+    /// Set the namespace isolating the jobs of the queue in the backend storage.
+    ///
+    /// # Arguments:
+    /// * `namespace` - Namespace to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn namespace(self, namespace: impl Into<String>) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_namespace(namespace);
 
         Self { jq }
     }
@@ -72,6 +91,22 @@ where
         Self { jq }
     }
 
+    /// Set the notifier posting the notifications of the job queue to webhooks.
+    ///
+    /// # Arguments:
+    /// * `notifier` - Instance to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    #[cfg(feature = "webhook")]
+    pub fn webhook_notifier(self, notifier: WebhookNotifier) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_webhook_notifier(notifier);
+
+        Self { jq }
+    }
+
     /// Set the context to be passed to every routine.
     ///
     /// # Arguments:
@@ -87,6 +122,754 @@ where
         Self { jq }
     }
 
+    /// Set the context to be passed to every routine, shared in the mode given.
+    ///
+    /// # Arguments:
+    /// * `mode` - Context and the way it is shared (see `JobQueue::set_context_mode`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn context_mode(self, mode: ContextMode<Context>) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_context_mode(mode);
+
+        Self { jq }
+    }
+
+    /// Set the factory building the context to be passed to every routine asynchronously, on
+    /// the runtime of the queue when started.
+    ///
+    /// # Arguments:
+    /// * `factory` - Function returning the future building the context (see
+    ///   `JobQueue::set_context_factory`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn context_factory<F, Fut, C>(self, factory: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<C, ApiError>> + Send + 'static,
+        C: Into<ContextMode<Context>>,
+    {
+        let mut jq = self.jq;
+
+        jq.set_context_factory(factory);
+
+        Self { jq }
+    }
+
+    /// Set the resolver of the context passed to the routines of the jobs holding their own
+    /// context.
+    ///
+    /// # Arguments:
+    /// * `resolver` - Function called with the context of the queue (if any) and the one of the
+    ///   job (see `JobQueue::set_context_resolver`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn context_resolver(
+        self,
+        resolver: impl Fn(
+                Option<&ContextMode<Context>>,
+                &serde_json::Value,
+            ) -> Result<ContextMode<Context>, ApiError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_context_resolver(resolver);
+
+        Self { jq }
+    }
+
+    /// Set the retention policy applied on finished jobs.
+    ///
+    /// # Arguments:
+    /// * `retention` - Retention policy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn retention(self, retention: RetentionPolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_retention(retention);
+
+        Self { jq }
+    }
+
+    /// Set the duration the results of a kind of job are kept once finished.
+    ///
+    /// # Arguments:
+    /// * `kind` - Kind of the jobs.
+    /// * `ttl` - Duration the results are kept (see `JobQueue::set_result_ttl`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn result_ttl(self, kind: impl Into<String>, ttl: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_result_ttl(kind, ttl);
+
+        Self { jq }
+    }
+
+    /// Set the timeout of the jobs enqueued without deadline.
+    ///
+    /// # Arguments:
+    /// * `timeout` - Timeout of the jobs (see `JobQueue::set_default_timeout`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn default_timeout(self, timeout: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_default_timeout(timeout);
+
+        Self { jq }
+    }
+
+    /// Set the strategy used to evaluate the expirations of the jobs.
+    ///
+    /// # Arguments:
+    /// * `sweep_strategy` - Strategy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn sweep_strategy(self, sweep_strategy: SweepStrategy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_sweep_strategy(sweep_strategy);
+
+        Self { jq }
+    }
+
+    /// Set the strategy deciding which eligible job runs next.
+    ///
+    /// # Arguments:
+    /// * `strategy` - Strategy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn scheduler(self, strategy: impl SchedulerStrategy + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_scheduler(strategy);
+
+        Self { jq }
+    }
+
+    /// Set the maximum number of jobs running at the same time.
+    ///
+    /// # Arguments:
+    /// * `max_in_flight` - Maximum number of jobs (at least 1).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn max_in_flight(self, max_in_flight: usize) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_max_in_flight(max_in_flight);
+
+        Self { jq }
+    }
+
+    /// Set the policy used to delay or drop the jobs of non-critical kinds when the queue is
+    /// overloaded.
+    ///
+    /// # Arguments:
+    /// * `shedding` - Policy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn shedding(self, shedding: SheddingPolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_shedding(shedding);
+
+        Self { jq }
+    }
+
+    /// Set the maximum number of jobs waiting to be dispatched.
+    ///
+    /// # Arguments:
+    /// * `max_pending` - Maximum number of jobs.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn max_pending(self, max_pending: usize) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_max_pending(max_pending);
+
+        Self { jq }
+    }
+
+    /// Set the quotas of the tenants owning the jobs.
+    ///
+    /// # Arguments:
+    /// * `quotas` - Quotas to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn tenant_quotas(self, quotas: TenantQuotas) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_tenant_quotas(quotas);
+
+        Self { jq }
+    }
+
+    /// Set the named queues hosted by the queue.
+    ///
+    /// # Arguments:
+    /// * `named_queues` - Named queues to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn named_queues(self, named_queues: NamedQueues) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_named_queues(named_queues);
+
+        Self { jq }
+    }
+
+    /// Route the jobs of a kind, enqueued without queue, to a named queue.
+    ///
+    /// # Arguments:
+    /// * `kind` - Kind of job.
+    /// * `queue` - Name of the queue.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn route_kind(self, kind: impl Into<String>, queue: impl Into<String>) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_route_kind(kind, queue);
+
+        Self { jq }
+    }
+
+    /// Set the policy letting the urgent jobs take the slot of the running jobs of lower
+    /// priority.
+    ///
+    /// # Arguments:
+    /// * `preemption` - Policy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn preemption(self, preemption: PreemptionPolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_preemption(preemption);
+
+        Self { jq }
+    }
+
+    /// Set the policy adjusting the maximum number of jobs in flight to the depth of the queue.
+    ///
+    /// # Arguments:
+    /// * `autoscale` - Policy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn autoscale(self, autoscale: AutoscalePolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_autoscale(autoscale);
+
+        Self { jq }
+    }
+
+    /// Enable the janitor releasing the locks held by dead workers.
+    ///
+    /// # Arguments:
+    /// * `interval` - Interval at which the stale locks are released.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn lock_janitor(self, interval: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_lock_janitor(interval);
+
+        Self { jq }
+    }
+
+    /// Set the duration of the leases of the jobs claimed from a shared backend.
+    ///
+    /// # Arguments:
+    /// * `lease` - Duration of the leases.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn lease_duration(self, lease: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_lease_duration(lease);
+
+        Self { jq }
+    }
+
+    /// Enable soft-delete, keeping the removed jobs to be restored.
+    ///
+    /// # Arguments:
+    /// * `retention` - Duration during which a removed job is kept.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn soft_delete(self, retention: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_soft_delete(retention);
+
+        Self { jq }
+    }
+
+    /// Set the maximum duration given to the queue to drain once stopped.
+    ///
+    /// # Arguments:
+    /// * `timeout` - Maximum duration of the drain.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn drain_timeout(self, timeout: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_drain_timeout(timeout);
+
+        Self { jq }
+    }
+
+    /// Set the duration after which a staged job neither confirmed nor aborted is dropped.
+    ///
+    /// # Arguments:
+    /// * `ttl` - Duration during which a job stays staged (see `JobQueue::set_staging_ttl`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn staging_ttl(self, ttl: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_staging_ttl(ttl);
+
+        Self { jq }
+    }
+
+    /// Set the window during which the jobs holding the same idempotency key are duplicates.
+    ///
+    /// # Arguments:
+    /// * `window` - Duration of the window.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn idempotency_window(self, window: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_idempotency_window(window);
+
+        Self { jq }
+    }
+
+    /// Enable the reaper recovering the jobs claimed by dead workers.
+    ///
+    /// # Arguments:
+    /// * `timeout` - Duration after which a heartbeat is stale.
+    /// * `policy` - Policy applied on the orphaned jobs.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn orphan_reaper(self, timeout: Duration, policy: OrphanPolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_orphan_reaper(timeout, policy);
+
+        Self { jq }
+    }
+
+    /// Enable the event log recording every change in the life of the jobs.
+    ///
+    /// # Arguments:
+    /// * `capacity` - Maximum number of events retained.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn event_log(self, capacity: usize) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_event_log(capacity);
+
+        Self { jq }
+    }
+
+    /// Enable the watchdog flagging the jobs running longer than expected.
+    ///
+    /// # Arguments:
+    /// * `policy` - Policy of the watchdog.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn watchdog(self, policy: WatchdogPolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_watchdog(policy);
+
+        Self { jq }
+    }
+
+    /// Set the policy running again the jobs whose routine fails.
+    ///
+    /// # Arguments:
+    /// * `policy` - Policy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn retry_policy(self, policy: RetryPolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_retry_policy(policy);
+
+        Self { jq }
+    }
+
+    /// Set the number of stored pending jobs checked against the routine type at start.
+    ///
+    /// # Arguments:
+    /// * `sample` - Maximum number of jobs checked (0 disables the check).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn schema_check_sample(self, sample: usize) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_schema_check_sample(sample);
+
+        Self { jq }
+    }
+
+    /// Set the policy applied on the dispatched jobs that cannot be deserialized anymore.
+    ///
+    /// # Arguments:
+    /// * `policy` - Policy to be set.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn undecodable_policy(self, policy: UndecodablePolicy) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_undecodable_policy(policy);
+
+        Self { jq }
+    }
+
+    /// Set a backend shared with other queues of the same process.
+    ///
+    /// # Arguments:
+    /// * `backend` - Shared backend instance.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn shared_backend(self, backend: SharedBackend<RoutineType, Context>) -> Self {
+        let mut jq = self.jq;
+
+        // Cannot fail as the queue is not started yet
+        let _ = jq.set_shared_backend(backend);
+
+        Self { jq }
+    }
+
+    /// Set the identifier of the worker claiming jobs in the backend.
+    ///
+    /// # Arguments:
+    /// * `worker_id` - Worker ID, unique among the workers sharing the backend.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn worker_id(self, worker_id: impl Into<String>) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_worker_id(worker_id);
+
+        Self { jq }
+    }
+
+    /// Set the routines registered by name, run by the jobs created with `Job::registered`.
+    ///
+    /// # Arguments:
+    /// * `registry` - Registry of the routines.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn registry(self, registry: RoutineRegistry<Context>) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_registry(registry);
+
+        Self { jq }
+    }
+
+    /// Set the migrator upgrading the routines stored by an older release.
+    ///
+    /// # Arguments:
+    /// * `migrator` - Migrator of the routines.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn migrator(self, migrator: impl RoutineMigrator + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_migrator(migrator);
+
+        Self { jq }
+    }
+
+    /// Set the format of the job records stored by the backend.
+    ///
+    /// # Arguments:
+    /// * `codec` - Codec of the records.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn codec(self, codec: impl Codec + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_codec(codec);
+
+        Self { jq }
+    }
+
+    /// Set the compression of the large entries of the job records stored by the backend.
+    ///
+    /// # Arguments:
+    /// * `compression` - Compression policy.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn compression(self, compression: Compression) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_compression(compression);
+
+        Self { jq }
+    }
+
+    /// Set the encryption at rest of the private data and results of the job records stored by
+    /// the backend.
+    ///
+    /// # Arguments:
+    /// * `encryption` - Encryption policy.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn encryption(self, encryption: Encryption) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_encryption(encryption);
+
+        Self { jq }
+    }
+
+    /// Set the clock of the expirations, delays and deadlines of the jobs.
+    ///
+    /// # Arguments:
+    /// * `clock` - Clock to be set (e.g. a `ManualClock` in tests).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn clock(self, clock: impl Clock + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_clock(clock);
+
+        Self { jq }
+    }
+
+    /// Set the policy writing the large results to an external store.
+    ///
+    /// # Arguments:
+    /// * `offload` - Policy offloading the large results.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn result_offload(self, offload: ResultOffload) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_result_offload(offload);
+
+        Self { jq }
+    }
+
+    /// Add a middleware wrapping the execution of every job, inside the ones added before.
+    ///
+    /// # Arguments:
+    /// * `middleware` - Middleware to be added.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn layer(self, middleware: impl Middleware + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.add_middleware(middleware);
+
+        Self { jq }
+    }
+
+    /// Set the hook called whenever a job starts running.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn on_start(self, hook: impl Fn(&Job) + Send + Sync + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_on_start(hook);
+
+        Self { jq }
+    }
+
+    /// Set the hook called whenever a job succeeds.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job and its result.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn on_success(self, hook: impl Fn(&Job, &[u8]) + Send + Sync + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_on_success(hook);
+
+        Self { jq }
+    }
+
+    /// Set the hook called whenever a job run fails.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job, its result status and its error message.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn on_failure(
+        self,
+        hook: impl Fn(&Job, ResultStatus, &str) + Send + Sync + 'static,
+    ) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_on_failure(hook);
+
+        Self { jq }
+    }
+
+    /// Set the hook called whenever a running job publishes a domain-specific signal.
+    ///
+    /// # Arguments:
+    /// * `hook` - Hook called with the job, the name of the command and its payload.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn on_command(self, hook: impl Fn(&Job, &str, &[u8]) + Send + Sync + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_on_command(hook);
+
+        Self { jq }
+    }
+
+    /// Add an interceptor inspecting the jobs enqueued, after the ones added before.
+    ///
+    /// # Arguments:
+    /// * `interceptor` - Interceptor to be added.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn interceptor(self, interceptor: impl EnqueueInterceptor + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.add_interceptor(interceptor);
+
+        Self { jq }
+    }
+
+    /// Set the generator of the IDs of the jobs enqueued.
+    ///
+    /// # Arguments:
+    /// * `generator` - Generator to be used (e.g. `UuidV7Generator` for time-sortable IDs).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn id_generator(self, generator: impl IdGenerator + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_id_generator(generator);
+
+        Self { jq }
+    }
+
+    /// Set the propagator of the trace contexts of the jobs.
+    ///
+    /// # Arguments:
+    /// * `propagator` - Bridge to the tracing stack.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn trace_propagator(self, propagator: impl TracePropagator + 'static) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_trace_propagator(propagator);
+
+        Self { jq }
+    }
+
+    /// Set the kinds of job executed by the worker.
+    ///
+    /// # Arguments:
+    /// * `kinds` - Filter of the kinds (e.g. `KindFilter::allow(["Train"])`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn kinds(self, kinds: KindFilter) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_kinds(kinds);
+
+        Self { jq }
+    }
+
+    /// Set the capabilities advertised by the worker.
+    ///
+    /// # Arguments:
+    /// * `capabilities` - Capabilities (e.g. `Capabilities::new(["gpu"])`).
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn capabilities(self, capabilities: Capabilities) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_capabilities(capabilities);
+
+        Self { jq }
+    }
+
+    /// Make the queue a worker claiming jobs from a backend shared with other workers.
+    ///
+    /// # Arguments:
+    /// * `poll_interval` - Interval at which the backend is polled.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueBuilder`.
+    pub fn distributed(self, poll_interval: Duration) -> Self {
+        let mut jq = self.jq;
+
+        jq.set_distributed(poll_interval);
+
+        Self { jq }
+    }
+
     /// Build the job queue consuming the current builder instance.
     ///
     /// # Returns
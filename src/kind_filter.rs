@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Kinds of job a worker executes, so that the workers of a heterogeneous fleet sharing a
+/// backend only claim the jobs they are provisioned for (e.g. ML kinds on GPU workers).
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum KindFilter {
+    /// Every kind is executed.
+    #[default]
+    All,
+
+    /// Only these kinds are executed.
+    Allow(HashSet<String>),
+
+    /// Every kind but these ones is executed.
+    Deny(HashSet<String>),
+}
+
+impl KindFilter {
+    /// Creates a filter executing only a list of kinds.
+    ///
+    /// # Arguments
+    /// * `kinds` - Kinds executed.
+    ///
+    /// # Returns
+    /// An instance of `KindFilter`.
+    pub fn allow<T: Into<String>>(kinds: impl IntoIterator<Item = T>) -> Self {
+        Self::Allow(kinds.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates a filter executing every kind but a list of kinds.
+    ///
+    /// # Arguments
+    /// * `kinds` - Kinds excluded.
+    ///
+    /// # Returns
+    /// An instance of `KindFilter`.
+    pub fn deny<T: Into<String>>(kinds: impl IntoIterator<Item = T>) -> Self {
+        Self::Deny(kinds.into_iter().map(Into::into).collect())
+    }
+
+    /// Checks if a kind of job is executed.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of job.
+    ///
+    /// # Returns
+    /// `true` if executed, `false` otherwise.
+    pub fn accepts(&self, kind: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Allow(kinds) => kinds.contains(kind),
+            Self::Deny(kinds) => !kinds.contains(kind),
+        }
+    }
+}
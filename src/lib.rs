@@ -1,11 +1,78 @@
+pub mod autoscale;
 pub mod backend;
+pub mod capabilities;
+pub mod channel;
+pub mod client;
+pub mod clock;
+pub mod codec;
+pub mod completion;
+pub mod compression;
+pub mod config;
+pub mod context;
+pub mod encryption;
 pub mod error;
+pub mod event_log;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod handles;
+pub mod hooks;
+pub mod id_generator;
+pub mod idempotency;
+pub mod inline;
+pub mod interceptor;
 pub mod job;
+pub mod job_info;
 pub mod job_queue;
 pub mod job_queue_builder;
+pub mod kind_filter;
+mod local_routine;
+pub mod lock;
 pub mod memory_backend;
+pub mod middleware;
+pub mod migration;
+pub mod named_queue;
+pub mod pagination;
+pub mod preemption;
 pub mod prelude;
+pub mod purge;
+pub mod query;
+pub mod read_only;
+pub mod recurrence;
+pub mod registry;
+pub mod resilient_backend;
+pub mod result_store;
+mod result_stream;
+pub mod retry;
+pub mod scheduler;
+pub mod schema;
+mod sensitive;
+pub mod service;
+pub mod shedding;
+#[cfg(feature = "signals")]
+mod signals;
+pub mod stats;
+pub mod subscription;
+mod tcp;
+pub mod tenant;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod tiered_backend;
+pub mod timeline;
+mod tombstones;
+pub mod trace_context;
 pub mod types;
+pub mod wal;
+pub mod watchdog;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+pub mod wire;
+pub mod yield_points;
+
+// Paths of the code generated by the macros resolved inside the crate too
+extern crate self as cs_jobs_v3;
+
+#[doc(hidden)]
+pub use serde;
 
 #[cfg(test)]
 mod tests {
@@ -18,6 +85,13 @@ mod tests {
 
     static FLAG: Mutex<bool> = Mutex::new(false);
     static COUNTER: Mutex<u32> = Mutex::new(0);
+    static EXPIRED: Mutex<Vec<Uuid>> = Mutex::new(Vec::new());
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    static SHED: Mutex<Vec<(Uuid, SheddingAction)>> = Mutex::new(Vec::new());
+    static STALE_LOCKS: Mutex<Vec<Lock>> = Mutex::new(Vec::new());
+    static DRIFTS: Mutex<Vec<SchemaDrift>> = Mutex::new(Vec::new());
+    static SCALED: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+    static PURGES: Mutex<Vec<(Uuid, PurgeProgress)>> = Mutex::new(Vec::new());
 
     pub struct Context {
         name: String,
@@ -39,9 +113,58 @@ mod tests {
             Notification::Status(id, status) => {
                 println!("STATUS({id}): {status:#?}")
             }
+
+            Notification::JobExpired(id) => EXPIRED.lock().unwrap().push(id),
+
+            Notification::JobStalled(id, elapsed) => println!("STALLED({id}): {elapsed:#?}"),
+
+            Notification::JobShed(id, action) => SHED.lock().unwrap().push((id, action)),
+
+            Notification::StaleLockReleased(lock) => STALE_LOCKS.lock().unwrap().push(lock),
+
+            Notification::SchemaDrift(drift) => DRIFTS.lock().unwrap().push(drift),
+
+            Notification::JobUndecodable(id, policy) => {
+                println!("UNDECODABLE({id}): {policy:#?}")
+            }
+
+            Notification::JobOrphaned(id, policy) => println!("ORPHANED({id}): {policy:#?}"),
+
+            Notification::Scaled(from, to) => SCALED.lock().unwrap().push((from, to)),
+
+            Notification::PurgeProgress(id, progress) => {
+                PURGES.lock().unwrap().push((id, progress))
+            }
+
+            Notification::StateChanged(state) => println!("STATE: {state:#?}"),
+
+            Notification::Idle => println!("IDLE"),
+
+            Notification::Custom(id, name, _) => println!("CUSTOM({id}): {name}"),
         }
     }
 
+    fn check_expired(id: &Uuid) {
+        assert!(EXPIRED.lock().unwrap().contains(id));
+    }
+
+    fn record(label: &str) -> Job {
+        Job::new(Routines::Record(RecordArgs {
+            label: label.to_string(),
+        }))
+        .unwrap()
+    }
+
+    fn records(prefix: &str) -> Vec<String> {
+        RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|label| label.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
     fn reset_counter() {
         *COUNTER.lock().unwrap() = 0;
     }
@@ -77,6 +200,22 @@ mod tests {
         expect_no_data: bool,
     }
 
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct RecordArgs {
+        label: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct SpinArgs {
+        iterations: u64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct StepsArgs {
+        count: u64,
+        interval: std::time::Duration,
+    }
+
     #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
     pub struct SleepArgs {
         duration: std::time::Duration,
@@ -84,13 +223,21 @@ mod tests {
 
     #[derive(Debug, PartialEq, Serialize, Deserialize)]
     pub enum Routines {
+        Annotate(SleepArgs),
+        BlockingSleep(SleepArgs),
         CheckContext,
         CheckPrivateData(CheckPrivateDataArgs),
         Nop,
+        Publish,
         RaiseError,
+        Record(RecordArgs),
+        Resumable,
         SetCounter,
         SetFlag(SetFlagArgs),
         Sleep(SleepArgs),
+        Spin(SpinArgs),
+        Steps(StepsArgs),
+        Stream(StepsArgs),
     }
 
     #[async_trait]
@@ -102,6 +249,21 @@ mod tests {
             context: Option<Shared<Context>>,
         ) -> Result<Vec<u8>, Error> {
             match self {
+                Self::Annotate(args) => {
+                    job.save_metadata(&messages_channel, "rows", serde_json::json!(42))
+                        .map_err(|e| *e)?;
+
+                    tokio::time::sleep(args.duration).await;
+
+                    Ok(vec![])
+                }
+
+                Self::BlockingSleep(args) => {
+                    std::thread::sleep(args.duration);
+
+                    Ok(vec![])
+                }
+
                 Self::CheckContext => {
                     assert!(context.is_some());
                     assert_eq!(&context.unwrap().lock().unwrap().name, "UNIT_TESTING");
@@ -122,10 +284,44 @@ mod tests {
 
                 Self::Nop => Ok(vec![]),
 
+                Self::Publish => {
+                    job.send_command(&messages_channel, "file_ready", b"/tmp/report.csv".to_vec())
+                        .map_err(|e| *e)?;
+
+                    Ok(vec![])
+                }
+
                 Self::RaiseError => {
                     return Err(Error::Custom("This is a failure".to_string()));
                 }
 
+                Self::Record(args) => {
+                    RECORDS.lock().unwrap().push(args.label.clone());
+
+                    Ok(vec![])
+                }
+
+                Self::Resumable => {
+                    let start = job.checkpoint().map(|bytes| bytes[0]).unwrap_or(0);
+
+                    for step in start..4 {
+                        // Fail on the first attempt once half of the work is done
+                        if step == 2 && start == 0 {
+                            return Err(Error::Custom("Crashed".to_string()));
+                        }
+
+                        job.save_checkpoint(&messages_channel, vec![step + 1])
+                            .map_err(|e| *e)?;
+                    }
+
+                    RECORDS
+                        .lock()
+                        .unwrap()
+                        .push(format!("RESUMED_FROM_{start}"));
+
+                    Ok(vec![])
+                }
+
                 Self::SetCounter => {
                     increment_counter();
 
@@ -163,6 +359,70 @@ mod tests {
 
                     Ok(vec![])
                 }
+
+                Self::Spin(args) => {
+                    let mut yields = job.yield_every(1_000);
+                    let mut sum = 0u64;
+
+                    for i in 0..args.iterations {
+                        sum = sum.wrapping_add(i);
+                        yields.tick().await;
+                    }
+
+                    Ok(sum.to_le_bytes().to_vec())
+                }
+
+                Self::Steps(args) => {
+                    let messages_channel = messages_channel.lock().unwrap().clone();
+
+                    messages_channel
+                        .send(Message::Command(Cmd::SetSteps(job.id(), args.count)))
+                        .unwrap();
+
+                    for step in 1..=args.count {
+                        job.yield_if_suspended().await;
+                        tokio::time::sleep(args.interval).await;
+
+                        messages_channel
+                            .send(Message::Command(Cmd::SetStep(job.id(), step)))
+                            .unwrap();
+                    }
+
+                    Ok(vec![])
+                }
+
+                Self::Stream(args) => {
+                    for chunk in 1..=args.count {
+                        tokio::time::sleep(args.interval).await;
+
+                        job.emit_chunk(vec![chunk as u8]).map_err(|e| *e)?;
+                    }
+
+                    Ok(vec![])
+                }
+            }
+        }
+
+        fn private_data_requirements(&self) -> Vec<PrivateDataRequirement> {
+            match self {
+                Self::CheckPrivateData(args) if !args.expect_no_data => {
+                    vec![PrivateDataRequirement::of::<PrivateData>()]
+                }
+                _ => vec![],
+            }
+        }
+
+        fn version(&self) -> u32 {
+            match self {
+                Self::Record(_) => 1,
+                _ => 0,
+            }
+        }
+
+        fn exec_class(&self) -> ExecClass {
+            match self {
+                Self::BlockingSleep(_) => ExecClass::Blocking,
+                _ => ExecClass::Async,
             }
         }
     }
@@ -360,6 +620,147 @@ mod tests {
             jq.join().unwrap();
         }
 
+        #[test]
+        fn missing_private_data() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new().unwrap().build();
+
+            jq.start().unwrap();
+
+            let mut job = Job::new(Routines::CheckPrivateData(CheckPrivateDataArgs {
+                value: 13,
+                expect_no_data: false,
+            }))
+            .unwrap();
+
+            // Rejected when enqueued, without or with private data of another type
+            for data in [None, Some(Value::String("13".to_owned()))] {
+                if let Some(data) = data {
+                    job.set_private_data(data).unwrap();
+                }
+
+                let err = jq.enqueue(job.clone()).unwrap_err();
+
+                assert!(matches!(
+                    *err,
+                    Error::UnmetPrivateDataRequirement(kind, type_name)
+                        if kind == "CheckPrivateData" && type_name.ends_with("PrivateData")
+                ));
+            }
+
+            job.set_private_data(PrivateData { value: 13 }).unwrap();
+            assert!(jq.enqueue(job).is_ok());
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn typed_entries() {
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Auth {
+                user: String,
+            }
+
+            let mut job = Job::new(Routines::CheckPrivateData(CheckPrivateDataArgs {
+                value: 13,
+                expect_no_data: false,
+            }))
+            .unwrap();
+
+            // Entries of several layers kept side by side, by type or by name
+            job.insert_data(Auth {
+                user: "alice".to_owned(),
+            })
+            .unwrap();
+            job.insert_data(PrivateData { value: 13 }).unwrap();
+            job.insert_named_data("region", "eu-west").unwrap();
+            job.insert_named_data("zone", "eu-west-3").unwrap();
+
+            assert_eq!(job.get_data::<Auth>().unwrap().user, "alice");
+            assert_eq!(job.get_data::<PrivateData>().unwrap().value, 13);
+            assert_eq!(job.get_named_data::<String>("region").unwrap(), "eu-west");
+            assert_eq!(job.get_named_data::<String>("zone").unwrap(), "eu-west-3");
+
+            // Typed entry meeting the requirement of the routine
+            job.check_private_data(&[PrivateDataRequirement::of::<PrivateData>()])
+                .unwrap();
+
+            // Kept by the records
+            let bytes = job.encode(&JsonCodec).unwrap();
+            let decoded = Job::decode(&bytes, &JsonCodec).unwrap();
+            assert!(decoded == job);
+            assert_eq!(decoded.get_data::<Auth>().unwrap().user, "alice");
+
+            assert!(job.remove_data("zone"));
+            assert!(!job.remove_data("zone"));
+            assert!(matches!(
+                *job.get_named_data::<String>("zone").unwrap_err(),
+                Error::MissingData(name) if name == "zone"
+            ));
+        }
+
+        #[test]
+        fn sensitive_entries() {
+            let mut job = Job::new_with_expire(Routines::Nop, ExpirePolicy::Manual).unwrap();
+
+            job.insert_sensitive_named_data("token", "API_TOKEN")
+                .unwrap();
+            job.insert_named_data("region", "eu-west").unwrap();
+            assert!(job.is_sensitive("token"));
+            assert!(!job.is_sensitive("region"));
+
+            // Redacted in the debug output
+            let debug = format!("{job:?}");
+            assert!(!debug.contains("API_TOKEN"));
+            assert!(debug.contains("<redacted>") && debug.contains("eu-west"));
+
+            // Left out of the serialized job, hence of the records
+            let json = serde_json::to_string(&job).unwrap();
+            assert!(!json.contains("API_TOKEN") && json.contains("eu-west"));
+
+            let bytes = job.encode(&JsonCodec).unwrap();
+            assert!(!String::from_utf8_lossy(&bytes).contains("API_TOKEN"));
+
+            let decoded = Job::decode(&bytes, &JsonCodec).unwrap();
+            assert!(decoded.get_named_data::<String>("token").is_err());
+            assert_eq!(
+                decoded.get_named_data::<String>("region").unwrap(),
+                "eu-west"
+            );
+
+            // Wiped once the job is finished
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                let stored = jq
+                    .with_backend(|mut backend| async move { backend.get(&job_id).await })
+                    .await
+                    .unwrap();
+
+                assert_eq!(stored.status(), Status::Finished(ResultStatus::Success));
+                assert!(matches!(
+                    *stored.get_named_data::<String>("token").unwrap_err(),
+                    Error::MissingData(_)
+                ));
+                assert_eq!(
+                    stored.get_named_data::<String>("region").unwrap(),
+                    "eu-west"
+                );
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
         #[test]
         fn check_no_private_data() {
             let mut jq = JobQueueBuilder::<Routines, Context>::new().unwrap().build();
@@ -422,8 +823,13 @@ mod tests {
                 let status = jq.job_status(&job_id).await.unwrap();
                 assert_eq!(status, Status::Finished(ResultStatus::Success));
 
-                // Fetch the result and verify that the job no longer exists after that
+                // Fetch the result and verify that the job is kept during the grace period
+                let _ = jq.job_result(&job_id).await.unwrap();
+                assert!(jq.job_status(&job_id).await.is_ok());
                 let _ = jq.job_result(&job_id).await.unwrap();
+
+                // Wait for the grace period to be over and verify that the job no longer exists
+                tokio::time::sleep(timeout * 2).await;
                 assert!(jq.job_status(&job_id).await.is_err());
 
                 // Stop the job queue
@@ -472,6 +878,156 @@ mod tests {
         }
     }
 
+    mod sweep {
+        use super::*;
+
+        #[test]
+        fn sweep_interval() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .sweep_strategy(SweepStrategy::Interval(std::time::Duration::from_millis(
+                    50,
+                )))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let policy = ExpirePolicy::Timeout(std::time::Duration::from_millis(100));
+                let job_id = jq
+                    .enqueue(Job::new_with_expire(Routines::Nop, policy).unwrap())
+                    .unwrap();
+
+                // Wait for the sweeper to reap the job
+                tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+                check_expired(&job_id);
+                assert!(jq.job_status(&job_id).await.is_err());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn sweep_lazily() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .sweep_strategy(SweepStrategy::Lazy)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let policy = ExpirePolicy::Timeout(std::time::Duration::from_millis(100));
+                let job_id = jq
+                    .enqueue(Job::new_with_expire(Routines::Nop, policy).unwrap())
+                    .unwrap();
+
+                // Nothing is reaped until the queue is accessed
+                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+                assert!(!EXPIRED.lock().unwrap().contains(&job_id));
+
+                assert!(jq.job_status(&job_id).await.is_err());
+                check_expired(&job_id);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod retention {
+        use super::*;
+
+        #[test]
+        fn expire_on_success_only() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new().unwrap().build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let policy = ExpirePolicy::OnSuccessOnly(std::time::Duration::from_millis(100));
+
+                // Create the jobs and push them
+                let success_id = jq
+                    .enqueue(Job::new_with_expire(Routines::Nop, policy).unwrap())
+                    .unwrap();
+                let failure_id = jq
+                    .enqueue(Job::new_with_expire(Routines::RaiseError, policy).unwrap())
+                    .unwrap();
+
+                // Wait for the expirations to be checked
+                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+
+                // Only the failure is kept
+                assert!(jq.job_status(&success_id).await.is_err());
+                assert_eq!(
+                    jq.job_status(&failure_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn keep_last_n() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .retention(RetentionPolicy::KeepLastN(2))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                // Create the jobs and push them
+                let never_id = jq
+                    .enqueue(Job::new_with_expire(Routines::Nop, ExpirePolicy::Never).unwrap())
+                    .unwrap();
+
+                let mut job_ids = Vec::new();
+
+                for _ in 0..4 {
+                    job_ids.push(jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap());
+                    tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                }
+
+                // Wait for the retention to be applied
+                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+
+                // Only the newest jobs are kept along with the one that never expires
+                assert_eq!(jq.jobs().await.unwrap().len(), 3);
+                assert!(jq.job_status(&never_id).await.is_ok());
+                assert!(jq.job_status(&job_ids[0]).await.is_err());
+                assert!(jq.job_status(&job_ids[1]).await.is_err());
+                assert!(jq.job_status(&job_ids[2]).await.is_ok());
+                assert!(jq.job_status(&job_ids[3]).await.is_ok());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
     mod concurrent_access {
         use super::*;
 
@@ -607,13 +1163,15 @@ mod tests {
                 assert_eq!(fetched.len(), 10);
 
                 for job in jobs {
-                    assert!(fetched.iter().find(|e| e.id() == job.id()).is_some());
+                    assert!(fetched.iter().any(|e| e.id == job.id()));
                 }
 
-                for job in fetched {
-                    if job.routine::<Routines, Context>().unwrap() != Routines::Nop {
-                        assert!(false);
-                    }
+                // Full jobs only fetched one by one
+                for info in fetched {
+                    let job = jq.job(&info.id).await.unwrap();
+
+                    assert_eq!(job.routine::<Routines, Context>().unwrap(), Routines::Nop);
+                    assert_eq!(info.kind, job.kind());
                 }
 
                 // Stop the job queue
@@ -624,22 +1182,7801 @@ mod tests {
         }
     }
 
-    mod errors {
+    mod drain_time {
         use super::*;
 
         #[test]
-        fn not_startable() {
-            let mut jq = JobQueueBuilder::<Routines, Context>::new()
-                .unwrap()
-                .notification_handler(notification_handler)
-                .build();
+        fn estimated_drain_time() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new().unwrap().build();
 
-            Runtime::new().unwrap().block_on(async {
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let sleep = || {
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: tokio::time::Duration::from_millis(50),
+                    }))
+                    .unwrap()
+                };
+
+                // Nothing pending
+                assert_eq!(
+                    jq.estimated_drain_time().await.unwrap(),
+                    Some(std::time::Duration::ZERO)
+                );
+
+                // No throughput measured yet for this kind
+                jq.enqueue(sleep()).unwrap();
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                assert!(jq.estimated_drain_time().await.unwrap().is_none());
+
+                // Once a job has completed, the estimation is available
+                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                jq.enqueue(sleep()).unwrap();
+                jq.enqueue(sleep()).unwrap();
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                let drain_time = jq.estimated_drain_time().await.unwrap().unwrap();
+                assert!(drain_time >= std::time::Duration::from_millis(100));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod scheduler {
+        use super::*;
+
+        fn run_scheduled(jq: &mut JobQueue<Routines, Context>, jobs: Vec<Job>) {
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                // Occupy the only slot so that the other jobs are pending
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: tokio::time::Duration::from_millis(50),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+
+                for job in jobs {
+                    jq.enqueue(job).unwrap();
+                }
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+        }
+
+        #[test]
+        fn fifo() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .build();
+
+            run_scheduled(&mut jq, vec![record("fifo-1"), record("fifo-2")]);
+            jq.join().unwrap();
+
+            assert_eq!(records("fifo-"), vec!["fifo-1", "fifo-2"]);
+        }
+
+        #[test]
+        fn priority() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .scheduler(PriorityStrategy)
+                .build();
+
+            let low = record("priority-low");
+            let mut high = record("priority-high");
+            high.set_priority(10);
+
+            run_scheduled(&mut jq, vec![low, high]);
+            jq.join().unwrap();
+
+            assert_eq!(records("priority-"), vec!["priority-high", "priority-low"]);
+        }
+
+        #[test]
+        fn deadline() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .scheduler(DeadlineStrategy)
+                .build();
+
+            let now = std::time::SystemTime::now();
+            let none = record("deadline-none");
+            let mut late = record("deadline-late");
+            late.set_deadline(now + std::time::Duration::from_secs(60));
+            let mut soon = record("deadline-soon");
+            soon.set_deadline(now + std::time::Duration::from_secs(10));
+
+            run_scheduled(&mut jq, vec![none, late, soon]);
+            jq.join().unwrap();
+
+            assert_eq!(
+                records("deadline-"),
+                vec!["deadline-soon", "deadline-late", "deadline-none"]
+            );
+        }
+
+        #[test]
+        fn fair_share() {
+            let mut strategy = FairShareStrategy::default();
+
+            let pending = |kind: &str| PendingJob {
+                id: Uuid::nil(),
+                kind: kind.to_string(),
+                priority: 0,
+                deadline: None,
+                enqueued: std::time::SystemTime::now(),
+                tenant: None,
+                queue: DEFAULT_QUEUE.to_owned(),
+            };
+
+            let mut candidates = vec![pending("A"), pending("A"), pending("B")];
+
+            assert_eq!(strategy.pick(&candidates), Some(0));
+            candidates.remove(0);
+            assert_eq!(strategy.pick(&candidates), Some(1));
+            candidates.remove(1);
+            assert_eq!(strategy.pick(&candidates), Some(0));
+        }
+
+        #[test]
+        fn lifo() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .scheduler(LifoStrategy)
+                .build();
+
+            run_scheduled(&mut jq, vec![record("lifo-1"), record("lifo-2")]);
+            jq.join().unwrap();
+
+            assert_eq!(records("lifo-"), vec!["lifo-2", "lifo-1"]);
+        }
+
+        #[test]
+        fn weighted_fair() {
+            let mut strategy = WeightedFairStrategy::default().weight("A", 2);
+
+            let pending = |kind: &str| PendingJob {
+                id: Uuid::nil(),
+                kind: kind.to_string(),
+                priority: 0,
+                deadline: None,
+                enqueued: std::time::SystemTime::now(),
+                tenant: None,
+                queue: DEFAULT_QUEUE.to_owned(),
+            };
+
+            let candidates = vec![pending("B"), pending("A")];
+            let kinds: Vec<&str> = (0..6)
+                .map(|_| {
+                    candidates[strategy.pick(&candidates).unwrap()]
+                        .kind
+                        .as_str()
+                })
+                .collect();
+
+            // Twice as many dispatches for A
+            assert_eq!(kinds, vec!["A", "B", "A", "A", "B", "A"]);
+        }
+
+        #[test]
+        fn custom() {
+            // Lowest priority first
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .scheduler(|candidates: &[PendingJob]| {
+                    candidates
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, job)| job.priority)
+                        .map(|(idx, _)| idx)
+                })
+                .build();
+
+            let mut high = record("custom-high");
+            high.set_priority(10);
+            let low = record("custom-low");
+
+            run_scheduled(&mut jq, vec![high, low]);
+            jq.join().unwrap();
+
+            assert_eq!(records("custom-"), vec!["custom-low", "custom-high"]);
+        }
+    }
+
+    mod shedding {
+        use super::*;
+
+        fn overload(jq: &JobQueue<Routines, Context>) {
+            // Occupy the only slot and make the pending jobs cross the threshold
+            jq.enqueue(
+                Job::new(Routines::Sleep(SleepArgs {
+                    duration: tokio::time::Duration::from_millis(50),
+                }))
+                .unwrap(),
+            )
+            .unwrap();
+
+            for _ in 0..2 {
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+            }
+        }
+
+        fn shed_first(action: SheddingAction) -> JobQueue<Routines, Context> {
+            JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .shedding(SheddingPolicy::new(["Record"], action).max_depth(1))
+                .build()
+        }
+
+        #[test]
+        fn drop_when_overloaded() {
+            let mut jq = shed_first(SheddingAction::Drop);
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                overload(&jq);
+
+                let job_id = jq
+                    .enqueue(
+                        Job::new(Routines::Record(RecordArgs {
+                            label: "shed-drop".to_string(),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+                // The job has never been stored nor run
+                assert!(jq.job_status(&job_id).await.is_err());
+                assert!(records("shed-drop").is_empty());
+                assert!(SHED
+                    .lock()
+                    .unwrap()
+                    .contains(&(job_id, SheddingAction::Drop)));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn delay_when_overloaded() {
+            let mut jq = shed_first(SheddingAction::Delay);
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                overload(&jq);
+
+                let job_id = jq
+                    .enqueue(
+                        Job::new(Routines::Record(RecordArgs {
+                            label: "shed-delay".to_string(),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // The job is kept but not run while overloaded
+                assert_eq!(jq.job_status(&job_id).await.unwrap(), Status::Ready);
+                assert!(SHED
+                    .lock()
+                    .unwrap()
+                    .contains(&(job_id, SheddingAction::Delay)));
+
+                // Then it's run once the overload is over
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+                assert_eq!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn delay_with_only_shed_kinds_pending() {
+            // Overloaded as soon as the latency of the backend is measured
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .shedding(
+                    SheddingPolicy::new(["Record"], SheddingAction::Delay)
+                        .max_depth(1)
+                        .max_backend_latency(std::time::Duration::ZERO),
+                )
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let job_ids: Vec<Uuid> = (0..3)
+                    .map(|idx| {
+                        jq.enqueue(
+                            Job::new(Routines::Record(RecordArgs {
+                                label: format!("shed-only-{idx}"),
+                            }))
+                            .unwrap(),
+                        )
+                        .unwrap()
+                    })
+                    .collect();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+
+                // Run one by one, nothing else running
+                for job_id in &job_ids {
+                    assert_eq!(
+                        jq.job_status(job_id).await.unwrap(),
+                        Status::Finished(ResultStatus::Success)
+                    );
+                }
+                assert_eq!(records("shed-only").len(), 3);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod remove {
+        use super::*;
+
+        fn sleep(millis: u64) -> Job {
+            Job::new(Routines::Sleep(SleepArgs {
+                duration: tokio::time::Duration::from_millis(millis),
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn cancel_and_remove() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let running_id = jq.enqueue(sleep(1000)).unwrap();
+                let pending_id = jq.enqueue(sleep(1000)).unwrap();
+                let next_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                assert_eq!(jq.job_status(&running_id).await.unwrap(), Status::Running);
+                assert_eq!(jq.job_status(&pending_id).await.unwrap(), Status::Ready);
+
+                // Both the running and the pending jobs can be removed
+                jq.remove_job_with(&running_id, RemovePolicy::CancelAndRemove)
+                    .await
+                    .unwrap();
+                jq.remove_job_with(&pending_id, RemovePolicy::CancelAndRemove)
+                    .await
+                    .unwrap();
+                assert!(jq.job_status(&running_id).await.is_err());
+                assert!(jq.job_status(&pending_id).await.is_err());
+
+                // The slot has been released for the next job
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                assert_eq!(
+                    jq.job_status(&next_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn cancel_and_remove_enqueued() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                for _ in 0..100 {
+                    jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                }
+
+                // Removed right away, while still waiting to be stored by the queue
+                let job_id = jq
+                    .enqueue(
+                        Job::new(Routines::Record(RecordArgs {
+                            label: "withdrawn".to_string(),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+                jq.remove_job_with(&job_id, RemovePolicy::CancelAndRemove)
+                    .await
+                    .unwrap();
+
+                // Never run, nor stored again
+                jq.wait_idle().await.unwrap();
+                assert!(jq.job_status(&job_id).await.is_err());
+                assert!(records("withdrawn").is_empty());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn wait_and_remove() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new().unwrap().build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let short_id = jq.enqueue(sleep(50)).unwrap();
+                let long_id = jq.enqueue(sleep(1000)).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Not finished yet
+                assert!(jq
+                    .remove_job_with(&short_id, RemovePolicy::OnlyFinished)
+                    .await
+                    .is_err());
+
+                // Finishes before the timeout
+                let policy = RemovePolicy::WaitAndRemove(std::time::Duration::from_millis(500));
+                jq.remove_job_with(&short_id, policy).await.unwrap();
+                assert!(jq.job_status(&short_id).await.is_err());
+
+                // Doesn't finish before the timeout
+                let policy = RemovePolicy::WaitAndRemove(std::time::Duration::from_millis(50));
+                assert!(jq.remove_job_with(&long_id, policy).await.is_err());
+                assert_eq!(jq.job_status(&long_id).await.unwrap(), Status::Running);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod test_util {
+        use super::*;
+        use crate::test_util::*;
+        use std::time::Duration;
+
+        #[test]
+        fn recording_backend() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .backend(backend)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Verify the lifecycle of the job went through the backend
+                let statuses: Vec<Status> = recorder
+                    .calls()
+                    .into_iter()
+                    .filter_map(|call| match call {
+                        BackendCall::SetStatus(id, status) if id == job_id => Some(status),
+                        _ => None,
+                    })
+                    .collect();
+
+                assert_eq!(
+                    recorder.calls()[0],
+                    BackendCall::SetNamespace(DEFAULT_NAMESPACE.to_owned())
+                );
+                assert_eq!(recorder.calls()[1], BackendCall::Jobs);
+                assert_eq!(recorder.calls()[2], BackendCall::Schedule(job_id));
+                assert_eq!(
+                    statuses,
+                    vec![
+                        Status::Ready,
+                        Status::Running,
+                        Status::Finished(ResultStatus::Success)
+                    ]
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn with_backend() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .backend(backend)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Operations not exposed by the queue run on the backend under its lock
+                let calls = recorder.calls().len();
+                let claimed = jq
+                    .with_backend(|mut backend| async move {
+                        let ids: Vec<Uuid> = backend.jobs()?.iter().map(|job| job.id()).collect();
+
+                        let claimed = backend.claim(
+                            "admin",
+                            tokio::time::Duration::from_secs(1),
+                            &KindFilter::All,
+                            &Capabilities::default(),
+                        )?;
+
+                        Ok::<_, ApiError>((ids, claimed.is_some()))
+                    })
+                    .await
+                    .unwrap();
+
+                assert_eq!(claimed, (vec![job_id], false));
+                let calls: Vec<BackendCall> = recorder.calls()[calls..]
+                    .iter()
+                    .filter(|call| **call != BackendCall::IsPaused)
+                    .cloned()
+                    .collect();
+                assert_eq!(
+                    calls,
+                    [BackendCall::Jobs, BackendCall::Claim("admin".to_owned())]
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn mock_executor() {
+            let executor = MockExecutor::<Routines, Context>::with_context(Context {
+                name: "UNIT_TESTING".to_string(),
+            });
+
+            let flag_id = executor
+                .enqueue(Job::new(Routines::SetFlag(SetFlagArgs { value: true })).unwrap())
+                .unwrap();
+            let context_id = executor
+                .enqueue(Job::new(Routines::CheckContext).unwrap())
+                .unwrap();
+            let error_id = executor
+                .enqueue(Job::new(Routines::RaiseError).unwrap())
+                .unwrap();
+
+            assert_eq!(executor.enqueued().len(), 3);
+
+            Runtime::new().unwrap().block_on(async {
+                let results = executor.run_all().await.unwrap();
+
+                assert!(executor.enqueued().is_empty());
+                assert_eq!(results.len(), 3);
+                assert_eq!(results[0].0, flag_id);
+                assert!(results[0].1.is_ok());
+                assert_eq!(results[1].0, context_id);
+                assert!(results[1].1.is_ok());
+                assert_eq!(results[2].0, error_id);
+                assert!(results[2].1.is_err());
+
+                assert_eq!(
+                    executor.progression(&flag_id),
+                    Some(Progression {
+                        step: 2,
+                        steps: 2,
+                        eta: None
+                    })
+                );
+
+                // Private data required by the routine checked as by the queue
+                let job = Job::new(Routines::CheckPrivateData(CheckPrivateDataArgs {
+                    value: 13,
+                    expect_no_data: false,
+                }))
+                .unwrap();
+                assert!(matches!(
+                    *executor.run(&job).await.unwrap_err(),
+                    Error::UnmetPrivateDataRequirement(..)
+                ));
+            });
+        }
+
+        #[test]
+        fn recorded_lifecycle() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .backend(backend)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = jq
+                    .enqueue(
+                        Job::new(Routines::Steps(StepsArgs {
+                            count: 3,
+                            interval: Duration::from_millis(5),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                jq.wait_for(&[job_id], Duration::from_secs(1))
+                    .await
+                    .unwrap();
+
+                // Progress updates applied by the queue thread
+                tokio::time::sleep(Duration::from_millis(10)).await;
+
+                assert_eq!(
+                    recorder.statuses(&job_id),
+                    vec![
+                        Status::Ready,
+                        Status::Running,
+                        Status::Finished(ResultStatus::Success)
+                    ]
+                );
+                assert_eq!(recorder.steps(&job_id), vec![1, 2, 3]);
+                assert_eq!(
+                    recorder.calls_for(&job_id)[0],
+                    BackendCall::Schedule(job_id)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn chaos_backend() {
+            let backend = ChaosBackend::new()
+                .seed(7)
+                .fault(ChaosOp::Schedule, Fault::default().fail_every(2));
+            let chaos = backend.controller();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .backend(backend)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let stored = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                let lost = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Second job failed to be scheduled
+                assert_eq!(
+                    jq.job_status(&stored).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert!(jq.job_status(&lost).await.is_err());
+                assert_eq!(chaos.injected(ChaosOp::Schedule), 1);
+
+                chaos.set_fault(
+                    ChaosOp::Result,
+                    Fault::default()
+                        .failure_rate(1.0)
+                        .latency(Duration::from_millis(5)),
+                );
+                assert!(jq.peek_result(&stored).await.is_err());
+
+                chaos.clear();
+                assert!(jq.peek_result(&stored).await.is_ok());
+                assert_eq!(chaos.injected(ChaosOp::Result), 1);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn manual_dispatch() {
+            let queue = MockExecutor::<Routines, Context>::new();
+            let record = |label: &str| {
+                Routines::Record(RecordArgs {
+                    label: label.to_string(),
+                })
+            };
+
+            let mut job = Job::new(record("test-queue-first")).unwrap();
+            job.set_on_complete_routine(record("test-queue-follow-up"))
+                .unwrap();
+            queue.enqueue(job).unwrap();
+
+            let mut job = Job::new(record("test-queue-delayed")).unwrap();
+            job.set_run_at(queue.now() + Duration::from_secs(60));
+            queue.enqueue(job).unwrap();
+
+            let mut job = Job::new(record("test-queue-recurring")).unwrap();
+            job.set_run_at(queue.now());
+            job.set_recurrence(Recurrence::every(Duration::from_secs(30)));
+            queue.enqueue(job).unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Follow-up job run, the delayed job and next occurrence left for later
+                let results = queue.run_until_idle().await.unwrap();
+                assert_eq!(results.len(), 3);
+                assert_eq!(queue.enqueued().len(), 2);
+                assert!(queue.run_next().await.unwrap().is_none());
+
+                queue.advance(Duration::from_secs(60));
+
+                // Delayed job now due, and the occurrences missed meanwhile run once
+                let results = queue.run_until_idle().await.unwrap();
+                assert_eq!(results.len(), 2);
+            });
+
+            assert_eq!(
+                records("test-queue-"),
+                vec![
+                    "test-queue-first",
+                    "test-queue-recurring",
+                    "test-queue-follow-up",
+                    "test-queue-delayed",
+                    "test-queue-recurring",
+                ]
+            );
+        }
+    }
+
+    mod service {
+        use super::*;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        #[test]
+        fn control_plane() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let service = JobQueueService::new(jq.clone()).poll_interval(Duration::from_millis(5));
+
+            Runtime::new().unwrap().block_on(async {
+                // Enqueue a job in the wire format and follow it
+                let wire = br#"{"version":1,"kind":"Sleep","args":{"duration":{"secs":0,"nanos":50000000}},"metadata":{"source":"service"}}"#;
+                let job_id = service.enqueue(wire).await.unwrap();
+
+                let mut updates = service.stream_progress(&job_id).unwrap();
+                let mut last = None;
+
+                while let Some(update) = updates.recv().await {
+                    last = Some(update.unwrap());
+                }
+
+                let last = last.unwrap();
+                assert_eq!(last.id, job_id);
+                assert_eq!(last.status, Status::Finished(ResultStatus::Success));
+                assert_eq!(
+                    service.get_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Invalid jobs are rejected
+                assert!(service
+                    .enqueue(br#"{"version":1,"kind":"Unknown"}"#)
+                    .await
+                    .is_err());
+
+                // Cancel a running job
+                let wire = br#"{"version":1,"kind":"Sleep","args":{"duration":{"secs":5,"nanos":0}}}"#;
+                let job_id = service.enqueue(wire).await.unwrap();
+
+                let mut updates = service.stream_progress(&job_id).unwrap();
+
+                while let Some(update) = updates.recv().await {
+                    if update.unwrap().status == Status::Running {
+                        break;
+                    }
+                }
+
+                service.cancel(&job_id).await.unwrap();
+                assert_eq!(
+                    service.get_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Cancelled)
+                );
+
+                let jobs = service.list_jobs().await.unwrap();
+                assert!(jobs
+                    .iter()
+                    .any(|job| job.id == job_id && job.kind == "Sleep"));
+
+                // Undecodable requests answered with an error
+                let response = service.handle(Method::GetStatus, b"not-an-id").await;
+                let response: Result<Value, String> = serde_json::from_slice(&response).unwrap();
+                assert!(response.is_err());
+            });
+
+            // Stop the job queue
+            drop(service);
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    #[cfg(feature = "grpc")]
+    mod grpc {
+        use super::*;
+        use crate::grpc::proto;
+        use crate::grpc::proto::job_queue_client::JobQueueClient as GrpcClient;
+        use crate::grpc::AUTHORIZATION_HEADER;
+        use std::sync::Arc;
+        use tokio::net::TcpListener;
+
+        #[test]
+        fn grpc_server() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let service =
+                JobQueueService::new(jq.clone()).poll_interval(std::time::Duration::from_millis(5));
+
+            Runtime::new().unwrap().block_on(async {
+                // Not served without auth token
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                assert!(matches!(
+                    JobQueueGrpcServer::new(service.clone())
+                        .serve(listener)
+                        .await
+                        .map_err(|e| *e),
+                    Err(Error::InvalidConfig(_))
+                ));
+
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+                let server =
+                    tokio::spawn(JobQueueGrpcServer::new(service.token("secret")).serve(listener));
+
+                // RPCs without the auth token rejected
+                let mut anonymous = GrpcClient::connect(format!("http://{addr}")).await.unwrap();
+                let error = anonymous.stats(proto::StatsRequest {}).await.unwrap_err();
+                assert_eq!(error.code(), tonic::Code::Unauthenticated);
+
+                let channel = tonic::transport::Channel::from_shared(format!("http://{addr}"))
+                    .unwrap()
+                    .connect()
+                    .await
+                    .unwrap();
+                let mut client =
+                    GrpcClient::with_interceptor(channel, |mut request: tonic::Request<()>| {
+                        request
+                            .metadata_mut()
+                            .insert(AUTHORIZATION_HEADER, "Bearer secret".parse().unwrap());
+
+                        Ok(request)
+                    });
+
+                // Enqueue a job in the wire format and follow it
+                let job = br#"{"version":1,"kind":"Record","args":{"label":"GRPC"}}"#.to_vec();
+                let id = client
+                    .enqueue(proto::EnqueueRequest { job })
+                    .await
+                    .unwrap()
+                    .into_inner()
+                    .id;
+
+                let mut updates = client
+                    .stream_progress(proto::StreamProgressRequest { id: id.clone() })
+                    .await
+                    .unwrap()
+                    .into_inner();
+                let mut last = None;
+
+                while let Some(update) = updates.message().await.unwrap() {
+                    last = Some(update);
+                }
+
+                assert_eq!(last.unwrap().status(), proto::Status::FinishedSuccess);
+                assert_eq!(records("GRPC"), vec!["GRPC"]);
+
+                let status = client
+                    .get_status(proto::GetStatusRequest { id: id.clone() })
+                    .await
+                    .unwrap()
+                    .into_inner();
+                assert_eq!(
+                    Status::from(status.status()),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                let stats = client.stats(proto::StatsRequest {}).await.unwrap();
+                assert!(QueueStats::from(stats.into_inner()).succeeded >= 1);
+
+                let jobs = client.list_jobs(proto::ListJobsRequest {}).await.unwrap();
+                assert!(jobs.into_inner().jobs.iter().any(|job| job.id == id));
+
+                // Errors of the queue mapped to gRPC codes
+                let unknown = Uuid::now_v1(&[0; 6]).to_string();
+                let error = client
+                    .get_status(proto::GetStatusRequest { id: unknown })
+                    .await
+                    .unwrap_err();
+                assert_eq!(error.code(), tonic::Code::NotFound);
+
+                let error = client
+                    .cancel(proto::CancelRequest {
+                        id: "not-an-id".to_string(),
+                    })
+                    .await
+                    .unwrap_err();
+                assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+                server.abort();
+            });
+
+            // Stop the job queue
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod client {
+        use super::*;
+        use std::sync::Arc;
+
+        // Same call sites whether the queue is in-process or remote
+        async fn record(api: &dyn JobQueueApi, label: &str) -> Uuid {
+            let routine = Routines::Record(RecordArgs {
+                label: label.to_string(),
+            });
+            let job_id = api
+                .enqueue(Job::new_with_expire(routine, ExpirePolicy::Manual).unwrap())
+                .await
+                .unwrap();
+
+            for _ in 0..100 {
+                if let Ok(Status::Finished(_)) = api.job_status(&job_id).await {
+                    break;
+                }
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+
+            assert_eq!(
+                api.job_status(&job_id).await.unwrap(),
+                Status::Finished(ResultStatus::Success)
+            );
+
+            job_id
+        }
+
+        #[test]
+        fn remote_client() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let client = JobQueueClient::new(LocalTransport::new(JobQueueService::new(jq.clone())));
+
+            Runtime::new().unwrap().block_on(async {
+                record(jq.as_ref(), "CLIENT_LOCAL").await;
+                let job_id = record(&client, "CLIENT_REMOTE").await;
+
+                assert_eq!(records("CLIENT_"), vec!["CLIENT_LOCAL", "CLIENT_REMOTE"]);
+                assert!(client
+                    .list_jobs()
+                    .await
+                    .unwrap()
+                    .iter()
+                    .any(|job| job.id == job_id));
+                assert!(client.job_result(&job_id).await.unwrap().is_empty());
+
+                // Errors of the remote queue are forwarded
+                let unknown = Uuid::now_v1(&[0; 6]);
+                assert!(matches!(
+                    client.job_status(&unknown).await.map_err(|e| *e),
+                    Err(Error::Remote(_))
+                ));
+                assert!(client.cancel_job(&unknown).await.is_err());
+            });
+
+            // Stop the job queue
+            drop(client);
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[cfg(feature = "grpc")]
+        #[test]
+        fn grpc_transport() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let server = JobQueueGrpcServer::new(JobQueueService::new(jq.clone()).token("secret"));
+
+            Runtime::new().unwrap().block_on(async {
+                // Serve the control plane on a random port
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+                let server = tokio::spawn(server.serve(listener));
+
+                let client = JobQueueClient::new(
+                    GrpcTransport::new(format!("http://{addr}"), "secret").unwrap(),
+                );
+                let job_id = record(&client, "REMOTE_GRPC").await;
+
+                assert!(client.job_result(&job_id).await.unwrap().is_empty());
+                assert!(client.stats().await.unwrap().succeeded >= 1);
+                assert!(client
+                    .list_jobs()
+                    .await
+                    .unwrap()
+                    .iter()
+                    .any(|job| job.id == job_id));
+                assert_eq!(
+                    client
+                        .purge(&PurgeFilter::finished().tag("none"))
+                        .await
+                        .unwrap(),
+                    vec![]
+                );
+
+                // Errors of the remote queue are forwarded
+                let unknown = Uuid::now_v1(&[0; 6]);
+                assert!(matches!(
+                    client.job_status(&unknown).await.map_err(|e| *e),
+                    Err(Error::Remote(_))
+                ));
+
+                server.abort();
+            });
+
+            // Stop the job queue
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod tcp {
+        use super::*;
+        use std::sync::Arc;
+        use tokio::net::TcpListener;
+
+        async fn wait_finished(client: &dyn JobQueueApi, job_id: &Uuid) {
+            for _ in 0..100 {
+                if let Ok(Status::Finished(_)) = client.job_status(job_id).await {
+                    break;
+                }
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+            }
+        }
+
+        #[test]
+        fn tcp_transport() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let service = JobQueueService::new(jq.clone()).token("secret");
+
+            Runtime::new().unwrap().block_on(async {
+                // Serve the control plane on a random port
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap().to_string();
+                let server = tokio::spawn(async move { service.serve(listener).await });
+
+                let client = JobQueueClient::new(TcpTransport::new(addr.as_str(), "secret"));
+
+                let job = Job::new(Routines::Record(RecordArgs {
+                    label: "TCP_JOB".to_string(),
+                }))
+                .unwrap();
+                let job_id = client.enqueue(job).await.unwrap();
+
+                wait_finished(&client, &job_id).await;
+
+                assert_eq!(
+                    client.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert!(client.stats().await.unwrap().succeeded >= 1);
+
+                // Retry the finished job
+                let retry_id = client.retry_job(&job_id).await.unwrap();
+                assert_ne!(retry_id, job_id);
+
+                wait_finished(&client, &retry_id).await;
+
+                assert_eq!(records("TCP_"), vec!["TCP_JOB", "TCP_JOB"]);
+
+                // Purge both jobs
+                let mut purged = client.purge(&PurgeFilter::finished()).await.unwrap();
+                let mut expected = vec![job_id, retry_id];
+                purged.sort();
+                expected.sort();
+                assert_eq!(purged, expected);
+
+                // Errors of the remote queue are forwarded
+                assert!(matches!(
+                    client
+                        .retry_job(&Uuid::now_v1(&[0; 6]))
+                        .await
+                        .map_err(|e| *e),
+                    Err(Error::Remote(_))
+                ));
+
+                // Requests with another auth token rejected
+                let client = JobQueueClient::new(TcpTransport::new(addr, "other"));
+                assert!(matches!(
+                    client.stats().await.map_err(|e| *e),
+                    Err(Error::Remote(_))
+                ));
+
+                server.abort();
+            });
+
+            // Stop the job queue
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn oversized_request() {
+            Runtime::new().unwrap().block_on(async {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+
+                // Line longer than the limit, without line feed within it
+                let writer = tokio::spawn(async move {
+                    let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+                    let line = vec![b'a'; 2 * crate::tcp::MAX_REQUEST_SIZE];
+
+                    let _ = crate::tcp::write_line(&stream, &line).await;
+
+                    stream
+                });
+
+                let (stream, _) = listener.accept().await.unwrap();
+                let result =
+                    crate::tcp::read_line(&stream, &mut vec![], crate::tcp::MAX_REQUEST_SIZE).await;
+                assert!(matches!(
+                    result.map_err(|e| *e),
+                    Err(Error::FrameTooLarge(crate::tcp::MAX_REQUEST_SIZE))
+                ));
+
+                writer.abort();
+            });
+        }
+
+        #[test]
+        fn unauthenticated_service() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let service = JobQueueService::new(jq.clone());
+
+            Runtime::new().unwrap().block_on(async {
+                // Not served without auth token
+                let listener = JobQueueService::<Routines, Context>::bind(Some("127.0.0.1:0"))
+                    .await
+                    .unwrap();
+                assert!(listener.local_addr().unwrap().ip().is_loopback());
+                assert!(matches!(
+                    service.serve(listener).await.map_err(|e| *e),
+                    Err(Error::InvalidConfig(_))
+                ));
+
+                assert!(service.authenticate("").is_err());
+                assert!(service
+                    .clone()
+                    .token("secret")
+                    .authenticate("secret")
+                    .is_ok());
+                assert!(service.token("secret").authenticate("secrets").is_err());
+            });
+
+            // Stop the job queue
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod namespace {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+
+        type MemBackend = dyn Backend<Routines, Context>;
+
+        #[test]
+        fn namespace_isolation() {
+            let mut backend: Box<MemBackend> = Box::new(MemoryBackend::new());
+
+            // Finished job stored in the staging namespace
+            backend.set_namespace("staging");
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            backend.schedule(job).unwrap();
+            backend.set_status(&job_id, Status::Ready).unwrap();
+            backend.set_status(&job_id, Status::Running).unwrap();
+            backend
+                .set_status(&job_id, Status::Finished(ResultStatus::Success))
+                .unwrap();
+
+            // Invisible from the prod namespace
+            backend.set_namespace("prod");
+            assert!(backend.status(&job_id).is_err());
+            assert!(backend.jobs().unwrap().is_empty());
+            assert!(backend.purge(&PurgeFilter::finished()).unwrap().is_empty());
+            assert!(backend.remove(&job_id).is_err());
+
+            // Still there in the staging namespace
+            backend.set_namespace("staging");
+            let jobs = backend.jobs().unwrap();
+            assert_eq!(jobs.len(), 1);
+            assert_eq!(jobs[0].namespace(), "staging");
+            assert_eq!(
+                backend.purge(&PurgeFilter::finished()).unwrap(),
+                vec![job_id]
+            );
+        }
+
+        #[test]
+        fn queue_namespace() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .namespace("staging")
+                .build();
+
+            assert_eq!(jq.namespace(), "staging");
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = Job::new(Routines::Nop).unwrap();
+                assert_eq!(job.namespace(), DEFAULT_NAMESPACE);
+                let job_id = job.id();
+
+                jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                // Verify that the job has been stored in the namespace of the queue
+                let job = jq.job(&job_id).await.unwrap();
+                assert_eq!(job.namespace(), "staging");
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod locks {
+        use super::*;
+        use std::time::Duration;
+
+        #[test]
+        fn stale_lock_janitor() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .lock_janitor(Duration::from_millis(20))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let ttl = Duration::from_millis(250);
+
+                // A lock held by a live worker is kept as long as it's refreshed
+                assert!(jq.acquire_lock("LOCK_LIVE", "worker-1", ttl).await.unwrap());
+                assert!(!jq.acquire_lock("LOCK_LIVE", "worker-2", ttl).await.unwrap());
+
+                // A lock held by a worker that died
+                assert!(jq
+                    .acquire_lock("LOCK_DEAD", "worker-3", Duration::from_millis(50))
+                    .await
+                    .unwrap());
+
+                for _ in 0..4 {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    assert!(jq.refresh_lock("LOCK_LIVE", "worker-1", ttl).await.unwrap());
+                }
+
+                // Verify that only the dead lock has been released
+                let released: Vec<String> = STALE_LOCKS
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|lock| lock.key.starts_with("LOCK_"))
+                    .map(|lock| lock.key.to_owned())
+                    .collect();
+                assert_eq!(released, vec!["LOCK_DEAD"]);
+
+                assert!(jq.acquire_lock("LOCK_DEAD", "worker-2", ttl).await.unwrap());
+                assert!(!jq.acquire_lock("LOCK_LIVE", "worker-2", ttl).await.unwrap());
+
+                // Only the owner can release a lock
+                assert!(!jq.release_lock("LOCK_LIVE", "worker-2").await.unwrap());
+                assert!(jq.release_lock("LOCK_LIVE", "worker-1").await.unwrap());
+                assert!(jq.acquire_lock("LOCK_LIVE", "worker-2", ttl).await.unwrap());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn tickers_stopped() {
+            static ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(|notification| {
+                    if let Notification::Error(e) = notification {
+                        ERRORS.lock().unwrap().push(e.to_string());
+                    }
+                })
+                .lock_janitor(Duration::from_millis(5))
+                .sweep_strategy(SweepStrategy::Interval(Duration::from_millis(5)))
+                .distributed(Duration::from_millis(5))
+                .orphan_reaper(Duration::from_millis(5), OrphanPolicy::Requeue)
+                .build();
+
+            jq.start().unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+
+            // Threads sending the periodic commands stopped and joined with the queue
+            jq.stop().unwrap();
+            jq.join().unwrap();
+
+            std::thread::sleep(Duration::from_millis(20));
+            assert!(ERRORS.lock().unwrap().is_empty());
+        }
+    }
+
+    mod two_phase {
+        use super::*;
+
+        #[test]
+        fn prepare_confirm_abort() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let confirmed = jq
+                    .prepare(
+                        Job::new(Routines::Record(RecordArgs {
+                            label: "TWO_PHASE_CONFIRMED".to_string(),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+                let aborted = jq
+                    .prepare(
+                        Job::new(Routines::Record(RecordArgs {
+                            label: "TWO_PHASE_ABORTED".to_string(),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                // Staged jobs are neither stored nor run
+                assert!(records("TWO_PHASE_").is_empty());
+                assert!(jq.job_status(&confirmed.id()).await.is_err());
+
+                jq.abort(aborted).unwrap();
+                let job_id = jq.confirm(confirmed).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                // Verify that only the confirmed job has been run
+                assert_eq!(records("TWO_PHASE_"), vec!["TWO_PHASE_CONFIRMED"]);
+                assert_eq!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn stale_staged_jobs() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .staging_ttl(std::time::Duration::from_millis(20))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let stale = jq.prepare(Job::new(Routines::Nop).unwrap()).unwrap();
+
+            std::thread::sleep(std::time::Duration::from_millis(30));
+
+            // Job staged for too long dropped
+            let fresh = jq.prepare(Job::new(Routines::Nop).unwrap()).unwrap();
+
+            assert!(matches!(
+                *jq.confirm(stale).unwrap_err(),
+                Error::JobNotFound(_)
+            ));
+            assert!(jq.confirm(fresh).is_ok());
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    #[cfg(feature = "webhook")]
+    mod webhook {
+        use super::*;
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+        use std::sync::{Arc, Mutex};
+        use std::time::Duration;
+
+        type Requests = Arc<Mutex<Vec<(Vec<String>, Vec<u8>)>>>;
+
+        /// Serves the given HTTP statuses, one request per connection, recording the headers
+        /// and the body of every request.
+        fn serve(statuses: Vec<u16>) -> (String, Requests) {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/hook", listener.local_addr().unwrap());
+            let requests: Requests = Arc::new(Mutex::new(vec![]));
+            let recorded = requests.clone();
+
+            std::thread::spawn(move || {
+                for status in statuses {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut bytes = vec![];
+                    let mut chunk = [0; 1024];
+
+                    let end = loop {
+                        let len = stream.read(&mut chunk).unwrap();
+                        bytes.extend_from_slice(&chunk[..len]);
+
+                        if let Some(pos) = bytes.windows(4).position(|w| w == b"\r\n\r\n") {
+                            break pos + 4;
+                        }
+                    };
+
+                    let headers: Vec<String> = String::from_utf8_lossy(&bytes[..end])
+                        .lines()
+                        .map(|line| line.to_lowercase())
+                        .collect();
+                    let length: usize = headers
+                        .iter()
+                        .find_map(|line| line.strip_prefix("content-length: "))
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+
+                    while bytes.len() < end + length {
+                        let len = stream.read(&mut chunk).unwrap();
+                        bytes.extend_from_slice(&chunk[..len]);
+                    }
+
+                    recorded
+                        .lock()
+                        .unwrap()
+                        .push((headers, bytes[end..end + length].to_vec()));
+
+                    write!(
+                        stream,
+                        "HTTP/1.1 {status} STATUS\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    )
+                    .unwrap();
+                }
+            });
+
+            (url, requests)
+        }
+
+        fn header<'a>(headers: &'a [String], name: &str) -> &'a str {
+            let prefix = format!("{}: ", name.to_lowercase());
+
+            headers
+                .iter()
+                .find_map(|line| line.strip_prefix(&prefix))
+                .unwrap()
+        }
+
+        #[test]
+        fn signed_notifications_with_retries() {
+            // The first delivery fails and is retried
+            let (url, requests) = serve(vec![503, 200, 200]);
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .webhook_notifier(
+                    WebhookNotifier::new([url])
+                        .secret("WEBHOOK_SECRET")
+                        .events([WebhookEvent::JobFinished, WebhookEvent::JobFailed])
+                        .retries(2, Duration::from_millis(10), Duration::from_millis(50)),
+                )
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let succeeded = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                let failed = jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                let requests = requests.lock().unwrap();
+                assert_eq!(requests.len(), 3);
+
+                // Every body is signed along with its timestamp
+                for (headers, body) in requests.iter() {
+                    let timestamp: u64 = header(headers, TIMESTAMP_HEADER).parse().unwrap();
+                    let signature =
+                        WebhookNotifier::sign(b"WEBHOOK_SECRET", timestamp, body).unwrap();
+
+                    assert_eq!(header(headers, SIGNATURE_HEADER), signature);
+                    assert_eq!(header(headers, "content-type"), "application/json");
+                }
+
+                let payloads: Vec<WebhookPayload> = requests
+                    .iter()
+                    .map(|(_, body)| serde_json::from_slice(body).unwrap())
+                    .collect();
+
+                assert_eq!(payloads[0], payloads[1]);
+                assert_eq!(payloads[1].event, WebhookEvent::JobFinished);
+                assert_eq!(payloads[1].job_id, succeeded);
+                assert_eq!(
+                    payloads[1].status,
+                    Some(Status::Finished(ResultStatus::Success))
+                );
+                assert_eq!(payloads[2].event, WebhookEvent::JobFailed);
+                assert_eq!(payloads[2].job_id, failed);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn failed_delivery_notified() {
+            static FAILURES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+            // The receiver keeps failing
+            let (url, requests) = serve(vec![500, 500]);
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .webhook_notifier(WebhookNotifier::new([url.clone()]).retries(
+                    1,
+                    Duration::from_millis(10),
+                    Duration::from_millis(10),
+                ))
+                .notification_handler(|notification| {
+                    if let Notification::Error(Error::WebhookDelivery(url, _)) = notification {
+                        FAILURES.lock().unwrap().push(url);
+                    }
+                })
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                // Delivered twice, then reported to the notification handler set afterwards
+                assert_eq!(requests.lock().unwrap().len(), 2);
+                assert_eq!(*FAILURES.lock().unwrap(), vec![url]);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod read_only {
+        use super::*;
+        use std::sync::Arc;
+
+        #[test]
+        fn read_only_handle() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .sweep_strategy(SweepStrategy::Lazy)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let job = Job::new_with_expire(
+                Routines::Record(RecordArgs {
+                    label: "READ_ONLY".to_string(),
+                }),
+                ExpirePolicy::OnResultFetch(std::time::Duration::from_millis(200)),
+            )
+            .unwrap();
+            let job_id = jq.enqueue(job).unwrap();
+
+            let jq = Arc::new(jq);
+            let reader = ReadOnlyQueue::new(jq.clone());
+
+            Runtime::new().unwrap().block_on(async {
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    reader.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert!(reader
+                    .jobs()
+                    .await
+                    .unwrap()
+                    .iter()
+                    .any(|job| job.id == job_id));
+                assert!(reader.stats().await.unwrap().succeeded >= 1);
+
+                // Reading the result doesn't consume it
+                assert!(reader.job_result(&job_id).await.unwrap().is_empty());
+                assert!(reader.job_status(&job_id).await.is_ok());
+
+                // The owner does, the job expiring once the grace period is over
+                assert!(jq.job_result(&job_id).await.unwrap().is_empty());
+                assert!(reader.job_status(&job_id).await.is_ok());
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(400)).await;
+                assert!(reader.job_status(&job_id).await.is_err());
+            });
+
+            // Stop the job queue
+            drop(reader);
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod schema {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::sync::Arc;
+
+        /// Routines of a previous release, `LegacyRename` having been renamed since.
+        #[derive(Serialize, Deserialize)]
+        enum LegacyRoutines {
+            Nop,
+            LegacyRename,
+        }
+
+        #[async_trait]
+        impl Routine<Context> for LegacyRoutines {
+            async fn call(
+                &self,
+                _job: &Job,
+                _messages_channel: SharedMessageChannel,
+                _context: Option<Shared<Context>>,
+            ) -> Result<Vec<u8>, Error> {
+                Ok(vec![])
+            }
+        }
+
+        #[test]
+        fn schema_drift_detection() {
+            let mut backend = MemoryBackend::new();
+
+            // Pending jobs stored by the previous release
+            let mut drifted = vec![];
+
+            for routine in [
+                LegacyRoutines::Nop,
+                LegacyRoutines::LegacyRename,
+                LegacyRoutines::LegacyRename,
+            ] {
+                let job = Job::new(routine).unwrap();
+
+                if job.kind() == "LegacyRename" {
+                    drifted.push(job.id());
+                }
+
+                Backend::<Routines, Context>::schedule(&mut backend, job).unwrap();
+            }
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .backend(backend)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            // Verify that only the renamed kind has been reported
+            let reported: Vec<SchemaDrift> = DRIFTS
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|drift| drift.kind.starts_with("Legacy"))
+                .cloned()
+                .collect();
+            assert_eq!(reported.len(), 1);
+            assert_eq!(reported[0].job_ids, drifted);
+
+            Runtime::new().unwrap().block_on(async {
+                // Same report on demand, nothing checked with an empty sample
+                let drifts = jq.check_schema(10).await.unwrap();
+                assert_eq!(drifts, reported);
+                assert!(jq.check_schema(0).await.unwrap().is_empty());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn undecodable_policies() {
+            for policy in [
+                UndecodablePolicy::DeadLetter,
+                UndecodablePolicy::Quarantine,
+                UndecodablePolicy::Drop,
+            ] {
+                let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                    .unwrap()
+                    .notification_handler(notification_handler)
+                    .undecodable_policy(policy)
+                    .build();
+
+                // Start queue
+                jq.start().unwrap();
+
+                let job = Job::new(LegacyRoutines::LegacyRename).unwrap();
+                let job_id = job.id();
+
+                let applied = Arc::new(Mutex::new(Vec::new()));
+                let received = applied.clone();
+                jq.subscribe(NotificationFilter::all().job(job_id), move |n| {
+                    if let Notification::JobUndecodable(_, policy) = n {
+                        received.lock().unwrap().push(*policy);
+                    }
+                })
+                .unwrap();
+
+                Runtime::new().unwrap().block_on(async {
+                    jq.enqueue(job).unwrap();
+
+                    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                    // Verify that the job has not been run but handled by the policy
+                    assert_eq!(*applied.lock().unwrap(), vec![policy]);
+
+                    let dead_letters: Vec<Uuid> =
+                        jq.dead_letters().unwrap().iter().map(Job::id).collect();
+                    let status = jq.job_status(&job_id).await;
+
+                    match policy {
+                        UndecodablePolicy::DeadLetter => {
+                            assert_eq!(dead_letters, vec![job_id]);
+                            assert!(status.is_err());
+                        }
+
+                        UndecodablePolicy::Quarantine => {
+                            assert_eq!(jq.quarantined_jobs().unwrap(), vec![job_id]);
+                            assert_eq!(status.unwrap(), Status::Ready);
+                        }
+
+                        _ => {
+                            assert!(dead_letters.is_empty());
+                            assert!(status.is_err());
+                        }
+                    }
+
+                    // Stop the job queue
+                    jq.stop().unwrap();
+                });
+
+                jq.join().unwrap();
+            }
+        }
+    }
+
+    mod subscription {
+        use super::*;
+        use std::sync::Arc;
+
+        #[test]
+        fn filtered_subscribers() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            let by_kind = Arc::new(Mutex::new(Vec::new()));
+            let by_job = Arc::new(Mutex::new(Vec::new()));
+            let by_type = Arc::new(Mutex::new(Vec::new()));
+
+            // Start queue
+            jq.start().unwrap();
+
+            let job = Job::new(Routines::Record(RecordArgs {
+                label: "SUBSCRIPTION".to_string(),
+            }))
+            .unwrap();
+            let job_id = job.id();
+
+            let received = by_kind.clone();
+            jq.subscribe(NotificationFilter::all().kind("Record"), move |n| {
+                received.lock().unwrap().push(n.job_id())
+            })
+            .unwrap();
+
+            let received = by_job.clone();
+            let job_subscription = jq
+                .subscribe(NotificationFilter::all().job(job_id), move |n| {
+                    received.lock().unwrap().push(n.notification_type())
+                })
+                .unwrap();
+
+            let received = by_type.clone();
+            jq.subscribe(
+                NotificationFilter::all()
+                    .job(job_id)
+                    .notification_type(NotificationType::Status),
+                move |n| {
+                    if let Notification::Status(_, status) = n {
+                        received.lock().unwrap().push(*status);
+                    }
+                },
+            )
+            .unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                // Verify that every subscriber only received its notifications
+                let kinds = by_kind.lock().unwrap().clone();
+                assert!(!kinds.is_empty());
+                assert!(kinds.iter().all(|id| *id == Some(job_id)));
+
+                let types = by_job.lock().unwrap().clone();
+                assert!(!types.is_empty());
+                assert!(types.iter().all(|t| *t == NotificationType::Status));
+
+                assert_eq!(
+                    by_type.lock().unwrap().last(),
+                    Some(&Status::Finished(ResultStatus::Success))
+                );
+
+                // Nothing delivered once unsubscribed
+                assert!(jq.unsubscribe(job_subscription).unwrap());
+                assert!(!jq.unsubscribe(job_subscription).unwrap());
+
+                jq.remove_job(&job_id).await.unwrap();
+                assert_eq!(by_job.lock().unwrap().len(), types.len());
+                assert_eq!(by_type.lock().unwrap().last(), Some(&Status::Removed));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod distributed {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::collections::HashSet;
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tokio::sync::Mutex as AsyncMutex;
+
+        #[test]
+        fn workers_sharing_backend() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let worker = |worker_id: &str| {
+                JobQueueBuilder::<Routines, Context>::new()
+                    .unwrap()
+                    .notification_handler(notification_handler)
+                    .shared_backend(backend.clone())
+                    .worker_id(worker_id)
+                    .distributed(Duration::from_millis(20))
+                    .max_in_flight(1)
+                    .build()
+            };
+
+            let mut jq_a = worker("worker-a");
+            let mut jq_b = worker("worker-b");
+            assert_eq!(jq_b.worker_id(), "worker-b");
+
+            // Start queues
+            jq_a.start().unwrap();
+            jq_b.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Every job enqueued on the first worker
+                let mut job_ids = vec![];
+
+                for _ in 0..4 {
+                    let routine = Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(50),
+                    });
+
+                    job_ids.push(jq_a.enqueue(Job::new(routine).unwrap()).unwrap());
+                }
+
+                tokio::time::sleep(Duration::from_millis(600)).await;
+
+                // Verify that the jobs have been spread across both workers, each run once
+                let mut workers = HashSet::new();
+
+                for job_id in job_ids {
+                    let job = jq_b.job(&job_id).await.unwrap();
+
+                    assert_eq!(job.status(), Status::Finished(ResultStatus::Success));
+                    assert!(job.heartbeat().is_some());
+                    workers.insert(job.worker().unwrap().to_owned());
+                }
+
+                assert_eq!(workers.len(), 2);
+
+                // Stop the job queues
+                jq_a.stop().unwrap();
+                jq_b.stop().unwrap();
+            });
+
+            jq_a.join().unwrap();
+            jq_b.join().unwrap();
+        }
+
+        #[test]
+        fn job_leases() {
+            let mut backend = MemoryBackend::new();
+            let backend: &mut dyn Backend<Routines, Context> = &mut backend;
+            let lease = Duration::from_millis(50);
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            backend.schedule(job).unwrap();
+            backend.set_status(&job_id, Status::Ready).unwrap();
+
+            // Leased to a single worker
+            let job = backend
+                .claim(
+                    "worker-a",
+                    lease,
+                    &KindFilter::All,
+                    &Capabilities::default(),
+                )
+                .unwrap()
+                .unwrap();
+            assert_eq!(job.id(), job_id);
+            assert_eq!(job.worker(), Some("worker-a"));
+            assert!(backend
+                .claim(
+                    "worker-b",
+                    lease,
+                    &KindFilter::All,
+                    &Capabilities::default()
+                )
+                .unwrap()
+                .is_none());
+            assert!(!backend.extend_lease(&job_id, "worker-b", lease).unwrap());
+            assert!(backend.extend_lease(&job_id, "worker-a", lease).unwrap());
+
+            // Visibility timeout: claimed again once the lease expired, even while running
+            backend.set_status(&job_id, Status::Running).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+
+            let job = backend
+                .claim(
+                    "worker-b",
+                    lease,
+                    &KindFilter::All,
+                    &Capabilities::default(),
+                )
+                .unwrap()
+                .unwrap();
+            assert_eq!(job.status(), Status::Ready);
+            assert_eq!(job.worker(), Some("worker-b"));
+            assert!(!backend.extend_lease(&job_id, "worker-a", lease).unwrap());
+
+            // Released, the job can be claimed right away
+            assert!(!backend.release(&job_id, "worker-a").unwrap());
+            assert!(backend.release(&job_id, "worker-b").unwrap());
+            assert_eq!(backend.status(&job_id).unwrap(), Status::Ready);
+
+            let job = backend
+                .claim(
+                    "worker-a",
+                    Duration::from_secs(60),
+                    &KindFilter::All,
+                    &Capabilities::default(),
+                )
+                .unwrap()
+                .unwrap();
+            assert_eq!(job.worker(), Some("worker-a"));
+        }
+
+        #[test]
+        fn orphaned_jobs() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let rt = Runtime::new().unwrap();
+
+            // Jobs claimed and started by a worker that died right after
+            let orphan = |label: &str| {
+                let job = Job::new(Routines::Record(RecordArgs {
+                    label: label.to_string(),
+                }))
+                .unwrap();
+                let job_id = job.id();
+
+                rt.block_on(async {
+                    let mut backend = backend.lock().await;
+
+                    backend.schedule(job).unwrap();
+                    backend.set_status(&job_id, Status::Ready).unwrap();
+                    assert!(backend
+                        .claim(
+                            "dead-worker",
+                            Duration::from_secs(60),
+                            &KindFilter::All,
+                            &Capabilities::default()
+                        )
+                        .unwrap()
+                        .is_some());
+                    backend.set_status(&job_id, Status::Running).unwrap();
+                });
+
+                job_id
+            };
+
+            let interrupted = orphan("ORPHAN_INTERRUPTED");
+
+            rt.block_on(async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                let mut backend = backend.lock().await;
+                let timeout = Duration::from_millis(50);
+
+                assert_eq!(
+                    backend
+                        .reap_orphans(timeout, OrphanPolicy::Interrupt)
+                        .unwrap(),
+                    vec![interrupted]
+                );
+                assert_eq!(
+                    backend.status(&interrupted).unwrap(),
+                    Status::Finished(ResultStatus::Interrupted)
+                );
+                assert!(backend
+                    .reap_orphans(timeout, OrphanPolicy::Interrupt)
+                    .unwrap()
+                    .is_empty());
+            });
+
+            let requeued = orphan("ORPHAN_REQUEUED");
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .shared_backend(backend.clone())
+                .distributed(Duration::from_millis(20))
+                .orphan_reaper(Duration::from_millis(100), OrphanPolicy::Requeue)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            rt.block_on(async {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+
+                // Verify that the orphaned job has been run again by the live worker
+                assert_eq!(records("ORPHAN_"), vec!["ORPHAN_REQUEUED"]);
+
+                let job = jq.job(&requeued).await.unwrap();
+                assert_eq!(job.status(), Status::Finished(ResultStatus::Success));
+                assert_eq!(job.worker(), Some(jq.worker_id()));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod utilization {
+        use super::*;
+        use std::time::Duration;
+
+        #[test]
+        fn utilization_and_saturation() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(2)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Idle worker
+                let stats = jq.worker_stats().await.unwrap();
+                assert_eq!(stats.worker_id, jq.worker_id());
+                assert_eq!(stats.slots, 2);
+                assert_eq!(stats.busy_slots, 0);
+                assert_eq!(stats.busy_time, Duration::ZERO);
+                assert!(stats.idle_time >= Duration::from_millis(100));
+                assert_eq!(stats.utilization(), 0.0);
+                assert!(!stats.is_saturated());
+
+                // More jobs ready than free slots
+                for _ in 0..3 {
+                    jq.enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_millis(100),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+                }
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                let stats = jq.worker_stats().await.unwrap();
+                assert_eq!(stats.busy_slots, 2);
+                assert_eq!(stats.free_slots(), 0);
+                assert_eq!(stats.ready, 1);
+                assert_eq!(stats.saturation(), 1.5);
+                assert!(stats.is_saturated());
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                // Every slot has been busy part of the time
+                let stats = jq.worker_stats().await.unwrap();
+                assert_eq!(stats.busy_slots, 0);
+                assert_eq!(stats.ready, 0);
+                assert!(stats.busy_time >= Duration::from_millis(300));
+                assert!(stats.utilization() > 0.0 && stats.utilization() < 100.0);
+                assert!(!stats.is_saturated());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn pool_slots() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new_with_pool_size(3)
+                .unwrap()
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Unlimited jobs in flight: the slots are the threads of the pool
+                assert_eq!(jq.worker_stats().await.unwrap().slots, 3);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod timeline {
+        use super::*;
+        use std::time::{Duration, SystemTime};
+
+        #[test]
+        fn job_intervals() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .build();
+
+            let since = SystemTime::now();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let sleep = || {
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(150),
+                    }))
+                    .unwrap()
+                };
+                let first = jq.enqueue(sleep()).unwrap();
+                let second = jq.enqueue(sleep()).unwrap();
+                let other = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // First job running, the others waiting for the slot
+                let range = since..SystemTime::now();
+                let filter = TimelineFilter::all().kind("Sleep");
+                let intervals = jq.timeline(range, &filter).await.unwrap();
+                assert_eq!(intervals.len(), 2);
+                assert_eq!(intervals[0].id, first);
+                assert!(intervals[0].started.is_some());
+                assert!(intervals[0].finished.is_none());
+                assert_eq!(intervals[1].id, second);
+                assert!(intervals[1].running().is_none());
+
+                tokio::time::sleep(Duration::from_millis(400)).await;
+
+                // Both finished, one after the other
+                let range = since..SystemTime::now();
+                let intervals = jq.timeline(range, &filter).await.unwrap();
+                assert!(intervals.iter().all(|interval| interval.finished.is_some()));
+                assert!(intervals[1].started.unwrap() >= intervals[0].finished.unwrap());
+                assert!(intervals[1].queued() >= Duration::from_millis(100));
+
+                let range = since..SystemTime::now();
+                let intervals = jq.timeline(range, &TimelineFilter::all()).await.unwrap();
+                assert!(intervals.iter().any(|interval| interval.id == other));
+
+                // Nothing active before the queue started
+                let range = SystemTime::UNIX_EPOCH..since;
+                assert!(jq.timeline(range, &filter).await.unwrap().is_empty());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod yield_points {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn cooperative_yields() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new_with_pool_size(1)
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Routine that would never release the single thread without yield points
+                let spin = Routines::Spin(SpinArgs {
+                    iterations: u64::MAX,
+                });
+                let spin_id = jq.enqueue(Job::new(spin).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                let job = Job::new(Routines::Record(RecordArgs {
+                    label: "YIELD_FAIR".to_string(),
+                }))
+                .unwrap();
+                let job_id = jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                // Verify that the other job has run meanwhile
+                assert_eq!(records("YIELD_"), vec!["YIELD_FAIR"]);
+                assert_eq!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(jq.job_status(&spin_id).await.unwrap(), Status::Running);
+
+                // And that the spinning job can be cancelled
+                jq.cancel_job(&spin_id).await.unwrap();
+                assert_eq!(
+                    jq.job_status(&spin_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Cancelled)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod exec_class {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[derive(Serialize, Deserialize)]
+        enum Crunch {
+            Sum(u64),
+        }
+
+        impl BlockingRoutine<Context> for Crunch {
+            fn call(
+                &self,
+                _job: &Job,
+                _messages_channel: SharedMessageChannel,
+                _context: Option<Shared<Context>>,
+            ) -> Result<Vec<u8>, Error> {
+                match self {
+                    Self::Sum(count) => Ok((1..=*count).sum::<u64>().to_le_bytes().to_vec()),
+                }
+            }
+        }
+
+        #[test]
+        fn blocking_routine() {
+            let mut jq = JobQueueBuilder::<Crunch, Context>::new_with_pool_size(1)
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = jq.enqueue(Job::new(Crunch::Sum(100)).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(
+                    jq.job_result(&job_id).await.unwrap(),
+                    5050u64.to_le_bytes().to_vec()
+                );
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn blocking_pool() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new_with_pool_size(1)
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Routine that would hold the single worker if run on it
+                let blocking = Routines::BlockingSleep(SleepArgs {
+                    duration: std::time::Duration::from_millis(300),
+                });
+                let blocking_id = jq.enqueue(Job::new(blocking).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                let job = Job::new(Routines::Record(RecordArgs {
+                    label: "EXEC_CLASS_ASYNC".to_string(),
+                }))
+                .unwrap();
+                jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Async job run meanwhile
+                assert_eq!(records("EXEC_CLASS_"), vec!["EXEC_CLASS_ASYNC"]);
+                assert_eq!(jq.job_status(&blocking_id).await.unwrap(), Status::Running);
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                assert_eq!(
+                    jq.job_status(&blocking_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod local {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn from_fn() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .undecodable_policy(UndecodablePolicy::DeadLetter)
+                .context(Context {
+                    name: "UNIT_TESTING".to_string(),
+                })
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = Job::from_fn("greet", |context: Option<Shared<Context>>| async move {
+                    let name = context.unwrap().lock().unwrap().name.clone();
+
+                    Ok(format!("Hello {name}").into_bytes())
+                });
+
+                assert_eq!(job.kind(), "greet");
+                assert!(job.is_local());
+                assert!(matches!(
+                    *job.to_wire().unwrap_err(),
+                    Error::LocalRoutine(_)
+                ));
+
+                let retried = job.retry();
+                let job_id = jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_result(&job_id).await.unwrap(),
+                    b"Hello UNIT_TESTING".to_vec()
+                );
+
+                // The closure only runs once
+                let retried_id = jq.enqueue(retried).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_status(&retried_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod macros {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[cs_job(context = Context)]
+        async fn concat(first: String, second: String) -> Result<Vec<u8>, Error> {
+            Ok(format!("{first}{second}").into_bytes())
+        }
+
+        #[cs_job(context = Context)]
+        async fn greet(
+            context: Option<Shared<Context>>,
+            greeting: String,
+        ) -> Result<Vec<u8>, Error> {
+            let name = context.unwrap().lock().unwrap().name.clone();
+
+            Ok(format!("{greeting} {name}").into_bytes())
+        }
+
+        #[derive(Serialize, Deserialize)]
+        enum Jobs {
+            Concat(ConcatArgs),
+            Greet(GreetArgs),
+        }
+
+        impl From<ConcatArgs> for Jobs {
+            fn from(args: ConcatArgs) -> Self {
+                Self::Concat(args)
+            }
+        }
+
+        impl From<GreetArgs> for Jobs {
+            fn from(args: GreetArgs) -> Self {
+                Self::Greet(args)
+            }
+        }
+
+        #[async_trait]
+        impl Routine<Context> for Jobs {
+            async fn call(
+                &self,
+                job: &Job,
+                messages_channel: SharedMessageChannel,
+                context: Option<Shared<Context>>,
+            ) -> Result<Vec<u8>, Error> {
+                match self {
+                    Self::Concat(args) => args.call(job, messages_channel, context).await,
+                    Self::Greet(args) => args.call(job, messages_channel, context).await,
+                }
+            }
+        }
+
+        #[test]
+        fn cs_job() {
+            let mut jq = JobQueueBuilder::<Jobs, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .context(Context {
+                    name: "UNIT_TESTING".to_string(),
+                })
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let concat_id = enqueue_concat(&jq, "cs_".to_owned(), "jobs".to_owned()).unwrap();
+                let greet_id = enqueue_greet(&jq, "Hello".to_owned()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_result(&concat_id).await.unwrap(),
+                    b"cs_jobs".to_vec()
+                );
+                assert_eq!(
+                    jq.job_result(&greet_id).await.unwrap(),
+                    b"Hello UNIT_TESTING".to_vec()
+                );
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod registry {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[cs_job(context = Context)]
+        async fn shout(text: String) -> Result<Vec<u8>, Error> {
+            Ok(text.to_uppercase().into_bytes())
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct Repeat {
+            text: String,
+            times: usize,
+        }
+
+        #[async_trait]
+        impl Routine<Context> for Repeat {
+            async fn call(
+                &self,
+                _job: &Job,
+                _messages_channel: SharedMessageChannel,
+                _context: Option<Shared<Context>>,
+            ) -> Result<Vec<u8>, Error> {
+                Ok(self.text.repeat(self.times).into_bytes())
+            }
+        }
+
+        #[test]
+        fn registered_routines() {
+            let mut registry = RoutineRegistry::<Context>::new();
+
+            register_shout(&mut registry)
+                .unwrap()
+                .register::<Repeat>("repeat")
+                .unwrap();
+
+            assert!(matches!(
+                *registry.register::<Repeat>("shout").unwrap_err(),
+                Error::RoutineAlreadyRegistered(_)
+            ));
+            assert_eq!(registry.names(), vec!["repeat", "shout"]);
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .registry(registry)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let shout = Job::registered(
+                    "shout",
+                    &ShoutArgs {
+                        text: "hello".to_owned(),
+                    },
+                )
+                .unwrap();
+
+                assert_eq!(shout.kind(), "shout");
+                assert!(shout.is_registered());
+
+                let repeat = Job::registered(
+                    "repeat",
+                    &Repeat {
+                        text: "ab".to_owned(),
+                        times: 3,
+                    },
+                )
+                .unwrap();
+
+                // Unknown to this binary
+                let resize =
+                    Job::registered("resize", &serde_json::json!({ "width": 640 })).unwrap();
+
+                let shout_id = jq.enqueue(shout).unwrap();
+                let repeat_id = jq.enqueue(repeat).unwrap();
+                let resize_id = jq.enqueue(resize).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(jq.job_result(&shout_id).await.unwrap(), b"HELLO".to_vec());
+                assert_eq!(jq.job_result(&repeat_id).await.unwrap(), b"ababab".to_vec());
+                assert_eq!(jq.job_status(&resize_id).await.unwrap(), Status::Ready);
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod migration {
+        use super::*;
+        use serde_json::json;
+        use tokio::time::Duration;
+
+        fn stored_job(routine: Value, version: u32) -> Job {
+            let mut job = serde_json::to_value(Job::new(Routines::Nop).unwrap()).unwrap();
+            let kind = routine.as_object().unwrap().keys().next().unwrap().clone();
+
+            job["kind"] = kind.into();
+            job["routine"] = routine;
+            job["routine_version"] = version.into();
+
+            serde_json::from_value(job).unwrap()
+        }
+
+        fn migrate(kind: &str, version: u32, routine: Value) -> Result<Value, ApiError> {
+            match (kind, version) {
+                // Variant renamed
+                ("Log", 0) => Ok(json!({ "Record": { "label": routine["Log"]["message"] } })),
+
+                // Label suffixed since version 1
+                ("Record", 0) => {
+                    let label = routine["Record"]["label"].as_str().unwrap_or_default();
+
+                    Ok(json!({ "Record": { "label": format!("{label}_V1") } }))
+                }
+
+                _ => Err(Box::new(Error::Custom(format!(
+                    "No migration for {kind} v{version}"
+                )))),
+            }
+        }
+
+        #[test]
+        fn migrate_stored_routines() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .migrator(migrate)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let current = Job::new(Routines::Record(RecordArgs {
+                    label: "MIGRATION_CURRENT".to_owned(),
+                }))
+                .unwrap();
+
+                assert_eq!(current.routine_version(), 1);
+
+                let renamed = stored_job(json!({ "Log": { "message": "MIGRATION_RENAMED" } }), 0);
+                let outdated =
+                    stored_job(json!({ "Record": { "label": "MIGRATION_OUTDATED" } }), 0);
+                let unknown = stored_job(json!({ "Shred": {} }), 0);
+
+                jq.enqueue(current).unwrap();
+                jq.enqueue(renamed).unwrap();
+                jq.enqueue(outdated).unwrap();
+
+                let unknown_id = jq.enqueue(unknown).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                let mut migrated = records("MIGRATION_");
+
+                migrated.sort();
+
+                assert_eq!(
+                    migrated,
+                    vec![
+                        "MIGRATION_CURRENT",
+                        "MIGRATION_OUTDATED_V1",
+                        "MIGRATION_RENAMED"
+                    ]
+                );
+                assert_eq!(
+                    jq.job_status(&unknown_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod codec {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use crate::test_util::*;
+
+        /// JSON documents behind a magic number, standing for a binary format.
+        struct MagicCodec;
+
+        impl Codec for MagicCodec {
+            fn name(&self) -> &str {
+                "magic"
+            }
+
+            fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+                Ok([b"CSJ1".to_vec(), JsonCodec.encode(job)?].concat())
+            }
+
+            fn decode(&self, bytes: &[u8]) -> Result<Job, ApiError> {
+                match bytes.strip_prefix(b"CSJ1") {
+                    Some(bytes) => JsonCodec.decode(bytes),
+                    None => Err(Box::new(Error::Custom("Missing magic number".to_owned()))),
+                }
+            }
+        }
+
+        #[test]
+        fn encode_records() {
+            let mut job = Job::new(Routines::Record(RecordArgs {
+                label: "CODEC".to_owned(),
+            }))
+            .unwrap();
+
+            job.set_private_data(PrivateData { value: 3 }).unwrap();
+
+            // Routine and private data embedded as structured values
+            let record: Value = serde_json::from_slice(&job.encode(&JsonCodec).unwrap()).unwrap();
+
+            assert_eq!(record["routine"]["Record"]["label"], "CODEC");
+            assert_eq!(record["private_data"]["value"], 3);
+
+            for codec in [&JsonCodec as &dyn Codec, &MagicCodec] {
+                let bytes = job.encode(codec).unwrap();
+
+                assert!(Job::decode(&bytes, codec).unwrap() == job);
+            }
+
+            let nop = Job::new(Routines::Nop).unwrap();
+            let bytes = nop.encode(&MagicCodec).unwrap();
+
+            assert!(Job::decode(&bytes, &MagicCodec).unwrap() == nop);
+            assert!(Job::decode(&bytes, &JsonCodec).is_err());
+        }
+
+        #[cfg(any(feature = "bincode", feature = "msgpack", feature = "cbor"))]
+        #[test]
+        fn binary_codecs() {
+            let mut job = Job::new(Routines::Record(RecordArgs {
+                label: "CODEC".to_owned(),
+            }))
+            .unwrap();
+
+            job.set_private_data(PrivateData { value: 3 }).unwrap();
+            job.insert_named_data("region", "eu-west").unwrap();
+            job.set_metadata("attempt", serde_json::json!({"count": 2}));
+            job.set_result(b"EMISSIONS".repeat(10)).unwrap();
+
+            let codecs: Vec<Box<dyn Codec>> = vec![
+                #[cfg(feature = "bincode")]
+                Box::new(BincodeCodec),
+                #[cfg(feature = "msgpack")]
+                Box::new(MessagePackCodec),
+                #[cfg(feature = "cbor")]
+                Box::new(CborCodec),
+            ];
+
+            /// Cipher flipping the bits, standing for a real one.
+            struct Flip;
+
+            impl Encryptor for Flip {
+                fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, ApiError> {
+                    Ok(plaintext.iter().map(|byte| !byte).collect())
+                }
+
+                fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ApiError> {
+                    self.encrypt(ciphertext)
+                }
+            }
+
+            // Entries encrypted as well
+            let compression = Compression::default();
+            let encryption = Encryption::new("k1", Flip);
+
+            for codec in codecs {
+                let bytes = job.encode(codec.as_ref()).unwrap();
+                assert!(Job::decode(&bytes, codec.as_ref()).unwrap() == job);
+
+                let bytes = job
+                    .encode_with(codec.as_ref(), &compression, &encryption)
+                    .unwrap();
+                assert!(
+                    Job::decode_with(&bytes, codec.as_ref(), &compression, &encryption).unwrap()
+                        == job
+                );
+            }
+        }
+
+        #[test]
+        fn codec_forwarded_to_wal() {
+            let path =
+                std::env::temp_dir().join(format!("cs-jobs-encoded-{}.wal", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            // Log opened with JSON, then the codec set by the queue
+            let mut memory = MemoryBackend::new()
+                .wal(Wal::open(&path).unwrap().compact_every(0))
+                .unwrap();
+            let backend: &mut dyn Backend<Routines, Context> = &mut memory;
+
+            backend.schedule(job).unwrap();
+            backend.set_codec(std::sync::Arc::new(MagicCodec));
+            backend.set_status(&job_id, Status::Ready).unwrap();
+            drop(memory);
+
+            // Rewritten with the codec on the next change
+            let bytes = std::fs::read(&path).unwrap();
+            assert!(bytes.windows(4).any(|window| window == b"CSJ1"));
+
+            let mut memory = MemoryBackend::new()
+                .wal(Wal::open(&path).unwrap().codec(MagicCodec))
+                .unwrap();
+            let backend: &mut dyn Backend<Routines, Context> = &mut memory;
+
+            assert_eq!(backend.jobs().unwrap()[0].status(), Status::Ready);
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        #[test]
+        fn codec_set_on_backend() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .backend(backend)
+                .codec(MagicCodec)
+                .build();
+
+            jq.start().unwrap();
+
+            assert!(recorder
+                .calls()
+                .contains(&BackendCall::SetCodec("magic".to_owned())));
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod compression {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+
+        /// Run-length encoding, as (count, byte) pairs.
+        struct Rle;
+
+        impl Compressor for Rle {
+            fn name(&self) -> &str {
+                "rle"
+            }
+
+            fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+                let mut compressed = vec![];
+
+                for byte in bytes {
+                    match compressed.len() {
+                        len if len > 0
+                            && compressed[len - 1] == *byte
+                            && compressed[len - 2] < u8::MAX =>
+                        {
+                            compressed[len - 2] += 1
+                        }
+                        _ => compressed.extend([1, *byte]),
+                    }
+                }
+
+                Ok(compressed)
+            }
+
+            fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+                Ok(bytes
+                    .chunks(2)
+                    .flat_map(|pair| std::iter::repeat(pair[1]).take(pair[0] as usize))
+                    .collect())
+            }
+        }
+
+        /// Compressor storing the bytes as is.
+        struct Identity;
+
+        impl Compressor for Identity {
+            fn name(&self) -> &str {
+                "identity"
+            }
+
+            fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+                Ok(bytes.to_vec())
+            }
+
+            fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+                Ok(bytes.to_vec())
+            }
+        }
+
+        #[test]
+        fn compress_large_entries() {
+            let mut job = Job::new(Routines::Record(RecordArgs {
+                label: "x".repeat(2_000),
+            }))
+            .unwrap();
+
+            job.set_private_data(PrivateData { value: 3 }).unwrap();
+            job.set_result(vec![b'a'; 10_000]).unwrap();
+
+            let compression = Compression::new(Rle, 1_024);
+            let bytes = job
+                .encode_with(&JsonCodec, &compression, &Encryption::default())
+                .unwrap();
+            let record: Value = serde_json::from_slice(&bytes).unwrap();
+
+            // Small entries kept as structured values
+            assert_eq!(record["packed"]["routine"]["compressor"], "rle");
+            assert_eq!(record["packed"]["result"]["compressor"], "rle");
+            assert!(record["packed"].get("private_data").is_none());
+            assert_eq!(record["private_data"]["value"], 3);
+            assert!(bytes.len() < job.encode(&JsonCodec).unwrap().len() / 10);
+
+            assert!(
+                Job::decode_with(&bytes, &JsonCodec, &compression, &Encryption::default()).unwrap()
+                    == job
+            );
+
+            // Entries compressed with a previous setting
+            let mixed = Compression::new(Identity, 1_024).reader(Rle);
+
+            assert!(
+                Job::decode_with(&bytes, &JsonCodec, &mixed, &Encryption::default()).unwrap()
+                    == job
+            );
+            assert!(matches!(
+                Job::decode(&bytes, &JsonCodec).map_err(|e| *e),
+                Err(Error::UnknownCompressor(_))
+            ));
+        }
+
+        #[test]
+        fn compression_forwarded_to_wal() {
+            let path =
+                std::env::temp_dir().join(format!("cs-jobs-compressed-{}.wal", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+
+            let job = Job::new(Routines::Record(RecordArgs {
+                label: "a".repeat(10_000),
+            }))
+            .unwrap();
+            let job_id = job.id();
+
+            // Log opened without compression, then set by the queue
+            let mut memory = MemoryBackend::new()
+                .wal(Wal::open(&path).unwrap().compact_every(0))
+                .unwrap();
+            let backend: &mut dyn Backend<Routines, Context> = &mut memory;
+
+            backend.schedule(job).unwrap();
+            let size = std::fs::metadata(&path).unwrap().len();
+
+            backend.set_compression(Compression::new(Rle, 1_024));
+            backend.set_status(&job_id, Status::Ready).unwrap();
+            drop(memory);
+
+            // Rewritten with the compression on the next change
+            assert!(std::fs::metadata(&path).unwrap().len() < size / 4);
+
+            let mut memory = MemoryBackend::new()
+                .wal(
+                    Wal::open(&path)
+                        .unwrap()
+                        .compression(Compression::new(Rle, 1_024)),
+                )
+                .unwrap();
+            let backend: &mut dyn Backend<Routines, Context> = &mut memory;
+
+            assert_eq!(backend.jobs().unwrap()[0].status(), Status::Ready);
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        #[cfg(any(feature = "zstd", feature = "gzip"))]
+        #[test]
+        fn bundled_compressors() {
+            let mut job = Job::new(Routines::Nop).unwrap();
+            job.set_result(b"EMISSIONS".repeat(1_000)).unwrap();
+
+            let mut compressors: Vec<(Compression, &str)> = vec![];
+
+            #[cfg(feature = "zstd")]
+            compressors.push((Compression::new(ZstdCompressor::default(), 1_024), "zstd"));
+
+            #[cfg(feature = "gzip")]
+            compressors.push((Compression::new(GzipCompressor::new(9), 1_024), "gzip"));
+
+            for (compression, name) in compressors {
+                let bytes = job
+                    .encode_with(&JsonCodec, &compression, &Encryption::default())
+                    .unwrap();
+                let record: Value = serde_json::from_slice(&bytes).unwrap();
+
+                assert_eq!(record["packed"]["result"]["compressor"], name);
+                assert!(bytes.len() < job.encode(&JsonCodec).unwrap().len() / 10);
+                assert!(
+                    Job::decode_with(&bytes, &JsonCodec, &compression, &Encryption::default())
+                        .unwrap()
+                        == job
+                );
+            }
+        }
+    }
+
+    mod encryption {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use crate::test_util::*;
+
+        /// Cipher XOR-ing the bytes with a key, standing for a real one.
+        struct Xor(u8);
+
+        impl Encryptor for Xor {
+            fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, ApiError> {
+                Ok(plaintext.iter().map(|byte| byte ^ self.0).collect())
+            }
+
+            fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, ApiError> {
+                self.encrypt(ciphertext)
+            }
+        }
+
+        /// Compressor storing the bytes reversed.
+        struct Reverse;
+
+        impl Compressor for Reverse {
+            fn name(&self) -> &str {
+                "reverse"
+            }
+
+            fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+                Ok(bytes.iter().rev().copied().collect())
+            }
+
+            fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, ApiError> {
+                self.compress(bytes)
+            }
+        }
+
+        #[test]
+        fn encrypt_at_rest() {
+            let mut job = Job::new(Routines::Nop).unwrap();
+
+            job.set_private_data(PrivateData { value: 42 }).unwrap();
+            job.insert_named_data("token", "SECRET").unwrap();
+            job.set_result(b"EMISSIONS".repeat(10)).unwrap();
+
+            let compression = Compression::new(Reverse, 64);
+            let encryption = Encryption::new("k1", Xor(0x5a));
+
+            let bytes = job
+                .encode_with(&JsonCodec, &compression, &encryption)
+                .unwrap();
+            let record: Value = serde_json::from_slice(&bytes).unwrap();
+
+            // No plaintext persisted, the large result being compressed first
+            assert!(record["private_data"].is_null());
+            assert_eq!(record["sealed"]["private_data"]["key_id"], "k1");
+            assert!(record["data"]["token"].is_null());
+            assert_eq!(record["sealed"]["data.token"]["key_id"], "k1");
+            assert!(!String::from_utf8_lossy(&bytes).contains("SECRET"));
+            assert_eq!(record["sealed"]["result"]["compressor"], "reverse");
+            assert!(!String::from_utf8_lossy(&bytes).contains("EMISSIONS"));
+
+            assert!(
+                Job::decode_with(&bytes, &JsonCodec, &compression, &encryption).unwrap() == job
+            );
+
+            // Key rotated, the retired one kept to read the records
+            let rotated = Encryption::new("k2", Xor(0x33)).key("k1", Xor(0x5a));
+
+            assert!(Job::decode_with(&bytes, &JsonCodec, &compression, &rotated).unwrap() == job);
+            assert!(matches!(
+                Job::decode_with(
+                    &bytes,
+                    &JsonCodec,
+                    &compression,
+                    &Encryption::new("k2", Xor(0x33))
+                )
+                .map_err(|e| *e),
+                Err(Error::UnknownEncryptionKey(_))
+            ));
+
+            let bytes = job.encode_with(&JsonCodec, &compression, &rotated).unwrap();
+            let record: Value = serde_json::from_slice(&bytes).unwrap();
+
+            assert_eq!(record["sealed"]["result"]["key_id"], "k2");
+        }
+
+        #[test]
+        fn encryption_set_on_backend() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .backend(backend)
+                .encryption(Encryption::new("k1", Xor(0x5a)))
+                .build();
+
+            jq.start().unwrap();
+
+            assert!(recorder.calls().contains(&BackendCall::SetEncryption));
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn encryption_forwarded_to_wal() {
+            let path =
+                std::env::temp_dir().join(format!("cs-jobs-encrypted-{}.wal", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+
+            let mut job = Job::new(Routines::Nop).unwrap();
+            job.insert_named_data("token", "SECRET").unwrap();
+            let job_id = job.id();
+
+            // Log opened without encryption, then set by the queue
+            let mut memory = MemoryBackend::new()
+                .wal(Wal::open(&path).unwrap().compact_every(0))
+                .unwrap();
+            let backend: &mut dyn Backend<Routines, Context> = &mut memory;
+
+            backend.schedule(job).unwrap();
+            backend.set_encryption(Encryption::new("k1", Xor(0x5a)));
+            backend.set_status(&job_id, Status::Ready).unwrap();
+            drop(memory);
+
+            // Rewritten with the encryption on the next change
+            assert!(!String::from_utf8_lossy(&std::fs::read(&path).unwrap()).contains("SECRET"));
+
+            let mut memory = MemoryBackend::new()
+                .wal(
+                    Wal::open(&path)
+                        .unwrap()
+                        .encryption(Encryption::new("k1", Xor(0x5a))),
+                )
+                .unwrap();
+            let backend: &mut dyn Backend<Routines, Context> = &mut memory;
+
+            let jobs = backend.jobs().unwrap();
+            assert_eq!(jobs[0].id(), job_id);
+            assert_eq!(jobs[0].get_named_data::<String>("token").unwrap(), "SECRET");
+
+            std::fs::remove_file(&path).unwrap();
+        }
+
+        #[cfg(feature = "aes-gcm")]
+        #[test]
+        fn aes_gcm() {
+            let mut job = Job::new(Routines::Nop).unwrap();
+            job.insert_named_data("token", "SECRET").unwrap();
+
+            let encryption = Encryption::new("k1", AesGcmEncryptor::new(&[7; 32]));
+            let bytes = job
+                .encode_with(&JsonCodec, &Compression::default(), &encryption)
+                .unwrap();
+
+            assert!(!String::from_utf8_lossy(&bytes).contains("SECRET"));
+            assert!(
+                Job::decode_with(&bytes, &JsonCodec, &Compression::default(), &encryption).unwrap()
+                    == job
+            );
+
+            // Random nonce for every entry
+            let cipher = AesGcmEncryptor::new(&[7; 32]);
+            assert_ne!(
+                cipher.encrypt(b"SECRET").unwrap(),
+                cipher.encrypt(b"SECRET").unwrap()
+            );
+
+            // Tampered entries and other keys rejected
+            let mut ciphertext = cipher.encrypt(b"SECRET").unwrap();
+            assert_eq!(cipher.decrypt(&ciphertext).unwrap(), b"SECRET");
+            assert!(matches!(
+                AesGcmEncryptor::new(&[8; 32])
+                    .decrypt(&ciphertext)
+                    .map_err(|e| *e),
+                Err(Error::Decryption(_))
+            ));
+
+            *ciphertext.last_mut().unwrap() ^= 1;
+            assert!(matches!(
+                cipher.decrypt(&ciphertext).map_err(|e| *e),
+                Err(Error::Decryption(_))
+            ));
+            assert!(cipher.decrypt(&[0; 4]).is_err());
+        }
+    }
+
+    mod result_store {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn offload_large_results() {
+            let dir = std::env::temp_dir().join(format!("cs-jobs-results-{}", std::process::id()));
+
+            let clock = ManualClock::default();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .clock(clock.clone())
+                .sweep_strategy(SweepStrategy::Lazy)
+                .result_offload(ResultOffload::new(FsResultStore::new(&dir).unwrap(), 1024))
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let large = Job::from_fn("large", |_: Option<Shared<Context>>| async move {
+                    Ok(vec![7; 4096])
+                });
+                let small = Job::from_fn("small", |_: Option<Shared<Context>>| async move {
+                    Ok(vec![1; 16])
+                });
+
+                let large_id = jq.enqueue(large).unwrap();
+                let small_id = jq.enqueue(small).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Only the large result written to the store
+                assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+                assert_eq!(jq.peek_result(&large_id).await.unwrap(), vec![7; 4096]);
+
+                assert_eq!(jq.job_result(&large_id).await.unwrap(), vec![7; 4096]);
+                assert_eq!(jq.job_result(&small_id).await.unwrap(), vec![1; 16]);
+
+                // Removed from the store along with the job, once its grace period is over
+                clock.advance(Duration::from_secs(61));
+                assert!(jq.job_status(&large_id).await.is_err());
+                assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    mod memory_limits {
+        use super::*;
+        use crate::memory_backend::{MemoryBackend, MemoryLimits};
+
+        type MemBackend = dyn Backend<Routines, Context>;
+
+        /// Stores a job, finished with a result if given.
+        fn store(backend: &mut MemBackend, result: Option<Vec<u8>>) -> Result<Uuid, ApiError> {
+            let job = Job::new(Routines::Nop).unwrap();
+            let id = job.id();
+
+            backend.schedule(job)?;
+
+            if let Some(result) = result {
+                backend.set_status(&id, Status::Ready)?;
+                backend.set_status(&id, Status::Running)?;
+                backend.set_result(&id, result)?;
+                backend.set_status(&id, Status::Finished(ResultStatus::Success))?;
+            }
+
+            Ok(id)
+        }
+
+        #[test]
+        fn evict_least_recently_used() {
+            let limits = MemoryLimits::default().max_jobs(3).max_result_bytes(100);
+            let mut memory = MemoryBackend::with_limits(limits);
+            let backend: &mut MemBackend = &mut memory;
+
+            let first = store(backend, Some(vec![0; 40])).unwrap();
+            let second = store(backend, Some(vec![])).unwrap();
+            let third = store(backend, Some(vec![0; 40])).unwrap();
+
+            // The first job used since, the second one evicted to free a slot
+            backend.status(&first).unwrap();
+
+            let fourth = store(backend, None).unwrap();
+
+            assert!(backend.status(&second).is_err());
+            assert_eq!(backend.jobs().unwrap().len(), 3);
+
+            // Results only evicted for their size, the least recently used first
+            backend.set_status(&fourth, Status::Ready).unwrap();
+            backend.set_status(&fourth, Status::Running).unwrap();
+            backend.set_result(&fourth, vec![0; 40]).unwrap();
+
+            assert!(backend.status(&first).is_ok());
+            assert!(backend.status(&third).is_err());
+            assert_eq!(memory.result_bytes(), 80);
+        }
+
+        #[test]
+        fn unfinished_jobs_never_evicted() {
+            let mut memory = MemoryBackend::with_limits(MemoryLimits::default().max_jobs(2));
+            let backend: &mut MemBackend = &mut memory;
+
+            store(backend, None).unwrap();
+            store(backend, None).unwrap();
+
+            assert!(matches!(
+                store(backend, None).map_err(|e| *e),
+                Err(Error::CapacityExceeded(_))
+            ));
+            assert_eq!(backend.jobs().unwrap().len(), 2);
+        }
+    }
+
+    mod wal {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::io::Write;
+
+        type MemBackend = dyn Backend<Routines, Context>;
+
+        #[test]
+        fn replay_write_ahead_log() {
+            let path = std::env::temp_dir().join(format!("cs-jobs-{}.wal", std::process::id()));
+            let _ = std::fs::remove_file(&path);
+
+            let mut memory = MemoryBackend::new()
+                .wal(Wal::open(&path).unwrap().compact_every(0))
+                .unwrap();
+            let backend: &mut MemBackend = &mut memory;
+
+            let pending = Job::new(Routines::Nop).unwrap();
+            let finished = Job::new(Routines::Nop).unwrap();
+            let removed = Job::new(Routines::Nop).unwrap();
+            let (pending_id, finished_id, removed_id) = (pending.id(), finished.id(), removed.id());
+
+            for job in [pending, finished, removed] {
+                backend.schedule(job).unwrap();
+            }
+
+            for id in [finished_id, removed_id] {
+                backend.set_status(&id, Status::Ready).unwrap();
+                backend.set_status(&id, Status::Running).unwrap();
+                backend.set_result(&id, b"42".to_vec()).unwrap();
+                backend
+                    .set_status(&id, Status::Finished(ResultStatus::Success))
+                    .unwrap();
+            }
+
+            backend.remove(&removed_id).unwrap();
+            backend.set_paused(true).unwrap();
+
+            let jobs = backend.jobs().unwrap();
+            drop(memory);
+
+            // Record torn by a crash while appending
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .unwrap();
+            file.write_all(&[0, 255, 255, 0, 0, 1]).unwrap();
+            drop(file);
+
+            let size = std::fs::metadata(&path).unwrap().len();
+            let mut memory = MemoryBackend::new().wal(Wal::open(&path).unwrap()).unwrap();
+            let backend: &mut MemBackend = &mut memory;
+
+            assert!(backend.jobs().unwrap() == jobs);
+            assert!(backend.is_paused().unwrap());
+            assert_eq!(backend.status(&pending_id).unwrap(), Status::NotReady);
+            assert_eq!(backend.result(&finished_id).unwrap(), b"42");
+            assert!(backend.status(&removed_id).is_err());
+
+            // Compacted on replay
+            assert!(std::fs::metadata(&path).unwrap().len() < size);
+
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    mod job_statuses {
+        use super::*;
+        use crate::test_util::*;
+
+        #[test]
+        fn batch_status_query() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .backend(backend)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let mut ids: Vec<Uuid> = (0..5)
+                    .map(|_| jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap())
+                    .collect();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+                // Unknown jobs left out
+                let unknown = Job::new(Routines::Nop).unwrap().id();
+                ids.push(unknown);
+
+                let calls = recorder.calls().len();
+                let statuses = jq.job_statuses(&ids).await.unwrap();
+
+                assert_eq!(statuses.len(), 5);
+                assert!(!statuses.contains_key(&unknown));
+                assert!(statuses
+                    .values()
+                    .all(|status| *status == Status::Finished(ResultStatus::Success)));
+
+                // Resolved in a single backend call
+                let statuses_calls: Vec<BackendCall> = recorder.calls()[calls..]
+                    .iter()
+                    .filter(|call| {
+                        matches!(call, BackendCall::Status(_) | BackendCall::Statuses(_))
+                    })
+                    .cloned()
+                    .collect();
+                assert_eq!(statuses_calls, vec![BackendCall::Statuses(ids)]);
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod statuses {
+        use super::*;
+        use std::time::Duration;
+
+        #[test]
+        fn retrying_transitions() {
+            let mut job = Job::new(Routines::Nop).unwrap();
+
+            // Only a running job waits for a retry
+            assert!(job.set_status(Status::Retrying).is_err());
+
+            for status in [
+                Status::Ready,
+                Status::Running,
+                Status::Retrying,
+                Status::Ready,
+                Status::Running,
+                Status::Retrying,
+            ] {
+                job.set_status(status).unwrap();
+            }
+
+            let stats = QueueStats::from_jobs(&[JobInfo::from(&job)]);
+            assert_eq!(stats.retrying, 1);
+            assert_eq!(stats.total(), 1);
+
+            job.set_status(Status::Finished(ResultStatus::TimedOut))
+                .unwrap();
+
+            // Reported as failures
+            let stats = QueueStats::from_jobs(&[JobInfo::from(&job)]);
+            assert_eq!(stats.failed, 1);
+            assert!(PurgeFilter::errors().matches(&job));
+        }
+
+        #[test]
+        fn retry_policy() {
+            let policy =
+                RetryPolicy::new(4).backoff(Duration::from_millis(100), Duration::from_millis(300));
+
+            assert_eq!(policy.delay(1), Some(Duration::from_millis(100)));
+            assert_eq!(policy.delay(2), Some(Duration::from_millis(200)));
+            assert_eq!(policy.delay(3), Some(Duration::from_millis(300)));
+            assert_eq!(policy.delay(4), None);
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .retry_policy(
+                    RetryPolicy::new(3).backoff(Duration::from_millis(50), Duration::from_secs(1)),
+                )
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let failing = jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+                let cancelled = jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+
+                // Waiting for the backoff delay between the attempts
+                tokio::time::sleep(Duration::from_millis(25)).await;
+
+                assert_eq!(jq.job_status(&failing).await.unwrap(), Status::Retrying);
+                assert_eq!(jq.job_status(&cancelled).await.unwrap(), Status::Retrying);
+
+                jq.cancel_job(&cancelled).await.unwrap();
+
+                // Run again under the same ID until the attempts are exhausted
+                tokio::time::sleep(Duration::from_millis(250)).await;
+
+                assert_eq!(
+                    jq.job_status(&failing).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+                assert_eq!(jq.job_attempts(&failing).await.unwrap().len(), 3);
+
+                assert_eq!(
+                    jq.job_status(&cancelled).await.unwrap(),
+                    Status::Finished(ResultStatus::Cancelled)
+                );
+                assert_eq!(jq.job_attempts(&cancelled).await.unwrap().len(), 1);
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod attempts {
+        use super::*;
+        use std::time::Duration;
+
+        #[test]
+        fn lost_attempts() {
+            let mut job = Job::new(Routines::Nop).unwrap();
+
+            job.set_status(Status::Ready).unwrap();
+            job.claim("worker-a", Duration::from_secs(60));
+            job.set_status(Status::Running).unwrap();
+
+            // Worker died: requeued, then interrupted on the next worker
+            job.requeue();
+            job.claim("worker-b", Duration::from_secs(60));
+            job.set_status(Status::Running).unwrap();
+            job.interrupt().unwrap();
+
+            let workers: Vec<Option<&str>> = job
+                .attempts()
+                .iter()
+                .map(|attempt| attempt.worker.as_deref())
+                .collect();
+
+            assert_eq!(workers, vec![Some("worker-a"), Some("worker-b")]);
+            assert!(job
+                .attempts()
+                .iter()
+                .all(|attempt| attempt.status == ResultStatus::Interrupted));
+            assert_eq!(job.status(), Status::Finished(ResultStatus::Interrupted));
+
+            // History carried by the retry
+            assert_eq!(job.retry().attempts(), job.attempts());
+        }
+    }
+
+    mod recurrence {
+        use super::*;
+        use std::time::{Duration, SystemTime};
+
+        fn records(label: &str) -> usize {
+            RECORDS
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|record| *record == label)
+                .count()
+        }
+
+        #[test]
+        fn next_occurrences() {
+            let every = Duration::from_secs(10);
+            let start = SystemTime::UNIX_EPOCH;
+            let now = start + Duration::from_secs(35);
+
+            // On time
+            let recurrence = Recurrence::every(every);
+            assert!(!recurrence.is_misfired(start, start + Duration::from_secs(5)));
+            assert_eq!(
+                recurrence.next(start, start + Duration::from_secs(5)),
+                start + every
+            );
+
+            // Late: caught up once, or every missed occurrence run
+            assert!(recurrence.is_misfired(start, now));
+            assert_eq!(recurrence.next(start, now), start + Duration::from_secs(40));
+            assert_eq!(
+                recurrence.misfire(MisfirePolicy::Skip).next(start, now),
+                start + Duration::from_secs(40)
+            );
+            assert_eq!(
+                recurrence.misfire(MisfirePolicy::RunAll).next(start, now),
+                start + every
+            );
+        }
+
+        #[test]
+        fn jitter() {
+            let run_at = SystemTime::now() + Duration::from_secs(60);
+            let jitter = Duration::from_secs(5);
+
+            for _ in 0..20 {
+                let mut job = Job::new(Routines::Nop).unwrap();
+
+                job.set_run_at(run_at);
+                job.set_jitter(jitter);
+
+                let due_at = job.due_at().unwrap();
+                assert!(due_at >= run_at && due_at <= run_at + jitter);
+                assert!(!job.is_due());
+            }
+        }
+
+        #[test]
+        fn delayed_and_recurring_jobs() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Delayed job not run before its time
+                let mut delayed = record("recurrence-delayed");
+                delayed.set_delay(Duration::from_millis(200));
+                let delayed = jq.enqueue(delayed).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                assert_eq!(records("recurrence-delayed"), 0);
+                assert_eq!(jq.job_status(&delayed).await.unwrap(), Status::Ready);
+
+                tokio::time::sleep(Duration::from_millis(250)).await;
+                assert_eq!(records("recurrence-delayed"), 1);
+
+                // Recurring job run again at every interval
+                let mut recurring = record("recurrence-recurring");
+                recurring.set_recurrence(Recurrence::every(Duration::from_millis(100)));
+                jq.enqueue(recurring).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(350)).await;
+                assert!(records("recurrence-recurring") >= 3);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn cancel_delayed_job() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let mut delayed = record("recurrence-cancelled");
+                delayed.set_delay(Duration::from_millis(100));
+                let delayed = jq.enqueue(delayed).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                jq.cancel_job(&delayed).await.unwrap();
+
+                assert_eq!(
+                    jq.job_status(&delayed).await.unwrap(),
+                    Status::Finished(ResultStatus::Cancelled)
+                );
+
+                // Never run once due
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                assert_eq!(records("recurrence-cancelled"), 0);
+                assert_eq!(
+                    jq.job_status(&delayed).await.unwrap(),
+                    Status::Finished(ResultStatus::Cancelled)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod overlap {
+        use super::*;
+        use crate::recurrence::{Overlap, Series};
+        use std::time::{Duration, SystemTime};
+
+        fn occurrence(overlap: OverlapPolicy) -> Job {
+            let mut job = Job::new(Routines::Nop).unwrap();
+
+            job.set_recurrence(Recurrence::every(Duration::from_secs(1)).overlap(overlap));
+
+            job
+        }
+
+        #[test]
+        fn overlap_policies() {
+            for (policy, expected) in [
+                (OverlapPolicy::Skip, Overlap::Skip),
+                (OverlapPolicy::Queue, Overlap::Wait),
+            ] {
+                let mut series = Series::default();
+                let first = occurrence(policy);
+                let second = first.next_occurrence(SystemTime::now()).unwrap();
+
+                assert_eq!(second.series(), first.id());
+                assert_eq!(series.start(&first), Overlap::Run);
+                assert_eq!(series.start(&second), expected);
+
+                // The waiting occurrence released once the previous one is finished
+                let released = series.finish(&first.id()).map(|pending_job| pending_job.id);
+                assert_eq!(
+                    released,
+                    (policy == OverlapPolicy::Queue).then_some(second.id())
+                );
+            }
+
+            let mut series = Series::default();
+            let first = occurrence(OverlapPolicy::CancelPrevious);
+            let second = first.next_occurrence(SystemTime::now()).unwrap();
+
+            assert_eq!(series.start(&first), Overlap::Run);
+            assert_eq!(series.start(&second), Overlap::CancelPrevious(first.id()));
+            assert!(series.finish(&first.id()).is_none());
+
+            // Jobs that are not recurring never overlap
+            let job = Job::new(Routines::Nop).unwrap();
+            assert_eq!(series.start(&job), Overlap::Run);
+            assert_eq!(series.start(&job), Overlap::Run);
+        }
+
+        fn run_series(policy: OverlapPolicy) -> Vec<JobInfo> {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jobs = Runtime::new().unwrap().block_on(async {
+                // Occurrences lasting longer than the interval
+                let mut job = Job::new(Routines::Sleep(SleepArgs {
+                    duration: Duration::from_millis(120),
+                }))
+                .unwrap();
+
+                job.set_recurrence(Recurrence::every(Duration::from_millis(50)).overlap(policy));
+                jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(600)).await;
+
+                let jobs = jq.jobs().await.unwrap();
+
+                // Stop the job queue
+                jq.stop().unwrap();
+
+                jobs
+            });
+
+            jq.join().unwrap();
+
+            jobs
+        }
+
+        fn assert_no_overlap(jobs: &[JobInfo]) {
+            let mut runs: Vec<(SystemTime, SystemTime)> = jobs
+                .iter()
+                .filter(|job| job.status == Status::Finished(ResultStatus::Success))
+                .map(|job| (job.timestamps.started, job.timestamps.finished))
+                .collect();
+
+            runs.sort();
+
+            assert!(runs.len() >= 2);
+            assert!(runs.windows(2).all(|runs| runs[0].1 <= runs[1].0));
+        }
+
+        #[test]
+        fn queued_overlaps() {
+            let jobs = run_series(OverlapPolicy::Queue);
+
+            assert_no_overlap(&jobs);
+            assert!(!jobs
+                .iter()
+                .any(|job| job.status == Status::Finished(ResultStatus::Cancelled)));
+        }
+
+        #[test]
+        fn skipped_overlaps() {
+            let jobs = run_series(OverlapPolicy::Skip);
+
+            assert_no_overlap(&jobs);
+            assert!(jobs
+                .iter()
+                .any(|job| job.status == Status::Finished(ResultStatus::Cancelled)));
+        }
+
+        #[test]
+        fn cancelled_overlaps() {
+            let jobs = run_series(OverlapPolicy::CancelPrevious);
+
+            // Every occurrence cancelled by the next one, due before it could finish
+            assert!(jobs
+                .iter()
+                .any(|job| job.status == Status::Finished(ResultStatus::Cancelled)));
+            assert!(!jobs
+                .iter()
+                .any(|job| job.status == Status::Finished(ResultStatus::Success)));
+        }
+    }
+
+    mod tenants {
+        use super::*;
+        use crate::scheduler::Dispatcher;
+        use tokio::time::Duration;
+
+        #[test]
+        fn quotas() {
+            let quotas = TenantQuotas::new(TenantQuota::default().max_running(1))
+                .tenant("small", TenantQuota::default().max_pending(1));
+            let mut dispatcher = Dispatcher::new(Box::new(FifoStrategy), None, None);
+
+            dispatcher.set_quotas(quotas);
+
+            // Pending quota, counting the jobs admitted and not pushed yet
+            let mut small = record("small");
+            small.set_tenant("small");
+            dispatcher.admit(small.id(), Some("small")).unwrap();
+            assert!(matches!(
+                dispatcher.admit(Uuid::now_v1(&[1; 6]), Some("small")).map_err(|e| *e),
+                Err(Error::QuotaExceeded(tenant)) if tenant == "small"
+            ));
+
+            dispatcher.push(PendingJob::from(&small));
+            assert!(dispatcher
+                .admit(Uuid::now_v1(&[2; 6]), Some("small"))
+                .is_err());
+
+            // Running quota, the other tenants dispatched meanwhile
+            let large: Vec<Job> = (0..2)
+                .map(|_| {
+                    let mut job = record("large");
+                    job.set_tenant("large");
+                    job
+                })
+                .collect();
+            let mut other = record("other");
+            other.set_tenant("other");
+
+            for job in large.iter().chain([&other]) {
+                dispatcher.push(PendingJob::from(job));
+            }
+
+            assert_eq!(dispatcher.next(), Some(small.id()));
+            assert_eq!(dispatcher.next(), Some(large[0].id()));
+            assert_eq!(dispatcher.next(), Some(other.id()));
+            assert_eq!(dispatcher.next(), None);
+
+            dispatcher.release(&large[0].id());
+            assert_eq!(dispatcher.next(), Some(large[1].id()));
+        }
+
+        #[test]
+        fn round_robin() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .scheduler(TenantRoundRobinStrategy::default())
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Occupy the only slot so that the other jobs are pending
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(50),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+
+                for (label, tenant) in [
+                    ("tenant-a1", "a"),
+                    ("tenant-a2", "a"),
+                    ("tenant-a3", "a"),
+                    ("tenant-b1", "b"),
+                    ("tenant-c1", "c"),
+                ] {
+                    let mut job = record(label);
+                    job.set_tenant(tenant);
+                    jq.enqueue(job).unwrap();
+                }
+
+                tokio::time::sleep(Duration::from_millis(200)).await;
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+
+            assert_eq!(
+                records("tenant-"),
+                vec![
+                    "tenant-a1",
+                    "tenant-b1",
+                    "tenant-c1",
+                    "tenant-a2",
+                    "tenant-a3"
+                ]
+            );
+        }
+
+        #[test]
+        fn pending_quota() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .tenant_quotas(TenantQuotas::new(TenantQuota::default().max_pending(2)))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Occupy the only slot so that the other jobs are pending
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(100),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+
+                for label in ["quota-1", "quota-2"] {
+                    let mut job = record(label);
+                    job.set_tenant("greedy");
+                    jq.enqueue(job).unwrap();
+                }
+
+                let mut job = record("quota-3");
+                job.set_tenant("greedy");
+                assert!(matches!(
+                    jq.enqueue(job).map_err(|e| *e),
+                    Err(Error::QuotaExceeded(_))
+                ));
+
+                // The other tenants not affected
+                let mut job = record("quota-4");
+                job.set_tenant("modest");
+                jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(250)).await;
+
+                // Accepted again once the pending jobs are dispatched
+                let mut job = record("quota-5");
+                job.set_tenant("greedy");
+                jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+
+            assert_eq!(
+                records("quota-"),
+                vec!["quota-1", "quota-2", "quota-4", "quota-5"]
+            );
+        }
+    }
+
+    mod preemption {
+        use super::*;
+        use crate::scheduler::Dispatcher;
+        use tokio::time::Duration;
+
+        #[test]
+        fn preempt_lowest_priority() {
+            let mut dispatcher = Dispatcher::new(Box::new(FifoStrategy), Some(2), None);
+
+            dispatcher.set_preemption(PreemptionPolicy::new(10).min_gap(5));
+
+            let [low, batch, urgent] = [0, 3, 10].map(|priority| {
+                let mut job = Job::new(Routines::Nop).unwrap();
+                job.set_priority(priority);
+                job
+            });
+
+            dispatcher.push(PendingJob::from(&low));
+            dispatcher.push(PendingJob::from(&batch));
+            assert_eq!(dispatcher.next(), Some(low.id()));
+            assert_eq!(dispatcher.next(), Some(batch.id()));
+
+            // Not urgent enough, or nothing suspendable
+            let mut pressing = Job::new(Routines::Nop).unwrap();
+            pressing.set_priority(9);
+            dispatcher.push(PendingJob::from(&pressing));
+            assert_eq!(dispatcher.preempt(|_| true), None);
+
+            dispatcher.push(PendingJob::from(&urgent));
+            assert_eq!(dispatcher.preempt(|_| false), None);
+
+            // Lowest priority preempted, and resumed once the urgent job is finished
+            assert_eq!(dispatcher.preempt(|_| true), Some((low.id(), urgent.id())));
+            assert_eq!(dispatcher.next(), None);
+            assert_eq!(dispatcher.release(&urgent.id()), Some(low.id()));
+            assert_eq!(dispatcher.next(), None);
+
+            // Slot freed once both are finished
+            assert_eq!(dispatcher.release(&low.id()), None);
+            assert!(dispatcher.next().is_some());
+        }
+
+        #[test]
+        fn suspend_for_urgent_jobs() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .preemption(PreemptionPolicy::new(10))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let batch = jq
+                    .enqueue(
+                        Job::new(Routines::Steps(StepsArgs {
+                            count: 10,
+                            interval: Duration::from_millis(20),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(60)).await;
+
+                let mut urgent = Job::new(Routines::Sleep(SleepArgs {
+                    duration: Duration::from_millis(150),
+                }))
+                .unwrap();
+
+                urgent.set_priority(10);
+
+                let urgent = jq.enqueue(urgent).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(80)).await;
+
+                // Batch job parked while the urgent job takes its slot
+                assert_eq!(jq.job_status(&urgent).await.unwrap(), Status::Running);
+                assert_eq!(jq.job_status(&batch).await.unwrap(), Status::Suspended);
+
+                tokio::time::sleep(Duration::from_millis(150)).await;
+
+                assert_eq!(
+                    jq.job_status(&urgent).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(jq.job_status(&batch).await.unwrap(), Status::Running);
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                assert_eq!(
+                    jq.job_status(&batch).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod bounded {
+        use super::*;
+        use std::time::Instant;
+        use tokio::time::Duration;
+
+        #[test]
+        fn bounded_queue() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .max_pending(2)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Occupy the only slot so that the other jobs are pending
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(150),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(30)).await;
+
+                for _ in 0..2 {
+                    jq.try_enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                }
+
+                // Full until the running job is finished
+                assert!(matches!(
+                    jq.try_enqueue(Job::new(Routines::Nop).unwrap())
+                        .map_err(|e| *e),
+                    Err(Error::QueueFull)
+                ));
+
+                let start = Instant::now();
+                assert!(matches!(
+                    jq.enqueue_timeout(Job::new(Routines::Nop).unwrap(), Duration::from_millis(30))
+                        .map_err(|e| *e),
+                    Err(Error::QueueFull)
+                ));
+                assert!(start.elapsed() >= Duration::from_millis(30));
+
+                let id = jq
+                    .enqueue_timeout(Job::new(Routines::Nop).unwrap(), Duration::from_secs(1))
+                    .unwrap();
+                assert!(start.elapsed() >= Duration::from_millis(80));
+
+                // Blocked until room is made
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_status(&id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn enqueue_async() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .max_pending(1)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            // Single thread, blocked if the enqueue blocks
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_time()
+                .build()
+                .unwrap();
+
+            runtime.block_on(async {
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(150),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(30)).await;
+
+                jq.try_enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                // Awaiting room while the other tasks go on
+                let start = Instant::now();
+                let (id, ticked) = tokio::join!(
+                    async {
+                        let id = jq.enqueue_async(Job::new(Routines::Nop).unwrap()).await;
+
+                        (id, start.elapsed())
+                    },
+                    async {
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+
+                        start.elapsed()
+                    }
+                );
+                let (id, enqueued) = id;
+
+                assert!(ticked < enqueued);
+                assert!(enqueued >= Duration::from_millis(80));
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_status(&id.unwrap()).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod deadline {
+        use super::*;
+        use std::time::SystemTime;
+        use tokio::time::Duration;
+
+        fn due_in(routine: Routines, delay: Duration) -> Job {
+            let mut job = Job::new(routine).unwrap();
+
+            job.set_deadline(SystemTime::now() + delay);
+
+            job
+        }
+
+        #[test]
+        fn deadline_exceeded() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .max_in_flight(1)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let exceeded = Status::Finished(ResultStatus::DeadlineExceeded);
+
+                // Cancelled while running
+                let running = jq
+                    .enqueue(due_in(
+                        Routines::Steps(StepsArgs {
+                            count: 10,
+                            interval: Duration::from_millis(20),
+                        }),
+                        Duration::from_millis(70),
+                    ))
+                    .unwrap();
+
+                // Dropped while waiting for the slot
+                let pending = jq
+                    .enqueue(due_in(
+                        Routines::Record(RecordArgs {
+                            label: "deadline-pending".to_string(),
+                        }),
+                        Duration::from_millis(30),
+                    ))
+                    .unwrap();
+
+                // Dropped when dispatched, once delayed past its deadline
+                let mut delayed = due_in(Routines::Nop, Duration::from_millis(20));
+                delayed.set_delay(Duration::from_millis(40));
+                let delayed = jq.enqueue(delayed).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(jq.job_status(&pending).await.unwrap(), exceeded);
+                assert_eq!(jq.job_status(&running).await.unwrap(), Status::Running);
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert_eq!(jq.job_status(&running).await.unwrap(), exceeded);
+                assert!(jq.job_progression(&running).await.unwrap().step < 10);
+                assert_eq!(jq.job_status(&delayed).await.unwrap(), exceeded);
+                assert!(records("deadline-pending").is_empty());
+
+                // Finished in time
+                let in_time = jq
+                    .enqueue(due_in(Routines::Nop, Duration::from_millis(100)))
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(150)).await;
+
+                assert_eq!(
+                    jq.job_status(&in_time).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn default_timeout() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .default_timeout(Duration::from_millis(50))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let steps = || {
+                    Routines::Steps(StepsArgs {
+                        count: 10,
+                        interval: Duration::from_millis(20),
+                    })
+                };
+
+                // Cancelled past the timeout of the queue, unless given a deadline
+                let timed_out = jq.enqueue(Job::new(steps()).unwrap()).unwrap();
+                let in_time = jq
+                    .enqueue(due_in(steps(), Duration::from_millis(500)))
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(300)).await;
+
+                assert_eq!(
+                    jq.job_status(&timed_out).await.unwrap(),
+                    Status::Finished(ResultStatus::DeadlineExceeded)
+                );
+                assert_eq!(
+                    jq.job_status(&in_time).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod middleware {
+        use super::*;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::time::Duration;
+
+        /// Records the calls around the jobs labelled by a metadata.
+        struct Trace(&'static str);
+
+        #[async_trait]
+        impl Middleware for Trace {
+            async fn handle(&self, job: &Job, next: Next<'_>) -> Result<Vec<u8>, ApiError> {
+                let label = job.metadata()["label"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_owned();
+
+                RECORDS
+                    .lock()
+                    .unwrap()
+                    .push(format!("{label}-{}-before", self.0));
+
+                let result = next.run(job).await;
+
+                RECORDS
+                    .lock()
+                    .unwrap()
+                    .push(format!("{label}-{}-after", self.0));
+
+                result
+            }
+        }
+
+        /// Rejects the jobs flagged by a metadata.
+        struct Deny;
+
+        #[async_trait]
+        impl Middleware for Deny {
+            async fn handle(&self, job: &Job, next: Next<'_>) -> Result<Vec<u8>, ApiError> {
+                match job.metadata().contains_key("denied") {
+                    true => Err(api_err!(Error::Custom("Denied".to_string()))),
+                    false => next.run(job).await,
+                }
+            }
+        }
+
+        /// Runs the failed jobs again, up to a number of attempts.
+        struct Retry(usize, Arc<AtomicUsize>);
+
+        #[async_trait]
+        impl Middleware for Retry {
+            async fn handle(&self, job: &Job, next: Next<'_>) -> Result<Vec<u8>, ApiError> {
+                let mut result = next.run(job).await;
+
+                for _ in 1..self.0 {
+                    if result.is_ok() {
+                        break;
+                    }
+
+                    self.1.fetch_add(1, Ordering::SeqCst);
+                    result = next.run(job).await;
+                }
+
+                result
+            }
+        }
+
+        fn labelled(routine: Routines, label: &str) -> Job {
+            let mut job = Job::new(routine).unwrap();
+
+            job.set_metadata("label", label.into());
+
+            job
+        }
+
+        #[test]
+        fn middleware_chain() {
+            let retries = Arc::new(AtomicUsize::new(0));
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .layer(Trace("outer"))
+                .layer(Deny)
+                .layer(Retry(3, retries.clone()))
+                .layer(Trace("inner"))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let ok = jq
+                    .enqueue(labelled(
+                        Routines::Record(RecordArgs {
+                            label: "chain-ok-routine".to_string(),
+                        }),
+                        "chain-ok",
+                    ))
+                    .unwrap();
+
+                let mut denied = labelled(
+                    Routines::Record(RecordArgs {
+                        label: "chain-denied-routine".to_string(),
+                    }),
+                    "chain-denied",
+                );
+                denied.set_metadata("denied", true.into());
+                let denied = jq.enqueue(denied).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_status(&ok).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(
+                    jq.job_status(&denied).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+
+                // Failed job run again by the middleware
+                let failed = jq
+                    .enqueue(labelled(Routines::RaiseError, "chain-failed"))
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(
+                    jq.job_status(&failed).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+
+            assert_eq!(
+                records("chain-ok"),
+                vec![
+                    "chain-ok-outer-before",
+                    "chain-ok-inner-before",
+                    "chain-ok-routine",
+                    "chain-ok-inner-after",
+                    "chain-ok-outer-after",
+                ]
+            );
+            assert_eq!(
+                records("chain-denied"),
+                vec!["chain-denied-outer-before", "chain-denied-outer-after"]
+            );
+            assert_eq!(records("chain-failed-inner-before").len(), 3);
+            assert_eq!(retries.load(Ordering::SeqCst), 2);
+        }
+    }
+
+    mod interceptor {
+        use super::*;
+        use tokio::time::Duration;
+
+        /// Rejects the jobs of a kind.
+        struct RejectKind(&'static str);
+
+        impl EnqueueInterceptor for RejectKind {
+            fn intercept(&self, job: &mut Job) -> Result<(), ApiError> {
+                match job.kind() == self.0 {
+                    true => Err(api_err!(Error::JobRejected(format!(
+                        "{} jobs are disabled",
+                        self.0
+                    )))),
+                    false => Ok(()),
+                }
+            }
+        }
+
+        #[test]
+        fn enqueue_interceptors() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .interceptor(RejectKind("RaiseError"))
+                .interceptor(|job: &mut Job| {
+                    job.add_tag("enriched");
+
+                    Ok(())
+                })
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Rejected before being taken
+                assert!(matches!(
+                    jq.enqueue(Job::new(Routines::RaiseError).unwrap())
+                        .map_err(|e| *e),
+                    Err(Error::JobRejected(reason)) if reason == "RaiseError jobs are disabled"
+                ));
+
+                // Completed when enqueued
+                let id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                let job = jq.job(&id).await.unwrap();
+                assert_eq!(job.tags(), ["enriched"]);
+                assert_eq!(job.status(), Status::Finished(ResultStatus::Success));
+                assert_eq!(jq.jobs().await.unwrap().len(), 1);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod hooks {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn lifecycle_hooks() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .on_start(|job| {
+                    RECORDS
+                        .lock()
+                        .unwrap()
+                        .push(format!("hooks-start-{}", job.kind()));
+                })
+                .on_success(|job, result| {
+                    RECORDS.lock().unwrap().push(format!(
+                        "hooks-success-{}-{}",
+                        job.kind(),
+                        result.len()
+                    ));
+                })
+                .on_failure(|job, status, error| {
+                    RECORDS.lock().unwrap().push(format!(
+                        "hooks-failure-{}-{status:?}-{}",
+                        job.kind(),
+                        !error.is_empty()
+                    ));
+                })
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Cancelled while running
+                let id = jq
+                    .enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_secs(1),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                jq.cancel_job(&id).await.unwrap();
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+
+            assert_eq!(
+                records("hooks-"),
+                vec![
+                    "hooks-start-Nop",
+                    "hooks-success-Nop-0",
+                    "hooks-start-RaiseError",
+                    "hooks-failure-RaiseError-Error-true",
+                    "hooks-start-Sleep",
+                    "hooks-failure-Sleep-Cancelled-true",
+                ]
+            );
+        }
+    }
+
+    mod completion {
+        use super::*;
+        use std::sync::{Arc, Mutex};
+        use tokio::time::Duration;
+
+        #[test]
+        fn on_complete() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let outcomes = Arc::new(Mutex::new(vec![]));
+
+                // Callback called in process
+                let mut job = Job::new(Routines::RaiseError).unwrap();
+                let callback = outcomes.clone();
+                job.set_on_complete(move |outcome: &Outcome| {
+                    callback.lock().unwrap().push(outcome.clone());
+                });
+                let failed = jq.enqueue(job).unwrap();
+
+                let mut job = Job::new(Routines::Sleep(SleepArgs {
+                    duration: Duration::from_secs(1),
+                }))
+                .unwrap();
+                let callback = outcomes.clone();
+                job.set_on_complete(move |outcome: &Outcome| {
+                    callback.lock().unwrap().push(outcome.clone());
+                });
+                let cancelled = jq.enqueue(job).unwrap();
+
+                // Follow-up job enqueued with the outcome
+                let mut job = Job::new(Routines::Nop).unwrap();
+                job.set_on_complete_routine(Routines::Record(RecordArgs {
+                    label: "completion-follow-up".to_string(),
+                }))
+                .unwrap();
+                let succeeded = jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                jq.cancel_job(&cancelled).await.unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                let outcomes = outcomes.lock().unwrap().clone();
+                assert_eq!(outcomes.len(), 2);
+                assert_eq!(outcomes[0].id, failed);
+                assert_eq!(outcomes[0].status, ResultStatus::Error);
+                assert!(outcomes[0].error.is_some());
+                assert_eq!(outcomes[1].id, cancelled);
+                assert_eq!(outcomes[1].status, ResultStatus::Cancelled);
+
+                let follow_up = jq
+                    .jobs()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .find(|info| info.kind == "Record")
+                    .unwrap();
+                let follow_up = jq.job(&follow_up.id).await.unwrap();
+                let outcome = follow_up.outcome().unwrap();
+                assert_eq!(outcome.id, succeeded);
+                assert_eq!(outcome.kind, "Nop");
+                assert_eq!(outcome.status, ResultStatus::Success);
+                assert_eq!(follow_up.status(), Status::Finished(ResultStatus::Success));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+
+            assert_eq!(records("completion-"), vec!["completion-follow-up"]);
+        }
+    }
+
+    mod wait_for {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn wait_for() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let nop = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                let failed = jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+                let slow = jq
+                    .enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_millis(100),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                // Timeout expired before the slow job is finished
+                let statuses = jq
+                    .wait_for(&[nop, failed, slow], Duration::from_millis(50))
+                    .await
+                    .unwrap();
+                assert_eq!(statuses[&nop], Status::Finished(ResultStatus::Success));
+                assert_eq!(statuses[&failed], Status::Finished(ResultStatus::Error));
+                assert!(!statuses[&slow].is_terminal());
+
+                let statuses = jq
+                    .wait_for(&[nop, failed, slow], Duration::from_secs(1))
+                    .await
+                    .unwrap();
+                assert_eq!(statuses[&slow], Status::Finished(ResultStatus::Success));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn wait_for_clock() {
+            let clock = ManualClock::default();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .clock(clock.clone())
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let slow = jq
+                    .enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_millis(200),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                // Timeout expired once the clock of the queue moves past it
+                let ids = [slow];
+                let (statuses, _) =
+                    tokio::join!(jq.wait_for(&ids, Duration::from_secs(3600)), async {
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        clock.advance(Duration::from_secs(3601));
+                    });
+                assert!(!statuses.unwrap()[&slow].is_terminal());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod clock {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn manual_clock() {
+            let clock = ManualClock::default();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .clock(clock.clone())
+                .sweep_strategy(SweepStrategy::Lazy)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Delayed job due once the clock has moved
+                let mut job = Job::new_with_expire(
+                    Routines::Nop,
+                    ExpirePolicy::Timeout(Duration::from_secs(3600)),
+                )
+                .unwrap();
+                job.set_run_at(clock.now() + Duration::from_secs(60));
+                let job_id = jq.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert_eq!(jq.job_status(&job_id).await.unwrap(), Status::Ready);
+
+                clock.advance(Duration::from_secs(60));
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert_eq!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(
+                    jq.job(&job_id).await.unwrap().timestamps().finished,
+                    clock.now()
+                );
+
+                // Expired an hour later
+                clock.advance(Duration::from_secs(3599));
+                assert!(jq.job_status(&job_id).await.is_ok());
+
+                clock.advance(Duration::from_secs(1));
+                assert!(jq.job_status(&job_id).await.is_err());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod inline {
+        use super::*;
+        use std::sync::{Arc, Mutex};
+
+        #[test]
+        fn inline_executor() {
+            let executor = InlineExecutor::<Routines, Context>::with_context(Context {
+                name: "UNIT_TESTING".to_string(),
+            });
+
+            Runtime::new().unwrap().block_on(async {
+                // Run when enqueued
+                let mut job = Job::new(Routines::Record(RecordArgs {
+                    label: "inline-first".to_string(),
+                }))
+                .unwrap();
+                job.set_on_complete_routine(Routines::Record(RecordArgs {
+                    label: "inline-follow-up".to_string(),
+                }))
+                .unwrap();
+                assert!(executor.enqueue(job).await.is_ok());
+                assert_eq!(records("inline-"), vec!["inline-first", "inline-follow-up"]);
+
+                assert!(executor
+                    .enqueue(Job::new(Routines::CheckContext).unwrap())
+                    .await
+                    .is_ok());
+
+                // Error of the routine returned, after the completion callback
+                let outcome = Arc::new(Mutex::new(None));
+                let mut job = Job::new(Routines::RaiseError).unwrap();
+                let callback = outcome.clone();
+                job.set_on_complete(move |outcome: &Outcome| {
+                    *callback.lock().unwrap() = Some(outcome.status);
+                });
+                assert!(executor.enqueue(job).await.is_err());
+                assert_eq!(*outcome.lock().unwrap(), Some(ResultStatus::Error));
+            });
+        }
+    }
+
+    mod tiered_backend {
+        use super::*;
+        use crate::test_util::{BackendCall, RecordingBackend};
+        use crate::tiered_backend::{ReadFallback, TieredBackend};
+        use tokio::time::Duration;
+
+        type DynBackend = dyn Backend<Routines, Context>;
+
+        #[test]
+        fn cached_reads() {
+            let store = RecordingBackend::new();
+            let recorder = store.recorder();
+            let mut backend: Box<DynBackend> = Box::new(TieredBackend::new(store));
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            // Writes going through to the store
+            backend.schedule(job).unwrap();
+            backend.set_status(&job_id, Status::Ready).unwrap();
+            backend.set_steps(&job_id, 2).unwrap();
+            backend.set_step(&job_id, 1).unwrap();
+            assert_eq!(recorder.statuses(&job_id), vec![Status::Ready]);
+            assert_eq!(recorder.steps(&job_id), vec![1]);
+
+            // Reads served by the cache
+            assert_eq!(backend.status(&job_id).unwrap(), Status::Ready);
+            assert_eq!(backend.progression(&job_id).unwrap().step, 1);
+            assert_eq!(
+                backend.statuses(&[job_id]).unwrap().get(&job_id),
+                Some(&Status::Ready)
+            );
+            assert!(!recorder.calls().iter().any(|call| matches!(
+                call,
+                BackendCall::Status(_) | BackendCall::Statuses(_) | BackendCall::Progression(_)
+            )));
+
+            // Job removed from the store and the cache once finished
+            backend
+                .set_status(&job_id, Status::Finished(ResultStatus::Success))
+                .unwrap();
+            assert_eq!(
+                backend.status(&job_id).unwrap(),
+                Status::Finished(ResultStatus::Success)
+            );
+            backend.remove(&job_id).unwrap();
+            assert!(backend.status(&job_id).is_err());
+            assert!(recorder.calls().contains(&BackendCall::Status(job_id)));
+        }
+
+        #[test]
+        fn stale_reads() {
+            let store = RecordingBackend::new();
+            let recorder = store.recorder();
+            let mut backend: Box<DynBackend> =
+                Box::new(TieredBackend::new(store).fallback(ReadFallback::OnStale(Duration::ZERO)));
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            backend.schedule(job).unwrap();
+            backend.set_status(&job_id, Status::Ready).unwrap();
+
+            // Every read falling back to the store
+            assert_eq!(backend.status(&job_id).unwrap(), Status::Ready);
+            assert_eq!(backend.status(&job_id).unwrap(), Status::Ready);
+            assert_eq!(
+                recorder
+                    .calls()
+                    .iter()
+                    .filter(|call| **call == BackendCall::Status(job_id))
+                    .count(),
+                2
+            );
+        }
+    }
+
+    mod resilient_backend {
+        use super::*;
+        use crate::resilient_backend::{BackendRetry, CircuitBreaker, ResilientBackend};
+        use crate::test_util::{ChaosBackend, ChaosOp, Fault};
+        use tokio::time::Duration;
+
+        type DynBackend = dyn Backend<Routines, Context>;
+
+        /// Retries of the failures injected by the chaos backend.
+        fn retry() -> BackendRetry {
+            BackendRetry::default()
+                .backoff(Duration::from_millis(1), Duration::from_millis(5))
+                .retry_if(|error| matches!(error, Error::Custom(_)))
+        }
+
+        #[test]
+        fn retried_operations() {
+            let chaos = ChaosBackend::new()
+                .fault(ChaosOp::Get, Fault::default().fail_every(2))
+                .fault(ChaosOp::Status, Fault::default().fail_every(2));
+            let controller = chaos.controller();
+            let mut backend: Box<DynBackend> =
+                Box::new(ResilientBackend::new(chaos).retry(retry()));
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            backend.schedule(job).unwrap();
+
+            // Every other call failing, hidden by the retries of the asynchronous operations
+            Runtime::new().unwrap().block_on(async {
+                for _ in 0..3 {
+                    assert_eq!(backend.get(&job_id).await.unwrap().id(), job_id);
+                }
+            });
+            assert_eq!(controller.injected(ChaosOp::Get), 2);
+
+            // Synchronous operations failing fast, without blocking the caller during a backoff
+            backend.set_status(&job_id, Status::Ready).unwrap();
+            assert!(matches!(
+                *backend.status(&job_id).unwrap_err(),
+                Error::Custom(_)
+            ));
+            assert_eq!(backend.status(&job_id).unwrap(), Status::Ready);
+            assert_eq!(controller.injected(ChaosOp::Status), 1);
+
+            // Errors not transient surfacing right away
+            Runtime::new().unwrap().block_on(async {
+                assert!(matches!(
+                    *backend.get(&Uuid::nil()).await.unwrap_err(),
+                    Error::JobNotFound(_)
+                ));
+            });
+        }
+
+        #[test]
+        fn circuit_breaker() {
+            let chaos =
+                ChaosBackend::new().fault(ChaosOp::Status, Fault::default().failure_rate(1.0));
+            let controller = chaos.controller();
+            let backend = ResilientBackend::new(chaos)
+                .retry(retry().max_retries(1))
+                .circuit_breaker(CircuitBreaker::new(2, Duration::from_millis(50)));
+            let mut backend: Box<DynBackend> = Box::new(backend);
+
+            let job = Job::new(Routines::Nop).unwrap();
+            let job_id = job.id();
+
+            backend.schedule(job).unwrap();
+
+            // Circuit opened by two operations failing in a row
+            for _ in 0..2 {
+                assert!(matches!(
+                    *backend.status(&job_id).unwrap_err(),
+                    Error::Custom(_)
+                ));
+            }
+            assert_eq!(controller.injected(ChaosOp::Status), 2);
+
+            // Operations failing fast, the backend not called
+            assert!(matches!(
+                *backend.status(&job_id).unwrap_err(),
+                Error::CircuitOpen
+            ));
+            assert_eq!(controller.injected(ChaosOp::Status), 2);
+
+            // Circuit closed by an operation succeeding after the cooldown
+            controller.clear();
+            std::thread::sleep(Duration::from_millis(60));
+            assert_eq!(backend.status(&job_id).unwrap(), Status::NotReady);
+            assert!(backend.status(&job_id).is_ok());
+        }
+    }
+
+    mod handles {
+        use super::*;
+        use std::sync::Arc;
+        use tokio::time::Duration;
+
+        #[test]
+        fn scoped_handles() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let jq = Arc::new(jq);
+            let admin = AdminHandle::new(jq.clone());
+            let producer = admin.producer();
+            let reader = admin.reader();
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = producer
+                    .enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_secs(5),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                assert_eq!(reader.job_status(&job_id).await.unwrap(), Status::Running);
+
+                // Destructive operations only on the admin handle
+                admin.cancel_job(&job_id).await.unwrap();
+                assert_eq!(
+                    admin.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Cancelled)
+                );
+
+                let retried_id = admin.retry_job(&job_id).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                admin.cancel_job(&retried_id).await.unwrap();
+                admin.remove_job(&job_id).await.unwrap();
+                assert!(reader.job_status(&job_id).await.is_err());
+            });
+
+            // Stop the job queue
+            drop((admin, producer, reader));
+            let mut jq = Arc::try_unwrap(jq).ok().unwrap();
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod named_queues {
+        use super::*;
+        use crate::scheduler::Dispatcher;
+        use std::collections::HashMap;
+        use tokio::time::Duration;
+
+        #[test]
+        fn limits_and_priorities() {
+            let named_queues = NamedQueues::default()
+                .queue("exports", QueueSettings::default().max_running(1))
+                .queue("emails", QueueSettings::default().priority(1));
+            let mut dispatcher = Dispatcher::new(Box::new(FifoStrategy), None, None);
+
+            dispatcher.set_named_queues(named_queues);
+
+            let exports: Vec<Job> = (0..2)
+                .map(|_| {
+                    let mut job = record("export");
+                    job.set_queue("exports");
+                    job
+                })
+                .collect();
+            let default = Job::new(Routines::Nop).unwrap();
+            let mut email = record("email");
+            email.set_queue("emails");
+
+            for job in exports.iter().chain([&default, &email]) {
+                dispatcher.push(PendingJob::from(job));
+            }
+
+            // Queue of highest priority first, then the others within their limit
+            assert_eq!(dispatcher.next(), Some(email.id()));
+            assert_eq!(dispatcher.next(), Some(exports[0].id()));
+            assert_eq!(dispatcher.next(), Some(default.id()));
+            assert_eq!(dispatcher.next(), None);
+
+            dispatcher.release(&exports[0].id());
+            assert_eq!(dispatcher.next(), Some(exports[1].id()));
+        }
+
+        #[test]
+        fn enqueue_to() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .named_queues(
+                    NamedQueues::default()
+                        .queue("exports", QueueSettings::default().max_running(1)),
+                )
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job_id = jq
+                    .enqueue_to("exports", Job::new(Routines::Nop).unwrap())
+                    .unwrap();
+                let other_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+
+                jq.wait_for(&[job_id, other_id], Duration::from_secs(1))
+                    .await
+                    .unwrap();
+
+                let info = jq.jobs().await.unwrap();
+                assert!(info
+                    .iter()
+                    .any(|job| job.id == job_id && job.queue == "exports"));
+
+                let stats = jq.queue_stats().await.unwrap();
+                assert_eq!(stats["exports"].total(), 1);
+                assert_eq!(stats[DEFAULT_QUEUE].total(), 1);
+            });
+
+            // Stop the job queue
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn routing() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .route_kind("Nop", "exports")
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Kind routed, unless enqueued to a queue explicitly
+                let routed_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                let explicit_id = jq
+                    .enqueue_to(DEFAULT_QUEUE, Job::new(Routines::Nop).unwrap())
+                    .unwrap();
+                let mut other = record("routing");
+                other.set_queue(DEFAULT_QUEUE);
+                let other_id = jq.enqueue(other).unwrap();
+
+                jq.wait_for(&[routed_id, explicit_id, other_id], Duration::from_secs(1))
+                    .await
+                    .unwrap();
+
+                let queues: HashMap<Uuid, String> = jq
+                    .jobs()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|job| (job.id, job.queue))
+                    .collect();
+                assert_eq!(queues[&routed_id], "exports");
+                assert_eq!(queues[&explicit_id], DEFAULT_QUEUE);
+                assert_eq!(queues[&other_id], DEFAULT_QUEUE);
+            });
+
+            // Stop the job queue
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod capabilities {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::sync::Arc;
+        use tokio::sync::Mutex as AsyncMutex;
+        use tokio::time::Duration;
+
+        #[test]
+        fn local_rejection() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .capabilities(Capabilities::new(["gpu"]))
+                .build();
+
+            jq.start().unwrap();
+
+            let mut job = record("CAPABILITIES_LOCAL");
+            job.set_requirements(["gpu", "geospatial"]);
+            let err = jq.enqueue(job).unwrap_err();
+            assert!(
+                matches!(*err, Error::MissingCapabilities(missing) if missing == ["geospatial"])
+            );
+
+            let mut job = record("CAPABILITIES_LOCAL");
+            job.set_requirements(["gpu"]);
+            assert!(jq.enqueue(job).is_ok());
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn pinned_jobs() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let worker = |capabilities: Capabilities| {
+                let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                    .unwrap()
+                    .notification_handler(notification_handler)
+                    .shared_backend(backend.clone())
+                    .distributed(Duration::from_millis(20))
+                    .capabilities(capabilities)
+                    .build();
+
+                jq.start().unwrap();
+
+                jq
+            };
+
+            let mut plain = worker(Capabilities::default());
+
+            Runtime::new().unwrap().block_on(async {
+                // Left ready while no worker provides the capabilities
+                let mut job = record("CAPABILITIES_PINNED");
+                job.set_requirements(["geospatial"]);
+                let id = plain.enqueue(job).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert!(records("CAPABILITIES_PINNED").is_empty());
+                assert_eq!(plain.job_status(&id).await.unwrap(), Status::Ready);
+            });
+
+            let mut geospatial = worker(Capabilities::new(["geospatial", "eu-region"]));
+
+            Runtime::new().unwrap().block_on(async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert_eq!(records("CAPABILITIES_PINNED"), vec!["CAPABILITIES_PINNED"]);
+            });
+
+            geospatial.stop().unwrap();
+            geospatial.join().unwrap();
+            plain.stop().unwrap();
+            plain.join().unwrap();
+        }
+    }
+
+    #[cfg(feature = "signals")]
+    mod signals {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn graceful_shutdown() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.install_signal_handler(Duration::from_millis(100))
+                .unwrap();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let short_id = jq
+                    .enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_millis(50),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+                let long_id = jq
+                    .enqueue(
+                        Job::new(Routines::Sleep(SleepArgs {
+                            duration: Duration::from_secs(5),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+
+                // SAFETY: the signal is handled by the queue
+                unsafe { libc::raise(libc::SIGTERM) };
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                // Default action restored: a second signal terminates the process
+                // SAFETY: the action set is the one already in place
+                let action = unsafe { libc::signal(libc::SIGTERM, libc::SIG_DFL) };
+                assert_eq!(action, libc::SIG_DFL);
+
+                // Draining: no other job accepted
+                assert_eq!(jq.state(), State::Stopping);
+                assert!(matches!(
+                    *jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap_err(),
+                    Error::Stopped
+                ));
+
+                // Stragglers cancelled once the grace period elapsed
+                tokio::time::sleep(Duration::from_millis(150)).await;
+                assert_eq!(
+                    jq.job_status(&short_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(
+                    jq.job_status(&long_id).await.unwrap(),
+                    Status::Finished(ResultStatus::TimedOut)
+                );
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod async_api {
+        use super::*;
+        use tokio::time::{Duration, Instant};
+
+        #[test]
+        fn start_and_shutdown() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            Runtime::new().unwrap().block_on(async {
+                jq.start_async().await.unwrap();
+                assert!(matches!(
+                    *jq.start_async().await.unwrap_err(),
+                    Error::AlreadyRunning
+                ));
+
+                jq.enqueue(
+                    Job::new(Routines::Record(RecordArgs {
+                        label: "async_api-record".to_string(),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(200),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // The job still running is waited for
+                let started = Instant::now();
+                jq.shutdown().await.unwrap();
+                assert!(started.elapsed() >= Duration::from_millis(100));
+
+                assert_eq!(records("async_api-"), vec!["async_api-record"]);
+            });
+        }
+    }
+
+    mod queue_state {
+        use super::*;
+        use std::sync::Arc;
+        use tokio::time::Duration;
+
+        #[test]
+        fn state_notifications() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            let received = Arc::new(Mutex::new(vec![]));
+            let states = received.clone();
+            jq.subscribe(
+                NotificationFilter::all().notification_type(NotificationType::StateChanged),
+                move |n| {
+                    if let Notification::StateChanged(state) = n {
+                        states.lock().unwrap().push(*state);
+                    }
+                },
+            )
+            .unwrap();
+
+            let idle = Arc::new(Mutex::new(0));
+            let count = idle.clone();
+            jq.subscribe(
+                NotificationFilter::all().notification_type(NotificationType::Idle),
+                move |_| *count.lock().unwrap() += 1,
+            )
+            .unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                assert!(matches!(
+                    *jq.wait_idle().await.unwrap_err(),
+                    Error::NotStarted
+                ));
+            });
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let ids = (0..3)
+                    .map(|_| {
+                        jq.enqueue(
+                            Job::new(Routines::Sleep(SleepArgs {
+                                duration: Duration::from_millis(50),
+                            }))
+                            .unwrap(),
+                        )
+                        .unwrap()
+                    })
+                    .collect::<Vec<_>>();
+
+                // Every job submitted is done once idle
+                jq.wait_idle().await.unwrap();
+
+                for status in jq.job_statuses(&ids).await.unwrap().values() {
+                    assert_eq!(*status, Status::Finished(ResultStatus::Success));
+                }
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                assert_eq!(*idle.lock().unwrap(), 1);
+
+                jq.enqueue(
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(100),
+                    }))
+                    .unwrap(),
+                )
+                .unwrap();
+            });
+
+            jq.stop().unwrap();
+
+            // Never idle once stopped with a job still running
+            Runtime::new().unwrap().block_on(async {
+                assert!(matches!(*jq.wait_idle().await.unwrap_err(), Error::Stopped));
+            });
+
+            jq.join().unwrap();
+
+            assert_eq!(
+                *received.lock().unwrap(),
+                vec![State::Running, State::Stopping, State::Stopped]
+            );
+        }
+    }
+
+    mod eta {
+        use super::*;
+        use std::sync::Arc;
+        use std::time::SystemTime;
+        use tokio::time::Duration;
+
+        #[test]
+        fn estimated_completion() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            let etas = Arc::new(Mutex::new(vec![]));
+            let received = etas.clone();
+            jq.subscribe(
+                NotificationFilter::all().notification_type(NotificationType::Progression),
+                move |n| {
+                    if let Notification::Progression(_, progression) = n {
+                        received.lock().unwrap().push(progression.eta);
+                    }
+                },
+            )
+            .unwrap();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let steps = || {
+                    Job::new(Routines::Steps(StepsArgs {
+                        count: 4,
+                        interval: Duration::from_millis(50),
+                    }))
+                    .unwrap()
+                };
+
+                // Nothing known before a first run
+                let first_id = jq.enqueue(steps()).unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                assert_eq!(jq.job_eta(&first_id).await.unwrap(), None);
+
+                jq.wait_idle().await.unwrap();
+                assert_eq!(jq.job_eta(&first_id).await.unwrap(), None);
+
+                let stats = jq
+                    .with_backend(|backend| async move { backend.duration_stats("Steps") })
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(stats.runs, 1);
+                assert!(stats.average >= Duration::from_millis(200));
+
+                // Extrapolated from the progression of the job
+                let second_id = jq.enqueue(steps()).unwrap();
+                tokio::time::sleep(Duration::from_millis(80)).await;
+
+                let now = SystemTime::now();
+                let eta = jq.job_eta(&second_id).await.unwrap().unwrap();
+                assert!(eta >= now);
+                assert!(eta <= now + Duration::from_millis(300));
+                assert!(jq.job_progression(&second_id).await.unwrap().eta.is_some());
+
+                jq.wait_idle().await.unwrap();
+            });
+
+            // The progression of the second job notified with its completion time
+            assert!(etas.lock().unwrap().iter().any(Option::is_some));
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod watchdog {
+        use super::*;
+        use std::sync::Arc;
+        use tokio::time::Duration;
+
+        #[test]
+        fn expected_durations() {
+            let policy = WatchdogPolicy::new()
+                .expect("Sleep", Duration::from_millis(50))
+                .learn(2.0)
+                .cancel_after(3.0);
+
+            let mut stats = DurationStats::default();
+            stats.record(Duration::from_millis(100));
+
+            assert_eq!(
+                policy.expected("Sleep", Some(&stats)),
+                Some(Duration::from_millis(50))
+            );
+            assert_eq!(
+                policy.expected("Nop", Some(&stats)),
+                Some(Duration::from_millis(200))
+            );
+            assert_eq!(policy.expected("Nop", None), None);
+            assert_eq!(
+                policy.cancel_threshold(Duration::from_millis(50)),
+                Some(Duration::from_millis(150))
+            );
+        }
+
+        #[test]
+        fn stalled_jobs() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .watchdog(
+                    WatchdogPolicy::new()
+                        .expect("Sleep", Duration::from_millis(50))
+                        .cancel_after(3.0),
+                )
+                .build();
+
+            let stalled = Arc::new(Mutex::new(vec![]));
+            let received = stalled.clone();
+            jq.subscribe(
+                NotificationFilter::all().notification_type(NotificationType::JobStalled),
+                move |n| {
+                    if let Notification::JobStalled(id, elapsed) = n {
+                        received.lock().unwrap().push((*id, *elapsed));
+                    }
+                },
+            )
+            .unwrap();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let sleep = |millis| {
+                    Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(millis),
+                    }))
+                    .unwrap()
+                };
+
+                let fast_id = jq.enqueue(sleep(10)).unwrap();
+                let slow_id = jq.enqueue(sleep(100)).unwrap();
+                let hung_id = jq.enqueue(sleep(5000)).unwrap();
+
+                jq.wait_idle().await.unwrap();
+
+                // Flagged once, the hung job cancelled past the threshold
+                let stalled = stalled.lock().unwrap().clone();
+                assert_eq!(stalled.len(), 2);
+                assert!(stalled
+                    .iter()
+                    .all(|(_, elapsed)| *elapsed >= Duration::from_millis(50)));
+
+                let ids = stalled.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+                assert!(!ids.contains(&fast_id));
+                assert!(ids.contains(&slow_id));
+                assert!(ids.contains(&hung_id));
+
+                assert_eq!(
+                    jq.job_status(&slow_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(
+                    jq.job_status(&hung_id).await.unwrap(),
+                    Status::Finished(ResultStatus::TimedOut)
+                );
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod event_log {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn replayable_events() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .event_log(8)
+                .build();
+
+            assert!(matches!(
+                *JobQueueBuilder::<Routines, Context>::new()
+                    .unwrap()
+                    .build()
+                    .events_since(0)
+                    .unwrap_err(),
+                Error::EventLogDisabled
+            ));
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let mut stream = jq.subscribe_events(0).unwrap();
+
+                let job_id = jq
+                    .enqueue(
+                        Job::new(Routines::Steps(StepsArgs {
+                            count: 2,
+                            interval: Duration::from_millis(10),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                jq.wait_idle().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+
+                let events = jq.events_since(0).unwrap();
+                let kinds = events.iter().map(|event| event.event).collect::<Vec<_>>();
+
+                assert!(events.iter().all(|event| event.job_id == job_id));
+                assert!(events.windows(2).all(|pair| pair[0].seq + 1 == pair[1].seq));
+                assert_eq!(kinds.first(), Some(&JobEvent::Enqueued));
+                assert_eq!(kinds.get(1), Some(&JobEvent::Started));
+                assert!(kinds.contains(&JobEvent::Finished(ResultStatus::Success)));
+                assert_eq!(
+                    kinds
+                        .iter()
+                        .filter(|kind| matches!(kind, JobEvent::Progress(_)))
+                        .count(),
+                    3
+                );
+
+                // Polled from the last event synced
+                assert_eq!(jq.events_since(events[3].seq).unwrap(), events[4..]);
+
+                // Streamed live as recorded
+                for event in &events {
+                    assert_eq!(stream.recv().await.as_ref(), Some(event));
+                }
+
+                // Events evicted past the capacity
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                jq.wait_idle().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+
+                assert!(matches!(
+                    *jq.events_since(0).unwrap_err(),
+                    Error::EventsEvicted(_)
+                ));
+                assert_eq!(events.len(), 6);
+                assert_eq!(jq.events_since(events[5].seq).unwrap().len(), 6);
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod trace_context {
+        use super::*;
+        use std::cell::RefCell;
+
+        thread_local! {
+            static CURRENT: RefCell<Option<TraceContext>> = RefCell::new(None);
+        }
+
+        static TRACED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+        const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        /// Propagator of a fake tracing stack, whose current span is thread local.
+        struct Propagator;
+
+        impl TracePropagator for Propagator {
+            fn current(&self) -> Option<TraceContext> {
+                CURRENT.with(|current| current.borrow().clone())
+            }
+
+            fn instrument<'a>(
+                &self,
+                job: &Job,
+                context: &TraceContext,
+                run: RoutineFuture<'a>,
+            ) -> RoutineFuture<'a> {
+                let label = job
+                    .metadata()
+                    .get("label")
+                    .and_then(|label| label.as_str())
+                    .unwrap_or_default()
+                    .to_owned();
+                let parent = context.parent_id().to_owned();
+
+                Box::pin(async move {
+                    TRACED.lock().unwrap().push((label, parent));
+                    run.await
+                })
+            }
+        }
+
+        #[test]
+        fn parse() {
+            let context = TraceContext::new(TRACEPARENT).unwrap().with_state("k=v");
+
+            assert_eq!(context.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+            assert_eq!(context.parent_id(), "00f067aa0ba902b7");
+            assert!(context.is_sampled());
+            assert_eq!(context.tracestate(), Some("k=v"));
+
+            for invalid in ["", "00-4bf9-00f0-01", &TRACEPARENT.to_uppercase()] {
+                assert!(matches!(
+                    *TraceContext::new(invalid).unwrap_err(),
+                    Error::InvalidTraceContext(_)
+                ));
+            }
+        }
+
+        #[test]
+        fn propagation() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .trace_propagator(Propagator)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = |label: &str| {
+                    let mut job = Job::new(Routines::Nop).unwrap();
+                    job.set_metadata("label", label.into());
+                    job
+                };
+
+                // Outside of a span
+                let untraced_id = jq.enqueue(job("untraced")).unwrap();
+
+                // Within the span of a request
+                CURRENT.with(|current| {
+                    *current.borrow_mut() = Some(TraceContext::new(TRACEPARENT).unwrap())
+                });
+                let traced_id = jq.enqueue(job("traced")).unwrap();
+                CURRENT.with(|current| *current.borrow_mut() = None);
+
+                jq.wait_idle().await.unwrap();
+
+                let traced = jq.job(&traced_id).await.unwrap();
+                assert_eq!(traced.trace_context().unwrap().traceparent(), TRACEPARENT);
+                assert!(jq
+                    .job(&untraced_id)
+                    .await
+                    .unwrap()
+                    .trace_context()
+                    .is_none());
+
+                assert_eq!(
+                    *TRACED.lock().unwrap(),
+                    vec![("traced".to_owned(), "00f067aa0ba902b7".to_owned())]
+                );
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[cfg(feature = "otel")]
+        #[test]
+        fn otel_propagation() {
+            use opentelemetry::trace::{
+                SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+            };
+
+            let context = TraceContext::new(TRACEPARENT).unwrap();
+            let span_context = SpanContext::new(
+                TraceId::from_hex(context.trace_id()).unwrap(),
+                SpanId::from_hex(context.parent_id()).unwrap(),
+                TraceFlags::SAMPLED,
+                true,
+                TraceState::default(),
+            );
+
+            // Outside of a span
+            assert!(OtelPropagator.current().is_none());
+
+            // Within the span of a request
+            {
+                let _guard = opentelemetry::Context::new()
+                    .with_remote_span_context(span_context)
+                    .attach();
+
+                assert_eq!(OtelPropagator.current(), Some(context.clone()));
+            }
+
+            // Parent restored while the job runs
+            let job = Job::new(Routines::Nop).unwrap();
+            let run = OtelPropagator.instrument(
+                &job,
+                &context,
+                Box::pin(async {
+                    let current = opentelemetry::Context::current();
+
+                    Ok(current
+                        .span()
+                        .span_context()
+                        .span_id()
+                        .to_string()
+                        .into_bytes())
+                }),
+            );
+
+            assert_eq!(
+                Runtime::new().unwrap().block_on(run).unwrap(),
+                b"00f067aa0ba902b7"
+            );
+        }
+    }
+
+    mod result_streaming {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn stream_chunks() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = Job::new_with_expire(
+                    Routines::Stream(StepsArgs {
+                        count: 4,
+                        interval: Duration::from_millis(30),
+                    }),
+                    ExpirePolicy::Manual,
+                )
+                .unwrap();
+                let job_id = jq.enqueue(job).unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+
+                // Chunks received while the job is running
+                let mut stream = jq.stream_result(&job_id).await.unwrap();
+                assert_eq!(stream.recv().await.unwrap().unwrap(), vec![1]);
+                assert!(matches!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Running
+                ));
+
+                let mut chunks = vec![vec![1]];
+                while let Some(chunk) = stream.recv().await {
+                    chunks.push(chunk.unwrap());
+                }
+
+                // Stream closed once finished, without missing the last chunks
+                assert_eq!(chunks, vec![vec![1], vec![2], vec![3], vec![4]]);
+                assert_eq!(
+                    jq.job_status(&job_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+
+                // Stored chunks replayed to the late streams
+                let mut replay = jq.stream_result(&job_id).await.unwrap();
+                let mut replayed = vec![];
+                while let Some(chunk) = replay.recv().await {
+                    replayed.push(chunk.unwrap());
+                }
+                assert_eq!(replayed, chunks);
+
+                // Unknown job
+                assert!(jq.stream_result(&Uuid::nil()).await.is_err());
+            });
+
+            // Chunks only emitted by the jobs run by a queue
+            let job = Job::new(Routines::Nop).unwrap();
+            assert!(matches!(
+                *job.emit_chunk(vec![]).unwrap_err(),
+                Error::JobNotRunning(_)
+            ));
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod custom_commands {
+        use super::*;
+        use std::sync::Arc;
+        use tokio::time::Duration;
+
+        #[test]
+        fn publish_signals() {
+            let hooked = Arc::new(Mutex::new(vec![]));
+            let received = hooked.clone();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .on_command(move |job, name, payload| {
+                    received
+                        .lock()
+                        .unwrap()
+                        .push((job.id(), name.to_owned(), payload.to_vec()))
+                })
+                .build();
+
+            let notified = Arc::new(Mutex::new(vec![]));
+            let received = notified.clone();
+            jq.subscribe(
+                NotificationFilter::all().notification_type(NotificationType::Custom),
+                move |n| {
+                    if let Notification::Custom(id, name, payload) = n {
+                        received
+                            .lock()
+                            .unwrap()
+                            .push((*id, name.clone(), payload.clone()));
+                    }
+                },
+            )
+            .unwrap();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let job_id = Runtime::new().unwrap().block_on(async {
+                let job_id = jq.enqueue(Job::new(Routines::Publish).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                job_id
+            });
+
+            // Signal handed to the hook and the subscribers
+            let expected = vec![(job_id, "file_ready".to_owned(), b"/tmp/report.csv".to_vec())];
+            assert_eq!(*hooked.lock().unwrap(), expected);
+            assert_eq!(*notified.lock().unwrap(), expected);
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod running_metadata {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn set_from_routine() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let mut job = Job::new_with_expire(
+                    Routines::Annotate(SleepArgs {
+                        duration: Duration::from_millis(100),
+                    }),
+                    ExpirePolicy::Manual,
+                )
+                .unwrap();
+                job.set_metadata("origin", serde_json::json!("test"));
+                let job_id = jq.enqueue(job).unwrap();
+
+                // Queryable while the job is running
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                assert_eq!(jq.job_status(&job_id).await.unwrap(), Status::Running);
+
+                let metadata = jq.job_metadata(&job_id).await.unwrap();
+                assert_eq!(metadata["origin"], "test");
+                assert_eq!(metadata["rows"], 42);
+
+                // Kept once finished
+                jq.wait_idle().await.unwrap();
+                assert_eq!(jq.job_metadata(&job_id).await.unwrap()["rows"], 42);
+
+                assert!(jq.job_metadata(&Uuid::nil()).await.is_err());
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod priority_channel {
+        use super::*;
+        use crate::channel;
+        use crate::test_util::{BackendCall, RecordingBackend};
+        use std::sync::mpsc::RecvTimeoutError;
+        use tokio::time::Duration;
+
+        #[test]
+        fn commands_first() {
+            let (tx, rx) = channel::channel();
+
+            let first = Job::new(Routines::Nop).unwrap();
+            let second = Job::new(Routines::Nop).unwrap();
+            let (first_id, second_id) = (first.id(), second.id());
+
+            tx.send(Message::Job(Box::new(first))).unwrap();
+            tx.send(Message::Job(Box::new(second))).unwrap();
+            tx.send(Message::Command(Cmd::SetStep(first_id, 1)))
+                .unwrap();
+            tx.clone().send(Message::Command(Cmd::Stop)).unwrap();
+
+            // Commands overtaking the jobs, each kept in order
+            assert!(rx.recv().unwrap() == Message::Command(Cmd::SetStep(first_id, 1)));
+            assert!(rx.recv().unwrap() == Message::Command(Cmd::Stop));
+            assert!(matches!(rx.recv().unwrap(), Message::Job(job) if job.id() == first_id));
+            assert!(matches!(
+                rx.recv_timeout(Duration::from_millis(10)).unwrap(),
+                Message::Job(job) if job.id() == second_id
+            ));
+
+            assert!(matches!(
+                rx.recv_timeout(Duration::from_millis(10)),
+                Err(RecvTimeoutError::Timeout)
+            ));
+
+            // Closed once the senders are dropped
+            drop(tx);
+            assert!(rx.recv().is_err());
+        }
+
+        #[test]
+        fn stop_keeps_accepted_jobs() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .backend(backend)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let job_ids = (0..500)
+                .map(|_| jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap())
+                .collect::<Vec<_>>();
+
+            // Stop overtaking the jobs waiting, still stored before the thread exits
+            jq.stop().unwrap();
+            jq.join().unwrap();
+
+            let calls = recorder.calls();
+            for job_id in job_ids {
+                assert!(calls.contains(&BackendCall::Schedule(job_id)));
+            }
+        }
+    }
+
+    mod context_modes {
+        use super::*;
+        use std::sync::Arc;
+
+        #[derive(Serialize, Deserialize)]
+        pub struct Settings {
+            name: String,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        pub struct ReadSettings;
+
+        #[async_trait]
+        impl Routine<Settings> for ReadSettings {
+            async fn call(
+                &self,
+                _job: &Job,
+                _messages_channel: SharedMessageChannel,
+                _context: Option<Shared<Settings>>,
+            ) -> Result<Vec<u8>, Error> {
+                Ok(b"EXCLUSIVE".to_vec())
+            }
+
+            async fn call_with_context(
+                &self,
+                job: &Job,
+                messages_channel: SharedMessageChannel,
+                context: Option<ContextMode<Settings>>,
+            ) -> Result<Vec<u8>, Error> {
+                match context {
+                    Some(ContextMode::ReadOnly(settings)) => Ok(settings.name.clone().into_bytes()),
+                    Some(ContextMode::ReadWrite(settings)) => {
+                        settings.write().unwrap().name.push_str("_WRITTEN");
+
+                        Ok(settings.read().unwrap().name.clone().into_bytes())
+                    }
+                    context => {
+                        let context = context.and_then(ContextMode::into_exclusive);
+
+                        self.call(job, messages_channel, context).await
+                    }
+                }
+            }
+        }
+
+        fn run(mode: ContextMode<Settings>) -> Vec<u8> {
+            let mut jq = JobQueueBuilder::<ReadSettings, Settings>::new()
+                .unwrap()
+                .context_mode(mode)
+                .build();
+
+            jq.start().unwrap();
+
+            let result = Runtime::new().unwrap().block_on(async {
+                let job = Job::new_with_expire(ReadSettings, ExpirePolicy::Manual).unwrap();
+                let job_id = jq.enqueue(job).unwrap();
+
+                jq.wait_idle().await.unwrap();
+
+                jq.job_result(&job_id).await.unwrap()
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+
+            result
+        }
+
+        #[test]
+        fn read_only() {
+            let settings = Arc::new(Settings {
+                name: "UNIT_TESTING".to_string(),
+            });
+
+            assert_eq!(
+                run(ContextMode::ReadOnly(settings.clone().into())),
+                b"UNIT_TESTING"
+            );
+
+            // Same instance shared, without any lock
+            assert_eq!(Arc::strong_count(&settings), 1);
+        }
+
+        #[test]
+        fn read_write() {
+            let mode = ContextMode::read_write(Settings {
+                name: "UNIT_TESTING".to_string(),
+            });
+
+            assert_eq!(run(mode.clone()), b"UNIT_TESTING_WRITTEN");
+
+            let settings = mode.into_read_write().unwrap();
+            assert_eq!(settings.read().unwrap().name, "UNIT_TESTING_WRITTEN");
+        }
+
+        #[test]
+        fn exclusive() {
+            let mode = ContextMode::exclusive(Settings {
+                name: "UNIT_TESTING".to_string(),
+            });
+
+            assert!(mode.clone().into_read_only().is_none());
+            assert_eq!(run(mode), b"EXCLUSIVE");
+        }
+
+        /// Context that can be sent to another thread but not shared.
+        pub struct Counter(std::cell::Cell<u32>);
+
+        #[derive(Serialize, Deserialize)]
+        pub struct Count;
+
+        #[async_trait]
+        impl Routine<Counter> for Count {
+            async fn call(
+                &self,
+                _job: &Job,
+                _messages_channel: SharedMessageChannel,
+                context: Option<Shared<Counter>>,
+            ) -> Result<Vec<u8>, Error> {
+                let context = context.unwrap();
+                let counter = context.lock().unwrap();
+
+                counter.0.set(counter.0.get() + 1);
+
+                Ok(counter.0.get().to_string().into_bytes())
+            }
+        }
+
+        #[test]
+        fn exclusive_not_sync() {
+            let mut jq = JobQueueBuilder::<Count, Counter>::new()
+                .unwrap()
+                .context(Counter(std::cell::Cell::new(0)))
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = Job::new_with_expire(Count, ExpirePolicy::Manual).unwrap();
+                let job_id = jq.enqueue(job).unwrap();
+
+                jq.wait_idle().await.unwrap();
+
+                assert_eq!(jq.job_result(&job_id).await.unwrap(), b"1");
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn built_by_factory() {
+            let mut jq = JobQueueBuilder::<ReadSettings, Settings>::new()
+                .unwrap()
+                .context_factory(|| async {
+                    // Built on the runtime of the queue
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+                    Ok(ContextMode::read_only(Settings {
+                        name: "BUILT".to_string(),
+                    }))
+                })
+                .build();
+
+            let rt = Runtime::new().unwrap();
+
+            rt.block_on(async {
+                jq.start_async().await.unwrap();
+
+                let job = Job::new_with_expire(ReadSettings, ExpirePolicy::Manual).unwrap();
+                let job_id = jq.enqueue(job).unwrap();
+
+                jq.wait_idle().await.unwrap();
+
+                assert_eq!(jq.job_result(&job_id).await.unwrap(), b"BUILT");
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+
+            // Queue not started if the context can't be built
+            let mut jq = JobQueueBuilder::<ReadSettings, Settings>::new()
+                .unwrap()
+                .context_factory(|| async {
+                    Err::<Settings, _>(api_err!(Error::Custom("Unreachable database".to_string())))
+                })
+                .build();
+
+            assert!(jq.start().is_err());
+            assert_eq!(jq.state(), State::Idle);
+        }
+
+        #[test]
+        fn per_job() {
+            let mut jq = JobQueueBuilder::<ReadSettings, Settings>::new()
+                .unwrap()
+                .context_mode(ContextMode::read_only(Settings {
+                    name: "GLOBAL".to_string(),
+                }))
+                .context_resolver(replace_context)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = Job::new_with_expire(ReadSettings, ExpirePolicy::Manual)
+                    .unwrap()
+                    .with_context(serde_json::json!({ "name": "TENANT" }))
+                    .unwrap();
+                let tenant_id = jq.enqueue(job).unwrap();
+
+                let job = Job::new_with_expire(ReadSettings, ExpirePolicy::Manual).unwrap();
+                let global_id = jq.enqueue(job).unwrap();
+
+                // Invalid context failing the job
+                let job = Job::new_with_expire(ReadSettings, ExpirePolicy::Manual)
+                    .unwrap()
+                    .with_context(42)
+                    .unwrap();
+                let invalid_id = jq.enqueue(job).unwrap();
+
+                jq.wait_idle().await.unwrap();
+
+                assert_eq!(jq.job_result(&tenant_id).await.unwrap(), b"TENANT");
+                assert_eq!(jq.job_result(&global_id).await.unwrap(), b"GLOBAL");
+                assert_eq!(
+                    jq.job_status(&invalid_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn merged_per_job() {
+            let mut jq = JobQueueBuilder::<ReadSettings, Settings>::new()
+                .unwrap()
+                .context_mode(ContextMode::read_only(Settings {
+                    name: "GLOBAL".to_string(),
+                }))
+                .context_resolver(|global, patch| {
+                    let global = global
+                        .cloned()
+                        .and_then(ContextMode::into_read_only)
+                        .unwrap();
+                    let suffix = patch["suffix"].as_str().unwrap_or_default();
+
+                    Ok(ContextMode::read_only(Settings {
+                        name: format!("{}_{}", global.name, suffix),
+                    }))
+                })
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let job = Job::new_with_expire(ReadSettings, ExpirePolicy::Manual)
+                    .unwrap()
+                    .with_context(serde_json::json!({ "suffix": "TENANT" }))
+                    .unwrap();
+                assert_eq!(job.context::<Value>().unwrap().unwrap()["suffix"], "TENANT");
+                let job_id = jq.enqueue(job).unwrap();
+
+                jq.wait_idle().await.unwrap();
+
+                assert_eq!(jq.job_result(&job_id).await.unwrap(), b"GLOBAL_TENANT");
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod job_ids {
+        use super::*;
+
+        #[test]
+        fn supplied_id() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let id = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+                let job = Job::new_with_id(id, Routines::Publish).unwrap();
+                assert_eq!(job.id(), id);
+
+                assert_eq!(jq.enqueue(job).unwrap(), id);
+
+                jq.wait_idle().await.unwrap();
+                assert!(matches!(
+                    jq.job_status(&id).await.unwrap(),
+                    Status::Finished(_)
+                ));
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn duplicate_id() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let id = Uuid::from_u128(0xfedc_ba98_7654_3210_fedc_ba98_7654_3210);
+                let sleep = || {
+                    Job::new_with_id(
+                        id,
+                        Routines::Sleep(SleepArgs {
+                            duration: std::time::Duration::from_millis(50),
+                        }),
+                    )
+                    .unwrap()
+                };
+
+                jq.enqueue(sleep()).unwrap();
+
+                // Job with the same ID still in the queue
+                assert!(matches!(
+                    *jq.enqueue(sleep()).unwrap_err(),
+                    Error::DuplicateJobId(duplicate) if duplicate == id
+                ));
+
+                // Job with the same ID stored once finished, left as it is
+                jq.wait_idle().await.unwrap();
+                assert!(matches!(
+                    *jq.enqueue_async(sleep()).await.unwrap_err(),
+                    Error::DuplicateJobId(_)
+                ));
+                assert!(matches!(
+                    *jq.enqueue(sleep()).unwrap_err(),
+                    Error::DuplicateJobId(_)
+                ));
+                assert_eq!(
+                    jq.job_status(&id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn time_sortable() {
+            let generator = UuidV7Generator::default();
+            let ids = (0..1000).map(|_| generator.generate()).collect::<Vec<_>>();
+
+            let mut sorted = ids.clone();
+            sorted.sort();
+            assert_eq!(ids, sorted);
+            assert!(ids.iter().all(|id| id.get_version_num() == 7));
+
+            // Generator of the queue replacing the IDs of the jobs enqueued
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .id_generator(UuidV7Generator::default())
+                .build();
+
+            jq.start().unwrap();
+
+            let ids = (0..10)
+                .map(|_| jq.enqueue(Job::new(Routines::Publish).unwrap()).unwrap())
+                .collect::<Vec<_>>();
+            let token = jq.prepare(Job::new(Routines::Publish).unwrap()).unwrap();
+            let staged = token.id();
+            let supplied = Uuid::from_u128(7);
+
+            let mut sorted = ids.clone();
+            sorted.sort();
+            assert_eq!(ids, sorted);
+            assert!(ids.iter().all(|id| id.get_version_num() == 7));
+            assert_eq!(staged.get_version_num(), 7);
+            assert_eq!(jq.confirm(token).unwrap(), staged);
+            assert_eq!(
+                jq.enqueue(Job::new_with_id(supplied, Routines::Publish).unwrap())
+                    .unwrap(),
+                supplied
+            );
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+
+            // Other queues left with the default generator
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+            assert_eq!(
+                jq.enqueue(Job::new(Routines::Publish).unwrap())
+                    .unwrap()
+                    .get_version_num(),
+                1
+            );
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod pagination {
+        use super::*;
+        use crate::test_util::{BackendCall, RecordingBackend};
+
+        #[test]
+        fn cursor_pages() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .backend(backend)
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // IDs not sorted like the enqueue times
+                let mut ids = vec![];
+
+                for n in (0..5u128).rev() {
+                    let job = Job::new_with_id(Uuid::from_u128(n + 1), Routines::Publish).unwrap();
+                    ids.push(jq.enqueue(job).unwrap());
+                    tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+                }
+
+                jq.wait_idle().await.unwrap();
+
+                let listed = jq.jobs().await.unwrap();
+                assert_eq!(listed.iter().map(|job| job.id).collect::<Vec<_>>(), ids);
+
+                let mut paged = vec![];
+                let mut cursor = None;
+
+                loop {
+                    let page = jq.jobs_page(cursor.as_ref(), 2).await.unwrap();
+                    assert!(page.jobs.len() <= 2);
+
+                    paged.extend(page.jobs.iter().map(|job| job.id));
+
+                    // Cursor handed over as a string
+                    match page.next {
+                        Some(next) => cursor = Some(next.to_string().parse::<Cursor>().unwrap()),
+                        None => break,
+                    }
+                }
+
+                assert_eq!(paged, ids);
+
+                // Pagination pushed down to the backend
+                assert!(recorder.calls().contains(&BackendCall::JobsPage(None, 2)));
+
+                assert!("invalid".parse::<Cursor>().is_err());
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod aggregation {
+        use super::*;
+        use crate::test_util::{BackendCall, RecordingBackend};
+
+        #[test]
+        fn count_and_group() {
+            let backend = RecordingBackend::new();
+            let recorder = backend.recorder();
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .backend(backend)
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                for _ in 0..3 {
+                    let mut job =
+                        Job::new_with_expire(Routines::Nop, ExpirePolicy::Manual).unwrap();
+                    job.add_tag("nightly");
+                    jq.enqueue(job).unwrap();
+                }
+
+                for _ in 0..2 {
+                    let job =
+                        Job::new_with_expire(Routines::RaiseError, ExpirePolicy::Manual).unwrap();
+                    jq.enqueue(job).unwrap();
+                }
+
+                jq.wait_idle().await.unwrap();
+
+                assert_eq!(jq.count_jobs(&JobFilter::default()).await.unwrap(), 5);
+                assert_eq!(
+                    jq.count_jobs(&JobFilter::default().tag("nightly"))
+                        .await
+                        .unwrap(),
+                    3
+                );
+                assert_eq!(
+                    jq.count_jobs(
+                        &JobFilter::default()
+                            .kind("RaiseError")
+                            .status(Status::Finished(ResultStatus::Error))
+                    )
+                    .await
+                    .unwrap(),
+                    2
+                );
+
+                let kinds = jq
+                    .aggregate_jobs(&JobFilter::default(), GroupBy::Kind)
+                    .await
+                    .unwrap();
+                assert_eq!(kinds[&GroupKey::Kind("Nop".to_string())], 3);
+                assert_eq!(kinds[&GroupKey::Kind("RaiseError".to_string())], 2);
+
+                // Stats counted by the backend, from the jobs it lists by default
+                let stats = jq.stats().await.unwrap();
+                assert_eq!((stats.succeeded, stats.failed), (3, 2));
+
+                let calls = recorder.calls();
+                assert!(calls.contains(&BackendCall::Jobs));
+                assert!(!calls.contains(&BackendCall::JobInfos));
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod result_retention {
+        use super::*;
+        use std::time::Duration;
+
+        #[test]
+        fn results_expire_before_jobs() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .result_ttl("Nop", Duration::from_millis(50))
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Result of the kind expiring
+                let job = Job::new_with_expire(Routines::Nop, ExpirePolicy::Manual).unwrap();
+                let kind_id = jq.enqueue(job).unwrap();
+
+                // Result of the job expiring
+                let mut job =
+                    Job::new_with_expire(Routines::Publish, ExpirePolicy::Manual).unwrap();
+                job.set_result_ttl(Duration::from_millis(50));
+                let job_id = jq.enqueue(job).unwrap();
+
+                // Result kept as long as the job
+                let job = Job::new_with_expire(Routines::Publish, ExpirePolicy::Manual).unwrap();
+                let kept_id = jq.enqueue(job).unwrap();
+
+                jq.wait_idle().await.unwrap();
+                assert!(jq.peek_result(&kind_id).await.is_ok());
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                for id in [kind_id, job_id] {
+                    assert!(matches!(
+                        *jq.job_result(&id).await.unwrap_err(),
+                        Error::ResultExpired(expired) if expired == id
+                    ));
+
+                    // Job itself kept
+                    assert_eq!(
+                        jq.job_status(&id).await.unwrap(),
+                        Status::Finished(ResultStatus::Success)
+                    );
+                }
+
+                assert!(jq.job_result(&kept_id).await.is_ok());
+
+                // Results dropped by the backend, keeping the jobs
+                let expired = jq
+                    .with_backend(|mut backend| async move { backend.expire_results() })
+                    .await
+                    .unwrap();
+                assert!(expired.iter().all(|(id, _)| [kind_id, job_id].contains(id)));
+
+                let job = jq.job(&kind_id).await.unwrap();
+                assert!(job.is_result_dropped());
+                assert!(job.result().is_empty());
+            });
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+    }
+
+    mod idempotency {
+        use super::*;
+        use tokio::time::Duration;
+
+        fn keyed(routine: Routines, key: &str) -> Job {
+            let mut job = Job::new(routine).unwrap();
+
+            job.set_idempotency_key(key);
+
+            job
+        }
+
+        #[test]
+        fn idempotency_keys() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .idempotency_window(Duration::from_millis(300))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let spin = || Routines::Spin(SpinArgs { iterations: 10 });
+
+                // Duplicate of a job not completed yet
+                let original = jq.enqueue(keyed(spin(), "invoice-1")).unwrap();
+                let duplicate = jq.enqueue_idempotent(keyed(spin(), "invoice-1")).unwrap();
+                assert_eq!(duplicate.id, original);
+                assert!(duplicate.duplicate);
+                assert!(duplicate.cached_result.is_none());
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                // Duplicate of a completed job, with its cached result
+                let duplicate = jq.enqueue_idempotent(keyed(spin(), "invoice-1")).unwrap();
+                assert_eq!(duplicate.id, original);
+                assert_eq!(duplicate.cached_result, Some(45u64.to_le_bytes().to_vec()));
+
+                // Even once the result of the original job has been consumed
+                jq.job_result(&original).await.unwrap();
+                assert_eq!(jq.enqueue(keyed(spin(), "invoice-1")).unwrap(), original);
+
+                // A failed job can be submitted again
+                let failed = jq
+                    .enqueue(keyed(Routines::RaiseError, "invoice-2"))
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_ne!(
+                    jq.enqueue(keyed(Routines::Nop, "invoice-2")).unwrap(),
+                    failed
+                );
+
+                // As well as a job completed out of the window
+                tokio::time::sleep(Duration::from_millis(400)).await;
+
+                let resubmitted = jq.enqueue_idempotent(keyed(spin(), "invoice-1")).unwrap();
+                assert_ne!(resubmitted.id, original);
+                assert!(!resubmitted.duplicate);
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod checkpoint {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::sync::Arc;
+        use tokio::sync::Mutex as AsyncMutex;
+        use tokio::time::Duration;
+
+        #[test]
+        fn resume_from_checkpoint() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .shared_backend(backend.clone())
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // The first attempt crashes after two steps
+                let id = jq.enqueue(Job::new(Routines::Resumable).unwrap()).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert_eq!(
+                    jq.job_status(&id).await.unwrap(),
+                    Status::Finished(ResultStatus::Error)
+                );
+
+                let job = backend.lock().await.get(&id).await.unwrap();
+                assert_eq!(job.checkpoint(), Some(&[2u8][..]));
+
+                // The retry resumes from the last checkpoint
+                let retried = jq.retry_job(&id).await.unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert_eq!(
+                    jq.job_status(&retried).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(records("RESUMED_FROM_"), vec!["RESUMED_FROM_2"]);
+
+                // The failed attempt kept in the history of the retry
+                let attempts = jq.job_attempts(&retried).await.unwrap();
+                let statuses: Vec<ResultStatus> =
+                    attempts.iter().map(|attempt| attempt.status).collect();
+
+                assert_eq!(statuses, vec![ResultStatus::Error, ResultStatus::Success]);
+                assert_eq!(attempts[0].error.as_deref(), Some("Crashed"));
+                assert_eq!(attempts[1].error, None);
+                assert!(attempts[0].finished <= attempts[1].started);
+                assert!(attempts
+                    .iter()
+                    .all(|attempt| attempt.worker.as_deref() == Some(jq.worker_id())));
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod drain {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::sync::Arc;
+        use tokio::sync::Mutex as AsyncMutex;
+        use tokio::time::Duration;
+
+        #[test]
+        fn drain_by_priority() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .shared_backend(backend.clone())
+                .max_in_flight(1)
+                .drain_timeout(Duration::from_millis(400))
+                .build();
+
+            let cancelled = Arc::new(Mutex::new(vec![]));
+            let received = cancelled.clone();
+
+            jq.subscribe(NotificationFilter::all(), move |n| {
+                if let Notification::Status(id, Status::Finished(ResultStatus::TimedOut)) = n {
+                    received.lock().unwrap().push(*id);
+                }
+            })
+            .unwrap();
+
+            // Start queue
+            jq.start().unwrap();
+
+            let sleep = |millis| {
+                Job::new(Routines::Sleep(SleepArgs {
+                    duration: Duration::from_millis(millis),
+                }))
+                .unwrap()
+            };
+
+            // Running while the other ones wait for the slot (enqueue order)
+            let [first, low, long, high] = [
+                (sleep(100), 0),
+                (record("DRAIN_LOW"), 0),
+                (sleep(2_000), 5),
+                (record("DRAIN_HIGH"), 10),
+            ]
+            .map(|(mut job, priority)| {
+                job.set_priority(priority);
+                jq.enqueue(job).unwrap()
+            });
+
+            // The most urgent jobs run first, the remaining ones are cancelled on timeout
+            jq.stop().unwrap();
+            jq.join().unwrap();
+
+            assert_eq!(records("DRAIN_"), vec!["DRAIN_HIGH"]);
+            assert_eq!(*cancelled.lock().unwrap(), vec![low, long]);
+
+            Runtime::new().unwrap().block_on(async {
+                let backend = backend.lock().await;
+
+                for (id, status) in [
+                    (first, ResultStatus::Success),
+                    (high, ResultStatus::Success),
+                    (low, ResultStatus::TimedOut),
+                    (long, ResultStatus::TimedOut),
+                ] {
+                    assert_eq!(backend.status(&id).unwrap(), Status::Finished(status));
+                }
+            });
+        }
+    }
+
+    mod suspend {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn suspend_and_resume() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let id = jq
+                    .enqueue(
+                        Job::new(Routines::Steps(StepsArgs {
+                            count: 10,
+                            interval: Duration::from_millis(20),
+                        }))
+                        .unwrap(),
+                    )
+                    .unwrap();
+
+                tokio::time::sleep(Duration::from_millis(60)).await;
+
+                // Parked at its next suspension point
+                jq.suspend_job(&id).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+
+                assert_eq!(jq.job_status(&id).await.unwrap(), Status::Suspended);
+                let step = jq.job_progression(&id).await.unwrap().step;
+                assert!(step < 10);
+
+                tokio::time::sleep(Duration::from_millis(150)).await;
+
+                assert_eq!(jq.job_status(&id).await.unwrap(), Status::Suspended);
+                assert_eq!(jq.job_progression(&id).await.unwrap().step, step);
+
+                // Resumed where it stopped
+                jq.resume_job(&id).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(20)).await;
+
+                assert_eq!(jq.job_status(&id).await.unwrap(), Status::Running);
+
+                tokio::time::sleep(Duration::from_millis(400)).await;
+
+                assert_eq!(
+                    jq.job_status(&id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(jq.job_progression(&id).await.unwrap().step, 10);
+
+                // Only a running job can be suspended
+                jq.suspend_job(&id).unwrap();
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod pause {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::sync::Arc;
+        use tokio::sync::Mutex as AsyncMutex;
+        use tokio::time::Duration;
+
+        #[test]
+        fn pause_every_worker() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let worker = || {
+                let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                    .unwrap()
+                    .notification_handler(notification_handler)
+                    .shared_backend(backend.clone())
+                    .distributed(Duration::from_millis(20))
+                    .build();
+
+                jq.start().unwrap();
+
+                jq
+            };
+
+            let first = Arc::new(worker());
+            let mut second = worker();
+            let client =
+                JobQueueClient::new(LocalTransport::new(JobQueueService::new(first.clone())));
+
+            Runtime::new().unwrap().block_on(async {
+                let record = |label: &str| {
+                    Job::new(Routines::Record(RecordArgs {
+                        label: label.to_string(),
+                    }))
+                    .unwrap()
+                };
+
+                // Paused from the control plane of one worker, honored by both
+                client.pause().await.unwrap();
+                assert!(second.is_paused().await.unwrap());
+
+                let first_id = first.enqueue(record("PAUSE_FIRST")).unwrap();
+                let second_id = second.enqueue(record("PAUSE_SECOND")).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert!(records("PAUSE_").is_empty());
+                assert_eq!(first.job_status(&first_id).await.unwrap(), Status::Ready);
+                assert_eq!(second.job_status(&second_id).await.unwrap(), Status::Ready);
+
+                // Resumed by the other worker
+                second.resume().await.unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                let mut recorded = records("PAUSE_");
+                recorded.sort();
+                assert_eq!(recorded, vec!["PAUSE_FIRST", "PAUSE_SECOND"]);
+
+                second.stop().unwrap();
+            });
+
+            // Stop the job queues
+            drop(client);
+            let mut first = Arc::try_unwrap(first).ok().unwrap();
+            first.stop().unwrap();
+            first.join().unwrap();
+            second.join().unwrap();
+        }
+    }
+
+    mod resize_pool {
+        use super::*;
+        use tokio::time::Duration;
+
+        async fn running(jq: &JobQueue<Routines, Context>, ids: &[Uuid]) -> usize {
+            let mut count = 0;
+
+            for id in ids {
+                if jq.job_status(id).await.unwrap() == Status::Running {
+                    count += 1;
+                }
+            }
+
+            count
+        }
+
+        #[test]
+        fn resize_pool() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .max_in_flight(1)
+                .build();
+
+            // Only resizable once started
+            assert!(matches!(*jq.resize_pool(4).unwrap_err(), Error::NotStarted));
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let mut ids = vec![];
+
+                for _ in 0..4 {
+                    let job = Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(300),
+                    }))
+                    .unwrap();
+
+                    ids.push(jq.enqueue(job).unwrap());
+                }
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert_eq!(running(&jq, &ids).await, 1);
+
+                // Pending jobs dispatched as soon as the pool grows
+                jq.resize_pool(4).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert_eq!(running(&jq, &ids).await, 4);
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod kinds {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+        use std::sync::Arc;
+        use tokio::sync::Mutex as AsyncMutex;
+        use tokio::time::Duration;
+
+        #[test]
+        fn local_rejection() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .kinds(KindFilter::deny(["Record"]))
+                .build();
+
+            jq.start().unwrap();
+
+            let err = jq.enqueue(record("KINDS_LOCAL")).unwrap_err();
+            assert!(matches!(*err, Error::KindNotAccepted(kind) if kind == "Record"));
+
+            assert!(jq.enqueue(Job::new(Routines::Nop).unwrap()).is_ok());
+
+            jq.stop().unwrap();
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn heterogeneous_fleet() {
+            let backend: SharedBackend<Routines, Context> =
+                Arc::new(AsyncMutex::new(Box::new(MemoryBackend::new())));
+
+            let worker = |kinds: KindFilter| {
+                let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                    .unwrap()
+                    .notification_handler(notification_handler)
+                    .shared_backend(backend.clone())
+                    .distributed(Duration::from_millis(20))
+                    .kinds(kinds)
+                    .build();
+
+                jq.start().unwrap();
+
+                jq
+            };
+
+            let mut small = worker(KindFilter::allow(["Nop"]));
+            let mut large = worker(KindFilter::deny(["Nop"]));
+
+            Runtime::new().unwrap().block_on(async {
+                // Enqueued by a worker not executing the kind, claimed by the other one
+                let id = small.enqueue(record("KINDS_ROUTED")).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert_eq!(records("KINDS_ROUTED"), vec!["KINDS_ROUTED"]);
+                assert_eq!(
+                    small.job_status(&id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+            });
+
+            large.stop().unwrap();
+            large.join().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                // Left ready while no worker executes the kind
+                let id = small.enqueue(record("KINDS_LEFT")).unwrap();
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                assert!(records("KINDS_LEFT").is_empty());
+                assert_eq!(small.job_status(&id).await.unwrap(), Status::Ready);
+
+                small.stop().unwrap();
+            });
+
+            small.join().unwrap();
+        }
+    }
+
+    mod autoscale {
+        use super::*;
+        use tokio::time::Duration;
+
+        #[test]
+        fn scale() {
+            let policy = AutoscalePolicy::new(2, 8, 4);
+
+            assert_eq!(policy.scale(2, 3), 2);
+            assert_eq!(policy.scale(2, 7), 5);
+            assert_eq!(policy.scale(5, 20), 8);
+            assert_eq!(policy.scale(5, 0), 4);
+            assert_eq!(policy.scale(2, 0), 2);
+        }
+
+        #[test]
+        fn scale_with_depth() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .autoscale(AutoscalePolicy::new(1, 3, 1))
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                for _ in 0..5 {
+                    let job = Job::new(Routines::Sleep(SleepArgs {
+                        duration: Duration::from_millis(100),
+                    }))
+                    .unwrap();
+
+                    jq.enqueue(job).unwrap();
+                }
+
+                // Scaled up while jobs are waiting, capped by the maximum
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert_eq!(*SCALED.lock().unwrap(), vec![(1, 2), (2, 3)]);
+
+                // Scaled down to the minimum once nothing is waiting
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                assert_eq!(SCALED.lock().unwrap().last(), Some(&(2, 1)));
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod types {
+        use super::*;
+        use crate::types::{duration_ms, timestamp_ms, Millis, Timestamp};
+        use std::time::{Duration, SystemTime};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Policy {
+            #[serde(with = "duration_ms")]
+            timeout: Duration,
+
+            #[serde(with = "timestamp_ms")]
+            since: SystemTime,
+
+            retry_after: Option<Millis>,
+        }
+
+        #[test]
+        fn canonical_time() {
+            let policy = Policy {
+                timeout: Duration::from_secs(90),
+                since: SystemTime::UNIX_EPOCH + Duration::from_millis(1_723_456_789_000),
+                retry_after: Some(Millis(Duration::from_millis(250))),
+            };
+
+            let json = serde_json::to_string(&policy).unwrap();
+            assert_eq!(
+                json,
+                r#"{"timeout":90000,"since":1723456789000,"retry_after":250}"#
+            );
+            assert_eq!(serde_json::from_str::<Policy>(&json).unwrap(), policy);
+
+            let timestamp: Timestamp = serde_json::from_str("0").unwrap();
+            assert_eq!(SystemTime::from(timestamp), SystemTime::UNIX_EPOCH);
+
+            // Instants before the epoch have no representation
+            let before = Timestamp(SystemTime::UNIX_EPOCH - Duration::from_secs(1));
+            assert!(serde_json::to_string(&before).is_err());
+        }
+    }
+
+    mod purge {
+        use super::*;
+
+        #[test]
+        fn purge_by_status_and_tag() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new().unwrap().build();
+
+            // Start queue
+            jq.start().unwrap();
+            assert_eq!(jq.state(), State::Running);
+
+            Runtime::new().unwrap().block_on(async {
+                // Create the jobs and push them
+                let mut tagged = Job::new(Routines::Nop).unwrap();
+                tagged.add_tag("cron");
+                let tagged_id = jq.enqueue(tagged).unwrap();
+                let untagged_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                let failed_id = jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Purge the jobs in error
+                let removed = jq.purge(PurgeFilter::errors()).await.unwrap();
+                assert_eq!(removed, vec![failed_id]);
+
+                // Purge the finished jobs holding the tag
+                let removed = jq.purge(PurgeFilter::finished().tag("cron")).await.unwrap();
+                assert_eq!(removed, vec![tagged_id]);
+                assert!(jq.job_status(&untagged_id).await.is_ok());
+
+                // Nothing is old enough to be purged
+                let filter = PurgeFilter::finished().older_than(std::time::Duration::from_secs(60));
+                assert!(jq.purge(filter).await.unwrap().is_empty());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn purge_in_background() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let mut job_ids = vec![];
+
+                for _ in 0..5 {
+                    job_ids.push(jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap());
+                }
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Removed by chunks of 2
+                let task = jq
+                    .purge_in_background(
+                        PurgeFilter::finished(),
+                        2,
+                        tokio::time::Duration::from_millis(10),
+                    )
+                    .unwrap();
+
+                let id = task.id();
+
+                assert_eq!(task.wait().await.unwrap(), 5);
+
+                let progress: Vec<PurgeProgress> = PURGES
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .filter(|(purge_id, _)| *purge_id == id)
+                    .map(|(_, progress)| *progress)
+                    .collect();
+
+                let removed: Vec<usize> = progress.iter().map(|p| p.removed).collect();
+                assert_eq!(removed, vec![2, 4, 5]);
+                assert!(progress.last().unwrap().done);
+
+                for job_id in job_ids {
+                    assert!(jq.job_status(&job_id).await.is_err());
+                }
+
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+
+        #[test]
+        fn soft_delete() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .soft_delete(std::time::Duration::from_millis(300))
+                .build();
+
+            // Start queue
+            jq.start().unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                let removed_id = jq.enqueue(Job::new(Routines::Nop).unwrap()).unwrap();
+                let purged_id = jq.enqueue(Job::new(Routines::RaiseError).unwrap()).unwrap();
+
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+
+                // Removed jobs kept, the most recently removed first
+                jq.remove_job(&removed_id).await.unwrap();
+                jq.purge(PurgeFilter::errors()).await.unwrap();
+
+                let deleted: Vec<Uuid> = jq
+                    .deleted_jobs()
+                    .unwrap()
+                    .iter()
+                    .map(|job| job.id())
+                    .collect();
+                assert_eq!(deleted, vec![purged_id, removed_id]);
+                assert!(jq.job_status(&removed_id).await.is_err());
+
+                // Restored as they were
+                jq.restore_job(&removed_id).await.unwrap();
+                assert_eq!(
+                    jq.job_status(&removed_id).await.unwrap(),
+                    Status::Finished(ResultStatus::Success)
+                );
+                assert_eq!(jq.deleted_jobs().unwrap().len(), 1);
+                assert!(jq.restore_job(&removed_id).await.is_err());
+
+                // Unless out of the retention window
+                tokio::time::sleep(tokio::time::Duration::from_millis(400)).await;
+
+                assert!(jq.deleted_jobs().unwrap().is_empty());
+                assert!(jq.restore_job(&purged_id).await.is_err());
+
+                // Stop the job queue
+                jq.stop().unwrap();
+            });
+
+            jq.join().unwrap();
+        }
+    }
+
+    mod errors {
+        use super::*;
+        use crate::memory_backend::MemoryBackend;
+
+        #[test]
+        fn not_startable() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            Runtime::new().unwrap().block_on(async {
                 jq.start().unwrap();
                 assert!(jq.start().is_err());
             });
         }
 
+        #[test]
+        fn backend_not_swappable() {
+            let mut jq = JobQueueBuilder::<Routines, Context>::new()
+                .unwrap()
+                .notification_handler(notification_handler)
+                .build();
+
+            jq.set_backend(MemoryBackend::new()).unwrap();
+
+            Runtime::new().unwrap().block_on(async {
+                jq.start().unwrap();
+
+                assert!(matches!(
+                    *jq.set_backend(MemoryBackend::new()).unwrap_err(),
+                    Error::AlreadyRunning
+                ));
+
+                jq.stop().unwrap();
+
+                assert!(matches!(
+                    *jq.set_backend(MemoryBackend::new()).unwrap_err(),
+                    Error::Stopped
+                ));
+            });
+        }
+
         #[test]
         fn not_joinable() {
             let jq = JobQueueBuilder::<Routines, Context>::new()
@@ -0,0 +1,55 @@
+use lazy_static::lazy_static;
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use crate::prelude::*;
+
+/// Future returned by the closure of a local job.
+type LocalFuture = Pin<Box<dyn Future<Output = Result<Vec<u8>, Error>> + Send>>;
+
+/// Closure run by a local job (see `Job::from_fn`).
+pub(crate) type LocalFn<Context> = Box<dyn FnOnce(Option<Shared<Context>>) -> LocalFuture + Send>;
+
+lazy_static! {
+    /// Closures of the local jobs not run yet, by key (type-erased on the context).
+    static ref REGISTRY: Mutex<HashMap<Uuid, Box<dyn Any + Send>>> = Mutex::new(HashMap::new());
+}
+
+/// Registers the closure of a local job.
+///
+/// # Arguments
+/// * `key` - Key of the closure.
+/// * `routine` - Closure to be run.
+pub(crate) fn register<Context: 'static>(key: Uuid, routine: LocalFn<Context>) {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(key, Box::new(routine));
+}
+
+/// Takes the closure of a local job, to be run once.
+///
+/// # Arguments
+/// * `key` - Key of the closure.
+///
+/// # Returns
+/// The closure.
+///
+/// # Errors
+/// `Error::LocalRoutine` if the closure has already been taken, belongs to another process
+/// or expects another type of context.
+pub(crate) fn take<Context: 'static>(key: &Uuid) -> Result<LocalFn<Context>, ApiError> {
+    let routine = REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .remove(key)
+        .ok_or(api_err!(Error::LocalRoutine(key.to_owned())))?;
+
+    routine
+        .downcast::<LocalFn<Context>>()
+        .map(|routine| *routine)
+        .map_err(|_| api_err!(Error::LocalRoutine(key.to_owned())))
+}
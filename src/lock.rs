@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+/// Lock taken by a worker on a key (serial key, singleton...) in a shared backend. A lock is
+/// kept alive by refreshing it before it expires: a lock not refreshed in time is considered
+/// held by a dead worker.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Lock {
+    /// Key locked.
+    pub key: String,
+
+    /// Identifier of the worker holding the lock.
+    pub owner: String,
+
+    /// Timestamp at which the lock expires if not refreshed.
+    pub expires_at: SystemTime,
+}
+
+impl Lock {
+    /// Creates a lock expiring after a given duration.
+    ///
+    /// # Arguments
+    /// * `key` - Key locked.
+    /// * `owner` - Identifier of the worker holding the lock.
+    /// * `ttl` - Duration after which the lock expires if not refreshed.
+    ///
+    /// # Returns
+    /// An instance of `Lock`.
+    pub fn new(key: impl Into<String>, owner: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            key: key.into(),
+            owner: owner.into(),
+            expires_at: SystemTime::now() + ttl,
+        }
+    }
+
+    /// Extends the lock.
+    ///
+    /// # Arguments
+    /// * `ttl` - Duration, from now, after which the lock expires if not refreshed.
+    pub fn refresh(&mut self, ttl: Duration) {
+        self.expires_at = SystemTime::now() + ttl;
+    }
+
+    /// Checks if the lock is stale (i.e. not refreshed in time by its owner).
+    ///
+    /// # Returns
+    /// `true` if stale, `false` otherwise.
+    pub fn is_stale(&self) -> bool {
+        SystemTime::now() >= self.expires_at
+    }
+}
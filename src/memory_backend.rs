@@ -1,31 +1,154 @@
 use async_trait::async_trait;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 use crate::prelude::*;
 
-/// A default backend implementation that stores everything in memory.
+/// Capacity limits of the memory backend. When a limit is reached, the finished jobs are
+/// evicted, the least recently used first; jobs kept forever (`ExpirePolicy::Never`) and
+/// unfinished jobs are never evicted.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryLimits {
+    /// Maximum number of jobs stored (unlimited if `None`).
+    pub max_jobs: Option<usize>,
+
+    /// Maximum total size in bytes of the results stored (unlimited if `None`).
+    pub max_result_bytes: Option<usize>,
+}
+
+impl MemoryLimits {
+    /// Limits the number of jobs stored.
+    ///
+    /// # Arguments
+    /// * `max` - Maximum number of jobs.
+    ///
+    /// # Returns
+    /// An instance of `MemoryLimits`.
+    pub fn max_jobs(self, max: usize) -> Self {
+        Self {
+            max_jobs: Some(max),
+            ..self
+        }
+    }
+
+    /// Limits the total size of the results stored.
+    ///
+    /// # Arguments
+    /// * `max` - Maximum size in bytes.
+    ///
+    /// # Returns
+    /// An instance of `MemoryLimits`.
+    pub fn max_result_bytes(self, max: usize) -> Self {
+        Self {
+            max_result_bytes: Some(max),
+            ..self
+        }
+    }
+
+    /// Checks if a limit is set.
+    ///
+    /// # Returns
+    /// `true` if at least one limit is set, `false` otherwise.
+    pub fn is_limited(&self) -> bool {
+        self.max_jobs.is_some() || self.max_result_bytes.is_some()
+    }
+}
+
+/// Last use of the jobs, to evict the least recently used first.
 #[derive(Default)]
+struct Recency {
+    /// Incremented on every use.
+    tick: u64,
+
+    /// Tick of the last use, by job.
+    used: HashMap<Uuid, u64>,
+}
+
+/// A default backend implementation that stores everything in memory.
 pub struct MemoryBackend {
     /// List of jobs stored and sorted by date added.
     jobs: BTreeMap<Uuid, Job>,
+
+    /// Locks taken, by namespace and key.
+    locks: BTreeMap<(String, String), Lock>,
+
+    /// Namespace of the jobs visible.
+    namespace: String,
+
+    /// Namespaces paused.
+    paused: BTreeSet<String>,
+
+    /// Statistics of the durations of the runs, by namespace and kind.
+    durations: BTreeMap<(String, String), DurationStats>,
+
+    /// Migrator of the routines stored by an older release.
+    migrator: Option<SharedMigrator>,
+
+    /// Capacity limits.
+    limits: MemoryLimits,
+
+    /// Last use of the jobs, only tracked with capacity limits.
+    recency: Mutex<Recency>,
+
+    /// Write-ahead log persisting the jobs.
+    wal: Option<Wal>,
+
+    /// Clock timestamping the jobs and evaluating their expiration.
+    clock: SharedClock,
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self {
+            jobs: BTreeMap::new(),
+            locks: BTreeMap::new(),
+            namespace: DEFAULT_NAMESPACE.to_owned(),
+            paused: BTreeSet::new(),
+            durations: BTreeMap::new(),
+            migrator: None,
+            limits: MemoryLimits::default(),
+            recency: Mutex::new(Recency::default()),
+            wal: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
 }
 
 #[async_trait]
 impl<RoutineType, Context> Backend<RoutineType, Context> for MemoryBackend
 where
-    RoutineType: Routine<Context> + Sync,
+    RoutineType: Routine<Context> + Sync + 'static,
     for<'async_trait> Context: Send + 'async_trait,
 {
     async fn get(&mut self, id: &Uuid) -> Result<Job, ApiError> {
-        Ok(self
-            .jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
-            .to_owned())
+        if let Some(migrator) = self.migrator.clone() {
+            if let Some(job) = self.job_mut(id) {
+                // Routines failing to migrate left to the undecodable policy
+                if let Ok(true) = job.migrate::<RoutineType, Context>(migrator.as_ref()) {
+                    self.persist(id)?;
+                }
+            }
+        }
+
+        Ok(self.job(id)?.to_owned())
     }
 
-    fn schedule(&mut self, job: Job) -> Result<(), ApiError> {
-        self.jobs.insert(job.id(), job);
+    fn schedule(&mut self, mut job: Job) -> Result<(), ApiError> {
+        job.set_namespace(self.namespace.to_owned());
+
+        let (jobs, bytes) = match self.jobs.get(&job.id()) {
+            Some(stored) => (0, job.result().len().saturating_sub(stored.result().len())),
+            None => (1, job.result().len()),
+        };
+
+        let id = job.id();
+
+        self.fit(jobs, bytes)?;
+        self.touch(&id);
+        self.jobs.insert(id, job);
+        self.persist(&id)?;
 
         Ok(())
     }
@@ -33,106 +156,180 @@ where
     async fn run(
         &mut self,
         id: &Uuid,
-        context: Option<Shared<Context>>,
+        context: Option<ContextMode<Context>>,
         messages_channel: SharedMessageChannel,
     ) -> Result<(), ApiError> {
-        if let Some(job) = self.jobs.get_mut(id) {
+        if let Some(job) = self.job_mut(id) {
             job.run::<RoutineType, Context>(messages_channel, context)
                 .await?;
 
-            Ok(())
+            self.persist(id)
         } else {
             Err(api_err!(Error::JobNotFound(id.to_owned())))
         }
     }
 
     fn status(&self, id: &Uuid) -> Result<Status, ApiError> {
-        Ok(self
-            .jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
-            .status())
+        Ok(self.job(id)?.status())
+    }
+
+    fn statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        Ok(ids
+            .iter()
+            .filter_map(|id| Some((id.to_owned(), self.job(id).ok()?.status())))
+            .collect())
     }
 
     fn set_status(&mut self, id: &Uuid, status: Status) -> Result<(), ApiError> {
-        if let Some(job) = self.jobs.get_mut(id) {
-            job.set_status(status)?;
+        let now = self.clock.now();
+
+        if let Some(job) = self.job_mut(id) {
+            job.set_status_at(status, now)?;
 
-            Ok(())
+            self.persist(id)
         } else {
             Err(api_err!(Error::JobNotFound(id.to_owned())))
         }
     }
 
     fn result(&self, id: &Uuid) -> Result<&[u8], ApiError> {
-        Ok(self
-            .jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
-            .result())
+        let job = self.job(id)?;
+
+        if job.is_result_expired_at(self.clock.now()) {
+            return Err(api_err!(Error::ResultExpired(*id)));
+        }
+
+        Ok(job.result())
     }
 
     fn set_result(&mut self, id: &Uuid, result: Vec<u8>) -> Result<(), ApiError> {
-        if let Some(job) = self.jobs.get_mut(id) {
+        let stored = self.job(id)?.result().len();
+
+        self.fit(0, result.len().saturating_sub(stored))?;
+
+        if let Some(job) = self.job_mut(id) {
             job.set_result(result)?;
 
-            Ok(())
+            self.persist(id)
+        } else {
+            Err(api_err!(Error::JobNotFound(id.to_owned())))
+        }
+    }
+
+    fn set_result_ref(&mut self, id: &Uuid, key: String) -> Result<(), ApiError> {
+        if let Some(job) = self.job_mut(id) {
+            job.set_result_ref(key);
+
+            self.persist(id)
         } else {
             Err(api_err!(Error::JobNotFound(id.to_owned())))
         }
     }
 
+    fn add_attempt(&mut self, id: &Uuid, attempt: Attempt) -> Result<(), ApiError> {
+        if let Some(job) = self.job_mut(id) {
+            job.add_attempt(attempt);
+
+            self.persist(id)
+        } else {
+            Err(api_err!(Error::JobNotFound(id.to_owned())))
+        }
+    }
+
+    fn set_checkpoint(&mut self, id: &Uuid, checkpoint: Vec<u8>) -> Result<(), ApiError> {
+        if let Some(job) = self.job_mut(id) {
+            job.set_checkpoint(checkpoint);
+
+            self.persist(id)
+        } else {
+            Err(api_err!(Error::JobNotFound(id.to_owned())))
+        }
+    }
+
+    fn set_metadata(
+        &mut self,
+        id: &Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        if let Some(job) = self.job_mut(id) {
+            job.set_metadata(key, value);
+
+            self.persist(id)
+        } else {
+            Err(api_err!(Error::JobNotFound(id.to_owned())))
+        }
+    }
+
+    fn append_chunk(&mut self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError> {
+        if let Some(job) = self.job_mut(id) {
+            job.append_chunk(chunk);
+
+            self.persist(id)
+        } else {
+            Err(api_err!(Error::JobNotFound(id.to_owned())))
+        }
+    }
+
+    fn chunks(&self, id: &Uuid, from: usize) -> Result<Vec<Vec<u8>>, ApiError> {
+        Ok(self.job(id)?.chunks().iter().skip(from).cloned().collect())
+    }
+
     fn set_steps(&mut self, id: &Uuid, steps: u64) -> Result<Progression, ApiError> {
-        if let Some(job) = self.jobs.get_mut(id) {
-            job.set_steps(steps)
+        if let Some(job) = self.job_mut(id) {
+            let progression = job.set_steps(steps)?;
+
+            self.persist(id)?;
+
+            Ok(progression)
         } else {
             Err(api_err!(Error::JobNotFound(id.to_owned())))
         }
     }
 
     fn set_step(&mut self, id: &Uuid, step: u64) -> Result<Progression, ApiError> {
-        if let Some(job) = self.jobs.get_mut(id) {
-            job.set_step(step)
+        if let Some(job) = self.job_mut(id) {
+            let progression = job.set_step(step)?;
+
+            self.persist(id)?;
+
+            Ok(progression)
         } else {
             Err(api_err!(Error::JobNotFound(id.to_owned())))
         }
     }
 
     fn progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
-        Ok(self
-            .jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
-            .progression())
+        Ok(self.job(id)?.progression())
     }
 
     fn routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
-        self.jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
+        self.migrated::<RoutineType, Context>(self.job(id)?)
             .routine()
     }
 
     fn expire_policy(&self, id: &Uuid) -> Result<ExpirePolicy, ApiError> {
-        Ok(self
-            .jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
-            .expire_policy())
+        Ok(self.job(id)?.expire_policy())
+    }
+
+    fn set_expiration(&mut self, id: &Uuid, expired: SystemTime) -> Result<(), ApiError> {
+        if let Some(job) = self.job_mut(id) {
+            job.set_expiration(expired);
+
+            self.persist(id)
+        } else {
+            Err(api_err!(Error::JobNotFound(id.to_owned())))
+        }
     }
 
     fn remove(&mut self, id: &Uuid) -> Result<(), ApiError> {
-        let status = self
-            .jobs
-            .get(id)
-            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))?
-            .status();
+        let status = self.job(id)?.status();
 
         match status {
             Status::Finished(_) => {
                 self.jobs.remove(id);
 
-                Ok(())
+                self.persist_removed(&[id.to_owned()])
             }
 
             _ => Err(api_err!(Error::JobNotFinished)),
@@ -144,7 +341,7 @@ where
             .jobs
             .iter()
             .filter_map(|(job_id, job)| {
-                if job.is_expired() {
+                if job.namespace() == self.namespace && job.is_expired_at(self.clock.now()) {
                     Some(job_id.to_owned())
                 } else {
                     None
@@ -156,15 +353,447 @@ where
             self.jobs.remove(job_id);
         }
 
+        self.persist_removed(&to_remove)?;
+
+        Ok(to_remove)
+    }
+
+    fn expire_results(&mut self) -> Result<Vec<(Uuid, Option<String>)>, ApiError> {
+        let now = self.clock.now();
+
+        let to_expire: Vec<_> = self
+            .jobs
+            .values()
+            .filter(|job| {
+                job.namespace() == self.namespace
+                    && !job.is_result_dropped()
+                    && job.is_result_expired_at(now)
+            })
+            .map(|job| job.id())
+            .collect();
+
+        let mut expired = Vec::with_capacity(to_expire.len());
+
+        for job_id in to_expire {
+            if let Some(job) = self.jobs.get_mut(&job_id) {
+                expired.push((job_id, job.expire_result()));
+            }
+
+            self.persist(&job_id)?;
+        }
+
+        Ok(expired)
+    }
+
+    fn retain_last(&mut self, count: usize) -> Result<Vec<Uuid>, ApiError> {
+        let mut finished: HashMap<&str, Vec<&Job>> = HashMap::new();
+
+        for job in self.jobs.values() {
+            if job.namespace() == self.namespace
+                && matches!(job.status(), Status::Finished(_))
+                && job.expire_policy() != ExpirePolicy::Never
+            {
+                finished.entry(job.kind()).or_default().push(job);
+            }
+        }
+
+        let mut to_remove = Vec::new();
+
+        for jobs in finished.values_mut() {
+            // Newest first
+            jobs.sort_by(|a, b| b.timestamps().finished.cmp(&a.timestamps().finished));
+
+            to_remove.extend(jobs.iter().skip(count).map(|job| job.id()));
+        }
+
+        for job_id in &to_remove {
+            self.jobs.remove(job_id);
+        }
+
+        self.persist_removed(&to_remove)?;
+
         Ok(to_remove)
     }
 
+    fn purge(&mut self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        let mut removed = Vec::new();
+
+        self.jobs.retain(|job_id, job| {
+            let full = filter.limit.is_some_and(|limit| removed.len() >= limit);
+
+            if !full && job.namespace() == self.namespace && filter.matches(job) {
+                removed.push(job_id.to_owned());
+
+                false
+            } else {
+                true
+            }
+        });
+
+        self.persist_removed(&removed)?;
+
+        Ok(removed)
+    }
+
+    fn acquire_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        let lock_key = (self.namespace.to_owned(), key.to_owned());
+
+        if let Some(lock) = self.locks.get(&lock_key) {
+            if lock.owner != owner && !lock.is_stale() {
+                return Ok(false);
+            }
+        }
+
+        self.locks.insert(lock_key, Lock::new(key, owner, ttl));
+
+        Ok(true)
+    }
+
+    fn refresh_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        match self
+            .locks
+            .get_mut(&(self.namespace.to_owned(), key.to_owned()))
+        {
+            Some(lock) if lock.owner == owner => {
+                lock.refresh(ttl);
+
+                Ok(true)
+            }
+
+            _ => Ok(false),
+        }
+    }
+
+    fn release_lock(&mut self, key: &str, owner: &str) -> Result<bool, ApiError> {
+        let lock_key = (self.namespace.to_owned(), key.to_owned());
+
+        match self.locks.get(&lock_key) {
+            Some(lock) if lock.owner == owner => {
+                self.locks.remove(&lock_key);
+
+                Ok(true)
+            }
+
+            _ => Ok(false),
+        }
+    }
+
+    fn release_stale_locks(&mut self) -> Result<Vec<Lock>, ApiError> {
+        let mut released = Vec::new();
+
+        self.locks.retain(|(namespace, _), lock| {
+            if namespace == &self.namespace && lock.is_stale() {
+                released.push(lock.clone());
+
+                false
+            } else {
+                true
+            }
+        });
+
+        Ok(released)
+    }
+
+    fn claim(
+        &mut self,
+        worker_id: &str,
+        lease: Duration,
+        kinds: &KindFilter,
+        capabilities: &Capabilities,
+    ) -> Result<Option<Job>, ApiError> {
+        let namespace = &self.namespace;
+
+        let job = self
+            .jobs
+            .values_mut()
+            .filter(|job| {
+                job.namespace() == namespace
+                    && job.is_claimable()
+                    && kinds.accepts(job.kind())
+                    && capabilities.satisfies(job.requirements())
+            })
+            .min_by_key(|job| job.timestamps().enqueued);
+
+        let job = job.map(|job| {
+            job.claim(worker_id, lease);
+            job.clone()
+        });
+
+        if let Some(job) = &job {
+            self.persist(&job.id())?;
+        }
+
+        Ok(job)
+    }
+
+    fn extend_lease(
+        &mut self,
+        id: &Uuid,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError> {
+        match self.job_mut(id) {
+            Some(job) if job.worker() == Some(worker_id) && job.lease_until().is_some() => {
+                job.extend_lease(lease);
+
+                self.persist(id)?;
+
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(api_err!(Error::JobNotFound(id.to_owned()))),
+        }
+    }
+
+    fn release(&mut self, id: &Uuid, worker_id: &str) -> Result<bool, ApiError> {
+        match self.job_mut(id) {
+            Some(job) if job.worker() == Some(worker_id) && job.lease_until().is_some() => {
+                job.release();
+
+                self.persist(id)?;
+
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(api_err!(Error::JobNotFound(id.to_owned()))),
+        }
+    }
+
+    fn reap_orphans(
+        &mut self,
+        timeout: Duration,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, ApiError> {
+        let namespace = &self.namespace;
+        let mut reaped = Vec::new();
+
+        for job in self.jobs.values_mut() {
+            if job.namespace() != namespace || !job.is_orphaned(timeout) {
+                continue;
+            }
+
+            match policy {
+                OrphanPolicy::Requeue => job.requeue(),
+                OrphanPolicy::Interrupt => job.interrupt()?,
+            }
+
+            reaped.push(job.id());
+        }
+
+        for job_id in &reaped {
+            self.persist(job_id)?;
+        }
+
+        Ok(reaped)
+    }
+
+    fn timeline(
+        &self,
+        range: &Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError> {
+        let mut intervals: Vec<JobInterval> = self
+            .jobs
+            .values()
+            .filter(|job| job.namespace() == self.namespace && filter.matches(job))
+            .map(JobInterval::from)
+            .filter(|interval| interval.overlaps(range))
+            .collect();
+
+        intervals.sort_by_key(|interval| interval.enqueued);
+
+        Ok(intervals)
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), ApiError> {
+        if paused {
+            self.paused.insert(self.namespace.to_owned());
+        } else {
+            self.paused.remove(&self.namespace);
+        }
+
+        if let Some(wal) = &mut self.wal {
+            wal.paused(&self.namespace, paused)?;
+        }
+
+        self.compact_if_needed()
+    }
+
+    fn is_paused(&self) -> Result<bool, ApiError> {
+        Ok(self.paused.contains(&self.namespace))
+    }
+
+    fn record_duration(&mut self, kind: &str, duration: Duration) -> Result<(), ApiError> {
+        self.durations
+            .entry((self.namespace.to_owned(), kind.to_owned()))
+            .or_default()
+            .record(duration);
+
+        Ok(())
+    }
+
+    fn duration_stats(&self, kind: &str) -> Result<Option<DurationStats>, ApiError> {
+        Ok(self
+            .durations
+            .get(&(self.namespace.to_owned(), kind.to_owned()))
+            .copied())
+    }
+
+    fn set_namespace(&mut self, namespace: &str) {
+        self.namespace = namespace.to_owned();
+    }
+
+    fn set_migrator(&mut self, migrator: SharedMigrator) {
+        self.migrator = Some(migrator);
+    }
+
+    fn set_codec(&mut self, codec: SharedCodec) {
+        // Jobs kept in memory as they are, only the write-ahead log encoded
+        if let Some(wal) = &mut self.wal {
+            wal.set_codec(codec);
+        }
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        // Jobs kept in memory as they are, only the write-ahead log compressed
+        if let Some(wal) = &mut self.wal {
+            wal.set_compression(compression);
+        }
+    }
+
+    fn set_encryption(&mut self, encryption: Encryption) {
+        // Jobs kept in memory as they are, only the write-ahead log encrypted
+        if let Some(wal) = &mut self.wal {
+            wal.set_encryption(encryption);
+        }
+    }
+
+    fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
     fn jobs(&self) -> Result<Vec<Job>, ApiError> {
-        Ok(self.jobs.values().cloned().collect())
+        Ok(self
+            .listed(None)
+            .into_iter()
+            .map(|job| self.migrated::<RoutineType, Context>(job))
+            .collect())
+    }
+
+    fn job_infos(&self) -> Result<Vec<JobInfo>, ApiError> {
+        Ok(self
+            .listed(None)
+            .into_iter()
+            .map(|job| self.info::<RoutineType, Context>(job))
+            .collect())
+    }
+
+    fn jobs_page(&self, after: Option<&Cursor>, limit: usize) -> Result<JobPage, ApiError> {
+        // One more job than the limit taken, telling if there is a next page
+        let jobs = self
+            .listed(after)
+            .into_iter()
+            .take(limit.max(1).saturating_add(1))
+            .map(|job| self.info::<RoutineType, Context>(job));
+
+        Ok(JobPage::cut(jobs, after, limit))
+    }
+
+    fn count(&self, filter: &JobFilter) -> Result<usize, ApiError> {
+        let mut count = 0;
+
+        self.select::<RoutineType, Context>(filter, |_| count += 1);
+
+        Ok(count)
+    }
+
+    fn aggregate(
+        &self,
+        filter: &JobFilter,
+        group_by: GroupBy,
+    ) -> Result<HashMap<GroupKey, usize>, ApiError> {
+        let mut groups = HashMap::new();
+
+        self.select::<RoutineType, Context>(filter, |job| {
+            *groups.entry(group_by.key(job)).or_default() += 1;
+        });
+
+        Ok(groups)
     }
 }
 
 impl MemoryBackend {
+    /// Lists the jobs of the namespace, ordered by enqueue time then ID.
+    ///
+    /// # Arguments
+    /// * `after` - Cursor the jobs listed come after, `None` to list all of them.
+    ///
+    /// # Returns
+    /// The jobs.
+    fn listed(&self, after: Option<&Cursor>) -> Vec<&Job> {
+        let mut jobs = self
+            .jobs
+            .values()
+            .filter(|job| job.namespace() == self.namespace)
+            .filter(|job| {
+                after.map_or(true, |cursor| {
+                    cursor.precedes(job.timestamps().enqueued, job.id())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        jobs.sort_by_key(|job| (job.timestamps().enqueued, job.id()));
+
+        jobs
+    }
+
+    /// Visits the jobs of the namespace selected by a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs.
+    /// * `visit` - Function called with every job selected.
+    fn select<RoutineType, Context>(&self, filter: &JobFilter, mut visit: impl FnMut(&Job))
+    where
+        RoutineType: Routine<Context>,
+    {
+        for job in self
+            .jobs
+            .values()
+            .filter(|job| job.namespace() == self.namespace)
+        {
+            match self.migrator {
+                // Kind possibly renamed by the migration
+                Some(_) => {
+                    let job = self.migrated::<RoutineType, Context>(job);
+
+                    if filter.matches(&job) {
+                        visit(&job);
+                    }
+                }
+                None if filter.matches(job) => visit(job),
+                None => (),
+            }
+        }
+    }
+
+    /// Gets the summary of a job.
+    ///
+    /// # Arguments
+    /// * `job` - Job stored.
+    ///
+    /// # Returns
+    /// The summary of the job, once migrated.
+    fn info<RoutineType, Context>(&self, job: &Job) -> JobInfo
+    where
+        RoutineType: Routine<Context>,
+    {
+        match self.migrator {
+            // Kind possibly renamed by the migration
+            Some(_) => JobInfo::from(&self.migrated::<RoutineType, Context>(job)),
+            None => JobInfo::from(job),
+        }
+    }
+
     /// Creates a new instance of the memory backend.
     ///
     /// # Returns
@@ -172,4 +801,265 @@ impl MemoryBackend {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a new instance of the memory backend with capacity limits, evicting the least
+    /// recently used finished jobs when reached.
+    ///
+    /// # Arguments
+    /// * `limits` - Capacity limits.
+    ///
+    /// # Returns
+    /// An instance of `MemoryBackend`.
+    pub fn with_limits(limits: MemoryLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Persists the jobs to a write-ahead log, replayed first to restore the jobs stored
+    /// before a restart.
+    ///
+    /// # Arguments
+    /// * `wal` - Write-ahead log.
+    ///
+    /// # Returns
+    /// The backend, with the jobs and paused namespaces of the log.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn wal(self, mut wal: Wal) -> Result<Self, ApiError> {
+        let (jobs, paused) = wal.replay()?;
+
+        // Rewritten from the replay, dropping a record torn by a crash
+        wal.compact(jobs.values(), &paused)?;
+
+        Ok(Self {
+            jobs,
+            paused,
+            wal: Some(wal),
+            ..self
+        })
+    }
+
+    /// Compacts the write-ahead log to one record per job stored, if set.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn compact(&mut self) -> Result<(), ApiError> {
+        if let Some(wal) = &mut self.wal {
+            wal.compact(self.jobs.values(), &self.paused)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the capacity limits.
+    ///
+    /// # Returns
+    /// The limits, unlimited by default.
+    pub fn limits(&self) -> MemoryLimits {
+        self.limits
+    }
+
+    /// Get the total size of the results stored.
+    ///
+    /// # Returns
+    /// The size in bytes, across all namespaces.
+    pub fn result_bytes(&self) -> usize {
+        self.jobs.values().map(|job| job.result().len()).sum()
+    }
+
+    /// Evicts finished jobs, the least recently used first, until the backend can take more
+    /// jobs and results within its limits.
+    ///
+    /// # Arguments
+    /// * `jobs` - Number of jobs to be added.
+    /// * `bytes` - Size in bytes of the results to be added.
+    ///
+    /// # Errors
+    /// `Error::CapacityExceeded` if a limit is still exceeded with no finished job left.
+    fn fit(&mut self, jobs: usize, bytes: usize) -> Result<(), ApiError> {
+        if !self.limits.is_limited() {
+            return Ok(());
+        }
+
+        let mut recency = self
+            .recency
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessBackend(e.to_string())))?;
+
+        // Forget the jobs removed since
+        recency.used.retain(|id, _| self.jobs.contains_key(id));
+
+        loop {
+            let jobs_exceeded = self
+                .limits
+                .max_jobs
+                .is_some_and(|max| self.jobs.len() + jobs > max);
+            let bytes_exceeded = self
+                .limits
+                .max_result_bytes
+                .is_some_and(|max| self.result_bytes() + bytes > max);
+
+            if !jobs_exceeded && !bytes_exceeded {
+                return Ok(());
+            }
+
+            // Jobs without result only freeing their slot
+            let evicted = self
+                .jobs
+                .values()
+                .filter(|job| {
+                    matches!(job.status(), Status::Finished(_))
+                        && job.expire_policy() != ExpirePolicy::Never
+                        && (jobs_exceeded || !job.result().is_empty())
+                })
+                .min_by_key(|job| recency.used.get(&job.id()).copied().unwrap_or_default())
+                .map(|job| job.id());
+
+            match evicted {
+                Some(id) => {
+                    self.jobs.remove(&id);
+                    recency.used.remove(&id);
+
+                    if let Some(wal) = &mut self.wal {
+                        wal.remove(&id)?;
+                    }
+                }
+                None if jobs_exceeded => {
+                    return Err(api_err!(Error::CapacityExceeded(format!(
+                        "max jobs {}",
+                        self.limits.max_jobs.unwrap_or_default()
+                    ))))
+                }
+                None => {
+                    return Err(api_err!(Error::CapacityExceeded(format!(
+                        "max result bytes {}",
+                        self.limits.max_result_bytes.unwrap_or_default()
+                    ))))
+                }
+            }
+        }
+    }
+
+    /// Records a job stored or changed in the write-ahead log, if set.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier changed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn persist(&mut self, id: &Uuid) -> Result<(), ApiError> {
+        if let (Some(wal), Some(job)) = (&mut self.wal, self.jobs.get(id)) {
+            // Closures not persistable
+            if !job.is_local() {
+                wal.put(job)?;
+            }
+        }
+
+        self.compact_if_needed()
+    }
+
+    /// Records jobs removed in the write-ahead log, if set.
+    ///
+    /// # Arguments
+    /// * `ids` - Job identifiers removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn persist_removed(&mut self, ids: &[Uuid]) -> Result<(), ApiError> {
+        if let Some(wal) = &mut self.wal {
+            for id in ids {
+                wal.remove(id)?;
+            }
+        }
+
+        self.compact_if_needed()
+    }
+
+    /// Compacts the write-ahead log once enough records are appended.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn compact_if_needed(&mut self) -> Result<(), ApiError> {
+        match &self.wal {
+            Some(wal) if wal.needs_compaction() => self.compact(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Marks a job as used, if capacity limits are set.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier used.
+    fn touch(&self, id: &Uuid) {
+        if !self.limits.is_limited() {
+            return;
+        }
+
+        if let Ok(mut recency) = self.recency.lock() {
+            recency.tick += 1;
+
+            let tick = recency.tick;
+
+            recency.used.insert(id.to_owned(), tick);
+        }
+    }
+
+    /// Get a job of the current namespace.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be fetched.
+    ///
+    /// # Returns
+    /// The job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn job(&self, id: &Uuid) -> Result<&Job, ApiError> {
+        self.touch(id);
+
+        self.jobs
+            .get(id)
+            .filter(|job| job.namespace() == self.namespace)
+            .ok_or(api_err!(Error::JobNotFound(id.to_owned())))
+    }
+
+    /// Get a copy of a job with its routine migrated, if a migrator is set.
+    ///
+    /// # Arguments
+    /// * `job` - Job stored.
+    ///
+    /// # Returns
+    /// The job migrated, or unchanged if its routine is up to date or fails to migrate.
+    fn migrated<RoutineType: Routine<Context>, Context>(&self, job: &Job) -> Job {
+        let mut migrated = job.to_owned();
+
+        if let Some(migrator) = &self.migrator {
+            if migrated
+                .migrate::<RoutineType, Context>(migrator.as_ref())
+                .is_err()
+            {
+                return job.to_owned();
+            }
+        }
+
+        migrated
+    }
+
+    /// Get a mutable job of the current namespace.
+    ///
+    /// # Arguments
+    /// * `id` - Job identifier to be fetched.
+    ///
+    /// # Returns
+    /// The job, `None` if not found.
+    fn job_mut(&mut self, id: &Uuid) -> Option<&mut Job> {
+        self.touch(id);
+
+        self.jobs
+            .get_mut(id)
+            .filter(|job| job.namespace() == self.namespace)
+    }
 }
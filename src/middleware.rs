@@ -0,0 +1,84 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share a middleware across threads.
+pub type SharedMiddleware = Arc<dyn Middleware>;
+
+/// Future running the routine of a job, at the end of the middleware chain.
+pub type RoutineFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, ApiError>> + Send + 'a>>;
+
+/// Trait implemented by the middlewares wrapping the execution of every job (timing, checks,
+/// tracing, retries...), without modifying the routines.
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Handles the execution of a job, calling the rest of the chain (see `Next::run`) to run
+    /// it, as many times as needed, or not at all to reject it.
+    ///
+    /// # Arguments
+    /// * `job` - Job executed.
+    /// * `next` - Rest of the chain, ending with the routine of the job.
+    ///
+    /// # Returns
+    /// The result of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum, the job being finished in error.
+    async fn handle(&self, job: &Job, next: Next<'_>) -> Result<Vec<u8>, ApiError>;
+}
+
+/// Rest of a middleware chain, ending with the routine of the job.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    /// Middlewares left to be called, the outermost first.
+    middlewares: &'a [SharedMiddleware],
+
+    /// Runs the routine of the job.
+    routine: &'a (dyn Fn() -> RoutineFuture<'a> + Send + Sync),
+}
+
+impl<'a> Next<'a> {
+    /// Creates a middleware chain.
+    ///
+    /// # Arguments
+    /// * `middlewares` - Middlewares of the chain, the outermost first.
+    /// * `routine` - Runs the routine of the job.
+    ///
+    /// # Returns
+    /// An instance of `Next`.
+    pub(crate) fn new(
+        middlewares: &'a [SharedMiddleware],
+        routine: &'a (dyn Fn() -> RoutineFuture<'a> + Send + Sync),
+    ) -> Self {
+        Self {
+            middlewares,
+            routine,
+        }
+    }
+
+    /// Runs the rest of the chain.
+    ///
+    /// # Arguments
+    /// * `job` - Job executed.
+    ///
+    /// # Returns
+    /// The result of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn run(self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        match self.middlewares.split_first() {
+            Some((middleware, middlewares)) => {
+                let next = Self {
+                    middlewares,
+                    routine: self.routine,
+                };
+
+                middleware.handle(job, next).await
+            }
+            None => (self.routine)().await,
+        }
+    }
+}
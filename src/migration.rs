@@ -0,0 +1,39 @@
+//! Migration of the routines persisted by an older release.
+//!
+//! Every job stores the version of its routine (see `Routine::version`). When a stored routine
+//! no longer deserializes (e.g. renamed variant) or is older than the current one, the backend
+//! hands it to the `RoutineMigrator` of the queue before running it.
+
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share the migrator with the backend.
+pub type SharedMigrator = Arc<dyn RoutineMigrator>;
+
+/// Trait upgrading the routines persisted by an older release to the current routine type.
+pub trait RoutineMigrator: Send + Sync {
+    /// Migrates a serialized routine.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the job (i.e. name of the variant when stored).
+    /// * `version` - Version of the routine when stored.
+    /// * `routine` - Routine as JSON value (externally tagged enum).
+    ///
+    /// # Returns
+    /// The routine as JSON value, deserializable by the current routine type.
+    ///
+    /// # Errors
+    /// One of `Error` enum, the job being then left to the undecodable policy.
+    fn migrate(&self, kind: &str, version: u32, routine: Value) -> Result<Value, ApiError>;
+}
+
+impl<F> RoutineMigrator for F
+where
+    F: Fn(&str, u32, Value) -> Result<Value, ApiError> + Send + Sync,
+{
+    fn migrate(&self, kind: &str, version: u32, routine: Value) -> Result<Value, ApiError> {
+        self(kind, version, routine)
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Name of the queue of the jobs not enqueued to a named queue.
+pub const DEFAULT_QUEUE: &str = "default";
+
+/// Settings of a named queue hosted by a job queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct QueueSettings {
+    /// Maximum number of jobs of the queue running at the same time (unlimited if `None`),
+    /// within the limit of the whole job queue.
+    pub max_running: Option<usize>,
+
+    /// Priority of the queue (the higher, the more urgent): the jobs of a queue are only
+    /// dispatched once no job of a queue of higher priority is eligible.
+    pub priority: i32,
+}
+
+impl QueueSettings {
+    /// Sets the maximum number of jobs running at the same time.
+    ///
+    /// # Arguments
+    /// * `max_running` - Maximum number of jobs.
+    ///
+    /// # Returns
+    /// The updated settings.
+    pub fn max_running(self, max_running: usize) -> Self {
+        Self {
+            max_running: Some(max_running),
+            ..self
+        }
+    }
+
+    /// Sets the priority of the queue.
+    ///
+    /// # Arguments
+    /// * `priority` - Priority of the queue.
+    ///
+    /// # Returns
+    /// The updated settings.
+    pub fn priority(self, priority: i32) -> Self {
+        Self { priority, ..self }
+    }
+}
+
+/// Named queues hosted by a job queue (see `Job::set_queue`), sharing its threads and backend.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct NamedQueues {
+    /// Settings of the queues without settings of their own, including `DEFAULT_QUEUE`.
+    pub default: QueueSettings,
+
+    /// Settings by queue.
+    pub queues: HashMap<String, QueueSettings>,
+
+    /// Queues of the jobs enqueued without queue, by kind of job.
+    #[serde(default)]
+    pub routes: HashMap<String, String>,
+}
+
+impl NamedQueues {
+    /// Creates named queues sharing the same settings.
+    ///
+    /// # Arguments
+    /// * `default` - Settings of the queues without settings of their own.
+    ///
+    /// # Returns
+    /// An instance of `NamedQueues`.
+    pub fn new(default: QueueSettings) -> Self {
+        Self {
+            default,
+            queues: HashMap::new(),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Sets the settings of a queue, in place of the default ones.
+    ///
+    /// # Arguments
+    /// * `queue` - Name of the queue.
+    /// * `settings` - Settings of the queue.
+    ///
+    /// # Returns
+    /// The updated queues.
+    pub fn queue(mut self, queue: impl Into<String>, settings: QueueSettings) -> Self {
+        self.queues.insert(queue.into(), settings);
+
+        self
+    }
+
+    /// Routes the jobs of a kind to a queue, when enqueued without queue: the producers don't
+    /// need to know the queues hosted.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of job.
+    /// * `queue` - Name of the queue.
+    ///
+    /// # Returns
+    /// The updated queues.
+    pub fn route_kind(mut self, kind: impl Into<String>, queue: impl Into<String>) -> Self {
+        self.routes.insert(kind.into(), queue.into());
+
+        self
+    }
+
+    /// Gets the queue the jobs of a kind are routed to.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of job.
+    ///
+    /// # Returns
+    /// The name of the queue, `None` if the kind isn't routed.
+    pub fn route(&self, kind: &str) -> Option<&str> {
+        self.routes.get(kind).map(String::as_str)
+    }
+
+    /// Gets the settings of a queue.
+    ///
+    /// # Arguments
+    /// * `queue` - Name of the queue.
+    ///
+    /// # Returns
+    /// The settings of the queue, the default ones if it has none of its own.
+    pub fn settings(&self, queue: &str) -> QueueSettings {
+        self.queues.get(queue).copied().unwrap_or(self.default)
+    }
+}
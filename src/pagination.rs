@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Position in the listing of the jobs, ordered by enqueue time then ID: a page starts right
+/// after it, whatever the jobs enqueued or removed in the meantime. Formatted as a string, to be
+/// handed over to the clients of an HTTP API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub struct Cursor {
+    /// Enqueue time of the last job of the page.
+    enqueued: SystemTime,
+
+    /// ID of the last job of the page.
+    id: Uuid,
+}
+
+impl Cursor {
+    /// Creates the cursor pointing at a job.
+    ///
+    /// # Arguments
+    /// * `job` - Last job of the page.
+    ///
+    /// # Returns
+    /// An instance of `Cursor`.
+    pub fn after(job: &JobInfo) -> Self {
+        Self {
+            enqueued: job.timestamps.enqueued,
+            id: job.id,
+        }
+    }
+
+    /// Checks if a job comes after the cursor.
+    ///
+    /// # Arguments
+    /// * `enqueued` - Enqueue time of the job.
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// `true` if the job is listed after the cursor, `false` otherwise.
+    pub fn precedes(&self, enqueued: SystemTime, id: Uuid) -> bool {
+        (self.enqueued, self.id) < (enqueued, id)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nanos = self
+            .enqueued
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        write!(f, "{}_{}", nanos, self.id.simple())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || api_err!(Error::InvalidCursor(s.to_owned()));
+
+        let (nanos, id) = s.split_once('_').ok_or_else(invalid)?;
+        let nanos = nanos.parse::<u128>().map_err(|_| invalid())?;
+        let id = Uuid::parse_str(id).map_err(|_| invalid())?;
+
+        let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| invalid())?;
+        let enqueued = SystemTime::UNIX_EPOCH
+            .checked_add(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+            .ok_or_else(invalid)?;
+
+        Ok(Self { enqueued, id })
+    }
+}
+
+/// Page of the listing of the jobs.
+#[derive(Clone, PartialEq, Deserialize, Serialize)]
+pub struct JobPage {
+    /// Summary of the jobs, ordered by enqueue time then ID.
+    pub jobs: Vec<JobInfo>,
+
+    /// Cursor of the next page, `None` if this one is the last.
+    pub next: Option<Cursor>,
+}
+
+impl JobPage {
+    /// Cuts a page out of the listing of the jobs.
+    ///
+    /// # Arguments
+    /// * `jobs` - Summary of the jobs, in any order.
+    /// * `after` - Cursor the page starts after, `None` for the first page.
+    /// * `limit` - Maximum number of jobs in the page (at least 1).
+    ///
+    /// # Returns
+    /// The page.
+    pub fn cut(
+        jobs: impl IntoIterator<Item = JobInfo>,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Self {
+        let limit = limit.max(1);
+        let mut jobs = jobs
+            .into_iter()
+            .filter(|job| {
+                after.map_or(true, |cursor| {
+                    cursor.precedes(job.timestamps.enqueued, job.id)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        jobs.sort_by_key(|job| (job.timestamps.enqueued, job.id));
+
+        let next = match jobs.len() > limit {
+            true => {
+                jobs.truncate(limit);
+                jobs.last().map(Cursor::after)
+            }
+            false => None,
+        };
+
+        Self { jobs, next }
+    }
+}
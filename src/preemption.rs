@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Policy letting the urgent jobs take the slot of a running job of lower priority when every
+/// slot is occupied: the running job is suspended at its next suspension point (see
+/// `Job::yield_if_suspended`), and resumed once the urgent job is finished.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PreemptionPolicy {
+    /// Minimum priority of the jobs preempting the running ones.
+    pub min_priority: i32,
+
+    /// Minimum difference of priority between a job and the running job it preempts.
+    pub min_gap: i32,
+}
+
+impl PreemptionPolicy {
+    /// Creates a policy for the jobs of a minimum priority, preempting any running job of a
+    /// lower priority.
+    ///
+    /// # Arguments
+    /// * `min_priority` - Minimum priority of the jobs preempting the running ones.
+    ///
+    /// # Returns
+    /// An instance of `PreemptionPolicy`.
+    pub fn new(min_priority: i32) -> Self {
+        Self {
+            min_priority,
+            min_gap: 1,
+        }
+    }
+
+    /// Sets the minimum difference of priority between a job and the running job it preempts.
+    ///
+    /// # Arguments
+    /// * `min_gap` - Minimum difference (at least 1).
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn min_gap(self, min_gap: i32) -> Self {
+        Self {
+            min_gap: min_gap.max(1),
+            ..self
+        }
+    }
+
+    /// Checks if a job preempts a running one.
+    ///
+    /// # Arguments
+    /// * `priority` - Priority of the job waiting for a slot.
+    /// * `running` - Priority of the running job.
+    ///
+    /// # Returns
+    /// `true` if the running job is preempted, `false` otherwise.
+    pub fn preempts(&self, priority: i32, running: i32) -> bool {
+        priority >= self.min_priority && priority.saturating_sub(running) >= self.min_gap
+    }
+}
@@ -1,12 +1,66 @@
 pub use async_trait::async_trait;
+pub use cs_jobs_macros::cs_job;
 pub use serde::{Deserialize, Serialize};
 pub use uuid::Uuid;
 
+pub use crate::autoscale::*;
+pub use crate::capabilities::*;
+pub use crate::channel::MessageSender;
+pub use crate::client::*;
+pub use crate::clock::*;
+pub use crate::codec::*;
+pub use crate::completion::{Outcome, OUTCOME_METADATA};
+pub use crate::compression::*;
+pub use crate::config::*;
+pub use crate::context::*;
+pub use crate::encryption::*;
 pub use crate::error::*;
+pub use crate::event_log::{Event, JobEvent};
+#[cfg(feature = "grpc")]
+pub use crate::grpc::JobQueueGrpcServer;
+pub use crate::handles::*;
+pub use crate::hooks::*;
+pub use crate::id_generator::{IdGenerator, SharedIdGenerator, UuidV1Generator, UuidV7Generator};
+pub use crate::idempotency::Enqueued;
+pub use crate::inline::*;
+pub use crate::interceptor::*;
 pub use crate::job::*;
+pub use crate::job_info::*;
 pub use crate::job_queue::*;
 pub use crate::job_queue_builder::*;
+pub use crate::kind_filter::*;
+pub use crate::lock::*;
+pub use crate::middleware::*;
+pub use crate::migration::*;
+pub use crate::named_queue::*;
+pub use crate::pagination::*;
+pub use crate::preemption::*;
+pub use crate::purge::*;
+pub use crate::query::*;
+pub use crate::read_only::*;
+pub use crate::recurrence::*;
+pub use crate::registry::*;
+pub use crate::result_store::*;
+pub use crate::retry::*;
+pub use crate::scheduler::*;
+pub use crate::schema::*;
+pub use crate::service::*;
+pub use crate::shedding::*;
+pub use crate::stats::*;
+pub use crate::subscription::*;
+pub use crate::tenant::*;
+pub use crate::timeline::*;
+#[cfg(feature = "otel")]
+pub use crate::trace_context::OtelPropagator;
+pub use crate::trace_context::{SharedTracePropagator, TraceContext, TracePropagator};
 pub use crate::types::*;
+pub use crate::wal::Wal;
+pub use crate::watchdog::*;
+#[cfg(feature = "webhook")]
+pub use crate::webhook::*;
+pub use crate::wire::*;
+pub use crate::yield_points::*;
 
 pub(crate) use crate::api_err;
+pub use crate::backend::DEFAULT_NAMESPACE;
 pub(crate) use crate::backend::*;
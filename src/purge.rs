@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use crate::prelude::*;
+
+/// Filter used to select the jobs removed by a purge.
+///
+/// Only finished jobs are ever purged. A job is selected if it matches at least one of the
+/// enabled categories (`success`, `error`, `expired`) and all of the optional criteria.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct PurgeFilter {
+    /// Select the jobs that have finished successfully.
+    pub success: bool,
+
+    /// Select the jobs that have finished in error.
+    pub error: bool,
+
+    /// Select the jobs that are expired.
+    pub expired: bool,
+
+    /// Only select the jobs finished for at least this duration.
+    pub older_than: Option<Duration>,
+
+    /// Only select the jobs holding this tag.
+    pub tag: Option<String>,
+
+    /// Remove at most this number of jobs (every job selected if `None`).
+    pub limit: Option<usize>,
+}
+
+impl PurgeFilter {
+    /// Creates a filter selecting every finished job.
+    ///
+    /// # Returns
+    /// An instance of `PurgeFilter`.
+    pub fn finished() -> Self {
+        Self {
+            success: true,
+            error: true,
+            expired: true,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a filter selecting the jobs finished in error.
+    ///
+    /// # Returns
+    /// An instance of `PurgeFilter`.
+    pub fn errors() -> Self {
+        Self {
+            error: true,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a filter selecting the expired jobs.
+    ///
+    /// # Returns
+    /// An instance of `PurgeFilter`.
+    pub fn expired() -> Self {
+        Self {
+            expired: true,
+            ..Default::default()
+        }
+    }
+
+    /// Restricts the filter to the jobs finished for at least a given duration.
+    ///
+    /// # Arguments
+    /// * `age` - Minimum age of the jobs (since they finished).
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn older_than(self, age: Duration) -> Self {
+        Self {
+            older_than: Some(age),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the jobs holding a tag.
+    ///
+    /// # Arguments
+    /// * `tag` - Tag the jobs must hold.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn tag(self, tag: impl Into<String>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            ..self
+        }
+    }
+
+    /// Limits the number of jobs removed at once.
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of jobs removed.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Checks if a job is selected by the filter.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be checked.
+    ///
+    /// # Returns
+    /// `true` if the job must be purged, `false` otherwise.
+    pub fn matches(&self, job: &Job) -> bool {
+        let selected = match job.status() {
+            Status::Finished(ResultStatus::Success) if self.success => true,
+            Status::Finished(
+                ResultStatus::Error
+                | ResultStatus::Interrupted
+                | ResultStatus::Cancelled
+                | ResultStatus::TimedOut
+                | ResultStatus::DeadlineExceeded,
+            ) if self.error => true,
+            Status::Finished(_) => self.expired && job.is_expired(),
+            _ => return false,
+        };
+
+        if !selected {
+            return false;
+        }
+
+        if let Some(age) = self.older_than {
+            let finished = job.timestamps().finished;
+
+            match SystemTime::now().duration_since(finished) {
+                Ok(elapsed) if elapsed >= age => (),
+                _ => return false,
+            }
+        }
+
+        if let Some(tag) = &self.tag {
+            if !job.has_tag(tag) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Progression of a purge run in the background.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct PurgeProgress {
+    /// Number of jobs removed so far.
+    pub removed: usize,
+
+    /// Whether the purge is over.
+    pub done: bool,
+}
+
+/// Purge removing the jobs by chunks in the background (see `JobQueue::purge_in_background`).
+pub struct PurgeTask {
+    /// Identifier of the purge, given by its progress notifications.
+    id: Uuid,
+
+    /// Handle of the task, returning the number of jobs removed.
+    handle: JoinHandle<usize>,
+}
+
+impl PurgeTask {
+    /// Creates a purge task.
+    ///
+    /// # Arguments
+    /// * `id` - Identifier of the purge.
+    /// * `handle` - Handle of the task.
+    ///
+    /// # Returns
+    /// An instance of `PurgeTask`.
+    pub(crate) fn new(id: Uuid, handle: JoinHandle<usize>) -> Self {
+        Self { id, handle }
+    }
+
+    /// Gets the identifier of the purge.
+    ///
+    /// # Returns
+    /// The ID given by the `Notification::PurgeProgress` of the purge.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Waits for the purge to be over.
+    ///
+    /// # Returns
+    /// The number of jobs removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn wait(self) -> Result<usize, ApiError> {
+        self.handle.await.map_err(|e| api_err!(e.into()))
+    }
+}
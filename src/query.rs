@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Filter selecting the jobs counted or aggregated by the backend (see `Backend::count`).
+///
+/// A job is selected if it matches all of the criteria set, every job being selected by the
+/// default filter.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct JobFilter {
+    /// Only select the jobs with this status.
+    pub status: Option<Status>,
+
+    /// Only select the jobs of this kind.
+    pub kind: Option<String>,
+
+    /// Only select the jobs holding this tag.
+    pub tag: Option<String>,
+
+    /// Only select the jobs of this named queue.
+    pub queue: Option<String>,
+}
+
+impl JobFilter {
+    /// Restricts the filter to the jobs with a status.
+    ///
+    /// # Arguments
+    /// * `status` - Status of the jobs.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn status(self, status: Status) -> Self {
+        Self {
+            status: Some(status),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the jobs of a kind.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the jobs.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn kind(self, kind: impl Into<String>) -> Self {
+        Self {
+            kind: Some(kind.into()),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the jobs holding a tag.
+    ///
+    /// # Arguments
+    /// * `tag` - Tag the jobs must hold.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn tag(self, tag: impl Into<String>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the jobs of a named queue.
+    ///
+    /// # Arguments
+    /// * `queue` - Name of the queue.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn queue(self, queue: impl Into<String>) -> Self {
+        Self {
+            queue: Some(queue.into()),
+            ..self
+        }
+    }
+
+    /// Checks if a job is selected by the filter.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be checked.
+    ///
+    /// # Returns
+    /// `true` if the job is selected, `false` otherwise.
+    pub fn matches(&self, job: &Job) -> bool {
+        self.status.map_or(true, |status| job.status() == status)
+            && self.kind.as_ref().map_or(true, |kind| job.kind() == kind)
+            && self
+                .tag
+                .as_ref()
+                .map_or(true, |tag| job.tags().contains(tag))
+            && self
+                .queue
+                .as_ref()
+                .map_or(true, |queue| job.queue() == queue)
+    }
+}
+
+/// Criterion grouping the jobs aggregated by the backend (see `Backend::aggregate`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GroupBy {
+    /// Jobs grouped by status.
+    Status,
+
+    /// Jobs grouped by kind.
+    Kind,
+
+    /// Jobs grouped by named queue.
+    Queue,
+}
+
+impl GroupBy {
+    /// Gets the group of a job.
+    ///
+    /// # Arguments
+    /// * `job` - Job aggregated.
+    ///
+    /// # Returns
+    /// The key of the group of the job.
+    pub fn key(&self, job: &Job) -> GroupKey {
+        match self {
+            Self::Status => GroupKey::Status(job.status()),
+            Self::Kind => GroupKey::Kind(job.kind().to_owned()),
+            Self::Queue => GroupKey::Queue(job.queue().to_owned()),
+        }
+    }
+}
+
+/// Key of a group of jobs aggregated by the backend.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum GroupKey {
+    /// Status of the jobs of the group.
+    Status(Status),
+
+    /// Kind of the jobs of the group.
+    Kind(String),
+
+    /// Named queue of the jobs of the group.
+    Queue(String),
+}
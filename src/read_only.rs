@@ -0,0 +1,285 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Handle on a job queue exposing only its read APIs (status, result, listing and
+/// statistics), safe to be handed to reporting or customer-facing services.
+pub struct ReadOnlyQueue<RoutineType, Context> {
+    /// Queue inspected.
+    queue: Arc<JobQueue<RoutineType, Context>>,
+}
+
+impl<RoutineType, Context> Clone for ReadOnlyQueue<RoutineType, Context> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<RoutineType, Context> From<Arc<JobQueue<RoutineType, Context>>>
+    for ReadOnlyQueue<RoutineType, Context>
+{
+    fn from(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self { queue }
+    }
+}
+
+impl<RoutineType, Context> ReadOnlyQueue<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates a read-only handle on a queue.
+    ///
+    /// # Arguments
+    /// * `queue` - Queue to be inspected.
+    ///
+    /// # Returns
+    /// An instance of `ReadOnlyQueue`.
+    pub fn new(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self { queue }
+    }
+
+    /// Get the status of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The status of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.queue.job_status(id).await
+    }
+
+    /// Get the execution attempts of a job, the oldest first.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The attempts of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_attempts(&self, id: &Uuid) -> Result<Vec<Attempt>, ApiError> {
+        self.queue.job_attempts(id).await
+    }
+
+    /// Get the status of several jobs in a single call to the backend.
+    ///
+    /// # Arguments
+    /// * `ids` - IDs of the jobs to be inspected.
+    ///
+    /// # Returns
+    /// The status of the jobs, by ID; the jobs not found are left out.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        self.queue.job_statuses(ids).await
+    }
+
+    /// Get the result of a job. The result is not consumed: the `ExpirePolicy::OnResultFetch`
+    /// policy is left to the owner of the queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The result of the job as vector of bytes.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        self.queue.peek_result(id).await
+    }
+
+    /// Get the progression of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The progression of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
+        self.queue.job_progression(id).await
+    }
+
+    /// Gets the events of the jobs recorded after a sequence number.
+    ///
+    /// # Arguments
+    /// * `seq` - Sequence number of the last event synced (0 to get every event retained).
+    ///
+    /// # Returns
+    /// The events, in order.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn events_since(&self, seq: u64) -> Result<Vec<Event>, ApiError> {
+        self.queue.events_since(seq)
+    }
+
+    /// Estimates the completion time of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The estimated completion time, `None` if the job is finished or nothing is known yet.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_eta(&self, id: &Uuid) -> Result<Option<SystemTime>, ApiError> {
+        self.queue.job_eta(id).await
+    }
+
+    /// Get the metadata attached to a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The key/value pairs attached.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn job_metadata(
+        &self,
+        id: &Uuid,
+    ) -> Result<BTreeMap<String, serde_json::Value>, ApiError> {
+        self.queue.job_metadata(id).await
+    }
+
+    /// Streams the result of a job chunk by chunk, as emitted by its routine.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be followed.
+    ///
+    /// # Returns
+    /// The receiving end of the stream, closed once the job is finished.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn stream_result(
+        &self,
+        id: &Uuid,
+    ) -> Result<tokio::sync::mpsc::Receiver<Result<Vec<u8>, ApiError>>, ApiError> {
+        self.queue.stream_result(id).await
+    }
+
+    /// Get the list of jobs.
+    ///
+    /// # Returns
+    /// The summary of every job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn jobs(&self) -> Result<Vec<JobInfo>, ApiError> {
+        self.queue.jobs().await
+    }
+
+    /// Get a page of the list of jobs, ordered by enqueue time then ID.
+    ///
+    /// # Arguments
+    /// * `after` - Cursor returned with the previous page, `None` for the first page.
+    /// * `limit` - Maximum number of jobs in the page.
+    ///
+    /// # Returns
+    /// The page, with the cursor of the next one.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn jobs_page(
+        &self,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Result<JobPage, ApiError> {
+        self.queue.jobs_page(after, limit).await
+    }
+
+    /// Counts the jobs selected by a filter, without listing them.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs counted.
+    ///
+    /// # Returns
+    /// The number of jobs selected.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn count_jobs(&self, filter: &JobFilter) -> Result<usize, ApiError> {
+        self.queue.count_jobs(filter).await
+    }
+
+    /// Counts the jobs selected by a filter, by group, without listing them.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs counted.
+    /// * `group_by` - Criterion grouping the jobs.
+    ///
+    /// # Returns
+    /// The number of jobs selected, by group.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn aggregate_jobs(
+        &self,
+        filter: &JobFilter,
+        group_by: GroupBy,
+    ) -> Result<HashMap<GroupKey, usize>, ApiError> {
+        self.queue.aggregate_jobs(filter, group_by).await
+    }
+
+    /// Gets a snapshot of the jobs of the queue, counted by status.
+    ///
+    /// # Returns
+    /// The statistics of the queue.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn stats(&self) -> Result<QueueStats, ApiError> {
+        self.queue.stats().await
+    }
+
+    /// Gets the utilization and saturation of the worker.
+    ///
+    /// # Returns
+    /// An instance of `WorkerStats`.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn worker_stats(&self) -> Result<WorkerStats, ApiError> {
+        self.queue.worker_stats().await
+    }
+
+    /// Gets a snapshot of the jobs of every named queue, counted by status.
+    ///
+    /// # Returns
+    /// The statistics of the queues, by name.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn queue_stats(&self) -> Result<HashMap<String, QueueStats>, ApiError> {
+        self.queue.queue_stats().await
+    }
+
+    /// Estimates the time needed to process every pending job.
+    ///
+    /// # Returns
+    /// The estimated duration, `None` if unknown.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn estimated_drain_time(&self) -> Result<Option<Duration>, ApiError> {
+        self.queue.estimated_drain_time().await
+    }
+}
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Policy applied to the occurrences of a recurring job missed while the queue was down or
+/// too busy, i.e. once the following occurrence is already due.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum MisfirePolicy {
+    /// The missed occurrences are not run, the job waits for its next occurrence.
+    Skip,
+
+    /// The missed occurrences are run once, then the job waits for its next occurrence.
+    #[default]
+    RunOnce,
+
+    /// Every missed occurrence is run, one after the other, until the job is caught up.
+    RunAll,
+}
+
+/// Policy applied to an occurrence of a recurring job due while the previous one is still
+/// running.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum OverlapPolicy {
+    /// The occurrence is not run, the job waits for its next occurrence.
+    Skip,
+
+    /// The occurrence waits for the previous one to finish before running.
+    #[default]
+    Queue,
+
+    /// The previous occurrence is cancelled, and the new one run right away.
+    CancelPrevious,
+}
+
+/// Recurrence of a job, run again at a fixed interval (see `Job::set_recurrence`). Every
+/// occurrence is a new job, enqueued once the previous one starts running.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Recurrence {
+    /// Interval between two occurrences.
+    pub every: Duration,
+
+    /// Policy applied to the missed occurrences.
+    pub misfire: MisfirePolicy,
+
+    /// Policy applied to the occurrences due while the previous one is still running.
+    #[serde(default)]
+    pub overlap: OverlapPolicy,
+}
+
+impl Recurrence {
+    /// Creates a recurrence at a fixed interval, the missed occurrences being run once and the
+    /// overlapping ones waiting for the previous one.
+    ///
+    /// # Arguments
+    /// * `every` - Interval between two occurrences.
+    ///
+    /// # Returns
+    /// An instance of `Recurrence`.
+    pub fn every(every: Duration) -> Self {
+        Self {
+            every,
+            misfire: MisfirePolicy::default(),
+            overlap: OverlapPolicy::default(),
+        }
+    }
+
+    /// Sets the policy applied to the missed occurrences.
+    ///
+    /// # Arguments
+    /// * `misfire` - Policy applied.
+    ///
+    /// # Returns
+    /// An instance of `Recurrence`.
+    pub fn misfire(self, misfire: MisfirePolicy) -> Self {
+        Self { misfire, ..self }
+    }
+
+    /// Sets the policy applied to the occurrences due while the previous one is still running.
+    ///
+    /// # Arguments
+    /// * `overlap` - Policy applied.
+    ///
+    /// # Returns
+    /// An instance of `Recurrence`.
+    pub fn overlap(self, overlap: OverlapPolicy) -> Self {
+        Self { overlap, ..self }
+    }
+
+    /// Checks if an occurrence has misfired: the following one is already due.
+    ///
+    /// # Arguments
+    /// * `run_at` - Time of the occurrence.
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// `true` if misfired, `false` otherwise.
+    pub fn is_misfired(&self, run_at: SystemTime, now: SystemTime) -> bool {
+        run_at + self.every <= now
+    }
+
+    /// Computes the time of the occurrence following another one.
+    ///
+    /// # Arguments
+    /// * `run_at` - Time of the previous occurrence.
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// The time of the next occurrence, possibly already due with `MisfirePolicy::RunAll`.
+    pub fn next(&self, run_at: SystemTime, now: SystemTime) -> SystemTime {
+        let next = run_at + self.every;
+
+        if self.misfire == MisfirePolicy::RunAll || next > now || self.every.is_zero() {
+            return next;
+        }
+
+        // Missed occurrences jumped over
+        let late = now.duration_since(next).unwrap_or_default();
+        let missed = late.as_nanos() / self.every.as_nanos() + 1;
+
+        next + self
+            .every
+            .saturating_mul(u32::try_from(missed).unwrap_or(u32::MAX))
+    }
+}
+
+/// Decision taken for an occurrence about to run (see `Series::start`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Overlap {
+    /// No other occurrence running, the occurrence runs.
+    Run,
+
+    /// The occurrence is skipped.
+    Skip,
+
+    /// The occurrence waits for the previous one.
+    Wait,
+
+    /// The previous occurrence, with this ID, is cancelled and the occurrence runs.
+    CancelPrevious(Uuid),
+}
+
+/// Occurrences of the recurring jobs running on a queue, by series.
+#[derive(Default)]
+pub(crate) struct Series {
+    /// Occurrence running, by series.
+    running: HashMap<Uuid, Uuid>,
+
+    /// Occurrences waiting for the previous one to finish, by series.
+    waiting: HashMap<Uuid, VecDeque<PendingJob>>,
+}
+
+impl Series {
+    /// Registers an occurrence about to run, unless another occurrence of its series is running
+    /// and its overlap policy says otherwise.
+    ///
+    /// # Arguments
+    /// * `job` - Occurrence about to run.
+    ///
+    /// # Returns
+    /// The decision taken for the occurrence.
+    pub(crate) fn start(&mut self, job: &Job) -> Overlap {
+        let Some(recurrence) = job.recurrence() else {
+            return Overlap::Run;
+        };
+
+        let Some(previous) = self.running.get(&job.series()).copied() else {
+            self.running.insert(job.series(), job.id());
+
+            return Overlap::Run;
+        };
+
+        match recurrence.overlap {
+            OverlapPolicy::Skip => Overlap::Skip,
+            OverlapPolicy::Queue => {
+                self.waiting
+                    .entry(job.series())
+                    .or_default()
+                    .push_back(PendingJob::from(job));
+
+                Overlap::Wait
+            }
+            OverlapPolicy::CancelPrevious => {
+                self.running.insert(job.series(), job.id());
+
+                Overlap::CancelPrevious(previous)
+            }
+        }
+    }
+
+    /// Removes an occurrence waiting for the previous one, when cancelled.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the occurrence.
+    ///
+    /// # Returns
+    /// `true` if the occurrence was waiting, `false` otherwise.
+    pub(crate) fn remove_waiting(&mut self, id: &Uuid) -> bool {
+        let mut removed = false;
+
+        self.waiting.retain(|_, waiting| {
+            waiting.retain(|pending_job| {
+                let found = pending_job.id == *id;
+                removed |= found;
+
+                !found
+            });
+
+            !waiting.is_empty()
+        });
+
+        removed
+    }
+
+    /// Unregisters an occurrence no longer running.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the occurrence.
+    ///
+    /// # Returns
+    /// The occurrence of the series waiting for this one, if any.
+    pub(crate) fn finish(&mut self, id: &Uuid) -> Option<PendingJob> {
+        let series = self
+            .running
+            .iter()
+            .find_map(|(series, running)| (running == id).then_some(*series))?;
+
+        self.running.remove(&series);
+
+        let waiting = self.waiting.get_mut(&series)?;
+        let next = waiting.pop_front();
+
+        if waiting.is_empty() {
+            self.waiting.remove(&series);
+        }
+
+        next
+    }
+}
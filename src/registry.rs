@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::prelude::*;
+
+/// Future returned when running a registered routine.
+type RegisteredFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>, ApiError>> + Send + 'a>>;
+
+/// Routine registered by name, type-erased on its arguments.
+struct Entry<Context> {
+    /// Runs the routine of a job.
+    run: for<'a> fn(
+        &'a Job,
+        SharedMessageChannel,
+        Option<ContextMode<Context>>,
+    ) -> RegisteredFuture<'a>,
+
+    /// Gets the private data needed by the routine of a job.
+    requirements: fn(&Job) -> Result<Vec<PrivateDataRequirement>, ApiError>,
+}
+
+/// Routines registered by name, run by the jobs created with `Job::registered` in place of
+/// the routine type of the queue. Independent modules can register their own routines without
+/// editing a central enum, and jobs of unknown names are kept pending.
+pub struct RoutineRegistry<Context> {
+    /// Routines by name.
+    entries: HashMap<String, Entry<Context>>,
+}
+
+impl<Context> Default for RoutineRegistry<Context> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<Context: Send + 'static> std::fmt::Debug for RoutineRegistry<Context> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RoutineRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+impl<Context: Send + 'static> RoutineRegistry<Context> {
+    /// Creates an empty registry.
+    ///
+    /// # Returns
+    /// An instance of `RoutineRegistry`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a routine under a name, its arguments being the payload of the jobs.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the routine.
+    ///
+    /// # Returns
+    /// The registry, to register more routines.
+    ///
+    /// # Errors
+    /// `Error::RoutineAlreadyRegistered` if the name is taken.
+    pub fn register<T>(&mut self, name: impl Into<String>) -> Result<&mut Self, ApiError>
+    where
+        T: Routine<Context> + Sync + 'static,
+    {
+        let name = name.into();
+
+        if self.entries.contains_key(&name) {
+            return Err(api_err!(Error::RoutineAlreadyRegistered(name)));
+        }
+
+        self.entries.insert(
+            name,
+            Entry {
+                run: |job, messages_channel, context| {
+                    Box::pin(async move {
+                        let routine: T = job.registered_args()?;
+
+                        job.call(routine, messages_channel, context).await
+                    })
+                },
+                requirements: |job| {
+                    let routine: T = job.registered_args()?;
+
+                    Ok(routine.private_data_requirements())
+                },
+            },
+        );
+
+        Ok(self)
+    }
+
+    /// Checks if a routine is registered under a name.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the routine.
+    ///
+    /// # Returns
+    /// `true` if registered, `false` otherwise.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Gets the names of the registered routines.
+    ///
+    /// # Returns
+    /// The names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(String::as_str).collect();
+
+        names.sort_unstable();
+        names
+    }
+
+    /// Gets the private data needed by the routine of a registered job.
+    ///
+    /// # Arguments
+    /// * `job` - Job created with `Job::registered`.
+    ///
+    /// # Returns
+    /// The requirements of the routine.
+    ///
+    /// # Errors
+    /// `Error::RoutineNotRegistered` for an unknown name, or a deserialization error.
+    pub(crate) fn requirements(&self, job: &Job) -> Result<Vec<PrivateDataRequirement>, ApiError> {
+        (self.entry(job)?.requirements)(job)
+    }
+
+    /// Runs the routine of a registered job.
+    ///
+    /// # Arguments
+    /// * `job` - Job created with `Job::registered`.
+    /// * `messages_channel` - Channel used to send message to the job queue.
+    /// * `context` - Context shared by the routines.
+    ///
+    /// # Errors
+    /// `Error::RoutineNotRegistered` for an unknown name, or any error of the routine.
+    pub(crate) async fn run(
+        &self,
+        job: &Job,
+        messages_channel: SharedMessageChannel,
+        context: Option<ContextMode<Context>>,
+    ) -> Result<Vec<u8>, ApiError> {
+        (self.entry(job)?.run)(job, messages_channel, context).await
+    }
+
+    /// Gets the entry of the routine of a registered job.
+    ///
+    /// # Arguments
+    /// * `job` - Job created with `Job::registered`.
+    ///
+    /// # Returns
+    /// The entry of the routine.
+    ///
+    /// # Errors
+    /// `Error::RoutineNotRegistered` for an unknown name.
+    fn entry(&self, job: &Job) -> Result<&Entry<Context>, ApiError> {
+        self.entries
+            .get(job.kind())
+            .ok_or(api_err!(Error::RoutineNotRegistered(job.kind().to_owned())))
+    }
+}
@@ -0,0 +1,570 @@
+//! Backend retrying the operations of another backend failing with transient errors, with an
+//! exponential backoff, and failing fast through a circuit breaker once the backend fails
+//! repeatedly. Distinct from the retries of the jobs, it keeps a remote backend briefly
+//! unreachable from failing the jobs or losing their status updates.
+//!
+//! The operations are retried as is, the backend wrapped being expected to apply them
+//! idempotently. Only the asynchronous operations are retried: the synchronous ones, called
+//! with the backend locked from the threads of the runtime, fail fast rather than block them
+//! during the backoff. Running a job (`Backend::run`) is never retried.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::prelude::*;
+
+/// Checks if an error of a backend is transient, i.e. the operation may succeed if retried.
+///
+/// # Arguments
+/// * `error` - Error of the backend.
+///
+/// # Returns
+/// `true` if transient, `false` otherwise.
+pub fn is_transient(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::CannotAccessBackend(_)
+            | Error::GenericError(_)
+            | Error::IO(_)
+            | Error::Remote(_)
+            | Error::Timeout(_)
+    )
+}
+
+/// Retries of the asynchronous operations of a backend failing with a transient error.
+#[derive(Clone, Copy, Debug)]
+pub struct BackendRetry {
+    /// Maximum number of retries of an operation (never retried if 0).
+    pub max_retries: u32,
+
+    /// Delay before the first retry, doubled for every following one.
+    pub initial_backoff: Duration,
+
+    /// Maximum delay between two retries.
+    pub max_backoff: Duration,
+
+    /// Checks if an error is worth retrying the operation (see `is_transient`).
+    pub retry_if: fn(&Error) -> bool,
+}
+
+impl Default for BackendRetry {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+            retry_if: is_transient,
+        }
+    }
+}
+
+impl BackendRetry {
+    /// Sets the maximum number of retries of an operation, 3 by default.
+    ///
+    /// # Arguments
+    /// * `max_retries` - Maximum number of retries, the operations never retried if 0.
+    ///
+    /// # Returns
+    /// An instance of `BackendRetry`.
+    pub fn max_retries(self, max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..self
+        }
+    }
+
+    /// Sets the delays between the retries, 10ms doubled up to 1s by default.
+    ///
+    /// # Arguments
+    /// * `initial` - Delay before the first retry.
+    /// * `max` - Maximum delay between two retries.
+    ///
+    /// # Returns
+    /// An instance of `BackendRetry`.
+    pub fn backoff(self, initial: Duration, max: Duration) -> Self {
+        Self {
+            initial_backoff: initial,
+            max_backoff: max,
+            ..self
+        }
+    }
+
+    /// Sets the check of the errors worth retrying the operation, `is_transient` by default.
+    ///
+    /// # Arguments
+    /// * `retry_if` - Check of the errors.
+    ///
+    /// # Returns
+    /// An instance of `BackendRetry`.
+    pub fn retry_if(self, retry_if: fn(&Error) -> bool) -> Self {
+        Self { retry_if, ..self }
+    }
+
+    /// Computes the delay before a retry.
+    ///
+    /// # Arguments
+    /// * `retry` - Number of the retry, starting at 0.
+    ///
+    /// # Returns
+    /// The delay before the retry.
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// Circuit breaker failing the operations of a backend fast, without calling it, once it fails
+/// repeatedly. The circuit is closed again by the first operation succeeding after a cooldown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CircuitBreaker {
+    /// Number of operations failing in a row, once retried, opening the circuit.
+    pub failure_threshold: u32,
+
+    /// Delay during which the circuit stays open, before the backend is called again.
+    pub cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    /// Creates a circuit breaker.
+    ///
+    /// # Arguments
+    /// * `failure_threshold` - Number of operations failing in a row opening the circuit.
+    /// * `cooldown` - Delay during which the circuit stays open.
+    ///
+    /// # Returns
+    /// An instance of `CircuitBreaker`.
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+        }
+    }
+}
+
+/// Failures of the backend tracked by the circuit breaker.
+#[derive(Default)]
+struct Failures {
+    /// Number of operations failing in a row.
+    count: u32,
+
+    /// Time until which the circuit is open.
+    open_until: Option<Instant>,
+}
+
+/// Retries and circuit breaker applied to the operations.
+struct Guard {
+    /// Retries of the operations.
+    retry: BackendRetry,
+
+    /// Circuit breaker, if any.
+    breaker: Option<CircuitBreaker>,
+
+    /// Failures of the backend.
+    failures: Mutex<Failures>,
+}
+
+impl Guard {
+    /// Checks if an operation is let through to the backend.
+    ///
+    /// # Errors
+    /// `Error::CircuitOpen` if the circuit is open.
+    fn admit(&self) -> Result<(), ApiError> {
+        let failures = self
+            .failures
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessBackend(e.to_string())))?;
+
+        match failures.open_until {
+            Some(open_until) if Instant::now() < open_until => Err(api_err!(Error::CircuitOpen)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records an operation succeeding, closing the circuit.
+    fn succeeded(&self) {
+        if let Ok(mut failures) = self.failures.lock() {
+            *failures = Failures::default();
+        }
+    }
+
+    /// Records an operation failing.
+    ///
+    /// # Arguments
+    /// * `error` - Error of the operation.
+    /// * `retry` - Number of retries of the operation so far.
+    ///
+    /// # Returns
+    /// The delay before retrying the operation, `None` if it's not retried.
+    fn failed(&self, error: &Error, retry: u32) -> Option<Duration> {
+        // Errors of the operation itself (job not found...) not telling anything of the backend
+        if !(self.retry.retry_if)(error) {
+            return None;
+        }
+
+        if retry < self.retry.max_retries {
+            return Some(self.retry.delay(retry));
+        }
+
+        self.gave_up();
+
+        None
+    }
+
+    /// Records an operation given up on a transient error, opening the circuit once too many
+    /// fail in a row.
+    fn gave_up(&self) {
+        if let (Some(breaker), Ok(mut failures)) = (self.breaker, self.failures.lock()) {
+            failures.count += 1;
+
+            if failures.count >= breaker.failure_threshold {
+                failures.open_until = Some(Instant::now() + breaker.cooldown);
+            }
+        }
+    }
+
+    /// Calls a synchronous operation of the backend, once: retrying it would block the thread
+    /// of the caller during the backoff.
+    ///
+    /// # Arguments
+    /// * `operation` - Operation called.
+    ///
+    /// # Returns
+    /// The result of the operation.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn call<T>(&self, operation: impl FnOnce() -> Result<T, ApiError>) -> Result<T, ApiError> {
+        self.admit()?;
+
+        match operation() {
+            Ok(value) => {
+                self.succeeded();
+
+                Ok(value)
+            }
+            Err(e) => {
+                if (self.retry.retry_if)(&e) {
+                    self.gave_up();
+                }
+
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Backend retrying the operations of another backend, behind a circuit breaker.
+pub struct ResilientBackend<B> {
+    /// Backend wrapped.
+    inner: B,
+
+    /// Retries and circuit breaker applied to the operations.
+    guard: Guard,
+}
+
+impl<B> ResilientBackend<B> {
+    /// Wraps a backend, its asynchronous operations being retried with the default
+    /// `BackendRetry`, without circuit breaker.
+    ///
+    /// # Arguments
+    /// * `inner` - Backend wrapped.
+    ///
+    /// # Returns
+    /// An instance of `ResilientBackend`.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            guard: Guard {
+                retry: BackendRetry::default(),
+                breaker: None,
+                failures: Mutex::new(Failures::default()),
+            },
+        }
+    }
+
+    /// Sets the retries of the asynchronous operations.
+    ///
+    /// # Arguments
+    /// * `retry` - Retries of the operations.
+    ///
+    /// # Returns
+    /// An instance of `ResilientBackend`.
+    pub fn retry(mut self, retry: BackendRetry) -> Self {
+        self.guard.retry = retry;
+
+        self
+    }
+
+    /// Sets the circuit breaker of the backend.
+    ///
+    /// # Arguments
+    /// * `breaker` - Circuit breaker.
+    ///
+    /// # Returns
+    /// An instance of `ResilientBackend`.
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.guard.breaker = Some(breaker);
+
+        self
+    }
+
+    /// Checks if the circuit is open, the operations failing without calling the backend.
+    ///
+    /// # Returns
+    /// `true` if open, `false` otherwise.
+    pub fn is_open(&self) -> bool {
+        self.guard.admit().is_err()
+    }
+}
+
+#[async_trait]
+impl<B, RoutineType, Context> Backend<RoutineType, Context> for ResilientBackend<B>
+where
+    B: Backend<RoutineType, Context>,
+    RoutineType: Routine<Context> + Sync,
+    for<'async_trait> Context: Send + 'async_trait,
+{
+    async fn get(&mut self, id: &Uuid) -> Result<Job, ApiError> {
+        let mut attempt = 0;
+
+        loop {
+            self.guard.admit()?;
+
+            match self.inner.get(id).await {
+                Ok(job) => {
+                    self.guard.succeeded();
+
+                    return Ok(job);
+                }
+                Err(e) => match self.guard.failed(&e, attempt) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+            }
+
+            attempt += 1;
+        }
+    }
+
+    fn schedule(&mut self, job: Job) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.schedule(job))
+    }
+
+    async fn run(
+        &mut self,
+        id: &Uuid,
+        context: Option<ContextMode<Context>>,
+        messages_channel: SharedMessageChannel,
+    ) -> Result<(), ApiError> {
+        // Not retried, the routine of the job being run by the call
+        self.inner.run(id, context, messages_channel).await
+    }
+
+    fn status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.guard.call(|| self.inner.status(id))
+    }
+
+    fn statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        self.guard.call(|| self.inner.statuses(ids))
+    }
+
+    fn set_status(&mut self, id: &Uuid, status: Status) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.set_status(id, status))
+    }
+
+    fn result(&self, id: &Uuid) -> Result<&[u8], ApiError> {
+        self.guard.call(|| self.inner.result(id))
+    }
+
+    fn set_result(&mut self, id: &Uuid, result: Vec<u8>) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.set_result(id, result))
+    }
+
+    fn set_result_ref(&mut self, id: &Uuid, key: String) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.set_result_ref(id, key))
+    }
+
+    fn add_attempt(&mut self, id: &Uuid, attempt: Attempt) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.add_attempt(id, attempt))
+    }
+
+    fn set_checkpoint(&mut self, id: &Uuid, checkpoint: Vec<u8>) -> Result<(), ApiError> {
+        self.guard
+            .call(|| self.inner.set_checkpoint(id, checkpoint))
+    }
+
+    fn set_metadata(
+        &mut self,
+        id: &Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.set_metadata(id, key, value))
+    }
+
+    fn append_chunk(&mut self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.append_chunk(id, chunk))
+    }
+
+    fn chunks(&self, id: &Uuid, from: usize) -> Result<Vec<Vec<u8>>, ApiError> {
+        self.guard.call(|| self.inner.chunks(id, from))
+    }
+
+    fn set_steps(&mut self, id: &Uuid, steps: u64) -> Result<Progression, ApiError> {
+        self.guard.call(|| self.inner.set_steps(id, steps))
+    }
+
+    fn set_step(&mut self, id: &Uuid, step: u64) -> Result<Progression, ApiError> {
+        self.guard.call(|| self.inner.set_step(id, step))
+    }
+
+    fn progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
+        self.guard.call(|| self.inner.progression(id))
+    }
+
+    fn routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
+        self.guard.call(|| self.inner.routine(id))
+    }
+
+    fn expire_policy(&self, id: &Uuid) -> Result<ExpirePolicy, ApiError> {
+        self.guard.call(|| self.inner.expire_policy(id))
+    }
+
+    fn set_expiration(&mut self, id: &Uuid, expired: SystemTime) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.set_expiration(id, expired))
+    }
+
+    fn remove(&mut self, id: &Uuid) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.remove(id))
+    }
+
+    fn remove_expired(&mut self) -> Result<Vec<Uuid>, ApiError> {
+        self.guard.call(|| self.inner.remove_expired())
+    }
+
+    fn expire_results(&mut self) -> Result<Vec<(Uuid, Option<String>)>, ApiError> {
+        self.guard.call(|| self.inner.expire_results())
+    }
+
+    fn retain_last(&mut self, count: usize) -> Result<Vec<Uuid>, ApiError> {
+        self.guard.call(|| self.inner.retain_last(count))
+    }
+
+    fn purge(&mut self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        self.guard.call(|| self.inner.purge(filter))
+    }
+
+    fn acquire_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.guard.call(|| self.inner.acquire_lock(key, owner, ttl))
+    }
+
+    fn refresh_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.guard.call(|| self.inner.refresh_lock(key, owner, ttl))
+    }
+
+    fn release_lock(&mut self, key: &str, owner: &str) -> Result<bool, ApiError> {
+        self.guard.call(|| self.inner.release_lock(key, owner))
+    }
+
+    fn release_stale_locks(&mut self) -> Result<Vec<Lock>, ApiError> {
+        self.guard.call(|| self.inner.release_stale_locks())
+    }
+
+    fn claim(
+        &mut self,
+        worker_id: &str,
+        lease: Duration,
+        kinds: &KindFilter,
+        capabilities: &Capabilities,
+    ) -> Result<Option<Job>, ApiError> {
+        self.guard
+            .call(|| self.inner.claim(worker_id, lease, kinds, capabilities))
+    }
+
+    fn extend_lease(
+        &mut self,
+        id: &Uuid,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError> {
+        self.guard
+            .call(|| self.inner.extend_lease(id, worker_id, lease))
+    }
+
+    fn release(&mut self, id: &Uuid, worker_id: &str) -> Result<bool, ApiError> {
+        self.guard.call(|| self.inner.release(id, worker_id))
+    }
+
+    fn reap_orphans(
+        &mut self,
+        timeout: Duration,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, ApiError> {
+        self.guard.call(|| self.inner.reap_orphans(timeout, policy))
+    }
+
+    fn timeline(
+        &self,
+        range: &Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError> {
+        self.guard.call(|| self.inner.timeline(range, filter))
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), ApiError> {
+        self.guard.call(|| self.inner.set_paused(paused))
+    }
+
+    fn is_paused(&self) -> Result<bool, ApiError> {
+        self.guard.call(|| self.inner.is_paused())
+    }
+
+    fn record_duration(&mut self, kind: &str, duration: Duration) -> Result<(), ApiError> {
+        self.guard
+            .call(|| self.inner.record_duration(kind, duration))
+    }
+
+    fn duration_stats(&self, kind: &str) -> Result<Option<DurationStats>, ApiError> {
+        self.guard.call(|| self.inner.duration_stats(kind))
+    }
+
+    fn set_namespace(&mut self, namespace: &str) {
+        self.inner.set_namespace(namespace)
+    }
+
+    fn set_migrator(&mut self, migrator: SharedMigrator) {
+        self.inner.set_migrator(migrator)
+    }
+
+    fn set_codec(&mut self, codec: SharedCodec) {
+        self.inner.set_codec(codec)
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        self.inner.set_compression(compression)
+    }
+
+    fn set_encryption(&mut self, encryption: Encryption) {
+        self.inner.set_encryption(encryption)
+    }
+
+    fn set_clock(&mut self, clock: SharedClock) {
+        self.inner.set_clock(clock)
+    }
+
+    fn jobs(&self) -> Result<Vec<Job>, ApiError> {
+        self.guard.call(|| self.inner.jobs())
+    }
+
+    fn job_infos(&self) -> Result<Vec<JobInfo>, ApiError> {
+        self.guard.call(|| self.inner.job_infos())
+    }
+
+    fn jobs_page(&self, after: Option<&Cursor>, limit: usize) -> Result<JobPage, ApiError> {
+        self.guard.call(|| self.inner.jobs_page(after, limit))
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share the result store across threads.
+pub type SharedResultStore = Arc<dyn ResultStore>;
+
+/// Trait of the external stores holding the large results of the jobs, the backend keeping
+/// only a reference to them (see `ResultOffload`).
+pub trait ResultStore: Send + Sync {
+    /// Writes the result of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    /// * `result` - Result of the job.
+    ///
+    /// # Returns
+    /// The key of the result in the store.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn put(&self, id: &Uuid, result: &[u8]) -> Result<String, ApiError>;
+
+    /// Reads a result.
+    ///
+    /// # Arguments
+    /// * `key` - Key of the result in the store.
+    ///
+    /// # Returns
+    /// The result.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn get(&self, key: &str) -> Result<Vec<u8>, ApiError>;
+
+    /// Deletes a result.
+    ///
+    /// # Arguments
+    /// * `key` - Key of the result in the store.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn remove(&self, key: &str) -> Result<(), ApiError>;
+}
+
+/// Result store writing every result to a file of a directory.
+#[derive(Clone, Debug)]
+pub struct FsResultStore {
+    /// Directory of the results.
+    dir: PathBuf,
+}
+
+impl FsResultStore {
+    /// Creates a store writing the results to a directory, created if missing.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory of the results.
+    ///
+    /// # Returns
+    /// An instance of `FsResultStore`.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, ApiError> {
+        let dir = dir.into();
+
+        std::fs::create_dir_all(&dir).map_err(|e| api_err!(e.into()))?;
+
+        Ok(Self { dir })
+    }
+}
+
+impl ResultStore for FsResultStore {
+    fn put(&self, id: &Uuid, result: &[u8]) -> Result<String, ApiError> {
+        let key = format!("{id}.result");
+
+        std::fs::write(self.dir.join(&key), result).map_err(|e| api_err!(e.into()))?;
+
+        Ok(key)
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, ApiError> {
+        std::fs::read(self.dir.join(key)).map_err(|e| api_err!(e.into()))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), ApiError> {
+        match std::fs::remove_file(self.dir.join(key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(api_err!(e.into())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Policy writing the results larger than a threshold to a result store.
+#[derive(Clone)]
+pub struct ResultOffload {
+    /// Store of the large results.
+    pub store: SharedResultStore,
+
+    /// Size in bytes from which a result is offloaded.
+    pub threshold: usize,
+}
+
+impl ResultOffload {
+    /// Creates a policy offloading the results from a given size.
+    ///
+    /// # Arguments
+    /// * `store` - Store of the large results.
+    /// * `threshold` - Size in bytes from which a result is offloaded.
+    ///
+    /// # Returns
+    /// An instance of `ResultOffload`.
+    pub fn new(store: impl ResultStore + 'static, threshold: usize) -> Self {
+        Self {
+            store: Arc::new(store),
+            threshold,
+        }
+    }
+}
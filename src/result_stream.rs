@@ -0,0 +1,46 @@
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::prelude::*;
+
+/// Sink of the chunks of the result emitted by a running job, shared between the queue and
+/// its routine: the queue stores the chunks received in the backend while the routine runs,
+/// then the ones left once it has returned, before the job is finished.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChunkSink {
+    /// Sending end of the chunks, `None` while the job isn't run by a queue.
+    sender: Option<UnboundedSender<Vec<u8>>>,
+}
+
+impl ChunkSink {
+    /// Creates a sink and the receiving end of its chunks.
+    ///
+    /// # Returns
+    /// The sink and the receiver.
+    pub(crate) fn channel() -> (Self, UnboundedReceiver<Vec<u8>>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        (Self { sender: Some(tx) }, rx)
+    }
+
+    /// Sends a chunk to the queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job emitting the chunk.
+    /// * `chunk` - Chunk to be sent.
+    ///
+    /// # Errors
+    /// `Error::JobNotRunning` if the job isn't run by a queue.
+    pub(crate) fn send(&self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError> {
+        self.sender
+            .as_ref()
+            .and_then(|sender| sender.send(chunk).ok())
+            .ok_or_else(|| api_err!(Error::JobNotRunning(id.to_owned())))
+    }
+}
+
+impl PartialEq for ChunkSink {
+    // Runtime state, not part of the description of a job
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
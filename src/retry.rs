@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Policy running again the jobs whose routine fails: the job waits for a backoff delay as
+/// `Status::Retrying`, then runs again under the same ID until it succeeds or its attempts are
+/// exhausted. Unlike `JobQueue::retry_job`, no new job is created.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts of a job, the first one included.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry, doubled for every following one.
+    pub initial_backoff: Duration,
+
+    /// Maximum delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying the jobs after 1s, doubled up to 1min.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - Maximum number of attempts of a job, the first one included.
+    ///
+    /// # Returns
+    /// An instance of `RetryPolicy`.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the delays between the attempts.
+    ///
+    /// # Arguments
+    /// * `initial` - Delay before the first retry.
+    /// * `max` - Maximum delay between two attempts.
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn backoff(self, initial: Duration, max: Duration) -> Self {
+        Self {
+            initial_backoff: initial,
+            max_backoff: max,
+            ..self
+        }
+    }
+
+    /// Computes the delay before the next attempt of a job that has failed.
+    ///
+    /// # Arguments
+    /// * `attempts` - Number of attempts made by the job, the one failed included.
+    ///
+    /// # Returns
+    /// The delay before the next attempt, `None` if the attempts are exhausted.
+    pub fn delay(&self, attempts: usize) -> Option<Duration> {
+        if attempts >= self.max_attempts as usize {
+            return None;
+        }
+
+        let retry = u32::try_from(attempts.saturating_sub(1)).unwrap_or(u32::MAX);
+
+        Some(
+            self.initial_backoff
+                .saturating_mul(2u32.saturating_pow(retry))
+                .min(self.max_backoff),
+        )
+    }
+}
@@ -0,0 +1,937 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Notify;
+
+use crate::prelude::*;
+
+/// Duration after which the latency of the backend last measured is halved, so that an
+/// overload ends even if no job is stored meanwhile.
+const LATENCY_HALF_LIFE: Duration = Duration::from_secs(1);
+
+/// Room made in a bounded queue (see `Dispatcher::set_max_pending`), waking up the callers
+/// waiting to enqueue once a job is dispatched or dropped.
+#[derive(Default)]
+pub(crate) struct Room {
+    /// Number of times room has been made, telling the waiters whether to wait.
+    made: Mutex<u64>,
+
+    /// Wakes up the threads waiting for room.
+    available: Condvar,
+
+    /// Wakes up the tasks waiting for room.
+    notify: Notify,
+}
+
+impl Room {
+    /// Gets the number of times room has been made, to be given to `wait` or `wait_async`.
+    ///
+    /// # Returns
+    /// The number of times.
+    pub(crate) fn made(&self) -> u64 {
+        *self.made.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wakes up the callers waiting for room.
+    fn make(&self) {
+        *self.made.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+
+        self.available.notify_all();
+        self.notify.notify_waiters();
+    }
+
+    /// Blocks the current thread until room is made.
+    ///
+    /// # Arguments
+    /// * `made` - Number of times room had been made when the queue was found full.
+    /// * `timeout` - Maximum duration to wait (forever if `None`).
+    pub(crate) fn wait(&self, made: u64, timeout: Option<Duration>) {
+        let guard = self.made.lock().unwrap_or_else(|e| e.into_inner());
+
+        match timeout {
+            Some(timeout) => drop(
+                self.available
+                    .wait_timeout_while(guard, timeout, |current| *current == made)
+                    .unwrap_or_else(|e| e.into_inner()),
+            ),
+            None => drop(
+                self.available
+                    .wait_while(guard, |current| *current == made)
+                    .unwrap_or_else(|e| e.into_inner()),
+            ),
+        }
+    }
+
+    /// Waits until room is made.
+    ///
+    /// # Arguments
+    /// * `made` - Number of times room had been made when the queue was found full.
+    pub(crate) async fn wait_async(&self, made: u64) {
+        let notified = self.notify.notified();
+
+        tokio::pin!(notified);
+
+        // Registered before checking, so that room made meanwhile isn't missed
+        notified.as_mut().enable();
+
+        if self.made() == made {
+            notified.await;
+        }
+    }
+}
+
+/// Summary of a job waiting to be dispatched, as seen by the scheduler strategies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingJob {
+    /// Unique identifier of the job.
+    pub id: Uuid,
+
+    /// Kind of the job.
+    pub kind: String,
+
+    /// Priority of the job (the higher, the more urgent).
+    pub priority: i32,
+
+    /// Deadline of the job, if any.
+    pub deadline: Option<SystemTime>,
+
+    /// Timestamp at which the job has been enqueued.
+    pub enqueued: SystemTime,
+
+    /// Tenant owning the job, if any.
+    pub tenant: Option<String>,
+
+    /// Named queue of the job.
+    pub queue: String,
+}
+
+impl From<&Job> for PendingJob {
+    fn from(job: &Job) -> Self {
+        Self {
+            id: job.id(),
+            kind: job.kind().to_owned(),
+            priority: job.priority(),
+            deadline: job.deadline(),
+            enqueued: job.timestamps().enqueued,
+            tenant: job.tenant().map(ToOwned::to_owned),
+            queue: job.queue().to_owned(),
+        }
+    }
+}
+
+/// Trait implemented by the strategies deciding which eligible job runs next.
+pub trait SchedulerStrategy: Send + Sync {
+    /// Picks the next job to be dispatched.
+    ///
+    /// # Arguments
+    /// * `candidates` - Jobs waiting to be dispatched, in enqueue order (never empty).
+    ///
+    /// # Returns
+    /// The index of the job to dispatch in `candidates`, `None` to dispatch nothing for now.
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize>;
+}
+
+/// Custom strategies written as closures, picking the index of the job to dispatch.
+impl<F> SchedulerStrategy for F
+where
+    F: FnMut(&[PendingJob]) -> Option<usize> + Send + Sync,
+{
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        self(candidates)
+    }
+}
+
+/// Strategy dispatching the jobs in enqueue order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FifoStrategy;
+
+impl SchedulerStrategy for FifoStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        (!candidates.is_empty()).then_some(0)
+    }
+}
+
+/// Strategy dispatching the jobs last enqueued first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LifoStrategy;
+
+impl SchedulerStrategy for LifoStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        candidates.len().checked_sub(1)
+    }
+}
+
+/// Strategy dispatching the jobs with the highest priority first (in enqueue order for a same
+/// priority).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PriorityStrategy;
+
+impl SchedulerStrategy for PriorityStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, job)| job.priority)
+            .map(|(idx, _)| idx)
+    }
+}
+
+/// Strategy sharing the dispatches equally between the kinds of job: the kind dispatched the
+/// fewest times so far goes first.
+#[derive(Clone, Debug, Default)]
+pub struct FairShareStrategy {
+    /// Number of dispatches by kind.
+    dispatched: HashMap<String, u64>,
+}
+
+impl SchedulerStrategy for FairShareStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        let idx = candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, job)| self.dispatched.get(&job.kind).copied().unwrap_or(0))
+            .map(|(idx, _)| idx)?;
+
+        *self
+            .dispatched
+            .entry(candidates[idx].kind.to_owned())
+            .or_default() += 1;
+
+        Some(idx)
+    }
+}
+
+/// Strategy sharing the dispatches between the kinds of job in proportion to their weight
+/// (1 for the kinds without weight): the kind furthest behind its share goes first.
+#[derive(Clone, Debug, Default)]
+pub struct WeightedFairStrategy {
+    /// Weight by kind.
+    weights: HashMap<String, u32>,
+
+    /// Number of dispatches by kind.
+    dispatched: HashMap<String, u64>,
+}
+
+impl WeightedFairStrategy {
+    /// Sets the weight of a kind of job.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of job.
+    /// * `weight` - Weight of the kind (at least 1).
+    ///
+    /// # Returns
+    /// The updated strategy.
+    pub fn weight(mut self, kind: impl Into<String>, weight: u32) -> Self {
+        self.weights.insert(kind.into(), weight.max(1));
+
+        self
+    }
+
+    /// Computes the share of the dispatches a kind would have reached once dispatched again.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of job.
+    ///
+    /// # Returns
+    /// The number of dispatches of the kind, plus one, relative to its weight.
+    fn share(&self, kind: &str) -> f64 {
+        let dispatched = self.dispatched.get(kind).copied().unwrap_or(0);
+        let weight = self.weights.get(kind).copied().unwrap_or(1);
+
+        (dispatched + 1) as f64 / f64::from(weight)
+    }
+}
+
+impl SchedulerStrategy for WeightedFairStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        let idx = candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| self.share(&a.kind).total_cmp(&self.share(&b.kind)))
+            .map(|(idx, _)| idx)?;
+
+        *self
+            .dispatched
+            .entry(candidates[idx].kind.to_owned())
+            .or_default() += 1;
+
+        Some(idx)
+    }
+}
+
+/// Strategy dispatching the jobs of the tenants in turn, so that a tenant enqueuing many jobs
+/// doesn't starve the others: the tenant dispatched the longest ago goes first (the jobs
+/// without tenant sharing a turn).
+#[derive(Clone, Debug, Default)]
+pub struct TenantRoundRobinStrategy {
+    /// Turn at which each tenant has been dispatched last.
+    turns: HashMap<Option<String>, u64>,
+
+    /// Number of dispatches so far.
+    turn: u64,
+}
+
+impl SchedulerStrategy for TenantRoundRobinStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        let idx = candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, job)| self.turns.get(&job.tenant).copied().unwrap_or(0))
+            .map(|(idx, _)| idx)?;
+
+        self.turn += 1;
+        self.turns
+            .insert(candidates[idx].tenant.to_owned(), self.turn);
+
+        Some(idx)
+    }
+}
+
+/// Strategy dispatching the jobs with the earliest deadline first, jobs without deadline come
+/// last (in enqueue order).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeadlineStrategy;
+
+impl SchedulerStrategy for DeadlineStrategy {
+    fn pick(&mut self, candidates: &[PendingJob]) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, job)| (job.deadline.is_none(), job.deadline))
+            .map(|(idx, _)| idx)
+    }
+}
+
+/// Dispatch state of the queue: jobs waiting for a slot and jobs in flight.
+pub(crate) struct Dispatcher {
+    /// Jobs waiting to be dispatched, in enqueue order.
+    pending: Vec<PendingJob>,
+
+    /// Number of jobs currently dispatched and not finished.
+    in_flight: usize,
+
+    /// Maximum number of jobs in flight (unlimited if `None`).
+    max_in_flight: Option<usize>,
+
+    /// Strategy deciding which job runs next.
+    strategy: Box<dyn SchedulerStrategy>,
+
+    /// Policy used to delay or drop non-critical jobs when overloaded.
+    shedding: Option<SheddingPolicy>,
+
+    /// Latency of the backend last measured.
+    backend_latency: Duration,
+
+    /// Time at which the latency of the backend has been measured.
+    latency_measured: Instant,
+
+    /// Number of threads of the pool, the slots of the worker if the jobs in flight are
+    /// unlimited.
+    pool_size: usize,
+
+    /// Time spent by the slots running jobs or idle.
+    utilization: Utilization,
+
+    /// Whether the queue is draining (no more jobs taken, pending ones run by priority).
+    draining: bool,
+
+    /// Policy adjusting the maximum number of jobs in flight to the depth.
+    autoscale: Option<AutoscalePolicy>,
+
+    /// Quotas of the tenants owning the jobs.
+    quotas: Option<TenantQuotas>,
+
+    /// Maximum number of jobs waiting to be dispatched (unlimited if `None`).
+    max_pending: Option<usize>,
+
+    /// Jobs enqueued and not pushed yet, with their tenant.
+    admitted: HashMap<Uuid, Option<String>>,
+
+    /// Tenants of the jobs in flight, by job.
+    running: HashMap<Uuid, String>,
+
+    /// Named queues hosted by the queue.
+    named_queues: Option<NamedQueues>,
+
+    /// Named queues of the jobs in flight, by job.
+    running_queues: HashMap<Uuid, String>,
+
+    /// Policy letting the urgent jobs take the slot of the running jobs of lower priority.
+    preemption: Option<PreemptionPolicy>,
+
+    /// Priorities of the jobs in flight, by job.
+    priorities: HashMap<Uuid, i32>,
+
+    /// Jobs preempted, by job having taken their slot.
+    preempted: HashMap<Uuid, Uuid>,
+
+    /// Room made when a job is dispatched or dropped.
+    room: Arc<Room>,
+}
+
+impl Dispatcher {
+    /// Creates a new dispatcher.
+    ///
+    /// # Arguments
+    /// * `strategy` - Strategy deciding which job runs next.
+    /// * `max_in_flight` - Maximum number of jobs in flight (unlimited if `None`).
+    /// * `shedding` - Policy used to delay or drop non-critical jobs when overloaded.
+    ///
+    /// # Returns
+    /// An instance of `Dispatcher`.
+    pub(crate) fn new(
+        strategy: Box<dyn SchedulerStrategy>,
+        max_in_flight: Option<usize>,
+        shedding: Option<SheddingPolicy>,
+    ) -> Self {
+        Self {
+            pending: vec![],
+            in_flight: 0,
+            max_in_flight,
+            strategy,
+            shedding,
+            backend_latency: Duration::ZERO,
+            latency_measured: Instant::now(),
+            pool_size: 1,
+            utilization: Utilization::new(max_in_flight.unwrap_or(1)),
+            draining: false,
+            autoscale: None,
+            quotas: None,
+            max_pending: None,
+            admitted: HashMap::new(),
+            running: HashMap::new(),
+            named_queues: None,
+            running_queues: HashMap::new(),
+            preemption: None,
+            priorities: HashMap::new(),
+            preempted: HashMap::new(),
+            room: Arc::new(Room::default()),
+        }
+    }
+
+    /// Sets the number of threads of the pool, the slots of the worker if the jobs in flight
+    /// are unlimited.
+    ///
+    /// # Arguments
+    /// * `pool_size` - Number of threads.
+    pub(crate) fn set_pool_size(&mut self, pool_size: usize) {
+        self.pool_size = pool_size.max(1);
+        self.update_utilization();
+    }
+
+    /// Gets the utilization and saturation of the worker.
+    ///
+    /// # Arguments
+    /// * `worker_id` - Identifier of the worker.
+    /// * `ready` - Number of jobs ready to be run.
+    ///
+    /// # Returns
+    /// An instance of `WorkerStats`.
+    pub(crate) fn worker_stats(&mut self, worker_id: &str, ready: usize) -> WorkerStats {
+        self.utilization.stats(worker_id, ready)
+    }
+
+    /// Updates the utilization tracker with the current slots and jobs in flight.
+    fn update_utilization(&mut self) {
+        let slots = self.max_in_flight.unwrap_or(self.pool_size);
+
+        self.utilization.update(slots, self.in_flight);
+    }
+
+    /// Sets the policy letting the urgent jobs take the slot of the running jobs of lower
+    /// priority.
+    ///
+    /// # Arguments
+    /// * `preemption` - Policy to set.
+    pub(crate) fn set_preemption(&mut self, preemption: PreemptionPolicy) {
+        self.preemption = Some(preemption);
+    }
+
+    /// Sets the quotas of the tenants owning the jobs.
+    ///
+    /// # Arguments
+    /// * `quotas` - Quotas to set.
+    pub(crate) fn set_quotas(&mut self, quotas: TenantQuotas) {
+        self.quotas = Some(quotas);
+    }
+
+    /// Sets the named queues hosted by the queue.
+    ///
+    /// # Arguments
+    /// * `named_queues` - Named queues to set.
+    pub(crate) fn set_named_queues(&mut self, named_queues: NamedQueues) {
+        self.named_queues = Some(named_queues);
+    }
+
+    /// Sets the maximum number of jobs waiting to be dispatched.
+    ///
+    /// # Arguments
+    /// * `max_pending` - Maximum number of jobs.
+    pub(crate) fn set_max_pending(&mut self, max_pending: usize) {
+        self.max_pending = Some(max_pending);
+    }
+
+    /// Admits a job enqueued, unless the queue is full or its tenant has reached its quota of
+    /// jobs waiting to be dispatched.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    /// * `tenant` - Tenant owning the job, if any.
+    ///
+    /// # Errors
+    /// `Error::DuplicateJobId` if a job with the same ID is admitted, pending or running,
+    /// `Error::QueueFull` if the queue is full, `Error::QuotaExceeded` if the quota is reached.
+    pub(crate) fn admit(&mut self, id: Uuid, tenant: Option<&str>) -> Result<(), ApiError> {
+        if self.admitted.contains_key(&id)
+            || self.priorities.contains_key(&id)
+            || self.pending.iter().any(|job| job.id == id)
+        {
+            return Err(api_err!(Error::DuplicateJobId(id)));
+        }
+
+        if self
+            .max_pending
+            .is_some_and(|max_pending| self.admitted.len() + self.pending.len() >= max_pending)
+        {
+            return Err(api_err!(Error::QueueFull));
+        }
+
+        if let (Some(quotas), Some(tenant)) = (&self.quotas, tenant) {
+            if let Some(max_pending) = quotas.quota(tenant).max_pending {
+                let admitted = self
+                    .admitted
+                    .values()
+                    .filter(|t| t.as_deref() == Some(tenant))
+                    .count();
+                let pending = self
+                    .pending
+                    .iter()
+                    .filter(|job| job.tenant.as_deref() == Some(tenant))
+                    .count();
+
+                if admitted + pending >= max_pending {
+                    return Err(api_err!(Error::QuotaExceeded(tenant.to_owned())));
+                }
+            }
+        }
+
+        self.admitted.insert(id, tenant.map(ToOwned::to_owned));
+
+        Ok(())
+    }
+
+    /// Forgets a job admitted but dropped before being pushed.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    pub(crate) fn forget(&mut self, id: &Uuid) {
+        if self.admitted.remove(id).is_some() {
+            self.room.make();
+        }
+    }
+
+    /// Keeps a job admitted while it waits to be pushed again (e.g. retried after its backoff
+    /// delay), regardless of the bounds of the queue.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    /// * `tenant` - Tenant owning the job, if any.
+    pub(crate) fn defer(&mut self, id: Uuid, tenant: Option<&str>) {
+        self.admitted.insert(id, tenant.map(ToOwned::to_owned));
+    }
+
+    /// Gets the room made in the queue, waited for by the callers finding it full.
+    ///
+    /// # Returns
+    /// The room of the queue.
+    pub(crate) fn room(&self) -> Arc<Room> {
+        self.room.clone()
+    }
+
+    /// Checks if the tenant or the named queue of a job has reached its quota of jobs running.
+    ///
+    /// # Arguments
+    /// * `job` - Job to check.
+    ///
+    /// # Returns
+    /// `true` if the job must wait, `false` otherwise.
+    fn is_over_quota(&self, job: &PendingJob) -> bool {
+        let queue_full = self.named_queues.as_ref().is_some_and(|named_queues| {
+            named_queues
+                .settings(&job.queue)
+                .max_running
+                .is_some_and(|max_running| {
+                    self.running_queues
+                        .values()
+                        .filter(|q| **q == job.queue)
+                        .count()
+                        >= max_running
+                })
+        });
+
+        let (Some(quotas), Some(tenant)) = (&self.quotas, &job.tenant) else {
+            return queue_full;
+        };
+
+        queue_full
+            || quotas.quota(tenant).max_running.is_some_and(|max_running| {
+                self.running.values().filter(|t| *t == tenant).count() >= max_running
+            })
+    }
+
+    /// Gets the priority of the named queue of a job.
+    ///
+    /// # Arguments
+    /// * `job` - Job to check.
+    ///
+    /// # Returns
+    /// The priority of the queue, 0 without named queues.
+    fn queue_priority(&self, job: &PendingJob) -> i32 {
+        self.named_queues
+            .as_ref()
+            .map_or(0, |named_queues| named_queues.settings(&job.queue).priority)
+    }
+
+    /// Sets the policy adjusting the maximum number of jobs in flight to the depth, starting
+    /// from its minimum.
+    ///
+    /// # Arguments
+    /// * `autoscale` - Policy to set.
+    pub(crate) fn set_autoscale(&mut self, autoscale: AutoscalePolicy) {
+        self.max_in_flight = Some(autoscale.min);
+        self.autoscale = Some(autoscale);
+        self.update_utilization();
+    }
+
+    /// Adjusts the maximum number of jobs in flight to the number of jobs waiting to be
+    /// dispatched, according to the autoscale policy.
+    ///
+    /// # Returns
+    /// The previous and new maximums, `None` if unchanged (or without autoscale policy).
+    pub(crate) fn autoscale(&mut self) -> Option<(usize, usize)> {
+        let autoscale = self.autoscale?;
+        let limit = self.max_in_flight.unwrap_or(autoscale.min);
+        let scaled = autoscale.scale(limit, self.pending.len());
+
+        self.max_in_flight = Some(scaled);
+        self.update_utilization();
+
+        (scaled != limit).then_some((limit, scaled))
+    }
+
+    /// Changes the maximum number of jobs in flight. The jobs in flight above a lowered
+    /// maximum keep running, no other job being dispatched until they've finished.
+    ///
+    /// # Arguments
+    /// * `max_in_flight` - Maximum number of jobs in flight.
+    pub(crate) fn set_max_in_flight(&mut self, max_in_flight: usize) {
+        self.max_in_flight = Some(max_in_flight);
+        self.update_utilization();
+    }
+
+    /// Starts draining: the pending jobs are dispatched in priority order, whatever the
+    /// strategy, and no other job is taken.
+    pub(crate) fn drain(&mut self) {
+        self.strategy = Box::new(PriorityStrategy);
+        self.draining = true;
+    }
+
+    /// Checks if every job taken has been dispatched and is finished.
+    ///
+    /// # Returns
+    /// `true` if idle, `false` otherwise.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.pending.is_empty() && self.in_flight == 0
+    }
+
+    /// Checks if no job is admitted (e.g. delayed), pending or in flight.
+    ///
+    /// # Returns
+    /// `true` if empty, `false` otherwise.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.admitted.is_empty() && self.is_idle()
+    }
+
+    /// Records the latency of the backend, used to detect overloads.
+    ///
+    /// # Arguments
+    /// * `latency` - Latency measured.
+    pub(crate) fn set_backend_latency(&mut self, latency: Duration) {
+        self.backend_latency = latency;
+        self.latency_measured = Instant::now();
+    }
+
+    /// Gets the latency of the backend last measured, decayed since it has been measured.
+    ///
+    /// # Returns
+    /// The latency of the backend.
+    fn backend_latency(&self) -> Duration {
+        let half_lives =
+            self.latency_measured.elapsed().as_secs_f64() / LATENCY_HALF_LIFE.as_secs_f64();
+
+        self.backend_latency.mul_f64(0.5f64.powf(half_lives))
+    }
+
+    /// Gets the shedding action to apply on a job, if it's of a shed-first kind and the queue
+    /// is overloaded.
+    ///
+    /// # Arguments
+    /// * `job` - Job to check.
+    ///
+    /// # Returns
+    /// The action to apply, `None` if the job must be processed normally.
+    pub(crate) fn shedding_action(&self, job: &PendingJob) -> Option<SheddingAction> {
+        let shedding = self.shedding.as_ref()?;
+
+        (shedding.sheds(&job.kind) && self.is_overloaded()).then_some(shedding.action)
+    }
+
+    /// Checks if the queue is overloaded according to the shedding policy.
+    ///
+    /// # Returns
+    /// `true` if overloaded, `false` otherwise (or without shedding policy).
+    fn is_overloaded(&self) -> bool {
+        self.shedding.as_ref().is_some_and(|shedding| {
+            // The jobs held back aren't counted, the overload would never end otherwise
+            let depth = self
+                .pending
+                .iter()
+                .filter(|job| !shedding.sheds(&job.kind))
+                .count();
+
+            shedding.is_overloaded(depth, self.backend_latency())
+        })
+    }
+
+    /// Checks if another job can be taken (e.g. claimed from a shared backend) without
+    /// exceeding the maximum number of jobs in flight.
+    ///
+    /// # Returns
+    /// `true` if a job can be taken, `false` otherwise.
+    pub(crate) fn has_capacity(&self) -> bool {
+        !self.draining
+            && self.max_in_flight.map_or(true, |max_in_flight| {
+                self.pending.len() + self.in_flight < max_in_flight
+            })
+    }
+
+    /// Gets the jobs waiting to be dispatched.
+    ///
+    /// # Returns
+    /// The list of IDs of the pending jobs.
+    pub(crate) fn pending_ids(&self) -> Vec<Uuid> {
+        self.pending.iter().map(|job| job.id).collect()
+    }
+
+    /// Adds a job to the ones waiting to be dispatched.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be added.
+    pub(crate) fn push(&mut self, job: PendingJob) {
+        self.admitted.remove(&job.id);
+        self.pending.push(job);
+    }
+
+    /// Takes the next job to be dispatched if a slot is available.
+    ///
+    /// # Returns
+    /// The ID of the job to be run, `None` if there's nothing to dispatch.
+    pub(crate) fn next(&mut self) -> Option<Uuid> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        if let Some(max_in_flight) = self.max_in_flight {
+            if self.in_flight >= max_in_flight {
+                return None;
+            }
+        }
+
+        // Jobs of the shed-first kinds are held back during an overload, and the jobs of the
+        // tenants and named queues at their quota until one of their jobs is finished
+        let overloaded = self.is_overloaded();
+        let sheds = |job: &PendingJob| {
+            self.shedding
+                .as_ref()
+                .is_some_and(|shedding| shedding.sheds(&job.kind))
+        };
+        let mut eligible: Vec<usize> = (0..self.pending.len())
+            .filter(|idx| {
+                let job = &self.pending[*idx];
+                let shed = overloaded && sheds(job);
+
+                !shed && !self.is_over_quota(job)
+            })
+            .collect();
+
+        // The jobs held back let through one by one when nothing else runs: no job left to
+        // protect, and no job finishing to dispatch them later
+        if overloaded && eligible.is_empty() && self.in_flight == 0 {
+            eligible = (0..self.pending.len())
+                .filter(|idx| !self.is_over_quota(&self.pending[*idx]))
+                .collect();
+        }
+
+        // Only the named queue of highest priority with eligible jobs dispatched
+        if let Some(priority) = eligible
+            .iter()
+            .map(|idx| self.queue_priority(&self.pending[*idx]))
+            .max()
+        {
+            eligible.retain(|idx| self.queue_priority(&self.pending[*idx]) == priority);
+        }
+
+        let idx = if eligible.len() == self.pending.len() {
+            self.strategy.pick(&self.pending)?
+        } else {
+            if eligible.is_empty() {
+                return None;
+            }
+
+            let candidates: Vec<PendingJob> = eligible
+                .iter()
+                .map(|idx| self.pending[*idx].clone())
+                .collect();
+
+            *eligible.get(self.strategy.pick(&candidates)?)?
+        };
+
+        if idx >= self.pending.len() {
+            return None;
+        }
+
+        self.in_flight += 1;
+        self.update_utilization();
+
+        Some(self.take(idx))
+    }
+
+    /// Takes the slot of a running job of lower priority for the most urgent job waiting, when
+    /// every slot is occupied, according to the preemption policy.
+    ///
+    /// # Arguments
+    /// * `suspendable` - Checks if a running job can be suspended.
+    ///
+    /// # Returns
+    /// The IDs of the job preempted, to be suspended, and of the job to be run in its slot,
+    /// `None` if there's nothing to preempt.
+    pub(crate) fn preempt(&mut self, suspendable: impl Fn(&Uuid) -> bool) -> Option<(Uuid, Uuid)> {
+        let preemption = self.preemption?;
+
+        if self
+            .max_in_flight
+            .map_or(true, |max_in_flight| self.in_flight < max_in_flight)
+        {
+            return None;
+        }
+
+        let (idx, priority) = self
+            .pending
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, job)| !self.is_over_quota(job))
+            .max_by_key(|(_, job)| job.priority)
+            .map(|(idx, job)| (idx, job.priority))?;
+
+        // Jobs already preempted left alone
+        let preempted = self
+            .priorities
+            .iter()
+            .filter(|(id, running)| {
+                preemption.preempts(priority, **running)
+                    && !self.preempted.values().any(|preempted| preempted == *id)
+                    && suspendable(id)
+            })
+            .min_by_key(|(_, running)| **running)
+            .map(|(id, _)| *id)?;
+
+        let id = self.take(idx);
+
+        self.preempted.insert(id, preempted);
+
+        Some((preempted, id))
+    }
+
+    /// Takes a pending job to be run.
+    ///
+    /// # Arguments
+    /// * `idx` - Index of the job in the pending ones.
+    ///
+    /// # Returns
+    /// The ID of the job.
+    fn take(&mut self, idx: usize) -> Uuid {
+        let job = self.pending.remove(idx);
+
+        self.room.make();
+
+        self.priorities.insert(job.id, job.priority);
+
+        if let Some(tenant) = job.tenant {
+            self.running.insert(job.id, tenant);
+        }
+
+        if self.named_queues.is_some() {
+            self.running_queues.insert(job.id, job.queue);
+        }
+
+        job.id
+    }
+
+    /// Removes a job from the ones waiting to be dispatched.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be removed.
+    ///
+    /// # Returns
+    /// `true` if the job was pending or admitted (e.g. delayed), `false` otherwise.
+    pub(crate) fn remove(&mut self, id: &Uuid) -> bool {
+        let len = self.pending.len();
+        let admitted = self.admitted.remove(id).is_some();
+
+        self.pending.retain(|job| &job.id != id);
+
+        let removed = admitted || self.pending.len() != len;
+
+        if removed {
+            self.room.make();
+        }
+
+        removed
+    }
+
+    /// Releases the slot of a finished job, given back to the job it has preempted if any.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job finished.
+    ///
+    /// # Returns
+    /// The ID of the job preempted, to be resumed.
+    pub(crate) fn release(&mut self, id: &Uuid) -> Option<Uuid> {
+        self.running.remove(id);
+        self.running_queues.remove(id);
+        self.priorities.remove(id);
+
+        if let Some(preempted) = self.preempted.remove(id) {
+            return Some(preempted);
+        }
+
+        // Job finished while preempted, its slot kept by the job that has taken it
+        let preempting = self
+            .preempted
+            .iter()
+            .find_map(|(preempting, preempted)| (preempted == id).then_some(*preempting));
+
+        match preempting {
+            Some(preempting) => {
+                self.preempted.remove(&preempting);
+            }
+            None => self.in_flight = self.in_flight.saturating_sub(1),
+        }
+
+        self.update_utilization();
+
+        None
+    }
+}
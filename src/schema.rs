@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::prelude::*;
+
+/// Kind of job whose stored payloads no longer deserialize against the current routine type.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SchemaDrift {
+    /// Kind of the jobs.
+    pub kind: String,
+
+    /// IDs of the incompatible jobs found in the sample.
+    pub job_ids: Vec<Uuid>,
+
+    /// Error raised by the first incompatible job.
+    pub error: String,
+}
+
+/// Checks that a sample of pending jobs (not ready or ready) still deserializes against the
+/// current routine type (local and registered jobs being skipped).
+///
+/// # Arguments
+/// * `jobs` - Jobs stored.
+/// * `sample` - Maximum number of pending jobs checked.
+///
+/// # Returns
+/// The incompatible kinds, sorted by name.
+pub fn detect_drift<RoutineType: Routine<Context>, Context>(
+    jobs: &[Job],
+    sample: usize,
+) -> Vec<SchemaDrift> {
+    let mut drifts: BTreeMap<&str, SchemaDrift> = BTreeMap::new();
+
+    let pending = jobs
+        .iter()
+        .filter(|job| {
+            matches!(job.status(), Status::NotReady | Status::Ready)
+                && !job.is_local()
+                && !job.is_registered()
+        })
+        .take(sample);
+
+    for job in pending {
+        if let Err(e) = job.routine::<RoutineType, Context>() {
+            drifts
+                .entry(job.kind())
+                .or_insert_with(|| SchemaDrift {
+                    kind: job.kind().to_owned(),
+                    job_ids: vec![],
+                    error: e.to_string(),
+                })
+                .job_ids
+                .push(job.id());
+        }
+    }
+
+    drifts.into_values().collect()
+}
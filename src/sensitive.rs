@@ -0,0 +1,180 @@
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+use crate::codec::embedded;
+
+/// String wiped from memory once dropped, holding the private data entries of a job so that
+/// secrets (short-lived API tokens...) don't linger in memory after use. Best effort only: the
+/// copies made while encoding the entry, or by the routine reading it, aren't wiped.
+#[derive(Clone, PartialEq)]
+pub(crate) struct Zeroizing(String);
+
+impl Zeroizing {
+    /// Gets the string held.
+    ///
+    /// # Returns
+    /// The string.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Zeroizing {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl Drop for Zeroizing {
+    fn drop(&mut self) {
+        // SAFETY: the bytes are only overwritten with zeroes, which keep the string valid UTF-8
+        let bytes = unsafe { self.0.as_mut_vec() };
+        let capacity = bytes.capacity();
+        let ptr = bytes.as_mut_ptr();
+
+        // Whole allocation wiped, including the bytes left by a shrunk string
+        for offset in 0..capacity {
+            // Volatile writes not optimized away, even if the memory is freed right after
+            // SAFETY: the offset is within the allocation of the string
+            unsafe { std::ptr::write_volatile(ptr.add(offset), 0) };
+        }
+
+        compiler_fence(Ordering::SeqCst);
+
+        bytes.clear();
+    }
+}
+
+/// Private data entry of a job, JSON encoded.
+#[derive(Clone, PartialEq)]
+struct DataEntry {
+    /// Value of the entry.
+    value: Zeroizing,
+
+    /// Only kept in memory, left out when serialized.
+    sensitive: bool,
+}
+
+/// Private data entries of a job by name. The sensitive ones are left out when serialized,
+/// hence never copied into the records of the backends.
+#[derive(Clone, Default, PartialEq)]
+pub(crate) struct DataStore(BTreeMap<String, DataEntry>);
+
+impl DataStore {
+    /// Gets an entry.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    ///
+    /// # Returns
+    /// The entry JSON encoded, `None` if missing.
+    pub(crate) fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(|entry| entry.value.as_str())
+    }
+
+    /// Inserts an entry, replacing the previous entry of the name.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    /// * `value` - Entry JSON encoded.
+    /// * `sensitive` - Whether the entry is only kept in memory.
+    pub(crate) fn insert(&mut self, name: String, value: String, sensitive: bool) {
+        let entry = DataEntry {
+            value: value.into(),
+            sensitive,
+        };
+
+        self.0.insert(name, entry);
+    }
+
+    /// Removes an entry.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    ///
+    /// # Returns
+    /// `true` if the entry was present, `false` otherwise.
+    pub(crate) fn remove(&mut self, name: &str) -> bool {
+        self.0.remove(name).is_some()
+    }
+
+    /// Removes the sensitive entries, wiped from memory when dropped.
+    pub(crate) fn remove_sensitive(&mut self) {
+        self.0.retain(|_, entry| !entry.sensitive);
+    }
+
+    /// Checks if an entry is sensitive.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the entry.
+    ///
+    /// # Returns
+    /// `true` if the entry is present and sensitive, `false` otherwise.
+    pub(crate) fn is_sensitive(&self, name: &str) -> bool {
+        self.0.get(name).map_or(false, |entry| entry.sensitive)
+    }
+
+    /// Iterates over the entries.
+    ///
+    /// # Returns
+    /// The name, the value JSON encoded and the sensitivity of every entry.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &str, bool)> {
+        self.0
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry.value.as_str(), entry.sensitive))
+    }
+}
+
+/// Value of an entry serialized, embedded as a structured value in the human-readable formats.
+struct Embedded<'a>(&'a str);
+
+impl Serialize for Embedded<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        embedded::serialize(self.0, serializer)
+    }
+}
+
+/// Value of an entry deserialized (see `Embedded`).
+struct Embedding(Zeroizing);
+
+impl<'de> Deserialize<'de> for Embedding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        embedded::deserialize(deserializer).map(|value| Self(value.into()))
+    }
+}
+
+impl Serialize for DataStore {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let entries = self
+            .iter()
+            .filter(|(_, _, sensitive)| !sensitive)
+            .collect::<Vec<_>>();
+        let mut map = serializer.serialize_map(Some(entries.len()))?;
+
+        for (name, value, _) in entries {
+            map.serialize_entry(name, &Embedded(value))?;
+        }
+
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for DataStore {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = BTreeMap::<String, Embedding>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, Embedding(value))| {
+                let entry = DataEntry {
+                    value,
+                    sensitive: false,
+                };
+
+                (name, entry)
+            })
+            .collect();
+
+        Ok(Self(entries))
+    }
+}
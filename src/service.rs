@@ -0,0 +1,590 @@
+//! Transport-agnostic control plane of a job queue, mirroring the RPCs of
+//! `proto/cs_jobs.proto` so that a server (gRPC, HTTP...) only has to map its messages.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use crate::prelude::*;
+use crate::tcp;
+
+/// Address the control plane is served on by default, only reachable from the same host.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:7878";
+
+/// Duration during which a streamed job not found yet is considered as being stored (the jobs
+/// are stored asynchronously by the thread of the queue).
+const NOT_FOUND_GRACE: Duration = Duration::from_secs(1);
+
+/// Summary of a job, as returned by `ListJobs`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct JobSummary {
+    /// Unique identifier of the job.
+    pub id: Uuid,
+
+    /// Kind of the job.
+    pub kind: String,
+
+    /// Status of the job.
+    pub status: Status,
+
+    /// Progression of the job.
+    pub progression: Progression,
+
+    /// Tags attached to the job.
+    pub tags: Vec<String>,
+}
+
+impl From<JobInfo> for JobSummary {
+    fn from(info: JobInfo) -> Self {
+        Self {
+            id: info.id,
+            kind: info.kind,
+            status: info.status,
+            progression: info.progression,
+            tags: info.tags,
+        }
+    }
+}
+
+/// Update of a job, as streamed by `StreamProgress`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ProgressUpdate {
+    /// Unique identifier of the job.
+    pub id: Uuid,
+
+    /// Status of the job.
+    pub status: Status,
+
+    /// Progression of the job.
+    pub progression: Progression,
+}
+
+/// Unary RPCs of the control plane, as carried by a transport.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum Method {
+    /// `Enqueue`: request is a job in the wire format, response is its ID.
+    Enqueue,
+
+    /// `GetStatus`: request is a job ID, response is its status.
+    GetStatus,
+
+    /// `GetResult`: request is a job ID, response is its result.
+    GetResult,
+
+    /// `Cancel`: request is a job ID, response is empty.
+    Cancel,
+
+    /// `ListJobs`: request is empty, response is the summary of every job.
+    ListJobs,
+
+    /// `Retry`: request is a job ID, response is the ID of the new job.
+    Retry,
+
+    /// `Purge`: request is a `PurgeFilter`, response is the IDs of the jobs removed.
+    Purge,
+
+    /// `Stats`: request is empty, response is the `QueueStats` of the queue.
+    Stats,
+
+    /// `Pause`: request is empty, response is empty.
+    Pause,
+
+    /// `Resume`: request is empty, response is empty.
+    Resume,
+}
+
+/// Control plane of a started job queue: `Enqueue`, `GetStatus`, `GetResult`,
+/// `StreamProgress`, `Cancel`, `ListJobs`, plus the admin RPCs `Retry`, `Purge`, `Stats`,
+/// `Pause` and `Resume`.
+pub struct JobQueueService<RoutineType, Context> {
+    /// Queue controlled.
+    queue: Arc<JobQueue<RoutineType, Context>>,
+
+    /// Interval between two checks of a job streamed.
+    poll_interval: Duration,
+
+    /// Auth token required from the clients by the servers.
+    token: Option<Arc<str>>,
+}
+
+impl<RoutineType, Context> Clone for JobQueueService<RoutineType, Context> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+            poll_interval: self.poll_interval,
+            token: self.token.clone(),
+        }
+    }
+}
+
+impl<RoutineType, Context> JobQueueService<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates the control plane of a queue.
+    ///
+    /// # Arguments
+    /// * `queue` - Queue to be controlled.
+    ///
+    /// # Returns
+    /// An instance of `JobQueueService`.
+    pub fn new(queue: Arc<JobQueue<RoutineType, Context>>) -> Self {
+        Self {
+            queue,
+            poll_interval: Duration::from_millis(100),
+            token: None,
+        }
+    }
+
+    /// Sets the interval between two checks of a job streamed.
+    ///
+    /// # Arguments
+    /// * `poll_interval` - Interval to set.
+    ///
+    /// # Returns
+    /// The updated service.
+    pub fn poll_interval(self, poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            ..self
+        }
+    }
+
+    /// Sets the auth token required from the clients, mandatory to serve the control plane.
+    ///
+    /// # Arguments
+    /// * `token` - Secret shared with the clients.
+    ///
+    /// # Returns
+    /// The updated service.
+    pub fn token(self, token: impl Into<String>) -> Self {
+        Self {
+            token: Some(token.into().into()),
+            ..self
+        }
+    }
+
+    /// Checks the auth token sent by a client.
+    ///
+    /// # Arguments
+    /// * `token` - Token sent by the client.
+    ///
+    /// # Errors
+    /// `Error::Unauthenticated` if no token is set or if the tokens differ.
+    pub fn authenticate(&self, token: &str) -> Result<(), ApiError> {
+        let expected = self
+            .token
+            .as_deref()
+            .ok_or(api_err!(Error::Unauthenticated))?;
+
+        // Compared in constant time, not to leak the length of the matching prefix
+        let differences = expected
+            .bytes()
+            .zip(token.bytes())
+            .fold(expected.len() ^ token.len(), |differences, (a, b)| {
+                differences | usize::from(a ^ b)
+            });
+
+        match differences {
+            0 => Ok(()),
+            _ => Err(api_err!(Error::Unauthenticated)),
+        }
+    }
+
+    /// Checks whether an auth token is set.
+    ///
+    /// # Returns
+    /// `true` if the control plane can be served.
+    pub(crate) fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Binds the listener of the control plane.
+    ///
+    /// # Arguments
+    /// * `addr` - Address to listen on, `DEFAULT_ADDR` (loopback) if `None`.
+    ///
+    /// # Returns
+    /// The listener bound.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn bind(addr: Option<&str>) -> Result<TcpListener, ApiError> {
+        TcpListener::bind(addr.unwrap_or(DEFAULT_ADDR))
+            .await
+            .map_err(|e| api_err!(e.into()))
+    }
+
+    /// Pushes a new job, encoded in the wire format, to be processed.
+    ///
+    /// # Arguments
+    /// * `job` - Job encoded in the wire format.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn enqueue(&self, job: &[u8]) -> Result<Uuid, ApiError> {
+        self.queue
+            .enqueue(Job::from_wire::<RoutineType, Context>(job)?)
+    }
+
+    /// Gets the status of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The status of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn get_status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.queue.job_status(id).await
+    }
+
+    /// Streams the updates of a job until it's finished (an error is streamed if the job is
+    /// removed before). Must be called within a Tokio runtime.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be followed.
+    ///
+    /// # Returns
+    /// The receiving end of the stream, closed after the last update or the first error.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn stream_progress(
+        &self,
+        id: &Uuid,
+    ) -> Result<mpsc::Receiver<Result<ProgressUpdate, ApiError>>, ApiError> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|e| api_err!(Error::CannotAccessRuntime(e.to_string())))?;
+
+        let (tx, rx) = mpsc::channel(16);
+        let service = self.clone();
+        let id = id.to_owned();
+
+        handle.spawn(async move {
+            let mut last: Option<ProgressUpdate> = None;
+            let started = std::time::Instant::now();
+
+            loop {
+                let update = match service.progress_update(&id).await {
+                    Ok(update) => update,
+                    Err(e)
+                        if matches!(*e, Error::JobNotFound(_))
+                            && last.is_none()
+                            && started.elapsed() < NOT_FOUND_GRACE =>
+                    {
+                        tokio::time::sleep(service.poll_interval).await;
+
+                        continue;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+
+                        return;
+                    }
+                };
+
+                if last != Some(update) {
+                    if tx.send(Ok(update)).await.is_err() {
+                        // Receiver dropped
+                        return;
+                    }
+
+                    last = Some(update);
+                }
+
+                if let Status::Finished(_) = update.status {
+                    return;
+                }
+
+                tokio::time::sleep(service.poll_interval).await;
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// Gets the result of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The result of the job as vector of bytes.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn get_result(&self, id: &Uuid) -> Result<Vec<u8>, ApiError> {
+        self.queue.job_result(id).await
+    }
+
+    /// Cancels a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be cancelled.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn cancel(&self, id: &Uuid) -> Result<(), ApiError> {
+        self.queue.cancel_job(id).await
+    }
+
+    /// Lists the jobs of the queue.
+    ///
+    /// # Returns
+    /// The summary of every job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn list_jobs(&self) -> Result<Vec<JobSummary>, ApiError> {
+        Ok(self
+            .queue
+            .jobs()
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    /// Retries a finished job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be retried.
+    ///
+    /// # Returns
+    /// The unique ID of the new job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn retry(&self, id: &Uuid) -> Result<Uuid, ApiError> {
+        self.queue.retry_job(id).await
+    }
+
+    /// Purges the finished jobs selected by a filter.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the jobs to be removed.
+    ///
+    /// # Returns
+    /// The list of IDs of the removed jobs.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn purge(&self, filter: PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        self.queue.purge(filter).await
+    }
+
+    /// Gets the statistics of the queue.
+    ///
+    /// # Returns
+    /// The jobs of the queue counted by status.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn stats(&self) -> Result<QueueStats, ApiError> {
+        self.queue.stats().await
+    }
+
+    /// Pauses the queue, and every worker sharing its backend.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn pause(&self) -> Result<(), ApiError> {
+        self.queue.pause().await
+    }
+
+    /// Resumes the queue, and every worker sharing its backend.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn resume(&self) -> Result<(), ApiError> {
+        self.queue.resume().await
+    }
+
+    /// Handles a unary RPC received by a transport. Requests and responses are JSON encoded,
+    /// a response being a `Result` whose error is the message of the error raised.
+    ///
+    /// # Arguments
+    /// * `method` - RPC called.
+    /// * `request` - Request encoded.
+    ///
+    /// # Returns
+    /// The response encoded.
+    pub async fn handle(&self, method: Method, request: &[u8]) -> Vec<u8> {
+        let response = self.call(method, request).await.map_err(|e| e.to_string());
+
+        Self::encode(response)
+    }
+
+    /// Serves the control plane over TCP, one JSON request and response per line, until the
+    /// listener fails. Every request must carry the auth token of the service, the connection
+    /// being closed otherwise, as well as on requests larger than 1 MiB.
+    ///
+    /// # Arguments
+    /// * `listener` - Listener accepting the connections of the clients (see `bind`).
+    ///
+    /// # Errors
+    /// `Error::InvalidConfig` if no auth token is set, one of `Error` enum otherwise.
+    pub async fn serve(&self, listener: TcpListener) -> Result<(), ApiError> {
+        if !self.has_token() {
+            return Err(api_err!(Error::InvalidConfig(
+                "An auth token is required to serve the control plane".to_owned()
+            )));
+        }
+
+        loop {
+            let (stream, _) = listener.accept().await.map_err(|e| api_err!(e.into()))?;
+            let service = self.clone();
+
+            tokio::spawn(async move {
+                let mut buffer = vec![];
+
+                loop {
+                    let line =
+                        match tcp::read_line(&stream, &mut buffer, tcp::MAX_REQUEST_SIZE).await {
+                            Ok(Some(line)) => line,
+                            Ok(None) => break,
+                            Err(e) => {
+                                let _ = tcp::write_line(&stream, &Self::error_response(&e)).await;
+
+                                break;
+                            }
+                        };
+
+                    let frame = match serde_json::from_slice::<tcp::Frame>(&line) {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            let _ = tcp::write_line(&stream, &Self::error_response(&e)).await;
+
+                            break;
+                        }
+                    };
+
+                    if let Err(e) = service.authenticate(&frame.token) {
+                        let _ = tcp::write_line(&stream, &Self::error_response(&e)).await;
+
+                        break;
+                    }
+
+                    let response = match frame.request() {
+                        Ok(request) => service.handle(frame.method, &request).await,
+                        Err(e) => Self::error_response(&e),
+                    };
+
+                    if tcp::write_line(&stream, &response).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+
+    /// Calls a RPC of the control plane.
+    ///
+    /// # Arguments
+    /// * `method` - RPC called.
+    /// * `request` - Request encoded.
+    ///
+    /// # Returns
+    /// The response of the RPC.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn call(&self, method: Method, request: &[u8]) -> Result<Value, ApiError> {
+        match method {
+            Method::Enqueue => Self::to_value(self.enqueue(request).await?),
+            Method::GetStatus => Self::to_value(self.get_status(&Self::decode(request)?).await?),
+            Method::GetResult => Self::to_value(self.get_result(&Self::decode(request)?).await?),
+            Method::Cancel => Self::to_value(self.cancel(&Self::decode(request)?).await?),
+            Method::ListJobs => Self::to_value(self.list_jobs().await?),
+            Method::Retry => Self::to_value(self.retry(&Self::decode(request)?).await?),
+            Method::Purge => Self::to_value(self.purge(Self::decode(request)?).await?),
+            Method::Stats => Self::to_value(self.stats().await?),
+            Method::Pause => Self::to_value(self.pause().await?),
+            Method::Resume => Self::to_value(self.resume().await?),
+        }
+    }
+
+    /// Decodes a request.
+    ///
+    /// # Arguments
+    /// * `request` - Request encoded.
+    ///
+    /// # Returns
+    /// The request decoded.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn decode<T: for<'a> Deserialize<'a>>(request: &[u8]) -> Result<T, ApiError> {
+        serde_json::from_slice(request).map_err(|e| api_err!(e.into()))
+    }
+
+    /// Converts the response of a RPC to a JSON value.
+    ///
+    /// # Arguments
+    /// * `response` - Response to be converted.
+    ///
+    /// # Returns
+    /// The response as JSON value.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn to_value<T: Serialize>(response: T) -> Result<Value, ApiError> {
+        serde_json::to_value(response).map_err(|e| api_err!(e.into()))
+    }
+
+    /// Encodes the response of a RPC that failed before being called.
+    ///
+    /// # Arguments
+    /// * `error` - Error raised.
+    ///
+    /// # Returns
+    /// The response encoded.
+    fn error_response(error: &impl ToString) -> Vec<u8> {
+        Self::encode(Err(error.to_string()))
+    }
+
+    /// Encodes the response of a RPC, as a `Result` serialized by serde (never failing, the
+    /// response being already converted to a JSON value).
+    ///
+    /// # Arguments
+    /// * `response` - Response of the RPC, or the message of the error raised.
+    ///
+    /// # Returns
+    /// The response encoded.
+    fn encode(response: Result<Value, String>) -> Vec<u8> {
+        let response = match response {
+            Ok(value) => serde_json::json!({ "Ok": value }),
+            Err(e) => serde_json::json!({ "Err": e }),
+        };
+
+        response.to_string().into_bytes()
+    }
+
+    /// Gets the current status and progression of a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job to be inspected.
+    ///
+    /// # Returns
+    /// The update of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn progress_update(&self, id: &Uuid) -> Result<ProgressUpdate, ApiError> {
+        Ok(ProgressUpdate {
+            id: id.to_owned(),
+            status: self.queue.job_status(id).await?,
+            progression: self.queue.job_progression(id).await?,
+        })
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Actions applied on the jobs of the shed-first kinds when the queue is overloaded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum SheddingAction {
+    /// The jobs are kept but not dispatched until the overload is over.
+    #[default]
+    Delay,
+
+    /// The jobs are dropped when enqueued (never stored nor run).
+    Drop,
+}
+
+/// Policy used to protect critical jobs when the queue is overloaded, by delaying or dropping
+/// the jobs of non-critical kinds.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct SheddingPolicy {
+    /// Kinds of job shed first when the queue is overloaded.
+    pub kinds: HashSet<String>,
+
+    /// The queue is overloaded above this number of jobs waiting to be dispatched, the ones of
+    /// the shed-first kinds excluded.
+    pub max_depth: Option<usize>,
+
+    /// The queue is overloaded when storing a job in the backend takes longer than this, the
+    /// latency last measured being halved every second until measured again.
+    pub max_backend_latency: Option<Duration>,
+
+    /// Action applied on the jobs of the shed-first kinds.
+    pub action: SheddingAction,
+}
+
+impl SheddingPolicy {
+    /// Creates a policy for a list of shed-first kinds, without thresholds.
+    ///
+    /// # Arguments
+    /// * `kinds` - Kinds of job shed first.
+    /// * `action` - Action applied on these jobs when the queue is overloaded.
+    ///
+    /// # Returns
+    /// An instance of `SheddingPolicy`.
+    pub fn new<T: Into<String>>(
+        kinds: impl IntoIterator<Item = T>,
+        action: SheddingAction,
+    ) -> Self {
+        Self {
+            kinds: kinds.into_iter().map(Into::into).collect(),
+            action,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the maximum number of jobs waiting to be dispatched.
+    ///
+    /// # Arguments
+    /// * `max_depth` - Number of jobs above which the queue is overloaded.
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    /// Sets the maximum latency of the backend.
+    ///
+    /// # Arguments
+    /// * `latency` - Latency above which the queue is overloaded.
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn max_backend_latency(self, latency: Duration) -> Self {
+        Self {
+            max_backend_latency: Some(latency),
+            ..self
+        }
+    }
+
+    /// Checks if a kind of job is shed first.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of job to check.
+    ///
+    /// # Returns
+    /// `true` if the kind is shed first, `false` otherwise.
+    pub fn sheds(&self, kind: &str) -> bool {
+        self.kinds.contains(kind)
+    }
+
+    /// Checks if the queue is overloaded.
+    ///
+    /// # Arguments
+    /// * `depth` - Number of jobs waiting to be dispatched, not of the shed-first kinds.
+    /// * `backend_latency` - Latency of the backend last measured.
+    ///
+    /// # Returns
+    /// `true` if one of the thresholds is crossed, `false` otherwise.
+    pub fn is_overloaded(&self, depth: usize, backend_latency: Duration) -> bool {
+        self.max_depth.is_some_and(|max_depth| depth > max_depth)
+            || self
+                .max_backend_latency
+                .is_some_and(|max_latency| backend_latency > max_latency)
+    }
+}
@@ -0,0 +1,69 @@
+//! Handler of the termination signals (`SIGTERM`, `SIGINT`), shutting the queues down
+//! gracefully (see `JobQueue::install_signal_handler`).
+//!
+//! The handler only raises a flag, the only work safe to be done in a signal handler: the
+//! queues watch it from a thread of their own. The default action of the signals is restored
+//! once the first one is received, so that a second one terminates the process.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::prelude::*;
+
+/// Whether a termination signal has been received by the process.
+static RECEIVED: AtomicBool = AtomicBool::new(false);
+
+/// Installation of the handler, done once per process: the OS error code if it failed.
+static INSTALL: OnceLock<Option<i32>> = OnceLock::new();
+
+/// Termination signals handled.
+const SIGNALS: [libc::c_int; 2] = [libc::SIGTERM, libc::SIGINT];
+
+/// Handles a termination signal.
+///
+/// # Arguments
+/// * `_signal` - Signal received.
+extern "C" fn on_signal(_signal: libc::c_int) {
+    RECEIVED.store(true, Ordering::SeqCst);
+
+    for signal in SIGNALS {
+        // SAFETY: signal is async-signal-safe
+        unsafe { libc::signal(signal, libc::SIG_DFL) };
+    }
+}
+
+/// Installs the handler of the termination signals, in place of their default action
+/// (terminating the process right away).
+///
+/// # Errors
+/// One of `Error` enum, the same on every call if the installation failed.
+pub(crate) fn install() -> Result<(), ApiError> {
+    let failure = INSTALL.get_or_init(|| {
+        let mut failure = None;
+
+        for signal in SIGNALS {
+            // SAFETY: the handler only stores to an atomic and restores the default action of
+            // the signals, which are async-signal-safe
+            let previous = unsafe { libc::signal(signal, on_signal as libc::sighandler_t) };
+
+            if previous == libc::SIG_ERR {
+                failure = std::io::Error::last_os_error().raw_os_error();
+            }
+        }
+
+        failure
+    });
+
+    match failure {
+        Some(code) => Err(api_err!(std::io::Error::from_raw_os_error(*code).into())),
+        None => Ok(()),
+    }
+}
+
+/// Checks if a termination signal has been received.
+///
+/// # Returns
+/// `true` if received, `false` otherwise.
+pub(crate) fn received() -> bool {
+    RECEIVED.load(Ordering::SeqCst)
+}
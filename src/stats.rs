@@ -0,0 +1,460 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::prelude::*;
+
+/// Weight given to the most recent sample in the exponential moving averages.
+const EMA_ALPHA: f64 = 0.2;
+
+/// Snapshot of the jobs of a queue, counted by status.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct QueueStats {
+    /// Number of jobs not ready yet.
+    pub not_ready: usize,
+
+    /// Number of jobs ready to be run.
+    pub ready: usize,
+
+    /// Number of jobs running.
+    pub running: usize,
+
+    /// Number of running jobs suspended.
+    pub suspended: usize,
+
+    /// Number of jobs waiting to be run again after a failed attempt.
+    #[serde(default)]
+    pub retrying: usize,
+
+    /// Number of jobs finished successfully.
+    pub succeeded: usize,
+
+    /// Number of jobs finished in error (including interrupted, cancelled and timed out).
+    pub failed: usize,
+
+    /// Estimated time needed to process every pending job, if known.
+    pub drain_time: Option<Duration>,
+}
+
+impl QueueStats {
+    /// Counts a list of jobs by status.
+    ///
+    /// # Arguments
+    /// * `jobs` - Jobs to be counted.
+    ///
+    /// # Returns
+    /// An instance of `QueueStats` (without drain time).
+    pub fn from_jobs(jobs: &[JobInfo]) -> Self {
+        let mut stats = Self::default();
+
+        for job in jobs {
+            stats.count(job);
+        }
+
+        stats
+    }
+
+    /// Counts a list of jobs by named queue and status.
+    ///
+    /// # Arguments
+    /// * `jobs` - Jobs to be counted.
+    ///
+    /// # Returns
+    /// An instance of `QueueStats` by queue (without drain time).
+    pub fn by_queue(jobs: &[JobInfo]) -> HashMap<String, Self> {
+        let mut queues: HashMap<String, Self> = HashMap::new();
+
+        for job in jobs {
+            queues.entry(job.queue.to_owned()).or_default().count(job);
+        }
+
+        queues
+    }
+
+    /// Gathers the numbers of jobs by status aggregated by a backend (see `Backend::aggregate`).
+    ///
+    /// # Arguments
+    /// * `groups` - Numbers of jobs, by status.
+    ///
+    /// # Returns
+    /// An instance of `QueueStats` (without drain time).
+    pub fn from_groups(groups: &HashMap<GroupKey, usize>) -> Self {
+        let mut stats = Self::default();
+
+        for (key, count) in groups {
+            if let GroupKey::Status(status) = key {
+                stats.add(*status, *count);
+            }
+        }
+
+        stats
+    }
+
+    /// Counts a job.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be counted.
+    fn count(&mut self, job: &JobInfo) {
+        self.add(job.status, 1);
+    }
+
+    /// Counts jobs with the same status.
+    ///
+    /// # Arguments
+    /// * `status` - Status of the jobs.
+    /// * `count` - Number of jobs.
+    fn add(&mut self, status: Status, count: usize) {
+        match status {
+            Status::NotReady => self.not_ready += count,
+            Status::Ready => self.ready += count,
+            Status::Running => self.running += count,
+            Status::Suspended => self.suspended += count,
+            Status::Retrying => self.retrying += count,
+            Status::Finished(ResultStatus::Success) => self.succeeded += count,
+            Status::Finished(
+                ResultStatus::Error
+                | ResultStatus::Interrupted
+                | ResultStatus::Cancelled
+                | ResultStatus::TimedOut
+                | ResultStatus::DeadlineExceeded,
+            ) => self.failed += count,
+            Status::Removed => (),
+        }
+    }
+
+    /// Gets the total number of jobs.
+    ///
+    /// # Returns
+    /// The number of jobs counted.
+    pub fn total(&self) -> usize {
+        self.not_ready
+            + self.ready
+            + self.running
+            + self.suspended
+            + self.retrying
+            + self.succeeded
+            + self.failed
+    }
+}
+
+/// Utilization and saturation of a worker, i.e. of the slots of a queue running jobs (its
+/// maximum number of jobs in flight, or the size of its thread pool if unlimited).
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct WorkerStats {
+    /// Identifier of the worker.
+    pub worker_id: String,
+
+    /// Number of slots of the worker.
+    pub slots: usize,
+
+    /// Number of slots currently running a job.
+    pub busy_slots: usize,
+
+    /// Number of jobs ready to be run.
+    pub ready: usize,
+
+    /// Time spent running jobs since the queue started, summed over the slots.
+    pub busy_time: Duration,
+
+    /// Time spent idle since the queue started, summed over the slots.
+    pub idle_time: Duration,
+}
+
+impl WorkerStats {
+    /// Gets the number of slots not running a job.
+    ///
+    /// # Returns
+    /// The number of free slots.
+    pub fn free_slots(&self) -> usize {
+        self.slots.saturating_sub(self.busy_slots)
+    }
+
+    /// Gets the utilization of the worker.
+    ///
+    /// # Returns
+    /// The percentage of the time of the slots spent running jobs (0 if not started).
+    pub fn utilization(&self) -> f64 {
+        let total = (self.busy_time + self.idle_time).as_secs_f64();
+
+        match total > 0.0 {
+            true => self.busy_time.as_secs_f64() / total * 100.0,
+            false => 0.0,
+        }
+    }
+
+    /// Gets the saturation of the worker: the jobs running or ready to be run per slot.
+    ///
+    /// # Returns
+    /// The ratio, above 1 when more jobs are ready than there are free slots.
+    pub fn saturation(&self) -> f64 {
+        (self.busy_slots + self.ready) as f64 / self.slots.max(1) as f64
+    }
+
+    /// Checks if the worker is saturated.
+    ///
+    /// # Returns
+    /// `true` if more jobs are ready than there are free slots, `false` otherwise.
+    pub fn is_saturated(&self) -> bool {
+        self.ready > self.free_slots()
+    }
+}
+
+/// Tracker of the time spent by the slots of a worker running jobs or idle.
+#[derive(Debug)]
+pub(crate) struct Utilization {
+    /// Number of slots.
+    slots: usize,
+
+    /// Number of slots running a job.
+    busy_slots: usize,
+
+    /// Time spent running jobs until the last update, summed over the slots.
+    busy_time: Duration,
+
+    /// Time spent by the slots until the last update, busy or idle.
+    slot_time: Duration,
+
+    /// Timestamp of the last update.
+    updated: Instant,
+}
+
+impl Utilization {
+    /// Creates a new tracker, every slot being idle.
+    ///
+    /// # Arguments
+    /// * `slots` - Number of slots.
+    ///
+    /// # Returns
+    /// An instance of `Utilization`.
+    pub(crate) fn new(slots: usize) -> Self {
+        Self {
+            slots,
+            busy_slots: 0,
+            busy_time: Duration::ZERO,
+            slot_time: Duration::ZERO,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Accumulates the time elapsed since the last update.
+    fn advance(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.saturating_duration_since(self.updated);
+
+        self.busy_time += elapsed * self.busy_slots as u32;
+        self.slot_time += elapsed * self.slots as u32;
+        self.updated = now;
+    }
+
+    /// Updates the number of slots and of slots running a job.
+    ///
+    /// # Arguments
+    /// * `slots` - Number of slots.
+    /// * `busy_slots` - Number of slots running a job (bounded by the number of slots).
+    pub(crate) fn update(&mut self, slots: usize, busy_slots: usize) {
+        self.advance();
+
+        self.slots = slots;
+        self.busy_slots = busy_slots.min(slots);
+    }
+
+    /// Gets the statistics of the worker.
+    ///
+    /// # Arguments
+    /// * `worker_id` - Identifier of the worker.
+    /// * `ready` - Number of jobs ready to be run.
+    ///
+    /// # Returns
+    /// An instance of `WorkerStats`.
+    pub(crate) fn stats(&mut self, worker_id: &str, ready: usize) -> WorkerStats {
+        self.advance();
+
+        WorkerStats {
+            worker_id: worker_id.to_owned(),
+            slots: self.slots,
+            busy_slots: self.busy_slots,
+            ready,
+            busy_time: self.busy_time,
+            idle_time: self.slot_time.saturating_sub(self.busy_time),
+        }
+    }
+}
+
+/// Statistics of the durations of the runs of a kind of job, stored by the backend so that
+/// every worker estimates the completion time of the jobs (see `JobQueue::job_eta`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct DurationStats {
+    /// Number of runs recorded.
+    pub runs: u64,
+
+    /// Moving average of the durations, the recent runs weighing more.
+    pub average: Duration,
+
+    /// Shortest duration.
+    pub min: Duration,
+
+    /// Longest duration.
+    pub max: Duration,
+}
+
+impl DurationStats {
+    /// Records the duration of a run.
+    ///
+    /// # Arguments
+    /// * `duration` - Time spent running the job.
+    pub fn record(&mut self, duration: Duration) {
+        if self.runs == 0 {
+            self.average = duration;
+            self.min = duration;
+            self.max = duration;
+        } else {
+            self.average = Duration::from_secs_f64(
+                EMA_ALPHA * duration.as_secs_f64() + (1.0 - EMA_ALPHA) * self.average.as_secs_f64(),
+            );
+            self.min = self.min.min(duration);
+            self.max = self.max.max(duration);
+        }
+
+        self.runs += 1;
+    }
+
+    /// Estimates the completion time of a job of the kind.
+    ///
+    /// A running job reporting its progression is extrapolated from the time spent on the
+    /// steps done, otherwise it's expected to last the average duration. The time spent
+    /// waiting to be dispatched isn't estimated.
+    ///
+    /// # Arguments
+    /// * `stats` - Statistics of the kind, if any run has been recorded.
+    /// * `job` - Job to be estimated.
+    /// * `now` - Current time.
+    ///
+    /// # Returns
+    /// The estimated completion time, `None` if the job is finished or nothing is known yet.
+    pub fn eta(stats: Option<&Self>, job: &Job, now: SystemTime) -> Option<SystemTime> {
+        let average = stats.map(|stats| stats.average);
+
+        match job.status() {
+            Status::Running | Status::Suspended => {
+                let started = job.timestamps().started;
+                let elapsed = now.duration_since(started).unwrap_or_default();
+                let Progression { step, steps, .. } = job.progression();
+
+                let total = if step > 0 && steps >= step {
+                    Some(elapsed.mul_f64(steps as f64 / step as f64))
+                } else {
+                    average
+                };
+
+                total.map(|total| (started + total).max(now))
+            }
+            Status::NotReady | Status::Ready | Status::Retrying => {
+                average.map(|average| now + average)
+            }
+            Status::Finished(_) | Status::Removed => None,
+        }
+    }
+}
+
+/// Throughput of a kind of job, tracked as an exponential moving average.
+#[derive(Clone, Copy, Debug)]
+struct KindThroughput {
+    /// Moving average of the time between two completions (in seconds).
+    interval: f64,
+
+    /// Timestamp of the last completion.
+    last_finished: SystemTime,
+}
+
+/// Tracker of the recent throughput of the queue, per kind of job.
+#[derive(Debug, Default)]
+pub struct Throughput {
+    /// Throughput by kind of job.
+    kinds: HashMap<String, KindThroughput>,
+}
+
+impl Throughput {
+    /// Creates a new empty tracker.
+    ///
+    /// # Returns
+    /// An instance of `Throughput`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the completion of a job.
+    ///
+    /// The sample is the time elapsed since the previous completion of the same kind, bounded by
+    /// the duration of the job itself so an idle period doesn't count as slow processing.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the job.
+    /// * `duration` - Time spent running the job.
+    /// * `finished` - Timestamp at which the job finished.
+    pub fn record(&mut self, kind: &str, duration: Duration, finished: SystemTime) {
+        let duration = duration.as_secs_f64();
+
+        match self.kinds.get_mut(kind) {
+            Some(throughput) => {
+                let since_last = finished
+                    .duration_since(throughput.last_finished)
+                    .unwrap_or_default()
+                    .as_secs_f64();
+
+                let sample = since_last.min(duration);
+
+                throughput.interval = EMA_ALPHA * sample + (1.0 - EMA_ALPHA) * throughput.interval;
+                throughput.last_finished = finished;
+            }
+
+            None => {
+                self.kinds.insert(
+                    kind.to_owned(),
+                    KindThroughput {
+                        interval: duration,
+                        last_finished: finished,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Gets the average time between two completions of a kind of job.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the job.
+    ///
+    /// # Returns
+    /// The average interval, `None` if no job of this kind has completed yet.
+    pub fn interval(&self, kind: &str) -> Option<Duration> {
+        self.kinds
+            .get(kind)
+            .map(|throughput| Duration::from_secs_f64(throughput.interval))
+    }
+
+    /// Estimates the time needed to process pending jobs.
+    ///
+    /// Kinds are assumed to be processed concurrently, so the estimation is the longest time
+    /// needed by a single kind to be drained at its own throughput.
+    ///
+    /// # Arguments
+    /// * `depths` - Number of pending jobs by kind.
+    ///
+    /// # Returns
+    /// The estimated duration, `None` if a kind has no throughput measured yet.
+    pub fn drain_time(&self, depths: &HashMap<String, u64>) -> Option<Duration> {
+        let mut drain_time = Duration::ZERO;
+
+        for (kind, depth) in depths {
+            if *depth == 0 {
+                continue;
+            }
+
+            let interval = self.interval(kind)?;
+
+            drain_time = drain_time.max(interval.mul_f64(*depth as f64));
+        }
+
+        Some(drain_time)
+    }
+}
@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::event_log::EventLog;
+use crate::prelude::*;
+
+/// Type used to share the handler of a subscription across threads.
+pub type SharedSubscriptionHandler = Arc<dyn Fn(&Notification) + Send + Sync>;
+
+/// Identifier of a subscription to the notifications of a queue.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SubscriptionId(u64);
+
+/// Types of notification, used to filter the notifications of a subscription.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum NotificationType {
+    /// `Notification::Error`.
+    Error,
+
+    /// `Notification::Progression`.
+    Progression,
+
+    /// `Notification::Status`.
+    Status,
+
+    /// `Notification::JobShed`.
+    JobShed,
+
+    /// `Notification::JobExpired`.
+    JobExpired,
+
+    /// `Notification::JobStalled`.
+    JobStalled,
+
+    /// `Notification::StaleLockReleased`.
+    StaleLockReleased,
+
+    /// `Notification::SchemaDrift`.
+    SchemaDrift,
+
+    /// `Notification::JobUndecodable`.
+    JobUndecodable,
+
+    /// `Notification::JobOrphaned`.
+    JobOrphaned,
+
+    /// `Notification::Scaled`.
+    Scaled,
+
+    /// `Notification::PurgeProgress`.
+    PurgeProgress,
+
+    /// `Notification::StateChanged`.
+    StateChanged,
+
+    /// `Notification::Idle`.
+    Idle,
+
+    /// `Notification::Custom`.
+    Custom,
+}
+
+impl Notification {
+    /// Gets the type of the notification.
+    ///
+    /// # Returns
+    /// A value of the enum `NotificationType`.
+    pub fn notification_type(&self) -> NotificationType {
+        match self {
+            Self::Error(_) => NotificationType::Error,
+            Self::Progression(..) => NotificationType::Progression,
+            Self::Status(..) => NotificationType::Status,
+            Self::JobShed(..) => NotificationType::JobShed,
+            Self::JobExpired(_) => NotificationType::JobExpired,
+            Self::JobStalled(..) => NotificationType::JobStalled,
+            Self::StaleLockReleased(_) => NotificationType::StaleLockReleased,
+            Self::SchemaDrift(_) => NotificationType::SchemaDrift,
+            Self::JobUndecodable(..) => NotificationType::JobUndecodable,
+            Self::JobOrphaned(..) => NotificationType::JobOrphaned,
+            Self::Scaled(..) => NotificationType::Scaled,
+            Self::PurgeProgress(..) => NotificationType::PurgeProgress,
+            Self::StateChanged(_) => NotificationType::StateChanged,
+            Self::Idle => NotificationType::Idle,
+            Self::Custom(..) => NotificationType::Custom,
+        }
+    }
+
+    /// Gets the ID of the job concerned by the notification.
+    ///
+    /// # Returns
+    /// The ID of the job, `None` if the notification doesn't concern a single job.
+    pub fn job_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Progression(id, _)
+            | Self::Status(id, _)
+            | Self::JobShed(id, _)
+            | Self::JobExpired(id)
+            | Self::JobStalled(id, _)
+            | Self::JobUndecodable(id, _)
+            | Self::JobOrphaned(id, _)
+            | Self::Custom(id, ..) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// Filter selecting the notifications delivered to a subscription. A notification is
+/// delivered if it matches all of the criteria set.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct NotificationFilter {
+    /// Only select the notifications concerning this job.
+    pub job_id: Option<Uuid>,
+
+    /// Only select the notifications concerning the jobs of this kind.
+    pub kind: Option<String>,
+
+    /// Only select the notifications of this type.
+    pub notification_type: Option<NotificationType>,
+}
+
+impl NotificationFilter {
+    /// Creates a filter selecting every notification.
+    ///
+    /// # Returns
+    /// An instance of `NotificationFilter`.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the filter to the notifications concerning a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn job(self, id: Uuid) -> Self {
+        Self {
+            job_id: Some(id),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the notifications concerning the jobs of a kind.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the jobs (name of the routine variant).
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn kind(self, kind: impl Into<String>) -> Self {
+        Self {
+            kind: Some(kind.into()),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to a type of notification.
+    ///
+    /// # Arguments
+    /// * `notification_type` - Type of the notifications.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn notification_type(self, notification_type: NotificationType) -> Self {
+        Self {
+            notification_type: Some(notification_type),
+            ..self
+        }
+    }
+
+    /// Checks if a notification is selected by the filter.
+    ///
+    /// # Arguments
+    /// * `notification` - Notification to be checked.
+    /// * `kind` - Kind of the job concerned by the notification, if any.
+    ///
+    /// # Returns
+    /// `true` if selected, `false` otherwise.
+    pub fn matches(&self, notification: &Notification, kind: Option<&str>) -> bool {
+        if let Some(notification_type) = self.notification_type {
+            if notification.notification_type() != notification_type {
+                return false;
+            }
+        }
+
+        if let Some(job_id) = self.job_id {
+            if notification.job_id() != Some(job_id) {
+                return false;
+            }
+        }
+
+        match &self.kind {
+            Some(expected) => kind == Some(expected.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// Subscriptions to the notifications of a queue.
+#[derive(Default)]
+pub(crate) struct Subscribers {
+    /// Value of the next subscription ID.
+    next_id: u64,
+
+    /// Filter and handler of every subscription.
+    subscriptions: BTreeMap<SubscriptionId, (NotificationFilter, SharedSubscriptionHandler)>,
+
+    /// Kinds of the jobs enqueued, used to filter by kind.
+    kinds: HashMap<Uuid, String>,
+
+    /// Log of the events of the jobs, fed with the notifications.
+    pub(crate) events: EventLog,
+}
+
+impl Subscribers {
+    /// Registers a subscription.
+    ///
+    /// # Arguments
+    /// * `filter` - Filter selecting the notifications delivered.
+    /// * `handler` - Handler called with every notification selected.
+    ///
+    /// # Returns
+    /// The ID of the subscription.
+    pub(crate) fn subscribe(
+        &mut self,
+        filter: NotificationFilter,
+        handler: SharedSubscriptionHandler,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+
+        self.next_id += 1;
+        self.subscriptions.insert(id, (filter, handler));
+
+        id
+    }
+
+    /// Unregisters a subscription.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the subscription.
+    ///
+    /// # Returns
+    /// `true` if the subscription was registered, `false` otherwise.
+    pub(crate) fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscriptions.remove(&id).is_some()
+    }
+
+    /// Keeps track of the kind of a job enqueued.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    /// * `kind` - Kind of the job.
+    pub(crate) fn track(&mut self, id: Uuid, kind: &str) {
+        self.kinds.insert(id, kind.to_owned());
+        self.events.record(id, JobEvent::Enqueued);
+    }
+
+    /// Checks if a job enqueued is still tracked, i.e. not removed yet.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// `true` if tracked, `false` otherwise.
+    pub(crate) fn is_tracked(&self, id: &Uuid) -> bool {
+        self.kinds.contains_key(id)
+    }
+
+    /// Selects the handlers of the subscriptions matching a notification. The kind of a job is
+    /// forgotten once the job is removed.
+    ///
+    /// # Arguments
+    /// * `notification` - Notification to be delivered.
+    ///
+    /// # Returns
+    /// The handlers to be called, outside of the lock so that they can subscribe in turn.
+    pub(crate) fn select(&mut self, notification: &Notification) -> Vec<SharedSubscriptionHandler> {
+        self.events.record_notification(notification);
+
+        let kind = match notification {
+            Notification::SchemaDrift(drift) => Some(drift.kind.to_owned()),
+            _ => notification
+                .job_id()
+                .and_then(|id| self.kinds.get(&id).cloned()),
+        };
+
+        let handlers = self
+            .subscriptions
+            .values()
+            .filter(|(filter, _)| filter.matches(notification, kind.as_deref()))
+            .map(|(_, handler)| handler.clone())
+            .collect();
+
+        match notification {
+            Notification::Status(id, Status::Removed) | Notification::JobExpired(id) => {
+                self.kinds.remove(id);
+            }
+            _ => {}
+        }
+
+        handlers
+    }
+}
@@ -0,0 +1,147 @@
+//! Framing of the control plane over TCP: every request and response is a JSON document on a
+//! single line.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::net::TcpStream;
+
+use crate::prelude::*;
+
+/// Maximum size of a request line read by the server.
+pub(crate) const MAX_REQUEST_SIZE: usize = 1 << 20;
+
+/// Maximum size of a response line read by the client.
+pub(crate) const MAX_RESPONSE_SIZE: usize = 64 << 20;
+
+/// Request sent on the wire.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct Frame {
+    /// RPC called.
+    pub(crate) method: Method,
+
+    /// Request of the RPC (`null` if empty).
+    pub(crate) request: Value,
+
+    /// Auth token of the client.
+    #[serde(default)]
+    pub(crate) token: String,
+}
+
+impl Frame {
+    /// Creates a frame from a request encoded.
+    ///
+    /// # Arguments
+    /// * `method` - RPC called.
+    /// * `request` - Request encoded (JSON or empty).
+    /// * `token` - Auth token of the client.
+    ///
+    /// # Returns
+    /// An instance of `Frame`.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn new(method: Method, request: &[u8], token: &str) -> Result<Self, ApiError> {
+        let request = if request.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(request).map_err(|e| api_err!(e.into()))?
+        };
+
+        Ok(Self {
+            method,
+            request,
+            token: token.to_owned(),
+        })
+    }
+
+    /// Gets the request encoded.
+    ///
+    /// # Returns
+    /// The request encoded (empty if `null`).
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn request(&self) -> Result<Vec<u8>, ApiError> {
+        match self.request {
+            Value::Null => Ok(vec![]),
+            _ => serde_json::to_vec(&self.request).map_err(|e| api_err!(e.into())),
+        }
+    }
+}
+
+/// Reads a line from a stream.
+///
+/// # Arguments
+/// * `stream` - Stream to read from.
+/// * `buffer` - Bytes read after the previous line.
+/// * `max_size` - Maximum size of the line.
+///
+/// # Returns
+/// The line read (without the line feed), `None` if the stream is closed.
+///
+/// # Errors
+/// `Error::FrameTooLarge` if no line feed is found within `max_size` bytes, one of `Error`
+/// enum otherwise.
+pub(crate) async fn read_line(
+    stream: &TcpStream,
+    buffer: &mut Vec<u8>,
+    max_size: usize,
+) -> Result<Option<Vec<u8>>, ApiError> {
+    // Bytes already searched for a line feed, only the ones read next searched again
+    let mut scanned = 0;
+
+    loop {
+        if let Some(pos) = buffer[scanned..].iter().position(|byte| *byte == b'\n') {
+            let pos = scanned + pos;
+            let line = buffer.drain(..=pos).take(pos).collect();
+
+            return Ok(Some(line));
+        }
+
+        if buffer.len() > max_size {
+            return Err(api_err!(Error::FrameTooLarge(max_size)));
+        }
+
+        scanned = buffer.len();
+
+        stream.readable().await.map_err(|e| api_err!(e.into()))?;
+
+        let mut chunk = [0; 4096];
+
+        match stream.try_read(&mut chunk) {
+            Ok(0) => return Ok(None),
+            Ok(len) => buffer.extend_from_slice(&chunk[..len]),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(api_err!(e.into())),
+        }
+    }
+}
+
+/// Writes a line to a stream.
+///
+/// # Arguments
+/// * `stream` - Stream to write to.
+/// * `line` - Line to be written (without line feed).
+///
+/// # Errors
+/// One of `Error` enum.
+pub(crate) async fn write_line(stream: &TcpStream, line: &[u8]) -> Result<(), ApiError> {
+    let mut bytes = Vec::with_capacity(line.len() + 1);
+
+    bytes.extend_from_slice(line);
+    bytes.push(b'\n');
+
+    let mut written = 0;
+
+    while written < bytes.len() {
+        stream.writable().await.map_err(|e| api_err!(e.into()))?;
+
+        match stream.try_write(&bytes[written..]) {
+            Ok(len) => written += len,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(e) => return Err(api_err!(e.into())),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Quota of the jobs of a tenant taken by a queue at the same time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct TenantQuota {
+    /// Maximum number of jobs of the tenant running at the same time (unlimited if `None`).
+    pub max_running: Option<usize>,
+
+    /// Maximum number of jobs of the tenant waiting to be dispatched, the jobs above being
+    /// rejected when enqueued (unlimited if `None`).
+    pub max_pending: Option<usize>,
+}
+
+impl TenantQuota {
+    /// Sets the maximum number of jobs running at the same time.
+    ///
+    /// # Arguments
+    /// * `max_running` - Maximum number of jobs.
+    ///
+    /// # Returns
+    /// The updated quota.
+    pub fn max_running(self, max_running: usize) -> Self {
+        Self {
+            max_running: Some(max_running),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of jobs waiting to be dispatched.
+    ///
+    /// # Arguments
+    /// * `max_pending` - Maximum number of jobs.
+    ///
+    /// # Returns
+    /// The updated quota.
+    pub fn max_pending(self, max_pending: usize) -> Self {
+        Self {
+            max_pending: Some(max_pending),
+            ..self
+        }
+    }
+}
+
+/// Quotas of the tenants owning the jobs (see `Job::set_tenant`), the jobs without tenant
+/// being unrestricted.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct TenantQuotas {
+    /// Quota of the tenants without a quota of their own.
+    pub default: TenantQuota,
+
+    /// Quotas by tenant.
+    pub tenants: HashMap<String, TenantQuota>,
+}
+
+impl TenantQuotas {
+    /// Creates quotas applied to every tenant.
+    ///
+    /// # Arguments
+    /// * `default` - Quota of the tenants without a quota of their own.
+    ///
+    /// # Returns
+    /// An instance of `TenantQuotas`.
+    pub fn new(default: TenantQuota) -> Self {
+        Self {
+            default,
+            tenants: HashMap::new(),
+        }
+    }
+
+    /// Sets the quota of a tenant, in place of the default one.
+    ///
+    /// # Arguments
+    /// * `tenant` - Tenant.
+    /// * `quota` - Quota of the tenant.
+    ///
+    /// # Returns
+    /// The updated quotas.
+    pub fn tenant(mut self, tenant: impl Into<String>, quota: TenantQuota) -> Self {
+        self.tenants.insert(tenant.into(), quota);
+
+        self
+    }
+
+    /// Gets the quota of a tenant.
+    ///
+    /// # Arguments
+    /// * `tenant` - Tenant.
+    ///
+    /// # Returns
+    /// The quota of the tenant, the default one if it has none of its own.
+    pub fn quota(&self, tenant: &str) -> TenantQuota {
+        self.tenants.get(tenant).copied().unwrap_or(self.default)
+    }
+}
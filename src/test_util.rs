@@ -0,0 +1,1359 @@
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::inline;
+use crate::memory_backend::MemoryBackend;
+use crate::prelude::*;
+
+/// Call made on a backend, as captured by the `RecordingBackend`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BackendCall {
+    /// `Backend::get`.
+    Get(Uuid),
+
+    /// `Backend::schedule`.
+    Schedule(Uuid),
+
+    /// `Backend::run`.
+    Run(Uuid),
+
+    /// `Backend::status`.
+    Status(Uuid),
+
+    /// `Backend::statuses`.
+    Statuses(Vec<Uuid>),
+
+    /// `Backend::set_status`.
+    SetStatus(Uuid, Status),
+
+    /// `Backend::result`.
+    Result(Uuid),
+
+    /// `Backend::set_result`.
+    SetResult(Uuid),
+
+    /// `Backend::set_result_ref`.
+    SetResultRef(Uuid),
+
+    /// `Backend::add_attempt`.
+    AddAttempt(Uuid, ResultStatus),
+
+    /// `Backend::set_checkpoint`.
+    SetCheckpoint(Uuid),
+
+    /// `Backend::set_metadata`.
+    SetMetadata(Uuid, String),
+
+    /// `Backend::append_chunk`.
+    AppendChunk(Uuid),
+
+    /// `Backend::chunks`.
+    Chunks(Uuid, usize),
+
+    /// `Backend::set_steps`.
+    SetSteps(Uuid, u64),
+
+    /// `Backend::set_step`.
+    SetStep(Uuid, u64),
+
+    /// `Backend::progression`.
+    Progression(Uuid),
+
+    /// `Backend::routine`.
+    Routine(Uuid),
+
+    /// `Backend::expire_policy`.
+    ExpirePolicy(Uuid),
+
+    /// `Backend::set_expiration`.
+    SetExpiration(Uuid),
+
+    /// `Backend::remove`.
+    Remove(Uuid),
+
+    /// `Backend::remove_expired`.
+    RemoveExpired,
+
+    /// `Backend::expire_results`.
+    ExpireResults,
+
+    /// `Backend::retain_last`.
+    RetainLast(usize),
+
+    /// `Backend::purge`.
+    Purge(PurgeFilter),
+
+    /// `Backend::acquire_lock`.
+    AcquireLock(String, String),
+
+    /// `Backend::refresh_lock`.
+    RefreshLock(String, String),
+
+    /// `Backend::release_lock`.
+    ReleaseLock(String, String),
+
+    /// `Backend::release_stale_locks`.
+    ReleaseStaleLocks,
+
+    /// `Backend::claim`.
+    Claim(String),
+
+    /// `Backend::extend_lease`.
+    ExtendLease(Uuid, String),
+
+    /// `Backend::release`.
+    Release(Uuid, String),
+
+    /// `Backend::reap_orphans`.
+    ReapOrphans(OrphanPolicy),
+
+    /// `Backend::timeline`.
+    Timeline(TimelineFilter),
+
+    /// `Backend::set_paused`.
+    SetPaused(bool),
+
+    /// `Backend::is_paused`.
+    IsPaused,
+
+    /// `Backend::record_duration`.
+    RecordDuration(String),
+
+    /// `Backend::duration_stats`.
+    DurationStats(String),
+
+    /// `Backend::set_namespace`.
+    SetNamespace(String),
+
+    /// `Backend::set_migrator`.
+    SetMigrator,
+
+    /// `Backend::set_codec`.
+    SetCodec(String),
+
+    /// `Backend::set_compression`.
+    SetCompression,
+
+    /// `Backend::set_encryption`.
+    SetEncryption,
+
+    /// `Backend::set_clock`.
+    SetClock,
+
+    /// `Backend::jobs`.
+    Jobs,
+
+    /// `Backend::job_infos`.
+    JobInfos,
+
+    /// `Backend::jobs_page`.
+    JobsPage(Option<Cursor>, usize),
+}
+
+impl BackendCall {
+    /// Gets the job concerned by the call.
+    ///
+    /// # Returns
+    /// The ID of the job, `None` if the call doesn't concern a single job.
+    pub fn job_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Get(id)
+            | Self::Schedule(id)
+            | Self::Run(id)
+            | Self::Status(id)
+            | Self::SetStatus(id, _)
+            | Self::Result(id)
+            | Self::SetResult(id)
+            | Self::SetResultRef(id)
+            | Self::AddAttempt(id, _)
+            | Self::SetCheckpoint(id)
+            | Self::SetMetadata(id, _)
+            | Self::AppendChunk(id)
+            | Self::Chunks(id, _)
+            | Self::SetSteps(id, _)
+            | Self::SetStep(id, _)
+            | Self::Progression(id)
+            | Self::Routine(id)
+            | Self::ExpirePolicy(id)
+            | Self::SetExpiration(id)
+            | Self::Remove(id)
+            | Self::ExtendLease(id, _)
+            | Self::Release(id, _) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// Handle used to inspect the calls captured by a `RecordingBackend`, even once the backend
+/// has been moved into the job queue.
+#[derive(Clone, Debug, Default)]
+pub struct CallRecorder {
+    /// Calls captured, in order.
+    calls: Shared<Vec<BackendCall>>,
+}
+
+impl CallRecorder {
+    /// Gets the calls captured so far.
+    ///
+    /// # Returns
+    /// The list of calls, in order.
+    pub fn calls(&self) -> Vec<BackendCall> {
+        self.calls
+            .lock()
+            .map(|calls| calls.clone())
+            .unwrap_or_default()
+    }
+
+    /// Gets the calls captured so far concerning a job.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The list of calls, in order.
+    pub fn calls_for(&self, id: &Uuid) -> Vec<BackendCall> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.job_id().as_ref() == Some(id))
+            .collect()
+    }
+
+    /// Gets the statuses a job went through so far.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The list of statuses set, in order.
+    pub fn statuses(&self, id: &Uuid) -> Vec<Status> {
+        self.calls_for(id)
+            .into_iter()
+            .filter_map(|call| match call {
+                BackendCall::SetStatus(_, status) => Some(status),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the progress updates reported by a job so far.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The list of steps set, in order.
+    pub fn steps(&self, id: &Uuid) -> Vec<u64> {
+        self.calls_for(id)
+            .into_iter()
+            .filter_map(|call| match call {
+                BackendCall::SetStep(_, step) => Some(step),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Forgets the calls captured so far.
+    pub fn clear(&self) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.clear();
+        }
+    }
+
+    /// Captures a call.
+    ///
+    /// # Arguments
+    /// * `call` - Call to be captured.
+    fn record(&self, call: BackendCall) {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(call);
+        }
+    }
+}
+
+/// Backend wrapper capturing every call before forwarding it to the wrapped backend.
+pub struct RecordingBackend<B> {
+    /// Wrapped backend.
+    inner: B,
+
+    /// Calls captured.
+    recorder: CallRecorder,
+}
+
+impl RecordingBackend<MemoryBackend> {
+    /// Creates a recording backend wrapping a memory backend.
+    ///
+    /// # Returns
+    /// An instance of `RecordingBackend`.
+    pub fn new() -> Self {
+        Self::wrap(MemoryBackend::new())
+    }
+}
+
+impl Default for RecordingBackend<MemoryBackend> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> RecordingBackend<B> {
+    /// Creates a recording backend wrapping another backend.
+    ///
+    /// # Arguments
+    /// * `inner` - Backend to be wrapped.
+    ///
+    /// # Returns
+    /// An instance of `RecordingBackend`.
+    pub fn wrap(inner: B) -> Self {
+        Self {
+            inner,
+            recorder: CallRecorder::default(),
+        }
+    }
+
+    /// Gets a handle on the calls captured.
+    ///
+    /// # Returns
+    /// A `CallRecorder` sharing the calls of this backend.
+    pub fn recorder(&self) -> CallRecorder {
+        self.recorder.clone()
+    }
+}
+
+#[async_trait]
+impl<B, RoutineType, Context> Backend<RoutineType, Context> for RecordingBackend<B>
+where
+    B: Backend<RoutineType, Context>,
+    RoutineType: Routine<Context> + Sync,
+    for<'async_trait> Context: Send + 'async_trait,
+{
+    async fn get(&mut self, id: &Uuid) -> Result<Job, ApiError> {
+        self.recorder.record(BackendCall::Get(id.to_owned()));
+        self.inner.get(id).await
+    }
+
+    fn schedule(&mut self, job: Job) -> Result<(), ApiError> {
+        self.recorder.record(BackendCall::Schedule(job.id()));
+        self.inner.schedule(job)
+    }
+
+    async fn run(
+        &mut self,
+        id: &Uuid,
+        context: Option<ContextMode<Context>>,
+        messages_channel: SharedMessageChannel,
+    ) -> Result<(), ApiError> {
+        self.recorder.record(BackendCall::Run(id.to_owned()));
+        self.inner.run(id, context, messages_channel).await
+    }
+
+    fn status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.recorder.record(BackendCall::Status(id.to_owned()));
+        self.inner.status(id)
+    }
+
+    fn statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        self.recorder.record(BackendCall::Statuses(ids.to_vec()));
+        self.inner.statuses(ids)
+    }
+
+    fn set_status(&mut self, id: &Uuid, status: Status) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::SetStatus(id.to_owned(), status));
+        self.inner.set_status(id, status)
+    }
+
+    fn result(&self, id: &Uuid) -> Result<&[u8], ApiError> {
+        self.recorder.record(BackendCall::Result(id.to_owned()));
+        self.inner.result(id)
+    }
+
+    fn set_result(&mut self, id: &Uuid, result: Vec<u8>) -> Result<(), ApiError> {
+        self.recorder.record(BackendCall::SetResult(id.to_owned()));
+        self.inner.set_result(id, result)
+    }
+
+    fn set_result_ref(&mut self, id: &Uuid, key: String) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::SetResultRef(id.to_owned()));
+        self.inner.set_result_ref(id, key)
+    }
+
+    fn add_attempt(&mut self, id: &Uuid, attempt: Attempt) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::AddAttempt(id.to_owned(), attempt.status));
+        self.inner.add_attempt(id, attempt)
+    }
+
+    fn set_checkpoint(&mut self, id: &Uuid, checkpoint: Vec<u8>) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::SetCheckpoint(id.to_owned()));
+        self.inner.set_checkpoint(id, checkpoint)
+    }
+
+    fn set_metadata(
+        &mut self,
+        id: &Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::SetMetadata(id.to_owned(), key.to_owned()));
+        self.inner.set_metadata(id, key, value)
+    }
+
+    fn append_chunk(&mut self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::AppendChunk(id.to_owned()));
+        self.inner.append_chunk(id, chunk)
+    }
+
+    fn chunks(&self, id: &Uuid, from: usize) -> Result<Vec<Vec<u8>>, ApiError> {
+        self.recorder
+            .record(BackendCall::Chunks(id.to_owned(), from));
+        self.inner.chunks(id, from)
+    }
+
+    fn set_steps(&mut self, id: &Uuid, steps: u64) -> Result<Progression, ApiError> {
+        self.recorder
+            .record(BackendCall::SetSteps(id.to_owned(), steps));
+        self.inner.set_steps(id, steps)
+    }
+
+    fn set_step(&mut self, id: &Uuid, step: u64) -> Result<Progression, ApiError> {
+        self.recorder
+            .record(BackendCall::SetStep(id.to_owned(), step));
+        self.inner.set_step(id, step)
+    }
+
+    fn progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
+        self.recorder
+            .record(BackendCall::Progression(id.to_owned()));
+        self.inner.progression(id)
+    }
+
+    fn routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
+        self.recorder.record(BackendCall::Routine(id.to_owned()));
+        self.inner.routine(id)
+    }
+
+    fn expire_policy(&self, id: &Uuid) -> Result<ExpirePolicy, ApiError> {
+        self.recorder
+            .record(BackendCall::ExpirePolicy(id.to_owned()));
+        self.inner.expire_policy(id)
+    }
+
+    fn set_expiration(&mut self, id: &Uuid, expired: SystemTime) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::SetExpiration(id.to_owned()));
+        self.inner.set_expiration(id, expired)
+    }
+
+    fn remove(&mut self, id: &Uuid) -> Result<(), ApiError> {
+        self.recorder.record(BackendCall::Remove(id.to_owned()));
+        self.inner.remove(id)
+    }
+
+    fn remove_expired(&mut self) -> Result<Vec<Uuid>, ApiError> {
+        self.recorder.record(BackendCall::RemoveExpired);
+        self.inner.remove_expired()
+    }
+
+    fn expire_results(&mut self) -> Result<Vec<(Uuid, Option<String>)>, ApiError> {
+        self.recorder.record(BackendCall::ExpireResults);
+        self.inner.expire_results()
+    }
+
+    fn retain_last(&mut self, count: usize) -> Result<Vec<Uuid>, ApiError> {
+        self.recorder.record(BackendCall::RetainLast(count));
+        self.inner.retain_last(count)
+    }
+
+    fn purge(&mut self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        self.recorder.record(BackendCall::Purge(filter.clone()));
+        self.inner.purge(filter)
+    }
+
+    fn acquire_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.recorder
+            .record(BackendCall::AcquireLock(key.to_owned(), owner.to_owned()));
+        self.inner.acquire_lock(key, owner, ttl)
+    }
+
+    fn refresh_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.recorder
+            .record(BackendCall::RefreshLock(key.to_owned(), owner.to_owned()));
+        self.inner.refresh_lock(key, owner, ttl)
+    }
+
+    fn release_lock(&mut self, key: &str, owner: &str) -> Result<bool, ApiError> {
+        self.recorder
+            .record(BackendCall::ReleaseLock(key.to_owned(), owner.to_owned()));
+        self.inner.release_lock(key, owner)
+    }
+
+    fn release_stale_locks(&mut self) -> Result<Vec<Lock>, ApiError> {
+        self.recorder.record(BackendCall::ReleaseStaleLocks);
+        self.inner.release_stale_locks()
+    }
+
+    fn claim(
+        &mut self,
+        worker_id: &str,
+        lease: Duration,
+        kinds: &KindFilter,
+        capabilities: &Capabilities,
+    ) -> Result<Option<Job>, ApiError> {
+        self.recorder
+            .record(BackendCall::Claim(worker_id.to_owned()));
+        self.inner.claim(worker_id, lease, kinds, capabilities)
+    }
+
+    fn extend_lease(
+        &mut self,
+        id: &Uuid,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError> {
+        self.recorder.record(BackendCall::ExtendLease(
+            id.to_owned(),
+            worker_id.to_owned(),
+        ));
+        self.inner.extend_lease(id, worker_id, lease)
+    }
+
+    fn release(&mut self, id: &Uuid, worker_id: &str) -> Result<bool, ApiError> {
+        self.recorder
+            .record(BackendCall::Release(id.to_owned(), worker_id.to_owned()));
+        self.inner.release(id, worker_id)
+    }
+
+    fn reap_orphans(
+        &mut self,
+        timeout: Duration,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, ApiError> {
+        self.recorder.record(BackendCall::ReapOrphans(policy));
+        self.inner.reap_orphans(timeout, policy)
+    }
+
+    fn timeline(
+        &self,
+        range: &Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError> {
+        self.recorder.record(BackendCall::Timeline(filter.clone()));
+        self.inner.timeline(range, filter)
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), ApiError> {
+        self.recorder.record(BackendCall::SetPaused(paused));
+        self.inner.set_paused(paused)
+    }
+
+    fn is_paused(&self) -> Result<bool, ApiError> {
+        self.recorder.record(BackendCall::IsPaused);
+        self.inner.is_paused()
+    }
+
+    fn record_duration(&mut self, kind: &str, duration: Duration) -> Result<(), ApiError> {
+        self.recorder
+            .record(BackendCall::RecordDuration(kind.to_owned()));
+        self.inner.record_duration(kind, duration)
+    }
+
+    fn duration_stats(&self, kind: &str) -> Result<Option<DurationStats>, ApiError> {
+        self.recorder
+            .record(BackendCall::DurationStats(kind.to_owned()));
+        self.inner.duration_stats(kind)
+    }
+
+    fn set_namespace(&mut self, namespace: &str) {
+        self.recorder
+            .record(BackendCall::SetNamespace(namespace.to_owned()));
+        self.inner.set_namespace(namespace)
+    }
+
+    fn set_migrator(&mut self, migrator: SharedMigrator) {
+        self.recorder.record(BackendCall::SetMigrator);
+        self.inner.set_migrator(migrator)
+    }
+
+    fn set_codec(&mut self, codec: SharedCodec) {
+        self.recorder
+            .record(BackendCall::SetCodec(codec.name().to_owned()));
+        self.inner.set_codec(codec)
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        self.recorder.record(BackendCall::SetCompression);
+        self.inner.set_compression(compression)
+    }
+
+    fn set_encryption(&mut self, encryption: Encryption) {
+        self.recorder.record(BackendCall::SetEncryption);
+        self.inner.set_encryption(encryption)
+    }
+
+    fn set_clock(&mut self, clock: SharedClock) {
+        self.recorder.record(BackendCall::SetClock);
+        self.inner.set_clock(clock)
+    }
+
+    fn jobs(&self) -> Result<Vec<Job>, ApiError> {
+        self.recorder.record(BackendCall::Jobs);
+        self.inner.jobs()
+    }
+
+    fn job_infos(&self) -> Result<Vec<JobInfo>, ApiError> {
+        self.recorder.record(BackendCall::JobInfos);
+        self.inner.job_infos()
+    }
+
+    fn jobs_page(&self, after: Option<&Cursor>, limit: usize) -> Result<JobPage, ApiError> {
+        self.recorder
+            .record(BackendCall::JobsPage(after.copied(), limit));
+        self.inner.jobs_page(after, limit)
+    }
+}
+
+/// Operations of a backend subject to the faults injected by a `ChaosBackend`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChaosOp {
+    /// `Backend::get`.
+    Get,
+
+    /// `Backend::schedule`.
+    Schedule,
+
+    /// `Backend::status`, `Backend::statuses` and `Backend::set_status`.
+    Status,
+
+    /// `Backend::result`, `Backend::set_result` and `Backend::set_result_ref`.
+    Result,
+}
+
+/// Faults injected in an operation of a backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fault {
+    /// Probability of a call to fail, between 0 and 1.
+    pub failure_rate: f64,
+
+    /// Every how many calls one fails (never if 0).
+    pub fail_every: usize,
+
+    /// Latency added to every call.
+    pub latency: Duration,
+}
+
+impl Fault {
+    /// Sets the probability of a call to fail.
+    ///
+    /// # Arguments
+    /// * `failure_rate` - Probability, between 0 and 1.
+    ///
+    /// # Returns
+    /// The updated fault.
+    pub fn failure_rate(self, failure_rate: f64) -> Self {
+        Self {
+            failure_rate: failure_rate.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Makes one call out of a number fail.
+    ///
+    /// # Arguments
+    /// * `calls` - Number of calls (never failing if 0).
+    ///
+    /// # Returns
+    /// The updated fault.
+    pub fn fail_every(self, calls: usize) -> Self {
+        Self {
+            fail_every: calls,
+            ..self
+        }
+    }
+
+    /// Sets the latency added to every call.
+    ///
+    /// # Arguments
+    /// * `latency` - Latency.
+    ///
+    /// # Returns
+    /// The updated fault.
+    pub fn latency(self, latency: Duration) -> Self {
+        Self { latency, ..self }
+    }
+}
+
+/// Faults injected by a `ChaosBackend`, and the calls made.
+struct ChaosState {
+    /// Faults injected, by operation.
+    faults: HashMap<ChaosOp, Fault>,
+
+    /// Number of calls made, by operation.
+    calls: HashMap<ChaosOp, usize>,
+
+    /// Number of failures injected, by operation.
+    injected: HashMap<ChaosOp, usize>,
+
+    /// Generator drawing the random failures.
+    rng: StdRng,
+}
+
+/// Handle used to change the faults injected by a `ChaosBackend`, even once the backend has
+/// been moved into the job queue.
+#[derive(Clone)]
+pub struct ChaosController {
+    /// State shared with the backend.
+    state: Shared<ChaosState>,
+}
+
+impl ChaosController {
+    /// Sets the faults injected in an operation, in place of the previous ones.
+    ///
+    /// # Arguments
+    /// * `op` - Operation.
+    /// * `fault` - Faults injected.
+    pub fn set_fault(&self, op: ChaosOp, fault: Fault) {
+        if let Ok(mut state) = self.state.lock() {
+            state.faults.insert(op, fault);
+        }
+    }
+
+    /// Stops injecting faults.
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.faults.clear();
+        }
+    }
+
+    /// Gets the number of failures injected in an operation.
+    ///
+    /// # Arguments
+    /// * `op` - Operation.
+    ///
+    /// # Returns
+    /// The number of failures.
+    pub fn injected(&self, op: ChaosOp) -> usize {
+        self.state
+            .lock()
+            .map(|state| state.injected.get(&op).copied().unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    /// Applies the faults of an operation to a call.
+    ///
+    /// # Arguments
+    /// * `op` - Operation called.
+    ///
+    /// # Errors
+    /// `Error::Custom` if a failure is injected.
+    fn inject(&self, op: ChaosOp) -> Result<(), ApiError> {
+        let (failed, latency) = {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|e| api_err!(Error::Custom(e.to_string())))?;
+
+            let calls = state.calls.entry(op).or_default();
+            *calls += 1;
+            let calls = *calls;
+
+            let Some(fault) = state.faults.get(&op).copied() else {
+                return Ok(());
+            };
+
+            let failed = (fault.fail_every > 0 && calls % fault.fail_every == 0)
+                || state.rng.gen_bool(fault.failure_rate.clamp(0.0, 1.0));
+
+            if failed {
+                *state.injected.entry(op).or_default() += 1;
+            }
+
+            (failed, fault.latency)
+        };
+
+        if !latency.is_zero() {
+            std::thread::sleep(latency);
+        }
+
+        match failed {
+            true => Err(api_err!(Error::Custom(format!(
+                "Fault injected in {op:?} operation"
+            )))),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Backend wrapper injecting failures and latency in the operations of the wrapped backend
+/// (see `ChaosOp`), to test the handling of the failures of a queue.
+pub struct ChaosBackend<B> {
+    /// Wrapped backend.
+    inner: B,
+
+    /// Faults injected.
+    controller: ChaosController,
+}
+
+impl ChaosBackend<MemoryBackend> {
+    /// Creates a chaos backend wrapping a memory backend.
+    ///
+    /// # Returns
+    /// An instance of `ChaosBackend`.
+    pub fn new() -> Self {
+        Self::wrap(MemoryBackend::new())
+    }
+}
+
+impl Default for ChaosBackend<MemoryBackend> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B> ChaosBackend<B> {
+    /// Creates a chaos backend wrapping another backend, without any fault injected yet.
+    ///
+    /// # Arguments
+    /// * `inner` - Backend to be wrapped.
+    ///
+    /// # Returns
+    /// An instance of `ChaosBackend`.
+    pub fn wrap(inner: B) -> Self {
+        Self {
+            inner,
+            controller: ChaosController {
+                state: Arc::new(Mutex::new(ChaosState {
+                    faults: HashMap::new(),
+                    calls: HashMap::new(),
+                    injected: HashMap::new(),
+                    rng: StdRng::from_entropy(),
+                })),
+            },
+        }
+    }
+
+    /// Seeds the generator drawing the random failures, for reproducible runs.
+    ///
+    /// # Arguments
+    /// * `seed` - Seed.
+    ///
+    /// # Returns
+    /// An instance of `ChaosBackend`.
+    pub fn seed(self, seed: u64) -> Self {
+        if let Ok(mut state) = self.controller.state.lock() {
+            state.rng = StdRng::seed_from_u64(seed);
+        }
+
+        self
+    }
+
+    /// Sets the faults injected in an operation.
+    ///
+    /// # Arguments
+    /// * `op` - Operation.
+    /// * `fault` - Faults injected.
+    ///
+    /// # Returns
+    /// An instance of `ChaosBackend`.
+    pub fn fault(self, op: ChaosOp, fault: Fault) -> Self {
+        self.controller.set_fault(op, fault);
+
+        self
+    }
+
+    /// Gets a handle on the faults injected.
+    ///
+    /// # Returns
+    /// A `ChaosController` sharing the faults of this backend.
+    pub fn controller(&self) -> ChaosController {
+        self.controller.clone()
+    }
+}
+
+#[async_trait]
+impl<B, RoutineType, Context> Backend<RoutineType, Context> for ChaosBackend<B>
+where
+    B: Backend<RoutineType, Context>,
+    RoutineType: Routine<Context> + Sync,
+    for<'async_trait> Context: Send + 'async_trait,
+{
+    async fn get(&mut self, id: &Uuid) -> Result<Job, ApiError> {
+        self.controller.inject(ChaosOp::Get)?;
+        self.inner.get(id).await
+    }
+
+    fn schedule(&mut self, job: Job) -> Result<(), ApiError> {
+        self.controller.inject(ChaosOp::Schedule)?;
+        self.inner.schedule(job)
+    }
+
+    async fn run(
+        &mut self,
+        id: &Uuid,
+        context: Option<ContextMode<Context>>,
+        messages_channel: SharedMessageChannel,
+    ) -> Result<(), ApiError> {
+        self.inner.run(id, context, messages_channel).await
+    }
+
+    fn status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        self.controller.inject(ChaosOp::Status)?;
+        self.inner.status(id)
+    }
+
+    fn statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        self.controller.inject(ChaosOp::Status)?;
+        self.inner.statuses(ids)
+    }
+
+    fn set_status(&mut self, id: &Uuid, status: Status) -> Result<(), ApiError> {
+        self.controller.inject(ChaosOp::Status)?;
+        self.inner.set_status(id, status)
+    }
+
+    fn result(&self, id: &Uuid) -> Result<&[u8], ApiError> {
+        self.controller.inject(ChaosOp::Result)?;
+        self.inner.result(id)
+    }
+
+    fn set_result(&mut self, id: &Uuid, result: Vec<u8>) -> Result<(), ApiError> {
+        self.controller.inject(ChaosOp::Result)?;
+        self.inner.set_result(id, result)
+    }
+
+    fn set_result_ref(&mut self, id: &Uuid, key: String) -> Result<(), ApiError> {
+        self.controller.inject(ChaosOp::Result)?;
+        self.inner.set_result_ref(id, key)
+    }
+
+    fn add_attempt(&mut self, id: &Uuid, attempt: Attempt) -> Result<(), ApiError> {
+        self.inner.add_attempt(id, attempt)
+    }
+
+    fn set_checkpoint(&mut self, id: &Uuid, checkpoint: Vec<u8>) -> Result<(), ApiError> {
+        self.inner.set_checkpoint(id, checkpoint)
+    }
+
+    fn set_metadata(
+        &mut self,
+        id: &Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        self.inner.set_metadata(id, key, value)
+    }
+
+    fn append_chunk(&mut self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError> {
+        self.inner.append_chunk(id, chunk)
+    }
+
+    fn chunks(&self, id: &Uuid, from: usize) -> Result<Vec<Vec<u8>>, ApiError> {
+        self.inner.chunks(id, from)
+    }
+
+    fn set_steps(&mut self, id: &Uuid, steps: u64) -> Result<Progression, ApiError> {
+        self.inner.set_steps(id, steps)
+    }
+
+    fn set_step(&mut self, id: &Uuid, step: u64) -> Result<Progression, ApiError> {
+        self.inner.set_step(id, step)
+    }
+
+    fn progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
+        self.inner.progression(id)
+    }
+
+    fn routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
+        self.inner.routine(id)
+    }
+
+    fn expire_policy(&self, id: &Uuid) -> Result<ExpirePolicy, ApiError> {
+        self.inner.expire_policy(id)
+    }
+
+    fn set_expiration(&mut self, id: &Uuid, expired: SystemTime) -> Result<(), ApiError> {
+        self.inner.set_expiration(id, expired)
+    }
+
+    fn remove(&mut self, id: &Uuid) -> Result<(), ApiError> {
+        self.inner.remove(id)
+    }
+
+    fn remove_expired(&mut self) -> Result<Vec<Uuid>, ApiError> {
+        self.inner.remove_expired()
+    }
+
+    fn expire_results(&mut self) -> Result<Vec<(Uuid, Option<String>)>, ApiError> {
+        self.inner.expire_results()
+    }
+
+    fn retain_last(&mut self, count: usize) -> Result<Vec<Uuid>, ApiError> {
+        self.inner.retain_last(count)
+    }
+
+    fn purge(&mut self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        self.inner.purge(filter)
+    }
+
+    fn acquire_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.inner.acquire_lock(key, owner, ttl)
+    }
+
+    fn refresh_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.inner.refresh_lock(key, owner, ttl)
+    }
+
+    fn release_lock(&mut self, key: &str, owner: &str) -> Result<bool, ApiError> {
+        self.inner.release_lock(key, owner)
+    }
+
+    fn release_stale_locks(&mut self) -> Result<Vec<Lock>, ApiError> {
+        self.inner.release_stale_locks()
+    }
+
+    fn claim(
+        &mut self,
+        worker_id: &str,
+        lease: Duration,
+        kinds: &KindFilter,
+        capabilities: &Capabilities,
+    ) -> Result<Option<Job>, ApiError> {
+        self.inner.claim(worker_id, lease, kinds, capabilities)
+    }
+
+    fn extend_lease(
+        &mut self,
+        id: &Uuid,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError> {
+        self.inner.extend_lease(id, worker_id, lease)
+    }
+
+    fn release(&mut self, id: &Uuid, worker_id: &str) -> Result<bool, ApiError> {
+        self.inner.release(id, worker_id)
+    }
+
+    fn reap_orphans(
+        &mut self,
+        timeout: Duration,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, ApiError> {
+        self.inner.reap_orphans(timeout, policy)
+    }
+
+    fn timeline(
+        &self,
+        range: &Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError> {
+        self.inner.timeline(range, filter)
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), ApiError> {
+        self.inner.set_paused(paused)
+    }
+
+    fn is_paused(&self) -> Result<bool, ApiError> {
+        self.inner.is_paused()
+    }
+
+    fn record_duration(&mut self, kind: &str, duration: Duration) -> Result<(), ApiError> {
+        self.inner.record_duration(kind, duration)
+    }
+
+    fn duration_stats(&self, kind: &str) -> Result<Option<DurationStats>, ApiError> {
+        self.inner.duration_stats(kind)
+    }
+
+    fn set_namespace(&mut self, namespace: &str) {
+        self.inner.set_namespace(namespace)
+    }
+
+    fn set_migrator(&mut self, migrator: SharedMigrator) {
+        self.inner.set_migrator(migrator)
+    }
+
+    fn set_codec(&mut self, codec: SharedCodec) {
+        self.inner.set_codec(codec)
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        self.inner.set_compression(compression)
+    }
+
+    fn set_encryption(&mut self, encryption: Encryption) {
+        self.inner.set_encryption(encryption)
+    }
+
+    fn set_clock(&mut self, clock: SharedClock) {
+        self.inner.set_clock(clock)
+    }
+
+    fn jobs(&self) -> Result<Vec<Job>, ApiError> {
+        self.inner.jobs()
+    }
+
+    fn job_infos(&self) -> Result<Vec<JobInfo>, ApiError> {
+        self.inner.job_infos()
+    }
+
+    fn jobs_page(&self, after: Option<&Cursor>, limit: usize) -> Result<JobPage, ApiError> {
+        self.inner.jobs_page(after, limit)
+    }
+}
+
+/// Executor running jobs inline on the calling task, without any queue thread, worker or
+/// timer. Enqueued jobs are only stored until explicitly run, the delayed ones once due
+/// according to the time of the executor, only moved forward by `advance`. It stands in for a
+/// `JobQueue` in the tests of the code enqueuing jobs, not for the queue itself: no backend,
+/// dispatch strategy, retry policy or interceptor is involved.
+pub struct MockExecutor<RoutineType, Context> {
+    /// Jobs enqueued and not run yet, in order.
+    enqueued: Mutex<Vec<Job>>,
+
+    /// Clock of the executor.
+    clock: ManualClock,
+
+    /// Progression reported by the jobs run.
+    progressions: Mutex<HashMap<Uuid, Progression>>,
+
+    /// Context passed to every routine.
+    context: Option<ContextMode<Context>>,
+
+    /// Type of routine handled.
+    routine: PhantomData<fn() -> RoutineType>,
+}
+
+impl<RoutineType, Context> Default for MockExecutor<RoutineType, Context> {
+    fn default() -> Self {
+        Self {
+            enqueued: Mutex::new(vec![]),
+            clock: ManualClock::default(),
+            progressions: Mutex::new(HashMap::new()),
+            context: None,
+            routine: PhantomData,
+        }
+    }
+}
+
+impl<RoutineType, Context> MockExecutor<RoutineType, Context>
+where
+    RoutineType: Routine<Context> + Sync + 'static,
+    Context: Send + 'static,
+{
+    /// Creates a new executor without context.
+    ///
+    /// # Returns
+    /// An instance of `MockExecutor`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new executor passing a context to every routine.
+    ///
+    /// # Arguments
+    /// * `context` - Context to be passed.
+    ///
+    /// # Returns
+    /// An instance of `MockExecutor`.
+    pub fn with_context(context: Context) -> Self {
+        Self {
+            context: Some(ContextMode::exclusive(context)),
+            ..Default::default()
+        }
+    }
+
+    /// Stores a job to be run later (by `run_all`).
+    ///
+    /// # Arguments
+    /// * `job` - Job to be stored.
+    ///
+    /// # Returns
+    /// The unique ID of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn enqueue(&self, job: Job) -> Result<Uuid, ApiError> {
+        let job_id = job.id();
+
+        self.enqueued
+            .lock()
+            .map_err(|e| api_err!(Error::Custom(e.to_string())))?
+            .push(job);
+
+        Ok(job_id)
+    }
+
+    /// Gets the jobs enqueued and not run yet.
+    ///
+    /// # Returns
+    /// The list of jobs, in order.
+    pub fn enqueued(&self) -> Vec<Job> {
+        self.enqueued
+            .lock()
+            .map(|jobs| jobs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Runs a job inline, as `InlineExecutor` does, and records the progression it reports.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be run.
+    ///
+    /// # Returns
+    /// The result of the routine.
+    ///
+    /// # Errors
+    /// One of `Error` enum, raised by the routine or when the job cannot be run (e.g. missing
+    /// private data).
+    pub async fn run(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        let (result, rx) = inline::run::<RoutineType, Context>(job, self.context.clone()).await;
+
+        // Apply the progression messages sent by the routine
+        let mut progression = Progression::default();
+
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                Message::Command(Cmd::SetSteps(id, steps)) if id == job.id() => {
+                    progression.steps = steps
+                }
+                Message::Command(Cmd::SetStep(id, step)) if id == job.id() => {
+                    progression.step = step
+                }
+                _ => (),
+            }
+        }
+
+        self.progressions
+            .lock()
+            .map_err(|e| api_err!(Error::Custom(e.to_string())))?
+            .insert(job.id(), progression);
+
+        result
+    }
+
+    /// Gets the clock of the executor, to be shared with the code under test.
+    ///
+    /// # Returns
+    /// The clock, sharing its time with the executor.
+    pub fn clock(&self) -> ManualClock {
+        self.clock.clone()
+    }
+
+    /// Gets the current time of the executor.
+    ///
+    /// # Returns
+    /// The time, frozen until moved forward by `advance`.
+    pub fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+
+    /// Moves the time of the executor forward, making the jobs delayed until then due.
+    ///
+    /// # Arguments
+    /// * `duration` - Duration elapsed.
+    pub fn advance(&self, duration: Duration) {
+        self.clock.advance(duration);
+    }
+
+    /// Runs the first enqueued job due, then calls its completion callback and enqueues its
+    /// follow-up job and next occurrence, if any.
+    ///
+    /// # Returns
+    /// The ID and result of the job run, `None` if no job is due.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn run_next(&self) -> Result<Option<(Uuid, Result<Vec<u8>, ApiError>)>, ApiError> {
+        let now = self.now();
+
+        let job = {
+            let mut enqueued = self
+                .enqueued
+                .lock()
+                .map_err(|e| api_err!(Error::Custom(e.to_string())))?;
+
+            match enqueued.iter().position(|job| job.is_due_at(now)) {
+                Some(idx) => enqueued.remove(idx),
+                None => return Ok(None),
+            }
+        };
+
+        let result = self.run(&job).await;
+
+        if let Some(follow_up) = job.complete(&job.outcome_of(&result))? {
+            self.enqueue(follow_up)?;
+        }
+
+        if let Some(next) = job.next_occurrence(now) {
+            self.enqueue(next)?;
+        }
+
+        Ok(Some((job.id(), result)))
+    }
+
+    /// Runs the enqueued jobs due, in order, until none is left (the jobs enqueued meanwhile
+    /// included).
+    ///
+    /// # Returns
+    /// The ID and result of every job run.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn run_until_idle(&self) -> Result<Vec<(Uuid, Result<Vec<u8>, ApiError>)>, ApiError> {
+        let mut results = vec![];
+
+        while let Some(result) = self.run_next().await? {
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
+    /// Runs every enqueued job inline, in order.
+    ///
+    /// # Returns
+    /// The ID and result of every job run.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub async fn run_all(&self) -> Result<Vec<(Uuid, Result<Vec<u8>, ApiError>)>, ApiError> {
+        let jobs: Vec<Job> = self
+            .enqueued
+            .lock()
+            .map_err(|e| api_err!(Error::Custom(e.to_string())))?
+            .drain(..)
+            .collect();
+
+        let mut results = Vec::with_capacity(jobs.len());
+
+        for job in jobs {
+            let result = self.run(&job).await;
+
+            results.push((job.id(), result));
+        }
+
+        Ok(results)
+    }
+
+    /// Gets the progression reported by a job run.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The last progression reported, `None` if the job has not been run.
+    pub fn progression(&self, id: &Uuid) -> Option<Progression> {
+        self.progressions.lock().ok()?.get(id).copied()
+    }
+}
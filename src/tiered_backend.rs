@@ -0,0 +1,511 @@
+//! Backend combining a persistent store with an in-memory cache of the status and progression
+//! of the jobs, so that polling them doesn't hit the store on every request. Writes go through
+//! to the store before updating the cache.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::prelude::*;
+
+/// Reads of the jobs falling back to the persistent store.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReadFallback {
+    /// Jobs missing from the cache read from the store, the cached ones never: the store is
+    /// only written through this backend.
+    #[default]
+    OnMiss,
+
+    /// Jobs missing from the cache, or cached for longer than a duration, read from the store:
+    /// the store is also written by other workers.
+    OnStale(Duration),
+}
+
+/// Status and progression of a job, as cached.
+#[derive(Clone, Copy, Debug)]
+struct CachedJob {
+    /// Status of the job.
+    status: Status,
+
+    /// Progression of the job.
+    progression: Progression,
+
+    /// Time at which the entry has been read from or written to the store.
+    refreshed: Instant,
+}
+
+impl CachedJob {
+    /// Creates an entry of the cache.
+    ///
+    /// # Arguments
+    /// * `status` - Status of the job.
+    /// * `progression` - Progression of the job.
+    ///
+    /// # Returns
+    /// An instance of `CachedJob`.
+    fn new(status: Status, progression: Progression) -> Self {
+        Self {
+            status,
+            progression,
+            refreshed: Instant::now(),
+        }
+    }
+
+    /// Updates the entry with a value written to the store.
+    ///
+    /// # Arguments
+    /// * `update` - Update of the entry.
+    fn refresh(&mut self, update: impl FnOnce(&mut Self)) {
+        update(self);
+
+        self.refreshed = Instant::now();
+    }
+}
+
+impl From<&Job> for CachedJob {
+    fn from(job: &Job) -> Self {
+        Self::new(job.status(), job.progression())
+    }
+}
+
+/// Backend caching the status and progression of the jobs of a persistent store in memory.
+pub struct TieredBackend<B> {
+    /// Persistent store.
+    store: B,
+
+    /// Status and progression of the jobs, by ID.
+    cache: Mutex<HashMap<Uuid, CachedJob>>,
+
+    /// Reads falling back to the store.
+    fallback: ReadFallback,
+}
+
+impl<B> TieredBackend<B> {
+    /// Creates a tiered backend over a persistent store.
+    ///
+    /// # Arguments
+    /// * `store` - Persistent store.
+    ///
+    /// # Returns
+    /// An instance of `TieredBackend`.
+    pub fn new(store: B) -> Self {
+        Self {
+            store,
+            cache: Mutex::new(HashMap::new()),
+            fallback: ReadFallback::default(),
+        }
+    }
+
+    /// Sets the reads falling back to the store, `ReadFallback::OnMiss` by default.
+    ///
+    /// # Arguments
+    /// * `fallback` - Reads falling back to the store.
+    ///
+    /// # Returns
+    /// An instance of `TieredBackend`.
+    pub fn fallback(self, fallback: ReadFallback) -> Self {
+        Self { fallback, ..self }
+    }
+
+    /// Gets the entries of the cache.
+    ///
+    /// # Returns
+    /// The entries, locked.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn entries(&self) -> Result<MutexGuard<'_, HashMap<Uuid, CachedJob>>, ApiError> {
+        self.cache
+            .lock()
+            .map_err(|e| api_err!(Error::CannotAccessBackend(e.to_string())))
+    }
+
+    /// Gets the entry of a job, unless missing or stale.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The entry of the job, `None` if to be read from the store.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn fresh(&self, id: &Uuid) -> Result<Option<CachedJob>, ApiError> {
+        let cached = self.entries()?.get(id).copied();
+
+        Ok(cached.filter(|cached| match self.fallback {
+            ReadFallback::OnMiss => true,
+            ReadFallback::OnStale(max_age) => cached.refreshed.elapsed() < max_age,
+        }))
+    }
+
+    /// Caches the status and progression of a job read from the store.
+    ///
+    /// # Arguments
+    /// * `job` - Job read.
+    fn cache(&self, job: &Job) {
+        if let Ok(mut entries) = self.entries() {
+            entries.insert(job.id(), CachedJob::from(job));
+        }
+    }
+
+    /// Updates the progression of a cached job written to the store.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    /// * `progression` - Progression written.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn set_progression(&self, id: &Uuid, progression: Progression) -> Result<(), ApiError> {
+        if let Some(cached) = self.entries()?.get_mut(id) {
+            cached.refresh(|cached| cached.progression = progression);
+        }
+
+        Ok(())
+    }
+
+    /// Evicts jobs changed in the store behind the cache, or removed.
+    ///
+    /// # Arguments
+    /// * `ids` - IDs of the jobs.
+    fn evict(&self, ids: &[Uuid]) {
+        if let Ok(mut entries) = self.entries() {
+            for id in ids {
+                entries.remove(id);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<B, RoutineType, Context> Backend<RoutineType, Context> for TieredBackend<B>
+where
+    B: Backend<RoutineType, Context>,
+    RoutineType: Routine<Context> + Sync,
+    for<'async_trait> Context: Send + 'async_trait,
+{
+    async fn get(&mut self, id: &Uuid) -> Result<Job, ApiError> {
+        let job = self.store.get(id).await?;
+
+        self.cache(&job);
+
+        Ok(job)
+    }
+
+    fn schedule(&mut self, job: Job) -> Result<(), ApiError> {
+        let cached = CachedJob::from(&job);
+        let id = job.id();
+
+        self.store.schedule(job)?;
+        self.entries()?.insert(id, cached);
+
+        Ok(())
+    }
+
+    async fn run(
+        &mut self,
+        id: &Uuid,
+        context: Option<ContextMode<Context>>,
+        messages_channel: SharedMessageChannel,
+    ) -> Result<(), ApiError> {
+        let run = self.store.run(id, context, messages_channel).await;
+
+        self.evict(&[id.to_owned()]);
+
+        run
+    }
+
+    fn status(&self, id: &Uuid) -> Result<Status, ApiError> {
+        if let Some(cached) = self.fresh(id)? {
+            return Ok(cached.status);
+        }
+
+        let status = self.store.status(id)?;
+        let progression = self.store.progression(id)?;
+
+        self.entries()?
+            .insert(id.to_owned(), CachedJob::new(status, progression));
+
+        Ok(status)
+    }
+
+    fn statuses(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, Status>, ApiError> {
+        let mut statuses = HashMap::with_capacity(ids.len());
+        let mut missed = vec![];
+
+        for id in ids {
+            match self.fresh(id)? {
+                Some(cached) => {
+                    statuses.insert(id.to_owned(), cached.status);
+                }
+                None => missed.push(id.to_owned()),
+            }
+        }
+
+        // Entries missing or stale read from the store in a single call, not cached without
+        // their progression
+        if !missed.is_empty() {
+            statuses.extend(self.store.statuses(&missed)?);
+        }
+
+        Ok(statuses)
+    }
+
+    fn set_status(&mut self, id: &Uuid, status: Status) -> Result<(), ApiError> {
+        self.store.set_status(id, status)?;
+
+        if let Some(cached) = self.entries()?.get_mut(id) {
+            cached.refresh(|cached| cached.status = status);
+        }
+
+        Ok(())
+    }
+
+    fn result(&self, id: &Uuid) -> Result<&[u8], ApiError> {
+        self.store.result(id)
+    }
+
+    fn set_result(&mut self, id: &Uuid, result: Vec<u8>) -> Result<(), ApiError> {
+        self.store.set_result(id, result)
+    }
+
+    fn set_result_ref(&mut self, id: &Uuid, key: String) -> Result<(), ApiError> {
+        self.store.set_result_ref(id, key)
+    }
+
+    fn add_attempt(&mut self, id: &Uuid, attempt: Attempt) -> Result<(), ApiError> {
+        self.store.add_attempt(id, attempt)
+    }
+
+    fn set_checkpoint(&mut self, id: &Uuid, checkpoint: Vec<u8>) -> Result<(), ApiError> {
+        self.store.set_checkpoint(id, checkpoint)
+    }
+
+    fn set_metadata(
+        &mut self,
+        id: &Uuid,
+        key: &str,
+        value: serde_json::Value,
+    ) -> Result<(), ApiError> {
+        self.store.set_metadata(id, key, value)
+    }
+
+    fn append_chunk(&mut self, id: &Uuid, chunk: Vec<u8>) -> Result<(), ApiError> {
+        self.store.append_chunk(id, chunk)
+    }
+
+    fn chunks(&self, id: &Uuid, from: usize) -> Result<Vec<Vec<u8>>, ApiError> {
+        self.store.chunks(id, from)
+    }
+
+    fn set_steps(&mut self, id: &Uuid, steps: u64) -> Result<Progression, ApiError> {
+        let progression = self.store.set_steps(id, steps)?;
+
+        self.set_progression(id, progression)?;
+
+        Ok(progression)
+    }
+
+    fn set_step(&mut self, id: &Uuid, step: u64) -> Result<Progression, ApiError> {
+        let progression = self.store.set_step(id, step)?;
+
+        self.set_progression(id, progression)?;
+
+        Ok(progression)
+    }
+
+    fn progression(&self, id: &Uuid) -> Result<Progression, ApiError> {
+        if let Some(cached) = self.fresh(id)? {
+            return Ok(cached.progression);
+        }
+
+        let status = self.store.status(id)?;
+        let progression = self.store.progression(id)?;
+
+        self.entries()?
+            .insert(id.to_owned(), CachedJob::new(status, progression));
+
+        Ok(progression)
+    }
+
+    fn routine(&self, id: &Uuid) -> Result<RoutineType, ApiError> {
+        self.store.routine(id)
+    }
+
+    fn expire_policy(&self, id: &Uuid) -> Result<ExpirePolicy, ApiError> {
+        self.store.expire_policy(id)
+    }
+
+    fn set_expiration(&mut self, id: &Uuid, expired: SystemTime) -> Result<(), ApiError> {
+        self.store.set_expiration(id, expired)
+    }
+
+    fn remove(&mut self, id: &Uuid) -> Result<(), ApiError> {
+        self.store.remove(id)?;
+        self.evict(&[id.to_owned()]);
+
+        Ok(())
+    }
+
+    fn remove_expired(&mut self) -> Result<Vec<Uuid>, ApiError> {
+        let removed = self.store.remove_expired()?;
+
+        self.evict(&removed);
+
+        Ok(removed)
+    }
+
+    fn expire_results(&mut self) -> Result<Vec<(Uuid, Option<String>)>, ApiError> {
+        let expired = self.store.expire_results()?;
+
+        // Cached jobs holding the results dropped
+        self.evict(&expired.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+
+        Ok(expired)
+    }
+
+    fn retain_last(&mut self, count: usize) -> Result<Vec<Uuid>, ApiError> {
+        let removed = self.store.retain_last(count)?;
+
+        self.evict(&removed);
+
+        Ok(removed)
+    }
+
+    fn purge(&mut self, filter: &PurgeFilter) -> Result<Vec<Uuid>, ApiError> {
+        let removed = self.store.purge(filter)?;
+
+        self.evict(&removed);
+
+        Ok(removed)
+    }
+
+    fn acquire_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.store.acquire_lock(key, owner, ttl)
+    }
+
+    fn refresh_lock(&mut self, key: &str, owner: &str, ttl: Duration) -> Result<bool, ApiError> {
+        self.store.refresh_lock(key, owner, ttl)
+    }
+
+    fn release_lock(&mut self, key: &str, owner: &str) -> Result<bool, ApiError> {
+        self.store.release_lock(key, owner)
+    }
+
+    fn release_stale_locks(&mut self) -> Result<Vec<Lock>, ApiError> {
+        self.store.release_stale_locks()
+    }
+
+    fn claim(
+        &mut self,
+        worker_id: &str,
+        lease: Duration,
+        kinds: &KindFilter,
+        capabilities: &Capabilities,
+    ) -> Result<Option<Job>, ApiError> {
+        let claimed = self.store.claim(worker_id, lease, kinds, capabilities)?;
+
+        if let Some(job) = &claimed {
+            self.cache(job);
+        }
+
+        Ok(claimed)
+    }
+
+    fn extend_lease(
+        &mut self,
+        id: &Uuid,
+        worker_id: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError> {
+        self.store.extend_lease(id, worker_id, lease)
+    }
+
+    fn release(&mut self, id: &Uuid, worker_id: &str) -> Result<bool, ApiError> {
+        let released = self.store.release(id, worker_id)?;
+
+        self.evict(&[id.to_owned()]);
+
+        Ok(released)
+    }
+
+    fn reap_orphans(
+        &mut self,
+        timeout: Duration,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, ApiError> {
+        let reaped = self.store.reap_orphans(timeout, policy)?;
+
+        self.evict(&reaped);
+
+        Ok(reaped)
+    }
+
+    fn timeline(
+        &self,
+        range: &Range<SystemTime>,
+        filter: &TimelineFilter,
+    ) -> Result<Vec<JobInterval>, ApiError> {
+        self.store.timeline(range, filter)
+    }
+
+    fn set_paused(&mut self, paused: bool) -> Result<(), ApiError> {
+        self.store.set_paused(paused)
+    }
+
+    fn is_paused(&self) -> Result<bool, ApiError> {
+        self.store.is_paused()
+    }
+
+    fn record_duration(&mut self, kind: &str, duration: Duration) -> Result<(), ApiError> {
+        self.store.record_duration(kind, duration)
+    }
+
+    fn duration_stats(&self, kind: &str) -> Result<Option<DurationStats>, ApiError> {
+        self.store.duration_stats(kind)
+    }
+
+    fn set_namespace(&mut self, namespace: &str) {
+        // Jobs of the previous namespace no longer visible
+        if let Ok(mut entries) = self.entries() {
+            entries.clear();
+        }
+
+        self.store.set_namespace(namespace)
+    }
+
+    fn set_migrator(&mut self, migrator: SharedMigrator) {
+        self.store.set_migrator(migrator)
+    }
+
+    fn set_codec(&mut self, codec: SharedCodec) {
+        self.store.set_codec(codec)
+    }
+
+    fn set_compression(&mut self, compression: Compression) {
+        self.store.set_compression(compression)
+    }
+
+    fn set_encryption(&mut self, encryption: Encryption) {
+        self.store.set_encryption(encryption)
+    }
+
+    fn set_clock(&mut self, clock: SharedClock) {
+        self.store.set_clock(clock)
+    }
+
+    fn jobs(&self) -> Result<Vec<Job>, ApiError> {
+        self.store.jobs()
+    }
+
+    fn job_infos(&self) -> Result<Vec<JobInfo>, ApiError> {
+        self.store.job_infos()
+    }
+
+    fn jobs_page(&self, after: Option<&Cursor>, limit: usize) -> Result<JobPage, ApiError> {
+        self.store.jobs_page(after, limit)
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Filter used to select the jobs of a timeline. A job is selected if it matches all of the
+/// criteria set.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct TimelineFilter {
+    /// Only select the jobs of this kind.
+    pub kind: Option<String>,
+
+    /// Only select the jobs claimed by this worker.
+    pub worker: Option<String>,
+
+    /// Only select the jobs holding this tag.
+    pub tag: Option<String>,
+}
+
+impl TimelineFilter {
+    /// Creates a filter selecting every job.
+    ///
+    /// # Returns
+    /// An instance of `TimelineFilter`.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Restricts the filter to the jobs of a kind.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the jobs.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn kind(self, kind: impl Into<String>) -> Self {
+        Self {
+            kind: Some(kind.into()),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the jobs claimed by a worker.
+    ///
+    /// # Arguments
+    /// * `worker_id` - Identifier of the worker.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn worker(self, worker_id: impl Into<String>) -> Self {
+        Self {
+            worker: Some(worker_id.into()),
+            ..self
+        }
+    }
+
+    /// Restricts the filter to the jobs holding a tag.
+    ///
+    /// # Arguments
+    /// * `tag` - Tag the jobs must hold.
+    ///
+    /// # Returns
+    /// The updated filter.
+    pub fn tag(self, tag: impl Into<String>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            ..self
+        }
+    }
+
+    /// Checks if a job is selected by the filter.
+    ///
+    /// # Arguments
+    /// * `job` - Job to be checked.
+    ///
+    /// # Returns
+    /// `true` if selected, `false` otherwise.
+    pub fn matches(&self, job: &Job) -> bool {
+        self.kind.as_deref().map_or(true, |kind| job.kind() == kind)
+            && self
+                .worker
+                .as_deref()
+                .map_or(true, |worker| job.worker() == Some(worker))
+            && self.tag.as_ref().map_or(true, |tag| job.has_tag(tag))
+    }
+}
+
+/// Intervals of a job on a timeline: queued from `enqueued` to `started`, then running from
+/// `started` to `finished`. An interval not over yet is left open (`None`).
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct JobInterval {
+    /// ID of the job.
+    pub id: Uuid,
+
+    /// Kind of the job.
+    pub kind: String,
+
+    /// Worker that claimed the job, if any.
+    pub worker: Option<String>,
+
+    /// Timestamp at which the job has been enqueued.
+    pub enqueued: SystemTime,
+
+    /// Timestamp at which the job has started.
+    pub started: Option<SystemTime>,
+
+    /// Timestamp at which the job has finished.
+    pub finished: Option<SystemTime>,
+}
+
+impl JobInterval {
+    /// Gets the time spent in the queue.
+    ///
+    /// # Returns
+    /// The duration until the job started (or until now if not started yet).
+    pub fn queued(&self) -> Duration {
+        self.started
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(self.enqueued)
+            .unwrap_or_default()
+    }
+
+    /// Gets the time spent running.
+    ///
+    /// # Returns
+    /// The duration until the job finished (or until now if still running), `None` if the job
+    /// is not started yet.
+    pub fn running(&self) -> Option<Duration> {
+        let started = self.started?;
+
+        Some(
+            self.finished
+                .unwrap_or_else(SystemTime::now)
+                .duration_since(started)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Checks if the job was queued or running during a time range.
+    ///
+    /// # Arguments
+    /// * `range` - Time range.
+    ///
+    /// # Returns
+    /// `true` if the intervals overlap the range, `false` otherwise.
+    pub fn overlaps(&self, range: &Range<SystemTime>) -> bool {
+        self.enqueued < range.end && self.finished.map_or(true, |end| end >= range.start)
+    }
+}
+
+impl From<&Job> for JobInterval {
+    fn from(job: &Job) -> Self {
+        let timestamps = job.timestamps();
+        let status = job.status();
+
+        Self {
+            id: job.id(),
+            kind: job.kind().to_owned(),
+            worker: job.worker().map(ToOwned::to_owned),
+            enqueued: timestamps.enqueued,
+            // A job cancelled before running has never started
+            started: (matches!(
+                status,
+                Status::Running | Status::Suspended | Status::Retrying | Status::Finished(_)
+            ) && timestamps.started != SystemTime::UNIX_EPOCH)
+                .then_some(timestamps.started),
+            finished: matches!(status, Status::Finished(_)).then_some(timestamps.finished),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::prelude::*;
+
+/// Job removed while soft-delete is enabled, kept to be restored.
+#[derive(Clone)]
+struct Tombstone {
+    /// Job as it was when removed.
+    job: Job,
+
+    /// Timestamp of the removal.
+    deleted: SystemTime,
+}
+
+/// Jobs removed from the backend, kept for a retention window during which they can be
+/// restored (soft-delete).
+#[derive(Default)]
+pub(crate) struct Tombstones {
+    /// Duration during which a removed job is kept (soft-delete disabled if `None`).
+    retention: Option<Duration>,
+
+    /// Removed jobs by ID.
+    tombstones: HashMap<Uuid, Tombstone>,
+}
+
+impl Tombstones {
+    /// Sets the duration during which a removed job is kept.
+    ///
+    /// # Arguments
+    /// * `retention` - Duration to set.
+    pub(crate) fn set_retention(&mut self, retention: Duration) {
+        self.retention = Some(retention);
+    }
+
+    /// Checks if the removed jobs are kept.
+    ///
+    /// # Returns
+    /// `true` if soft-delete is enabled, `false` otherwise.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.retention.is_some()
+    }
+
+    /// Keeps a removed job, if soft-delete is enabled.
+    ///
+    /// # Arguments
+    /// * `job` - Job removed.
+    pub(crate) fn bury(&mut self, job: Job) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.prune();
+
+        self.tombstones.insert(
+            job.id(),
+            Tombstone {
+                job,
+                deleted: SystemTime::now(),
+            },
+        );
+    }
+
+    /// Gets the removed jobs still in the retention window.
+    ///
+    /// # Returns
+    /// The removed jobs, the most recently removed first.
+    pub(crate) fn jobs(&mut self) -> Vec<Job> {
+        self.prune();
+
+        let mut tombstones: Vec<&Tombstone> = self.tombstones.values().collect();
+
+        tombstones.sort_by(|a, b| b.deleted.cmp(&a.deleted));
+
+        tombstones
+            .into_iter()
+            .map(|tombstone| tombstone.job.clone())
+            .collect()
+    }
+
+    /// Takes a removed job back, to be restored.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job.
+    ///
+    /// # Returns
+    /// The job, `None` if not removed or out of the retention window.
+    pub(crate) fn exhume(&mut self, id: &Uuid) -> Option<Job> {
+        self.prune();
+
+        self.tombstones.remove(id).map(|tombstone| tombstone.job)
+    }
+
+    /// Forgets the jobs removed out of the retention window.
+    fn prune(&mut self) {
+        let Some(retention) = self.retention else {
+            return;
+        };
+
+        self.tombstones.retain(|_, tombstone| {
+            SystemTime::now()
+                .duration_since(tombstone.deleted)
+                .map_or(true, |elapsed| elapsed < retention)
+        });
+    }
+}
@@ -0,0 +1,246 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Type used to share a trace propagator across threads.
+pub type SharedTracePropagator = Arc<dyn TracePropagator>;
+
+/// Trace context of the span enqueuing a job, in the W3C Trace Context format, so that the
+/// span running the job joins the same distributed trace.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TraceContext {
+    /// `traceparent` header (`{version}-{trace-id}-{parent-id}-{trace-flags}`).
+    traceparent: String,
+
+    /// `tracestate` header carrying vendor specific data, if any.
+    #[serde(default)]
+    tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Creates a trace context from a `traceparent` header.
+    ///
+    /// # Arguments
+    /// * `traceparent` - Value of the header.
+    ///
+    /// # Returns
+    /// An instance of `TraceContext`.
+    ///
+    /// # Errors
+    /// `Error::InvalidTraceContext` if the header is malformed.
+    pub fn new(traceparent: impl Into<String>) -> Result<Self, ApiError> {
+        let traceparent = traceparent.into();
+
+        let lengths = traceparent.split('-').map(str::len).collect::<Vec<_>>();
+        let valid = lengths.len() == 4
+            && lengths[..] == [2, 32, 16, 2]
+            && traceparent
+                .chars()
+                .all(|c| c == '-' || c.is_ascii_digit() || ('a'..='f').contains(&c));
+
+        if !valid {
+            return Err(api_err!(Error::InvalidTraceContext(traceparent)));
+        }
+
+        Ok(Self {
+            traceparent,
+            tracestate: None,
+        })
+    }
+
+    /// Sets the `tracestate` header.
+    ///
+    /// # Arguments
+    /// * `tracestate` - Value of the header.
+    ///
+    /// # Returns
+    /// The updated context.
+    pub fn with_state(self, tracestate: impl Into<String>) -> Self {
+        Self {
+            tracestate: Some(tracestate.into()),
+            ..self
+        }
+    }
+
+    /// Gets the `traceparent` header.
+    ///
+    /// # Returns
+    /// The value of the header.
+    pub fn traceparent(&self) -> &str {
+        &self.traceparent
+    }
+
+    /// Gets the `tracestate` header.
+    ///
+    /// # Returns
+    /// The value of the header, `None` if not set.
+    pub fn tracestate(&self) -> Option<&str> {
+        self.tracestate.as_deref()
+    }
+
+    /// Gets the ID of the trace.
+    ///
+    /// # Returns
+    /// The trace ID (32 hexadecimal digits).
+    pub fn trace_id(&self) -> &str {
+        &self.traceparent[3..35]
+    }
+
+    /// Gets the ID of the span enqueuing the job, parent of the span running it.
+    ///
+    /// # Returns
+    /// The span ID (16 hexadecimal digits).
+    pub fn parent_id(&self) -> &str {
+        &self.traceparent[36..52]
+    }
+
+    /// Checks if the trace is sampled.
+    ///
+    /// # Returns
+    /// `true` if sampled, `false` otherwise.
+    pub fn is_sampled(&self) -> bool {
+        u8::from_str_radix(&self.traceparent[53..55], 16).is_ok_and(|flags| flags & 1 == 1)
+    }
+}
+
+/// Trait implemented by the bridges to a tracing stack (`tracing`, OpenTelemetry...),
+/// propagating the trace context from the span enqueuing a job to the span running it (see
+/// `OtelPropagator`, behind the `otel` feature).
+pub trait TracePropagator: Send + Sync {
+    /// Gets the trace context of the current span, attached to the jobs enqueued without one.
+    ///
+    /// # Returns
+    /// The trace context, `None` outside of a span.
+    fn current(&self) -> Option<TraceContext>;
+
+    /// Runs a job within a span whose parent is restored from the trace context of the job.
+    ///
+    /// # Arguments
+    /// * `job` - Job run.
+    /// * `context` - Trace context attached to the job.
+    /// * `run` - Future running the job (rest of the middleware chain and routine).
+    ///
+    /// # Returns
+    /// The future instrumented.
+    fn instrument<'a>(
+        &self,
+        job: &Job,
+        context: &TraceContext,
+        run: RoutineFuture<'a>,
+    ) -> RoutineFuture<'a>;
+}
+
+/// Middleware running the jobs holding a trace context within the span restored by a
+/// propagator.
+pub(crate) struct TraceMiddleware(pub(crate) SharedTracePropagator);
+
+#[async_trait]
+impl Middleware for TraceMiddleware {
+    async fn handle(&self, job: &Job, next: Next<'_>) -> Result<Vec<u8>, ApiError> {
+        match job.trace_context() {
+            Some(context) => {
+                self.0
+                    .instrument(job, context, Box::pin(next.run(job)))
+                    .await
+            }
+            None => next.run(job).await,
+        }
+    }
+}
+
+/// Propagator bridging to OpenTelemetry: the jobs get the trace context of the current
+/// `tracing` span (exported by a `tracing_opentelemetry` layer), or else of the current
+/// OpenTelemetry context, and are run within a `job` span whose parent is restored from it.
+#[cfg(feature = "otel")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OtelPropagator;
+
+#[cfg(feature = "otel")]
+impl OtelPropagator {
+    /// Restores the OpenTelemetry context of the span that enqueued a job.
+    ///
+    /// # Arguments
+    /// * `context` - Trace context attached to the job.
+    ///
+    /// # Returns
+    /// The OpenTelemetry context, `None` if the trace context can't be converted.
+    fn parent(context: &TraceContext) -> Option<opentelemetry::Context> {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+
+        let state = match context.tracestate() {
+            Some(state) => state.parse::<TraceState>().ok()?,
+            None => TraceState::default(),
+        };
+        let span_context = SpanContext::new(
+            TraceId::from_hex(context.trace_id()).ok()?,
+            SpanId::from_hex(context.parent_id()).ok()?,
+            match context.is_sampled() {
+                true => TraceFlags::SAMPLED,
+                false => TraceFlags::default(),
+            },
+            true,
+            state,
+        );
+
+        Some(opentelemetry::Context::new().with_remote_span_context(span_context))
+    }
+}
+
+#[cfg(feature = "otel")]
+impl TracePropagator for OtelPropagator {
+    fn current(&self) -> Option<TraceContext> {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let mut context = tracing::Span::current().context();
+
+        // Current span not exported by `tracing`, if any
+        if !context.has_active_span() {
+            context = opentelemetry::Context::current();
+        }
+
+        let span = context.span();
+        let span_context = span.span_context();
+
+        if !span_context.is_valid() {
+            return None;
+        }
+
+        let traceparent = format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        );
+        let context = TraceContext::new(traceparent).ok()?;
+
+        Some(match span_context.trace_state().header() {
+            state if state.is_empty() => context,
+            state => context.with_state(state),
+        })
+    }
+
+    fn instrument<'a>(
+        &self,
+        job: &Job,
+        context: &TraceContext,
+        run: RoutineFuture<'a>,
+    ) -> RoutineFuture<'a> {
+        use opentelemetry::trace::FutureExt;
+        use tracing::Instrument;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let Some(parent) = Self::parent(context) else {
+            return run;
+        };
+
+        let span = tracing::info_span!("job", id = %job.id(), kind = job.kind());
+        span.set_parent(parent.clone());
+
+        // Parent also current for the spans started through OpenTelemetry directly
+        Box::pin(run.with_context(parent).instrument(span))
+    }
+}
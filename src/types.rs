@@ -1,8 +1,16 @@
-use std::sync::mpsc::Sender;
+//! Shared types, and the canonical representations of time exchanged with other systems
+//! (custom backends, HTTP APIs, producers written in other languages):
+//!
+//! * durations are integers of milliseconds (`Millis`, `duration_ms`),
+//! * instants are integers of milliseconds since the Unix epoch (`Timestamp`, `timestamp_ms`).
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use tokio::runtime::Runtime;
 
-use crate::job_queue::{Message, Notification};
+use crate::channel::MessageSender;
+use crate::job_queue::Notification;
 
 /// Type used to share some instance across threads.
 pub type Shared<T> = Arc<Mutex<T>>;
@@ -11,7 +19,133 @@ pub type Shared<T> = Arc<Mutex<T>>;
 pub type SharedNotificationHandler = Arc<dyn Fn(Notification) + Send + Sync>;
 
 /// Type used to share the message channel.
-pub type SharedMessageChannel = Arc<Mutex<Sender<Message>>>;
+pub type SharedMessageChannel = Arc<Mutex<MessageSender>>;
 
 /// Type used to share the runtime instance across threads.
 pub type SharedRuntime = Arc<Mutex<Runtime>>;
+
+/// Duration serialized as an integer of milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Millis(pub Duration);
+
+impl From<Duration> for Millis {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<Millis> for Duration {
+    fn from(millis: Millis) -> Self {
+        millis.0
+    }
+}
+
+impl Serialize for Millis {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        duration_ms::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Millis {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        duration_ms::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Instant serialized as an integer of milliseconds since the Unix epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(pub SystemTime);
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Self(time)
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.0
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        timestamp_ms::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        timestamp_ms::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Serializes a `Duration` field as an integer of milliseconds, with
+/// `#[serde(with = "cs_jobs_v3::types::duration_ms")]`.
+pub mod duration_ms {
+    use super::*;
+
+    /// Serializes a duration.
+    ///
+    /// # Arguments
+    /// * `duration` - Duration to be serialized.
+    /// * `serializer` - Serializer used.
+    ///
+    /// # Errors
+    /// The error of the serializer, if the duration overflows an integer of milliseconds.
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        let millis = u64::try_from(duration.as_millis()).map_err(serde::ser::Error::custom)?;
+
+        serializer.serialize_u64(millis)
+    }
+
+    /// Deserializes a duration.
+    ///
+    /// # Arguments
+    /// * `deserializer` - Deserializer used.
+    ///
+    /// # Returns
+    /// The duration.
+    ///
+    /// # Errors
+    /// The error of the deserializer.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// Serializes a `SystemTime` field as an integer of milliseconds since the Unix epoch, with
+/// `#[serde(with = "cs_jobs_v3::types::timestamp_ms")]`.
+pub mod timestamp_ms {
+    use super::*;
+
+    /// Serializes an instant.
+    ///
+    /// # Arguments
+    /// * `time` - Instant to be serialized.
+    /// * `serializer` - Serializer used.
+    ///
+    /// # Errors
+    /// The error of the serializer, if the instant is before the Unix epoch.
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let elapsed = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(serde::ser::Error::custom)?;
+
+        duration_ms::serialize(&elapsed, serializer)
+    }
+
+    /// Deserializes an instant.
+    ///
+    /// # Arguments
+    /// * `deserializer` - Deserializer used.
+    ///
+    /// # Returns
+    /// The instant.
+    ///
+    /// # Errors
+    /// The error of the deserializer.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        duration_ms::deserialize(deserializer).map(|elapsed| SystemTime::UNIX_EPOCH + elapsed)
+    }
+}
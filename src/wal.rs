@@ -0,0 +1,389 @@
+//! Write-ahead log persisting the jobs of the memory backend (see `MemoryBackend::wal`).
+//!
+//! Every change of a job is appended to the log as a record of the whole job, encoded like the
+//! records of the other backends (`Job::encode_with`). The log is replayed when opened by the
+//! backend, and compacted to one record per job stored once enough records are appended. A
+//! record torn by a crash at the end of the log is ignored on replay.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::prelude::*;
+
+/// Tag of the records of a job stored or changed.
+const PUT: u8 = 0;
+
+/// Tag of the records of a job removed.
+const REMOVE: u8 = 1;
+
+/// Tag of the records of a namespace paused or resumed.
+const PAUSED: u8 = 2;
+
+/// Size of the header of a record: its tag and the length of its payload.
+const HEADER_SIZE: usize = 5;
+
+/// Append-only log of the changes of the jobs stored in memory.
+pub struct Wal {
+    /// Path of the log.
+    path: PathBuf,
+
+    /// Log opened for appending.
+    file: File,
+
+    /// Codec of the jobs recorded.
+    codec: SharedCodec,
+
+    /// Compression of the jobs recorded.
+    compression: Compression,
+
+    /// Encryption of the jobs recorded.
+    encryption: Encryption,
+
+    /// Number of records appended before the log is compacted (never compacted if 0).
+    compact_every: usize,
+
+    /// Number of records appended since the last compaction.
+    appended: usize,
+
+    /// Whether the records have been encoded with a previous policy, the log being then
+    /// rewritten on the next change.
+    stale: bool,
+}
+
+impl Wal {
+    /// Opens a log, created if missing.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the log.
+    ///
+    /// # Returns
+    /// An instance of `Wal`.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, ApiError> {
+        let path = path.into();
+        let file = Self::append_to(&path)?;
+
+        Ok(Self {
+            path,
+            file,
+            codec: Arc::new(JsonCodec),
+            compression: Compression::default(),
+            encryption: Encryption::default(),
+            compact_every: 1000,
+            appended: 0,
+            stale: false,
+        })
+    }
+
+    /// Sets the codec of the jobs recorded, JSON by default.
+    ///
+    /// # Arguments
+    /// * `codec` - Codec of the jobs.
+    ///
+    /// # Returns
+    /// An instance of `Wal`.
+    pub fn codec(self, codec: impl Codec + 'static) -> Self {
+        Self {
+            codec: Arc::new(codec),
+            ..self
+        }
+    }
+
+    /// Sets the compression of the jobs recorded, disabled by default. Replaced by the policy
+    /// of the queue, if set (see `JobQueue::set_compression`): the log must be opened with the
+    /// readers of that policy to be replayed.
+    ///
+    /// # Arguments
+    /// * `compression` - Compression policy.
+    ///
+    /// # Returns
+    /// An instance of `Wal`.
+    pub fn compression(self, compression: Compression) -> Self {
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    /// Sets the encryption of the jobs recorded, disabled by default. Replaced by the policy
+    /// of the queue, if set (see `JobQueue::set_encryption`): the log must be opened with the
+    /// keys of that policy to be replayed.
+    ///
+    /// # Arguments
+    /// * `encryption` - Encryption policy.
+    ///
+    /// # Returns
+    /// An instance of `Wal`.
+    pub fn encryption(self, encryption: Encryption) -> Self {
+        Self { encryption, ..self }
+    }
+
+    /// Sets the number of records appended before the log is compacted, 1000 by default.
+    ///
+    /// # Arguments
+    /// * `records` - Number of records, the log never being compacted if 0.
+    ///
+    /// # Returns
+    /// An instance of `Wal`.
+    pub fn compact_every(self, records: usize) -> Self {
+        Self {
+            compact_every: records,
+            ..self
+        }
+    }
+
+    /// Replays the log.
+    ///
+    /// # Returns
+    /// The jobs stored, and the namespaces paused.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn replay(&self) -> Result<(BTreeMap<Uuid, Job>, BTreeSet<String>), ApiError> {
+        let bytes = std::fs::read(&self.path).map_err(|e| api_err!(e.into()))?;
+        let mut jobs = BTreeMap::new();
+        let mut paused = BTreeSet::new();
+        let mut offset = 0;
+
+        while offset + HEADER_SIZE <= bytes.len() {
+            let tag = bytes[offset];
+            let size = u32::from_le_bytes([
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+                bytes[offset + 4],
+            ]) as usize;
+
+            let Some(payload) = bytes.get(offset + HEADER_SIZE..offset + HEADER_SIZE + size) else {
+                // Torn by a crash
+                break;
+            };
+
+            match tag {
+                PUT => {
+                    let job = Job::decode_with(
+                        payload,
+                        self.codec.as_ref(),
+                        &self.compression,
+                        &self.encryption,
+                    )?;
+
+                    jobs.insert(job.id(), job);
+                }
+                REMOVE => {
+                    let id = Uuid::from_slice(payload)
+                        .map_err(|e| api_err!(Error::Custom(e.to_string())))?;
+
+                    jobs.remove(&id);
+                }
+                PAUSED if !payload.is_empty() => {
+                    let namespace = String::from_utf8_lossy(&payload[1..]).into_owned();
+
+                    if payload[0] == 1 {
+                        paused.insert(namespace);
+                    } else {
+                        paused.remove(&namespace);
+                    }
+                }
+                _ => {
+                    return Err(api_err!(Error::Custom(format!(
+                        "Unknown write-ahead log record {tag}"
+                    ))))
+                }
+            }
+
+            offset += HEADER_SIZE + size;
+        }
+
+        Ok((jobs, paused))
+    }
+
+    /// Records a job stored or changed.
+    ///
+    /// # Arguments
+    /// * `job` - Job stored.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn put(&mut self, job: &Job) -> Result<(), ApiError> {
+        let payload = self.encode(job)?;
+
+        self.append(PUT, &payload)
+    }
+
+    /// Records a job removed.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the job removed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn remove(&mut self, id: &Uuid) -> Result<(), ApiError> {
+        self.append(REMOVE, id.as_bytes())
+    }
+
+    /// Records a namespace paused or resumed.
+    ///
+    /// # Arguments
+    /// * `namespace` - Namespace.
+    /// * `paused` - `true` if paused, `false` if resumed.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn paused(&mut self, namespace: &str, paused: bool) -> Result<(), ApiError> {
+        let mut payload = vec![u8::from(paused)];
+
+        payload.extend_from_slice(namespace.as_bytes());
+
+        self.append(PAUSED, &payload)
+    }
+
+    /// Checks if enough records are appended for the log to be compacted.
+    ///
+    /// # Returns
+    /// `true` if the log is to be compacted, `false` otherwise.
+    pub(crate) fn needs_compaction(&self) -> bool {
+        self.stale || (self.compact_every > 0 && self.appended >= self.compact_every)
+    }
+
+    /// Replaces the codec of the jobs recorded, the records already appended being rewritten
+    /// with it on the next change.
+    ///
+    /// # Arguments
+    /// * `codec` - Codec of the jobs.
+    pub(crate) fn set_codec(&mut self, codec: SharedCodec) {
+        self.codec = codec;
+        self.stale = true;
+    }
+
+    /// Replaces the compression of the jobs recorded, the records already appended being
+    /// rewritten with it on the next change.
+    ///
+    /// # Arguments
+    /// * `compression` - Compression policy, with the readers of the records already appended.
+    pub(crate) fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+        self.stale = true;
+    }
+
+    /// Replaces the encryption of the jobs recorded, the records already appended being
+    /// rewritten with it on the next change.
+    ///
+    /// # Arguments
+    /// * `encryption` - Encryption policy, with the keys of the records already appended.
+    pub(crate) fn set_encryption(&mut self, encryption: Encryption) {
+        self.encryption = encryption;
+        self.stale = true;
+    }
+
+    /// Rewrites the log with one record per job stored and namespace paused.
+    ///
+    /// # Arguments
+    /// * `jobs` - Jobs stored.
+    /// * `paused` - Namespaces paused.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub(crate) fn compact<'a>(
+        &mut self,
+        jobs: impl Iterator<Item = &'a Job>,
+        paused: &BTreeSet<String>,
+    ) -> Result<(), ApiError> {
+        let mut bytes = Vec::new();
+
+        for job in jobs.filter(|job| !job.is_local()) {
+            Self::frame(&mut bytes, PUT, &self.encode(job)?);
+        }
+
+        for namespace in paused {
+            let mut payload = vec![1];
+
+            payload.extend_from_slice(namespace.as_bytes());
+            Self::frame(&mut bytes, PAUSED, &payload);
+        }
+
+        // Swapped in once fully written, the previous log kept intact on failure
+        let compacted = self.path.with_extension("compacting");
+        let mut file = File::create(&compacted).map_err(|e| api_err!(e.into()))?;
+
+        file.write_all(&bytes).map_err(|e| api_err!(e.into()))?;
+        file.sync_all().map_err(|e| api_err!(e.into()))?;
+        std::fs::rename(&compacted, &self.path).map_err(|e| api_err!(e.into()))?;
+
+        self.file = Self::append_to(&self.path)?;
+        self.appended = 0;
+        self.stale = false;
+
+        Ok(())
+    }
+
+    /// Encodes a job recorded.
+    ///
+    /// # Arguments
+    /// * `job` - Job recorded.
+    ///
+    /// # Returns
+    /// The record of the job.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn encode(&self, job: &Job) -> Result<Vec<u8>, ApiError> {
+        job.encode_with(self.codec.as_ref(), &self.compression, &self.encryption)
+    }
+
+    /// Appends a record to the log.
+    ///
+    /// # Arguments
+    /// * `tag` - Tag of the record.
+    /// * `payload` - Payload of the record.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn append(&mut self, tag: u8, payload: &[u8]) -> Result<(), ApiError> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+
+        Self::frame(&mut bytes, tag, payload);
+
+        self.file
+            .write_all(&bytes)
+            .map_err(|e| api_err!(e.into()))?;
+        self.appended += 1;
+
+        Ok(())
+    }
+
+    /// Writes a record, its payload prefixed by its tag and length.
+    ///
+    /// # Arguments
+    /// * `bytes` - Buffer written.
+    /// * `tag` - Tag of the record.
+    /// * `payload` - Payload of the record.
+    fn frame(bytes: &mut Vec<u8>, tag: u8, payload: &[u8]) {
+        bytes.push(tag);
+        bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(payload);
+    }
+
+    /// Opens a log for appending, created if missing.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the log.
+    ///
+    /// # Returns
+    /// The log opened.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    fn append_to(path: &Path) -> Result<File, ApiError> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| api_err!(e.into()))
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::prelude::*;
+
+/// Policy of the watchdog flagging the jobs running longer than expected (see
+/// `Notification::JobStalled`), so that silent hangs are noticed.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct WatchdogPolicy {
+    /// Expected durations, by kind of job.
+    pub expected: HashMap<String, Duration>,
+
+    /// Multiple of the average duration of its kind (see `DurationStats`) after which a job
+    /// without expected duration is stalled, if learned from the previous runs.
+    pub learned: Option<f64>,
+
+    /// Multiple of the expected duration after which a stalled job is cancelled (finished as
+    /// `ResultStatus::TimedOut`), if any.
+    pub cancel_after: Option<f64>,
+}
+
+impl WatchdogPolicy {
+    /// Creates a policy watching no job.
+    ///
+    /// # Returns
+    /// An instance of `WatchdogPolicy`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the expected duration of the jobs of a kind.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the jobs.
+    /// * `duration` - Expected duration.
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn expect(mut self, kind: impl Into<String>, duration: Duration) -> Self {
+        self.expected.insert(kind.into(), duration);
+
+        self
+    }
+
+    /// Learns the expected duration of the other kinds from their previous runs.
+    ///
+    /// # Arguments
+    /// * `factor` - Multiple of the average duration after which a job is stalled.
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn learn(self, factor: f64) -> Self {
+        Self {
+            learned: Some(factor),
+            ..self
+        }
+    }
+
+    /// Cancels the stalled jobs past a threshold.
+    ///
+    /// # Arguments
+    /// * `factor` - Multiple of the expected duration after which a job is cancelled.
+    ///
+    /// # Returns
+    /// The updated policy.
+    pub fn cancel_after(self, factor: f64) -> Self {
+        Self {
+            cancel_after: Some(factor),
+            ..self
+        }
+    }
+
+    /// Gets the expected duration of a job.
+    ///
+    /// # Arguments
+    /// * `kind` - Kind of the job.
+    /// * `stats` - Statistics of the durations of the kind, if any.
+    ///
+    /// # Returns
+    /// The expected duration, `None` if the job isn't watched.
+    pub fn expected(&self, kind: &str, stats: Option<&DurationStats>) -> Option<Duration> {
+        match self.expected.get(kind) {
+            Some(expected) => Some(*expected),
+            None => self
+                .learned
+                .zip(stats)
+                .map(|(factor, stats)| stats.average.mul_f64(factor)),
+        }
+    }
+
+    /// Gets the duration after which a job is cancelled.
+    ///
+    /// # Arguments
+    /// * `expected` - Expected duration of the job.
+    ///
+    /// # Returns
+    /// The duration, `None` if the stalled jobs aren't cancelled.
+    pub fn cancel_threshold(&self, expected: Duration) -> Option<Duration> {
+        self.cancel_after.map(|factor| expected.mul_f64(factor))
+    }
+}
@@ -0,0 +1,344 @@
+//! Notifier POSTing the notifications of a job queue to webhooks, so that external systems
+//! are informed of the completion of the jobs without polling.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Handle;
+
+use crate::prelude::*;
+
+/// Header holding the signature of the body.
+pub const SIGNATURE_HEADER: &str = "X-Cs-Jobs-Signature";
+
+/// Header holding the timestamp (in milliseconds) at which the body has been signed.
+pub const TIMESTAMP_HEADER: &str = "X-Cs-Jobs-Timestamp";
+
+/// Events posted to the webhooks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A job has finished successfully.
+    JobFinished,
+
+    /// A job has finished in error.
+    JobFailed,
+
+    /// A job has been reaped because it has expired.
+    JobExpired,
+}
+
+/// Body POSTed to the webhooks, JSON encoded.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct WebhookPayload {
+    /// Event notified.
+    pub event: WebhookEvent,
+
+    /// Unique identifier of the job.
+    pub job_id: Uuid,
+
+    /// Status of the job (`None` for an expired job).
+    pub status: Option<Status>,
+
+    /// Timestamp (in milliseconds since the Unix epoch) at which the event occurred.
+    pub timestamp: u64,
+}
+
+impl WebhookPayload {
+    /// Creates the payload of a notification of the queue.
+    ///
+    /// # Arguments
+    /// * `notification` - Notification of the queue.
+    ///
+    /// # Returns
+    /// The payload, `None` if the notification isn't posted to the webhooks.
+    pub fn from_notification(notification: &Notification) -> Option<Self> {
+        let (event, job_id, status) = match notification {
+            Notification::Status(id, status @ Status::Finished(ResultStatus::Success)) => {
+                (WebhookEvent::JobFinished, id, Some(*status))
+            }
+            Notification::Status(id, status @ Status::Finished(_)) => {
+                (WebhookEvent::JobFailed, id, Some(*status))
+            }
+            Notification::JobExpired(id) => (WebhookEvent::JobExpired, id, None),
+            _ => return None,
+        };
+
+        Some(Self {
+            event,
+            job_id: job_id.to_owned(),
+            status,
+            timestamp: timestamp_millis(SystemTime::now()),
+        })
+    }
+}
+
+/// Notifier POSTing the completions, failures and expirations of the jobs to webhooks, with
+/// retries and an optional HMAC-SHA256 signature. Registered on a queue next to (or instead
+/// of) its notification handler, see `JobQueue::set_webhook_notifier`.
+#[derive(Clone, Debug)]
+pub struct WebhookNotifier {
+    /// URLs the notifications are posted to.
+    urls: Vec<String>,
+
+    /// Secret used to sign the bodies (unsigned if `None`).
+    secret: Option<Vec<u8>>,
+
+    /// Events posted (all of them if empty).
+    events: HashSet<WebhookEvent>,
+
+    /// Number of retries after a failed delivery.
+    max_retries: u32,
+
+    /// Delay before the first retry, doubled after every attempt.
+    backoff: Duration,
+
+    /// Maximum delay between two attempts.
+    max_backoff: Duration,
+
+    /// Timeout of every attempt.
+    timeout: Duration,
+
+    /// HTTP client posting the notifications.
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    /// Creates a notifier posting every event to a list of URLs, retrying 3 times.
+    ///
+    /// # Arguments
+    /// * `urls` - URLs the notifications are posted to.
+    ///
+    /// # Returns
+    /// An instance of `WebhookNotifier`.
+    pub fn new<T: Into<String>>(urls: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            urls: urls.into_iter().map(Into::into).collect(),
+            secret: None,
+            events: HashSet::new(),
+            max_retries: 3,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            timeout: Duration::from_secs(10),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sets the secret used to sign the bodies posted: the signature is the hexadecimal
+    /// HMAC-SHA256 of the timestamp header, a dot and the body, sent as `sha256=<signature>`.
+    ///
+    /// # Arguments
+    /// * `secret` - Secret shared with the receivers.
+    ///
+    /// # Returns
+    /// The updated notifier.
+    pub fn secret(self, secret: impl Into<Vec<u8>>) -> Self {
+        Self {
+            secret: Some(secret.into()),
+            ..self
+        }
+    }
+
+    /// Restricts the events posted.
+    ///
+    /// # Arguments
+    /// * `events` - Events to be posted.
+    ///
+    /// # Returns
+    /// The updated notifier.
+    pub fn events(self, events: impl IntoIterator<Item = WebhookEvent>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Sets the retries of a failed delivery (network error, `429` or `5xx` response).
+    ///
+    /// # Arguments
+    /// * `max_retries` - Number of retries after the first attempt.
+    /// * `backoff` - Delay before the first retry, doubled after every attempt.
+    /// * `max_backoff` - Maximum delay between two attempts.
+    ///
+    /// # Returns
+    /// The updated notifier.
+    pub fn retries(self, max_retries: u32, backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            backoff,
+            max_backoff,
+            ..self
+        }
+    }
+
+    /// Sets the timeout of every attempt.
+    ///
+    /// # Arguments
+    /// * `timeout` - Timeout to set.
+    ///
+    /// # Returns
+    /// The updated notifier.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Signs a body.
+    ///
+    /// # Arguments
+    /// * `secret` - Secret shared with the receivers.
+    /// * `timestamp` - Timestamp sent in the timestamp header.
+    /// * `body` - Body to be signed.
+    ///
+    /// # Returns
+    /// The value of the signature header.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    pub fn sign(secret: &[u8], timestamp: u64, body: &[u8]) -> Result<String, ApiError> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+            .map_err(|e| api_err!(Error::Custom(e.to_string())))?;
+
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(body);
+
+        let signature = mac.finalize().into_bytes().iter().fold(
+            String::from("sha256="),
+            |mut signature, byte| {
+                signature.push_str(&format!("{byte:02x}"));
+                signature
+            },
+        );
+
+        Ok(signature)
+    }
+
+    /// Posts a notification of the queue to every URL, in the background on a runtime.
+    /// Deliveries still failing after the last retry are reported to the error handler.
+    ///
+    /// # Arguments
+    /// * `runtime` - Runtime running the deliveries.
+    /// * `notification` - Notification of the queue.
+    /// * `error_handler` - Handler notified of the failed deliveries.
+    pub fn notify(
+        &self,
+        runtime: &Handle,
+        notification: &Notification,
+        error_handler: &SharedNotificationHandler,
+    ) {
+        let Some(payload) = WebhookPayload::from_notification(notification) else {
+            return;
+        };
+
+        if !self.events.is_empty() && !self.events.contains(&payload.event) {
+            return;
+        }
+
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => return error_handler(Notification::Error(e.into())),
+        };
+
+        for url in &self.urls {
+            let notifier = self.clone();
+            let url = url.to_owned();
+            let body = body.clone();
+            let error_handler = error_handler.clone();
+
+            runtime.spawn(async move {
+                if let Err(e) = notifier.deliver(&url, body).await {
+                    error_handler(Notification::Error(*e));
+                }
+            });
+        }
+    }
+
+    /// Posts a body to a URL, retrying on failure.
+    ///
+    /// # Arguments
+    /// * `url` - URL the body is posted to.
+    /// * `body` - Body to be posted.
+    ///
+    /// # Errors
+    /// `Error::WebhookDelivery` if the last attempt failed.
+    async fn deliver(&self, url: &str, body: Vec<u8>) -> Result<(), ApiError> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+
+        loop {
+            let error = match self.post(url, body.clone()).await {
+                Ok(status) if status.is_success() => return Ok(()),
+                Ok(status) => {
+                    let error =
+                        api_err!(Error::WebhookDelivery(url.to_owned(), status.to_string()));
+
+                    // Rejected by the receiver, not worth retrying
+                    if status.as_u16() != 429 && !status.is_server_error() {
+                        return Err(error);
+                    }
+
+                    error
+                }
+                Err(e) => e,
+            };
+
+            if attempt >= self.max_retries {
+                return Err(error);
+            }
+
+            tokio::time::sleep(backoff).await;
+
+            attempt += 1;
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+
+    /// Posts a body to a URL once.
+    ///
+    /// # Arguments
+    /// * `url` - URL the body is posted to.
+    /// * `body` - Body to be posted.
+    ///
+    /// # Returns
+    /// The HTTP status of the response.
+    ///
+    /// # Errors
+    /// One of `Error` enum.
+    async fn post(&self, url: &str, body: Vec<u8>) -> Result<reqwest::StatusCode, ApiError> {
+        let timestamp = timestamp_millis(SystemTime::now());
+
+        let mut request = self
+            .client
+            .post(url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp);
+
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, Self::sign(secret, timestamp, &body)?);
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| api_err!(Error::WebhookDelivery(url.to_owned(), e.to_string())))?;
+
+        Ok(response.status())
+    }
+}
+
+/// Converts a timestamp to milliseconds since the Unix epoch.
+///
+/// # Arguments
+/// * `time` - Timestamp to convert.
+///
+/// # Returns
+/// The number of milliseconds.
+fn timestamp_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
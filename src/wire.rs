@@ -0,0 +1,98 @@
+//! Stable wire format used to exchange jobs with producers written in other languages.
+//!
+//! A job is encoded as a JSON object:
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "id": "0b6d5b4e-5a0e-11ef-8000-0123456789ab",
+//!     "kind": "MyRoutine",
+//!     "args": { "first_arg": "Hello World" },
+//!     "metadata": { "source": "billing-api" },
+//!     "idempotency_key": "invoice-42",
+//!     "deadline": 1723456789000
+//! }
+//! ```
+//!
+//! * `version` - Version of the format (mandatory).
+//! * `id` - Identifier of the job (optional, generated if missing).
+//! * `kind` - Name of the variant of the routine enum (mandatory).
+//! * `args` - Arguments of the variant, `null` or missing for a variant without arguments.
+//! * `metadata` - Free key/value pairs attached to the job (optional).
+//! * `idempotency_key` - Key identifying duplicated submissions (optional).
+//! * `deadline` - Instant the job should be finished by, in milliseconds since the Unix epoch
+//!   (optional).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::prelude::*;
+
+/// Current version of the wire format.
+pub const WIRE_VERSION: u32 = 1;
+
+/// Job as exchanged on the wire.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct WireJob {
+    /// Version of the format.
+    pub version: u32,
+
+    /// Identifier of the job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+
+    /// Kind of the routine.
+    pub kind: String,
+
+    /// Arguments of the routine.
+    #[serde(default)]
+    pub args: Value,
+
+    /// Free key/value pairs attached to the job.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, Value>,
+
+    /// Key identifying duplicated submissions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+
+    /// Instant the job should be finished by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<Timestamp>,
+}
+
+impl WireJob {
+    /// Builds the serialized routine (externally tagged enum) described by the wire job.
+    ///
+    /// # Returns
+    /// The routine as JSON value.
+    pub fn routine(&self) -> Value {
+        match &self.args {
+            Value::Null => Value::String(self.kind.to_owned()),
+            args => Value::Object(
+                [(self.kind.to_owned(), args.to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Splits a serialized routine (externally tagged enum) into its kind and arguments.
+    ///
+    /// # Arguments
+    /// * `routine` - Routine as JSON value.
+    ///
+    /// # Returns
+    /// The kind and the arguments (`null` for a variant without arguments).
+    pub fn split_routine(routine: Value) -> (String, Value) {
+        match routine {
+            Value::String(kind) => (kind, Value::Null),
+            Value::Object(map) if map.len() == 1 => map
+                .into_iter()
+                .next()
+                .unwrap_or((String::new(), Value::Null)),
+            args => (String::new(), args),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Cooperative yield points for compute-heavy async routines: the task yields to the runtime
+/// every given number of ticks, so that the other jobs sharing the runtime keep progressing.
+/// A job cancelled meanwhile is aborted at its next yield point.
+#[derive(Clone, Debug)]
+pub struct YieldPoints {
+    /// Number of ticks between two yields.
+    every: u64,
+
+    /// Number of ticks so far.
+    ticks: u64,
+}
+
+impl YieldPoints {
+    /// Creates yield points.
+    ///
+    /// # Arguments
+    /// * `every` - Number of ticks between two yields (at least 1).
+    ///
+    /// # Returns
+    /// An instance of `YieldPoints`.
+    pub fn new(every: u64) -> Self {
+        Self {
+            every: every.max(1),
+            ticks: 0,
+        }
+    }
+
+    /// Counts a unit of work (e.g. an iteration), yielding to the runtime every `every` ticks.
+    pub async fn tick(&mut self) {
+        self.ticks += 1;
+
+        if self.ticks % self.every == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Gets the number of ticks so far.
+    ///
+    /// # Returns
+    /// The number of ticks.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+}
+
+/// Suspension state of a running job, shared between the queue and its routine: a suspended
+/// routine is parked at its next suspension point (see `Job::yield_if_suspended`) until
+/// resumed.
+#[derive(Clone, Debug, Default)]
+pub struct Suspension {
+    /// State shared by the clones.
+    state: Arc<SuspensionState>,
+}
+
+/// State of a suspension.
+#[derive(Debug, Default)]
+struct SuspensionState {
+    /// Whether the job is suspended.
+    suspended: AtomicBool,
+
+    /// Wakes up the routine parked when resumed.
+    resumed: Notify,
+}
+
+impl Suspension {
+    /// Suspends the job, parked at its next suspension point.
+    pub(crate) fn suspend(&self) {
+        self.state.suspended.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes the job, waking up the routine if parked.
+    pub(crate) fn resume(&self) {
+        self.state.suspended.store(false, Ordering::SeqCst);
+        self.state.resumed.notify_waiters();
+    }
+
+    /// Checks if the job is suspended.
+    ///
+    /// # Returns
+    /// `true` if suspended, `false` otherwise.
+    pub fn is_suspended(&self) -> bool {
+        self.state.suspended.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the job to be resumed, returns right away if it's not suspended.
+    pub async fn wait_resumed(&self) {
+        loop {
+            // Registered before checking the flag so that a resume is never missed
+            let resumed = self.state.resumed.notified();
+
+            if !self.is_suspended() {
+                return;
+            }
+
+            resumed.await;
+        }
+    }
+}
+
+impl PartialEq for Suspension {
+    // Runtime state, not part of the description of a job
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}